@@ -0,0 +1,2 @@
+ALTER TABLE offers
+DROP COLUMN updated_at;
@@ -0,0 +1,4 @@
+ALTER TABLE offers
+ADD COLUMN updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP AFTER created_at;
+
+UPDATE offers SET updated_at = created_at;
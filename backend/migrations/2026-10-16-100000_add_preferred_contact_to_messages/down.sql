@@ -0,0 +1,5 @@
+ALTER TABLE messages_archive
+DROP COLUMN preferred_contact;
+
+ALTER TABLE messages
+DROP COLUMN preferred_contact;
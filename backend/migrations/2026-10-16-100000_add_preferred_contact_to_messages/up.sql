@@ -0,0 +1,5 @@
+ALTER TABLE messages
+ADD COLUMN preferred_contact VARCHAR(16) NULL;
+
+ALTER TABLE messages_archive
+ADD COLUMN preferred_contact VARCHAR(16) NULL;
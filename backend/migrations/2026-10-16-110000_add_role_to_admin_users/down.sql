@@ -0,0 +1,5 @@
+ALTER TABLE admin_user_invites
+DROP COLUMN role;
+
+ALTER TABLE admin_users
+DROP COLUMN role;
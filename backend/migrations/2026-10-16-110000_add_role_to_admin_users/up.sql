@@ -0,0 +1,5 @@
+ALTER TABLE admin_users
+ADD COLUMN role VARCHAR(32) NOT NULL DEFAULT 'superadmin';
+
+ALTER TABLE admin_user_invites
+ADD COLUMN role VARCHAR(32) NOT NULL DEFAULT 'superadmin';
@@ -0,0 +1,4 @@
+DROP TABLE admin_password_resets;
+
+ALTER TABLE admin_users
+DROP COLUMN email;
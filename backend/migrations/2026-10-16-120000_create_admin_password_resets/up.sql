@@ -0,0 +1,14 @@
+ALTER TABLE admin_users
+ADD COLUMN email VARCHAR(255) NULL;
+
+CREATE TABLE admin_password_resets (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    token_hash VARCHAR(64) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY idx_admin_password_resets_token_hash (token_hash),
+    CONSTRAINT fk_admin_password_resets_user_id
+        FOREIGN KEY (user_id) REFERENCES admin_users(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
@@ -0,0 +1,2 @@
+ALTER TABLE admin_user_invites
+DROP COLUMN email;
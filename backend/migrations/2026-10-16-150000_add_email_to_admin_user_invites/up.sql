@@ -0,0 +1,2 @@
+ALTER TABLE admin_user_invites
+ADD COLUMN email VARCHAR(255) NULL;
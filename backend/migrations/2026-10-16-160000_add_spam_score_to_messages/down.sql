@@ -0,0 +1,5 @@
+ALTER TABLE messages_archive
+DROP COLUMN spam_score;
+
+ALTER TABLE messages
+DROP COLUMN spam_score;
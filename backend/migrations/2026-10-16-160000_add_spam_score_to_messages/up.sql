@@ -0,0 +1,5 @@
+ALTER TABLE messages
+ADD COLUMN spam_score INT NOT NULL DEFAULT 0;
+
+ALTER TABLE messages_archive
+ADD COLUMN spam_score INT NOT NULL DEFAULT 0;
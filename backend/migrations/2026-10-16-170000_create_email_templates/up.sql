@@ -0,0 +1,17 @@
+CREATE TABLE email_templates (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    subject VARCHAR(191) NOT NULL,
+    body TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY idx_email_templates_name (name)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO email_templates (name, subject, body, is_active) VALUES (
+    'contact_auto_reply',
+    'Prijali sme Vašu správu',
+    'Dobrý deň {name},\n\nďakujeme za Vašu správu. Prijali sme ju a čoskoro Vás budeme kontaktovať.\n\nVaša správa:\n{message}\n',
+    FALSE
+);
@@ -0,0 +1,5 @@
+ALTER TABLE messages_archive
+DROP COLUMN read_at;
+
+ALTER TABLE messages
+DROP COLUMN read_at;
@@ -0,0 +1,9 @@
+ALTER TABLE messages
+ADD COLUMN read_at TIMESTAMP NULL DEFAULT NULL;
+
+ALTER TABLE messages_archive
+ADD COLUMN read_at TIMESTAMP NULL DEFAULT NULL;
+
+-- Messages received before read tracking existed have been seen already
+UPDATE messages SET read_at = created_at;
+UPDATE messages_archive SET read_at = created_at;
@@ -0,0 +1,7 @@
+ALTER TABLE messages_archive
+DROP COLUMN answered_at;
+
+ALTER TABLE messages
+DROP COLUMN answered_at;
+
+DROP TABLE message_replies;
@@ -0,0 +1,21 @@
+-- Keyed by message id without a foreign key, like attachments, so replies
+-- follow their message into the archive
+CREATE TABLE message_replies (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    message_id BIGINT NOT NULL,
+    admin_user_id BIGINT NULL,
+    admin_username VARCHAR(255) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_message_replies_message_id (message_id),
+    CONSTRAINT fk_message_replies_admin_user_id
+        FOREIGN KEY (admin_user_id) REFERENCES admin_users(id)
+        ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+ALTER TABLE messages
+ADD COLUMN answered_at TIMESTAMP NULL DEFAULT NULL;
+
+ALTER TABLE messages_archive
+ADD COLUMN answered_at TIMESTAMP NULL DEFAULT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE messages
+DROP INDEX ft_messages_search;
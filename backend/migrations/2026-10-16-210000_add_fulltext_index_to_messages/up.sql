@@ -0,0 +1,2 @@
+ALTER TABLE messages
+ADD FULLTEXT INDEX ft_messages_search (name, email, subject, message);
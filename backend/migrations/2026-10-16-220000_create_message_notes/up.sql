@@ -0,0 +1,14 @@
+-- Internal notes on contact messages; keyed by message id without a foreign
+-- key, like replies, so notes follow their message into the archive
+CREATE TABLE message_notes (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    message_id BIGINT NOT NULL,
+    admin_user_id BIGINT NULL,
+    admin_username VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_message_notes_message_id (message_id),
+    CONSTRAINT fk_message_notes_admin_user_id
+        FOREIGN KEY (admin_user_id) REFERENCES admin_users(id)
+        ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
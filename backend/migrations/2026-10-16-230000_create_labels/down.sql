@@ -0,0 +1,3 @@
+DROP TABLE message_labels;
+
+DROP TABLE labels;
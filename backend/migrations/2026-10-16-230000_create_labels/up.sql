@@ -0,0 +1,24 @@
+CREATE TABLE labels (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    color VARCHAR(7) NOT NULL DEFAULT '#6b7280',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_labels_name (name)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Keyed by message id without a foreign key, like replies and notes, so
+-- labels follow their message into the archive
+CREATE TABLE message_labels (
+    message_id BIGINT NOT NULL,
+    label_id BIGINT NOT NULL,
+    PRIMARY KEY (message_id, label_id),
+    KEY idx_message_labels_label_id (label_id),
+    CONSTRAINT fk_message_labels_label_id
+        FOREIGN KEY (label_id) REFERENCES labels(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO labels (name, color) VALUES
+    ('sales', '#16a34a'),
+    ('support', '#2563eb'),
+    ('spam', '#dc2626');
@@ -0,0 +1,2 @@
+ALTER TABLE messages_archive
+DROP INDEX ft_messages_archive_search;
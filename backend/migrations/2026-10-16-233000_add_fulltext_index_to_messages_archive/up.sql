@@ -0,0 +1,2 @@
+ALTER TABLE messages_archive
+ADD FULLTEXT INDEX ft_messages_archive_search (name, email, subject, message);
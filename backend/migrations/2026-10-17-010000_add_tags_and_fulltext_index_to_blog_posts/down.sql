@@ -0,0 +1,5 @@
+ALTER TABLE blog_posts
+DROP INDEX ft_blog_posts_search;
+
+ALTER TABLE blog_posts
+DROP COLUMN tags;
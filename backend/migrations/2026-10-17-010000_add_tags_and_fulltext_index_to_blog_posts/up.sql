@@ -0,0 +1,6 @@
+-- Comma-separated, lowercase tags
+ALTER TABLE blog_posts
+ADD COLUMN tags VARCHAR(512) NOT NULL DEFAULT '' AFTER content;
+
+ALTER TABLE blog_posts
+ADD FULLTEXT INDEX ft_blog_posts_search (title, excerpt, content);
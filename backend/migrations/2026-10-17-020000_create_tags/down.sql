@@ -0,0 +1,13 @@
+ALTER TABLE blog_posts
+ADD COLUMN tags VARCHAR(512) NOT NULL DEFAULT '' AFTER content;
+
+UPDATE blog_posts
+SET tags = COALESCE((
+    SELECT GROUP_CONCAT(LOWER(tags.name) ORDER BY tags.name SEPARATOR ',')
+    FROM blog_post_tags
+    JOIN tags ON tags.id = blog_post_tags.tag_id
+    WHERE blog_post_tags.blog_post_id = blog_posts.id
+), '');
+
+DROP TABLE blog_post_tags;
+DROP TABLE tags;
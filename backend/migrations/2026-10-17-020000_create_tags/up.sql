@@ -0,0 +1,52 @@
+CREATE TABLE tags (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    slug VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_tags_name (name),
+    UNIQUE KEY uq_tags_slug (slug)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE blog_post_tags (
+    blog_post_id BIGINT NOT NULL,
+    tag_id BIGINT NOT NULL,
+    PRIMARY KEY (blog_post_id, tag_id),
+    KEY idx_blog_post_tags_tag_id (tag_id),
+    CONSTRAINT fk_blog_post_tags_blog_post_id
+        FOREIGN KEY (blog_post_id) REFERENCES blog_posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_blog_post_tags_tag_id
+        FOREIGN KEY (tag_id) REFERENCES tags(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Move the comma-separated tags of each post into the new tables. Slugs
+-- follow the same rule as the application: runs of anything but letters
+-- and digits become a single dash.
+CREATE TEMPORARY TABLE migrated_blog_post_tags AS
+SELECT
+    blog_posts.id AS blog_post_id,
+    jt.name AS name,
+    TRIM(BOTH '-' FROM REGEXP_REPLACE(LOWER(jt.name), '[^\\p{L}\\p{N}]+', '-')) AS slug
+FROM blog_posts,
+    JSON_TABLE(
+        CONCAT('["', REPLACE(REPLACE(REPLACE(blog_posts.tags, '\\', '\\\\'), '"', '\\"'), ',', '","'), '"]'),
+        '$[*]' COLUMNS (name VARCHAR(64) PATH '$')
+    ) AS jt
+WHERE blog_posts.tags <> '';
+
+INSERT IGNORE INTO tags (name, slug)
+SELECT name, slug
+FROM migrated_blog_post_tags
+WHERE slug <> ''
+GROUP BY name, slug;
+
+INSERT IGNORE INTO blog_post_tags (blog_post_id, tag_id)
+SELECT migrated_blog_post_tags.blog_post_id, tags.id
+FROM migrated_blog_post_tags
+JOIN tags ON tags.slug = migrated_blog_post_tags.slug;
+
+DROP TEMPORARY TABLE migrated_blog_post_tags;
+
+ALTER TABLE blog_posts
+DROP COLUMN tags;
@@ -0,0 +1,23 @@
+-- Snapshot of a blog post taken before every update or restore. Images are
+-- not part of revisions.
+CREATE TABLE blog_post_revisions (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    blog_post_id BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    slug TEXT NOT NULL,
+    excerpt TEXT NULL,
+    content TEXT NOT NULL,
+    -- Comma-separated tag names
+    tags TEXT NOT NULL,
+    published BOOLEAN NOT NULL,
+    admin_user_id BIGINT NULL,
+    admin_username VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_blog_post_revisions_blog_post_id (blog_post_id, id),
+    CONSTRAINT fk_blog_post_revisions_blog_post_id
+        FOREIGN KEY (blog_post_id) REFERENCES blog_posts(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_blog_post_revisions_admin_user_id
+        FOREIGN KEY (admin_user_id) REFERENCES admin_users(id)
+        ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
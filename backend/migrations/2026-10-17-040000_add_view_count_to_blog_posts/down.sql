@@ -0,0 +1,4 @@
+DROP INDEX idx_blog_posts_view_count ON blog_posts;
+
+ALTER TABLE blog_posts
+DROP COLUMN view_count;
@@ -0,0 +1,5 @@
+-- Total reads of each post, flushed from memory in batches
+ALTER TABLE blog_posts
+ADD COLUMN view_count BIGINT NOT NULL DEFAULT 0 AFTER published;
+
+CREATE INDEX idx_blog_posts_view_count ON blog_posts (published, view_count);
@@ -0,0 +1,5 @@
+ALTER TABLE blog_posts
+DROP COLUMN og_image,
+DROP COLUMN canonical_url,
+DROP COLUMN meta_description,
+DROP COLUMN meta_title;
@@ -0,0 +1,7 @@
+-- Optional SEO overrides; the post's own title, excerpt, URL and image are
+-- used when they are empty
+ALTER TABLE blog_posts
+ADD COLUMN meta_title VARCHAR(255) NULL AFTER content,
+ADD COLUMN meta_description VARCHAR(500) NULL AFTER meta_title,
+ADD COLUMN canonical_url VARCHAR(2048) NULL AFTER meta_description,
+ADD COLUMN og_image VARCHAR(2048) NULL AFTER canonical_url;
@@ -0,0 +1,17 @@
+ALTER TABLE blog_posts
+    ADD COLUMN image LONGBLOB NULL AFTER og_image,
+    ADD COLUMN image_mime VARCHAR(255) NULL AFTER image;
+
+-- Only the cover of each post fits back into blog_posts
+UPDATE blog_posts
+JOIN blog_post_images ON blog_post_images.id = (
+    SELECT cover.id
+    FROM blog_post_images AS cover
+    WHERE cover.blog_post_id = blog_posts.id
+    ORDER BY cover.position, cover.id
+    LIMIT 1
+)
+SET blog_posts.image = blog_post_images.image,
+    blog_posts.image_mime = blog_post_images.image_mime;
+
+DROP TABLE blog_post_images;
@@ -0,0 +1,23 @@
+-- Any number of images per blog post. The image with the lowest position is
+-- the cover shown in lists and used as the OpenGraph image.
+CREATE TABLE blog_post_images (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    blog_post_id BIGINT NOT NULL,
+    image LONGBLOB NOT NULL,
+    image_mime VARCHAR(255) NOT NULL,
+    position INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_blog_post_images_blog_post_id (blog_post_id, position, id),
+    CONSTRAINT fk_blog_post_images_blog_post_id
+        FOREIGN KEY (blog_post_id) REFERENCES blog_posts(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO blog_post_images (blog_post_id, image, image_mime, position)
+SELECT id, image, COALESCE(image_mime, 'image/jpeg'), 0
+FROM blog_posts
+WHERE image IS NOT NULL;
+
+ALTER TABLE blog_posts
+    DROP COLUMN image,
+    DROP COLUMN image_mime;
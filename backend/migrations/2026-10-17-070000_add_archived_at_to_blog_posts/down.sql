@@ -0,0 +1,3 @@
+ALTER TABLE blog_posts
+    DROP KEY idx_blog_posts_archived_at,
+    DROP COLUMN archived_at;
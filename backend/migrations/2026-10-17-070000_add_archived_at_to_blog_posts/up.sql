@@ -0,0 +1,5 @@
+-- Archived posts are hidden from the public site and the admin's working
+-- list, whether or not they are published
+ALTER TABLE blog_posts
+    ADD COLUMN archived_at TIMESTAMP NULL DEFAULT NULL AFTER published,
+    ADD KEY idx_blog_posts_archived_at (archived_at);
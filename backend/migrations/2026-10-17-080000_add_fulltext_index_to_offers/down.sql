@@ -0,0 +1,2 @@
+ALTER TABLE offers
+DROP INDEX ft_offers_search;
@@ -0,0 +1,2 @@
+ALTER TABLE offers
+ADD FULLTEXT INDEX ft_offers_search (title, excerpt, content);
@@ -0,0 +1,3 @@
+ALTER TABLE blog_posts
+    DROP COLUMN reading_time_minutes,
+    DROP COLUMN word_count;
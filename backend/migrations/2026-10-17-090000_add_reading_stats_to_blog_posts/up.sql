@@ -0,0 +1,16 @@
+-- Stored when a post is saved, so lists and posts can show a reading time
+-- without rendering the content on every request
+ALTER TABLE blog_posts
+    ADD COLUMN word_count INT UNSIGNED NOT NULL DEFAULT 0 AFTER content,
+    ADD COLUMN reading_time_minutes INT UNSIGNED NOT NULL DEFAULT 0 AFTER word_count;
+
+-- Estimate existing posts by the runs of letters and digits in their
+-- Markdown source; the exact values are computed the next time a post is
+-- saved
+UPDATE blog_posts
+SET word_count =
+    CHAR_LENGTH(REGEXP_REPLACE(content, '[\\p{L}\\p{N}]+', 'x'))
+    - CHAR_LENGTH(REGEXP_REPLACE(content, '[\\p{L}\\p{N}]+', ''));
+
+UPDATE blog_posts
+SET reading_time_minutes = CEIL(word_count / 200);
@@ -0,0 +1,4 @@
+ALTER TABLE blog_posts
+    DROP FOREIGN KEY fk_blog_posts_author_id,
+    DROP KEY idx_blog_posts_author_id,
+    DROP COLUMN author_id;
@@ -0,0 +1,22 @@
+-- The admin user who wrote a post; posts keep their content when the user
+-- is deleted and are then shown without an author
+ALTER TABLE blog_posts
+    ADD COLUMN author_id BIGINT NULL AFTER og_image,
+    ADD KEY idx_blog_posts_author_id (author_id),
+    ADD CONSTRAINT fk_blog_posts_author_id
+        FOREIGN KEY (author_id) REFERENCES admin_users(id)
+        ON DELETE SET NULL;
+
+-- Attribute existing posts to the user who created, duplicated or imported
+-- them, as far as the audit log still records it
+UPDATE blog_posts
+JOIN (
+    SELECT entity_id, MIN(id) AS first_entry_id
+    FROM audit_log
+    WHERE entity_type = 'blog_post'
+        AND action IN ('blog_post.create', 'blog_post.duplicate', 'blog_post.import')
+    GROUP BY entity_id
+) AS created ON created.entity_id = CAST(blog_posts.id AS CHAR)
+JOIN audit_log ON audit_log.id = created.first_entry_id
+JOIN admin_users ON admin_users.id = audit_log.admin_user_id
+SET blog_posts.author_id = admin_users.id;
@@ -0,0 +1,4 @@
+ALTER TABLE blog_posts
+    DROP KEY idx_blog_posts_featured,
+    DROP COLUMN featured_order,
+    DROP COLUMN featured;
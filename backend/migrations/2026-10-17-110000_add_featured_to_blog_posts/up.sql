@@ -0,0 +1,6 @@
+-- Posts picked for the homepage, shown by `featured_order` independent of
+-- their publication date
+ALTER TABLE blog_posts
+    ADD COLUMN featured BOOLEAN NOT NULL DEFAULT FALSE AFTER published,
+    ADD COLUMN featured_order INT NOT NULL DEFAULT 0 AFTER featured,
+    ADD KEY idx_blog_posts_featured (featured, featured_order);
@@ -0,0 +1,3 @@
+ALTER TABLE offers
+    DROP KEY idx_offers_sort_order,
+    DROP COLUMN sort_order;
@@ -0,0 +1,10 @@
+-- Display order of offers chosen by admins, lowest first. Existing offers
+-- keep their newest-first order.
+ALTER TABLE offers
+    ADD COLUMN sort_order INT NOT NULL DEFAULT 0 AFTER longitude,
+    ADD KEY idx_offers_sort_order (sort_order);
+
+SET @position = -1;
+UPDATE offers
+SET sort_order = (@position := @position + 1)
+ORDER BY created_at DESC, id DESC;
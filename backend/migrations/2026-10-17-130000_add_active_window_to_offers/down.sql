@@ -0,0 +1,5 @@
+ALTER TABLE offers
+    DROP KEY idx_offers_active_window,
+    DROP COLUMN ends_at,
+    DROP COLUMN starts_at,
+    DROP COLUMN active;
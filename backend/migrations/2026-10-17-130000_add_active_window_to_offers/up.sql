@@ -0,0 +1,7 @@
+-- Offers can be switched off or limited to a time window, for promotions.
+-- Offers without a window are shown while they are active.
+ALTER TABLE offers
+    ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE AFTER sort_order,
+    ADD COLUMN starts_at TIMESTAMP NULL DEFAULT NULL AFTER active,
+    ADD COLUMN ends_at TIMESTAMP NULL DEFAULT NULL AFTER starts_at,
+    ADD KEY idx_offers_active_window (active, starts_at, ends_at);
@@ -0,0 +1,14 @@
+-- One row per click on an offer's link. IP addresses are stored with the
+-- host part zeroed, so clicks cannot be traced back to a visitor.
+CREATE TABLE offer_clicks (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    offer_id BIGINT NOT NULL,
+    clicked_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    referrer VARCHAR(512) NULL,
+    ip_address VARCHAR(45) NULL,
+    KEY idx_offer_clicks_offer_id (offer_id, clicked_at),
+    KEY idx_offer_clicks_clicked_at (clicked_at),
+    CONSTRAINT fk_offer_clicks_offer_id
+        FOREIGN KEY (offer_id) REFERENCES offers(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
@@ -0,0 +1,10 @@
+DELETE FROM offers WHERE deleted_at IS NOT NULL;
+DELETE FROM blog_posts WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE blog_posts
+    DROP KEY idx_blog_posts_deleted_at,
+    DROP COLUMN deleted_at;
+
+ALTER TABLE offers
+    DROP KEY idx_offers_deleted_at,
+    DROP COLUMN deleted_at;
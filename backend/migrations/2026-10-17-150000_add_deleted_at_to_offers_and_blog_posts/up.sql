@@ -0,0 +1,10 @@
+-- Deleted offers and posts are kept in the trash until they are deleted
+-- permanently, so they can be restored with their images, tags, revisions
+-- and clicks
+ALTER TABLE offers
+    ADD COLUMN deleted_at TIMESTAMP NULL DEFAULT NULL AFTER ends_at,
+    ADD KEY idx_offers_deleted_at (deleted_at);
+
+ALTER TABLE blog_posts
+    ADD COLUMN deleted_at TIMESTAMP NULL DEFAULT NULL AFTER archived_at,
+    ADD KEY idx_blog_posts_deleted_at (deleted_at);
@@ -0,0 +1,17 @@
+ALTER TABLE offers
+    ADD COLUMN image LONGBLOB NULL AFTER link,
+    ADD COLUMN image_mime VARCHAR(255) NULL AFTER image;
+
+-- Only the primary image of each offer fits back into offers
+UPDATE offers
+JOIN offer_images ON offer_images.id = (
+    SELECT primary_image.id
+    FROM offer_images AS primary_image
+    WHERE primary_image.offer_id = offers.id
+    ORDER BY primary_image.position, primary_image.id
+    LIMIT 1
+)
+SET offers.image = offer_images.image,
+    offers.image_mime = offer_images.image_mime;
+
+DROP TABLE offer_images;
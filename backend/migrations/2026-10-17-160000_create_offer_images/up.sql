@@ -0,0 +1,23 @@
+-- Any number of images per offer. The image with the lowest position is the
+-- primary image shown in lists and served at /api/offers/:id/image.
+CREATE TABLE offer_images (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    offer_id BIGINT NOT NULL,
+    image LONGBLOB NOT NULL,
+    image_mime VARCHAR(255) NOT NULL,
+    position INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_offer_images_offer_id (offer_id, position, id),
+    CONSTRAINT fk_offer_images_offer_id
+        FOREIGN KEY (offer_id) REFERENCES offers(id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO offer_images (offer_id, image, image_mime, position)
+SELECT id, image, COALESCE(image_mime, 'image/jpeg'), 0
+FROM offers
+WHERE image IS NOT NULL;
+
+ALTER TABLE offers
+    DROP COLUMN image,
+    DROP COLUMN image_mime;
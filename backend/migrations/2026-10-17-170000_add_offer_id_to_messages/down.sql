@@ -0,0 +1,8 @@
+ALTER TABLE messages_archive
+    DROP FOREIGN KEY fk_messages_archive_offer_id,
+    DROP COLUMN offer_id;
+
+ALTER TABLE messages
+    DROP FOREIGN KEY fk_messages_offer_id,
+    DROP KEY idx_messages_offer_id,
+    DROP COLUMN offer_id;
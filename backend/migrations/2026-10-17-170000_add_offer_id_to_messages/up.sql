@@ -0,0 +1,14 @@
+-- The offer an inquiry was sent from. Inquiries are kept when their offer is
+-- deleted permanently.
+ALTER TABLE messages
+    ADD COLUMN offer_id BIGINT NULL DEFAULT NULL,
+    ADD KEY idx_messages_offer_id (offer_id),
+    ADD CONSTRAINT fk_messages_offer_id
+        FOREIGN KEY (offer_id) REFERENCES offers(id)
+        ON DELETE SET NULL;
+
+ALTER TABLE messages_archive
+    ADD COLUMN offer_id BIGINT NULL DEFAULT NULL,
+    ADD CONSTRAINT fk_messages_archive_offer_id
+        FOREIGN KEY (offer_id) REFERENCES offers(id)
+        ON DELETE SET NULL;
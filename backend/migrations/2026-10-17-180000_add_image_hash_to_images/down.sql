@@ -0,0 +1,2 @@
+ALTER TABLE offer_images DROP COLUMN image_hash;
+ALTER TABLE blog_post_images DROP COLUMN image_hash;
@@ -0,0 +1,8 @@
+-- SHA-256 of the stored image data, served as its ETag
+ALTER TABLE blog_post_images ADD COLUMN image_hash VARCHAR(64) NOT NULL DEFAULT '';
+UPDATE blog_post_images SET image_hash = SHA2(image, 256);
+ALTER TABLE blog_post_images ALTER COLUMN image_hash DROP DEFAULT;
+
+ALTER TABLE offer_images ADD COLUMN image_hash VARCHAR(64) NOT NULL DEFAULT '';
+UPDATE offer_images SET image_hash = SHA2(image, 256);
+ALTER TABLE offer_images ALTER COLUMN image_hash DROP DEFAULT;
@@ -0,0 +1,7 @@
+ALTER TABLE offer_images
+    DROP COLUMN medium_image,
+    DROP COLUMN thumb_image;
+
+ALTER TABLE blog_post_images
+    DROP COLUMN medium_image,
+    DROP COLUMN thumb_image;
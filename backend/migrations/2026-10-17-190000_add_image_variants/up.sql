@@ -0,0 +1,9 @@
+-- Smaller variants of each image for list pages. NULL for images stored
+-- before they were introduced; those are generated on first request.
+ALTER TABLE blog_post_images
+    ADD COLUMN thumb_image LONGBLOB NULL DEFAULT NULL,
+    ADD COLUMN medium_image LONGBLOB NULL DEFAULT NULL;
+
+ALTER TABLE offer_images
+    ADD COLUMN thumb_image LONGBLOB NULL DEFAULT NULL,
+    ADD COLUMN medium_image LONGBLOB NULL DEFAULT NULL;
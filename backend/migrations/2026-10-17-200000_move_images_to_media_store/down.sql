@@ -0,0 +1,31 @@
+-- Only images kept in the database store can be restored; rows whose data
+-- lives on disk or in a bucket are dropped.
+ALTER TABLE offer_images
+    ADD COLUMN image LONGBLOB NULL,
+    ADD COLUMN thumb_image LONGBLOB NULL DEFAULT NULL,
+    ADD COLUMN medium_image LONGBLOB NULL DEFAULT NULL;
+UPDATE offer_images
+SET image = (SELECT data FROM media_blobs WHERE storage_key = offer_images.storage_key),
+    thumb_image = (SELECT data FROM media_blobs WHERE storage_key = CONCAT(offer_images.storage_key, '.thumb')),
+    medium_image = (SELECT data FROM media_blobs WHERE storage_key = CONCAT(offer_images.storage_key, '.medium'));
+DELETE FROM offer_images WHERE image IS NULL;
+ALTER TABLE offer_images
+    MODIFY COLUMN image LONGBLOB NOT NULL,
+    DROP KEY idx_offer_images_storage_key,
+    DROP COLUMN storage_key;
+
+ALTER TABLE blog_post_images
+    ADD COLUMN image LONGBLOB NULL,
+    ADD COLUMN thumb_image LONGBLOB NULL DEFAULT NULL,
+    ADD COLUMN medium_image LONGBLOB NULL DEFAULT NULL;
+UPDATE blog_post_images
+SET image = (SELECT data FROM media_blobs WHERE storage_key = blog_post_images.storage_key),
+    thumb_image = (SELECT data FROM media_blobs WHERE storage_key = CONCAT(blog_post_images.storage_key, '.thumb')),
+    medium_image = (SELECT data FROM media_blobs WHERE storage_key = CONCAT(blog_post_images.storage_key, '.medium'));
+DELETE FROM blog_post_images WHERE image IS NULL;
+ALTER TABLE blog_post_images
+    MODIFY COLUMN image LONGBLOB NOT NULL,
+    DROP KEY idx_blog_post_images_storage_key,
+    DROP COLUMN storage_key;
+
+DROP TABLE media_blobs;
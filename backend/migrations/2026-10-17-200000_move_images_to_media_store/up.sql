@@ -0,0 +1,45 @@
+-- Image data moves out of the image tables into the media store; rows keep
+-- only the key it is stored under. `media_blobs` backs the database store
+-- and receives the existing images, which the filesystem and S3 stores move
+-- over on first request.
+CREATE TABLE media_blobs (
+    storage_key VARCHAR(255) NOT NULL PRIMARY KEY,
+    data LONGBLOB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('blog/', id), image FROM blog_post_images;
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('blog/', id, '.thumb'), thumb_image FROM blog_post_images
+WHERE thumb_image IS NOT NULL;
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('blog/', id, '.medium'), medium_image FROM blog_post_images
+WHERE medium_image IS NOT NULL;
+
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('offers/', id), image FROM offer_images;
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('offers/', id, '.thumb'), thumb_image FROM offer_images
+WHERE thumb_image IS NOT NULL;
+INSERT INTO media_blobs (storage_key, data)
+SELECT CONCAT('offers/', id, '.medium'), medium_image FROM offer_images
+WHERE medium_image IS NOT NULL;
+
+ALTER TABLE blog_post_images ADD COLUMN storage_key VARCHAR(255) NOT NULL DEFAULT '';
+UPDATE blog_post_images SET storage_key = CONCAT('blog/', id);
+ALTER TABLE blog_post_images
+    ALTER COLUMN storage_key DROP DEFAULT,
+    ADD KEY idx_blog_post_images_storage_key (storage_key),
+    DROP COLUMN image,
+    DROP COLUMN thumb_image,
+    DROP COLUMN medium_image;
+
+ALTER TABLE offer_images ADD COLUMN storage_key VARCHAR(255) NOT NULL DEFAULT '';
+UPDATE offer_images SET storage_key = CONCAT('offers/', id);
+ALTER TABLE offer_images
+    ALTER COLUMN storage_key DROP DEFAULT,
+    ADD KEY idx_offer_images_storage_key (storage_key),
+    DROP COLUMN image,
+    DROP COLUMN thumb_image,
+    DROP COLUMN medium_image;
@@ -0,0 +1,157 @@
+// Optional CAPTCHA verification for contact submissions
+
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+
+/// Supported CAPTCHA services. All of them share the same siteverify API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    Hcaptcha,
+    Turnstile,
+    Recaptcha,
+}
+
+impl CaptchaProvider {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "off" => None,
+            "hcaptcha" => Some(CaptchaProvider::Hcaptcha),
+            "turnstile" => Some(CaptchaProvider::Turnstile),
+            "recaptcha" => Some(CaptchaProvider::Recaptcha),
+            other => {
+                warn!("Unknown CAPTCHA provider '{}', CAPTCHA disabled", other);
+                None
+            }
+        }
+    }
+
+    fn verify_url(self) -> &'static str {
+        match self {
+            CaptchaProvider::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::Turnstile => {
+                "https://challenges.cloudflare.com/turnstile/v0/siteverify"
+            }
+            CaptchaProvider::Recaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+/// What the frontend needs to render the CAPTCHA widget
+#[derive(Debug, Serialize)]
+pub struct CaptchaSettings {
+    pub provider: Option<CaptchaProvider>,
+    pub site_key: Option<String>,
+}
+
+/// Verifies CAPTCHA tokens with the configured provider. Disabled unless
+/// `CAPTCHA_PROVIDER` and `CAPTCHA_SECRET` are set.
+pub struct CaptchaVerifier {
+    http: reqwest::Client,
+    provider: Option<CaptchaProvider>,
+    secret: String,
+    site_key: String,
+}
+
+impl CaptchaVerifier {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let secret = config.captcha_secret.trim().to_string();
+        CaptchaVerifier {
+            http: reqwest::Client::new(),
+            provider: CaptchaProvider::parse(&config.captcha_provider)
+                .filter(|_| !secret.is_empty()),
+            secret,
+            site_key: config.captcha_site_key.trim().to_string(),
+        }
+    }
+
+    pub fn settings(&self) -> CaptchaSettings {
+        CaptchaSettings {
+            provider: self.provider,
+            site_key: self
+                .provider
+                .map(|_| self.site_key.clone())
+                .filter(|key| !key.is_empty()),
+        }
+    }
+
+    /// Whether the token proves a solved CAPTCHA. Always true while
+    /// disabled; a missing token never passes otherwise.
+    pub async fn verify(&self, token: Option<&str>, client_ip: Option<IpAddr>) -> AppResult<bool> {
+        let Some(provider) = self.provider else {
+            return Ok(true);
+        };
+        let Some(token) = token.map(str::trim).filter(|token| !token.is_empty()) else {
+            return Ok(false);
+        };
+
+        let remote_ip = client_ip.map(|ip| ip.to_string()).unwrap_or_default();
+        let mut params = vec![("secret", self.secret.as_str()), ("response", token)];
+        if !remote_ip.is_empty() {
+            params.push(("remoteip", remote_ip.as_str()));
+        }
+
+        let response: VerifyResponse = self
+            .http
+            .post(provider.verify_url())
+            .form(&params)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Captcha(format!("verification failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| AppError::Captcha(format!("invalid verification response: {e}")))?;
+
+        if !response.success {
+            warn!(
+                "CAPTCHA rejected by {:?}: {:?}",
+                provider, response.error_codes
+            );
+        }
+        Ok(response.success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_provider() {
+        assert_eq!(CaptchaProvider::parse("off"), None);
+        assert_eq!(CaptchaProvider::parse(""), None);
+        assert_eq!(
+            CaptchaProvider::parse(" Turnstile "),
+            Some(CaptchaProvider::Turnstile)
+        );
+        assert_eq!(
+            CaptchaProvider::parse("hcaptcha"),
+            Some(CaptchaProvider::Hcaptcha)
+        );
+        assert_eq!(CaptchaProvider::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_verify_response_parsing() {
+        let response: VerifyResponse =
+            serde_json::from_str(r#"{"success":false,"error-codes":["invalid-input-response"]}"#)
+                .unwrap();
+        assert!(!response.success);
+        assert_eq!(response.error_codes, ["invalid-input-response"]);
+
+        let response: VerifyResponse = serde_json::from_str(r#"{"success":true}"#).unwrap();
+        assert!(response.success);
+    }
+}
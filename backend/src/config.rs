@@ -16,6 +16,330 @@ pub struct AppConfig {
     pub rocket_address: String,
     #[serde(default = "default_static_dir", alias = "STATIC_DIR")]
     pub static_dir: String,
+    #[serde(default, alias = "CORS_ENABLED")]
+    pub cors_enabled: bool,
+    #[serde(default, alias = "CORS_ALLOWED_ORIGINS")]
+    pub cors_allowed_origins: String,
+    #[serde(default = "default_cors_max_age_secs", alias = "CORS_MAX_AGE_SECS")]
+    pub cors_max_age_secs: u64,
+    #[serde(default = "default_normalize_slugs", alias = "NORMALIZE_SLUGS")]
+    pub normalize_slugs: bool,
+    #[serde(default, alias = "ADMIN_BASIC_AUTH_ENABLED")]
+    pub admin_basic_auth_enabled: bool,
+    #[serde(
+        default = "default_admin_basic_auth_trusted_ips",
+        alias = "ADMIN_BASIC_AUTH_TRUSTED_IPS"
+    )]
+    pub admin_basic_auth_trusted_ips: String,
+    #[serde(default, alias = "HTTPS_REDIRECT_ENABLED")]
+    pub https_redirect_enabled: bool,
+    #[serde(
+        default = "default_https_redirect_exempt_paths",
+        alias = "HTTPS_REDIRECT_EXEMPT_PATHS"
+    )]
+    pub https_redirect_exempt_paths: String,
+    #[serde(
+        default = "default_admin_session_idle_secs",
+        alias = "ADMIN_SESSION_IDLE_SECS"
+    )]
+    pub admin_session_idle_secs: u64,
+    #[serde(
+        default = "default_admin_session_max_lifetime_secs",
+        alias = "ADMIN_SESSION_MAX_LIFETIME_SECS"
+    )]
+    pub admin_session_max_lifetime_secs: u64,
+    #[serde(
+        default = "default_admin_session_cleanup_interval_secs",
+        alias = "ADMIN_SESSION_CLEANUP_INTERVAL_SECS"
+    )]
+    pub admin_session_cleanup_interval_secs: u64,
+    #[serde(
+        default = "default_admin_session_cookie_name",
+        alias = "ADMIN_SESSION_COOKIE_NAME"
+    )]
+    pub admin_session_cookie_name: String,
+    #[serde(default, alias = "ADMIN_SESSION_COOKIE_SECURE")]
+    pub admin_session_cookie_secure: bool,
+    #[serde(
+        default = "default_admin_session_cookie_same_site",
+        alias = "ADMIN_SESSION_COOKIE_SAME_SITE"
+    )]
+    pub admin_session_cookie_same_site: String,
+    #[serde(default, alias = "ADMIN_SESSION_COOKIE_MAX_AGE_SECS")]
+    pub admin_session_cookie_max_age_secs: Option<u64>,
+    #[serde(
+        default = "default_admin_remember_me_secs",
+        alias = "ADMIN_REMEMBER_ME_SECS"
+    )]
+    pub admin_remember_me_secs: u64,
+    #[serde(
+        default = "default_admin_remember_me_cookie_name",
+        alias = "ADMIN_REMEMBER_ME_COOKIE_NAME"
+    )]
+    pub admin_remember_me_cookie_name: String,
+    #[serde(
+        default = "default_admin_login_max_failures",
+        alias = "ADMIN_LOGIN_MAX_FAILURES"
+    )]
+    pub admin_login_max_failures: u64,
+    #[serde(
+        default = "default_admin_login_backoff_secs",
+        alias = "ADMIN_LOGIN_BACKOFF_SECS"
+    )]
+    pub admin_login_backoff_secs: u64,
+    #[serde(
+        default = "default_admin_login_lockout_secs",
+        alias = "ADMIN_LOGIN_LOCKOUT_SECS"
+    )]
+    pub admin_login_lockout_secs: u64,
+    /// Password reset requests allowed from one client IP, and for one
+    /// username, within the reset window; 0 disables the limit
+    #[serde(
+        default = "default_admin_password_reset_max_per_ip",
+        alias = "ADMIN_PASSWORD_RESET_MAX_PER_IP"
+    )]
+    pub admin_password_reset_max_per_ip: u64,
+    #[serde(
+        default = "default_admin_password_reset_max_per_user",
+        alias = "ADMIN_PASSWORD_RESET_MAX_PER_USER"
+    )]
+    pub admin_password_reset_max_per_user: u64,
+    #[serde(
+        default = "default_admin_password_reset_window_secs",
+        alias = "ADMIN_PASSWORD_RESET_WINDOW_SECS"
+    )]
+    pub admin_password_reset_window_secs: u64,
+    #[serde(
+        default = "default_admin_session_ip_pinning",
+        alias = "ADMIN_SESSION_IP_PINNING"
+    )]
+    pub admin_session_ip_pinning: String,
+    #[serde(
+        default = "default_admin_session_user_agent_binding",
+        alias = "ADMIN_SESSION_USER_AGENT_BINDING"
+    )]
+    pub admin_session_user_agent_binding: String,
+    #[serde(
+        default = "default_admin_login_alert_threshold",
+        alias = "ADMIN_LOGIN_ALERT_THRESHOLD"
+    )]
+    pub admin_login_alert_threshold: u64,
+    #[serde(
+        default = "default_admin_login_alert_window_secs",
+        alias = "ADMIN_LOGIN_ALERT_WINDOW_SECS"
+    )]
+    pub admin_login_alert_window_secs: u64,
+    #[serde(default, alias = "ADMIN_LOGIN_ALERT_EMAILS")]
+    pub admin_login_alert_emails: String,
+    #[serde(default, alias = "ADMIN_LOGIN_ALERT_WEBHOOK_URL")]
+    pub admin_login_alert_webhook_url: Option<String>,
+    /// Seconds between checks for due webhook deliveries; 0 disables sending
+    #[serde(
+        default = "default_webhook_poll_interval_secs",
+        alias = "WEBHOOK_POLL_INTERVAL_SECS"
+    )]
+    pub webhook_poll_interval_secs: u64,
+    #[serde(
+        default = "default_webhook_max_attempts",
+        alias = "WEBHOOK_MAX_ATTEMPTS"
+    )]
+    pub webhook_max_attempts: u32,
+    #[serde(
+        default = "default_webhook_timeout_secs",
+        alias = "WEBHOOK_TIMEOUT_SECS"
+    )]
+    pub webhook_timeout_secs: u64,
+    /// Days delivered and failed webhook deliveries are kept; 0 keeps them
+    #[serde(
+        default = "default_webhook_delivery_retention_days",
+        alias = "WEBHOOK_DELIVERY_RETENTION_DAYS"
+    )]
+    pub webhook_delivery_retention_days: u32,
+    /// Slack or Discord incoming webhook that receives chat notifications
+    #[serde(default, alias = "CHAT_WEBHOOK_URL")]
+    pub chat_webhook_url: Option<String>,
+    /// `slack` or `discord`; detected from the URL when empty
+    #[serde(default, alias = "CHAT_WEBHOOK_PROVIDER")]
+    pub chat_webhook_provider: String,
+    #[serde(
+        default = "default_chat_notify_enabled",
+        alias = "CHAT_NOTIFY_MESSAGE_CREATED"
+    )]
+    pub chat_notify_message_created: bool,
+    #[serde(default, alias = "CHAT_NOTIFY_MESSAGE_ARCHIVED")]
+    pub chat_notify_message_archived: bool,
+    #[serde(default, alias = "CHAT_NOTIFY_OFFER_CREATED")]
+    pub chat_notify_offer_created: bool,
+    #[serde(
+        default = "default_contact_rate_limit_max",
+        alias = "CONTACT_RATE_LIMIT_MAX"
+    )]
+    pub contact_rate_limit_max: u64,
+    #[serde(
+        default = "default_contact_rate_limit_window_secs",
+        alias = "CONTACT_RATE_LIMIT_WINDOW_SECS"
+    )]
+    pub contact_rate_limit_window_secs: u64,
+    #[serde(
+        default = "default_contact_attachment_max_bytes",
+        alias = "CONTACT_ATTACHMENT_MAX_BYTES"
+    )]
+    pub contact_attachment_max_bytes: u64,
+    #[serde(default = "default_contact_page_path", alias = "CONTACT_SUCCESS_PATH")]
+    pub contact_success_path: String,
+    #[serde(default = "default_contact_page_path", alias = "CONTACT_ERROR_PATH")]
+    pub contact_error_path: String,
+    #[serde(default, alias = "CONTACT_REQUIRED_FIELDS")]
+    pub contact_required_fields: String,
+    #[serde(default = "default_spam_keywords", alias = "SPAM_KEYWORDS")]
+    pub spam_keywords: String,
+    #[serde(default = "default_spam_min_fill_secs", alias = "SPAM_MIN_FILL_SECS")]
+    pub spam_min_fill_secs: u64,
+    #[serde(default = "default_spam_reject_score", alias = "SPAM_REJECT_SCORE")]
+    pub spam_reject_score: u32,
+    #[serde(default, alias = "TRUSTED_PROXIES")]
+    pub trusted_proxies: String,
+    #[serde(default, alias = "SMTP_HOST")]
+    pub smtp_host: Option<String>,
+    #[serde(default = "default_smtp_port", alias = "SMTP_PORT")]
+    pub smtp_port: u16,
+    #[serde(default, alias = "SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+    #[serde(default, alias = "SMTP_PASSWORD")]
+    pub smtp_password: Option<String>,
+    #[serde(default = "default_smtp_from", alias = "SMTP_FROM")]
+    pub smtp_from: String,
+    #[serde(default = "default_smtp_security", alias = "SMTP_SECURITY")]
+    pub smtp_security: String,
+    #[serde(default, alias = "PUBLIC_BASE_URL")]
+    pub public_base_url: String,
+    #[serde(default = "default_site_title", alias = "SITE_TITLE")]
+    pub site_title: String,
+    #[serde(default, alias = "SITE_DESCRIPTION")]
+    pub site_description: String,
+    /// Absolute URL of the public site used in feeds; falls back to
+    /// `public_base_url`
+    #[serde(default, alias = "SITE_BASE_URL")]
+    pub site_base_url: String,
+    /// Comma-separated paths of the static pages listed in the sitemap
+    #[serde(
+        default = "default_sitemap_static_pages",
+        alias = "SITEMAP_STATIC_PAGES"
+    )]
+    pub sitemap_static_pages: String,
+    /// Seconds between writes of buffered blog post views; 0 disables counting
+    #[serde(
+        default = "default_blog_view_flush_interval_secs",
+        alias = "BLOG_VIEW_FLUSH_INTERVAL_SECS"
+    )]
+    pub blog_view_flush_interval_secs: u64,
+    /// Key signing the preview links of unpublished blog posts; when empty
+    /// a random key is used and links stop working on restart
+    #[serde(default, alias = "BLOG_PREVIEW_SECRET")]
+    pub blog_preview_secret: String,
+    /// Seconds a blog preview link stays valid
+    #[serde(
+        default = "default_blog_preview_link_ttl_secs",
+        alias = "BLOG_PREVIEW_LINK_TTL_SECS"
+    )]
+    pub blog_preview_link_ttl_secs: u64,
+    /// Comma-separated HTML tags kept when blog posts are saved; others are
+    /// removed from the Markdown source
+    #[serde(
+        default = "default_blog_allowed_html_tags",
+        alias = "BLOG_ALLOWED_HTML_TAGS"
+    )]
+    pub blog_allowed_html_tags: String,
+    /// Comma-separated attributes kept on the allowed HTML tags
+    #[serde(
+        default = "default_blog_allowed_html_attributes",
+        alias = "BLOG_ALLOWED_HTML_ATTRIBUTES"
+    )]
+    pub blog_allowed_html_attributes: String,
+    /// Format uploaded images are stored in: `jpeg` or `webp`
+    #[serde(default = "default_image_output_format", alias = "IMAGE_OUTPUT_FORMAT")]
+    pub image_output_format: String,
+    /// Largest accepted image upload in bytes
+    #[serde(
+        default = "default_image_max_upload_bytes",
+        alias = "IMAGE_MAX_UPLOAD_BYTES"
+    )]
+    pub image_max_upload_bytes: u64,
+    /// Largest accepted width or height of an uploaded image
+    #[serde(
+        default = "default_image_max_source_dimension",
+        alias = "IMAGE_MAX_SOURCE_DIMENSION"
+    )]
+    pub image_max_source_dimension: u32,
+    /// Largest accepted number of pixels of an uploaded image
+    #[serde(
+        default = "default_image_max_source_pixels",
+        alias = "IMAGE_MAX_SOURCE_PIXELS"
+    )]
+    pub image_max_source_pixels: u64,
+    /// Where image data is kept: `database`, `filesystem` or `s3`
+    #[serde(default = "default_media_store", alias = "MEDIA_STORE")]
+    pub media_store: String,
+    /// Directory of the filesystem media store
+    #[serde(default = "default_media_dir", alias = "MEDIA_DIR")]
+    pub media_dir: String,
+    /// Endpoint of the S3-compatible service, e.g. `https://s3.eu-central-1.amazonaws.com`
+    #[serde(default, alias = "MEDIA_S3_ENDPOINT")]
+    pub media_s3_endpoint: String,
+    #[serde(default, alias = "MEDIA_S3_BUCKET")]
+    pub media_s3_bucket: String,
+    #[serde(default = "default_media_s3_region", alias = "MEDIA_S3_REGION")]
+    pub media_s3_region: String,
+    #[serde(default, alias = "MEDIA_S3_ACCESS_KEY")]
+    pub media_s3_access_key: String,
+    #[serde(default, alias = "MEDIA_S3_SECRET_KEY")]
+    pub media_s3_secret_key: String,
+    #[serde(default, alias = "OIDC_ISSUER_URL")]
+    pub oidc_issuer_url: Option<String>,
+    #[serde(default, alias = "OIDC_CLIENT_ID")]
+    pub oidc_client_id: String,
+    #[serde(default, alias = "OIDC_CLIENT_SECRET")]
+    pub oidc_client_secret: String,
+    #[serde(default, alias = "OIDC_REDIRECT_URL")]
+    pub oidc_redirect_url: String,
+    #[serde(default, alias = "OIDC_ALLOWED_EMAILS")]
+    pub oidc_allowed_emails: String,
+    #[serde(default, alias = "CAPTCHA_PROVIDER")]
+    pub captcha_provider: String,
+    #[serde(default, alias = "CAPTCHA_SECRET")]
+    pub captcha_secret: String,
+    #[serde(default, alias = "CAPTCHA_SITE_KEY")]
+    pub captcha_site_key: String,
+    #[serde(default)]
+    pub features: FeatureFlags,
+}
+
+/// Optional feature groups. Routes of a disabled group are not mounted, so
+/// its endpoints return 404.
+#[derive(Deserialize, Clone, Debug)]
+pub struct FeatureFlags {
+    #[serde(default = "default_feature_enabled")]
+    pub offers: bool,
+    #[serde(default = "default_feature_enabled")]
+    pub blog: bool,
+    #[serde(default = "default_feature_enabled")]
+    pub banner: bool,
+    #[serde(default = "default_feature_enabled")]
+    pub short_links: bool,
+    #[serde(default = "default_feature_enabled")]
+    pub content_changes: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        FeatureFlags {
+            offers: true,
+            blog: true,
+            banner: true,
+            short_links: true,
+            content_changes: true,
+        }
+    }
 }
 
 fn default_rocket_port() -> u16 {
@@ -30,13 +354,317 @@ fn default_static_dir() -> String {
     "/app/static".to_string()
 }
 
+fn default_cors_max_age_secs() -> u64 {
+    60 * 60
+}
+
+fn default_normalize_slugs() -> bool {
+    true
+}
+
+fn default_admin_basic_auth_trusted_ips() -> String {
+    "127.0.0.1,::1".to_string()
+}
+
+fn default_https_redirect_exempt_paths() -> String {
+    "/health,/metrics".to_string()
+}
+
+fn default_admin_session_idle_secs() -> u64 {
+    60 * 60 * 24
+}
+
+fn default_admin_session_max_lifetime_secs() -> u64 {
+    60 * 60 * 24 * 7
+}
+
+fn default_webhook_poll_interval_secs() -> u64 {
+    10
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    5
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    10
+}
+
+fn default_webhook_delivery_retention_days() -> u32 {
+    30
+}
+
+fn default_chat_notify_enabled() -> bool {
+    true
+}
+
+fn default_site_title() -> String {
+    "MK-SBD s.r.o.".to_string()
+}
+
+fn default_sitemap_static_pages() -> String {
+    "/,/about,/offer,/cennik,/blog,/contact".to_string()
+}
+
+fn default_blog_view_flush_interval_secs() -> u64 {
+    60
+}
+
+fn default_blog_preview_link_ttl_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_blog_allowed_html_tags() -> String {
+    "a,abbr,b,blockquote,br,caption,cite,code,dd,del,details,div,dl,dt,em,figcaption,figure,\
+     h1,h2,h3,h4,h5,h6,hr,i,img,ins,kbd,li,mark,ol,p,pre,q,s,small,span,strong,sub,summary,sup,\
+     table,tbody,td,tfoot,th,thead,tr,u,ul"
+        .to_string()
+}
+
+fn default_blog_allowed_html_attributes() -> String {
+    "alt,cite,colspan,height,href,open,rowspan,src,title,width".to_string()
+}
+
+fn default_image_output_format() -> String {
+    "jpeg".to_string()
+}
+
+fn default_image_max_upload_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_image_max_source_dimension() -> u32 {
+    10_000
+}
+
+fn default_image_max_source_pixels() -> u64 {
+    50_000_000
+}
+
+fn default_media_store() -> String {
+    "database".to_string()
+}
+
+fn default_media_dir() -> String {
+    "media".to_string()
+}
+
+fn default_media_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_admin_session_cleanup_interval_secs() -> u64 {
+    60 * 60
+}
+
+fn default_admin_session_cookie_name() -> String {
+    "admin_auth".to_string()
+}
+
+fn default_admin_session_cookie_same_site() -> String {
+    "lax".to_string()
+}
+
+fn default_admin_remember_me_secs() -> u64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_admin_remember_me_cookie_name() -> String {
+    "admin_remember".to_string()
+}
+
+fn default_admin_session_ip_pinning() -> String {
+    "strict".to_string()
+}
+
+fn default_admin_session_user_agent_binding() -> String {
+    "off".to_string()
+}
+
+fn default_admin_login_max_failures() -> u64 {
+    5
+}
+
+fn default_admin_login_backoff_secs() -> u64 {
+    30
+}
+
+fn default_admin_login_lockout_secs() -> u64 {
+    15 * 60
+}
+
+fn default_admin_password_reset_max_per_ip() -> u64 {
+    5
+}
+
+fn default_admin_password_reset_max_per_user() -> u64 {
+    3
+}
+
+fn default_admin_password_reset_window_secs() -> u64 {
+    60 * 60
+}
+
+fn default_contact_rate_limit_max() -> u64 {
+    3
+}
+
+fn default_contact_rate_limit_window_secs() -> u64 {
+    10 * 60
+}
+
+fn default_contact_attachment_max_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_contact_page_path() -> String {
+    "/contact".to_string()
+}
+
+fn default_spam_keywords() -> String {
+    "viagra,casino,crypto,bitcoin,forex,backlinks,seo services,loan offer".to_string()
+}
+
+fn default_spam_min_fill_secs() -> u64 {
+    3
+}
+
+fn default_spam_reject_score() -> u32 {
+    100
+}
+
+fn default_admin_login_alert_threshold() -> u64 {
+    20
+}
+
+fn default_admin_login_alert_window_secs() -> u64 {
+    60 * 60
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_smtp_from() -> String {
+    "noreply@localhost".to_string()
+}
+
+fn default_smtp_security() -> String {
+    "starttls".to_string()
+}
+
+fn default_feature_enabled() -> bool {
+    true
+}
+
 impl AppConfig {
     pub fn load() -> Self {
         Figment::new()
             .merge(Toml::file("Config.toml"))
             .merge(Toml::file("../Config.toml"))
-            .merge(Env::raw().only(&["DATABASE_URL", "REDIS_URL", "ROCKET_PORT", "ROCKET_ADDRESS", "STATIC_DIR"]))
+            .merge(Env::raw().only(&[
+                "DATABASE_URL",
+                "REDIS_URL",
+                "ROCKET_PORT",
+                "ROCKET_ADDRESS",
+                "STATIC_DIR",
+                "CORS_ENABLED",
+                "CORS_ALLOWED_ORIGINS",
+                "CORS_MAX_AGE_SECS",
+                "NORMALIZE_SLUGS",
+                "ADMIN_BASIC_AUTH_ENABLED",
+                "ADMIN_BASIC_AUTH_TRUSTED_IPS",
+                "HTTPS_REDIRECT_ENABLED",
+                "HTTPS_REDIRECT_EXEMPT_PATHS",
+                "ADMIN_SESSION_IDLE_SECS",
+                "ADMIN_SESSION_MAX_LIFETIME_SECS",
+                "ADMIN_SESSION_CLEANUP_INTERVAL_SECS",
+                "ADMIN_SESSION_COOKIE_NAME",
+                "ADMIN_SESSION_COOKIE_SECURE",
+                "ADMIN_SESSION_COOKIE_SAME_SITE",
+                "ADMIN_SESSION_COOKIE_MAX_AGE_SECS",
+                "ADMIN_REMEMBER_ME_SECS",
+                "ADMIN_REMEMBER_ME_COOKIE_NAME",
+                "ADMIN_LOGIN_MAX_FAILURES",
+                "ADMIN_LOGIN_BACKOFF_SECS",
+                "ADMIN_LOGIN_LOCKOUT_SECS",
+                "ADMIN_PASSWORD_RESET_MAX_PER_IP",
+                "ADMIN_PASSWORD_RESET_MAX_PER_USER",
+                "ADMIN_PASSWORD_RESET_WINDOW_SECS",
+                "ADMIN_SESSION_IP_PINNING",
+                "ADMIN_SESSION_USER_AGENT_BINDING",
+                "ADMIN_LOGIN_ALERT_THRESHOLD",
+                "ADMIN_LOGIN_ALERT_WINDOW_SECS",
+                "ADMIN_LOGIN_ALERT_EMAILS",
+                "ADMIN_LOGIN_ALERT_WEBHOOK_URL",
+                "WEBHOOK_POLL_INTERVAL_SECS",
+                "WEBHOOK_MAX_ATTEMPTS",
+                "WEBHOOK_TIMEOUT_SECS",
+                "WEBHOOK_DELIVERY_RETENTION_DAYS",
+                "CHAT_WEBHOOK_URL",
+                "CHAT_WEBHOOK_PROVIDER",
+                "CHAT_NOTIFY_MESSAGE_CREATED",
+                "CHAT_NOTIFY_MESSAGE_ARCHIVED",
+                "CHAT_NOTIFY_OFFER_CREATED",
+                "CONTACT_RATE_LIMIT_MAX",
+                "CONTACT_RATE_LIMIT_WINDOW_SECS",
+                "CONTACT_ATTACHMENT_MAX_BYTES",
+                "CONTACT_SUCCESS_PATH",
+                "CONTACT_ERROR_PATH",
+                "CONTACT_REQUIRED_FIELDS",
+                "SPAM_KEYWORDS",
+                "SPAM_MIN_FILL_SECS",
+                "SPAM_REJECT_SCORE",
+                "TRUSTED_PROXIES",
+                "SMTP_HOST",
+                "SMTP_PORT",
+                "SMTP_USERNAME",
+                "SMTP_PASSWORD",
+                "SMTP_FROM",
+                "SMTP_SECURITY",
+                "PUBLIC_BASE_URL",
+                "SITE_TITLE",
+                "SITE_DESCRIPTION",
+                "SITE_BASE_URL",
+                "SITEMAP_STATIC_PAGES",
+                "BLOG_VIEW_FLUSH_INTERVAL_SECS",
+                "BLOG_PREVIEW_SECRET",
+                "BLOG_PREVIEW_LINK_TTL_SECS",
+                "BLOG_ALLOWED_HTML_TAGS",
+                "BLOG_ALLOWED_HTML_ATTRIBUTES",
+                "IMAGE_OUTPUT_FORMAT",
+                "IMAGE_MAX_UPLOAD_BYTES",
+                "IMAGE_MAX_SOURCE_DIMENSION",
+                "IMAGE_MAX_SOURCE_PIXELS",
+                "MEDIA_STORE",
+                "MEDIA_DIR",
+                "MEDIA_S3_ENDPOINT",
+                "MEDIA_S3_BUCKET",
+                "MEDIA_S3_REGION",
+                "MEDIA_S3_ACCESS_KEY",
+                "MEDIA_S3_SECRET_KEY",
+                "OIDC_ISSUER_URL",
+                "OIDC_CLIENT_ID",
+                "OIDC_CLIENT_SECRET",
+                "OIDC_REDIRECT_URL",
+                "OIDC_ALLOWED_EMAILS",
+                "CAPTCHA_PROVIDER",
+                "CAPTCHA_SECRET",
+                "CAPTCHA_SITE_KEY",
+            ]))
+            .merge(Env::prefixed("FEATURE_").map(|key| format!("features.{key}").into()))
             .extract()
             .expect("Failed to load configuration. Ensure Config.toml exists or environment variables are set (DATABASE_URL, REDIS_URL).")
     }
+
+    /// Public site URL without a trailing slash
+    pub fn site_url(&self) -> &str {
+        let url = self.site_base_url.trim();
+        let url = if url.is_empty() {
+            self.public_base_url.trim()
+        } else {
+            url
+        };
+        url.trim_end_matches('/')
+    }
 }
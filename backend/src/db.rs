@@ -1,6 +1,8 @@
 // Database connection and initialization
 
 use diesel::Connection;
+use diesel::expression::SqlLiteral;
+use diesel::sql_types::{BigInt, Unsigned};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use rocket::Rocket;
 use rocket_db_pools::Database;
@@ -12,6 +14,12 @@ use tracing::{error, info};
 #[database("messages_db")]
 pub struct MessagesDB(MysqlPool);
 
+/// `LAST_INSERT_ID()`, the id generated by the last insert on the current
+/// connection
+pub fn last_insert_id() -> SqlLiteral<Unsigned<BigInt>> {
+    diesel::dsl::sql("LAST_INSERT_ID()")
+}
+
 // Embed migrations from the migrations directory
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
@@ -1,6 +1,6 @@
 // Error types and conversions for the application
 
-use rocket::http::Status;
+use rocket::http::{ContentType, Header, Status};
 use rocket::response::{self, Responder};
 use rocket::{Request, Response};
 use std::io::Cursor;
@@ -10,7 +10,7 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
-    Database(#[from] diesel::result::Error),
+    Database(diesel::result::Error),
 
     #[error("Redis error: {0}")]
     Redis(#[from] redis::RedisError),
@@ -21,6 +21,9 @@ pub enum AppError {
     #[error("Password hashing error: {0}")]
     PasswordHash(#[from] bcrypt::BcryptError),
 
+    #[error("Password hashing error: {0}")]
+    Argon2(argon2::password_hash::Error),
+
     #[error("Database pool error: {0}")]
     DatabasePool(#[from] rocket::tokio::task::JoinError),
 
@@ -30,14 +33,43 @@ pub enum AppError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Forbidden")]
+    Forbidden,
+
     #[error("Resource not found")]
     NotFound,
 
+    #[error("The slug '{slug}' is already in use")]
+    SlugConflict {
+        slug: String,
+        /// A free variant of the slug, when one was looked up
+        suggested_slug: Option<String>,
+    },
+
+    /// A write hit a unique index; `field` is the column it covers
+    #[error("{message}")]
+    Conflict { field: String, message: String },
+
     #[error("Unsupported media type")]
     UnsupportedMediaType,
 
+    #[error("Too many requests, retry in {retry_after_secs} seconds")]
+    TooManyRequests { retry_after_secs: u64 },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Email error: {0}")]
+    Email(String),
+
+    #[error("Identity provider error: {0}")]
+    Oidc(String),
+
+    #[error("CAPTCHA provider error: {0}")]
+    Captcha(String),
+
+    #[error("Media storage error: {0}")]
+    Storage(String),
 }
 
 impl AppError {
@@ -48,12 +80,21 @@ impl AppError {
             AppError::Redis(_) => Status::InternalServerError,
             AppError::Serialization(_) => Status::InternalServerError,
             AppError::PasswordHash(_) => Status::InternalServerError,
+            AppError::Argon2(_) => Status::InternalServerError,
             AppError::DatabasePool(_) => Status::InternalServerError,
             AppError::InvalidInput(_) => Status::BadRequest,
             AppError::Unauthorized => Status::Unauthorized,
+            AppError::Forbidden => Status::Forbidden,
             AppError::NotFound => Status::NotFound,
+            AppError::SlugConflict { .. } => Status::Conflict,
+            AppError::Conflict { .. } => Status::Conflict,
             AppError::UnsupportedMediaType => Status::UnsupportedMediaType,
+            AppError::TooManyRequests { .. } => Status::TooManyRequests,
             AppError::Io(_) => Status::InternalServerError,
+            AppError::Email(_) => Status::InternalServerError,
+            AppError::Oidc(_) => Status::BadGateway,
+            AppError::Captcha(_) => Status::BadGateway,
+            AppError::Storage(_) => Status::InternalServerError,
         }
     }
 
@@ -65,8 +106,13 @@ impl AppError {
                 | AppError::Redis(_)
                 | AppError::Serialization(_)
                 | AppError::PasswordHash(_)
+                | AppError::Argon2(_)
                 | AppError::DatabasePool(_)
                 | AppError::Io(_)
+                | AppError::Email(_)
+                | AppError::Oidc(_)
+                | AppError::Captcha(_)
+                | AppError::Storage(_)
         )
     }
 }
@@ -83,12 +129,139 @@ impl<'r> Responder<'r, 'r> for AppError {
             tracing::debug!("Client error: {}", message);
         }
 
-        Response::build()
-            .status(status)
-            .sized_body(message.len(), Cursor::new(message))
-            .ok()
+        let mut response = Response::build();
+        response.status(status);
+        // Slug conflicts carry a free slug the form can offer instead
+        if let AppError::SlugConflict {
+            slug,
+            suggested_slug,
+        } = &self
+        {
+            let body = serde_json::json!({
+                "error": "slug_conflict",
+                "message": message,
+                "slug": slug,
+                "suggested_slug": suggested_slug,
+            })
+            .to_string();
+            response
+                .header(ContentType::JSON)
+                .sized_body(body.len(), Cursor::new(body));
+        } else if let AppError::Conflict { field, .. } = &self {
+            let body = serde_json::json!({
+                "error": "conflict",
+                "field": field,
+                "message": message,
+            })
+            .to_string();
+            response
+                .header(ContentType::JSON)
+                .sized_body(body.len(), Cursor::new(body));
+        } else {
+            response.sized_body(message.len(), Cursor::new(message));
+        }
+        if let AppError::TooManyRequests { retry_after_secs } = self {
+            response.header(Header::new("Retry-After", retry_after_secs.to_string()));
+        }
+        response.ok()
+    }
+}
+
+/// Column covered by the unique index named in a MySQL duplicate entry
+/// message such as `Duplicate entry 'x' for key 'offers.idx_offers_slug'`.
+/// Index names follow `idx_<table>_<column>` or `uq_<table>_<column>`.
+fn conflicting_field(message: &str) -> String {
+    let key = message
+        .rsplit_once("for key '")
+        .and_then(|(_, rest)| rest.split_once('\''))
+        .map(|(key, _)| key)
+        .unwrap_or_default();
+    let (table, index) = key.split_once('.').unwrap_or(("", key));
+    let column = index
+        .strip_prefix("idx_")
+        .or_else(|| index.strip_prefix("uq_"))
+        .unwrap_or(index);
+    let column = match table {
+        "" => UNIQUE_INDEX_TABLES
+            .iter()
+            .find_map(|table| column.strip_prefix(table)?.strip_prefix('_'))
+            .unwrap_or(column),
+        table => column
+            .strip_prefix(table)
+            .and_then(|column| column.strip_prefix('_'))
+            .unwrap_or(column),
+    };
+    if column.is_empty() {
+        "unknown".to_string()
+    } else {
+        column.to_string()
+    }
+}
+
+/// Tables with unique indexes, for index names reported without their table
+/// by MySQL before 8.0
+const UNIQUE_INDEX_TABLES: [&str; 9] = [
+    "admin_password_resets",
+    "admin_user_invites",
+    "email_templates",
+    "admin_users",
+    "blog_posts",
+    "api_keys",
+    "offers",
+    "labels",
+    "tags",
+];
+
+impl From<diesel::result::Error> for AppError {
+    fn from(error: diesel::result::Error) -> Self {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        if let Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info) = &error {
+            let field = conflicting_field(info.message());
+            return AppError::Conflict {
+                message: format!("The {} is already in use", field.replace('_', " ")),
+                field,
+            };
+        }
+        AppError::Database(error)
     }
 }
 
 /// Result type alias for the application
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicting_field() {
+        assert_eq!(
+            conflicting_field("Duplicate entry 'fasady' for key 'offers.idx_offers_slug'"),
+            "slug"
+        );
+        assert_eq!(
+            conflicting_field("Duplicate entry 'tipy' for key 'uq_tags_slug'"),
+            "slug"
+        );
+        assert_eq!(
+            conflicting_field("Duplicate entry 'jana' for key 'idx_admin_user_invites_username'"),
+            "username"
+        );
+        assert_eq!(conflicting_field("Duplicate entry"), "unknown");
+    }
+
+    #[test]
+    fn test_unique_violation_is_conflict() {
+        let error = AppError::from(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            Box::new("Duplicate entry 'a' for key 'blog_posts.idx_blog_posts_slug'".to_string()),
+        ));
+        assert_eq!(error.status(), Status::Conflict);
+        assert!(matches!(error, AppError::Conflict { field, .. } if field == "slug"));
+        assert_eq!(
+            AppError::from(diesel::result::Error::NotFound).status(),
+            Status::InternalServerError
+        );
+    }
+}
@@ -0,0 +1,496 @@
+// Request and response fairings for cross-cutting HTTP concerns
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Method, Status};
+use rocket::{Data, Orbit, Request, Response, Rocket};
+use std::io::Cursor;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::routes::admin::sessions::prune_expired_sessions;
+use crate::utils::ip_in_list;
+
+/// Adds CORS headers to responses for allowed origins and answers
+/// preflight requests. Does nothing unless CORS is enabled in the config.
+pub struct Cors {
+    enabled: bool,
+    allowed_origins: Vec<String>,
+    max_age_secs: u64,
+}
+
+impl Cors {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Cors {
+            enabled: config.cors_enabled,
+            allowed_origins: config
+                .cors_allowed_origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect(),
+            max_age_secs: config.cors_max_age_secs,
+        }
+    }
+
+    /// `Access-Control-Allow-Origin` value for a request origin, and whether
+    /// credentials are allowed with it. Listed origins are reflected with
+    /// credentials; `*` allows any other origin, but without credentials.
+    fn allowed_origin(&self, origin: &str) -> Option<(String, bool)> {
+        if self
+            .allowed_origins
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+        {
+            Some((origin.to_string(), true))
+        } else if self.allowed_origins.iter().any(|allowed| allowed == "*") {
+            Some(("*".to_string(), false))
+        } else {
+            None
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if !self.enabled {
+            return;
+        }
+
+        let Some(origin) = request.headers().get_one("Origin") else {
+            return;
+        };
+
+        let Some((allow_origin, credentials)) = self.allowed_origin(origin) else {
+            return;
+        };
+
+        response.set_header(Header::new("Access-Control-Allow-Origin", allow_origin));
+        if credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+        response.adjoin_header(Header::new("Vary", "Origin"));
+
+        let requested_method = request.headers().get_one("Access-Control-Request-Method");
+        if request.method() != Method::Options || requested_method.is_none() {
+            return;
+        }
+
+        // Preflight request: there are no OPTIONS routes, so turn the
+        // resulting 404 into an empty success response.
+        response.set_header(Header::new(
+            "Access-Control-Allow-Methods",
+            "GET, POST, PUT, PATCH, DELETE, OPTIONS",
+        ));
+        if let Some(requested_headers) = request.headers().get_one("Access-Control-Request-Headers")
+        {
+            response.set_header(Header::new(
+                "Access-Control-Allow-Headers",
+                requested_headers.to_string(),
+            ));
+        }
+        response.set_header(Header::new(
+            "Access-Control-Max-Age",
+            self.max_age_secs.to_string(),
+        ));
+
+        if response.status() == Status::NotFound {
+            response.set_status(Status::NoContent);
+            response.set_sized_body(0, Cursor::new(Vec::new()));
+        }
+    }
+}
+
+/// Path requests are rerouted to while a redirect is pending, so that no
+/// handler runs for the plain HTTP request
+const HTTPS_REDIRECT_PATH: &str = "/__https_redirect";
+
+/// Redirects requests forwarded as plain HTTP by a TLS-terminating proxy
+/// (`X-Forwarded-Proto: http`) to their HTTPS equivalent, except for exempt
+/// paths such as health checks. Forwarding headers are only honoured from
+/// trusted proxies.
+pub struct HttpsRedirect {
+    enabled: bool,
+    exempt_paths: Vec<String>,
+    trusted_proxies: String,
+    /// Host of `PUBLIC_BASE_URL`, used when the proxy sends no
+    /// `X-Forwarded-Host`
+    public_host: Option<String>,
+}
+
+/// Redirect target computed for the current request, if any
+struct HttpsRedirectTarget(Option<String>);
+
+impl HttpsRedirect {
+    pub fn from_config(config: &AppConfig) -> Self {
+        HttpsRedirect {
+            enabled: config.https_redirect_enabled,
+            exempt_paths: config
+                .https_redirect_exempt_paths
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(|path| path.trim_end_matches('/').to_string())
+                .collect(),
+            trusted_proxies: config.trusted_proxies.clone(),
+            public_host: public_host(&config.public_base_url),
+        }
+    }
+
+    fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_paths.iter().any(|exempt| {
+            path.strip_prefix(exempt.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    fn redirect_location(&self, request: &Request<'_>) -> Option<String> {
+        let remote = request.remote()?.ip();
+        if !ip_in_list(&self.trusted_proxies, remote) {
+            return None;
+        }
+
+        let proto = request.headers().get_one("X-Forwarded-Proto")?;
+        let proto = proto.split(',').next().unwrap_or_default().trim();
+        if !proto.eq_ignore_ascii_case("http") {
+            return None;
+        }
+
+        if self.is_exempt(request.uri().path().as_str()) {
+            return None;
+        }
+
+        let host = request
+            .headers()
+            .get_one("X-Forwarded-Host")
+            .or(self.public_host.as_deref())
+            .or_else(|| request.headers().get_one("Host"))?;
+
+        Some(format!("https://{}{}", host.trim(), request.uri()))
+    }
+}
+
+/// Host, with the port if it is not the default, of a configured URL
+fn public_host(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+#[rocket::async_trait]
+impl Fairing for HttpsRedirect {
+    fn info(&self) -> Info {
+        Info {
+            name: "HTTPS Redirect",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+        if !self.enabled {
+            return;
+        }
+
+        let Some(location) = self.redirect_location(request) else {
+            return;
+        };
+
+        request.local_cache(|| HttpsRedirectTarget(Some(location)));
+        request.set_uri(Origin::parse(HTTPS_REDIRECT_PATH).expect("valid redirect path"));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let HttpsRedirectTarget(Some(location)) = request.local_cache(|| HttpsRedirectTarget(None))
+        else {
+            return;
+        };
+
+        let mut redirect = Response::build()
+            .status(Status::MovedPermanently)
+            .header(Header::new("Location", location.clone()))
+            .sized_body(0, Cursor::new(Vec::new()))
+            .finalize();
+        std::mem::swap(response, &mut redirect);
+    }
+}
+
+/// Periodically purges expired admin sessions from the per-user session
+/// index once the server is up. An interval of zero disables the job.
+pub struct SessionCleanup {
+    interval_secs: u64,
+}
+
+impl SessionCleanup {
+    pub fn from_config(config: &AppConfig) -> Self {
+        SessionCleanup {
+            interval_secs: config.admin_session_cleanup_interval_secs,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for SessionCleanup {
+    fn info(&self) -> Info {
+        Info {
+            name: "Session Cleanup",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        if self.interval_secs == 0 {
+            return;
+        }
+
+        let Some(redis) = rocket.state::<redis::Client>().cloned() else {
+            error!("Session cleanup disabled: no Redis client configured");
+            return;
+        };
+
+        let mut interval = rocket::tokio::time::interval(Duration::from_secs(self.interval_secs));
+        rocket::tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                match prune_expired_sessions(&redis).await {
+                    Ok(0) => {}
+                    Ok(removed) => info!("Purged {} expired admin sessions", removed),
+                    Err(e) => error!("Failed to purge expired admin sessions: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking::Client;
+
+    #[get("/ping")]
+    fn ping() -> &'static str {
+        "pong"
+    }
+
+    #[test]
+    fn test_wildcard_origin_is_never_credentialed() {
+        let cors = Cors {
+            enabled: true,
+            allowed_origins: vec!["https://admin.example.com".to_string(), "*".to_string()],
+            max_age_secs: 60,
+        };
+        assert_eq!(
+            cors.allowed_origin("https://evil.example"),
+            Some(("*".to_string(), false))
+        );
+        assert_eq!(
+            cors.allowed_origin("https://admin.example.com"),
+            Some(("https://admin.example.com".to_string(), true))
+        );
+
+        let rocket = rocket::build().attach(cors).mount("/", routes![ping]);
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+        let response = client
+            .get("/ping")
+            .header(Header::new("Origin", "https://evil.example"))
+            .dispatch();
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(headers.get_one("Access-Control-Allow-Credentials"), None);
+    }
+
+    fn redirect_client(enabled: bool) -> Client {
+        let redirect = HttpsRedirect {
+            enabled,
+            exempt_paths: vec!["/health".to_string()],
+            trusted_proxies: "10.0.0.0/8".to_string(),
+            public_host: public_host("https://example.org:8443/"),
+        };
+        let rocket = rocket::build()
+            .attach(redirect)
+            .mount("/", routes![ping, health]);
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[get("/health")]
+    fn health() -> &'static str {
+        "ok"
+    }
+
+    fn forwarded_get<'c>(
+        client: &'c Client,
+        uri: &'static str,
+        proto: &str,
+    ) -> rocket::local::blocking::LocalResponse<'c> {
+        client
+            .get(uri)
+            .remote("10.0.0.2:40000".parse().unwrap())
+            .header(Header::new("X-Forwarded-Host", "example.com"))
+            .header(Header::new("X-Forwarded-Proto", proto.to_string()))
+            .dispatch()
+    }
+
+    #[test]
+    fn test_http_request_redirected_to_https() {
+        let client = redirect_client(true);
+        let response = forwarded_get(&client, "/ping?page=2", "http");
+
+        assert_eq!(response.status(), Status::MovedPermanently);
+        assert_eq!(
+            response.headers().get_one("Location"),
+            Some("https://example.com/ping?page=2")
+        );
+    }
+
+    #[test]
+    fn test_forwarding_headers_only_trusted_from_proxies() {
+        let client = redirect_client(true);
+        let response = client
+            .get("/ping")
+            .remote("203.0.113.7:40000".parse().unwrap())
+            .header(Header::new("X-Forwarded-Host", "evil.example"))
+            .header(Header::new("X-Forwarded-Proto", "http"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        // Without `X-Forwarded-Host` the configured public host is used
+        let response = client
+            .get("/ping")
+            .remote("10.0.0.2:40000".parse().unwrap())
+            .header(Header::new("Host", "evil.example"))
+            .header(Header::new("X-Forwarded-Proto", "http"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Location"),
+            Some("https://example.org:8443/ping")
+        );
+    }
+
+    #[test]
+    fn test_https_request_passes_through() {
+        let client = redirect_client(true);
+        let response = forwarded_get(&client, "/ping", "https");
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().as_deref(), Some("pong"));
+    }
+
+    #[test]
+    fn test_exempt_path_not_redirected() {
+        let client = redirect_client(true);
+        let response = forwarded_get(&client, "/health", "http");
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_no_redirect_when_disabled() {
+        let client = redirect_client(false);
+        let response = forwarded_get(&client, "/ping", "http");
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    fn client(enabled: bool, max_age_secs: u64) -> Client {
+        let cors = Cors {
+            enabled,
+            allowed_origins: vec!["https://example.com".to_string()],
+            max_age_secs,
+        };
+        let rocket = rocket::build().attach(cors).mount("/", routes![ping]);
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    fn preflight<'c>(
+        client: &'c Client,
+        origin: &str,
+    ) -> rocket::local::blocking::LocalResponse<'c> {
+        client
+            .req(Method::Options, "/ping")
+            .header(Header::new("Origin", origin.to_string()))
+            .header(Header::new("Access-Control-Request-Method", "GET"))
+            .dispatch()
+    }
+
+    #[test]
+    fn test_preflight_sets_configured_max_age() {
+        let client = client(true, 1234);
+        let response = preflight(&client, "https://example.com");
+
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Max-Age"),
+            Some("1234")
+        );
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://example.com")
+        );
+    }
+
+    #[test]
+    fn test_preflight_ignored_when_cors_disabled() {
+        let client = client(false, 1234);
+        let response = preflight(&client, "https://example.com");
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(
+            response
+                .headers()
+                .get_one("Access-Control-Max-Age")
+                .is_none()
+        );
+        assert!(
+            response
+                .headers()
+                .get_one("Access-Control-Allow-Origin")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_preflight_ignored_for_unknown_origin() {
+        let client = client(true, 1234);
+        let response = preflight(&client, "https://evil.example");
+
+        assert!(
+            response
+                .headers()
+                .get_one("Access-Control-Max-Age")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_simple_request_has_no_max_age() {
+        let client = client(true, 1234);
+        let response = client
+            .get("/ping")
+            .header(Header::new("Origin", "https://example.com"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://example.com")
+        );
+        assert!(
+            response
+                .headers()
+                .get_one("Access-Control-Max-Age")
+                .is_none()
+        );
+    }
+}
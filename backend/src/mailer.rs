@@ -0,0 +1,89 @@
+// Outgoing email over SMTP
+
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+
+/// Sends plain text emails through the configured SMTP relay. Without an
+/// `SMTP_HOST` the mailer is disabled and callers fall back to logging.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn from_config(config: &AppConfig) -> AppResult<Self> {
+        let from = config
+            .smtp_from
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::Email(format!("invalid SMTP_FROM: {e}")))?;
+
+        let Some(host) = config
+            .smtp_host
+            .as_deref()
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+        else {
+            return Ok(Mailer {
+                transport: None,
+                from,
+            });
+        };
+
+        let mut builder = match config.smtp_security.trim().to_ascii_lowercase().as_str() {
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+            other => {
+                return Err(AppError::Email(format!(
+                    "SMTP_SECURITY must be starttls, tls or none, got {other:?}"
+                )));
+            }
+        }
+        .map_err(|e| AppError::Email(e.to_string()))?
+        .port(config.smtp_port);
+
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Mailer {
+            transport: Some(builder.build()),
+            from,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> AppResult<()> {
+        let Some(transport) = &self.transport else {
+            return Err(AppError::Email("SMTP is not configured".to_string()));
+        };
+
+        let to = to
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::Email(format!("invalid recipient: {e}")))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| AppError::Email(e.to_string()))?;
+
+        transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Email(e.to_string()))?;
+        Ok(())
+    }
+}
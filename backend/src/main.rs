@@ -3,21 +3,46 @@
 #[macro_use]
 extern crate rocket;
 
+mod captcha;
 mod config;
 mod db;
 mod error;
+mod fairings;
+mod mailer;
+mod markdown;
+mod media;
 mod models;
+mod notifier;
+mod oidc;
+mod password;
 mod routes;
+mod sanitize;
 mod schema;
+mod spam;
 mod utils;
+mod views;
+mod webhooks;
 
 use rocket::fairing::AdHoc;
 use rocket::fs::FileServer;
 use rocket_db_pools::Database;
 
+use captcha::CaptchaVerifier;
 use config::AppConfig;
 use db::MessagesDB;
-use routes::{admin, contact};
+use fairings::{Cors, HttpsRedirect, SessionCleanup};
+use mailer::Mailer;
+use media::Media;
+use notifier::ChatNotifier;
+use oidc::OidcClient;
+use routes::admin::blog_preview::BlogPreviewSigner;
+use routes::admin::login_limit::LoginAlerts;
+use routes::{admin, contact, search, sitemap};
+use sanitize::HtmlPolicy;
+use spam::SpamScorer;
+use utils::ImageSettings;
+use views::{BlogViewCounter, BlogViewFlush};
+use webhooks::WebhookDispatcher;
 
 #[rocket::launch]
 fn rocket() -> _ {
@@ -33,11 +58,15 @@ fn rocket() -> _ {
     let redis_client =
         redis::Client::open(app_config.redis_url.clone()).expect("Invalid REDIS_URL configuration");
 
+    // At least 10 MB, which imports and attachments rely on; image uploads
+    // are checked against IMAGE_MAX_UPLOAD_BYTES on their own
+    let upload_limit = app_config.image_max_upload_bytes.max(10 * 1024 * 1024);
+
     let figment = rocket::Config::figment()
         .merge(("port", app_config.rocket_port))
         .merge(("address", app_config.rocket_address.clone()))
-        .merge(("limits.data-form", 10 * 1024 * 1024)) // 10 MB for form data (images will be compressed)
-        .merge(("limits.file", 10 * 1024 * 1024)) // 10 MB for file uploads
+        .merge(("limits.data-form", upload_limit)) // form data (images will be compressed)
+        .merge(("limits.file", upload_limit)) // file uploads
         .merge((
             "databases.messages_db",
             rocket_db_pools::Config {
@@ -51,54 +80,124 @@ fn rocket() -> _ {
         ));
 
     let static_dir = app_config.static_dir.clone();
+    let features = app_config.features.clone();
+    let cors = Cors::from_config(&app_config);
+    let https_redirect = HttpsRedirect::from_config(&app_config);
+    let session_cleanup = SessionCleanup::from_config(&app_config);
+    let webhook_dispatcher = WebhookDispatcher::from_config(&app_config);
+    let mailer = Mailer::from_config(&app_config).expect("Invalid SMTP configuration");
+    let oidc_client = OidcClient::from_config(&app_config);
+    let captcha = CaptchaVerifier::from_config(&app_config);
+    let spam_scorer = SpamScorer::from_config(&app_config);
+    let login_alerts = LoginAlerts::from_config(&app_config, mailer.clone());
+    let chat_notifier = ChatNotifier::from_config(&app_config);
+    let blog_views = BlogViewCounter::from_config(&app_config);
+    let blog_view_flush = BlogViewFlush::from_config(&app_config);
+    let blog_preview = BlogPreviewSigner::from_config(&app_config);
+    let html_policy = HtmlPolicy::from_config(&app_config);
+    let image_settings = ImageSettings::from_config(&app_config);
+    let media = Media::from_config(&app_config).expect("Invalid media storage configuration");
 
     rocket::custom(figment)
         .manage(redis_client)
+        .manage(mailer)
+        .manage(oidc_client)
+        .manage(captcha)
+        .manage(spam_scorer)
+        .manage(login_alerts)
+        .manage(chat_notifier)
+        .manage(blog_views)
+        .manage(blog_preview)
+        .manage(html_policy)
+        .manage(image_settings)
+        .manage(media)
+        .manage(app_config)
         .attach(MessagesDB::init())
         .attach(AdHoc::on_ignite("Database Migrations", db::run_migrations))
-        .mount("/", routes![contact::submit_message])
+        .attach(session_cleanup)
+        .attach(webhook_dispatcher)
+        .attach(blog_view_flush)
+        .attach(https_redirect)
+        .attach(cors)
+        .mount(
+            "/",
+            routes![
+                contact::submit_message,
+                contact::submit_message_json,
+                contact::submit_message_multipart,
+                contact::get_captcha_settings,
+                contact::get_required_fields
+            ],
+        )
+        .mount("/", routes![sitemap::get_sitemap, search::site_search])
         .mount(
             "/",
             routes![
                 admin::admin_login,
                 admin::admin_logout,
+                admin::admin_oidc_login,
+                admin::admin_oidc_callback,
+                admin::admin_refresh,
                 admin::admin_status,
                 admin::admin_setup,
                 admin::get_admin_invite_status,
                 admin::accept_admin_invite,
+                admin::request_password_reset,
+                admin::confirm_password_reset,
                 admin::get_messages,
                 admin::delete_message,
                 admin::archive_message,
+                admin::search_messages,
+                admin::get_message_thread,
+                admin::export_messages,
+                admin::list_message_notes,
+                admin::create_message_note,
+                admin::delete_message_note,
+                admin::set_message_labels,
+                admin::list_labels,
+                admin::create_label,
+                admin::update_label,
+                admin::delete_label,
+                admin::bulk_update_messages,
+                admin::get_message,
+                admin::reply_to_message,
+                admin::mark_message_read,
+                admin::get_unread_message_count,
+                admin::list_message_attachments,
+                admin::download_message_attachment,
                 admin::get_archived_messages,
+                admin::restore_archived_message,
                 admin::permanently_delete_archived_message,
-                admin::list_offers,
-                admin::get_offer_by_slug,
-                admin::get_offer_image,
-                admin::create_offer,
-                admin::delete_offer,
-                admin::update_offer,
-                admin::list_blog_posts,
-                admin::list_all_blog_posts,
-                admin::get_blog_post_by_slug,
-                admin::get_blog_post_image,
-                admin::create_blog_post,
-                admin::update_blog_post,
-                admin::delete_blog_post,
+                admin::gdpr_erase,
+                admin::privacy_erase,
+                admin::privacy_export,
+                admin::get_audit_log,
+                admin::list_api_keys,
+                admin::create_api_key,
+                admin::update_api_key,
+                admin::revoke_api_key,
+                admin::list_webhooks,
+                admin::create_webhook,
+                admin::update_webhook,
+                admin::delete_webhook,
+                admin::list_webhook_deliveries,
+                admin::list_email_templates,
+                admin::update_email_template,
+                admin::validate_image,
                 admin::list_admin_users,
                 admin::create_admin_user,
                 admin::update_admin_user,
                 admin::delete_admin_user,
+                admin::change_admin_password,
                 admin::list_admin_invites,
                 admin::create_admin_invite,
                 admin::delete_admin_invite,
-                admin::get_active_banner,
-                admin::get_admin_banner,
-                admin::upsert_banner,
-                admin::delete_banner,
-                routes::offer_detail_page,
-                routes::blog_detail_page,
+                admin::list_admin_sessions,
+                admin::revoke_admin_session,
+                admin::revoke_all_admin_sessions,
             ],
         )
+        .mount("/", routes::feature_routes(&features))
         .mount("/", FileServer::from(&static_dir))
         .register("/", catchers![routes::not_found])
 }
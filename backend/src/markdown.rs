@@ -0,0 +1,134 @@
+// Markdown rendering for blog content
+
+use pulldown_cmark::{Event, Options, Parser, TagEnd, html};
+
+/// Reading speed used for the reading time of posts
+const WORDS_PER_MINUTE: u32 = 200;
+
+/// Markdown extensions of blog posts, following the frontend editor
+pub const MARKDOWN_OPTIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_STRIKETHROUGH)
+    .union(Options::ENABLE_TASKLISTS)
+    .union(Options::ENABLE_FOOTNOTES);
+
+/// Render Markdown to HTML that is safe to insert into a page.
+///
+/// Follows the frontend editor: GitHub-style tables, strikethrough and task
+/// lists, and single line breaks are kept. Raw HTML in the source is allowed
+/// but sanitized, so scripts, event handlers and `javascript:` links are
+/// removed.
+pub fn render_markdown(source: &str) -> String {
+    let parser = Parser::new_ext(source, MARKDOWN_OPTIONS).map(|event| match event {
+        Event::SoftBreak => Event::HardBreak,
+        other => other,
+    });
+
+    let mut unsafe_html = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::Builder::default()
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// The text of Markdown without its formatting, with blocks and line breaks
+/// turned into single spaces. Raw HTML is dropped.
+pub fn markdown_to_plain_text(source: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(
+        source,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    ) {
+        match event {
+            Event::Text(chunk) | Event::Code(chunk) => text.push_str(&chunk),
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::BlockQuote(_)
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Number of words in the text of Markdown; formatting, link targets and
+/// tokens without letters or digits are not counted
+pub fn word_count(source: &str) -> u32 {
+    markdown_to_plain_text(source)
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count() as u32
+}
+
+/// Minutes needed to read `words` words, rounded up
+pub fn reading_time_minutes(words: u32) -> u32 {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_plain_text() {
+        assert_eq!(
+            markdown_to_plain_text(
+                "# Nadpis\n\n**Tučný** text a [odkaz](https://example.com).\nĎalší riadok"
+            ),
+            "Nadpis Tučný text a odkaz. Ďalší riadok"
+        );
+        assert_eq!(markdown_to_plain_text("<b>html</b> `kód`"), "html kód");
+    }
+
+    #[test]
+    fn test_word_count() {
+        assert_eq!(
+            word_count("# Nadpis\n\n**Tučný** text - [odkaz](https://example.com)."),
+            4
+        );
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn test_reading_time_minutes() {
+        assert_eq!(reading_time_minutes(0), 0);
+        assert_eq!(reading_time_minutes(1), 1);
+        assert_eq!(reading_time_minutes(200), 1);
+        assert_eq!(reading_time_minutes(201), 2);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(
+            render_markdown("# Nadpis\n\n**tučné**"),
+            "<h1>Nadpis</h1>\n<p><strong>tučné</strong></p>\n"
+        );
+        assert_eq!(render_markdown("a\nb"), "<p>a<br>\nb</p>\n");
+        assert!(render_markdown("| a |\n|---|\n| b |").contains("<table>"));
+        // Gallery images are linked by their site path
+        assert!(
+            render_markdown("![Fasáda](/api/blog/7/images/3)")
+                .contains("<img src=\"/api/blog/7/images/3\" alt=\"Fasáda\">")
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_sanitizes_html() {
+        let html = render_markdown(
+            "<script>alert(1)</script>\n\n[x](javascript:alert(1)) <img src=x onerror=alert(1)>",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onerror"));
+
+        let link = render_markdown("[web](https://example.com)");
+        assert!(link.contains(r#"rel="noopener noreferrer nofollow""#));
+    }
+}
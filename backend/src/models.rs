@@ -1,14 +1,19 @@
 // Data models for contact messages
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use rocket::form::FromForm;
 use rocket::fs::TempFile;
 use rocket::serde::{Deserialize, Serialize};
 use rocket_db_pools::diesel::prelude::*;
+use std::collections::HashMap;
 
 use crate::schema::{
-    admin_user_invites, admin_users, banners, blog_posts, messages, messages_archive, offers,
+    admin_password_resets, admin_user_invites, admin_users, api_keys, audit_log, banners,
+    blog_post_images, blog_post_revisions, blog_post_tags, blog_posts, email_templates, labels,
+    message_attachments, message_labels, message_notes, message_replies, messages,
+    messages_archive, offer_clicks, offer_images, offers, tags, webhook_deliveries, webhooks,
 };
+use crate::utils::{has_control_chars, validate_email, validate_not_empty};
 
 /// Form data received from the contact form
 #[derive(Debug, Clone, Deserialize, Serialize, FromForm)]
@@ -20,6 +25,82 @@ pub struct ContactMessageForm {
     pub phone: Option<String>,
     pub subject: Option<String>,
     pub message: String,
+    pub preferred_contact: Option<String>,
+    /// Id of the offer the inquiry is about, when sent from an offer page
+    #[serde(default)]
+    pub offer_id: Option<i64>,
+    /// CAPTCHA response; the widgets' own field names are accepted in JSON
+    #[serde(
+        default,
+        alias = "h-captcha-response",
+        alias = "cf-turnstile-response",
+        alias = "g-recaptcha-response"
+    )]
+    pub captcha_token: Option<String>,
+    /// Unix timestamp in milliseconds of when the form was rendered, set by
+    /// the frontend in a hidden field
+    #[serde(default)]
+    pub form_started_at: Option<i64>,
+}
+
+/// Multipart variant of the contact form, which may carry an attachment
+#[derive(Debug, FromForm)]
+pub struct ContactMessageMultipart<'r> {
+    pub company: Option<String>,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub subject: Option<String>,
+    pub message: String,
+    pub preferred_contact: Option<String>,
+    pub offer_id: Option<i64>,
+    pub captcha_token: Option<String>,
+    pub form_started_at: Option<i64>,
+    #[field(name = "attachment")]
+    pub attachment: Option<TempFile<'r>>,
+}
+
+impl<'r> ContactMessageMultipart<'r> {
+    /// Split into the plain form fields and the uploaded file, if any
+    pub fn into_parts(self) -> (ContactMessageForm, Option<TempFile<'r>>) {
+        let form = ContactMessageForm {
+            company: self.company,
+            name: self.name,
+            email: self.email,
+            phone: self.phone,
+            subject: self.subject,
+            message: self.message,
+            preferred_contact: self.preferred_contact,
+            offer_id: self.offer_id,
+            captcha_token: self.captcha_token,
+            form_started_at: self.form_started_at,
+        };
+        (form, self.attachment.filter(|file| file.len() > 0))
+    }
+}
+
+/// Channel a sender would like to be answered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreferredContact {
+    Email,
+    Phone,
+}
+
+impl PreferredContact {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "email" => Some(PreferredContact::Email),
+            "phone" => Some(PreferredContact::Phone),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreferredContact::Email => "email",
+            PreferredContact::Phone => "phone",
+        }
+    }
 }
 
 /// Database representation of a contact message
@@ -32,10 +113,21 @@ pub struct ContactMessage {
     pub phone: Option<String>,
     pub subject: Option<String>,
     pub message: String,
+    pub preferred_contact: Option<String>,
+    pub spam_score: i32,
+    pub read_at: Option<NaiveDateTime>,
+    pub answered_at: Option<NaiveDateTime>,
+    pub offer_id: Option<i64>,
 }
 
-impl From<ContactMessageForm> for ContactMessage {
-    fn from(form: ContactMessageForm) -> Self {
+impl ContactMessage {
+    pub fn from_form(form: ContactMessageForm, spam_score: u32) -> Self {
+        let preferred_contact = form
+            .preferred_contact()
+            .ok()
+            .flatten()
+            .map(|channel| channel.as_str().to_string());
+
         ContactMessage {
             id: None,
             name: form.name,
@@ -43,15 +135,218 @@ impl From<ContactMessageForm> for ContactMessage {
             phone: form.phone,
             subject: form.subject,
             message: form.message,
+            preferred_contact,
+            spam_score: i32::try_from(spam_score).unwrap_or(i32::MAX),
+            read_at: None,
+            answered_at: None,
+            offer_id: form.offer_id,
+        }
+    }
+}
+
+/// Optional contact fields the operator has made mandatory, so the backend
+/// enforces what the frontend form marks as required
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ContactRequiredFields {
+    pub phone: bool,
+    pub subject: bool,
+}
+
+impl ContactRequiredFields {
+    /// Parse a comma-separated list such as `phone,subject`. Other names are
+    /// ignored; name, email and message are always required.
+    pub fn parse(list: &str) -> Self {
+        let mut required = ContactRequiredFields::default();
+        for field in list.split(',').map(|field| field.trim().to_lowercase()) {
+            match field.as_str() {
+                "phone" => required.phone = true,
+                "subject" => required.subject = true,
+                _ => {}
+            }
+        }
+        required
+    }
+}
+
+const MAX_CONTACT_NAME_LEN: usize = 100;
+const MAX_CONTACT_EMAIL_LEN: usize = 254;
+const MAX_CONTACT_PHONE_LEN: usize = 32;
+const MAX_CONTACT_SUBJECT_LEN: usize = 200;
+const MAX_CONTACT_MESSAGE_LEN: usize = 5000;
+
+/// A contact form field that failed validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ContactFieldError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+impl ContactFieldError {
+    fn new(field: &'static str, message: &'static str) -> Self {
+        ContactFieldError { field, message }
+    }
+}
+
+/// Body returned by the JSON contact endpoint
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ContactSubmitResponse {
+    pub success: bool,
+    pub message: String,
+    /// Failing fields, empty unless validation failed
+    pub errors: Vec<ContactFieldError>,
+}
+
+impl ContactSubmitResponse {
+    pub fn sent() -> Self {
+        ContactSubmitResponse {
+            success: true,
+            message: "Message sent".to_string(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn invalid(errors: Vec<ContactFieldError>) -> Self {
+        ContactSubmitResponse {
+            success: false,
+            message: "Validation failed".to_string(),
+            errors,
+        }
+    }
+
+    pub fn rejected(reason: &str) -> Self {
+        ContactSubmitResponse {
+            success: false,
+            message: reason.to_string(),
+            errors: Vec::new(),
         }
     }
 }
 
+/// Check the length and characters of an optional single-line field
+fn check_optional_field(
+    errors: &mut Vec<ContactFieldError>,
+    field: &'static str,
+    value: Option<&str>,
+    max_len: usize,
+    too_long: &'static str,
+) {
+    let Some(value) = value else {
+        return;
+    };
+    if value.chars().count() > max_len {
+        errors.push(ContactFieldError::new(field, too_long));
+    } else if has_control_chars(value, false) {
+        errors.push(ContactFieldError::new(field, "Contains invalid characters"));
+    }
+}
+
 impl ContactMessageForm {
+    /// Check every field, collecting all problems so they can be reported
+    /// together
+    pub fn validate(&self, required: ContactRequiredFields) -> Result<(), Vec<ContactFieldError>> {
+        let mut errors = Vec::new();
+
+        if !validate_not_empty(&self.name) {
+            errors.push(ContactFieldError::new("name", "Name is required"));
+        } else {
+            check_optional_field(
+                &mut errors,
+                "name",
+                Some(&self.name),
+                MAX_CONTACT_NAME_LEN,
+                "Name is too long",
+            );
+        }
+
+        if !validate_email(&self.email) {
+            errors.push(ContactFieldError::new("email", "Valid email is required"));
+        } else {
+            check_optional_field(
+                &mut errors,
+                "email",
+                Some(&self.email),
+                MAX_CONTACT_EMAIL_LEN,
+                "Email is too long",
+            );
+        }
+
+        if required.phone && !self.phone.as_deref().is_some_and(validate_not_empty) {
+            errors.push(ContactFieldError::new("phone", "Phone number is required"));
+        } else {
+            check_optional_field(
+                &mut errors,
+                "phone",
+                self.phone.as_deref(),
+                MAX_CONTACT_PHONE_LEN,
+                "Phone number is too long",
+            );
+        }
+        if required.subject && !self.subject.as_deref().is_some_and(validate_not_empty) {
+            errors.push(ContactFieldError::new("subject", "Subject is required"));
+        } else {
+            check_optional_field(
+                &mut errors,
+                "subject",
+                self.subject.as_deref(),
+                MAX_CONTACT_SUBJECT_LEN,
+                "Subject is too long",
+            );
+        }
+
+        if !validate_not_empty(&self.message) {
+            errors.push(ContactFieldError::new("message", "Message is required"));
+        } else if self.message.chars().count() > MAX_CONTACT_MESSAGE_LEN {
+            errors.push(ContactFieldError::new("message", "Message is too long"));
+        } else if has_control_chars(&self.message, true) {
+            errors.push(ContactFieldError::new(
+                "message",
+                "Contains invalid characters",
+            ));
+        }
+
+        if let Err(reason) = self.preferred_contact() {
+            errors.push(ContactFieldError::new("preferred_contact", reason));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Check if this submission is likely from a bot
     pub fn is_bot(&self) -> bool {
         self.company.as_ref().is_some_and(|c| !c.is_empty())
     }
+
+    /// Parse the optional preferred reply channel. Preferring phone requires
+    /// a phone number to be given.
+    pub fn preferred_contact(&self) -> Result<Option<PreferredContact>, &'static str> {
+        let Some(value) = self
+            .preferred_contact
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+
+        let channel = PreferredContact::parse(value)
+            .ok_or("Preferred contact must be either email or phone")?;
+
+        let has_phone = self
+            .phone
+            .as_deref()
+            .is_some_and(|phone| !phone.trim().is_empty());
+        if channel == PreferredContact::Phone && !has_phone {
+            return Err("Phone number is required when phone is the preferred contact");
+        }
+
+        Ok(Some(channel))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
@@ -64,6 +359,14 @@ pub struct Message {
     pub subject: Option<String>,
     pub message: String,
     pub created_at: NaiveDateTime,
+    pub preferred_contact: Option<String>,
+    pub spam_score: i32,
+    /// When an admin marked the message as read; `None` while it is new
+    pub read_at: Option<NaiveDateTime>,
+    /// When the last reply was sent; `None` until an admin replies
+    pub answered_at: Option<NaiveDateTime>,
+    /// The offer the inquiry was sent from, if any
+    pub offer_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
@@ -79,6 +382,11 @@ pub struct ArchivedMessage {
     pub message: String,
     pub created_at: NaiveDateTime,
     pub archived_at: NaiveDateTime,
+    pub preferred_contact: Option<String>,
+    pub spam_score: i32,
+    pub read_at: Option<NaiveDateTime>,
+    pub answered_at: Option<NaiveDateTime>,
+    pub offer_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -91,6 +399,73 @@ pub struct NewArchivedMessage {
     pub subject: Option<String>,
     pub message: String,
     pub created_at: NaiveDateTime,
+    pub preferred_contact: Option<String>,
+    pub spam_score: i32,
+    pub read_at: Option<NaiveDateTime>,
+    pub answered_at: Option<NaiveDateTime>,
+    pub offer_id: Option<i64>,
+}
+
+impl ArchivedMessage {
+    /// Convert an archived record back into an insertable message. The
+    /// original id is reused unless another message has taken it since, in
+    /// which case the database assigns a fresh one.
+    pub fn into_restored(self, original_id_taken: bool) -> ContactMessage {
+        ContactMessage {
+            id: (!original_id_taken).then_some(self.original_id),
+            name: self.name,
+            email: self.email,
+            phone: self.phone,
+            subject: self.subject,
+            message: self.message,
+            preferred_contact: self.preferred_contact,
+            spam_score: self.spam_score,
+            read_at: self.read_at,
+            answered_at: self.answered_at,
+            offer_id: self.offer_id,
+        }
+    }
+}
+
+/// Action applied to every message of a bulk request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum BulkMessageAction {
+    Archive,
+    /// Permanently delete, without archiving
+    Delete,
+    MarkRead,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminBulkMessagesRequest {
+    pub ids: Vec<i64>,
+    pub action: BulkMessageAction,
+}
+
+/// Result of a bulk request. `missing` lists the ids that were not active
+/// messages and were skipped.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkMessagesResponse {
+    pub action: BulkMessageAction,
+    pub processed: usize,
+    pub missing: Vec<i64>,
+}
+
+/// Body of the read status endpoint; `false` marks the message as new again
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageReadRequest {
+    pub read: bool,
+}
+
+/// Active messages no admin has marked as read yet
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct UnreadMessageCount {
+    pub unread: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +481,25 @@ pub struct ArchiveRequest {
     pub action: String,
 }
 
+/// Whether a message currently lives in `messages` or `messages_archive`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum MessageState {
+    Active,
+    Archived,
+}
+
+/// Result of an archive or restore request. `noop` is set when the message
+/// was already in the requested state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ArchiveOutcome {
+    pub state: MessageState,
+    pub message_id: i64,
+    pub archive_id: Option<i64>,
+    pub noop: bool,
+}
+
 impl Message {
     /// Convert a Message into a NewArchivedMessage suitable for inserting into
     /// the `messages_archive` table. This intentionally does NOT include an
@@ -119,6 +513,11 @@ impl Message {
             subject: self.subject,
             message: self.message,
             created_at: self.created_at,
+            preferred_contact: self.preferred_contact,
+            spam_score: self.spam_score,
+            read_at: self.read_at,
+            answered_at: self.answered_at,
+            offer_id: self.offer_id,
         }
     }
 }
@@ -128,6 +527,9 @@ impl Message {
 pub struct AdminLoginRequest {
     pub username: String,
     pub password: String,
+    /// Also issue a long-lived remember-me token
+    #[serde(default)]
+    pub remember_me: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -142,6 +544,8 @@ pub struct AdminSetupRequest {
 pub struct AdminCreateUserRequest {
     pub username: String,
     pub password: String,
+    pub role: Option<String>,
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -149,12 +553,40 @@ pub struct AdminCreateUserRequest {
 pub struct AdminUpdateUserRequest {
     pub username: String,
     pub password: Option<String>,
+    pub role: Option<String>,
+    /// New email address; omitted keeps the current one, empty clears it
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminCreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Days until the key expires, 1 to 3650; keys without it never expire
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminUpdateApiKeyRequest {
+    pub scopes: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AdminCreateInviteRequest {
     pub username: String,
+    pub role: Option<String>,
+    /// Where to send the signup link; it is also stored on the new account
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -164,6 +596,25 @@ pub struct AdminAcceptInviteRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminPasswordResetRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminPasswordResetConfirmRequest {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminGdprEraseRequest {
+    pub email: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AdminUpsertBannerRequest {
@@ -175,6 +626,14 @@ pub struct AdminUpsertBannerRequest {
     pub is_active: bool,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminUpdateEmailTemplateRequest {
+    pub subject: String,
+    pub body: String,
+    pub is_active: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AdminStatusResponse {
@@ -182,6 +641,26 @@ pub struct AdminStatusResponse {
     pub setup_required: bool,
     pub current_user_id: Option<i64>,
     pub current_username: Option<String>,
+    pub current_role: Option<String>,
+    /// Whether single sign-on through OpenID Connect is offered
+    pub oidc_enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminSessionRefreshResponse {
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminSessionDto {
+    pub id: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub current: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,6 +672,172 @@ pub struct PaginatedMessages {
     pub limit: i64,
 }
 
+/// Messages from one sender, represented by the most recent one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageGroup {
+    pub email: String,
+    pub count: i64,
+    pub latest: Message,
+}
+
+impl MessageGroup {
+    /// Group messages by sender email (ignoring case and surrounding
+    /// whitespace), ordered by the latest activity first
+    pub fn group_by_email(messages: Vec<Message>) -> Vec<MessageGroup> {
+        let mut groups: HashMap<String, MessageGroup> = HashMap::new();
+
+        for message in messages {
+            let key = message.email.trim().to_lowercase();
+            match groups.get_mut(&key) {
+                Some(group) => {
+                    group.count += 1;
+                    if (message.created_at, message.id) > (group.latest.created_at, group.latest.id)
+                    {
+                        group.latest = message;
+                    }
+                }
+                None => {
+                    groups.insert(
+                        key.clone(),
+                        MessageGroup {
+                            email: key,
+                            count: 1,
+                            latest: message,
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut groups: Vec<MessageGroup> = groups.into_values().collect();
+        groups.sort_by(|a, b| {
+            (b.latest.created_at, b.latest.id).cmp(&(a.latest.created_at, a.latest.id))
+        });
+        groups
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedMessageGroups {
+    pub data: Vec<MessageGroup>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
+
+/// A message in a sender's thread, active or archived, with the replies
+/// sent to it
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ThreadMessage {
+    pub state: MessageState,
+    /// Id of the active message, or the original id of an archived one
+    pub message_id: i64,
+    pub archive_id: Option<i64>,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub subject: Option<String>,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+    pub archived_at: Option<NaiveDateTime>,
+    pub preferred_contact: Option<String>,
+    pub spam_score: i32,
+    pub read_at: Option<NaiveDateTime>,
+    pub answered_at: Option<NaiveDateTime>,
+    pub offer_id: Option<i64>,
+    pub replies: Vec<MessageReply>,
+}
+
+impl From<Message> for ThreadMessage {
+    fn from(message: Message) -> Self {
+        ThreadMessage {
+            state: MessageState::Active,
+            message_id: message.id,
+            archive_id: None,
+            name: message.name,
+            email: message.email,
+            phone: message.phone,
+            subject: message.subject,
+            message: message.message,
+            created_at: message.created_at,
+            archived_at: None,
+            preferred_contact: message.preferred_contact,
+            spam_score: message.spam_score,
+            read_at: message.read_at,
+            answered_at: message.answered_at,
+            offer_id: message.offer_id,
+            replies: Vec::new(),
+        }
+    }
+}
+
+impl From<ArchivedMessage> for ThreadMessage {
+    fn from(archived: ArchivedMessage) -> Self {
+        ThreadMessage {
+            state: MessageState::Archived,
+            message_id: archived.original_id,
+            archive_id: Some(archived.id),
+            name: archived.name,
+            email: archived.email,
+            phone: archived.phone,
+            subject: archived.subject,
+            message: archived.message,
+            created_at: archived.created_at,
+            archived_at: Some(archived.archived_at),
+            preferred_contact: archived.preferred_contact,
+            spam_score: archived.spam_score,
+            read_at: archived.read_at,
+            answered_at: archived.answered_at,
+            offer_id: archived.offer_id,
+            replies: Vec::new(),
+        }
+    }
+}
+
+/// Every message from one sender email, oldest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageThread {
+    pub email: String,
+    pub messages: Vec<ThreadMessage>,
+}
+
+impl MessageThread {
+    /// Merge active and archived messages into chronological order and
+    /// attach the replies of each message
+    pub fn assemble(
+        email: String,
+        active: Vec<Message>,
+        archived: Vec<ArchivedMessage>,
+        replies: Vec<MessageReply>,
+    ) -> Self {
+        let mut replies_by_message: HashMap<i64, Vec<MessageReply>> = HashMap::new();
+        for reply in replies {
+            replies_by_message
+                .entry(reply.message_id)
+                .or_default()
+                .push(reply);
+        }
+
+        let mut messages: Vec<ThreadMessage> = active
+            .into_iter()
+            .map(ThreadMessage::from)
+            .chain(archived.into_iter().map(ThreadMessage::from))
+            .collect();
+        messages.sort_by_key(|message| (message.created_at, message.message_id));
+        for message in &mut messages {
+            if let Some(replies) = replies_by_message.get(&message.message_id) {
+                message.replies = replies.clone();
+            }
+        }
+
+        MessageThread { email, messages }
+    }
+}
+
 //
 // Offers - DB models and DTOs
 //
@@ -205,6 +850,98 @@ pub struct AdminUser {
     pub password_hash: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub role: String,
+    pub email: Option<String>,
+}
+
+impl AdminUser {
+    /// Parsed role; unknown values grant no permissions
+    pub fn role(&self) -> Option<AdminRole> {
+        AdminRole::parse(&self.role)
+    }
+}
+
+/// Role of an admin user, deciding which admin endpoints they may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminRole {
+    /// Manages offers, blog posts, the banner and images
+    Editor,
+    /// Handles contact messages, including permanent deletion
+    Moderator,
+    /// Full access, including managing other admins
+    Superadmin,
+}
+
+/// Group of admin actions checked against the user's role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ManageContent,
+    ManageMessages,
+    DeleteMessages,
+    ManageUsers,
+    ViewAuditLog,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 5] = [
+        Permission::ManageContent,
+        Permission::ManageMessages,
+        Permission::DeleteMessages,
+        Permission::ManageUsers,
+        Permission::ViewAuditLog,
+    ];
+
+    /// Parse an API key scope name
+    pub fn parse_scope(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "content" => Some(Permission::ManageContent),
+            "messages" => Some(Permission::ManageMessages),
+            "delete_messages" => Some(Permission::DeleteMessages),
+            "users" => Some(Permission::ManageUsers),
+            "audit" => Some(Permission::ViewAuditLog),
+            _ => None,
+        }
+    }
+
+    pub fn scope(&self) -> &'static str {
+        match self {
+            Permission::ManageContent => "content",
+            Permission::ManageMessages => "messages",
+            Permission::DeleteMessages => "delete_messages",
+            Permission::ManageUsers => "users",
+            Permission::ViewAuditLog => "audit",
+        }
+    }
+}
+
+impl AdminRole {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "editor" => Some(AdminRole::Editor),
+            "moderator" => Some(AdminRole::Moderator),
+            "superadmin" => Some(AdminRole::Superadmin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminRole::Editor => "editor",
+            AdminRole::Moderator => "moderator",
+            AdminRole::Superadmin => "superadmin",
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            AdminRole::Superadmin => true,
+            AdminRole::Editor => permission == Permission::ManageContent,
+            AdminRole::Moderator => matches!(
+                permission,
+                Permission::ManageMessages | Permission::DeleteMessages
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -212,6 +949,8 @@ pub struct AdminUser {
 pub struct NewAdminUser {
     pub username: String,
     pub password_hash: String,
+    pub role: String,
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -219,6 +958,8 @@ pub struct NewAdminUser {
 pub struct AdminUserDto {
     pub id: i64,
     pub username: String,
+    pub role: String,
+    pub email: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -233,198 +974,1141 @@ pub struct AdminUserInvite {
     pub expires_at: NaiveDateTime,
     pub created_at: NaiveDateTime,
     pub created_by: Option<i64>,
+    pub role: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = admin_password_resets)]
+#[allow(dead_code)]
+pub struct AdminPasswordReset {
+    pub id: i64,
+    pub user_id: i64,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Insertable)]
-#[diesel(table_name = admin_user_invites)]
-pub struct NewAdminUserInvite {
-    pub username: String,
-    pub token: String,
+#[diesel(table_name = admin_password_resets)]
+pub struct NewAdminPasswordReset {
+    pub user_id: i64,
+    pub token_hash: String,
     pub expires_at: NaiveDateTime,
-    pub created_by: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One recorded admin action. The username is copied so entries stay
+/// readable after the admin user is deleted.
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = audit_log)]
 #[serde(crate = "rocket::serde")]
-pub struct AdminUserInviteDto {
+pub struct AuditLogEntry {
     pub id: i64,
-    pub username: String,
-    pub token: String,
-    pub invite_path: String,
-    pub expires_at: NaiveDateTime,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub ip_address: Option<String>,
     pub created_at: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, Queryable, Selectable)]
-#[diesel(table_name = banners)]
-pub struct Banner {
-    pub id: i64,
-    pub title: String,
-    pub message: String,
-    pub tone: String,
-    pub link_label: Option<String>,
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLogEntry {
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub ip_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedAuditLog {
+    pub data: Vec<AuditLogEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = admin_user_invites)]
+pub struct NewAdminUserInvite {
+    pub username: String,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+    pub created_by: Option<i64>,
+    pub role: String,
+    pub email: Option<String>,
+}
+
+/// Key for headless admin access. It acts as its owner, limited to the
+/// stored comma-separated scopes.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = api_keys)]
+#[allow(dead_code)]
+pub struct ApiKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub scopes: String,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl ApiKey {
+    /// Granted scopes; unknown names are ignored
+    pub fn permissions(&self) -> Vec<Permission> {
+        self.scopes
+            .split(',')
+            .filter_map(Permission::parse_scope)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKey {
+    pub user_id: i64,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub scopes: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiKeyDto {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A newly created key. The plain `key` is only ever returned here.
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiKeyCreatedResponse {
+    pub key: String,
+    pub api_key: ApiKeyDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminUserInviteDto {
+    pub id: i64,
+    pub username: String,
+    pub role: String,
+    pub token: String,
+    pub invite_path: String,
+    pub email: Option<String>,
+    /// Whether the signup link was emailed; only set when the invite is created
+    pub email_sent: bool,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = banners)]
+pub struct Banner {
+    pub id: i64,
+    pub title: String,
+    pub message: String,
+    pub tone: String,
+    pub link_label: Option<String>,
     pub link_url: Option<String>,
     pub is_active: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, Insertable)]
-#[diesel(table_name = banners)]
-pub struct NewBanner {
-    pub title: String,
-    pub message: String,
-    pub tone: String,
-    pub link_label: Option<String>,
-    pub link_url: Option<String>,
-    pub is_active: bool,
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = banners)]
+pub struct NewBanner {
+    pub title: String,
+    pub message: String,
+    pub tone: String,
+    pub link_label: Option<String>,
+    pub link_url: Option<String>,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BannerDto {
+    pub id: i64,
+    pub title: String,
+    pub message: String,
+    pub tone: String,
+    pub link_label: Option<String>,
+    pub link_url: Option<String>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Attachment metadata, without the file contents
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = message_attachments)]
+pub struct MessageAttachmentInfo {
+    pub id: i64,
+    pub message_id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = message_attachments)]
+pub struct NewMessageAttachment {
+    pub message_id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub data: Vec<u8>,
+}
+
+/// A reply an admin sent to a contact message
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = message_replies)]
+pub struct MessageReply {
+    pub id: i64,
+    pub message_id: i64,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub subject: String,
+    pub body: String,
+    pub sent_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = message_replies)]
+pub struct NewMessageReply {
+    pub message_id: i64,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminMessageReplyRequest {
+    /// Defaults to `Re:` and the message subject
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub body: String,
+}
+
+/// An internal note staff attached to a contact message; never sent to
+/// the sender
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = message_notes)]
+pub struct MessageNote {
+    pub id: i64,
+    pub message_id: i64,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = message_notes)]
+pub struct NewMessageNote {
+    pub message_id: i64,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminMessageNoteRequest {
+    pub body: String,
+}
+
+/// A category admins sort messages into, such as "sales" or "support"
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = labels)]
+pub struct Label {
+    pub id: i64,
+    pub name: String,
+    /// Hex color of the label badge, `#rrggbb`
+    pub color: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = labels)]
+pub struct NewLabel {
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminLabelRequest {
+    pub name: String,
+    /// Defaults to gray
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = message_labels)]
+pub struct NewMessageLabel {
+    pub message_id: i64,
+    pub label_id: i64,
+}
+
+/// Replaces all labels of a message
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageLabelsRequest {
+    pub label_ids: Vec<i64>,
+}
+
+/// A message with its attachments, the replies sent to it, internal notes
+/// and labels
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageDetail {
+    #[serde(flatten)]
+    pub message: Message,
+    pub attachments: Vec<MessageAttachmentInfo>,
+    pub replies: Vec<MessageReply>,
+    pub notes: Vec<MessageNote>,
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = email_templates)]
+pub struct EmailTemplate {
+    pub id: i64,
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = email_templates)]
+pub struct NewEmailTemplate {
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct EmailTemplateDto {
+    pub id: i64,
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+    pub is_active: bool,
+    /// Placeholders such as `{name}` the template may use
+    pub placeholders: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = offers)]
+pub struct Offer {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: Option<String>,
+    pub link: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub sort_order: i32,
+    pub active: bool,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    #[allow(dead_code)]
+    pub deleted_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = offers)]
+pub struct NewOffer {
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: Option<String>,
+    pub link: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub sort_order: i32,
+    pub active: bool,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+}
+
+/// DTO used by the frontend / API for returning offer data.
+/// Images are listed without their data and served via separate image
+/// endpoints; `image_mime` and `image_url` describe the primary image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferDto {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: Option<String>,
+    pub link: Option<String>,
+    pub image_mime: Option<String>,
+    pub image_url: Option<String>,
+    pub images: Vec<OfferImageDto>,
+    pub created_at: NaiveDateTime,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub sort_order: i32,
+    pub active: bool,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+    pub short_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedOffers {
+    pub data: Vec<OfferDto>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
+
+#[derive(Debug, FromForm)]
+pub struct AdminImportOffersMultipart<'r> {
+    #[field(name = "file")]
+    pub file: TempFile<'r>,
+}
+
+/// An offer created by a CSV import, with the row it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportedOffer {
+    pub row: usize,
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+}
+
+/// A row of a CSV import that was not imported, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferImportError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferImportReport {
+    pub imported: Vec<ImportedOffer>,
+    pub errors: Vec<OfferImportError>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = offer_clicks)]
+pub struct NewOfferClick {
+    pub offer_id: i64,
+    pub referrer: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Clicks on an offer's link on one day (UTC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DailyOfferClicks {
+    pub date: NaiveDate,
+    pub clicks: i64,
+}
+
+/// Clicks on an offer's link in the requested range, by day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferClickStats {
+    pub offer_id: i64,
+    pub title: String,
+    pub slug: String,
+    pub total: i64,
+    pub daily: Vec<DailyOfferClicks>,
+}
+
+/// An image of an offer, without its data
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[diesel(table_name = offer_images)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferImageInfo {
+    pub id: i64,
+    pub offer_id: i64,
+    pub image_mime: String,
+    pub position: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = offer_images)]
+pub struct NewOfferImage {
+    pub offer_id: i64,
+    /// Key of the image data in the media store
+    pub storage_key: String,
+    pub image_mime: String,
+    pub image_hash: String,
+    pub position: i32,
+}
+
+/// An image of an offer with its URL, which stays the same when the images
+/// are reordered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OfferImageDto {
+    #[serde(flatten)]
+    pub image: OfferImageInfo,
+    pub url: String,
+}
+
+#[derive(Debug, FromForm)]
+pub struct AdminUploadOfferImageMultipart<'r> {
+    #[field(name = "image")]
+    pub image: TempFile<'r>,
+}
+
+/// New order of the images of an offer; the first is the primary image
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminOfferImageOrderRequest {
+    pub image_ids: Vec<i64>,
+}
+
+/// New display order of offers; must list every offer exactly once
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminOfferOrderRequest {
+    pub offer_ids: Vec<i64>,
+}
+
+#[derive(Debug, FromForm)]
+pub struct AdminCreateOfferMultipart<'r> {
+    pub title: String,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
+    pub excerpt: Option<String>,
+    pub content: Option<String>,
+    pub link: Option<String>,
+    /// Image uploaded as file instead of base64
+    #[field(name = "image")]
+    pub image: Option<TempFile<'r>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Offers stay active unless this is false
+    pub active: Option<bool>,
+    /// Start and end of the time the offer is shown, as RFC 3339 times
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+}
+
+#[derive(Debug, FromForm)]
+pub struct AdminUpdateOfferMultipart<'r> {
+    pub title: String,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
+    pub excerpt: Option<String>,
+    pub content: Option<String>,
+    pub link: Option<String>,
+    /// Optional: replaces the primary image, or adds one when the offer has
+    /// no images
+    #[field(name = "image")]
+    pub image: Option<TempFile<'r>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Offers stay active unless this is false
+    pub active: Option<bool>,
+    /// Start and end of the time the offer is shown, as RFC 3339 times
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+}
+
+/// Keep a field that is set to `null` apart from one that is left out:
+/// `None` when it is missing, `Some(None)` when it is `null`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: rocket::serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Fields of an offer to change; fields left out are kept, and `null`
+/// clears an optional field
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct AdminPatchOfferRequest {
+    pub title: Option<String>,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub excerpt: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub content: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub link: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub latitude: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub longitude: Option<Option<f64>>,
+    pub active: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    pub starts_at: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub ends_at: Option<Option<String>>,
+}
+
+/// Columns of an offer changed by a partial update; `None` leaves a column
+/// as it is
+#[derive(Debug, Clone, Default, PartialEq, AsChangeset)]
+#[diesel(table_name = offers)]
+pub struct OfferChangeset {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub excerpt: Option<Option<String>>,
+    pub content: Option<Option<String>>,
+    pub link: Option<Option<String>>,
+    pub latitude: Option<Option<f64>>,
+    pub longitude: Option<Option<f64>>,
+    pub active: Option<bool>,
+    pub starts_at: Option<Option<NaiveDateTime>>,
+    pub ends_at: Option<Option<NaiveDateTime>>,
+}
+
+//
+// Image validation
+//
+
+#[derive(Debug, FromForm)]
+pub struct AdminValidateImageMultipart<'r> {
+    #[field(name = "image")]
+    pub image: TempFile<'r>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ImageValidationResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+}
+
+//
+// Site search
+//
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum SearchResultKind {
+    BlogPost,
+    Offer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum TrashItemKind {
+    Offer,
+    BlogPost,
+}
+
+/// An offer or blog post in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TrashItem {
+    #[serde(rename = "type")]
+    pub kind: TrashItemKind,
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub deleted_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedTrash {
+    pub data: Vec<TrashItem>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
+
+/// A published blog post or an offer matching a site search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SearchResult {
+    #[serde(rename = "type")]
+    pub kind: SearchResultKind,
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    /// Path of the page on the site
+    pub url: String,
+    /// Escaped plain text around the first match, with the search terms
+    /// wrapped in `<mark>`
+    pub snippet_html: String,
+    /// Full-text relevance; higher is better
+    pub score: f64,
+}
+
+//
+// Blog Posts - DB models and DTOs
+//
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = blog_posts)]
+pub struct BlogPost {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: String,
+    pub word_count: u32,
+    pub reading_time_minutes: u32,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    pub author_id: Option<i64>,
+    pub published: bool,
+    pub featured: bool,
+    pub featured_order: i32,
+    pub archived_at: Option<NaiveDateTime>,
+    #[allow(dead_code)]
+    pub deleted_at: Option<NaiveDateTime>,
+    pub view_count: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = blog_posts)]
+pub struct NewBlogPost {
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: String,
+    pub word_count: u32,
+    pub reading_time_minutes: u32,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    pub author_id: Option<i64>,
+    pub published: bool,
+}
+
+/// The admin user credited for a post. `avatar_url` is the Gravatar image
+/// of their email, if they have one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogAuthor {
+    pub id: i64,
+    pub name: String,
+    pub avatar_url: Option<String>,
+}
+
+/// An author with the number of their published posts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogAuthorSummary {
+    #[serde(flatten)]
+    pub author: BlogAuthor,
+    pub post_count: i64,
+}
+
+/// DTO used by the frontend / API for returning blog post data.
+/// `image_mime` is the type of the cover, the first of `images`, which is
+/// served via a separate image endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostDto {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    /// Markdown source
+    pub content: String,
+    /// `content` rendered to sanitized HTML
+    pub content_html: String,
+    /// Words in the text of `content`, counted when the post is saved
+    pub word_count: u32,
+    /// Estimated minutes to read `content`
+    pub reading_time_minutes: u32,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    /// `None` when the post was written before authors were recorded or
+    /// its author was deleted
+    pub author: Option<BlogAuthor>,
+    pub tags: Vec<Tag>,
+    pub image_mime: Option<String>,
+    /// URL of the cover image, the `url` of the first of `images`
+    pub image_url: Option<String>,
+    pub images: Vec<BlogPostImageDto>,
+    pub published: bool,
+    /// Highlighted on the homepage, ordered by `featured_order`
+    pub featured: bool,
+    pub featured_order: i32,
+    /// Set while the post is archived and hidden from the public site
+    pub archived_at: Option<NaiveDateTime>,
+    /// Reads counted so far; recent views are written in batches
+    pub view_count: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub short_code: String,
+}
+
+/// Blog post columns needed for listings, without the content
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = blog_posts)]
+pub struct BlogPostListing {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub reading_time_minutes: u32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Blog post columns needed for the RSS and Atom feeds
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = blog_posts)]
+pub struct BlogFeedEntry {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub content: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A blog post in the public list; the content is only returned by the
+/// by-slug endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostSummary {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub reading_time_minutes: u32,
+    pub tags: Vec<Tag>,
+    pub image_mime: Option<String>,
+    /// URL of the cover image
+    pub image_url: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub short_code: String,
+}
+
+/// SEO and OpenGraph metadata of a published post, with the post's own
+/// title, excerpt, URL and image filled in where no override is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostMeta {
+    pub title: String,
+    pub description: Option<String>,
+    pub canonical_url: String,
+    pub og_image: Option<String>,
+    pub og_type: String,
+    pub site_name: String,
+    pub published_time: NaiveDateTime,
+    pub modified_time: NaiveDateTime,
+}
+
+/// An image of a blog post, without the image data
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[diesel(table_name = blog_post_images)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostImageInfo {
+    pub id: i64,
+    pub blog_post_id: i64,
+    pub image_mime: String,
+    pub position: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = blog_post_images)]
+pub struct NewBlogPostImage {
+    pub blog_post_id: i64,
+    /// Key of the image data in the media store
+    pub storage_key: String,
+    pub image_mime: String,
+    pub image_hash: String,
+    pub position: i32,
+}
+
+/// An image of a blog post with its URL, which stays the same when the
+/// images are reordered and can be used in the post content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostImageDto {
+    #[serde(flatten)]
+    pub image: BlogPostImageInfo,
+    pub url: String,
+}
+
+#[derive(Debug, FromForm)]
+pub struct AdminUploadBlogPostImageMultipart<'r> {
+    #[field(name = "image")]
+    pub image: TempFile<'r>,
+}
+
+/// A zip of Markdown files with front matter, as written by the blog export
+#[derive(Debug, FromForm)]
+pub struct AdminImportBlogBundleMultipart<'r> {
+    #[field(name = "file")]
+    pub file: TempFile<'r>,
+}
+
+/// A post created by a blog import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogImportedPost {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub published: bool,
+    pub images: usize,
+}
+
+/// A link that shows an unpublished post to anyone who has it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPreviewLink {
+    pub url: String,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// New order of the images of a post; must list every image exactly once
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminBlogPostImageOrderRequest {
+    pub image_ids: Vec<i64>,
+}
+
+/// A post in the admin list, without its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminBlogPostSummary {
+    #[serde(flatten)]
+    pub post: BlogPostSummary,
+    pub published: bool,
+    pub featured: bool,
+    pub featured_order: i32,
+    pub archived_at: Option<NaiveDateTime>,
+    pub view_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedAdminBlogPosts {
+    pub data: Vec<AdminBlogPostSummary>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
+
+/// A published post with its read count, for the popular posts widget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PopularBlogPost {
+    #[serde(flatten)]
+    pub post: BlogPostSummary,
+    pub view_count: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
-pub struct BannerDto {
-    pub id: i64,
-    pub title: String,
-    pub message: String,
-    pub tone: String,
-    pub link_label: Option<String>,
-    pub link_url: Option<String>,
-    pub is_active: bool,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+pub struct PaginatedBlogPosts {
+    pub data: Vec<BlogPostSummary>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
 }
 
-#[derive(Debug, Clone, Queryable, Selectable)]
-#[diesel(table_name = offers)]
-pub struct Offer {
-    pub id: i64,
-    pub title: String,
-    pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: Option<String>,
-    pub link: Option<String>,
-    pub image: Option<Vec<u8>>,
-    pub image_mime: Option<String>,
-    pub created_at: NaiveDateTime,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
+/// Whether a post is featured, and its place among featured posts; lower
+/// `featured_order` comes first
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogFeatureRequest {
+    pub featured: bool,
+    #[serde(default)]
+    pub featured_order: i32,
 }
 
-#[derive(Debug, Clone, Insertable)]
-#[diesel(table_name = offers)]
-pub struct NewOffer {
-    pub title: String,
-    pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: Option<String>,
-    pub link: Option<String>,
-    pub image: Option<Vec<u8>>,
-    pub image_mime: Option<String>,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
+/// Markdown rendered by the admin preview, not saved
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPreviewRequest {
+    pub content: String,
 }
 
-/// DTO used by the frontend / API for returning offer data.
-/// Images are represented by `image_mime` and served via a separate
-/// image endpoint; handlers may inline images when necessary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
-pub struct OfferDto {
+pub struct BlogPreviewResponse {
+    pub html: String,
+}
+
+/// A blog post tag; posts are filtered by its slug
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = tags)]
+pub struct Tag {
     pub id: i64,
-    pub title: String,
+    pub name: String,
     pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: Option<String>,
-    pub link: Option<String>,
-    pub image_mime: Option<String>,
     pub created_at: NaiveDateTime,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
 }
 
-#[derive(Debug, FromForm)]
-pub struct AdminCreateOfferMultipart<'r> {
-    pub title: String,
+#[derive(Debug, Clone, PartialEq, Eq, Insertable)]
+#[diesel(table_name = tags)]
+pub struct NewTag {
+    pub name: String,
     pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: Option<String>,
-    pub link: Option<String>,
-    /// Image uploaded as file instead of base64
-    #[field(name = "image")]
-    pub image: Option<TempFile<'r>>,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
 }
 
-#[derive(Debug, FromForm)]
-pub struct AdminUpdateOfferMultipart<'r> {
-    pub title: String,
-    pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: Option<String>,
-    pub link: Option<String>,
-    /// Optional: Only provided if the user uploaded a new image
-    #[field(name = "image")]
-    pub image: Option<TempFile<'r>>,
-    pub latitude: Option<f64>,
-    pub longitude: Option<f64>,
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminTagRequest {
+    pub name: String,
+    /// Derived from the name when omitted
+    #[serde(default)]
+    pub slug: Option<String>,
 }
 
-//
-// Blog Posts - DB models and DTOs
-//
+/// A tag with the number of posts using it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TagUsage {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub post_count: i64,
+}
 
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = blog_post_tags)]
+pub struct NewBlogPostTag {
+    pub blog_post_id: i64,
+    pub tag_id: i64,
+}
+
+/// A blog post as it was before an update
 #[derive(Debug, Clone, Queryable, Selectable)]
-#[diesel(table_name = blog_posts)]
-pub struct BlogPost {
+#[diesel(table_name = blog_post_revisions)]
+pub struct BlogPostRevision {
     pub id: i64,
+    pub blog_post_id: i64,
     pub title: String,
     pub slug: String,
     pub excerpt: Option<String>,
     pub content: String,
-    pub image: Option<Vec<u8>>,
-    pub image_mime: Option<String>,
+    pub tags: String,
     pub published: bool,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
     pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Insertable)]
-#[diesel(table_name = blog_posts)]
-pub struct NewBlogPost {
+#[diesel(table_name = blog_post_revisions)]
+pub struct NewBlogPostRevision {
+    pub blog_post_id: i64,
     pub title: String,
     pub slug: String,
     pub excerpt: Option<String>,
     pub content: String,
-    pub image: Option<Vec<u8>>,
-    pub image_mime: Option<String>,
+    pub tags: String,
     pub published: bool,
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
 }
 
-/// DTO used by the frontend / API for returning blog post data.
-/// Images are represented by `image_mime` and served via a separate
-/// image endpoint; handlers may inline images when necessary.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A revision in the history list, without its content
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
-pub struct BlogPostDto {
+#[diesel(table_name = blog_post_revisions)]
+pub struct BlogPostRevisionSummary {
     pub id: i64,
+    pub blog_post_id: i64,
     pub title: String,
     pub slug: String,
-    pub excerpt: Option<String>,
-    pub content: String,
-    pub image_mime: Option<String>,
     pub published: bool,
+    /// Admin whose edit replaced this version
+    pub admin_user_id: Option<i64>,
+    pub admin_username: String,
     pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+}
+
+/// Differences between two versions of a post. `to` is `None` when the
+/// revision is compared with the current post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BlogPostRevisionDiff {
+    pub from: BlogPostRevisionSummary,
+    pub to: Option<BlogPostRevisionSummary>,
+    /// Names of the fields that differ, such as `title` or `content`
+    pub changed_fields: Vec<String>,
+    /// Unified diff of the Markdown content
+    pub content_diff: String,
 }
 
 #[derive(Debug, FromForm)]
 pub struct AdminCreateBlogPostMultipart<'r> {
     pub title: String,
-    pub slug: String,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
     pub excerpt: Option<String>,
     pub content: String,
-    /// Image uploaded as file instead of base64
+    /// Comma-separated tag names; unknown tags are created
+    pub tags: Option<String>,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    /// Cover image, saved as the first image of the post
     #[field(name = "image")]
     pub image: Option<TempFile<'r>>,
     #[field(name = "published")]
@@ -434,16 +2118,67 @@ pub struct AdminCreateBlogPostMultipart<'r> {
 #[derive(Debug, FromForm)]
 pub struct AdminUpdateBlogPostMultipart<'r> {
     pub title: String,
-    pub slug: String,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
     pub excerpt: Option<String>,
     pub content: String,
-    /// Optional: Only provided if the user uploaded a new image
+    /// Comma-separated tags; the current tags are kept when omitted
+    pub tags: Option<String>,
+    /// SEO overrides; blank or omitted fields are cleared
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    /// Optional: replaces the cover image, or adds one when the post has no
+    /// images
     #[field(name = "image")]
     pub image: Option<TempFile<'r>>,
     #[field(name = "published")]
     pub published: Option<bool>,
 }
 
+/// Fields of a post to change; fields left out are kept, and `null` clears
+/// an optional field
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct AdminPatchBlogPostRequest {
+    pub title: Option<String>,
+    /// Generated from the title when blank
+    pub slug: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub excerpt: Option<Option<String>>,
+    pub content: Option<String>,
+    /// Replaces all tags of the post
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub meta_title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub meta_description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub canonical_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub og_image: Option<Option<String>>,
+    pub published: Option<bool>,
+}
+
+/// Columns of a post changed by a partial update; `None` leaves a column as
+/// it is
+#[derive(Debug, Clone, Default, PartialEq, AsChangeset)]
+#[diesel(table_name = blog_posts)]
+pub struct BlogPostChangeset {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub excerpt: Option<Option<String>>,
+    pub content: Option<String>,
+    pub word_count: Option<u32>,
+    pub reading_time_minutes: Option<u32>,
+    pub meta_title: Option<Option<String>>,
+    pub meta_description: Option<Option<String>>,
+    pub canonical_url: Option<Option<String>>,
+    pub og_image: Option<Option<String>>,
+    pub published: Option<bool>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,6 +2193,10 @@ mod tests {
             phone: None,
             subject: None,
             message: "Spam message".to_string(),
+            preferred_contact: None,
+            offer_id: None,
+            captcha_token: None,
+            form_started_at: None,
         };
         assert!(bot_form.is_bot());
 
@@ -469,6 +2208,10 @@ mod tests {
             phone: None,
             subject: Some("Test".to_string()),
             message: "Hello, this is a test".to_string(),
+            preferred_contact: None,
+            offer_id: None,
+            captcha_token: None,
+            form_started_at: None,
         };
         assert!(!legit_form.is_bot());
 
@@ -480,10 +2223,145 @@ mod tests {
             phone: None,
             subject: None,
             message: "Another test".to_string(),
+            preferred_contact: None,
+            offer_id: None,
+            captcha_token: None,
+            form_started_at: None,
         };
         assert!(!empty_company.is_bot());
     }
 
+    fn contact_form(phone: Option<&str>, preferred_contact: Option<&str>) -> ContactMessageForm {
+        ContactMessageForm {
+            company: None,
+            name: "Dave".to_string(),
+            email: "dave@example.com".to_string(),
+            phone: phone.map(str::to_string),
+            subject: None,
+            message: "Please call me back".to_string(),
+            preferred_contact: preferred_contact.map(str::to_string),
+            offer_id: None,
+            captcha_token: None,
+            form_started_at: None,
+        }
+    }
+
+    #[test]
+    fn test_preferred_contact_validation() {
+        assert_eq!(contact_form(None, None).preferred_contact(), Ok(None));
+        assert_eq!(contact_form(None, Some("")).preferred_contact(), Ok(None));
+        assert_eq!(
+            contact_form(None, Some("email")).preferred_contact(),
+            Ok(Some(PreferredContact::Email))
+        );
+        assert_eq!(
+            contact_form(Some("+421 900 000 000"), Some("Phone")).preferred_contact(),
+            Ok(Some(PreferredContact::Phone))
+        );
+        assert!(contact_form(None, Some("fax")).preferred_contact().is_err());
+    }
+
+    #[test]
+    fn test_preferred_phone_requires_phone_number() {
+        assert!(
+            contact_form(None, Some("phone"))
+                .preferred_contact()
+                .is_err()
+        );
+        assert!(
+            contact_form(Some("  "), Some("phone"))
+                .preferred_contact()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_contact_form_validation() {
+        assert_eq!(
+            contact_form(None, None).validate(ContactRequiredFields::default()),
+            Ok(())
+        );
+
+        let mut form = contact_form(None, Some("phone"));
+        form.name = "  ".to_string();
+        form.email = "not-an-email".to_string();
+        form.subject = Some("x".repeat(MAX_CONTACT_SUBJECT_LEN + 1));
+        let fields: Vec<&str> = form
+            .validate(ContactRequiredFields::default())
+            .unwrap_err()
+            .iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["name", "email", "subject", "preferred_contact"]);
+    }
+
+    #[test]
+    fn test_contact_required_fields() {
+        assert_eq!(
+            ContactRequiredFields::parse(""),
+            ContactRequiredFields::default()
+        );
+        let required = ContactRequiredFields::parse(" Phone, subject ,name");
+        assert_eq!(
+            required,
+            ContactRequiredFields {
+                phone: true,
+                subject: true,
+            }
+        );
+
+        let mut form = contact_form(Some(" "), None);
+        let fields: Vec<&str> = form
+            .validate(required)
+            .unwrap_err()
+            .iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["phone", "subject"]);
+
+        form.phone = Some("+421 900 000 000".to_string());
+        form.subject = Some("Ponuka".to_string());
+        assert_eq!(form.validate(required), Ok(()));
+    }
+
+    #[test]
+    fn test_contact_submit_response_lists_field_errors() {
+        let errors = contact_form(None, Some("phone"))
+            .validate(ContactRequiredFields::default())
+            .unwrap_err();
+        let body = serde_json::to_value(ContactSubmitResponse::invalid(errors)).unwrap();
+
+        assert_eq!(body["success"], false);
+        assert_eq!(body["errors"][0]["field"], "preferred_contact");
+        assert_eq!(
+            serde_json::to_value(ContactSubmitResponse::sent()).unwrap()["errors"],
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn test_contact_form_rejects_control_characters() {
+        let mut form = contact_form(None, None);
+        form.message = "Hello,\r\nplease call me.\tThanks".to_string();
+        assert_eq!(form.validate(ContactRequiredFields::default()), Ok(()));
+
+        form.name = "Dave\u{0}".to_string();
+        form.message = "Hi\u{1b}[2J".to_string();
+        let fields: Vec<&str> = form
+            .validate(ContactRequiredFields::default())
+            .unwrap_err()
+            .iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["name", "message"]);
+    }
+
+    #[test]
+    fn test_contact_message_stores_normalized_preferred_contact() {
+        let contact = ContactMessage::from_form(contact_form(Some("0900"), Some(" PHONE ")), 0);
+        assert_eq!(contact.preferred_contact.as_deref(), Some("phone"));
+    }
+
     #[test]
     fn test_contact_message_from_form() {
         let form = ContactMessageForm {
@@ -493,9 +2371,13 @@ mod tests {
             phone: Some("123-456-7890".to_string()),
             subject: Some("Question".to_string()),
             message: "I have a question about your services".to_string(),
+            preferred_contact: None,
+            offer_id: None,
+            captcha_token: None,
+            form_started_at: None,
         };
 
-        let contact = ContactMessage::from(form.clone());
+        let contact = ContactMessage::from_form(form.clone(), 0);
 
         assert_eq!(contact.id, None);
         assert_eq!(contact.name, form.name);
@@ -503,6 +2385,7 @@ mod tests {
         assert_eq!(contact.phone, form.phone);
         assert_eq!(contact.subject, form.subject);
         assert_eq!(contact.message, form.message);
+        assert_eq!(contact.read_at, None);
     }
 
     #[test]
@@ -520,6 +2403,11 @@ mod tests {
             subject: Some("Inquiry".to_string()),
             message: "Interested in your product".to_string(),
             created_at,
+            preferred_contact: Some("phone".to_string()),
+            spam_score: 30,
+            read_at: Some(created_at),
+            answered_at: Some(created_at),
+            offer_id: Some(7),
         };
 
         let archived = message.clone().into_archived();
@@ -531,5 +2419,296 @@ mod tests {
         assert_eq!(archived.subject, message.subject);
         assert_eq!(archived.message, message.message);
         assert_eq!(archived.created_at, message.created_at);
+        assert_eq!(archived.preferred_contact, message.preferred_contact);
+        assert_eq!(archived.spam_score, message.spam_score);
+        assert_eq!(archived.read_at, message.read_at);
+        assert_eq!(archived.answered_at, message.answered_at);
+        assert_eq!(archived.offer_id, message.offer_id);
+    }
+
+    fn message_at(id: i64, email: &str, created_at: &str) -> Message {
+        Message {
+            id,
+            name: "Erin".to_string(),
+            email: email.to_string(),
+            phone: None,
+            subject: None,
+            message: format!("Message {id}"),
+            created_at: NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S")
+                .expect("Failed to parse datetime"),
+            preferred_contact: None,
+            spam_score: 0,
+            read_at: None,
+            answered_at: None,
+            offer_id: None,
+        }
+    }
+
+    #[test]
+    fn test_admin_role_permissions() {
+        let editor = AdminRole::parse("editor").unwrap();
+        assert!(editor.allows(Permission::ManageContent));
+        assert!(!editor.allows(Permission::ManageMessages));
+        assert!(!editor.allows(Permission::DeleteMessages));
+        assert!(!editor.allows(Permission::ManageUsers));
+
+        let moderator = AdminRole::parse("Moderator").unwrap();
+        assert!(!moderator.allows(Permission::ManageContent));
+        assert!(moderator.allows(Permission::ManageMessages));
+        assert!(moderator.allows(Permission::DeleteMessages));
+        assert!(!moderator.allows(Permission::ManageUsers));
+
+        let superadmin = AdminRole::parse("superadmin").unwrap();
+        for permission in [
+            Permission::ManageContent,
+            Permission::ManageMessages,
+            Permission::DeleteMessages,
+            Permission::ManageUsers,
+        ] {
+            assert!(superadmin.allows(permission));
+        }
+
+        assert_eq!(AdminRole::parse("owner"), None);
+    }
+
+    #[test]
+    fn test_group_messages_by_email() {
+        let groups = MessageGroup::group_by_email(vec![
+            message_at(1, "repeat@example.com", "2024-01-01 10:00:00"),
+            message_at(2, "once@example.com", "2024-01-02 10:00:00"),
+            message_at(3, "Repeat@Example.com ", "2024-01-03 10:00:00"),
+            message_at(4, "repeat@example.com", "2024-01-02 12:00:00"),
+            message_at(5, "other@example.com", "2024-01-01 09:00:00"),
+            message_at(6, "other@example.com", "2024-01-01 08:00:00"),
+        ]);
+
+        let summary: Vec<(&str, i64, i64)> = groups
+            .iter()
+            .map(|group| (group.email.as_str(), group.count, group.latest.id))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("repeat@example.com", 3, 3),
+                ("once@example.com", 1, 2),
+                ("other@example.com", 2, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_message_thread_is_chronological() {
+        let mut archived = archived_message(9, 2, "Archived");
+        archived.created_at =
+            NaiveDateTime::parse_from_str("2024-01-02 08:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let reply = MessageReply {
+            id: 1,
+            message_id: 2,
+            admin_user_id: None,
+            admin_username: "admin".to_string(),
+            subject: "Re: Archived".to_string(),
+            body: "Thanks".to_string(),
+            sent_at: archived.created_at,
+        };
+
+        let thread = MessageThread::assemble(
+            "carol@example.com".to_string(),
+            vec![
+                message_at(5, "carol@example.com", "2024-01-03 10:00:00"),
+                message_at(1, "carol@example.com", "2024-01-01 10:00:00"),
+            ],
+            vec![archived],
+            vec![reply],
+        );
+
+        let order: Vec<(i64, MessageState, usize)> = thread
+            .messages
+            .iter()
+            .map(|message| (message.message_id, message.state, message.replies.len()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, MessageState::Active, 0),
+                (2, MessageState::Archived, 1),
+                (5, MessageState::Active, 0),
+            ]
+        );
+        assert_eq!(thread.messages[1].archive_id, Some(9));
+    }
+
+    #[test]
+    fn test_group_messages_by_email_empty() {
+        assert!(MessageGroup::group_by_email(Vec::new()).is_empty());
+    }
+
+    fn archived_message(id: i64, original_id: i64, message: &str) -> ArchivedMessage {
+        let timestamp = NaiveDateTime::parse_from_str("2024-01-01 12:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("Failed to parse datetime");
+
+        ArchivedMessage {
+            id,
+            original_id,
+            name: "Carol".to_string(),
+            email: "carol@example.com".to_string(),
+            phone: None,
+            subject: None,
+            message: message.to_string(),
+            created_at: timestamp,
+            archived_at: timestamp,
+            preferred_contact: None,
+            spam_score: 0,
+            read_at: Some(timestamp),
+            answered_at: None,
+            offer_id: None,
+        }
+    }
+
+    #[test]
+    fn test_archived_message_into_restored_keeps_original_id() {
+        let restored = archived_message(7, 42, "Hello").into_restored(false);
+
+        assert_eq!(restored.id, Some(42));
+        assert_eq!(restored.message, "Hello");
+        assert!(restored.read_at.is_some());
+    }
+
+    #[test]
+    fn test_archived_message_into_restored_on_id_collision() {
+        let restored = archived_message(7, 42, "Hello").into_restored(true);
+
+        assert_eq!(restored.id, None);
+        assert_eq!(restored.name, "Carol");
+    }
+
+    #[test]
+    fn test_patch_request_keeps_null_apart_from_missing() {
+        let patch: AdminPatchBlogPostRequest =
+            serde_json::from_str(r#"{ "published": true, "excerpt": null }"#).unwrap();
+        assert_eq!(patch.published, Some(true));
+        assert_eq!(patch.excerpt, Some(None));
+        assert_eq!(patch.meta_title, None);
+        assert_eq!(patch.title, None);
+
+        let patch: AdminPatchOfferRequest =
+            serde_json::from_str(r#"{ "latitude": 49.1, "longitude": null }"#).unwrap();
+        assert_eq!(patch.latitude, Some(Some(49.1)));
+        assert_eq!(patch.longitude, Some(None));
+        assert!(serde_json::from_str::<AdminPatchOfferRequest>(r#"{ "titel": "x" }"#).is_err());
+    }
+}
+
+/// An endpoint notified of site events. `events` holds the subscribed event
+/// names, comma-separated.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    /// Key for the HMAC-SHA256 signature of each payload
+    pub secret: String,
+    pub events: String,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, event: &str) -> bool {
+        self.events.split(',').any(|name| name == event)
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = webhooks)]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub events: String,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AdminWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+    /// Defaults to active
+    #[serde(default)]
+    pub is_active: Option<bool>,
+}
+
+/// A webhook as listed to admins, without its secret
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookDto {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<Webhook> for WebhookDto {
+    fn from(webhook: Webhook) -> Self {
+        WebhookDto {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook
+                .events
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+            is_active: webhook.is_active,
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+        }
     }
 }
+
+/// A newly created webhook. The signing `secret` is only ever returned here.
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookCreatedResponse {
+    pub secret: String,
+    pub webhook: WebhookDto,
+}
+
+/// One queued or attempted webhook call
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[serde(crate = "rocket::serde")]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub payload: String,
+    /// `pending`, `delivered` or `failed`
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub next_attempt_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub webhook_id: i64,
+    pub event: String,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaginatedWebhookDeliveries {
+    pub data: Vec<WebhookDelivery>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+}
@@ -0,0 +1,226 @@
+// Chat notifications posted to a Slack or Discord incoming webhook
+
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+use crate::models::{Message, OfferDto};
+
+/// Longest message excerpt included in a notification
+const EXCERPT_LEN: usize = 300;
+/// Discord rejects `content` longer than this
+const DISCORD_MAX_CONTENT_LEN: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChatProvider {
+    Slack,
+    Discord,
+}
+
+impl ChatProvider {
+    /// Parse `CHAT_WEBHOOK_PROVIDER`, detecting the provider from the webhook
+    /// URL when it is empty
+    fn parse(value: &str, url: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "slack" => ChatProvider::Slack,
+            "discord" => ChatProvider::Discord,
+            other => {
+                if !other.is_empty() {
+                    warn!(
+                        "Unknown chat webhook provider '{}', detecting it from the URL",
+                        other
+                    );
+                }
+                let url = url.to_ascii_lowercase();
+                if url.contains("discord.com/") || url.contains("discordapp.com/") {
+                    ChatProvider::Discord
+                } else {
+                    ChatProvider::Slack
+                }
+            }
+        }
+    }
+
+    /// Escape text so it cannot trigger mentions or formatting
+    fn escape(self, text: &str) -> String {
+        match self {
+            ChatProvider::Slack => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+            ChatProvider::Discord => text
+                .chars()
+                .flat_map(|c| {
+                    let escaped = matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '@');
+                    escaped.then_some('\\').into_iter().chain([c])
+                })
+                .collect(),
+        }
+    }
+
+    fn payload(self, text: &str) -> serde_json::Value {
+        match self {
+            ChatProvider::Slack => json!({ "text": text }),
+            ChatProvider::Discord => json!({
+                "content": text.chars().take(DISCORD_MAX_CONTENT_LEN).collect::<String>(),
+                "allowed_mentions": { "parse": [] },
+            }),
+        }
+    }
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > EXCERPT_LEN {
+        let cut: String = text.chars().take(EXCERPT_LEN).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        text
+    }
+}
+
+/// Posts a short summary of site events to the configured chat webhook.
+/// Each event type can be switched off, and delivery never blocks the
+/// request that triggered it.
+#[derive(Clone)]
+pub struct ChatNotifier {
+    target: Option<(ChatProvider, String)>,
+    message_created: bool,
+    message_archived: bool,
+    offer_created: bool,
+    http: reqwest::Client,
+}
+
+impl ChatNotifier {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let target = config
+            .chat_webhook_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                (
+                    ChatProvider::parse(&config.chat_webhook_provider, url),
+                    url.to_string(),
+                )
+            });
+
+        ChatNotifier {
+            target,
+            message_created: config.chat_notify_message_created,
+            message_archived: config.chat_notify_message_archived,
+            offer_created: config.chat_notify_offer_created,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn message_created(&self, message: &Message) {
+        if !self.message_created {
+            return;
+        }
+        self.post(|provider| {
+            let subject = message
+                .subject
+                .as_deref()
+                .filter(|subject| !subject.trim().is_empty())
+                .unwrap_or("(no subject)");
+            format!(
+                "New contact message #{} from {} ({})\nSubject: {}\nSpam score: {}\n{}",
+                message.id,
+                provider.escape(&message.name),
+                provider.escape(&message.email),
+                provider.escape(subject),
+                message.spam_score,
+                provider.escape(&excerpt(&message.message)),
+            )
+        });
+    }
+
+    pub fn message_archived(&self, message: &Message) {
+        if !self.message_archived {
+            return;
+        }
+        self.post(|provider| {
+            format!(
+                "Contact message #{} from {} was archived",
+                message.id,
+                provider.escape(&message.email),
+            )
+        });
+    }
+
+    pub fn offer_created(&self, offer: &OfferDto) {
+        if !self.offer_created {
+            return;
+        }
+        self.post(|provider| {
+            format!(
+                "New offer #{} created: {} ({})",
+                offer.id,
+                provider.escape(&offer.title),
+                provider.escape(&offer.slug),
+            )
+        });
+    }
+
+    /// Send the text built for the configured provider in the background
+    fn post(&self, text: impl FnOnce(ChatProvider) -> String) {
+        let Some((provider, url)) = self.target.clone() else {
+            return;
+        };
+
+        let payload = provider.payload(&text(provider));
+        let http = self.http.clone();
+        rocket::tokio::spawn(async move {
+            match http
+                .post(&url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                Ok(_) => info!("Posted {:?} chat notification", provider),
+                Err(e) => warn!("Failed to post {:?} chat notification: {}", provider, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_detection() {
+        let discord = "https://discord.com/api/webhooks/1/abc";
+        assert_eq!(ChatProvider::parse("", discord), ChatProvider::Discord);
+        assert_eq!(
+            ChatProvider::parse("", "https://hooks.slack.com/services/T/B/x"),
+            ChatProvider::Slack
+        );
+        assert_eq!(ChatProvider::parse("Slack", discord), ChatProvider::Slack);
+    }
+
+    #[test]
+    fn test_escape_blocks_mentions() {
+        assert_eq!(
+            ChatProvider::Slack.escape("<!channel> & co"),
+            "&lt;!channel&gt; &amp; co"
+        );
+        assert_eq!(
+            ChatProvider::Discord.escape("@everyone *hi*"),
+            "\\@everyone \\*hi\\*"
+        );
+        assert_eq!(
+            ChatProvider::Discord.payload("hi")["allowed_mentions"]["parse"],
+            json!([])
+        );
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("Hello\n\n  there"), "Hello there");
+        let long = "a".repeat(EXCERPT_LEN + 10);
+        assert_eq!(excerpt(&long).chars().count(), EXCERPT_LEN + 1);
+    }
+}
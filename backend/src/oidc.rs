@@ -0,0 +1,259 @@
+// OpenID Connect client for the optional admin single sign-on login
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+
+/// Endpoints advertised by the identity provider's discovery document
+#[derive(Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    /// A single client id or a list of them
+    aud: serde_json::Value,
+    exp: i64,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+/// Authorization code flow against the configured identity provider.
+/// Disabled unless `OIDC_ISSUER_URL` and `OIDC_CLIENT_ID` are set.
+pub struct OidcClient {
+    http: reqwest::Client,
+    issuer_url: Option<String>,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    allowed_emails: Vec<String>,
+}
+
+impl OidcClient {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let redirect_url = if config.oidc_redirect_url.trim().is_empty() {
+            format!(
+                "{}/admin/oidc/callback",
+                config.public_base_url.trim_end_matches('/')
+            )
+        } else {
+            config.oidc_redirect_url.trim().to_string()
+        };
+
+        OidcClient {
+            http: reqwest::Client::new(),
+            issuer_url: config
+                .oidc_issuer_url
+                .as_deref()
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty() && !config.oidc_client_id.trim().is_empty()),
+            client_id: config.oidc_client_id.trim().to_string(),
+            client_secret: config.oidc_client_secret.clone(),
+            redirect_url,
+            allowed_emails: config
+                .oidc_allowed_emails
+                .split(',')
+                .map(|email| email.trim().to_lowercase())
+                .filter(|email| !email.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.issuer_url.is_some()
+    }
+
+    pub async fn discover(&self) -> AppResult<ProviderMetadata> {
+        let Some(issuer_url) = &self.issuer_url else {
+            return Err(AppError::NotFound);
+        };
+
+        self.http
+            .get(format!("{issuer_url}/.well-known/openid-configuration"))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Oidc(format!("discovery failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| AppError::Oidc(format!("invalid discovery document: {e}")))
+    }
+
+    /// Where to send the browser to sign in at the provider
+    pub fn authorization_url(
+        &self,
+        provider: &ProviderMetadata,
+        state: &str,
+        nonce: &str,
+    ) -> AppResult<String> {
+        Url::parse_with_params(
+            &provider.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("scope", "openid email"),
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("state", state),
+                ("nonce", nonce),
+            ],
+        )
+        .map(String::from)
+        .map_err(|e| AppError::Oidc(format!("invalid authorization endpoint: {e}")))
+    }
+
+    /// Redeem an authorization code and return the verified email address of
+    /// the signed-in user. The ID token comes straight from the token
+    /// endpoint over TLS, so its claims are checked without verifying the
+    /// signature (OpenID Connect Core 3.1.3.7).
+    pub async fn exchange_code(
+        &self,
+        provider: &ProviderMetadata,
+        code: &str,
+        nonce: &str,
+    ) -> AppResult<String> {
+        let tokens: TokenResponse = self
+            .http
+            .post(&provider.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Oidc(format!("token exchange failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| AppError::Oidc(format!("invalid token response: {e}")))?;
+
+        let claims = decode_claims(&tokens.id_token).ok_or(AppError::Unauthorized)?;
+        validate_claims(
+            &claims,
+            &provider.issuer,
+            &self.client_id,
+            nonce,
+            chrono::Utc::now().timestamp(),
+        )?;
+
+        let email = claims.email.ok_or(AppError::Unauthorized)?;
+        if !email_allowed(&self.allowed_emails, &email) {
+            return Err(AppError::Forbidden);
+        }
+        Ok(email)
+    }
+}
+
+fn decode_claims(id_token: &str) -> Option<IdTokenClaims> {
+    let payload = id_token.split('.').nth(1)?;
+    let json = BASE64_URL.decode(payload).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+fn validate_claims(
+    claims: &IdTokenClaims,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+    now: i64,
+) -> AppResult<()> {
+    let audience_ok = match &claims.aud {
+        serde_json::Value::String(aud) => aud == client_id,
+        serde_json::Value::Array(auds) => auds.iter().any(|aud| aud == client_id),
+        _ => false,
+    };
+
+    if claims.iss.trim_end_matches('/') != issuer.trim_end_matches('/')
+        || !audience_ok
+        || claims.exp <= now
+        || claims.nonce.as_deref() != Some(nonce)
+        || claims.email_verified != Some(true)
+    {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Entries are full addresses, or `@domain` to allow a whole domain
+fn email_allowed(allowed_emails: &[String], email: &str) -> bool {
+    let email = email.trim().to_lowercase();
+    allowed_emails.iter().any(|allowed| {
+        if allowed.starts_with('@') {
+            email.ends_with(allowed.as_str())
+        } else {
+            *allowed == email
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(json: serde_json::Value) -> IdTokenClaims {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_decode_claims_from_id_token() {
+        let payload = BASE64_URL.encode(r#"{"iss":"https://idp","aud":"site","exp":10}"#);
+        let decoded = decode_claims(&format!("e30.{payload}.sig")).unwrap();
+
+        assert_eq!(decoded.iss, "https://idp");
+        assert_eq!(decoded.exp, 10);
+        assert!(decode_claims("not-a-jwt").is_none());
+    }
+
+    #[test]
+    fn test_validate_claims() {
+        let valid = claims(serde_json::json!({
+            "iss": "https://idp/", "aud": ["other", "site"], "exp": 200,
+            "nonce": "n1", "email": "a@example.com", "email_verified": true
+        }));
+        assert!(validate_claims(&valid, "https://idp", "site", "n1", 100).is_ok());
+        assert!(validate_claims(&valid, "https://idp", "site", "n2", 100).is_err());
+        assert!(validate_claims(&valid, "https://idp", "site", "n1", 200).is_err());
+        assert!(validate_claims(&valid, "https://evil", "site", "n1", 100).is_err());
+        assert!(validate_claims(&valid, "https://idp", "nope", "n1", 100).is_err());
+
+        let unverified = claims(serde_json::json!({
+            "iss": "https://idp", "aud": "site", "exp": 200,
+            "nonce": "n1", "email_verified": false
+        }));
+        assert!(validate_claims(&unverified, "https://idp", "site", "n1", 100).is_err());
+
+        // Providers that leave the claim out are not trusted either
+        let unknown = claims(serde_json::json!({
+            "iss": "https://idp", "aud": "site", "exp": 200,
+            "nonce": "n1", "email": "a@example.com"
+        }));
+        assert!(validate_claims(&unknown, "https://idp", "site", "n1", 100).is_err());
+    }
+
+    #[test]
+    fn test_email_allowed() {
+        let allowed = vec!["boss@example.com".to_string(), "@kerdik.sk".to_string()];
+
+        assert!(email_allowed(&allowed, "Boss@Example.com"));
+        assert!(email_allowed(&allowed, "anyone@kerdik.sk"));
+        assert!(!email_allowed(&allowed, "other@example.com"));
+        assert!(!email_allowed(&allowed, "x@notkerdik.sk"));
+        assert!(!email_allowed(&[], "boss@example.com"));
+    }
+}
@@ -0,0 +1,68 @@
+// Admin password hashing. New hashes use Argon2id; bcrypt hashes from older
+// accounts are still accepted and upgraded on the next successful login.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2};
+
+use crate::error::{AppError, AppResult};
+
+pub fn hash_password(password: &str) -> AppResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(AppError::Argon2)
+}
+
+/// Check a password against a stored hash, picking the algorithm from the
+/// hash format. Malformed hashes never match.
+pub fn verify_password(password: &str, stored_hash: &str) -> bool {
+    if stored_hash.starts_with("$argon2") {
+        PasswordHash::new(stored_hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    } else {
+        bcrypt::verify(password, stored_hash).unwrap_or(false)
+    }
+}
+
+/// Whether a stored hash should be replaced by a fresh Argon2id hash
+pub fn needs_rehash(stored_hash: &str) -> bool {
+    PasswordHash::new(stored_hash)
+        .map(|hash| hash.algorithm != Algorithm::Argon2id.ident())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argon2id_hash_roundtrip() {
+        let hash = hash_password("s3cret-pass").unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("s3cret-pass", &hash));
+        assert!(!verify_password("wrong-pass", &hash));
+        assert!(!needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_bcrypt_hash_still_verifies_and_needs_rehash() {
+        let hash = bcrypt::hash("s3cret-pass", 4).unwrap();
+
+        assert!(verify_password("s3cret-pass", &hash));
+        assert!(!verify_password("wrong-pass", &hash));
+        assert!(needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_malformed_hash_never_matches() {
+        assert!(!verify_password("anything", ""));
+        assert!(!verify_password("anything", "$argon2id$garbage"));
+        assert!(needs_rehash("not-a-hash"));
+    }
+}
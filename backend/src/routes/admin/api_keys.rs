@@ -0,0 +1,317 @@
+// API keys for headless admin access, e.g. from scripts and CI jobs
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    self, AdminCreateApiKeyRequest, AdminUpdateApiKeyRequest, ApiKey, ApiKeyCreatedResponse,
+    ApiKeyDto, NewApiKey, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::sessions::hash_token;
+use crate::schema::{admin_users, api_keys};
+
+/// Marks keys issued by this site, so leaked keys are easy to recognise
+const KEY_PREFIX: &str = "kdk_";
+/// Characters of the key kept in plain text to tell keys apart
+const VISIBLE_KEY_LEN: usize = 12;
+/// Longest lifetime a key can be created with, about ten years
+const MAX_EXPIRES_IN_DAYS: u32 = 3650;
+
+/// Key taken from an `Authorization: Bearer` header
+pub struct BearerKey(String);
+
+fn parse_bearer(header: &str) -> Option<String> {
+    let (scheme, key) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+
+    let key = key.trim();
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BearerKey {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req
+            .headers()
+            .get_one("Authorization")
+            .and_then(parse_bearer)
+        {
+            Some(key) => Outcome::Success(BearerKey(key)),
+            None => Outcome::Forward(Status::Unauthorized),
+        }
+    }
+}
+
+/// Expiry of a key created now that lasts `days`, from 1 to
+/// `MAX_EXPIRES_IN_DAYS`; no expiry when `days` is not given
+fn key_expiry(days: Option<u32>) -> AppResult<Option<chrono::NaiveDateTime>> {
+    let Some(days) = days else {
+        return Ok(None);
+    };
+    if !(1..=MAX_EXPIRES_IN_DAYS).contains(&days) {
+        return Err(AppError::InvalidInput(format!(
+            "expires_in_days must be between 1 and {MAX_EXPIRES_IN_DAYS}."
+        )));
+    }
+    Ok(Some(
+        (chrono::Utc::now() + chrono::Duration::days(i64::from(days))).naive_utc(),
+    ))
+}
+
+fn generate_key() -> String {
+    format!("{KEY_PREFIX}{}", Uuid::new_v4().simple())
+}
+
+/// Validate requested scope names, keeping them in a stable order
+fn parse_scopes(scopes: &[String]) -> AppResult<String> {
+    let mut permissions = Vec::new();
+    for scope in scopes {
+        let permission = Permission::parse_scope(scope).ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Unknown scope '{}'. Use content, messages, delete_messages, users or audit.",
+                scope.trim()
+            ))
+        })?;
+        permissions.push(permission);
+    }
+
+    let scopes: Vec<&str> = Permission::ALL
+        .iter()
+        .filter(|permission| permissions.contains(permission))
+        .map(Permission::scope)
+        .collect();
+    if scopes.is_empty() {
+        return Err(AppError::InvalidInput(
+            "At least one scope is required.".to_string(),
+        ));
+    }
+    Ok(scopes.join(","))
+}
+
+fn to_api_key_dto(key: ApiKey) -> ApiKeyDto {
+    ApiKeyDto {
+        scopes: key
+            .permissions()
+            .iter()
+            .map(|permission| permission.scope().to_string())
+            .collect(),
+        id: key.id,
+        user_id: key.user_id,
+        name: key.name,
+        key_prefix: key.key_prefix,
+        expires_at: key.expires_at,
+        last_used_at: key.last_used_at,
+        created_at: key.created_at,
+    }
+}
+
+/// Resolve the owner of an API key together with the scopes it grants.
+/// Unknown and expired keys yield `None`.
+pub async fn get_api_key_user(
+    db: &mut Connection<MessagesDB>,
+    key: &BearerKey,
+) -> AppResult<Option<(models::AdminUser, Vec<Permission>)>> {
+    let now = chrono::Utc::now().naive_utc();
+    let api_key = api_keys::table
+        .filter(api_keys::key_hash.eq(hash_token(&key.0)))
+        .select(ApiKey::as_select())
+        .first(db)
+        .await
+        .optional()?;
+
+    let Some(api_key) = api_key.filter(|api_key| api_key.expires_at.is_none_or(|at| at > now))
+    else {
+        warn!("Rejected unknown or expired API key");
+        return Ok(None);
+    };
+
+    let user = admin_users::table
+        .find(api_key.user_id)
+        .select(models::AdminUser::as_select())
+        .first(db)
+        .await
+        .optional()?;
+
+    if let Err(e) = diesel::update(api_keys::table.find(api_key.id))
+        .set(api_keys::last_used_at.eq(now))
+        .execute(db)
+        .await
+    {
+        error!("Failed to record use of API key {}: {}", api_key.id, e);
+    }
+
+    Ok(user.map(|user| (user, api_key.permissions())))
+}
+
+#[get("/admin/api/keys")]
+pub async fn list_api_keys(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<ApiKeyDto>>> {
+    admin.require(Permission::ManageUsers)?;
+
+    let keys = api_keys::table
+        .order(api_keys::created_at.desc())
+        .select(ApiKey::as_select())
+        .load(&mut db)
+        .await?;
+
+    Ok(Json(keys.into_iter().map(to_api_key_dto).collect()))
+}
+
+/// Create a key acting as the signed-in admin. The key is returned once and
+/// only its hash is stored.
+#[post("/admin/api/keys", format = "json", data = "<request>")]
+pub async fn create_api_key(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminCreateApiKeyRequest>,
+) -> AppResult<Json<ApiKeyCreatedResponse>> {
+    admin.require(Permission::ManageUsers)?;
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Name is required.".to_string()));
+    }
+
+    let expires_at = key_expiry(request.expires_in_days)?;
+    let key = generate_key();
+    let key_hash = hash_token(&key);
+    let new_key = NewApiKey {
+        user_id: admin.id,
+        name: name.to_string(),
+        key_prefix: key[..VISIBLE_KEY_LEN].to_string(),
+        key_hash: key_hash.clone(),
+        scopes: parse_scopes(&request.scopes)?,
+        expires_at,
+    };
+
+    diesel::insert_into(api_keys::table)
+        .values(&new_key)
+        .execute(&mut db)
+        .await?;
+
+    let created = api_keys::table
+        .filter(api_keys::key_hash.eq(&key_hash))
+        .select(ApiKey::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "api_key.create", "api_key", created.id).await;
+    info!("Admin '{}' created API key {}", admin.username, created.id);
+    Ok(Json(ApiKeyCreatedResponse {
+        key,
+        api_key: to_api_key_dto(created),
+    }))
+}
+
+#[put("/admin/api/keys/<id>", format = "json", data = "<request>")]
+pub async fn update_api_key(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminUpdateApiKeyRequest>,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageUsers)?;
+
+    let scopes = parse_scopes(&request.scopes)?;
+    let _existing: ApiKey = api_keys::table
+        .find(id)
+        .select(ApiKey::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    diesel::update(api_keys::table.find(id))
+        .set(api_keys::scopes.eq(scopes))
+        .execute(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "api_key.update", "api_key", id).await;
+    Ok(Status::Ok)
+}
+
+#[delete("/admin/api/keys/<id>")]
+pub async fn revoke_api_key(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageUsers)?;
+
+    let deleted = diesel::delete(api_keys::table.find(id))
+        .execute(&mut db)
+        .await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "api_key.revoke", "api_key", id).await;
+    info!("Admin '{}' revoked API key {}", admin.username, id);
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bearer() {
+        assert_eq!(parse_bearer("Bearer kdk_abc").as_deref(), Some("kdk_abc"));
+        assert_eq!(parse_bearer("bearer  kdk_abc ").as_deref(), Some("kdk_abc"));
+        assert_eq!(parse_bearer("Basic YWRtaW46c2VjcmV0"), None);
+        assert_eq!(parse_bearer("Bearer "), None);
+    }
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(
+            parse_scopes(&["audit".to_string(), " Content ".to_string()]).unwrap(),
+            "content,audit"
+        );
+        assert!(matches!(parse_scopes(&[]), Err(AppError::InvalidInput(_))));
+        assert!(matches!(
+            parse_scopes(&["root".to_string()]),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_expiry() {
+        assert_eq!(key_expiry(None).unwrap(), None);
+        assert!(key_expiry(Some(1)).unwrap().is_some());
+        assert!(key_expiry(Some(MAX_EXPIRES_IN_DAYS)).unwrap().is_some());
+        assert!(matches!(
+            key_expiry(Some(0)),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            key_expiry(Some(u32::MAX)),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_generated_keys_are_unique_and_prefixed() {
+        let first = generate_key();
+        let second = generate_key();
+
+        assert!(first.starts_with(KEY_PREFIX));
+        assert!(first.len() > VISIBLE_KEY_LEN);
+        assert_ne!(first, second);
+    }
+}
@@ -1,19 +1,23 @@
 // Archived message management endpoints
 
-use rocket::State;
-use rocket::http::{CookieJar, Status};
+use diesel::mysql::Mysql;
+use diesel::sql_types::{Double, Text};
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::serde::{Deserialize, Serialize};
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::db::MessagesDB;
+use crate::db::{MessagesDB, last_insert_id};
 use crate::error::{AppError, AppResult};
-use crate::models::ArchivedMessage;
-use crate::routes::admin::auth::is_admin_authenticated;
-use crate::schema::messages_archive;
+use crate::models::{ArchivedMessage, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{
+    message_attachments, message_labels, message_notes, message_replies, messages, messages_archive,
+};
+use crate::utils::{DateRange, search_query};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -24,24 +28,175 @@ pub struct PaginatedArchivedMessages {
     pub limit: i64,
 }
 
-#[get("/admin/api/archived/messages?<page>&<limit>")]
+/// Move an archived record back into `messages` and drop the archive row.
+/// Falls back to a newly assigned id if the original one is already in use;
+/// the attachments, replies, notes and labels under the original id then
+/// stay with the message holding it, as they cannot be told apart. Returns
+/// the id of the restored message.
+pub async fn restore_archived(
+    db: &mut Connection<MessagesDB>,
+    archived: ArchivedMessage,
+) -> AppResult<i64> {
+    let archive_id = archived.id;
+    let original_id = archived.original_id;
+
+    db.transaction(|mut conn| {
+        Box::pin(async move {
+            let original_id_taken: i64 = messages::table
+                .find(original_id)
+                .count()
+                .get_result(&mut conn)
+                .await?;
+
+            let message = archived.into_restored(original_id_taken > 0);
+            diesel::insert_into(messages::table)
+                .values(&message)
+                .execute(&mut conn)
+                .await?;
+
+            let message_id = match message.id {
+                Some(id) => id,
+                None => {
+                    let id: u64 = diesel::select(last_insert_id())
+                        .get_result(&mut conn)
+                        .await?;
+                    warn!(
+                        "Message id {} is in use, archived message {} restored as {} without its attachments, replies, notes and labels",
+                        original_id, archive_id, id
+                    );
+                    id as i64
+                }
+            };
+
+            diesel::delete(messages_archive::table.find(archive_id))
+                .execute(&mut conn)
+                .await?;
+
+            Ok::<_, diesel::result::Error>(message_id)
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!("Error restoring message in transaction: {}", e);
+        AppError::from(e)
+    })
+}
+
+/// Whether the attachments, replies, notes and labels under an archived
+/// record's original id are its own, given how many live messages and
+/// archived records hold that id. Once the id has been reused they cannot
+/// be told apart and must be left alone.
+pub(crate) fn owns_side_rows(live_holders: i64, archived_holders: i64) -> bool {
+    live_holders == 0 && archived_holders == 1
+}
+
+/// Order of the archived message list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveSort {
+    ArchivedDesc,
+    ArchivedAsc,
+    CreatedDesc,
+    CreatedAsc,
+    /// Best search matches first; only with a search query
+    Relevance,
+}
+
+impl ArchiveSort {
+    /// Parse the `sort` parameter. Defaults to relevance when searching and
+    /// to the most recently archived first otherwise.
+    fn parse(value: Option<&str>, searching: bool) -> AppResult<Self> {
+        let sort = match value.map(str::trim).filter(|value| !value.is_empty()) {
+            None if searching => ArchiveSort::Relevance,
+            None | Some("archived_desc") => ArchiveSort::ArchivedDesc,
+            Some("archived_asc") => ArchiveSort::ArchivedAsc,
+            Some("created_desc") => ArchiveSort::CreatedDesc,
+            Some("created_asc") => ArchiveSort::CreatedAsc,
+            Some("relevance") => ArchiveSort::Relevance,
+            Some(_) => {
+                return Err(AppError::InvalidInput(
+                    "Sort must be one of archived_desc, archived_asc, created_desc, created_asc or relevance.".to_string(),
+                ));
+            }
+        };
+        if sort == ArchiveSort::Relevance && !searching {
+            return Err(AppError::InvalidInput(
+                "Sorting by relevance needs a search query.".to_string(),
+            ));
+        }
+        Ok(sort)
+    }
+}
+
+/// `MATCH ... AGAINST` over the FULLTEXT index of the archive, like the one
+/// used to search active messages
+fn archive_search_relevance(
+    query: &str,
+) -> Box<dyn BoxableExpression<messages_archive::table, Mysql, SqlType = Double>> {
+    Box::new(
+        diesel::dsl::sql::<Double>("MATCH (name, email, subject, message) AGAINST (")
+            .bind::<Text, _>(query.to_string())
+            .sql(" IN NATURAL LANGUAGE MODE)"),
+    )
+}
+
+/// Archived messages matching the optional search query and date ranges
+fn filtered_archive<'a>(
+    query: Option<&str>,
+    created: DateRange,
+    archived: DateRange,
+) -> messages_archive::BoxedQuery<'a, Mysql> {
+    let mut filtered = messages_archive::table.into_boxed();
+    if let Some(query) = query {
+        filtered = filtered.filter(archive_search_relevance(query).gt(0.0));
+    }
+    if let Some(from) = created.from {
+        filtered = filtered.filter(messages_archive::created_at.ge(from));
+    }
+    if let Some(until) = created.until {
+        filtered = filtered.filter(messages_archive::created_at.lt(until));
+    }
+    if let Some(from) = archived.from {
+        filtered = filtered.filter(messages_archive::archived_at.ge(from));
+    }
+    if let Some(until) = archived.until {
+        filtered = filtered.filter(messages_archive::archived_at.lt(until));
+    }
+    filtered
+}
+
+/// Paginated archived messages, optionally searched, limited to `created_at`
+/// and `archived_at` date ranges and sorted
+#[get(
+    "/admin/api/archived/messages?<page>&<limit>&<q>&<created_from>&<created_to>&<archived_from>&<archived_to>&<sort>"
+)]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_archived_messages(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
     page: Option<i64>,
     limit: Option<i64>,
+    q: Option<&str>,
+    created_from: Option<&str>,
+    created_to: Option<&str>,
+    archived_from: Option<&str>,
+    archived_to: Option<&str>,
+    sort: Option<&str>,
 ) -> AppResult<Json<PaginatedArchivedMessages>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageMessages)?;
 
     let page = page.unwrap_or(1);
     let limit = limit.unwrap_or(10);
     let offset = (page - 1) * limit;
 
-    let total_count: i64 = messages_archive::table
+    let query = match q.map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => Some(search_query(q)?),
+        None => None,
+    };
+    let created = DateRange::parse(created_from, created_to)?;
+    let archived = DateRange::parse(archived_from, archived_to)?;
+    let sort = ArchiveSort::parse(sort, query.is_some())?;
+
+    let total_count: i64 = filtered_archive(query, created, archived)
         .count()
         .get_result(&mut db)
         .await
@@ -50,8 +205,19 @@ pub async fn get_archived_messages(
             AppError::from(e)
         })?;
 
-    let results = messages_archive::table
-        .order(messages_archive::archived_at.desc())
+    let filtered = filtered_archive(query, created, archived);
+    let ordered = match (sort, query) {
+        (ArchiveSort::Relevance, Some(query)) => filtered.order((
+            archive_search_relevance(query).desc(),
+            messages_archive::archived_at.desc(),
+        )),
+        (ArchiveSort::ArchivedAsc, _) => filtered.order(messages_archive::archived_at.asc()),
+        (ArchiveSort::CreatedDesc, _) => filtered.order(messages_archive::created_at.desc()),
+        (ArchiveSort::CreatedAsc, _) => filtered.order(messages_archive::created_at.asc()),
+        _ => filtered.order(messages_archive::archived_at.desc()),
+    };
+    let results = ordered
+        .then_order_by(messages_archive::id.desc())
         .limit(limit)
         .offset(offset)
         .select(ArchivedMessage::as_select())
@@ -77,26 +243,136 @@ pub async fn get_archived_messages(
     }))
 }
 
-#[delete("/admin/api/archived/messages/<id>")]
-pub async fn permanently_delete_archived_message(
+#[post("/admin/api/archived/messages/<archive_id>/restore")]
+pub async fn restore_archived_message(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
-    id: i64,
+    admin: AdminUser,
+    archive_id: i64,
 ) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageMessages)?;
 
-    diesel::delete(messages_archive::table.find(id))
-        .execute(&mut db)
+    let archived: ArchivedMessage = messages_archive::table
+        .find(archive_id)
+        .select(ArchivedMessage::as_select())
+        .first(&mut db)
         .await
         .map_err(|e| {
-            error!("Error permanently deleting archived message {}: {}", id, e);
-            AppError::from(e)
+            error!("Error retrieving archived message {}: {}", archive_id, e);
+            AppError::NotFound
         })?;
 
+    restore_archived(&mut db, archived).await?;
+    record_audit(
+        &mut db,
+        &admin,
+        "message.restore",
+        "archived_message",
+        archive_id,
+    )
+    .await;
+
+    info!("Archived message {} restored successfully", archive_id);
+    Ok(Status::Ok)
+}
+
+#[delete("/admin/api/archived/messages/<id>")]
+pub async fn permanently_delete_archived_message(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::DeleteMessages)?;
+
+    db.transaction(|mut conn| {
+        Box::pin(async move {
+            let original_id: Option<i64> = messages_archive::table
+                .find(id)
+                .select(messages_archive::original_id)
+                .first(&mut conn)
+                .await
+                .optional()?;
+            let Some(original_id) = original_id else {
+                return Ok(());
+            };
+            let live_holders: i64 = messages::table
+                .find(original_id)
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            let archived_holders: i64 = messages_archive::table
+                .filter(messages_archive::original_id.eq(original_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if owns_side_rows(live_holders, archived_holders) {
+                diesel::delete(
+                    message_attachments::table
+                        .filter(message_attachments::message_id.eq(original_id)),
+                )
+                .execute(&mut conn)
+                .await?;
+                diesel::delete(
+                    message_replies::table.filter(message_replies::message_id.eq(original_id)),
+                )
+                .execute(&mut conn)
+                .await?;
+                diesel::delete(
+                    message_notes::table.filter(message_notes::message_id.eq(original_id)),
+                )
+                .execute(&mut conn)
+                .await?;
+                diesel::delete(
+                    message_labels::table.filter(message_labels::message_id.eq(original_id)),
+                )
+                .execute(&mut conn)
+                .await?;
+            }
+
+            diesel::delete(messages_archive::table.find(id))
+                .execute(&mut conn)
+                .await?;
+            Ok::<_, diesel::result::Error>(())
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!("Error permanently deleting archived message {}: {}", id, e);
+        AppError::from(e)
+    })?;
+    record_audit(&mut db, &admin, "message.purge", "archived_message", id).await;
+
     info!("Archived message {} permanently deleted", id);
     Ok(Status::Ok)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_rows_left_alone_on_id_collision() {
+        assert!(owns_side_rows(0, 1));
+        // A live message holds the original id
+        assert!(!owns_side_rows(1, 1));
+        // Another archived record holds the original id
+        assert!(!owns_side_rows(0, 2));
+    }
+
+    #[test]
+    fn test_archive_sort() {
+        assert_eq!(
+            ArchiveSort::parse(None, false).unwrap(),
+            ArchiveSort::ArchivedDesc
+        );
+        assert_eq!(
+            ArchiveSort::parse(None, true).unwrap(),
+            ArchiveSort::Relevance
+        );
+        assert_eq!(
+            ArchiveSort::parse(Some("created_asc"), true).unwrap(),
+            ArchiveSort::CreatedAsc
+        );
+        assert!(ArchiveSort::parse(Some("relevance"), false).is_err());
+        assert!(ArchiveSort::parse(Some("name"), false).is_err());
+    }
+}
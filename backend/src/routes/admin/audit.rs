@@ -0,0 +1,90 @@
+// Audit trail of admin mutations
+
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::db::MessagesDB;
+use crate::error::AppResult;
+use crate::models::{AuditLogEntry, NewAuditLogEntry, PaginatedAuditLog, Permission};
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::audit_log;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+/// Record that `admin` performed `action` (e.g. `offer.update`) on an
+/// entity. A failed write is logged but never fails the action itself.
+pub async fn record_audit(
+    db: &mut Connection<MessagesDB>,
+    admin: &AdminUser,
+    action: &str,
+    entity_type: &str,
+    entity_id: impl ToString,
+) {
+    let entry = NewAuditLogEntry {
+        admin_user_id: Some(admin.id),
+        admin_username: admin.username.clone(),
+        action: action.to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id: entity_id.to_string(),
+        ip_address: admin.client_ip().map(|ip| ip.to_string()),
+    };
+
+    if let Err(e) = diesel::insert_into(audit_log::table)
+        .values(&entry)
+        .execute(db)
+        .await
+    {
+        error!(
+            "Failed to write audit log entry {} by '{}': {}",
+            entry.action, entry.admin_username, e
+        );
+    }
+}
+
+fn page_bounds(page: Option<i64>, limit: Option<i64>) -> (i64, i64) {
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    (page, limit)
+}
+
+#[get("/admin/api/audit?<page>&<limit>")]
+pub async fn get_audit_log(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    page: Option<i64>,
+    limit: Option<i64>,
+) -> AppResult<Json<PaginatedAuditLog>> {
+    admin.require(Permission::ViewAuditLog)?;
+
+    let (page, limit) = page_bounds(page, limit);
+    let total: i64 = audit_log::table.count().get_result(&mut db).await?;
+    let data = audit_log::table
+        .order((audit_log::created_at.desc(), audit_log::id.desc()))
+        .limit(limit)
+        .offset((page - 1) * limit)
+        .select(AuditLogEntry::as_select())
+        .load(&mut db)
+        .await?;
+
+    Ok(Json(PaginatedAuditLog {
+        data,
+        total,
+        page,
+        limit,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_bounds() {
+        assert_eq!(page_bounds(None, None), (1, DEFAULT_PAGE_SIZE));
+        assert_eq!(page_bounds(Some(0), Some(0)), (1, 1));
+        assert_eq!(page_bounds(Some(3), Some(10_000)), (3, MAX_PAGE_SIZE));
+    }
+}
@@ -1,52 +1,111 @@
 // Admin authentication endpoints
 
-use bcrypt::verify;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use redis::AsyncCommands;
 use rocket::State;
-use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::http::{CookieJar, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::Redirect;
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
+use std::net::IpAddr;
+use std::ops::Deref;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::config::AppConfig;
 use crate::db::MessagesDB;
 use crate::error::{AppError, AppResult};
-use crate::models::{AdminLoginRequest, AdminStatusResponse, AdminUser};
+use crate::models::{
+    self, AdminLoginRequest, AdminSessionRefreshResponse, AdminStatusResponse, Permission,
+};
+use crate::oidc::OidcClient;
+use crate::password::{hash_password, needs_rehash, verify_password};
+use crate::routes::admin::api_keys::{BearerKey, get_api_key_user};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::login_limit::{
+    LoginAlerts, clear_login_failures, ensure_login_allowed, record_login_failure,
+};
+use crate::routes::admin::remember_me::{
+    forget_remember_me_token, issue_remember_me_token, resume_remembered_session,
+};
+use crate::routes::admin::sessions::{
+    UserAgent, clear_session_cookie, delete_session, session_token, start_admin_session,
+    touch_session,
+};
+use crate::routes::client_ip::ClientIp;
 use crate::schema::admin_users;
+use crate::utils::ip_in_list;
 
-const SESSION_COOKIE: &str = "admin_auth";
-const SESSION_PREFIX: &str = "admin_session:";
-const SESSION_TTL_SECS: u64 = 60 * 60 * 24;
+const OIDC_STATE_PREFIX: &str = "admin_oidc_state:";
+/// How long a started single sign-on may take before its state expires
+const OIDC_STATE_TTL_SECS: u64 = 10 * 60;
+const OIDC_FAILED_REDIRECT: &str = "/admin/login?error=oidc";
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct AdminSessionData {
-    user_id: i64,
-    ip_address: Option<String>,
+/// Credentials taken from an `Authorization: Basic` header. Only produced when
+/// the basic-auth fallback is enabled and the client address is trusted, so
+/// handlers take it as `Option<BasicAuth>`.
+pub struct BasicAuth {
+    username: String,
+    password: String,
 }
 
-fn session_key(token: &str) -> String {
-    format!("{SESSION_PREFIX}{token}")
+fn parse_basic_auth(header: &str) -> Option<BasicAuth> {
+    let (scheme, encoded) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+
+    let decoded = BASE64.decode(encoded.trim()).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    let username = username.trim();
+    if username.is_empty() {
+        return None;
+    }
+
+    Some(BasicAuth {
+        username: username.to_string(),
+        password: password.to_string(),
+    })
 }
 
-async fn store_session(
-    redis: &State<redis::Client>,
-    token: &str,
-    session: &AdminSessionData,
-) -> AppResult<()> {
-    let payload = serde_json::to_string(session)?;
-    let mut conn = redis.get_multiplexed_async_connection().await?;
-    let _: () = conn
-        .set_ex(session_key(token), payload, SESSION_TTL_SECS)
-        .await?;
-    Ok(())
+fn is_trusted_ip(trusted_ips: &str, ip: Option<IpAddr>) -> bool {
+    ip.is_some_and(|ip| ip_in_list(trusted_ips, ip))
 }
 
-async fn delete_session(redis: &State<redis::Client>, token: &str) -> AppResult<()> {
-    let mut conn = redis.get_multiplexed_async_connection().await?;
-    let _: usize = conn.del(session_key(token)).await?;
-    Ok(())
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BasicAuth {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(config) = req.rocket().state::<AppConfig>() else {
+            return Outcome::Forward(Status::Unauthorized);
+        };
+        if !config.admin_basic_auth_enabled {
+            return Outcome::Forward(Status::Unauthorized);
+        }
+
+        let Some(header) = req.headers().get_one("Authorization") else {
+            return Outcome::Forward(Status::Unauthorized);
+        };
+
+        let ClientIp(client_ip) = match req.guard::<ClientIp>().await {
+            Outcome::Success(client_ip) => client_ip,
+            _ => ClientIp(None),
+        };
+        if !is_trusted_ip(&config.admin_basic_auth_trusted_ips, client_ip) {
+            warn!("Ignoring basic auth from untrusted address {:?}", client_ip);
+            return Outcome::Forward(Status::Unauthorized);
+        }
+
+        match parse_basic_auth(header) {
+            Some(auth) => Outcome::Success(auth),
+            None => Outcome::Forward(Status::Unauthorized),
+        }
+    }
 }
 
 pub async fn has_admin_users(db: &mut Connection<MessagesDB>) -> AppResult<bool> {
@@ -54,43 +113,113 @@ pub async fn has_admin_users(db: &mut Connection<MessagesDB>) -> AppResult<bool>
     Ok(count > 0)
 }
 
-pub async fn get_authenticated_user(
-    cookies: &CookieJar<'_>,
+/// Check Basic credentials, counting failures towards the same lockout as
+/// the login form
+async fn get_basic_auth_user(
     db: &mut Connection<MessagesDB>,
     redis: &State<redis::Client>,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<Option<AdminUser>> {
-    let cookie = match cookies.get(SESSION_COOKIE) {
-        Some(cookie) => cookie,
-        None => return Ok(None),
-    };
+    config: &AppConfig,
+    alerts: &LoginAlerts,
+    client_ip: Option<IpAddr>,
+    auth: &BasicAuth,
+) -> AppResult<Option<models::AdminUser>> {
+    ensure_login_allowed(redis, client_ip).await?;
 
-    let token = cookie.value();
-    let mut conn = redis.get_multiplexed_async_connection().await?;
-    let session_payload: Option<String> = conn.get(session_key(token)).await?;
+    let user = admin_users::table
+        .filter(admin_users::username.eq(&auth.username))
+        .select(models::AdminUser::as_select())
+        .first(db)
+        .await
+        .optional()?;
+
+    match user {
+        Some(user) if verify_password(&auth.password, &user.password_hash) => {
+            clear_login_failures(redis, client_ip).await?;
+            Ok(Some(user))
+        }
+        _ => {
+            warn!(
+                "Failed basic auth attempt for '{}' from {:?}",
+                auth.username, client_ip
+            );
+            record_login_failure(redis, config, alerts, client_ip).await?;
+            Ok(None)
+        }
+    }
+}
 
-    let session_payload = match session_payload {
-        Some(value) => value,
-        None => return Ok(None),
+/// Re-hash a legacy bcrypt password with Argon2id after it was verified.
+/// Failures are only logged, the old hash keeps working.
+async fn upgrade_password_hash(
+    db: &mut Connection<MessagesDB>,
+    user: &models::AdminUser,
+    password: &str,
+) {
+    let result = match hash_password(password) {
+        Ok(password_hash) => diesel::update(admin_users::table.find(user.id))
+            .set(admin_users::password_hash.eq(password_hash))
+            .execute(db)
+            .await
+            .map_err(AppError::from),
+        Err(e) => Err(e),
     };
 
-    let session: AdminSessionData = serde_json::from_str(&session_payload)?;
+    match result {
+        Ok(_) => info!(
+            "Upgraded password hash of admin '{}' to Argon2id",
+            user.username
+        ),
+        Err(e) => error!(
+            "Failed to upgrade password hash of admin '{}': {}",
+            user.username, e
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn get_authenticated_user(
+    cookies: &CookieJar<'_>,
+    db: &mut Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    alerts: &LoginAlerts,
+    client_ip: Option<IpAddr>,
+    user_agent: &UserAgent,
+    basic_auth: Option<&BasicAuth>,
+) -> AppResult<Option<models::AdminUser>> {
+    if let Some(user) = get_session_user(cookies, db, redis, config, client_ip, user_agent).await? {
+        return Ok(Some(user));
+    }
+
+    match basic_auth {
+        Some(auth) => get_basic_auth_user(db, redis, config, alerts, client_ip, auth).await,
+        None => Ok(None),
+    }
+}
 
-    if let Some(saved_ip) = session.ip_address {
-        if let Some(current_ip) = remote_addr {
-            if saved_ip != current_ip.ip().to_string() {
-                warn!("Admin session IP mismatch");
-                return Ok(None);
+async fn get_session_user(
+    cookies: &CookieJar<'_>,
+    db: &mut Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    client_ip: Option<IpAddr>,
+    user_agent: &UserAgent,
+) -> AppResult<Option<models::AdminUser>> {
+    let user_id = match touch_session(cookies, redis, config, client_ip, user_agent).await? {
+        Some((session, _)) => session.user_id,
+        None => {
+            match resume_remembered_session(cookies, redis, config, client_ip, user_agent.clone())
+                .await?
+            {
+                Some(user_id) => user_id,
+                None => return Ok(None),
             }
-        } else {
-            debug!("Session has IP but request has none");
-            return Ok(None);
         }
-    }
+    };
 
     let user = admin_users::table
-        .find(session.user_id)
-        .select(AdminUser::as_select())
+        .find(user_id)
+        .select(models::AdminUser::as_select())
         .first(db)
         .await
         .optional()
@@ -102,60 +231,133 @@ pub async fn get_authenticated_user(
     Ok(user)
 }
 
-pub async fn get_authenticated_user_id(
-    cookies: &CookieJar<'_>,
-    db: &mut Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<Option<i64>> {
-    Ok(get_authenticated_user(cookies, db, redis, remote_addr)
-        .await?
-        .map(|user| user.id))
+/// Request guard for admin endpoints. Resolves the admin from an API key
+/// bearer token, or from the session cookie (or the basic auth fallback),
+/// and fails with 401 otherwise.
+pub struct AdminUser {
+    user: models::AdminUser,
+    client_ip: Option<IpAddr>,
+    /// Scopes of the API key used for the request; `None` for logins
+    scopes: Option<Vec<Permission>>,
 }
 
-/// Helper function to check if admin is authenticated
-pub async fn is_admin_authenticated(
-    cookies: &CookieJar<'_>,
-    db: &mut Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<bool> {
-    Ok(get_authenticated_user(cookies, db, redis, remote_addr)
-        .await?
-        .is_some())
+impl AdminUser {
+    fn new(user: models::AdminUser, client_ip: Option<IpAddr>) -> Self {
+        AdminUser {
+            user,
+            client_ip,
+            scopes: None,
+        }
+    }
+
+    /// Address the request came from, as resolved by the `ClientIp` guard
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// Check that the admin's role, and the API key scopes if any, grant
+    /// `permission`, failing with 403
+    pub fn require(&self, permission: Permission) -> AppResult<()> {
+        let role_allows = self.user.role().is_some_and(|role| role.allows(permission));
+        let scope_allows = self
+            .scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&permission));
+        if role_allows && scope_allows {
+            return Ok(());
+        }
+
+        warn!(
+            "Admin '{}' with role '{}' denied {:?}",
+            self.user.username, self.user.role, permission
+        );
+        Err(AppError::Forbidden)
+    }
 }
 
-pub async fn start_admin_session(
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    user_id: i64,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<()> {
-    let token = Uuid::new_v4().to_string();
-    let session = AdminSessionData {
-        user_id,
-        ip_address: remote_addr.map(|addr| addr.ip().to_string()),
-    };
+impl Deref for AdminUser {
+    type Target = models::AdminUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminUser {
+    type Error = AppError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let mut db = match req.guard::<Connection<MessagesDB>>().await {
+            Outcome::Success(db) => db,
+            Outcome::Error((status, _)) | Outcome::Forward(status) => {
+                return Outcome::Forward(status);
+            }
+        };
+        let (Some(redis), Some(config), Some(alerts)) = (
+            State::<redis::Client>::get(req.rocket()),
+            req.rocket().state::<AppConfig>(),
+            req.rocket().state::<LoginAlerts>(),
+        ) else {
+            return Outcome::Forward(Status::InternalServerError);
+        };
+        let ClientIp(client_ip) = match req.guard::<ClientIp>().await {
+            Outcome::Success(client_ip) => client_ip,
+            _ => ClientIp(None),
+        };
 
-    store_session(redis, &token, &session).await?;
+        // A bearer key is the only credential considered when present
+        if let Outcome::Success(key) = req.guard::<BearerKey>().await {
+            return match get_api_key_user(&mut db, &key).await {
+                Ok(Some((user, scopes))) => Outcome::Success(AdminUser {
+                    user,
+                    client_ip,
+                    scopes: Some(scopes),
+                }),
+                Ok(None) => Outcome::Error((Status::Unauthorized, AppError::Unauthorized)),
+                Err(e) => Outcome::Error((e.status(), e)),
+            };
+        }
 
-    let mut cookie = Cookie::new(SESSION_COOKIE, token);
-    cookie.set_http_only(true);
-    cookie.set_same_site(SameSite::Lax);
-    cookie.set_path("/");
-    cookie.set_max_age(rocket::time::Duration::hours(24));
-    cookies.add(cookie);
+        let basic_auth = match req.guard::<Option<BasicAuth>>().await {
+            Outcome::Success(basic_auth) => basic_auth,
+            _ => None,
+        };
+        let user_agent = match req.guard::<UserAgent>().await {
+            Outcome::Success(user_agent) => user_agent,
+            _ => UserAgent(None),
+        };
 
-    Ok(())
+        match get_authenticated_user(
+            req.cookies(),
+            &mut db,
+            redis,
+            config,
+            alerts,
+            client_ip,
+            &user_agent,
+            basic_auth.as_ref(),
+        )
+        .await
+        {
+            Ok(Some(user)) => Outcome::Success(AdminUser::new(user, client_ip)),
+            Ok(None) => Outcome::Error((Status::Unauthorized, AppError::Unauthorized)),
+            Err(e) => Outcome::Error((e.status(), e)),
+        }
+    }
 }
 
 #[post("/admin/login", format = "json", data = "<login>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn admin_login(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    alerts: &State<LoginAlerts>,
     cookies: &CookieJar<'_>,
     login: Json<AdminLoginRequest>,
-    remote_addr: Option<SocketAddr>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
 ) -> AppResult<Status> {
     if !has_admin_users(&mut db).await? {
         return Err(AppError::InvalidInput(
@@ -163,6 +365,8 @@ pub async fn admin_login(
         ));
     }
 
+    ensure_login_allowed(redis, client_ip.0).await?;
+
     let username = login.username.trim();
     if username.is_empty() {
         return Err(AppError::InvalidInput("Username is required.".to_string()));
@@ -170,7 +374,7 @@ pub async fn admin_login(
 
     let user = admin_users::table
         .filter(admin_users::username.eq(username))
-        .select(AdminUser::as_select())
+        .select(models::AdminUser::as_select())
         .first(&mut db)
         .await
         .optional()
@@ -180,37 +384,155 @@ pub async fn admin_login(
         })?;
 
     let Some(user) = user else {
-        cookies.remove(Cookie::from(SESSION_COOKIE));
+        clear_session_cookie(cookies, config);
         warn!("Failed admin login attempt for unknown user '{}'", username);
+        record_login_failure(redis, config, alerts, client_ip.0).await?;
         return Err(AppError::Unauthorized);
     };
 
-    if verify(&login.password, &user.password_hash).unwrap_or(false) {
-        start_admin_session(redis, cookies, user.id, remote_addr).await?;
+    if verify_password(&login.password, &user.password_hash) {
+        clear_login_failures(redis, client_ip.0).await?;
+        if needs_rehash(&user.password_hash) {
+            upgrade_password_hash(&mut db, &user, &login.password).await;
+        }
+        start_admin_session(redis, config, cookies, user.id, client_ip.0, user_agent).await?;
+        if login.remember_me {
+            issue_remember_me_token(redis, config, cookies, user.id).await?;
+        }
 
+        let admin = AdminUser::new(user, client_ip.0);
+        record_audit(&mut db, &admin, "login", "admin_user", admin.id).await;
         info!(
             "Admin login successful for '{}' from {:?}",
-            user.username, remote_addr
+            admin.username, client_ip.0
         );
         Ok(Status::Ok)
     } else {
-        cookies.remove(Cookie::from(SESSION_COOKIE));
+        clear_session_cookie(cookies, config);
         warn!(
             "Failed admin login attempt for '{}' from {:?}",
-            user.username, remote_addr
+            user.username, client_ip.0
         );
+        record_login_failure(redis, config, alerts, client_ip.0).await?;
         Err(AppError::Unauthorized)
     }
 }
 
+/// Start single sign-on at the configured OpenID Connect provider
+#[get("/admin/oidc/login")]
+pub async fn admin_oidc_login(
+    redis: &State<redis::Client>,
+    oidc: &State<OidcClient>,
+) -> AppResult<Redirect> {
+    if !oidc.is_enabled() {
+        return Err(AppError::NotFound);
+    }
+
+    let provider = oidc.discover().await?;
+    let state = Uuid::new_v4().simple().to_string();
+    let nonce = Uuid::new_v4().simple().to_string();
+
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let _: () = conn
+        .set_ex(
+            format!("{OIDC_STATE_PREFIX}{state}"),
+            &nonce,
+            OIDC_STATE_TTL_SECS,
+        )
+        .await?;
+
+    Ok(Redirect::to(
+        oidc.authorization_url(&provider, &state, &nonce)?,
+    ))
+}
+
+/// Finish single sign-on. The provider's verified email address has to be
+/// allowed by `OIDC_ALLOWED_EMAILS` and belong to an admin user; failures
+/// count towards the login lockout and lead back to the login page.
+#[get("/admin/oidc/callback?<code>&<state>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn admin_oidc_callback(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    oidc: &State<OidcClient>,
+    alerts: &State<LoginAlerts>,
+    cookies: &CookieJar<'_>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    code: Option<&str>,
+    state: Option<&str>,
+) -> AppResult<Redirect> {
+    if !oidc.is_enabled() {
+        return Err(AppError::NotFound);
+    }
+    ensure_login_allowed(redis, client_ip.0).await?;
+
+    // The provider omits the code when the user cancelled or was refused
+    let (Some(code), Some(state)) = (code, state) else {
+        return Ok(Redirect::to(OIDC_FAILED_REDIRECT));
+    };
+
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let nonce: Option<String> = conn.get_del(format!("{OIDC_STATE_PREFIX}{state}")).await?;
+    let Some(nonce) = nonce else {
+        warn!(
+            "OIDC callback with unknown or expired state from {:?}",
+            client_ip.0
+        );
+        return Ok(Redirect::to(OIDC_FAILED_REDIRECT));
+    };
+
+    let provider = oidc.discover().await?;
+    let email = match oidc.exchange_code(&provider, code, &nonce).await {
+        Ok(email) => email,
+        Err(AppError::Unauthorized | AppError::Forbidden) => {
+            warn!("Rejected OIDC login from {:?}", client_ip.0);
+            record_login_failure(redis, config, alerts, client_ip.0).await?;
+            return Ok(Redirect::to(OIDC_FAILED_REDIRECT));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let user = admin_users::table
+        .filter(admin_users::email.eq(&email))
+        .select(models::AdminUser::as_select())
+        .first(&mut db)
+        .await
+        .optional()?;
+    let Some(user) = user else {
+        warn!("OIDC login for '{}' matches no admin user", email);
+        record_login_failure(redis, config, alerts, client_ip.0).await?;
+        return Ok(Redirect::to(OIDC_FAILED_REDIRECT));
+    };
+
+    clear_login_failures(redis, client_ip.0).await?;
+    start_admin_session(redis, config, cookies, user.id, client_ip.0, user_agent).await?;
+
+    let admin = AdminUser::new(user, client_ip.0);
+    record_audit(&mut db, &admin, "login.oidc", "admin_user", admin.id).await;
+    info!(
+        "Admin OIDC login successful for '{}' from {:?}",
+        admin.username, client_ip.0
+    );
+    Ok(Redirect::to("/admin/messages"))
+}
+
 #[post("/admin/logout")]
 pub async fn admin_logout(
+    mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
     cookies: &CookieJar<'_>,
+    admin: Option<AdminUser>,
 ) -> AppResult<Status> {
-    if let Some(cookie) = cookies.get(SESSION_COOKIE) {
-        delete_session(redis, cookie.value()).await?;
-        cookies.remove(Cookie::from(SESSION_COOKIE));
+    forget_remember_me_token(redis, config, cookies).await?;
+    if let Some(token) = session_token(cookies, config) {
+        delete_session(redis, &token).await?;
+        clear_session_cookie(cookies, config);
+        if let Some(admin) = admin {
+            record_audit(&mut db, &admin, "logout", "admin_user", admin.id).await;
+        }
         info!("Admin logged out successfully");
     } else {
         debug!("Logout attempted without session cookie");
@@ -218,12 +540,45 @@ pub async fn admin_logout(
     Ok(Status::Ok)
 }
 
+/// Explicitly extend the current session, e.g. while an admin is editing
+/// without making other requests. An expired session is resumed from a
+/// remember-me token; fails with 401 once both are gone.
+#[post("/admin/refresh")]
+pub async fn admin_refresh(
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+) -> AppResult<Json<AdminSessionRefreshResponse>> {
+    if let Some((_, expires_in_secs)) =
+        touch_session(cookies, redis, config, client_ip.0, &user_agent).await?
+    {
+        return Ok(Json(AdminSessionRefreshResponse { expires_in_secs }));
+    }
+
+    match resume_remembered_session(cookies, redis, config, client_ip.0, user_agent).await? {
+        Some(_) => Ok(Json(AdminSessionRefreshResponse {
+            expires_in_secs: config
+                .admin_session_idle_secs
+                .min(config.admin_session_max_lifetime_secs),
+        })),
+        None => Err(AppError::Unauthorized),
+    }
+}
+
 #[get("/admin/status")]
+#[allow(clippy::too_many_arguments)]
 pub async fn admin_status(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    alerts: &State<LoginAlerts>,
+    oidc: &State<OidcClient>,
     cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    basic_auth: Option<BasicAuth>,
 ) -> AppResult<Json<AdminStatusResponse>> {
     let setup_required = !has_admin_users(&mut db).await?;
 
@@ -233,14 +588,188 @@ pub async fn admin_status(
             setup_required: true,
             current_user_id: None,
             current_username: None,
+            current_role: None,
+            oidc_enabled: oidc.is_enabled(),
         }));
     }
 
-    let user = get_authenticated_user(cookies, &mut db, redis, remote_addr).await?;
+    let user = get_authenticated_user(
+        cookies,
+        &mut db,
+        redis,
+        config,
+        alerts,
+        client_ip.0,
+        &user_agent,
+        basic_auth.as_ref(),
+    )
+    .await?;
     Ok(Json(AdminStatusResponse {
         authenticated: user.is_some(),
         setup_required: false,
         current_user_id: user.as_ref().map(|entry| entry.id),
+        current_role: user.as_ref().map(|entry| entry.role.clone()),
         current_username: user.map(|entry| entry.username),
+        oidc_enabled: oidc.is_enabled(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+
+    #[get("/whoami")]
+    fn whoami(auth: Option<BasicAuth>) -> String {
+        auth.map(|auth| auth.username).unwrap_or_default()
+    }
+
+    fn config(enabled: bool, trusted_ips: &str) -> AppConfig {
+        serde_json::from_value(serde_json::json!({
+            "database_url": "mysql://localhost/test",
+            "redis_url": "redis://localhost",
+            "admin_basic_auth_enabled": enabled,
+            "admin_basic_auth_trusted_ips": trusted_ips,
+        }))
+        .expect("valid config")
+    }
+
+    fn whoami_as(config: AppConfig, remote: &str, header: &str) -> String {
+        let rocket = rocket::build().manage(config).mount("/", routes![whoami]);
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+        client
+            .get("/whoami")
+            .remote(remote.parse().unwrap())
+            .header(Header::new("Authorization", header.to_string()))
+            .dispatch()
+            .into_string()
+            .unwrap()
+    }
+
+    fn basic_header(credentials: &str) -> String {
+        format!("Basic {}", BASE64.encode(credentials))
+    }
+
+    fn admin_with_role(role: &str) -> AdminUser {
+        let timestamp = chrono::NaiveDateTime::default();
+        AdminUser::new(
+            models::AdminUser {
+                id: 1,
+                username: "admin".to_string(),
+                password_hash: String::new(),
+                created_at: timestamp,
+                updated_at: timestamp,
+                role: role.to_string(),
+                email: None,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_admin_guard_requires_permission() {
+        let editor = admin_with_role("editor");
+        assert!(editor.require(Permission::ManageContent).is_ok());
+        assert!(matches!(
+            editor.require(Permission::ManageUsers),
+            Err(AppError::Forbidden)
+        ));
+
+        let unknown = admin_with_role("intern");
+        assert!(matches!(
+            unknown.require(Permission::ManageContent),
+            Err(AppError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_api_key_scopes_narrow_role() {
+        let mut key_admin = admin_with_role("superadmin");
+        key_admin.scopes = Some(vec![Permission::ManageContent]);
+        assert!(key_admin.require(Permission::ManageContent).is_ok());
+        assert!(matches!(
+            key_admin.require(Permission::ManageUsers),
+            Err(AppError::Forbidden)
+        ));
+
+        // Scopes never grant more than the owner's role
+        let mut key_editor = admin_with_role("editor");
+        key_editor.scopes = Some(vec![Permission::ManageMessages]);
+        assert!(matches!(
+            key_editor.require(Permission::ManageMessages),
+            Err(AppError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_parse_basic_auth() {
+        let auth = parse_basic_auth(&basic_header("admin:s3cret:with-colon")).unwrap();
+        assert_eq!(auth.username, "admin");
+        assert_eq!(auth.password, "s3cret:with-colon");
+
+        assert!(parse_basic_auth("Bearer abc").is_none());
+        assert!(parse_basic_auth("Basic not-base64!").is_none());
+        assert!(parse_basic_auth(&basic_header("no-colon")).is_none());
+        assert!(parse_basic_auth(&basic_header(":password")).is_none());
+    }
+
+    #[test]
+    fn test_is_trusted_ip() {
+        let localhost = Some("127.0.0.1".parse().unwrap());
+        assert!(is_trusted_ip("127.0.0.1,::1", localhost));
+        assert!(is_trusted_ip("::1, 127.0.0.1", localhost));
+        assert!(is_trusted_ip("*", Some("203.0.113.7".parse().unwrap())));
+        assert!(!is_trusted_ip(
+            "127.0.0.1",
+            Some("203.0.113.7".parse().unwrap())
+        ));
+        assert!(!is_trusted_ip("*", None));
+    }
+
+    #[test]
+    fn test_credentials_checked_against_password_hash() {
+        let hash = bcrypt::hash("s3cret", 4).unwrap();
+        let valid = parse_basic_auth(&basic_header("admin:s3cret")).unwrap();
+        let invalid = parse_basic_auth(&basic_header("admin:wrong")).unwrap();
+
+        assert!(verify_password(&valid.password, &hash));
+        assert!(!verify_password(&invalid.password, &hash));
+    }
+
+    #[test]
+    fn test_guard_accepts_basic_header_from_trusted_ip() {
+        let username = whoami_as(
+            config(true, "127.0.0.1"),
+            "127.0.0.1:5000",
+            &basic_header("admin:s3cret"),
+        );
+        assert_eq!(username, "admin");
+    }
+
+    #[test]
+    fn test_guard_rejects_malformed_header() {
+        let username = whoami_as(config(true, "127.0.0.1"), "127.0.0.1:5000", "Basic ???");
+        assert_eq!(username, "");
+    }
+
+    #[test]
+    fn test_guard_ignored_when_disabled() {
+        let username = whoami_as(
+            config(false, "127.0.0.1"),
+            "127.0.0.1:5000",
+            &basic_header("admin:s3cret"),
+        );
+        assert_eq!(username, "");
+    }
+
+    #[test]
+    fn test_guard_ignored_for_untrusted_ip() {
+        let username = whoami_as(
+            config(true, "127.0.0.1"),
+            "203.0.113.7:5000",
+            &basic_header("admin:s3cret"),
+        );
+        assert_eq!(username, "");
+    }
+}
@@ -1,14 +1,13 @@
-use rocket::State;
-use rocket::http::{CookieJar, Status};
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
 
 use crate::db::MessagesDB;
 use crate::error::{AppError, AppResult};
-use crate::models::{AdminUpsertBannerRequest, Banner, BannerDto, NewBanner};
-use crate::routes::admin::auth::is_admin_authenticated;
+use crate::models::{AdminUpsertBannerRequest, Banner, BannerDto, NewBanner, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
 use crate::schema::banners;
 
 fn to_banner_dto(banner: Banner) -> BannerDto {
@@ -62,13 +61,9 @@ pub async fn get_active_banner(
 #[get("/admin/api/banner")]
 pub async fn get_admin_banner(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
 ) -> AppResult<Json<Option<BannerDto>>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageContent)?;
 
     let banner = get_current_banner_row(&mut db).await?;
     Ok(Json(banner.map(to_banner_dto)))
@@ -77,14 +72,10 @@ pub async fn get_admin_banner(
 #[put("/admin/api/banner", format = "json", data = "<request>")]
 pub async fn upsert_banner(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
     request: Json<AdminUpsertBannerRequest>,
 ) -> AppResult<Json<BannerDto>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageContent)?;
 
     let title = request.title.trim();
     let message = request.message.trim();
@@ -139,24 +130,19 @@ pub async fn upsert_banner(
     let banner = get_current_banner_row(&mut db)
         .await?
         .ok_or_else(|| AppError::NotFound)?;
+    record_audit(&mut db, &admin, "banner.update", "banner", banner.id).await;
     Ok(Json(to_banner_dto(banner)))
 }
 
 #[delete("/admin/api/banner")]
-pub async fn delete_banner(
-    mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+pub async fn delete_banner(mut db: Connection<MessagesDB>, admin: AdminUser) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
 
     if let Some(existing) = get_current_banner_row(&mut db).await? {
         diesel::delete(banners::table.find(existing.id))
             .execute(&mut db)
             .await?;
+        record_audit(&mut db, &admin, "banner.delete", "banner", existing.id).await;
     }
 
     Ok(Status::Ok)
@@ -1,189 +1,717 @@
 // Blog post management endpoints (admin and public)
 
+use chrono::{NaiveDateTime, Utc};
+use diesel::mysql::Mysql;
+use diesel::sql_types::{Double, Text};
 use rocket::State;
 use rocket::form::Form;
-use rocket::http::{ContentType, CookieJar, Status};
+use rocket::http::{Accept, Status};
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
 use tracing::{error, info};
 
-use crate::db::MessagesDB;
+use crate::config::AppConfig;
+use crate::db::{MessagesDB, last_insert_id};
 use crate::error::{AppError, AppResult};
+use crate::markdown::{reading_time_minutes, render_markdown, word_count};
+use crate::media::Media;
 use crate::models::{
-    AdminCreateBlogPostMultipart, AdminUpdateBlogPostMultipart, BlogPost, BlogPostDto, NewBlogPost,
+    AdminBlogPostSummary, AdminCreateBlogPostMultipart, AdminPatchBlogPostRequest,
+    AdminUpdateBlogPostMultipart, BlogAuthor, BlogFeatureRequest, BlogPost, BlogPostChangeset,
+    BlogPostDto, BlogPostImageDto, BlogPostListing, BlogPostSummary, BlogPreviewRequest,
+    BlogPreviewResponse, NewBlogPost, NewBlogPostTag, NewTag, PaginatedAdminBlogPosts,
+    PaginatedBlogPosts, Permission, PopularBlogPost, Tag,
 };
-use crate::routes::admin::auth::is_admin_authenticated;
-use crate::schema::blog_posts;
-use crate::utils::process_image_upload;
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::blog_authors::load_author;
+use crate::routes::admin::blog_images::{
+    MEDIA_PREFIX, copy_post_images, load_post_images, post_image_response, rewrite_image_urls,
+    set_cover_image,
+};
+use crate::routes::admin::blog_revisions::record_revision;
+use crate::routes::admin::blog_seo::seo_fields;
+use crate::routes::admin::images::CachedImage;
+use crate::routes::admin::tags::{load_post_tags, parse_tag_list, set_post_tags, tag_slug};
+use crate::routes::feeds::ConditionalHeaders;
+use crate::sanitize::{HtmlPolicy, sanitize_markdown};
+use crate::schema::{blog_post_images, blog_post_tags, blog_posts, tags};
+use crate::utils::{
+    ImageSettings, ImageSize, ProcessedImage, ShortLinkTarget, contains_pattern,
+    map_slug_write_error, next_free_slug, normalize_slug, process_image_upload, search_query,
+    slugify, validate_not_empty,
+};
+use crate::views::BlogViewCounter;
+
+const DEFAULT_PAGE_SIZE: i64 = 10;
+const MAX_PAGE_SIZE: i64 = 50;
+const DEFAULT_POPULAR_LIMIT: i64 = 5;
+const MAX_POPULAR_LIMIT: i64 = 20;
+const DEFAULT_FEATURED_LIMIT: i64 = 3;
+const MAX_FEATURED_LIMIT: i64 = 20;
+/// Appended to the title of a duplicated post
+const COPY_TITLE_SUFFIX: &str = " (copy)";
+/// Slugs that would be shadowed by fixed `/api/blog/...` routes
+const RESERVED_SLUGS: [&str; 3] = ["popular", "authors", "featured"];
+
+pub(crate) fn to_blog_post_dto(
+    post: BlogPost,
+    author: Option<BlogAuthor>,
+    tags: Vec<Tag>,
+    images: Vec<BlogPostImageDto>,
+) -> BlogPostDto {
+    BlogPostDto {
+        id: post.id,
+        title: post.title,
+        slug: post.slug,
+        excerpt: post.excerpt,
+        content_html: render_markdown(&post.content),
+        content: post.content,
+        word_count: post.word_count,
+        reading_time_minutes: post.reading_time_minutes,
+        meta_title: post.meta_title,
+        meta_description: post.meta_description,
+        canonical_url: post.canonical_url,
+        og_image: post.og_image,
+        author,
+        tags,
+        image_mime: images
+            .as_slice()
+            .first()
+            .map(|cover| cover.image.image_mime.clone()),
+        image_url: images.as_slice().first().map(|cover| cover.url.clone()),
+        images,
+        published: post.published,
+        featured: post.featured,
+        featured_order: post.featured_order,
+        archived_at: post.archived_at,
+        view_count: post.view_count,
+        created_at: post.created_at,
+        updated_at: post.updated_at,
+        short_code: ShortLinkTarget::BlogPost(post.id).short_code(),
+    }
+}
+
+fn to_blog_post_summary(
+    post: BlogPostListing,
+    tags: Vec<Tag>,
+    images: Vec<BlogPostImageDto>,
+) -> BlogPostSummary {
+    BlogPostSummary {
+        id: post.id,
+        title: post.title,
+        slug: post.slug,
+        excerpt: post.excerpt,
+        reading_time_minutes: post.reading_time_minutes,
+        tags,
+        image_mime: images
+            .as_slice()
+            .first()
+            .map(|cover| cover.image.image_mime.clone()),
+        image_url: images.into_iter().next().map(|cover| cover.url),
+        created_at: post.created_at,
+        updated_at: post.updated_at,
+        short_code: ShortLinkTarget::BlogPost(post.id).short_code(),
+    }
+}
+
+/// `MATCH ... AGAINST` over the FULLTEXT index of blog posts
+pub(crate) fn blog_search_relevance(
+    query: &str,
+) -> Box<dyn BoxableExpression<blog_posts::table, Mysql, SqlType = Double>> {
+    Box::new(
+        diesel::dsl::sql::<Double>("MATCH (title, excerpt, content) AGAINST (")
+            .bind::<Text, _>(query.to_string())
+            .sql(" IN NATURAL LANGUAGE MODE)"),
+    )
+}
+
+type PubliclyVisible = diesel::dsl::And<
+    diesel::dsl::And<
+        diesel::dsl::Eq<blog_posts::published, bool>,
+        diesel::dsl::IsNull<blog_posts::archived_at>,
+    >,
+    diesel::dsl::IsNull<blog_posts::deleted_at>,
+>;
+
+/// Posts shown on the public site: published, not archived and not in the
+/// trash
+pub(crate) fn publicly_visible() -> PubliclyVisible {
+    blog_posts::published
+        .eq(true)
+        .and(blog_posts::archived_at.is_null())
+        .and(blog_posts::deleted_at.is_null())
+}
+
+/// Posts shown in the admin list for a `status` filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StatusFilter {
+    /// Every post that is not archived
+    Working,
+    Draft,
+    Published,
+    Archived,
+}
+
+/// Visibility filter of the admin list. There is no scheduled publishing,
+/// so `scheduled` is rejected.
+fn status_filter(status: Option<&str>) -> AppResult<StatusFilter> {
+    match status.map(str::trim).filter(|status| !status.is_empty()) {
+        None | Some("all") => Ok(StatusFilter::Working),
+        Some("draft") => Ok(StatusFilter::Draft),
+        Some("published") => Ok(StatusFilter::Published),
+        Some("archived") => Ok(StatusFilter::Archived),
+        Some("scheduled") => Err(AppError::InvalidInput(
+            "Scheduled publishing is not supported; posts are either drafts or published."
+                .to_string(),
+        )),
+        Some(_) => Err(AppError::InvalidInput(
+            "Status must be one of: draft, published, archived.".to_string(),
+        )),
+    }
+}
+
+/// Posts for the admin list by visibility, optionally by title; posts in
+/// the trash are listed with the trash
+fn admin_posts<'a>(status: StatusFilter, title: Option<&str>) -> blog_posts::BoxedQuery<'a, Mysql> {
+    let mut filtered = match status {
+        StatusFilter::Archived => blog_posts::table
+            .filter(blog_posts::archived_at.is_not_null())
+            .into_boxed(),
+        _ => blog_posts::table
+            .filter(blog_posts::archived_at.is_null())
+            .into_boxed(),
+    };
+    filtered = filtered.filter(blog_posts::deleted_at.is_null());
+    match status {
+        StatusFilter::Draft => filtered = filtered.filter(blog_posts::published.eq(false)),
+        StatusFilter::Published => filtered = filtered.filter(blog_posts::published.eq(true)),
+        StatusFilter::Working | StatusFilter::Archived => {}
+    }
+    if let Some(title) = title {
+        filtered = filtered.filter(blog_posts::title.like(contains_pattern(title)));
+    }
+    filtered
+}
+
+/// Published posts, optionally limited to a tag and a search query
+fn published_posts<'a>(
+    tag: Option<&str>,
+    author: Option<i64>,
+    query: Option<&str>,
+) -> blog_posts::BoxedQuery<'a, Mysql> {
+    let mut filtered = blog_posts::table.filter(publicly_visible()).into_boxed();
+    if let Some(author) = author {
+        filtered = filtered.filter(blog_posts::author_id.eq(author));
+    }
+    if let Some(tag) = tag {
+        filtered = filtered.filter(
+            blog_posts::id.eq_any(
+                blog_post_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::slug.eq(tag.to_string()))
+                    .select(blog_post_tags::blog_post_id),
+            ),
+        );
+    }
+    if let Some(query) = query {
+        filtered = filtered.filter(blog_search_relevance(query).gt(0.0));
+    }
+    filtered
+}
+
+/// The slug to save for a post. A blank slug is generated from the title
+/// and made unique with a numeric suffix; a submitted slug that another post
+/// already uses is rejected with a free alternative.
+pub(crate) async fn resolve_slug(
+    db: &mut Connection<MessagesDB>,
+    requested: Option<&str>,
+    title: &str,
+    exclude_id: Option<i64>,
+) -> AppResult<String> {
+    let requested = requested.map(str::trim).filter(|slug| !slug.is_empty());
+    let base = match requested {
+        Some(slug) => slug.to_string(),
+        None => Some(slugify(title))
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| "post".to_string()),
+    };
+
+    let mut similar = blog_posts::table
+        .filter(
+            blog_posts::slug
+                .eq(base.clone())
+                .or(blog_posts::slug.like(format!("{base}-%"))),
+        )
+        .select(blog_posts::slug)
+        .into_boxed();
+    if let Some(id) = exclude_id {
+        similar = similar.filter(blog_posts::id.ne(id));
+    }
+    let mut taken: Vec<String> = similar.load(db).await?;
+    taken.extend(RESERVED_SLUGS.map(String::from));
+
+    let free = next_free_slug(&base, &taken);
+    if requested.is_some() && free != base {
+        return Err(AppError::SlugConflict {
+            slug: base,
+            suggested_slug: Some(free),
+        });
+    }
+    Ok(free)
+}
+
+/// Replace the tags of a post in a transaction and return the new ones
+async fn replace_post_tags(
+    db: &mut Connection<MessagesDB>,
+    id: i64,
+    new_tags: Vec<NewTag>,
+) -> AppResult<Vec<Tag>> {
+    db.transaction(|conn| Box::pin(async move { set_post_tags(conn, id, &new_tags).await }))
+        .await
+        .map_err(|e| {
+            error!("Error setting tags of blog post {}: {}", id, e);
+            AppError::from(e)
+        })?;
+    Ok(load_post_tags(db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default())
+}
+
+/// Images of a post in display order
+async fn load_images(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Vec<BlogPostImageDto>> {
+    Ok(load_post_images(db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default())
+}
+
+/// Put an image into the media store and make it the cover of a post. The
+/// replaced cover is released once the change is committed.
+async fn save_cover_image(
+    db: &mut Connection<MessagesDB>,
+    media: &Media,
+    id: i64,
+    image: ProcessedImage,
+) -> AppResult<()> {
+    let stored = media.store_image(db, MEDIA_PREFIX, &image).await?;
+    let stored_key = stored.key.clone();
+    let saved = db
+        .transaction(|conn| Box::pin(async move { set_cover_image(conn, id, &stored).await }))
+        .await;
+    match saved {
+        Ok(replaced) => {
+            media.release_images(db, replaced.as_slice()).await;
+            Ok(())
+        }
+        Err(e) => {
+            error!("Error saving the cover image of blog post {}: {}", id, e);
+            media.release_images(db, &[stored_key]).await;
+            Err(e.into())
+        }
+    }
+}
 
 #[post("/admin/api/blog", data = "<post_form>")]
 pub async fn create_blog_post(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    policy: &State<HtmlPolicy>,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
     post_form: Form<AdminCreateBlogPostMultipart<'_>>,
 ) -> AppResult<Json<BlogPostDto>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageContent)?;
 
     let post = post_form.into_inner();
+    let new_tags = parse_tag_list(post.tags.as_deref().unwrap_or_default())?;
+    let seo = seo_fields(
+        post.meta_title.as_deref(),
+        post.meta_description.as_deref(),
+        post.canonical_url.as_deref(),
+        post.og_image.as_deref(),
+    )?;
+    let slug = resolve_slug(&mut db, post.slug.as_deref(), &post.title, None).await?;
 
-    // Process image if uploaded
-    let (image_bytes, image_mime) = match process_image_upload(post.image).await? {
-        Some((bytes, mime)) => (Some(bytes), Some(mime)),
-        None => (None, None),
-    };
+    let cover = process_image_upload(post.image, image_settings).await?;
 
+    let content = sanitize_markdown(&post.content, policy);
+    let excerpt = post
+        .excerpt
+        .as_deref()
+        .map(|excerpt| sanitize_markdown(excerpt, policy));
+    let words = word_count(&content);
     let new_post = NewBlogPost {
         title: post.title,
-        slug: post.slug,
-        excerpt: post.excerpt,
-        content: post.content,
-        image: image_bytes,
-        image_mime,
+        slug,
+        excerpt,
+        content,
+        word_count: words,
+        reading_time_minutes: reading_time_minutes(words),
+        meta_title: seo.meta_title,
+        meta_description: seo.meta_description,
+        canonical_url: seo.canonical_url,
+        og_image: seo.og_image,
+        author_id: Some(admin.id),
         published: post.published.unwrap_or(false),
     };
 
-    // Insert
-    diesel::insert_into(blog_posts::table)
-        .values(&new_post)
-        .execute(&mut db)
-        .await
-        .map_err(|e| {
-            error!("Error inserting blog post: {}", e);
-            AppError::from(e)
-        })?;
+    let slug = new_post.slug.clone();
 
-    // Retrieve inserted row by slug (slug should be unique)
-    let inserted: BlogPost = blog_posts::table
-        .filter(blog_posts::slug.eq(&new_post.slug))
-        .select(BlogPost::as_select())
-        .first(&mut db)
+    // Insert and read the row back by its generated id on one connection
+    let inserted = db
+        .transaction(|conn| {
+            Box::pin(async move {
+                diesel::insert_into(blog_posts::table)
+                    .values(&new_post)
+                    .execute(conn)
+                    .await?;
+                let id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+                blog_posts::table
+                    .find(id as i64)
+                    .select(BlogPost::as_select())
+                    .first::<BlogPost>(conn)
+                    .await
+            })
+        })
         .await
         .map_err(|e| {
-            error!("Error fetching created blog post: {}", e);
-            AppError::from(e)
+            error!("Error inserting blog post: {}", e);
+            map_slug_write_error(e, &slug)
         })?;
 
-    let dto = BlogPostDto {
-        id: inserted.id,
-        title: inserted.title,
-        slug: inserted.slug,
-        excerpt: inserted.excerpt,
-        content: inserted.content,
-        image_mime: inserted.image_mime,
-        published: inserted.published,
-        created_at: inserted.created_at,
-        updated_at: inserted.updated_at,
-    };
+    let post_tags = replace_post_tags(&mut db, inserted.id, new_tags).await?;
+    if let Some(image) = cover {
+        save_cover_image(&mut db, media, inserted.id, image).await?;
+    }
+    let images = load_images(&mut db, inserted.id).await?;
+    let author = load_author(&mut db, inserted.author_id).await?;
 
+    record_audit(
+        &mut db,
+        &admin,
+        "blog_post.create",
+        "blog_post",
+        inserted.id,
+    )
+    .await;
     info!("Blog post created successfully with id: {}", inserted.id);
-    Ok(Json(dto))
+    Ok(Json(to_blog_post_dto(inserted, author, post_tags, images)))
 }
 
 #[put("/admin/api/blog/<id>", data = "<update_form>")]
 pub async fn update_blog_post(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    policy: &State<HtmlPolicy>,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
     id: i64,
     update_form: Form<AdminUpdateBlogPostMultipart<'_>>,
-) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+) -> AppResult<Json<BlogPostDto>> {
+    admin.require(Permission::ManageContent)?;
 
     let update_data = update_form.into_inner();
     let target = blog_posts::table.find(id);
 
-    // Check if blog post exists
-    let _existing_post: BlogPost =
-        blog_posts::table
-            .find(id)
-            .first(&mut db)
-            .await
-            .map_err(|e| {
-                error!("Error checking for existing blog post {}: {}", id, e);
-                AppError::NotFound
-            })?;
+    // Check if blog post exists; posts in the trash must be restored first
+    let existing_post: BlogPost = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(BlogPost::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error checking for existing blog post {}: {}", id, e);
+            AppError::NotFound
+        })?;
 
     let published = update_data.published.unwrap_or(false);
-
-    let update_values = match process_image_upload(update_data.image).await? {
-        Some((buffer, ct_string)) => {
-            // Update with new image
-            diesel::update(target)
-                .set((
-                    blog_posts::title.eq(&update_data.title),
-                    blog_posts::slug.eq(&update_data.slug),
-                    blog_posts::excerpt.eq(&update_data.excerpt),
-                    blog_posts::content.eq(&update_data.content),
-                    blog_posts::image.eq(buffer),
-                    blog_posts::image_mime.eq(Some(ct_string)),
-                    blog_posts::published.eq(published),
-                ))
-                .execute(&mut db)
-                .await
-        }
-        None => {
-            // No new image provided - keep existing image
-            diesel::update(target)
-                .set((
-                    blog_posts::title.eq(&update_data.title),
-                    blog_posts::slug.eq(&update_data.slug),
-                    blog_posts::excerpt.eq(&update_data.excerpt),
-                    blog_posts::content.eq(&update_data.content),
-                    blog_posts::published.eq(published),
-                ))
-                .execute(&mut db)
-                .await
-        }
+    let new_tags = match update_data.tags.as_deref() {
+        Some(raw) => Some(parse_tag_list(raw)?),
+        None => None,
     };
+    let seo = seo_fields(
+        update_data.meta_title.as_deref(),
+        update_data.meta_description.as_deref(),
+        update_data.canonical_url.as_deref(),
+        update_data.og_image.as_deref(),
+    )?;
+    let slug = resolve_slug(
+        &mut db,
+        update_data.slug.as_deref(),
+        &update_data.title,
+        Some(id),
+    )
+    .await?;
+
+    record_revision(&mut db, &admin, &existing_post).await?;
+
+    let cover = process_image_upload(update_data.image, image_settings).await?;
+    let content = sanitize_markdown(&update_data.content, policy);
+    let excerpt = update_data
+        .excerpt
+        .as_deref()
+        .map(|excerpt| sanitize_markdown(excerpt, policy));
+    let words = word_count(&content);
+    let update_values = diesel::update(target)
+        .set((
+            blog_posts::title.eq(&update_data.title),
+            blog_posts::slug.eq(&slug),
+            blog_posts::excerpt.eq(&excerpt),
+            blog_posts::content.eq(&content),
+            blog_posts::word_count.eq(words),
+            blog_posts::reading_time_minutes.eq(reading_time_minutes(words)),
+            blog_posts::meta_title.eq(&seo.meta_title),
+            blog_posts::meta_description.eq(&seo.meta_description),
+            blog_posts::canonical_url.eq(&seo.canonical_url),
+            blog_posts::og_image.eq(&seo.og_image),
+            blog_posts::published.eq(published),
+        ))
+        .execute(&mut db)
+        .await;
 
     update_values.map_err(|e| {
         error!("Error updating blog post {}: {}", id, e);
-        AppError::from(e)
+        map_slug_write_error(e, &slug)
     })?;
 
+    if let Some(new_tags) = new_tags {
+        replace_post_tags(&mut db, id, new_tags).await?;
+    }
+    if let Some(image) = cover {
+        save_cover_image(&mut db, media, id, image).await?;
+    }
+
+    // Reload for the timestamps set by the database
+    let updated: BlogPost = target
+        .select(BlogPost::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error fetching updated blog post {}: {}", id, e);
+            AppError::from(e)
+        })?;
+    let post_tags = load_post_tags(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let images = load_images(&mut db, id).await?;
+    let author = load_author(&mut db, updated.author_id).await?;
+
+    record_audit(&mut db, &admin, "blog_post.update", "blog_post", id).await;
     info!("Blog post {} updated successfully", id);
-    Ok(Status::Ok)
+    Ok(Json(to_blog_post_dto(updated, author, post_tags, images)))
 }
 
-#[delete("/admin/api/blog/<id>")]
-pub async fn delete_blog_post(
+/// Change only the fields given as JSON, e.g. `{ "published": true }`. SEO
+/// overrides are validated together with the ones that are kept.
+#[patch("/admin/api/blog/<id>", format = "json", data = "<request>")]
+pub async fn patch_blog_post(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    policy: &State<HtmlPolicy>,
     id: i64,
-) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
+    request: Json<AdminPatchBlogPostRequest>,
+) -> AppResult<Json<BlogPostDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let patch = request.into_inner();
+    let target = blog_posts::table.find(id);
+
+    // Posts in the trash must be restored first
+    let existing: BlogPost = target
+        .filter(blog_posts::deleted_at.is_null())
+        .select(BlogPost::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error checking for existing blog post {}: {}", id, e);
+            AppError::NotFound
+        })?;
+
+    if patch
+        .title
+        .as_deref()
+        .is_some_and(|title| !validate_not_empty(title))
+    {
+        return Err(AppError::InvalidInput(
+            "Title must not be empty.".to_string(),
+        ));
     }
+    let new_tags = match &patch.tags {
+        Some(names) => Some(parse_tag_list(&names.join(","))?),
+        None => None,
+    };
+    let seo = seo_fields(
+        kept(&patch.meta_title, &existing.meta_title),
+        kept(&patch.meta_description, &existing.meta_description),
+        kept(&patch.canonical_url, &existing.canonical_url),
+        kept(&patch.og_image, &existing.og_image),
+    )?;
+    let slug = match patch.slug.as_deref() {
+        Some(requested) => {
+            let title = patch.title.as_deref().unwrap_or(&existing.title);
+            Some(resolve_slug(&mut db, Some(requested), title, Some(id)).await?)
+        }
+        None => None,
+    };
 
-    diesel::delete(blog_posts::table.find(id))
-        .execute(&mut db)
+    let content = patch
+        .content
+        .as_deref()
+        .map(|content| sanitize_markdown(content, policy));
+    let words = content.as_deref().map(word_count);
+    let changes = BlogPostChangeset {
+        title: patch.title,
+        slug,
+        excerpt: patch.excerpt.map(|excerpt| {
+            excerpt
+                .as_deref()
+                .map(|excerpt| sanitize_markdown(excerpt, policy))
+        }),
+        content,
+        word_count: words,
+        reading_time_minutes: words.map(reading_time_minutes),
+        meta_title: patch.meta_title.map(|_| seo.meta_title),
+        meta_description: patch.meta_description.map(|_| seo.meta_description),
+        canonical_url: patch.canonical_url.map(|_| seo.canonical_url),
+        og_image: patch.og_image.map(|_| seo.og_image),
+        published: patch.published,
+    };
+
+    if changes != BlogPostChangeset::default() || new_tags.is_some() {
+        record_revision(&mut db, &admin, &existing).await?;
+    }
+    if changes != BlogPostChangeset::default() {
+        let slug = changes.slug.clone().unwrap_or(existing.slug);
+        diesel::update(target)
+            .set(&changes)
+            .execute(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error patching blog post {}: {}", id, e);
+                map_slug_write_error(e, &slug)
+            })?;
+    }
+    if let Some(new_tags) = new_tags {
+        replace_post_tags(&mut db, id, new_tags).await?;
+    }
+
+    // Reload for the timestamps set by the database
+    let updated: BlogPost = target
+        .select(BlogPost::as_select())
+        .first(&mut db)
         .await
         .map_err(|e| {
-            error!("Error deleting blog post {}: {}", id, e);
+            error!("Error fetching updated blog post {}: {}", id, e);
             AppError::from(e)
         })?;
+    let post_tags = load_post_tags(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let images = load_images(&mut db, id).await?;
+    let author = load_author(&mut db, updated.author_id).await?;
+
+    record_audit(&mut db, &admin, "blog_post.update", "blog_post", id).await;
+    info!("Blog post {} patched successfully", id);
+    Ok(Json(to_blog_post_dto(updated, author, post_tags, images)))
+}
+
+/// The value of an optional field after a partial update: the patched value
+/// when it is given, else the current one
+fn kept<'a>(patched: &'a Option<Option<String>>, current: &'a Option<String>) -> Option<&'a str> {
+    match patched {
+        Some(value) => value.as_deref(),
+        None => current.as_deref(),
+    }
+}
+
+/// Render Markdown the way saved posts are rendered, without saving it
+#[post("/admin/api/blog/preview", format = "json", data = "<request>")]
+pub async fn preview_blog_post(
+    admin: AdminUser,
+    request: Json<BlogPreviewRequest>,
+) -> AppResult<Json<BlogPreviewResponse>> {
+    admin.require(Permission::ManageContent)?;
+
+    Ok(Json(BlogPreviewResponse {
+        html: render_markdown(&request.content),
+    }))
+}
+
+/// Move a post to the trash, from where it can be restored or deleted
+/// permanently
+#[delete("/admin/api/blog/<id>")]
+pub async fn delete_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    diesel::update(
+        blog_posts::table
+            .find(id)
+            .filter(blog_posts::deleted_at.is_null()),
+    )
+    .set(blog_posts::deleted_at.eq(Utc::now().naive_utc()))
+    .execute(&mut db)
+    .await
+    .map_err(|e| {
+        error!("Error deleting blog post {}: {}", id, e);
+        AppError::from(e)
+    })?;
 
-    info!("Blog post {} deleted successfully", id);
+    record_audit(&mut db, &admin, "blog_post.delete", "blog_post", id).await;
+    info!("Blog post {} moved to the trash", id);
     Ok(Status::Ok)
 }
 
-#[get("/api/blog")]
-pub async fn list_blog_posts(mut db: Connection<MessagesDB>) -> AppResult<Json<Vec<BlogPostDto>>> {
-    let results: Vec<BlogPost> = blog_posts::table
-        .filter(blog_posts::published.eq(true))
-        .order(blog_posts::created_at.desc())
-        .select(BlogPost::as_select())
+/// Published posts, newest first or best matches first when searching,
+/// without their content. `author` is the id of an admin user.
+#[get("/api/blog?<page>&<limit>&<tag>&<author>&<q>")]
+pub async fn list_blog_posts(
+    mut db: Connection<MessagesDB>,
+    page: Option<i64>,
+    limit: Option<i64>,
+    tag: Option<&str>,
+    author: Option<i64>,
+    q: Option<&str>,
+) -> AppResult<Json<PaginatedBlogPosts>> {
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let tag = tag.map(tag_slug).filter(|tag| !tag.is_empty());
+    let query = match q.map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => Some(search_query(q)?),
+        None => None,
+    };
+
+    let total: i64 = published_posts(tag.as_deref(), author, query)
+        .count()
+        .get_result(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error counting blog posts: {}", e);
+            AppError::from(e)
+        })?;
+
+    let filtered = published_posts(tag.as_deref(), author, query);
+    let ordered = match query {
+        Some(query) => filtered.order((
+            blog_search_relevance(query).desc(),
+            blog_posts::created_at.desc(),
+        )),
+        None => filtered.order(blog_posts::created_at.desc()),
+    };
+    let results: Vec<BlogPostListing> = ordered
+        .then_order_by(blog_posts::id.desc())
+        .limit(limit)
+        .offset((page - 1) * limit)
+        .select(BlogPostListing::as_select())
         .load(&mut db)
         .await
         .map_err(|e| {
@@ -191,118 +719,558 @@ pub async fn list_blog_posts(mut db: Connection<MessagesDB>) -> AppResult<Json<V
             AppError::from(e)
         })?;
 
-    let dtos: Vec<BlogPostDto> = results
-        .into_iter()
-        .map(|p| BlogPostDto {
-            id: p.id,
-            title: p.title,
-            slug: p.slug,
-            excerpt: p.excerpt,
-            content: p.content,
-            image_mime: p.image_mime,
-            published: p.published,
-            created_at: p.created_at,
-            updated_at: p.updated_at,
-        })
-        .collect();
+    let ids: Vec<i64> = results.iter().map(|post| post.id).collect();
+    let mut post_tags = load_post_tags(&mut db, &ids).await?;
+    let mut post_images = load_post_images(&mut db, &ids).await?;
 
-    info!("Retrieved {} published blog posts", dtos.len());
-    Ok(Json(dtos))
+    info!(
+        "Retrieved {} of {} published blog posts",
+        results.len(),
+        total
+    );
+    Ok(Json(PaginatedBlogPosts {
+        data: results
+            .into_iter()
+            .map(|post| {
+                let tags = post_tags.remove(&post.id).unwrap_or_default();
+                let images = post_images.remove(&post.id).unwrap_or_default();
+                to_blog_post_summary(post, tags, images)
+            })
+            .collect(),
+        total,
+        page,
+        limit,
+    }))
 }
 
-#[get("/admin/api/blog")]
+/// Posts for the admin list, newest first and without their content,
+/// filtered by `status` (`draft`, `published` or `archived`; archived posts
+/// are left out otherwise) and optionally by `q` in the title
+#[get("/admin/api/blog?<page>&<limit>&<status>&<q>")]
 pub async fn list_all_blog_posts(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
-) -> AppResult<Json<Vec<BlogPostDto>>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin: AdminUser,
+    page: Option<i64>,
+    limit: Option<i64>,
+    status: Option<&str>,
+    q: Option<&str>,
+) -> AppResult<Json<PaginatedAdminBlogPosts>> {
+    admin.require(Permission::ManageContent)?;
 
-    let results: Vec<BlogPost> = blog_posts::table
-        .order(blog_posts::created_at.desc())
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let status = status_filter(status)?;
+    let title = q.map(str::trim).filter(|q| !q.is_empty());
+
+    let total: i64 = admin_posts(status, title)
+        .count()
+        .get_result(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error counting blog posts: {}", e);
+            AppError::from(e)
+        })?;
+
+    let results: Vec<(BlogPostListing, bool, bool, i32, Option<NaiveDateTime>, i64)> =
+        admin_posts(status, title)
+            .order((blog_posts::created_at.desc(), blog_posts::id.desc()))
+            .limit(limit)
+            .offset((page - 1) * limit)
+            .select((
+                BlogPostListing::as_select(),
+                blog_posts::published,
+                blog_posts::featured,
+                blog_posts::featured_order,
+                blog_posts::archived_at,
+                blog_posts::view_count,
+            ))
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error loading all blog posts: {}", e);
+                AppError::from(e)
+            })?;
+
+    let ids: Vec<i64> = results.iter().map(|(post, ..)| post.id).collect();
+    let mut post_tags = load_post_tags(&mut db, &ids).await?;
+    let mut post_images = load_post_images(&mut db, &ids).await?;
+
+    info!("Retrieved {} of {} blog posts", results.len(), total);
+    Ok(Json(PaginatedAdminBlogPosts {
+        data: results
+            .into_iter()
+            .map(
+                |(post, published, featured, featured_order, archived_at, view_count)| {
+                    let tags = post_tags.remove(&post.id).unwrap_or_default();
+                    let images = post_images.remove(&post.id).unwrap_or_default();
+                    AdminBlogPostSummary {
+                        post: to_blog_post_summary(post, tags, images),
+                        published,
+                        featured,
+                        featured_order,
+                        archived_at,
+                        view_count,
+                    }
+                },
+            )
+            .collect(),
+        total,
+        page,
+        limit,
+    }))
+}
+
+/// A single post with its content, drafts included, for the editor
+#[get("/admin/api/blog/<id>")]
+pub async fn get_admin_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<BlogPostDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let post: BlogPost = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
         .select(BlogPost::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let post_tags = load_post_tags(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let images = load_images(&mut db, id).await?;
+    let author = load_author(&mut db, post.author_id).await?;
+    Ok(Json(to_blog_post_dto(post, author, post_tags, images)))
+}
+
+/// Most-read published posts, without their content. Views from the last
+/// `BLOG_VIEW_FLUSH_INTERVAL_SECS` are not included yet.
+#[get("/api/blog/popular?<limit>")]
+pub async fn list_popular_blog_posts(
+    mut db: Connection<MessagesDB>,
+    limit: Option<i64>,
+) -> AppResult<Json<Vec<PopularBlogPost>>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_POPULAR_LIMIT)
+        .clamp(1, MAX_POPULAR_LIMIT);
+
+    let results: Vec<(BlogPostListing, i64)> = blog_posts::table
+        .filter(publicly_visible())
+        .filter(blog_posts::view_count.gt(0))
+        .order((blog_posts::view_count.desc(), blog_posts::id.desc()))
+        .limit(limit)
+        .select((BlogPostListing::as_select(), blog_posts::view_count))
         .load(&mut db)
         .await
         .map_err(|e| {
-            error!("Error loading all blog posts: {}", e);
+            error!("Error loading popular blog posts: {}", e);
             AppError::from(e)
         })?;
 
-    let dtos: Vec<BlogPostDto> = results
-        .into_iter()
-        .map(|p| BlogPostDto {
-            id: p.id,
-            title: p.title,
-            slug: p.slug,
-            excerpt: p.excerpt,
-            content: p.content,
-            image_mime: p.image_mime,
-            published: p.published,
-            created_at: p.created_at,
-            updated_at: p.updated_at,
-        })
-        .collect();
+    let ids: Vec<i64> = results.iter().map(|(post, _)| post.id).collect();
+    let mut post_tags = load_post_tags(&mut db, &ids).await?;
+    let mut post_images = load_post_images(&mut db, &ids).await?;
+    Ok(Json(
+        results
+            .into_iter()
+            .map(|(post, view_count)| {
+                let tags = post_tags.remove(&post.id).unwrap_or_default();
+                let images = post_images.remove(&post.id).unwrap_or_default();
+                PopularBlogPost {
+                    post: to_blog_post_summary(post, tags, images),
+                    view_count,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Published posts picked for the homepage, without their content, by
+/// `featured_order` and then newest first
+#[get("/api/blog/featured?<limit>")]
+pub async fn list_featured_blog_posts(
+    mut db: Connection<MessagesDB>,
+    limit: Option<i64>,
+) -> AppResult<Json<Vec<BlogPostSummary>>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_FEATURED_LIMIT)
+        .clamp(1, MAX_FEATURED_LIMIT);
 
-    info!("Retrieved {} total blog posts", dtos.len());
-    Ok(Json(dtos))
+    let results: Vec<BlogPostListing> = blog_posts::table
+        .filter(publicly_visible())
+        .filter(blog_posts::featured.eq(true))
+        .order((
+            blog_posts::featured_order.asc(),
+            blog_posts::created_at.desc(),
+            blog_posts::id.desc(),
+        ))
+        .limit(limit)
+        .select(BlogPostListing::as_select())
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading featured blog posts: {}", e);
+            AppError::from(e)
+        })?;
+
+    let ids: Vec<i64> = results.iter().map(|post| post.id).collect();
+    let mut post_tags = load_post_tags(&mut db, &ids).await?;
+    let mut post_images = load_post_images(&mut db, &ids).await?;
+    Ok(Json(
+        results
+            .into_iter()
+            .map(|post| {
+                let tags = post_tags.remove(&post.id).unwrap_or_default();
+                let images = post_images.remove(&post.id).unwrap_or_default();
+                to_blog_post_summary(post, tags, images)
+            })
+            .collect(),
+    ))
 }
 
+/// A published post by its slug, normalized when `NORMALIZE_SLUGS` is on
+pub(crate) async fn find_published_post(
+    db: &mut Connection<MessagesDB>,
+    config: &AppConfig,
+    slug: String,
+) -> AppResult<BlogPost> {
+    let slug = if config.normalize_slugs {
+        normalize_slug(&slug)
+    } else {
+        slug
+    };
+
+    blog_posts::table
+        .filter(blog_posts::slug.eq(&slug))
+        .filter(publicly_visible())
+        .select(BlogPost::as_select())
+        .first(db)
+        .await
+        .map_err(|e| {
+            error!("Error fetching blog post by slug '{}': {}", slug, e);
+            AppError::NotFound
+        })
+}
+
+/// A published post by slug; every successful read counts as a view
 #[get("/api/blog/<slug>")]
 pub async fn get_blog_post_by_slug(
     mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    views: &State<BlogViewCounter>,
     slug: String,
 ) -> AppResult<Json<BlogPostDto>> {
-    let post: BlogPost = blog_posts::table
-        .filter(blog_posts::slug.eq(&slug))
-        .filter(blog_posts::published.eq(true))
+    let post = find_published_post(&mut db, config, slug).await?;
+
+    let post_tags = load_post_tags(&mut db, &[post.id])
+        .await?
+        .remove(&post.id)
+        .unwrap_or_default();
+    let images = load_images(&mut db, post.id).await?;
+    let author = load_author(&mut db, post.author_id).await?;
+    views.record(post.id);
+    Ok(Json(to_blog_post_dto(post, author, post_tags, images)))
+}
+
+/// The cover image of a post, the first of its images, in the size given
+/// by `size`
+#[get("/api/blog/<id>/image?<size>")]
+pub async fn get_blog_post_image(
+    mut db: Connection<MessagesDB>,
+    media: &State<Media>,
+    conditional: ConditionalHeaders,
+    accept: Option<&Accept>,
+    id: i64,
+    size: Option<&str>,
+) -> AppResult<CachedImage> {
+    let size = ImageSize::parse(size)?;
+    cover_image(&mut db, media, id, size, accept, &conditional).await
+}
+
+/// The cover image of a published post by its slug. Numeric slugs are
+/// taken as ids by the route above, which is tried first.
+#[get("/api/blog/<slug>/image?<size>", rank = 2)]
+pub async fn get_blog_post_image_by_slug(
+    mut db: Connection<MessagesDB>,
+    media: &State<Media>,
+    config: &State<AppConfig>,
+    conditional: ConditionalHeaders,
+    accept: Option<&Accept>,
+    slug: String,
+    size: Option<&str>,
+) -> AppResult<CachedImage> {
+    let size = ImageSize::parse(size)?;
+    let post = find_published_post(&mut db, config, slug).await?;
+    cover_image(&mut db, media, post.id, size, accept, &conditional).await
+}
+
+async fn cover_image(
+    db: &mut Connection<MessagesDB>,
+    media: &Media,
+    id: i64,
+    size: ImageSize,
+    accept: Option<&Accept>,
+    conditional: &ConditionalHeaders,
+) -> AppResult<CachedImage> {
+    let image_id: i64 = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq(id))
+        .order((blog_post_images::position.asc(), blog_post_images::id.asc()))
+        .select(blog_post_images::id)
+        .first(db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching the cover image of blog post {}: {}", id, e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound)?;
+
+    post_image_response(db, media, id, image_id, size, accept, conditional).await
+}
+
+/// Copy a post as an unpublished draft to use it as a template. The copy
+/// gets the tags and copies of the images of the original, and its content
+/// links to the copied images.
+#[post("/admin/api/blog/<id>/duplicate")]
+pub async fn duplicate_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<BlogPostDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let original: BlogPost = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
         .select(BlogPost::as_select())
         .first(&mut db)
         .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let title = format!("{}{COPY_TITLE_SUFFIX}", original.title);
+    let slug = resolve_slug(&mut db, None, &title, None).await?;
+    let copy = NewBlogPost {
+        title,
+        slug: slug.clone(),
+        excerpt: original.excerpt,
+        content: original.content,
+        word_count: original.word_count,
+        reading_time_minutes: original.reading_time_minutes,
+        meta_title: original.meta_title,
+        meta_description: original.meta_description,
+        canonical_url: original.canonical_url,
+        og_image: original.og_image,
+        author_id: Some(admin.id),
+        published: false,
+    };
+
+    let copy_id = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                diesel::insert_into(blog_posts::table)
+                    .values(&copy)
+                    .execute(&mut conn)
+                    .await?;
+                let copy_id: u64 = diesel::select(last_insert_id())
+                    .get_result(&mut conn)
+                    .await?;
+                let copy_id = copy_id as i64;
+
+                let assignments: Vec<NewBlogPostTag> = blog_post_tags::table
+                    .filter(blog_post_tags::blog_post_id.eq(id))
+                    .select(blog_post_tags::tag_id)
+                    .load::<i64>(&mut conn)
+                    .await?
+                    .into_iter()
+                    .map(|tag_id| NewBlogPostTag {
+                        blog_post_id: copy_id,
+                        tag_id,
+                    })
+                    .collect();
+                if !assignments.is_empty() {
+                    diesel::insert_into(blog_post_tags::table)
+                        .values(&assignments)
+                        .execute(&mut conn)
+                        .await?;
+                }
+
+                let urls = copy_post_images(conn, id, copy_id).await?;
+                if !urls.is_empty() {
+                    let rewrite = |text: &str| rewrite_image_urls(text, &urls);
+                    diesel::update(blog_posts::table.find(copy_id))
+                        .set((
+                            blog_posts::excerpt.eq(copy.excerpt.as_deref().map(rewrite)),
+                            blog_posts::content.eq(rewrite(&copy.content)),
+                            blog_posts::og_image.eq(copy.og_image.as_deref().map(rewrite)),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                }
+                Ok::<_, diesel::result::Error>(copy_id)
+            })
+        })
+        .await
         .map_err(|e| {
-            error!("Error fetching blog post by slug '{}': {}", slug, e);
-            AppError::NotFound
+            error!("Error duplicating blog post {}: {}", id, e);
+            map_slug_write_error(e, &slug)
         })?;
 
-    let dto = BlogPostDto {
-        id: post.id,
-        title: post.title,
-        slug: post.slug,
-        excerpt: post.excerpt,
-        content: post.content,
-        image_mime: post.image_mime,
-        published: post.published,
-        created_at: post.created_at,
-        updated_at: post.updated_at,
-    };
+    let duplicate: BlogPost = blog_posts::table
+        .find(copy_id)
+        .select(BlogPost::as_select())
+        .first(&mut db)
+        .await?;
+    let post_tags = load_post_tags(&mut db, &[copy_id])
+        .await?
+        .remove(&copy_id)
+        .unwrap_or_default();
+    let images = load_images(&mut db, copy_id).await?;
+    let author = load_author(&mut db, duplicate.author_id).await?;
 
-    Ok(Json(dto))
+    record_audit(&mut db, &admin, "blog_post.duplicate", "blog_post", copy_id).await;
+    info!(
+        "Admin '{}' duplicated blog post {} as {}",
+        admin.username, id, copy_id
+    );
+    Ok(Json(to_blog_post_dto(duplicate, author, post_tags, images)))
 }
 
-#[get("/api/blog/<id>/image")]
-pub async fn get_blog_post_image(
+/// Hide a post from the public site and the working list without deleting
+/// it. Archiving an archived post keeps its original archive time.
+#[post("/admin/api/blog/<id>/archive")]
+pub async fn archive_blog_post(
     mut db: Connection<MessagesDB>,
+    admin: AdminUser,
     id: i64,
-) -> AppResult<(ContentType, Vec<u8>)> {
-    let post: BlogPost = blog_posts::table
+) -> AppResult<Status> {
+    set_archived(&mut db, &admin, id, true).await
+}
+
+/// Bring an archived post back; it is public again if it is published
+#[post("/admin/api/blog/<id>/restore")]
+pub async fn restore_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    set_archived(&mut db, &admin, id, false).await
+}
+
+/// Feature a post on the homepage or stop featuring it. Drafts and archived
+/// posts can be featured ahead of time; they are listed once public.
+#[post("/admin/api/blog/<id>/feature", format = "json", data = "<request>")]
+pub async fn feature_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<BlogFeatureRequest>,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let featured_order = if request.featured {
+        request.featured_order
+    } else {
+        0
+    };
+    blog_posts::table
         .find(id)
-        .first(&mut db)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(blog_posts::id)
+        .first::<i64>(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    diesel::update(blog_posts::table.find(id))
+        .set((
+            blog_posts::featured.eq(request.featured),
+            blog_posts::featured_order.eq(featured_order),
+        ))
+        .execute(&mut db)
         .await
         .map_err(|e| {
-            error!("Error fetching blog post {} for image: {}", id, e);
-            AppError::NotFound
+            error!("Error changing whether blog post {} is featured: {}", id, e);
+            AppError::from(e)
         })?;
 
-    if let Some(image_bytes) = post.image {
-        let content_type = post
-            .image_mime
-            .and_then(|m| ContentType::parse_flexible(&m))
-            .unwrap_or(ContentType::JPEG);
+    let action = if request.featured {
+        "blog_post.feature"
+    } else {
+        "blog_post.unfeature"
+    };
+    record_audit(&mut db, &admin, action, "blog_post", id).await;
+    info!(
+        "Admin '{}' set blog post {} featured: {}",
+        admin.username, id, request.featured
+    );
+    Ok(Status::Ok)
+}
+
+async fn set_archived(
+    db: &mut Connection<MessagesDB>,
+    admin: &AdminUser,
+    id: i64,
+    archived: bool,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
 
-        Ok((content_type, image_bytes))
+    let archived_at: Option<NaiveDateTime> = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(blog_posts::archived_at)
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    if archived_at.is_some() == archived {
+        return Ok(Status::Ok);
+    }
+
+    let new_archived_at = archived.then(|| Utc::now().naive_utc());
+    diesel::update(blog_posts::table.find(id))
+        .set(blog_posts::archived_at.eq(new_archived_at))
+        .execute(db)
+        .await
+        .map_err(|e| {
+            error!(
+                "Error changing the archive state of blog post {}: {}",
+                id, e
+            );
+            AppError::from(e)
+        })?;
+
+    let (action, verb) = if archived {
+        ("blog_post.archive", "archived")
     } else {
-        Err(AppError::NotFound)
+        ("blog_post.unarchive", "restored")
+    };
+    record_audit(db, admin, action, "blog_post", id).await;
+    info!("Admin '{}' {} blog post {}", admin.username, verb, id);
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_filter() {
+        assert_eq!(status_filter(None).unwrap(), StatusFilter::Working);
+        assert_eq!(status_filter(Some(" ")).unwrap(), StatusFilter::Working);
+        assert_eq!(status_filter(Some("all")).unwrap(), StatusFilter::Working);
+        assert_eq!(status_filter(Some("draft")).unwrap(), StatusFilter::Draft);
+        assert_eq!(
+            status_filter(Some("published")).unwrap(),
+            StatusFilter::Published
+        );
+        assert_eq!(
+            status_filter(Some("archived")).unwrap(),
+            StatusFilter::Archived
+        );
+        assert!(status_filter(Some("scheduled")).is_err());
+        assert!(status_filter(Some("deleted")).is_err());
     }
 }
@@ -0,0 +1,106 @@
+// Authors of blog posts: the admin users who wrote them
+
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{BlogAuthor, BlogAuthorSummary};
+use crate::routes::admin::blog::publicly_visible;
+use crate::schema::{admin_users, blog_posts};
+
+/// Gravatar serves a generic silhouette for emails without an image
+const GRAVATAR_URL: &str = "https://www.gravatar.com/avatar";
+
+/// Gravatar image of an email, addressed by the SHA-256 of the trimmed,
+/// lowercased address
+fn avatar_url(email: Option<&str>) -> Option<String> {
+    let email = email.map(str::trim).filter(|email| !email.is_empty())?;
+    let hash = Sha256::digest(email.to_lowercase().as_bytes());
+    Some(format!("{GRAVATAR_URL}/{hash:x}?d=mp"))
+}
+
+fn to_author(id: i64, username: String, email: Option<String>) -> BlogAuthor {
+    BlogAuthor {
+        id,
+        name: username,
+        avatar_url: avatar_url(email.as_deref()),
+    }
+}
+
+/// The author of a post, if it has one that still exists
+pub(crate) async fn load_author(
+    conn: &mut AsyncMysqlConnection,
+    author_id: Option<i64>,
+) -> Result<Option<BlogAuthor>, diesel::result::Error> {
+    let Some(author_id) = author_id else {
+        return Ok(None);
+    };
+    let author: Option<(i64, String, Option<String>)> = admin_users::table
+        .find(author_id)
+        .select((admin_users::id, admin_users::username, admin_users::email))
+        .first(conn)
+        .await
+        .optional()?;
+    Ok(author.map(|(id, username, email)| to_author(id, username, email)))
+}
+
+/// Authors of published posts with their number of posts, most posts first
+#[get("/api/blog/authors")]
+pub async fn list_blog_authors(
+    mut db: Connection<MessagesDB>,
+) -> AppResult<Json<Vec<BlogAuthorSummary>>> {
+    let post_count = diesel::dsl::count(blog_posts::id);
+    let authors: Vec<(i64, String, Option<String>, i64)> = blog_posts::table
+        .inner_join(admin_users::table)
+        .filter(publicly_visible())
+        .group_by((admin_users::id, admin_users::username, admin_users::email))
+        .order((post_count.desc(), admin_users::username.asc()))
+        .select((
+            admin_users::id,
+            admin_users::username,
+            admin_users::email,
+            post_count,
+        ))
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading blog authors: {}", e);
+            AppError::from(e)
+        })?;
+
+    Ok(Json(
+        authors
+            .into_iter()
+            .map(|(id, username, email, post_count)| BlogAuthorSummary {
+                author: to_author(id, username, email),
+                post_count,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avatar_url() {
+        let expected = "https://www.gravatar.com/avatar/\
+            973dfe463ec85785f5f95af5ba3906eedb2d931c24e69824a89ea65dba4e813b?d=mp";
+        assert_eq!(
+            avatar_url(Some("test@example.com")).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            avatar_url(Some(" Test@Example.com ")).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(avatar_url(Some("  ")), None);
+        assert_eq!(avatar_url(None), None);
+    }
+}
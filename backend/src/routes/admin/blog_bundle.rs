@@ -0,0 +1,743 @@
+// Export and import of blog posts as a zip of Markdown files with front
+// matter, laid out like the page bundles of static site generators
+
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rocket::State;
+use rocket::form::Form;
+use rocket::http::{ContentType, Header};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::markdown::{reading_time_minutes, word_count};
+use crate::media::{Media, StoredImage};
+use crate::models::{
+    AdminImportBlogBundleMultipart, BlogImportedPost, BlogPost, NewBlogPost, NewTag, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::blog::resolve_slug;
+use crate::routes::admin::blog_images::{
+    MAX_IMAGES_PER_POST, MEDIA_PREFIX, image_url, load_post_images, new_image, rewrite_image_urls,
+};
+use crate::routes::admin::blog_seo::seo_fields;
+use crate::routes::admin::tags::{load_post_tags, parse_tag_list, set_post_tags};
+use crate::sanitize::{HtmlPolicy, sanitize_markdown};
+use crate::schema::{blog_post_images, blog_posts};
+use crate::utils::{ImageSettings, ProcessedImage, process_image_bytes, read_upload};
+
+/// Name of the Markdown file in the folder of each exported post
+const POST_FILE: &str = "index.md";
+/// Format of exported dates; the stored timestamps have no time zone
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+const MAX_IMPORT_POSTS: usize = 500;
+/// Size of a single file of an import once unpacked
+const MAX_IMPORT_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Size of all files read from an import once unpacked
+const MAX_IMPORT_TOTAL_BYTES: u64 = 200 * 1024 * 1024;
+
+/// YAML front matter of a post file. `draft`, `date` and `lastmod` follow
+/// the static site generators; the other fields use the names of the API.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+struct FrontMatter {
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lastmod: Option<String>,
+    draft: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excerpt: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canonical_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    og_image: Option<String>,
+    /// Image files next to the post file, the cover first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+/// A post as stored in an archive
+#[derive(Debug, Clone, PartialEq)]
+struct BundledPost {
+    /// Path of the Markdown file in the archive
+    path: String,
+    front: FrontMatter,
+    content: String,
+    /// Data of the files named in `front.images`, in the same order
+    images: Vec<Vec<u8>>,
+}
+
+/// A validated post that is ready to be saved
+struct ImportedPost {
+    post: NewBlogPost,
+    created_at: Option<NaiveDateTime>,
+    updated_at: Option<NaiveDateTime>,
+    archived: bool,
+    tags: Vec<NewTag>,
+    /// Name the text refers to each image by, with the processed image
+    images: Vec<(String, ProcessedImage)>,
+}
+
+/// A zip archive sent as a file download
+#[derive(Responder)]
+pub struct BlogBundle {
+    data: Vec<u8>,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::InvalidInput(message)
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    error!("Error writing the blog export: {}", e);
+    AppError::Io(std::io::Error::other(e))
+}
+
+/// Folder of a path in an archive, empty at the top level
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    let name = name.strip_prefix("./").unwrap_or(name);
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+fn is_image_file(name: &str) -> bool {
+    name.rsplit_once('.')
+        .and_then(|(_, extension)| ContentType::from_extension(extension))
+        .is_some_and(|content_type| content_type.top() == "image")
+}
+
+/// Name of the `position`th image of a post in an export
+fn image_file_name(position: usize, image_mime: &str) -> String {
+    let extension = match image_mime {
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "jpg",
+    };
+    format!("image-{}.{extension}", position + 1)
+}
+
+/// Dates of the front matter: RFC 3339, the export format or a plain date
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, DATE_FORMAT))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn front_matter_date(
+    value: Option<&str>,
+    field: &str,
+    path: &str,
+) -> AppResult<Option<NaiveDateTime>> {
+    value
+        .map(|value| {
+            parse_date(value).ok_or_else(|| {
+                invalid(format!(
+                    "{path}: {field} must be a date like 2026-03-02 or 2026-03-02T09:30:00."
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Split a Markdown file into its YAML front matter and its content
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let rest = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let content = rest[offset + line.len()..].trim_start_matches(['\r', '\n']);
+            return Some((&rest[..offset], content));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn write_post(zip: &mut ZipWriter<Cursor<Vec<u8>>>, post: &BundledPost) -> AppResult<()> {
+    let yaml = serde_yaml_ng::to_string(&post.front).map_err(|e| {
+        error!("Error writing the front matter of {}: {}", post.path, e);
+        AppError::Io(std::io::Error::other(e))
+    })?;
+    zip.start_file(post.path.as_str(), SimpleFileOptions::default())
+        .map_err(zip_error)?;
+    zip.write_all(format!("---\n{yaml}---\n\n{}", post.content).as_bytes())?;
+
+    // Images are stored compressed already
+    let dir = parent_dir(&post.path);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, data) in post.front.images.iter().zip(&post.images) {
+        zip.start_file(join_path(dir, name), stored)
+            .map_err(zip_error)?;
+        zip.write_all(data)?;
+    }
+    Ok(())
+}
+
+/// Read a file of an archive, counting its size against `budget`
+fn read_file(
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    name: &str,
+    budget: &mut u64,
+) -> AppResult<Vec<u8>> {
+    let file = archive
+        .by_name(name)
+        .map_err(|_| invalid(format!("{name} could not be read from the archive.")))?;
+    let limit = MAX_IMPORT_FILE_BYTES.min(*budget);
+    let mut data = Vec::new();
+    file.take(limit + 1)
+        .read_to_end(&mut data)
+        .map_err(|_| invalid(format!("{name} could not be read from the archive.")))?;
+    if data.len() as u64 > limit {
+        return Err(invalid(format!("{name} is too large once unpacked.")));
+    }
+    *budget -= data.len() as u64;
+    Ok(data)
+}
+
+/// Posts of an archive: every `.md` file with front matter and the images
+/// it lists. A post file without `images` takes the image files in its
+/// folder when it is an `index.md`, and its slug defaults to the name of
+/// its folder or file.
+fn read_bundle(data: Vec<u8>) -> AppResult<Vec<BundledPost>> {
+    let mut archive = ZipArchive::new(Cursor::new(data))
+        .map_err(|_| invalid("The file is not a zip archive.".to_string()))?;
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| {
+            !name.ends_with('/')
+                && !name.starts_with("__MACOSX/")
+                && !name.split('/').any(|part| part.starts_with('.'))
+        })
+        .map(str::to_string)
+        .collect();
+    names.sort();
+
+    let post_files: Vec<String> = names
+        .iter()
+        .filter(|name| name.ends_with(".md"))
+        .cloned()
+        .collect();
+    if post_files.len() > MAX_IMPORT_POSTS {
+        return Err(invalid(format!(
+            "An import can contain at most {MAX_IMPORT_POSTS} posts."
+        )));
+    }
+
+    let mut budget = MAX_IMPORT_TOTAL_BYTES;
+    let mut posts = Vec::with_capacity(post_files.len());
+    for path in post_files {
+        let text = String::from_utf8(read_file(&mut archive, &path, &mut budget)?)
+            .map_err(|_| invalid(format!("{path} is not UTF-8 text.")))?;
+        let (yaml, content) = split_front_matter(&text)
+            .ok_or_else(|| invalid(format!("{path} does not start with front matter.")))?;
+        let mut front: FrontMatter = serde_yaml_ng::from_str(yaml)
+            .map_err(|e| invalid(format!("{path}: invalid front matter: {e}")))?;
+
+        let dir = parent_dir(&path);
+        let file = path.rsplit('/').next().unwrap_or(&path);
+        let is_bundle = file == POST_FILE;
+        if front.slug.is_none() {
+            let name = if is_bundle {
+                dir.rsplit('/').next().unwrap_or(dir)
+            } else {
+                file.trim_end_matches(".md")
+            };
+            front.slug = Some(name.to_string()).filter(|name| !name.is_empty());
+        }
+        if front.images.is_empty() && is_bundle {
+            front.images = names
+                .iter()
+                .filter(|name| parent_dir(name) == dir && is_image_file(name))
+                .map(|name| name.rsplit('/').next().unwrap_or(name).to_string())
+                .collect();
+        }
+        if front.images.len() > MAX_IMAGES_PER_POST as usize {
+            return Err(invalid(format!(
+                "{path}: a post can have at most {MAX_IMAGES_PER_POST} images."
+            )));
+        }
+
+        let mut images = Vec::with_capacity(front.images.len());
+        for name in &front.images {
+            let image_path = join_path(dir, name);
+            if !names.contains(&image_path) {
+                return Err(invalid(format!(
+                    "{path}: image {name} is missing from the archive."
+                )));
+            }
+            images.push(read_file(&mut archive, &image_path, &mut budget)?);
+        }
+
+        posts.push(BundledPost {
+            path,
+            front,
+            content: content.to_string(),
+            images,
+        });
+    }
+    Ok(posts)
+}
+
+/// Validate a post of an import and process its images. Imported posts are
+/// credited to the admin importing them, and their HTML is sanitized like
+/// that of posts saved in the editor.
+async fn prepare_post(
+    db: &mut Connection<MessagesDB>,
+    policy: &HtmlPolicy,
+    image_settings: &ImageSettings,
+    bundled: BundledPost,
+    author_id: i64,
+) -> AppResult<ImportedPost> {
+    let BundledPost {
+        path,
+        front,
+        content,
+        images,
+    } = bundled;
+    let title = front.title.trim().to_string();
+    if title.is_empty() {
+        return Err(invalid(format!("{path}: title is required.")));
+    }
+    let created_at = front_matter_date(front.date.as_deref(), "date", &path)?;
+    let updated_at = front_matter_date(front.lastmod.as_deref(), "lastmod", &path)?;
+    let tags = parse_tag_list(&front.tags.join(","))?;
+    let seo = seo_fields(
+        front.meta_title.as_deref(),
+        front.meta_description.as_deref(),
+        front.canonical_url.as_deref(),
+        front.og_image.as_deref(),
+    )?;
+    let slug = resolve_slug(db, front.slug.as_deref(), &title, None).await?;
+
+    let dir = parent_dir(&path);
+    let mut processed = Vec::with_capacity(images.len());
+    for (name, data) in front.images.into_iter().zip(images) {
+        let content_type = name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| ContentType::from_extension(extension));
+        let image = process_image_bytes(&data, content_type.as_ref(), image_settings).map_err(
+            |e| match e {
+                AppError::InvalidInput(reason) => {
+                    invalid(format!("{}: {reason}", join_path(dir, &name)))
+                }
+                other => other,
+            },
+        )?;
+        processed.push((name, image));
+    }
+
+    let content = sanitize_markdown(&content, policy);
+    let words = word_count(&content);
+    Ok(ImportedPost {
+        post: NewBlogPost {
+            title,
+            slug,
+            excerpt: front
+                .excerpt
+                .filter(|excerpt| !excerpt.trim().is_empty())
+                .map(|excerpt| sanitize_markdown(&excerpt, policy)),
+            content,
+            word_count: words,
+            reading_time_minutes: reading_time_minutes(words),
+            meta_title: seo.meta_title,
+            meta_description: seo.meta_description,
+            canonical_url: seo.canonical_url,
+            og_image: seo.og_image,
+            author_id: Some(author_id),
+            published: !front.draft,
+        },
+        created_at,
+        updated_at,
+        archived: front.archived,
+        tags,
+        images: processed,
+    })
+}
+
+/// Save an imported post with its tags and images, already put into the
+/// media store in the order of `imported.images`, and point the image names
+/// in its text to the saved images. Run it inside a transaction.
+async fn save_post(
+    conn: &mut AsyncMysqlConnection,
+    imported: ImportedPost,
+    images: &[StoredImage],
+) -> Result<BlogImportedPost, diesel::result::Error> {
+    diesel::insert_into(blog_posts::table)
+        .values(&imported.post)
+        .execute(conn)
+        .await?;
+    let id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+    let id = id as i64;
+    set_post_tags(conn, id, &imported.tags).await?;
+
+    let image_count = imported.images.len();
+    let mut urls = Vec::with_capacity(image_count * 2);
+    for (position, ((name, _), image)) in imported.images.into_iter().zip(images).enumerate() {
+        diesel::insert_into(blog_post_images::table)
+            .values(&new_image(id, image, position as i32))
+            .execute(conn)
+            .await?;
+        let image_id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+        let url = image_url(id, image_id as i64);
+        urls.push((format!("./{name}"), url.clone()));
+        urls.push((name, url));
+    }
+
+    let now = Utc::now().naive_utc();
+    let rewrite = |text: &str| rewrite_image_urls(text, &urls);
+    let post = imported.post;
+    diesel::update(blog_posts::table.find(id))
+        .set((
+            blog_posts::excerpt.eq(post.excerpt.as_deref().map(rewrite)),
+            blog_posts::content.eq(rewrite(&post.content)),
+            blog_posts::og_image.eq(post.og_image.as_deref().map(rewrite)),
+            blog_posts::archived_at.eq(imported.archived.then_some(now)),
+            blog_posts::created_at.eq(imported.created_at.unwrap_or(now)),
+            blog_posts::updated_at.eq(imported.updated_at.or(imported.created_at).unwrap_or(now)),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(BlogImportedPost {
+        id,
+        title: post.title,
+        slug: post.slug,
+        published: post.published,
+        images: image_count,
+    })
+}
+
+/// Every post outside the trash, including drafts and archived posts, as a zip with a folder
+/// per post holding its `index.md` and images. Image URLs in the text are
+/// replaced by the names of the image files.
+#[get("/admin/api/blog/export")]
+pub async fn export_blog_posts(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    media: &State<Media>,
+) -> AppResult<BlogBundle> {
+    admin.require(Permission::ManageContent)?;
+
+    let posts: Vec<BlogPost> = blog_posts::table
+        .filter(blog_posts::deleted_at.is_null())
+        .order(blog_posts::id.asc())
+        .select(BlogPost::as_select())
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading blog posts for export: {}", e);
+            AppError::from(e)
+        })?;
+    let ids: Vec<i64> = posts.iter().map(|post| post.id).collect();
+    let mut tags = load_post_tags(&mut db, &ids).await?;
+    let mut images = load_post_images(&mut db, &ids).await?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for post in &posts {
+        let post_images = images.remove(&post.id).unwrap_or_default();
+        let files: Vec<String> = post_images
+            .iter()
+            .enumerate()
+            .map(|(position, image)| image_file_name(position, &image.image.image_mime))
+            .collect();
+        let urls: Vec<(String, String)> = post_images
+            .iter()
+            .zip(&files)
+            .map(|(image, file)| (image.url.clone(), file.clone()))
+            .collect();
+        let rewrite = |text: &str| rewrite_image_urls(text, &urls);
+
+        let mut data = Vec::with_capacity(post_images.len());
+        for image in &post_images {
+            let key: String = blog_post_images::table
+                .find(image.image.id)
+                .select(blog_post_images::storage_key)
+                .first(&mut db)
+                .await?;
+            let image_data = media
+                .get(&mut db, &key, &image.image.image_mime)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Storage(format!("image data {key} is missing from the media store"))
+                })?;
+            data.push(image_data);
+        }
+
+        let bundled = BundledPost {
+            path: format!("{}/{POST_FILE}", post.slug),
+            front: FrontMatter {
+                title: post.title.clone(),
+                slug: Some(post.slug.clone()),
+                date: Some(post.created_at.format(DATE_FORMAT).to_string()),
+                lastmod: Some(post.updated_at.format(DATE_FORMAT).to_string()),
+                draft: !post.published,
+                archived: post.archived_at.is_some(),
+                excerpt: post.excerpt.as_deref().map(rewrite),
+                tags: tags
+                    .remove(&post.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|tag| tag.name)
+                    .collect(),
+                meta_title: post.meta_title.clone(),
+                meta_description: post.meta_description.clone(),
+                canonical_url: post.canonical_url.clone(),
+                og_image: post.og_image.as_deref().map(rewrite),
+                images: files,
+            },
+            content: rewrite(&post.content),
+            images: data,
+        };
+        write_post(&mut zip, &bundled)?;
+    }
+    let data = zip.finish().map_err(zip_error)?.into_inner();
+
+    record_audit(&mut db, &admin, "blog_post.export", "blog_post", "all").await;
+    info!(
+        "Admin '{}' exported {} blog posts",
+        admin.username,
+        posts.len()
+    );
+
+    let filename = format!("blog-{}.zip", Utc::now().format("%Y-%m-%d"));
+    Ok(BlogBundle {
+        data,
+        content_type: ContentType::ZIP,
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{filename}\""),
+        ),
+    })
+}
+
+/// Create posts from a zip in the export format. Everything is validated
+/// before anything is saved, and either all posts are created or none.
+#[post("/admin/api/blog/import", data = "<upload>")]
+pub async fn import_blog_posts(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    policy: &State<HtmlPolicy>,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
+    upload: Form<AdminImportBlogBundleMultipart<'_>>,
+) -> AppResult<Json<Vec<BlogImportedPost>>> {
+    admin.require(Permission::ManageContent)?;
+
+    let bundled = read_bundle(read_upload(&upload.file).await?)?;
+    if bundled.is_empty() {
+        return Err(invalid(
+            "The archive does not contain any Markdown files.".to_string(),
+        ));
+    }
+
+    let mut posts = Vec::with_capacity(bundled.len());
+    let mut slugs = HashSet::new();
+    for post in bundled {
+        let post = prepare_post(&mut db, policy, image_settings, post, admin.id).await?;
+        if !slugs.insert(post.post.slug.clone()) {
+            return Err(AppError::SlugConflict {
+                slug: post.post.slug,
+                suggested_slug: None,
+            });
+        }
+        posts.push(post);
+    }
+
+    // Images go to the media store first and are released again when the
+    // import fails
+    let mut stored_keys = Vec::new();
+    let mut stored = Vec::with_capacity(posts.len());
+    for post in &posts {
+        let mut images = Vec::with_capacity(post.images.len());
+        for (_, image) in &post.images {
+            match media.store_image(&mut db, MEDIA_PREFIX, image).await {
+                Ok(image) => {
+                    stored_keys.push(image.key.clone());
+                    images.push(image);
+                }
+                Err(e) => {
+                    media.release_images(&mut db, &stored_keys).await;
+                    return Err(e);
+                }
+            }
+        }
+        stored.push(images);
+    }
+
+    let imported = db
+        .transaction(|conn| {
+            Box::pin(async move {
+                let mut imported = Vec::with_capacity(posts.len());
+                for (post, images) in posts.into_iter().zip(stored) {
+                    imported.push(save_post(conn, post, &images).await?);
+                }
+                Ok::<_, diesel::result::Error>(imported)
+            })
+        })
+        .await;
+    let imported = match imported {
+        Ok(imported) => imported,
+        Err(e) => {
+            error!("Error importing blog posts: {}", e);
+            media.release_images(&mut db, &stored_keys).await;
+            return Err(e.into());
+        }
+    };
+
+    for post in &imported {
+        record_audit(&mut db, &admin, "blog_post.import", "blog_post", post.id).await;
+    }
+    info!(
+        "Admin '{}' imported {} blog posts",
+        admin.username,
+        imported.len()
+    );
+    Ok(Json(imported))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundled(slug: &str) -> BundledPost {
+        BundledPost {
+            path: format!("{slug}/{POST_FILE}"),
+            front: FrontMatter {
+                title: "Zateplenie: tipy".to_string(),
+                slug: Some(slug.to_string()),
+                date: Some("2026-03-02T09:30:00".to_string()),
+                draft: true,
+                tags: vec!["fasády".to_string(), "tipy".to_string()],
+                images: vec!["image-1.jpg".to_string()],
+                ..FrontMatter::default()
+            },
+            content: "# Nadpis\n\n![Fasáda](image-1.jpg)\n".to_string(),
+            images: vec![vec![1, 2, 3]],
+        }
+    }
+
+    fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let posts = vec![bundled("zateplenie"), bundled("strechy")];
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for post in &posts {
+            write_post(&mut zip, post).unwrap();
+        }
+        let data = zip.finish().unwrap().into_inner();
+
+        let mut read = read_bundle(data).unwrap();
+        read.sort_by(|a, b| b.path.cmp(&a.path));
+        assert_eq!(read, posts);
+    }
+
+    #[test]
+    fn test_read_bundle_defaults() {
+        let data = zip_of(&[
+            ("strechy/index.md", b"---\ntitle: Strechy\n---\nText"),
+            ("strechy/b.png", b"png"),
+            ("strechy/a.jpg", b"jpg"),
+            ("poznamka.md", b"---\ntitle: Pozn\xc3\xa1mka\n---\n"),
+            ("__MACOSX/strechy/._index.md", b"junk"),
+        ]);
+        let posts = read_bundle(data).unwrap();
+        assert_eq!(posts.len(), 2);
+
+        assert_eq!(posts[0].path, "poznamka.md");
+        assert_eq!(posts[0].front.slug.as_deref(), Some("poznamka"));
+        assert!(posts[0].front.images.is_empty());
+
+        assert_eq!(posts[1].front.slug.as_deref(), Some("strechy"));
+        assert_eq!(posts[1].front.images, vec!["a.jpg", "b.png"]);
+        assert_eq!(posts[1].images, vec![b"jpg".to_vec(), b"png".to_vec()]);
+        assert_eq!(posts[1].content, "Text");
+        assert!(!posts[1].front.draft);
+    }
+
+    #[test]
+    fn test_read_bundle_errors() {
+        assert!(read_bundle(b"not a zip".to_vec()).is_err());
+        assert!(read_bundle(zip_of(&[("a.md", b"# No front matter")])).is_err());
+        assert!(
+            read_bundle(zip_of(&[(
+                "a/index.md",
+                b"---\ntitle: A\nimages: [missing.jpg]\n---\n"
+            )]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_split_front_matter() {
+        assert_eq!(
+            split_front_matter("---\r\ntitle: A\r\n---\r\n\r\nText\n"),
+            Some(("title: A\r\n", "Text\n"))
+        );
+        assert_eq!(split_front_matter("title: A\n---\nText"), None);
+        assert_eq!(split_front_matter("---\ntitle: A\n"), None);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let expected =
+            NaiveDateTime::parse_from_str("2026-03-02 09:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(parse_date("2026-03-02T09:30:00"), Some(expected));
+        assert_eq!(parse_date("2026-03-02T10:30:00+01:00"), Some(expected));
+        assert_eq!(
+            parse_date("2026-03-02"),
+            Some(expected.date().and_hms_opt(0, 0, 0).unwrap())
+        );
+        assert_eq!(parse_date("March 2"), None);
+    }
+}
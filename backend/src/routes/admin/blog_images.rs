@@ -0,0 +1,470 @@
+// Image galleries of blog posts
+
+use std::collections::HashMap;
+
+use diesel::sql_types::BigInt;
+use rocket::State;
+use rocket::form::Form;
+use rocket::http::{Accept, Status};
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::media::{Media, StoredImage};
+use crate::models::{
+    AdminBlogPostImageOrderRequest, AdminUploadBlogPostImageMultipart, BlogPostImageDto,
+    BlogPostImageInfo, NewBlogPostImage, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::images::{CachedImage, stored_image_response};
+use crate::routes::feeds::ConditionalHeaders;
+use crate::schema::{blog_post_images, blog_posts};
+use crate::utils::{ImageSettings, ImageSize, process_image_upload};
+
+/// Media store keys of blog post images start with this
+pub(crate) const MEDIA_PREFIX: &str = "blog";
+
+pub(crate) const MAX_IMAGES_PER_POST: i64 = 50;
+
+/// Public URL of an image; it does not change when images are reordered
+pub(crate) fn image_url(post_id: i64, image_id: i64) -> String {
+    format!("/api/blog/{post_id}/images/{image_id}")
+}
+
+fn to_image_dto(image: BlogPostImageInfo) -> BlogPostImageDto {
+    BlogPostImageDto {
+        url: image_url(image.blog_post_id, image.id),
+        image,
+    }
+}
+
+/// Check that `requested` lists each of the `current` image ids exactly once
+fn check_image_order(current: &[i64], requested: &[i64]) -> AppResult<()> {
+    let mut current = current.to_vec();
+    let mut requested = requested.to_vec();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(AppError::InvalidInput(
+            "The new order must list every image of the post exactly once.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Replace image URLs in `text` using `urls` of `(old, new)` pairs. A URL
+/// followed by another digit is a different image and is left alone.
+pub(crate) fn rewrite_image_urls(text: &str, urls: &[(String, String)]) -> String {
+    let mut text = text.to_string();
+    for (old, new) in urls {
+        let mut rewritten = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(found) = rest.find(old.as_str()) {
+            let end = found + old.len();
+            rewritten.push_str(&rest[..found]);
+            if rest[end..].starts_with(|c: char| c.is_ascii_digit()) {
+                rewritten.push_str(old);
+            } else {
+                rewritten.push_str(new);
+            }
+            rest = &rest[end..];
+        }
+        rewritten.push_str(rest);
+        text = rewritten;
+    }
+    text
+}
+
+/// Copy every image of post `from` to post `to`, keeping their order. The
+/// copies refer to the same data in the media store. Returns the
+/// `(old, new)` URL of each image. Run it inside a transaction.
+pub(crate) async fn copy_post_images(
+    conn: &mut AsyncMysqlConnection,
+    from: i64,
+    to: i64,
+) -> Result<Vec<(String, String)>, diesel::result::Error> {
+    let image_ids: Vec<i64> = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq(from))
+        .order((blog_post_images::position.asc(), blog_post_images::id.asc()))
+        .select(blog_post_images::id)
+        .load(conn)
+        .await?;
+
+    let mut urls = Vec::with_capacity(image_ids.len());
+    for image_id in image_ids {
+        diesel::insert_into(blog_post_images::table)
+            .values(blog_post_images::table.find(image_id).select((
+                to.into_sql::<BigInt>(),
+                blog_post_images::storage_key,
+                blog_post_images::image_mime,
+                blog_post_images::image_hash,
+                blog_post_images::position,
+            )))
+            .into_columns((
+                blog_post_images::blog_post_id,
+                blog_post_images::storage_key,
+                blog_post_images::image_mime,
+                blog_post_images::image_hash,
+                blog_post_images::position,
+            ))
+            .execute(conn)
+            .await?;
+        let copy_id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+        urls.push((image_url(from, image_id), image_url(to, copy_id as i64)));
+    }
+    Ok(urls)
+}
+
+/// Images of the given blog posts by post id, each in display order with
+/// the cover first
+pub(crate) async fn load_post_images(
+    conn: &mut AsyncMysqlConnection,
+    post_ids: &[i64],
+) -> Result<HashMap<i64, Vec<BlogPostImageDto>>, diesel::result::Error> {
+    let mut by_post: HashMap<i64, Vec<BlogPostImageDto>> = HashMap::new();
+    if post_ids.is_empty() {
+        return Ok(by_post);
+    }
+
+    let rows = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq_any(post_ids))
+        .order((
+            blog_post_images::blog_post_id.asc(),
+            blog_post_images::position.asc(),
+            blog_post_images::id.asc(),
+        ))
+        .select(BlogPostImageInfo::as_select())
+        .load(conn)
+        .await?;
+    for image in rows {
+        by_post
+            .entry(image.blog_post_id)
+            .or_default()
+            .push(to_image_dto(image));
+    }
+    Ok(by_post)
+}
+
+/// An image of a post put into the media store, ready to be inserted
+pub(crate) fn new_image(owner_id: i64, image: &StoredImage, position: i32) -> NewBlogPostImage {
+    NewBlogPostImage {
+        blog_post_id: owner_id,
+        storage_key: image.key.clone(),
+        image_mime: image.mime.clone(),
+        image_hash: image.hash.clone(),
+        position,
+    }
+}
+
+/// Make an image the cover of a post, replacing the current cover or adding
+/// it in front when the post has no images. Returns the storage key of the
+/// replaced image, to be released once the change is committed.
+pub(crate) async fn set_cover_image(
+    conn: &mut AsyncMysqlConnection,
+    post_id: i64,
+    image: &StoredImage,
+) -> Result<Option<String>, diesel::result::Error> {
+    let cover: Option<(i64, String)> = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq(post_id))
+        .order((blog_post_images::position.asc(), blog_post_images::id.asc()))
+        .select((blog_post_images::id, blog_post_images::storage_key))
+        .first(conn)
+        .await
+        .optional()?;
+
+    match cover {
+        Some((cover_id, replaced_key)) => {
+            diesel::update(blog_post_images::table.find(cover_id))
+                .set((
+                    blog_post_images::storage_key.eq(&image.key),
+                    blog_post_images::image_mime.eq(&image.mime),
+                    blog_post_images::image_hash.eq(&image.hash),
+                ))
+                .execute(conn)
+                .await?;
+            Ok(Some(replaced_key))
+        }
+        None => {
+            diesel::insert_into(blog_post_images::table)
+                .values(&new_image(post_id, image, 0))
+                .execute(conn)
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn require_post(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<()> {
+    blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(blog_posts::id)
+        .first::<i64>(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    Ok(())
+}
+
+async fn post_images(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Vec<BlogPostImageDto>> {
+    Ok(load_post_images(db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default())
+}
+
+/// Images of a post in display order, drafts included
+#[get("/admin/api/blog/<id>/images")]
+pub async fn list_blog_post_images(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<Vec<BlogPostImageDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_post(&mut db, id).await?;
+    Ok(Json(post_images(&mut db, id).await?))
+}
+
+/// Add an image after the existing images of a post
+#[post("/admin/api/blog/<id>/images", data = "<upload>")]
+pub async fn upload_blog_post_image(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
+    id: i64,
+    upload: Form<AdminUploadBlogPostImageMultipart<'_>>,
+) -> AppResult<Json<BlogPostImageDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_post(&mut db, id).await?;
+    let (count, last_position): (i64, Option<i32>) = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq(id))
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::max(blog_post_images::position),
+        ))
+        .first(&mut db)
+        .await?;
+    if count >= MAX_IMAGES_PER_POST {
+        return Err(AppError::InvalidInput(format!(
+            "A post can have at most {MAX_IMAGES_PER_POST} images."
+        )));
+    }
+
+    let image = process_image_upload(Some(upload.into_inner().image), image_settings)
+        .await?
+        .ok_or_else(|| AppError::InvalidInput("No image was uploaded.".to_string()))?;
+    let stored = media.store_image(&mut db, MEDIA_PREFIX, &image).await?;
+    let new_image = new_image(
+        id,
+        &stored,
+        last_position.map_or(0, |position| position + 1),
+    );
+    let saved = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                diesel::insert_into(blog_post_images::table)
+                    .values(&new_image)
+                    .execute(&mut conn)
+                    .await?;
+                let image_id: u64 = diesel::select(last_insert_id())
+                    .get_result(&mut conn)
+                    .await?;
+                Ok::<_, diesel::result::Error>(image_id as i64)
+            })
+        })
+        .await;
+    let image_id = match saved {
+        Ok(image_id) => image_id,
+        Err(e) => {
+            error!("Error saving an image of blog post {}: {}", id, e);
+            media.release_images(&mut db, &[stored.key]).await;
+            return Err(e.into());
+        }
+    };
+
+    let image = blog_post_images::table
+        .find(image_id)
+        .select(BlogPostImageInfo::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "blog_post.image_upload", "blog_post", id).await;
+    info!(
+        "Admin '{}' added image {} to blog post {}",
+        admin.username, image_id, id
+    );
+    Ok(Json(to_image_dto(image)))
+}
+
+/// Put the images of a post in the given order; the first becomes the cover
+#[put(
+    "/admin/api/blog/<id>/images/order",
+    format = "json",
+    data = "<request>"
+)]
+pub async fn reorder_blog_post_images(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminBlogPostImageOrderRequest>,
+) -> AppResult<Json<Vec<BlogPostImageDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_post(&mut db, id).await?;
+    let current: Vec<i64> = post_images(&mut db, id)
+        .await?
+        .iter()
+        .map(|image| image.image.id)
+        .collect();
+    let image_ids = request.into_inner().image_ids;
+    check_image_order(&current, &image_ids)?;
+
+    db.transaction(|conn| {
+        Box::pin(async move {
+            for (position, image_id) in image_ids.into_iter().enumerate() {
+                diesel::update(blog_post_images::table.find(image_id))
+                    .set(blog_post_images::position.eq(position as i32))
+                    .execute(conn)
+                    .await?;
+            }
+            Ok::<_, diesel::result::Error>(())
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!("Error reordering the images of blog post {}: {}", id, e);
+        AppError::from(e)
+    })?;
+
+    record_audit(&mut db, &admin, "blog_post.image_reorder", "blog_post", id).await;
+    info!(
+        "Admin '{}' reordered the images of blog post {}",
+        admin.username, id
+    );
+    Ok(Json(post_images(&mut db, id).await?))
+}
+
+/// Delete an image of a post. Content that still links to it shows a
+/// broken image.
+#[delete("/admin/api/blog/<id>/images/<image_id>")]
+pub async fn delete_blog_post_image(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    media: &State<Media>,
+    id: i64,
+    image_id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let image_row = blog_post_images::table
+        .find(image_id)
+        .filter(blog_post_images::blog_post_id.eq(id));
+    let storage_key: String = image_row
+        .select(blog_post_images::storage_key)
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let deleted = diesel::delete(image_row)
+        .execute(&mut db)
+        .await
+        .map_err(|e| {
+            error!(
+                "Error deleting image {} of blog post {}: {}",
+                image_id, id, e
+            );
+            AppError::from(e)
+        })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    media.release_images(&mut db, &[storage_key]).await;
+
+    record_audit(&mut db, &admin, "blog_post.image_delete", "blog_post", id).await;
+    info!(
+        "Admin '{}' deleted image {} of blog post {}",
+        admin.username, image_id, id
+    );
+    Ok(Status::Ok)
+}
+
+/// An image of a post in the requested size
+pub(crate) async fn post_image_response(
+    db: &mut Connection<MessagesDB>,
+    media: &Media,
+    id: i64,
+    image_id: i64,
+    size: ImageSize,
+    accept: Option<&Accept>,
+    conditional: &ConditionalHeaders,
+) -> AppResult<CachedImage> {
+    let (key, mime, hash) = blog_post_images::table
+        .find(image_id)
+        .filter(blog_post_images::blog_post_id.eq(id))
+        .select((
+            blog_post_images::storage_key,
+            blog_post_images::image_mime,
+            blog_post_images::image_hash,
+        ))
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let image = StoredImage { key, mime, hash };
+    stored_image_response(db, media, &image, size, accept, conditional).await
+}
+
+/// An image of a post at its stable URL, in the size given by `size`
+#[get("/api/blog/<id>/images/<image_id>?<size>")]
+pub async fn get_blog_post_image_file(
+    mut db: Connection<MessagesDB>,
+    media: &State<Media>,
+    conditional: ConditionalHeaders,
+    accept: Option<&Accept>,
+    id: i64,
+    image_id: i64,
+    size: Option<&str>,
+) -> AppResult<CachedImage> {
+    let size = ImageSize::parse(size)?;
+    post_image_response(&mut db, media, id, image_id, size, accept, &conditional).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_url() {
+        assert_eq!(image_url(7, 42), "/api/blog/7/images/42");
+    }
+
+    #[test]
+    fn test_rewrite_image_urls() {
+        let urls = vec![(image_url(7, 3), image_url(9, 12))];
+        assert_eq!(
+            rewrite_image_urls(
+                "![a](/api/blog/7/images/3) ![b](/api/blog/7/images/35)\n![c](/api/blog/7/images/3)",
+                &urls
+            ),
+            "![a](/api/blog/9/images/12) ![b](/api/blog/7/images/35)\n![c](/api/blog/9/images/12)"
+        );
+        assert_eq!(rewrite_image_urls("bez obrázkov", &urls), "bez obrázkov");
+    }
+
+    #[test]
+    fn test_check_image_order() {
+        assert!(check_image_order(&[1, 2, 3], &[3, 1, 2]).is_ok());
+        assert!(check_image_order(&[], &[]).is_ok());
+        assert!(check_image_order(&[1, 2, 3], &[1, 2]).is_err());
+        assert!(check_image_order(&[1, 2], &[1, 1]).is_err());
+        assert!(check_image_order(&[1, 2], &[1, 2, 9]).is_err());
+    }
+}
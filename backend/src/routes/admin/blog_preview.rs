@@ -0,0 +1,200 @@
+// Signed, expiring links that show unpublished blog posts to reviewers
+// without an admin account
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use sha2::Sha256;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{BlogPost, BlogPostDto, BlogPreviewLink, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::blog::to_blog_post_dto;
+use crate::routes::admin::blog_authors::load_author;
+use crate::routes::admin::blog_images::load_post_images;
+use crate::routes::admin::tags::load_post_tags;
+use crate::schema::blog_posts;
+use crate::utils::{ShortLinkTarget, hmac_sha256_hex};
+
+/// Query parameter of the post page that carries a preview token
+const PREVIEW_QUERY_PARAM: &str = "preview";
+
+/// Signs and checks preview tokens of the form `<post id>.<expiry>.<hmac>`,
+/// where the expiry is a Unix timestamp
+pub struct BlogPreviewSigner {
+    key: Vec<u8>,
+    ttl_secs: i64,
+}
+
+impl BlogPreviewSigner {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let secret = config.blog_preview_secret.trim();
+        let key = if secret.is_empty() {
+            warn!("BLOG_PREVIEW_SECRET is not set; blog preview links expire on restart");
+            format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()).into_bytes()
+        } else {
+            secret.as_bytes().to_vec()
+        };
+        BlogPreviewSigner {
+            key,
+            ttl_secs: i64::try_from(config.blog_preview_link_ttl_secs).unwrap_or(i64::MAX),
+        }
+    }
+
+    fn signed_message(post_id: i64, expires: i64) -> String {
+        format!("blog-preview.{post_id}.{expires}")
+    }
+
+    fn signature(&self, post_id: i64, expires: i64) -> String {
+        hmac_sha256_hex(&self.key, Self::signed_message(post_id, expires).as_bytes())
+    }
+
+    /// Token for `post_id` valid until `expires`
+    fn sign(&self, post_id: i64, expires: i64) -> String {
+        format!("{post_id}.{expires}.{}", self.signature(post_id, expires))
+    }
+
+    /// The post a token was signed for, if the signature matches and the
+    /// token has not expired at `now`
+    fn verify(&self, token: &str, now: i64) -> Option<i64> {
+        let mut parts = token.splitn(3, '.');
+        let post_id: i64 = parts.next()?.parse().ok()?;
+        let expires: i64 = parts.next()?.parse().ok()?;
+        let signature = hex::decode(parts.next()?).ok()?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).ok()?;
+        mac.update(Self::signed_message(post_id, expires).as_bytes());
+        (mac.verify_slice(&signature).is_ok() && now < expires).then_some(post_id)
+    }
+}
+
+/// Create a link to the page of a post that shows it even while it is a
+/// draft or archived. Links cannot be revoked one by one; changing
+/// `BLOG_PREVIEW_SECRET` invalidates all of them.
+#[post("/admin/api/blog/<id>/preview-link")]
+pub async fn create_blog_preview_link(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    config: &State<AppConfig>,
+    signer: &State<BlogPreviewSigner>,
+    id: i64,
+) -> AppResult<Json<BlogPreviewLink>> {
+    admin.require(Permission::ManageContent)?;
+
+    let slug: String = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(blog_posts::slug)
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching blog post {}: {}", id, e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound)?;
+
+    let expires = Utc::now().timestamp().saturating_add(signer.ttl_secs);
+    let expires_at = DateTime::from_timestamp(expires, 0)
+        .map(|at| at.naive_utc())
+        .unwrap_or(NaiveDateTime::MAX);
+    let token = signer.sign(id, expires);
+
+    record_audit(&mut db, &admin, "blog_post.preview_link", "blog_post", id).await;
+
+    Ok(Json(BlogPreviewLink {
+        url: format!(
+            "{}{}?{PREVIEW_QUERY_PARAM}={token}",
+            config.site_url(),
+            ShortLinkTarget::BlogPost(id).page_path(&slug)
+        ),
+        token,
+        expires_at,
+    }))
+}
+
+/// A post shown through a preview link, whether published or not. Views
+/// are not counted.
+#[get("/api/blog/preview/<token>", rank = 1)]
+pub async fn get_blog_post_preview(
+    mut db: Connection<MessagesDB>,
+    signer: &State<BlogPreviewSigner>,
+    token: &str,
+) -> AppResult<Json<BlogPostDto>> {
+    let id = signer
+        .verify(token, Utc::now().timestamp())
+        .ok_or(AppError::NotFound)?;
+
+    let post: BlogPost = blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(BlogPost::as_select())
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching blog post {}: {}", id, e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound)?;
+
+    let tags = load_post_tags(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let images = load_post_images(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let author = load_author(&mut db, post.author_id).await?;
+    Ok(Json(to_blog_post_dto(post, author, tags, images)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> BlogPreviewSigner {
+        BlogPreviewSigner {
+            key: b"secret".to_vec(),
+            ttl_secs: 3600,
+        }
+    }
+
+    #[test]
+    fn test_verify_signed_token() {
+        let signer = signer();
+        let token = signer.sign(42, 2_000);
+
+        assert!(token.starts_with("42.2000."));
+        assert_eq!(signer.verify(&token, 1_999), Some(42));
+        assert_eq!(signer.verify(&token, 2_000), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_tokens() {
+        let signer = signer();
+        let token = signer.sign(42, 2_000);
+        let signature = token.rsplit('.').next().unwrap();
+
+        assert_eq!(signer.verify(&format!("43.2000.{signature}"), 0), None);
+        assert_eq!(signer.verify(&format!("42.9000.{signature}"), 0), None);
+        assert_eq!(signer.verify("42.2000", 0), None);
+        assert_eq!(signer.verify("42.2000.not-hex", 0), None);
+        assert_eq!(signer.verify("", 0), None);
+
+        let other = BlogPreviewSigner {
+            key: b"other".to_vec(),
+            ttl_secs: 3600,
+        };
+        assert_eq!(other.verify(&token, 0), None);
+    }
+}
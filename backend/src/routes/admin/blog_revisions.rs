@@ -0,0 +1,347 @@
+// Revision history of blog posts
+
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use similar::TextDiff;
+use tracing::{error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::markdown::{reading_time_minutes, word_count};
+use crate::models::{
+    BlogPost, BlogPostDto, BlogPostRevision, BlogPostRevisionDiff, BlogPostRevisionSummary,
+    NewBlogPostRevision, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::blog::to_blog_post_dto;
+use crate::routes::admin::blog_authors::load_author;
+use crate::routes::admin::blog_images::load_post_images;
+use crate::routes::admin::tags::{load_post_tags, parse_tag_list, set_post_tags};
+use crate::sanitize::{HtmlPolicy, sanitize_markdown};
+use crate::schema::{blog_post_revisions, blog_posts};
+use crate::utils::map_slug_write_error;
+
+/// Lines of unchanged content shown around each change
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// The fields of a post that revisions keep
+#[derive(Debug, Clone, PartialEq)]
+struct PostVersion {
+    title: String,
+    slug: String,
+    excerpt: Option<String>,
+    content: String,
+    tags: String,
+    published: bool,
+}
+
+impl From<BlogPostRevision> for PostVersion {
+    fn from(revision: BlogPostRevision) -> Self {
+        PostVersion {
+            title: revision.title,
+            slug: revision.slug,
+            excerpt: revision.excerpt,
+            content: revision.content,
+            tags: revision.tags,
+            published: revision.published,
+        }
+    }
+}
+
+impl PostVersion {
+    /// Names of the fields that differ from `other`
+    fn changed_fields(&self, other: &PostVersion) -> Vec<String> {
+        [
+            ("title", self.title != other.title),
+            ("slug", self.slug != other.slug),
+            ("excerpt", self.excerpt != other.excerpt),
+            ("content", self.content != other.content),
+            ("tags", self.tags != other.tags),
+            ("published", self.published != other.published),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
+
+/// Unified diff of two Markdown texts
+fn content_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string()
+}
+
+fn revision_summary(revision: &BlogPostRevision) -> BlogPostRevisionSummary {
+    BlogPostRevisionSummary {
+        id: revision.id,
+        blog_post_id: revision.blog_post_id,
+        title: revision.title.clone(),
+        slug: revision.slug.clone(),
+        published: revision.published,
+        admin_user_id: revision.admin_user_id,
+        admin_username: revision.admin_username.clone(),
+        created_at: revision.created_at,
+    }
+}
+
+async fn find_post(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<BlogPost> {
+    blog_posts::table
+        .find(id)
+        .filter(blog_posts::deleted_at.is_null())
+        .select(BlogPost::as_select())
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+async fn find_revision(
+    db: &mut Connection<MessagesDB>,
+    post_id: i64,
+    revision_id: i64,
+) -> AppResult<BlogPostRevision> {
+    blog_post_revisions::table
+        .find(revision_id)
+        .filter(blog_post_revisions::blog_post_id.eq(post_id))
+        .select(BlogPostRevision::as_select())
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+/// Current state of a post, with its tag names joined like in revisions
+async fn current_version(
+    db: &mut Connection<MessagesDB>,
+    post: &BlogPost,
+) -> AppResult<PostVersion> {
+    let tags = load_post_tags(db, &[post.id])
+        .await?
+        .remove(&post.id)
+        .unwrap_or_default();
+    Ok(PostVersion {
+        title: post.title.clone(),
+        slug: post.slug.clone(),
+        excerpt: post.excerpt.clone(),
+        content: post.content.clone(),
+        tags: tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        published: post.published,
+    })
+}
+
+/// Save the current state of a post before `admin` changes it
+pub(crate) async fn record_revision(
+    db: &mut Connection<MessagesDB>,
+    admin: &AdminUser,
+    post: &BlogPost,
+) -> AppResult<()> {
+    let version = current_version(db, post).await?;
+    let revision = NewBlogPostRevision {
+        blog_post_id: post.id,
+        title: version.title,
+        slug: version.slug,
+        excerpt: version.excerpt,
+        content: version.content,
+        tags: version.tags,
+        published: version.published,
+        admin_user_id: Some(admin.id),
+        admin_username: admin.username.clone(),
+    };
+    diesel::insert_into(blog_post_revisions::table)
+        .values(&revision)
+        .execute(db)
+        .await
+        .map_err(|e| {
+            error!("Error saving a revision of blog post {}: {}", post.id, e);
+            AppError::from(e)
+        })?;
+    Ok(())
+}
+
+/// Revisions of a post, newest first
+#[get("/admin/api/blog/<id>/revisions")]
+pub async fn list_blog_post_revisions(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<Vec<BlogPostRevisionSummary>>> {
+    admin.require(Permission::ManageContent)?;
+
+    find_post(&mut db, id).await?;
+    let revisions = blog_post_revisions::table
+        .filter(blog_post_revisions::blog_post_id.eq(id))
+        .order(blog_post_revisions::id.desc())
+        .select(BlogPostRevisionSummary::as_select())
+        .load(&mut db)
+        .await?;
+    Ok(Json(revisions))
+}
+
+/// Compare revision `from` with revision `to`, or with the current post
+/// when `to` is omitted
+#[get("/admin/api/blog/<id>/revisions/diff?<from>&<to>")]
+pub async fn diff_blog_post_revisions(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    from: i64,
+    to: Option<i64>,
+) -> AppResult<Json<BlogPostRevisionDiff>> {
+    admin.require(Permission::ManageContent)?;
+
+    let post = find_post(&mut db, id).await?;
+    let from_revision = find_revision(&mut db, id, from).await?;
+    let from_summary = revision_summary(&from_revision);
+    let from_label = format!("revision {from}");
+
+    let (to_summary, to_version, to_label) = match to {
+        Some(to) => {
+            let to_revision = find_revision(&mut db, id, to).await?;
+            (
+                Some(revision_summary(&to_revision)),
+                PostVersion::from(to_revision),
+                format!("revision {to}"),
+            )
+        }
+        None => (
+            None,
+            current_version(&mut db, &post).await?,
+            "current".to_string(),
+        ),
+    };
+    let from_version = PostVersion::from(from_revision);
+
+    Ok(Json(BlogPostRevisionDiff {
+        from: from_summary,
+        to: to_summary,
+        changed_fields: from_version.changed_fields(&to_version),
+        content_diff: content_diff(
+            &from_version.content,
+            &to_version.content,
+            &from_label,
+            &to_label,
+        ),
+    }))
+}
+
+/// Bring back the text, slug, tags and visibility of a revision. The state
+/// being replaced is saved as a new revision first. Revisions from before
+/// HTML was sanitized on save are sanitized as they are restored.
+#[post("/admin/api/blog/<id>/revisions/<revision_id>/restore")]
+pub async fn restore_blog_post_revision(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    policy: &State<HtmlPolicy>,
+    id: i64,
+    revision_id: i64,
+) -> AppResult<Json<BlogPostDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let post = find_post(&mut db, id).await?;
+    let revision = find_revision(&mut db, id, revision_id).await?;
+    let tags = parse_tag_list(&revision.tags)?;
+    let slug = revision.slug.clone();
+    let content = sanitize_markdown(&revision.content, policy);
+    let excerpt = revision
+        .excerpt
+        .as_deref()
+        .map(|excerpt| sanitize_markdown(excerpt, policy));
+    let words = word_count(&content);
+    record_revision(&mut db, &admin, &post).await?;
+
+    db.transaction(|conn| {
+        Box::pin(async move {
+            diesel::update(blog_posts::table.find(id))
+                .set((
+                    blog_posts::title.eq(&revision.title),
+                    blog_posts::slug.eq(&revision.slug),
+                    blog_posts::excerpt.eq(&excerpt),
+                    blog_posts::content.eq(&content),
+                    blog_posts::word_count.eq(words),
+                    blog_posts::reading_time_minutes.eq(reading_time_minutes(words)),
+                    blog_posts::published.eq(revision.published),
+                ))
+                .execute(conn)
+                .await?;
+            set_post_tags(conn, id, &tags).await
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!(
+            "Error restoring revision {} of blog post {}: {}",
+            revision_id, id, e
+        );
+        map_slug_write_error(e, &slug)
+    })?;
+
+    let restored = find_post(&mut db, id).await?;
+    let tags = load_post_tags(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let images = load_post_images(&mut db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    let author = load_author(&mut db, restored.author_id).await?;
+    record_audit(&mut db, &admin, "blog_post.restore", "blog_post", id).await;
+    info!(
+        "Admin '{}' restored revision {} of blog post {}",
+        admin.username, revision_id, id
+    );
+    Ok(Json(to_blog_post_dto(restored, author, tags, images)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(title: &str, content: &str) -> PostVersion {
+        PostVersion {
+            title: title.to_string(),
+            slug: "zateplenie".to_string(),
+            excerpt: None,
+            content: content.to_string(),
+            tags: "fasády, tipy".to_string(),
+            published: true,
+        }
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let old = version("Zateplenie", "a\n");
+        assert!(old.changed_fields(&old.clone()).is_empty());
+
+        let mut new = version("Zateplenie domu", "b\n");
+        new.published = false;
+        assert_eq!(
+            old.changed_fields(&new),
+            vec!["title", "content", "published"]
+        );
+    }
+
+    #[test]
+    fn test_content_diff() {
+        let diff = content_diff(
+            "# Nadpis\nprvý\ndruhý\n",
+            "# Nadpis\nprvý\ntretí\n",
+            "revision 1",
+            "current",
+        );
+        assert!(diff.starts_with("--- revision 1\n+++ current\n"));
+        assert!(diff.contains("-druhý\n+tretí\n"));
+        assert!(content_diff("same\n", "same\n", "a", "b").is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+// SEO and OpenGraph metadata of blog posts
+
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::markdown::markdown_to_plain_text;
+use crate::models::{BlogPost, BlogPostMeta};
+use crate::routes::admin::blog::find_published_post;
+use crate::routes::admin::blog_images::load_post_images;
+use crate::utils::{ShortLinkTarget, is_http_url};
+
+const MAX_META_TITLE_LEN: usize = 255;
+const MAX_META_DESCRIPTION_LEN: usize = 500;
+const MAX_SEO_URL_LEN: usize = 2048;
+/// Length of descriptions taken from the excerpt or content, as search
+/// engines show about this much
+const GENERATED_DESCRIPTION_LEN: usize = 160;
+
+/// Validated SEO overrides of a post; blank fields become `None`
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SeoFields {
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+}
+
+fn non_blank(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn check_len(value: &Option<String>, max: usize, field: &str) -> AppResult<()> {
+    match value {
+        Some(value) if value.chars().count() > max => Err(AppError::InvalidInput(format!(
+            "{field} must be at most {max} characters."
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Validate the SEO fields of the blog forms. `canonical_url` must be an
+/// absolute http(s) URL; `og_image` may also be a path on this site.
+pub(crate) fn seo_fields(
+    meta_title: Option<&str>,
+    meta_description: Option<&str>,
+    canonical_url: Option<&str>,
+    og_image: Option<&str>,
+) -> AppResult<SeoFields> {
+    let fields = SeoFields {
+        meta_title: non_blank(meta_title),
+        meta_description: non_blank(meta_description),
+        canonical_url: non_blank(canonical_url),
+        og_image: non_blank(og_image),
+    };
+    check_len(&fields.meta_title, MAX_META_TITLE_LEN, "Meta title")?;
+    check_len(
+        &fields.meta_description,
+        MAX_META_DESCRIPTION_LEN,
+        "Meta description",
+    )?;
+    check_len(&fields.canonical_url, MAX_SEO_URL_LEN, "Canonical URL")?;
+    check_len(&fields.og_image, MAX_SEO_URL_LEN, "OpenGraph image")?;
+
+    if fields
+        .canonical_url
+        .as_deref()
+        .is_some_and(|url| !is_http_url(url))
+    {
+        return Err(AppError::InvalidInput(
+            "Canonical URL must be an absolute http(s) URL.".to_string(),
+        ));
+    }
+    if fields.og_image.as_deref().is_some_and(|image| {
+        let site_path = image.starts_with('/') && !image.starts_with("//");
+        !(is_http_url(image) || site_path && !image.chars().any(char::is_whitespace))
+    }) {
+        return Err(AppError::InvalidInput(
+            "OpenGraph image must be an http(s) URL or a path starting with '/'.".to_string(),
+        ));
+    }
+    Ok(fields)
+}
+
+/// First `max_chars` characters of `text`, cut at a word boundary
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+/// Metadata of a post, falling back to its title, excerpt or content, page
+/// URL and the site path of its cover image
+fn post_meta(
+    post: &BlogPost,
+    cover_url: Option<&str>,
+    site_url: &str,
+    site_name: &str,
+) -> BlogPostMeta {
+    let description = post.meta_description.clone().or_else(|| {
+        let source = post.excerpt.as_deref().unwrap_or(&post.content);
+        Some(shorten(
+            &markdown_to_plain_text(source),
+            GENERATED_DESCRIPTION_LEN,
+        ))
+        .filter(|text| !text.is_empty())
+    });
+    let canonical_url = post.canonical_url.clone().unwrap_or_else(|| {
+        format!(
+            "{site_url}{}",
+            ShortLinkTarget::BlogPost(post.id).page_path(&post.slug)
+        )
+    });
+    let og_image = match post.og_image.as_deref().or(cover_url) {
+        Some(path) if path.starts_with('/') => Some(format!("{site_url}{path}")),
+        Some(url) => Some(url.to_string()),
+        None => None,
+    };
+
+    BlogPostMeta {
+        title: post
+            .meta_title
+            .clone()
+            .unwrap_or_else(|| post.title.clone()),
+        description,
+        canonical_url,
+        og_image,
+        og_type: "article".to_string(),
+        site_name: site_name.to_string(),
+        published_time: post.created_at,
+        modified_time: post.updated_at,
+    }
+}
+
+/// SEO and OpenGraph metadata of a published post
+#[get("/api/blog/<slug>/meta")]
+pub async fn get_blog_post_meta(
+    mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    slug: String,
+) -> AppResult<Json<BlogPostMeta>> {
+    let post = find_published_post(&mut db, config, slug).await?;
+    let cover = load_post_images(&mut db, &[post.id])
+        .await?
+        .remove(&post.id)
+        .and_then(|images| images.into_iter().next());
+    Ok(Json(post_meta(
+        &post,
+        cover.as_ref().map(|cover| cover.url.as_str()),
+        config.site_url(),
+        &config.site_title,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn post() -> BlogPost {
+        let at = NaiveDateTime::parse_from_str("2026-03-02 09:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        BlogPost {
+            id: 7,
+            title: "Zateplenie".to_string(),
+            slug: "zateplenie".to_string(),
+            excerpt: None,
+            content: "# Nadpis\n\nPrvý **odsek**.".to_string(),
+            word_count: 3,
+            reading_time_minutes: 1,
+            meta_title: None,
+            meta_description: None,
+            canonical_url: None,
+            og_image: None,
+            author_id: None,
+            published: true,
+            featured: false,
+            featured_order: 0,
+            archived_at: None,
+            deleted_at: None,
+            view_count: 0,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn test_seo_fields() {
+        let fields = seo_fields(
+            Some(" Titulok "),
+            Some(""),
+            Some("https://example.com/blog/a"),
+            Some("/images/og.jpg"),
+        )
+        .unwrap();
+        assert_eq!(fields.meta_title.as_deref(), Some("Titulok"));
+        assert_eq!(fields.meta_description, None);
+        assert_eq!(fields.og_image.as_deref(), Some("/images/og.jpg"));
+        assert_eq!(
+            seo_fields(None, None, None, None).unwrap(),
+            SeoFields::default()
+        );
+
+        assert!(seo_fields(None, None, Some("/blog/a"), None).is_err());
+        assert!(seo_fields(None, None, None, Some("//cdn.example.com/a.jpg")).is_err());
+        assert!(seo_fields(None, None, None, Some("javascript:alert(1)")).is_err());
+        assert!(seo_fields(Some(&"a".repeat(MAX_META_TITLE_LEN + 1)), None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("krátky text", 20), "krátky text");
+        assert_eq!(shorten("jeden dva, tri štyri", 12), "jeden dva…");
+    }
+
+    #[test]
+    fn test_post_meta_falls_back_to_post() {
+        let meta = post_meta(
+            &post(),
+            Some("/api/blog/7/images/3"),
+            "https://example.com",
+            "MK-SBD",
+        );
+        assert_eq!(meta.title, "Zateplenie");
+        assert_eq!(meta.description.as_deref(), Some("Nadpis Prvý odsek."));
+        assert_eq!(meta.canonical_url, "https://example.com/blog/zateplenie");
+        assert_eq!(
+            meta.og_image.as_deref(),
+            Some("https://example.com/api/blog/7/images/3")
+        );
+        assert_eq!(meta.og_type, "article");
+        assert_eq!(
+            post_meta(&post(), None, "https://example.com", "MK-SBD").og_image,
+            None
+        );
+    }
+
+    #[test]
+    fn test_post_meta_uses_overrides() {
+        let mut post = post();
+        post.meta_title = Some("SEO titulok".to_string());
+        post.meta_description = Some("Popis".to_string());
+        post.canonical_url = Some("https://other.example.com/a".to_string());
+        post.og_image = Some("/og.png".to_string());
+
+        let meta = post_meta(
+            &post,
+            Some("/api/blog/7/images/3"),
+            "https://example.com",
+            "MK-SBD",
+        );
+        assert_eq!(meta.title, "SEO titulok");
+        assert_eq!(meta.description.as_deref(), Some("Popis"));
+        assert_eq!(meta.canonical_url, "https://other.example.com/a");
+        assert_eq!(meta.og_image.as_deref(), Some("https://example.com/og.png"));
+    }
+}
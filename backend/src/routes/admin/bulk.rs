@@ -0,0 +1,210 @@
+// Bulk actions on active messages
+
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AdminBulkMessagesRequest, BulkMessageAction, BulkMessagesResponse, Message, NewArchivedMessage,
+    Permission,
+};
+use crate::notifier::ChatNotifier;
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{
+    message_attachments, message_labels, message_notes, message_replies, messages, messages_archive,
+};
+use crate::webhooks::enqueue_webhook_event;
+
+/// Most messages a single bulk request may touch
+const MAX_BULK_IDS: usize = 500;
+
+/// Deduplicated ids of a bulk request, in ascending order
+fn bulk_ids(ids: &[i64]) -> AppResult<Vec<i64>> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput(
+            "At least one message id is required.".to_string(),
+        ));
+    }
+    if ids.len() > MAX_BULK_IDS {
+        return Err(AppError::InvalidInput(format!(
+            "At most {MAX_BULK_IDS} messages can be changed at once."
+        )));
+    }
+    Ok(ids)
+}
+
+/// Requested ids that were not found
+fn missing_ids(requested: &[i64], found: &[i64]) -> Vec<i64> {
+    requested
+        .iter()
+        .filter(|id| !found.contains(id))
+        .copied()
+        .collect()
+}
+
+/// Archive, permanently delete or mark as read several active messages in
+/// one transaction. Ids that are not active messages are skipped and
+/// reported.
+#[post("/admin/api/messages/bulk", format = "json", data = "<request>")]
+pub async fn bulk_update_messages(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    notifier: &State<ChatNotifier>,
+    request: Json<AdminBulkMessagesRequest>,
+) -> AppResult<Json<BulkMessagesResponse>> {
+    let action = request.action;
+    admin.require(match action {
+        BulkMessageAction::Delete => Permission::DeleteMessages,
+        BulkMessageAction::Archive | BulkMessageAction::MarkRead => Permission::ManageMessages,
+    })?;
+    let ids = bulk_ids(&request.ids)?;
+
+    let transaction_ids = ids.clone();
+    let found = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                let ids = transaction_ids;
+                let found: Vec<Message> = messages::table
+                    .filter(messages::id.eq_any(&ids))
+                    .select(Message::as_select())
+                    .for_update()
+                    .load(&mut conn)
+                    .await?;
+                let found_ids: Vec<i64> = found.iter().map(|message| message.id).collect();
+                if found_ids.is_empty() {
+                    return Ok(found);
+                }
+
+                match action {
+                    BulkMessageAction::Archive => {
+                        let archived: Vec<NewArchivedMessage> =
+                            found.iter().cloned().map(Message::into_archived).collect();
+                        diesel::insert_into(messages_archive::table)
+                            .values(&archived)
+                            .execute(&mut conn)
+                            .await?;
+                        diesel::delete(messages::table.filter(messages::id.eq_any(&found_ids)))
+                            .execute(&mut conn)
+                            .await?;
+                    }
+                    BulkMessageAction::Delete => {
+                        diesel::delete(
+                            message_attachments::table
+                                .filter(message_attachments::message_id.eq_any(&found_ids)),
+                        )
+                        .execute(&mut conn)
+                        .await?;
+                        diesel::delete(
+                            message_replies::table
+                                .filter(message_replies::message_id.eq_any(&found_ids)),
+                        )
+                        .execute(&mut conn)
+                        .await?;
+                        diesel::delete(
+                            message_notes::table
+                                .filter(message_notes::message_id.eq_any(&found_ids)),
+                        )
+                        .execute(&mut conn)
+                        .await?;
+                        diesel::delete(
+                            message_labels::table
+                                .filter(message_labels::message_id.eq_any(&found_ids)),
+                        )
+                        .execute(&mut conn)
+                        .await?;
+                        diesel::delete(messages::table.filter(messages::id.eq_any(&found_ids)))
+                            .execute(&mut conn)
+                            .await?;
+                    }
+                    BulkMessageAction::MarkRead => {
+                        diesel::update(
+                            messages::table
+                                .filter(messages::id.eq_any(&found_ids))
+                                .filter(messages::read_at.is_null()),
+                        )
+                        .set(messages::read_at.eq(chrono::Utc::now().naive_utc()))
+                        .execute(&mut conn)
+                        .await?;
+                    }
+                }
+
+                Ok::<_, diesel::result::Error>(found)
+            })
+        })
+        .await
+        .map_err(|e| {
+            error!("Error in bulk {:?} of messages: {}", action, e);
+            AppError::from(e)
+        })?;
+    let found_ids: Vec<i64> = found.iter().map(|message| message.id).collect();
+
+    if action == BulkMessageAction::Archive {
+        for message in found {
+            notifier.message_archived(&message);
+            enqueue_webhook_event(
+                &mut db,
+                "message.archived",
+                serde_json::json!({ "message": message }),
+            )
+            .await;
+        }
+    }
+
+    let audit_action = match action {
+        BulkMessageAction::Archive => Some("message.archive"),
+        BulkMessageAction::Delete => Some("message.delete"),
+        BulkMessageAction::MarkRead => None,
+    };
+    if let Some(audit_action) = audit_action {
+        for id in &found_ids {
+            record_audit(&mut db, &admin, audit_action, "message", id).await;
+        }
+    }
+
+    info!(
+        "Bulk {:?} by admin '{}' applied to {} of {} messages",
+        action,
+        admin.username,
+        found_ids.len(),
+        ids.len()
+    );
+    Ok(Json(BulkMessagesResponse {
+        action,
+        processed: found_ids.len(),
+        missing: missing_ids(&ids, &found_ids),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_ids() {
+        assert_eq!(bulk_ids(&[3, 1, 3, 2]).unwrap(), [1, 2, 3]);
+        assert!(bulk_ids(&[]).is_err());
+        let too_many: Vec<i64> = (0..=MAX_BULK_IDS as i64).collect();
+        assert!(bulk_ids(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_missing_ids_and_action_names() {
+        assert_eq!(missing_ids(&[1, 2, 3], &[2]), [1, 3]);
+
+        let request: AdminBulkMessagesRequest =
+            serde_json::from_str(r#"{"ids":[1],"action":"mark_read"}"#).unwrap();
+        assert_eq!(request.action, BulkMessageAction::MarkRead);
+        assert!(
+            serde_json::from_str::<AdminBulkMessagesRequest>(r#"{"ids":[1],"action":"purge"}"#)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,274 @@
+// Editable email templates and the contact auto-reply sent with them
+
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{debug, error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::mailer::Mailer;
+use crate::models::{
+    AdminUpdateEmailTemplateRequest, ContactMessageForm, EmailTemplate, EmailTemplateDto,
+    NewEmailTemplate, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::email_templates;
+
+/// Acknowledgement sent to the sender of a contact message
+pub const CONTACT_AUTO_REPLY: &str = "contact_auto_reply";
+
+const MAX_SUBJECT_LEN: usize = 191;
+const MAX_BODY_LEN: usize = 10_000;
+
+/// Placeholders each known template may use, or `None` for unknown names
+fn template_placeholders(name: &str) -> Option<&'static [&'static str]> {
+    match name {
+        CONTACT_AUTO_REPLY => Some(&["name", "email", "subject", "message"]),
+        _ => None,
+    }
+}
+
+/// Replace `{placeholder}`s in a single pass, so substituted values are never
+/// expanded themselves. Unknown placeholders are left as they are.
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(key, _)| *key == &after[..end])
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Placeholders used in `text` that are not in `allowed`
+fn unknown_placeholders(text: &str, allowed: &[&str]) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        let name = &after[..end];
+        let is_identifier =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_identifier && !allowed.contains(&name) && !unknown.iter().any(|u| u == name) {
+            unknown.push(name.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    unknown
+}
+
+fn to_email_template_dto(template: EmailTemplate) -> EmailTemplateDto {
+    EmailTemplateDto {
+        placeholders: template_placeholders(&template.name)
+            .unwrap_or_default()
+            .iter()
+            .map(|placeholder| placeholder.to_string())
+            .collect(),
+        id: template.id,
+        name: template.name,
+        subject: template.subject,
+        body: template.body,
+        is_active: template.is_active,
+        created_at: template.created_at,
+        updated_at: template.updated_at,
+    }
+}
+
+async fn find_template(
+    db: &mut Connection<MessagesDB>,
+    name: &str,
+) -> AppResult<Option<EmailTemplate>> {
+    Ok(email_templates::table
+        .filter(email_templates::name.eq(name))
+        .select(EmailTemplate::as_select())
+        .first(db)
+        .await
+        .optional()?)
+}
+
+#[get("/admin/api/email-templates")]
+pub async fn list_email_templates(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<EmailTemplateDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    let templates = email_templates::table
+        .order(email_templates::name.asc())
+        .select(EmailTemplate::as_select())
+        .load(&mut db)
+        .await?;
+
+    Ok(Json(
+        templates
+            .into_iter()
+            .filter(|template| template_placeholders(&template.name).is_some())
+            .map(to_email_template_dto)
+            .collect(),
+    ))
+}
+
+#[put(
+    "/admin/api/email-templates/<name>",
+    format = "json",
+    data = "<request>"
+)]
+pub async fn update_email_template(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    name: &str,
+    request: Json<AdminUpdateEmailTemplateRequest>,
+) -> AppResult<Json<EmailTemplateDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let placeholders = template_placeholders(name).ok_or(AppError::NotFound)?;
+    let subject = request.subject.trim();
+    let body = request.body.trim();
+    if subject.is_empty() || body.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Template subject and body are required.".to_string(),
+        ));
+    }
+    if subject.chars().count() > MAX_SUBJECT_LEN || subject.contains(['\r', '\n']) {
+        return Err(AppError::InvalidInput(format!(
+            "Template subject must be a single line of at most {MAX_SUBJECT_LEN} characters."
+        )));
+    }
+    if body.chars().count() > MAX_BODY_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Template body must be at most {MAX_BODY_LEN} characters."
+        )));
+    }
+    let unknown = unknown_placeholders(&format!("{subject}\n{body}"), placeholders);
+    if !unknown.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Unknown placeholders: {}. Available: {}.",
+            unknown.join(", "),
+            placeholders.join(", ")
+        )));
+    }
+
+    if let Some(existing) = find_template(&mut db, name).await? {
+        diesel::update(email_templates::table.find(existing.id))
+            .set((
+                email_templates::subject.eq(subject),
+                email_templates::body.eq(body),
+                email_templates::is_active.eq(request.is_active),
+            ))
+            .execute(&mut db)
+            .await?;
+    } else {
+        diesel::insert_into(email_templates::table)
+            .values(&NewEmailTemplate {
+                name: name.to_string(),
+                subject: subject.to_string(),
+                body: body.to_string(),
+                is_active: request.is_active,
+            })
+            .execute(&mut db)
+            .await?;
+    }
+
+    let template = find_template(&mut db, name)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    record_audit(
+        &mut db,
+        &admin,
+        "email_template.update",
+        "email_template",
+        template.id,
+    )
+    .await;
+    info!("Email template '{}' updated", name);
+    Ok(Json(to_email_template_dto(template)))
+}
+
+/// Acknowledge a saved contact message to its sender when the auto-reply
+/// template is active and SMTP is configured. The email is sent in the
+/// background; failures are only logged.
+pub(crate) async fn send_contact_auto_reply(
+    db: &mut Connection<MessagesDB>,
+    mailer: &Mailer,
+    form: &ContactMessageForm,
+) {
+    if !mailer.is_enabled() {
+        return;
+    }
+    let template = match find_template(db, CONTACT_AUTO_REPLY).await {
+        Ok(Some(template)) if template.is_active => template,
+        Ok(_) => return,
+        Err(e) => {
+            error!("Failed to load contact auto-reply template: {}", e);
+            return;
+        }
+    };
+
+    let values = [
+        ("name", form.name.trim()),
+        ("email", form.email.trim()),
+        (
+            "subject",
+            form.subject.as_deref().unwrap_or_default().trim(),
+        ),
+        ("message", form.message.trim()),
+    ];
+    let subject = render(&template.subject, &values).replace(['\r', '\n'], " ");
+    let body = render(&template.body, &values);
+    let to = form.email.trim().to_string();
+    let mailer = mailer.clone();
+
+    rocket::tokio::spawn(async move {
+        match mailer.send(&to, &subject, body).await {
+            Ok(()) => debug!("Sent contact auto-reply"),
+            Err(e) => error!("Failed to send contact auto-reply: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_replaces_known_placeholders_once() {
+        let rendered = render(
+            "Hi {name}, re: {subject} {unknown} {",
+            &[("name", "{subject}"), ("subject", "Offer")],
+        );
+        assert_eq!(rendered, "Hi {subject}, re: Offer {unknown} {");
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        let allowed = template_placeholders(CONTACT_AUTO_REPLY).unwrap();
+        assert!(unknown_placeholders("Hi {name}: {message}", allowed).is_empty());
+        assert_eq!(
+            unknown_placeholders("{nme} {nme} {phone} {not a placeholder}", allowed),
+            ["nme", "phone"]
+        );
+        assert!(template_placeholders("missing").is_none());
+    }
+}
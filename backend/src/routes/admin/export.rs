@@ -0,0 +1,364 @@
+// Export of messages as CSV or JSON downloads
+
+use chrono::NaiveDateTime;
+use diesel::mysql::Mysql;
+use rocket::http::{ContentType, Header};
+use rocket::response::stream::TextStream;
+use rocket::serde::Serialize;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{ArchivedMessage, Message, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{messages, messages_archive};
+use crate::utils::DateRange;
+
+/// Rows loaded per query while streaming
+const EXPORT_BATCH_SIZE: i64 = 500;
+const CSV_HEADER: &str = "id,state,name,email,phone,subject,message,preferred_contact,spam_score,created_at,archived_at,read_at,answered_at\r\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn parse(value: Option<&str>) -> AppResult<Self> {
+        match value
+            .map(|value| value.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("") | Some("csv") => Ok(ExportFormat::Csv),
+            Some("json") => Ok(ExportFormat::Json),
+            Some(_) => Err(AppError::InvalidInput(
+                "Export format must be csv or json.".to_string(),
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    fn content_type(self) -> ContentType {
+        match self {
+            ExportFormat::Csv => ContentType::CSV,
+            ExportFormat::Json => ContentType::JSON,
+        }
+    }
+}
+
+/// One exported message, active or archived. Archived messages keep the id
+/// they had while active.
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ExportRow {
+    id: i64,
+    state: &'static str,
+    name: String,
+    email: String,
+    phone: Option<String>,
+    subject: Option<String>,
+    message: String,
+    preferred_contact: Option<String>,
+    spam_score: i32,
+    created_at: NaiveDateTime,
+    archived_at: Option<NaiveDateTime>,
+    read_at: Option<NaiveDateTime>,
+    answered_at: Option<NaiveDateTime>,
+}
+
+impl From<Message> for ExportRow {
+    fn from(message: Message) -> Self {
+        ExportRow {
+            id: message.id,
+            state: "active",
+            name: message.name,
+            email: message.email,
+            phone: message.phone,
+            subject: message.subject,
+            message: message.message,
+            preferred_contact: message.preferred_contact,
+            spam_score: message.spam_score,
+            created_at: message.created_at,
+            archived_at: None,
+            read_at: message.read_at,
+            answered_at: message.answered_at,
+        }
+    }
+}
+
+impl From<ArchivedMessage> for ExportRow {
+    fn from(message: ArchivedMessage) -> Self {
+        ExportRow {
+            id: message.original_id,
+            state: "archived",
+            name: message.name,
+            email: message.email,
+            phone: message.phone,
+            subject: message.subject,
+            message: message.message,
+            preferred_contact: message.preferred_contact,
+            spam_score: message.spam_score,
+            created_at: message.created_at,
+            archived_at: Some(message.archived_at),
+            read_at: message.read_at,
+            answered_at: message.answered_at,
+        }
+    }
+}
+
+/// Quote a CSV field when needed. Values that spreadsheets would evaluate as
+/// formulas are prefixed with `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn csv_timestamp(value: Option<NaiveDateTime>) -> String {
+    value
+        .map(|value| value.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+impl ExportRow {
+    fn to_csv(&self) -> String {
+        let fields = [
+            self.id.to_string(),
+            self.state.to_string(),
+            csv_field(&self.name),
+            csv_field(&self.email),
+            csv_field(self.phone.as_deref().unwrap_or_default()),
+            csv_field(self.subject.as_deref().unwrap_or_default()),
+            csv_field(&self.message),
+            self.preferred_contact.clone().unwrap_or_default(),
+            self.spam_score.to_string(),
+            csv_timestamp(Some(self.created_at)),
+            csv_timestamp(self.archived_at),
+            csv_timestamp(self.read_at),
+            csv_timestamp(self.answered_at),
+        ];
+        format!("{}\r\n", fields.join(","))
+    }
+}
+
+fn active_batch<'a>(range: DateRange, after: i64) -> messages::BoxedQuery<'a, Mysql> {
+    let mut query = messages::table
+        .filter(messages::id.gt(after))
+        .order(messages::id.asc())
+        .limit(EXPORT_BATCH_SIZE)
+        .into_boxed();
+    if let Some(from) = range.from {
+        query = query.filter(messages::created_at.ge(from));
+    }
+    if let Some(until) = range.until {
+        query = query.filter(messages::created_at.lt(until));
+    }
+    query
+}
+
+fn archived_batch<'a>(range: DateRange, after: i64) -> messages_archive::BoxedQuery<'a, Mysql> {
+    let mut query = messages_archive::table
+        .filter(messages_archive::id.gt(after))
+        .order(messages_archive::id.asc())
+        .limit(EXPORT_BATCH_SIZE)
+        .into_boxed();
+    if let Some(from) = range.from {
+        query = query.filter(messages_archive::created_at.ge(from));
+    }
+    if let Some(until) = range.until {
+        query = query.filter(messages_archive::created_at.lt(until));
+    }
+    query
+}
+
+/// A streamed export sent as a file download
+#[derive(Responder)]
+pub struct MessageExport<S> {
+    stream: S,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+/// Export messages created within an optional date range, streamed in
+/// batches. Archived messages are included when `include_archived` is set.
+#[get("/admin/api/messages/export?<format>&<from>&<to>&<include_archived>")]
+pub async fn export_messages(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    format: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    include_archived: Option<bool>,
+) -> AppResult<MessageExport<TextStream![String]>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let format = ExportFormat::parse(format)?;
+    let range = DateRange::parse(from, to)?;
+    let include_archived = include_archived.unwrap_or(false);
+
+    record_audit(&mut db, &admin, "message.export", "message", "all").await;
+    info!(
+        "Admin '{}' exported messages as {} (archived included: {})",
+        admin.username,
+        format.extension(),
+        include_archived
+    );
+
+    let stream = TextStream! {
+        let mut first = true;
+        yield match format {
+            ExportFormat::Csv => CSV_HEADER.to_string(),
+            ExportFormat::Json => "[".to_string(),
+        };
+
+        let mut after = 0;
+        loop {
+            let batch = match active_batch(range, after)
+                .select(Message::as_select())
+                .load(&mut db)
+                .await
+            {
+                Ok(batch) => batch,
+                Err(e) => {
+                    error!("Message export failed: {}", e);
+                    break;
+                }
+            };
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after = last.id;
+            for message in batch {
+                yield export_line(format, ExportRow::from(message), &mut first);
+            }
+        }
+
+        if include_archived {
+            let mut after = 0;
+            loop {
+                let batch = match archived_batch(range, after)
+                    .select(ArchivedMessage::as_select())
+                    .load(&mut db)
+                    .await
+                {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        error!("Archived message export failed: {}", e);
+                        break;
+                    }
+                };
+                let Some(last) = batch.last() else {
+                    break;
+                };
+                after = last.id;
+                for message in batch {
+                    yield export_line(format, ExportRow::from(message), &mut first);
+                }
+            }
+        }
+
+        if format == ExportFormat::Json {
+            yield "\n]\n".to_string();
+        }
+    };
+
+    let filename = format!(
+        "messages-{}.{}",
+        chrono::Utc::now().format("%Y-%m-%d"),
+        format.extension()
+    );
+    Ok(MessageExport {
+        stream,
+        content_type: format.content_type(),
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{filename}\""),
+        ),
+    })
+}
+
+/// A row in the export format; JSON rows after the first are preceded by a
+/// comma
+fn export_line(format: ExportFormat, row: ExportRow, first: &mut bool) -> String {
+    match format {
+        ExportFormat::Csv => row.to_csv(),
+        ExportFormat::Json => {
+            let separator = if *first { "\n" } else { ",\n" };
+            *first = false;
+            match serde_json::to_string(&row) {
+                Ok(json) => format!("{separator}{json}"),
+                Err(e) => {
+                    error!("Failed to serialize exported message {}: {}", row.id, e);
+                    String::new()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> ExportRow {
+        ExportRow {
+            id: 7,
+            state: "active",
+            name: "Jana, \"Firma\"".to_string(),
+            email: "jana@example.com".to_string(),
+            phone: None,
+            subject: Some("=HYPERLINK(\"x\")".to_string()),
+            message: "Riadok 1\nRiadok 2".to_string(),
+            preferred_contact: Some("email".to_string()),
+            spam_score: 10,
+            created_at: NaiveDateTime::parse_from_str("2024-01-01 12:00:00", "%Y-%m-%d %H:%M:%S")
+                .unwrap(),
+            archived_at: None,
+            read_at: None,
+            answered_at: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_row_escaping() {
+        assert_eq!(
+            row().to_csv(),
+            "7,active,\"Jana, \"\"Firma\"\"\",jana@example.com,,\"'=HYPERLINK(\"\"x\"\")\",\"Riadok 1\nRiadok 2\",email,10,2024-01-01 12:00:00,,,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_json_lines_are_separated() {
+        let mut first = true;
+        let line = export_line(ExportFormat::Json, row(), &mut first);
+        assert!(line.starts_with("\n{\"id\":7,\"state\":\"active\""));
+        assert!(export_line(ExportFormat::Json, row(), &mut first).starts_with(",\n{"));
+    }
+
+    #[test]
+    fn test_export_parameters() {
+        assert_eq!(ExportFormat::parse(None).unwrap(), ExportFormat::Csv);
+        assert_eq!(
+            ExportFormat::parse(Some("JSON")).unwrap(),
+            ExportFormat::Json
+        );
+        assert!(ExportFormat::parse(Some("xml")).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+// Personal data erasure and export endpoints for GDPR requests
+
+use chrono::NaiveDateTime;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AdminGdprEraseRequest, ArchivedMessage, Message, MessageAttachmentInfo, MessageNote,
+    MessageReply, Permission,
+};
+use crate::routes::admin::archive::owns_side_rows;
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{
+    labels, message_attachments, message_labels, message_notes, message_replies, messages,
+    messages_archive, webhook_deliveries,
+};
+use crate::utils::{contains_pattern, validate_email};
+
+/// Number of rows removed from each table holding personal data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GdprEraseResponse {
+    pub email: String,
+    pub messages: usize,
+    pub messages_archive: usize,
+    pub attachments: usize,
+    pub replies: usize,
+    pub notes: usize,
+    pub labels: usize,
+    pub webhook_deliveries: usize,
+    pub erased_at: NaiveDateTime,
+}
+
+/// A label assigned to one of the exported messages
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportedMessageLabel {
+    pub message_id: i64,
+    pub label: String,
+}
+
+/// Everything stored about an email address. Side tables are keyed by the
+/// message id, which is the `original_id` for archived messages.
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PrivacyExport {
+    pub email: String,
+    pub exported_at: NaiveDateTime,
+    pub messages: Vec<Message>,
+    pub archived_messages: Vec<ArchivedMessage>,
+    pub attachments: Vec<MessageAttachmentInfo>,
+    pub replies: Vec<MessageReply>,
+    pub notes: Vec<MessageNote>,
+    pub labels: Vec<ExportedMessageLabel>,
+}
+
+fn erasure_email(raw: &str) -> AppResult<String> {
+    let email = raw.trim();
+    if !validate_email(email) {
+        return Err(AppError::InvalidInput(
+            "Valid email is required".to_string(),
+        ));
+    }
+    Ok(email.to_string())
+}
+
+/// `LIKE` pattern for webhook payloads that carry a message from `email`,
+/// which events serialize with an `email` field
+fn delivery_payload_pattern(email: &str) -> String {
+    contains_pattern(&format!("\"email\":{}", serde_json::Value::from(email)))
+}
+
+/// Ids of the active and archived messages sent from an address, as used by
+/// the side tables. An archived message's original id is left out when a
+/// live message or another archived record also holds it, as the side rows
+/// under it may then belong to someone else.
+async fn personal_message_ids(
+    conn: &mut AsyncMysqlConnection,
+    email: &str,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    let mut message_ids: Vec<i64> = messages::table
+        .filter(messages::email.eq(email))
+        .select(messages::id)
+        .load(conn)
+        .await?;
+
+    let archived_ids: Vec<i64> = messages_archive::table
+        .filter(messages_archive::email.eq(email))
+        .select(messages_archive::original_id)
+        .load(conn)
+        .await?;
+    let live_holders: Vec<i64> = messages::table
+        .filter(messages::id.eq_any(&archived_ids))
+        .select(messages::id)
+        .load(conn)
+        .await?;
+    let archived_holders: Vec<i64> = messages_archive::table
+        .filter(messages_archive::original_id.eq_any(&archived_ids))
+        .select(messages_archive::original_id)
+        .load(conn)
+        .await?;
+    let holders = |ids: &[i64], id: i64| ids.iter().filter(|&&held| held == id).count() as i64;
+    message_ids.extend(
+        archived_ids.into_iter().filter(|&id| {
+            owns_side_rows(holders(&live_holders, id), holders(&archived_holders, id))
+        }),
+    );
+    Ok(message_ids)
+}
+
+/// Delete everything stored about an address in one transaction and record
+/// the erasure in the audit log
+async fn erase_personal_data(
+    db: &mut Connection<MessagesDB>,
+    admin: &AdminUser,
+    email: String,
+) -> AppResult<Json<GdprEraseResponse>> {
+    let filter_email = email.clone();
+    let report = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                let message_ids = personal_message_ids(conn, &filter_email).await?;
+                let attachments = diesel::delete(
+                    message_attachments::table
+                        .filter(message_attachments::message_id.eq_any(&message_ids)),
+                )
+                .execute(&mut conn)
+                .await?;
+                let replies = diesel::delete(
+                    message_replies::table.filter(message_replies::message_id.eq_any(&message_ids)),
+                )
+                .execute(&mut conn)
+                .await?;
+                let notes = diesel::delete(
+                    message_notes::table.filter(message_notes::message_id.eq_any(&message_ids)),
+                )
+                .execute(&mut conn)
+                .await?;
+                let labels = diesel::delete(
+                    message_labels::table.filter(message_labels::message_id.eq_any(&message_ids)),
+                )
+                .execute(&mut conn)
+                .await?;
+
+                let messages_deleted =
+                    diesel::delete(messages::table.filter(messages::email.eq(&filter_email)))
+                        .execute(&mut conn)
+                        .await?;
+
+                let archived_deleted = diesel::delete(
+                    messages_archive::table.filter(messages_archive::email.eq(&filter_email)),
+                )
+                .execute(&mut conn)
+                .await?;
+
+                // Queued and logged webhook events keep a copy of the message
+                let webhook_deliveries = diesel::delete(webhook_deliveries::table.filter(
+                    webhook_deliveries::payload.like(delivery_payload_pattern(&filter_email)),
+                ))
+                .execute(&mut conn)
+                .await?;
+
+                Ok::<_, diesel::result::Error>(GdprEraseResponse {
+                    email: filter_email,
+                    messages: messages_deleted,
+                    messages_archive: archived_deleted,
+                    attachments,
+                    replies,
+                    notes,
+                    labels,
+                    webhook_deliveries,
+                    erased_at: chrono::Utc::now().naive_utc(),
+                })
+            })
+        })
+        .await
+        .map_err(|e| {
+            error!("Error erasing personal data: {}", e);
+            AppError::from(e)
+        })?;
+
+    // The erased address itself must not end up in the audit log
+    record_audit(db, admin, "gdpr.erase", "email", "redacted").await;
+    info!(
+        "GDPR erasure by admin '{}': removed {} messages, {} archived messages, {} attachments, {} replies, {} notes and {} webhook deliveries",
+        admin.username,
+        report.messages,
+        report.messages_archive,
+        report.attachments,
+        report.replies,
+        report.notes,
+        report.webhook_deliveries
+    );
+
+    Ok(Json(report))
+}
+
+/// Delete every stored message and archived message sent from an email
+/// address. Running it again for the same address reports zero counts.
+#[post("/admin/api/gdpr/erase", format = "json", data = "<request>")]
+pub async fn gdpr_erase(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminGdprEraseRequest>,
+) -> AppResult<Json<GdprEraseResponse>> {
+    admin.require(Permission::DeleteMessages)?;
+
+    let email = erasure_email(&request.email)?;
+    erase_personal_data(&mut db, &admin, email).await
+}
+
+/// Alias of `gdpr_erase` next to the matching export
+#[post("/admin/api/privacy/erase", format = "json", data = "<request>")]
+pub async fn privacy_erase(
+    db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminGdprEraseRequest>,
+) -> AppResult<Json<GdprEraseResponse>> {
+    gdpr_erase(db, admin, request).await
+}
+
+/// Export everything stored about an email address, read in one
+/// transaction so the parts are consistent with each other
+#[get("/admin/api/privacy/export?<email>")]
+pub async fn privacy_export(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    email: &str,
+) -> AppResult<Json<PrivacyExport>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let email = erasure_email(email)?;
+    let filter_email = email.clone();
+    let export = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                let message_ids = personal_message_ids(conn, &filter_email).await?;
+                let messages = messages::table
+                    .filter(messages::email.eq(&filter_email))
+                    .order(messages::id.asc())
+                    .select(Message::as_select())
+                    .load(&mut conn)
+                    .await?;
+                let archived_messages = messages_archive::table
+                    .filter(messages_archive::email.eq(&filter_email))
+                    .order(messages_archive::id.asc())
+                    .select(ArchivedMessage::as_select())
+                    .load(&mut conn)
+                    .await?;
+                let attachments = message_attachments::table
+                    .filter(message_attachments::message_id.eq_any(&message_ids))
+                    .order(message_attachments::id.asc())
+                    .select(MessageAttachmentInfo::as_select())
+                    .load(&mut conn)
+                    .await?;
+                let replies = message_replies::table
+                    .filter(message_replies::message_id.eq_any(&message_ids))
+                    .order(message_replies::id.asc())
+                    .select(MessageReply::as_select())
+                    .load(&mut conn)
+                    .await?;
+                let notes = message_notes::table
+                    .filter(message_notes::message_id.eq_any(&message_ids))
+                    .order(message_notes::id.asc())
+                    .select(MessageNote::as_select())
+                    .load(&mut conn)
+                    .await?;
+                let labels = message_labels::table
+                    .inner_join(labels::table)
+                    .filter(message_labels::message_id.eq_any(&message_ids))
+                    .order((message_labels::message_id.asc(), labels::name.asc()))
+                    .select((message_labels::message_id, labels::name))
+                    .load::<(i64, String)>(&mut conn)
+                    .await?
+                    .into_iter()
+                    .map(|(message_id, label)| ExportedMessageLabel { message_id, label })
+                    .collect();
+
+                Ok::<_, diesel::result::Error>(PrivacyExport {
+                    email: filter_email,
+                    exported_at: chrono::Utc::now().naive_utc(),
+                    messages,
+                    archived_messages,
+                    attachments,
+                    replies,
+                    notes,
+                    labels,
+                })
+            })
+        })
+        .await
+        .map_err(|e| {
+            error!("Error exporting personal data: {}", e);
+            AppError::from(e)
+        })?;
+
+    record_audit(&mut db, &admin, "gdpr.export", "email", "redacted").await;
+    info!(
+        "GDPR export by admin '{}': {} messages and {} archived messages",
+        admin.username,
+        export.messages.len(),
+        export.archived_messages.len()
+    );
+
+    Ok(Json(export))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erasure_email_is_trimmed() {
+        assert_eq!(
+            erasure_email("  jane@example.com ").unwrap(),
+            "jane@example.com"
+        );
+    }
+
+    #[test]
+    fn test_erasure_email_rejects_invalid_address() {
+        assert!(matches!(
+            erasure_email("not-an-email"),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            erasure_email("   "),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_delivery_payload_pattern() {
+        assert_eq!(
+            delivery_payload_pattern("jane_doe@example.com"),
+            "%\"email\":\"jane\\_doe@example.com\"%"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// Image upload pre-validation endpoint and cached image responses
+
+use std::io::Cursor;
+
+use rocket::State;
+use rocket::form::Form;
+use rocket::http::{Accept, ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use tracing::{debug, error};
+
+use crate::error::{AppError, AppResult};
+use crate::media::{Media, StoredImage};
+use crate::models::{AdminValidateImageMultipart, ImageValidationResponse, Permission};
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::feeds::ConditionalHeaders;
+use crate::utils::{
+    ImageSettings, ImageSize, needs_jpeg_fallback, stored_image_variant, transcode_to_jpeg,
+    validate_image_upload,
+};
+
+/// Images keep their URL when replaced, so clients revalidate them with the
+/// `ETag` after a day
+const IMAGE_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// Stored image data, answered with `304 Not Modified` when the client's
+/// `If-None-Match` has its hash
+pub struct CachedImage {
+    data: Vec<u8>,
+    content_type: ContentType,
+    etag: String,
+    not_modified: bool,
+}
+
+impl CachedImage {
+    pub fn new(
+        data: Vec<u8>,
+        image_mime: &str,
+        image_hash: &str,
+        conditional: &ConditionalHeaders,
+    ) -> Self {
+        let etag = format!("\"{image_hash}\"");
+        let not_modified = conditional.matches_etag(&etag);
+        CachedImage {
+            data,
+            content_type: ContentType::parse_flexible(image_mime).unwrap_or(ContentType::JPEG),
+            etag,
+            not_modified,
+        }
+    }
+
+    /// `304 Not Modified` when the client already has the image with this
+    /// hash, checked before the image data is loaded
+    pub fn unchanged(image_hash: &str, conditional: &ConditionalHeaders) -> Option<Self> {
+        let etag = format!("\"{image_hash}\"");
+        conditional.matches_etag(&etag).then_some(CachedImage {
+            data: Vec::new(),
+            content_type: ContentType::Binary,
+            etag,
+            not_modified: true,
+        })
+    }
+}
+
+impl<'r> Responder<'r, 'static> for CachedImage {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .raw_header("ETag", self.etag)
+            .raw_header("Cache-Control", IMAGE_CACHE_CONTROL)
+            // Images stored in a type the client does not accept are sent as JPEG
+            .raw_header("Vary", "Accept");
+        if self.not_modified {
+            response.status(Status::NotModified);
+        } else {
+            response
+                .header(self.content_type)
+                .sized_body(self.data.len(), Cursor::new(self.data));
+        }
+        response.ok()
+    }
+}
+
+/// An image from the media store in the requested size. Variants missing
+/// from the store, e.g. of images stored before variants were generated,
+/// are created and saved on the first request. Clients that do not accept
+/// the stored type get a JPEG copy, likewise saved when first requested.
+pub(crate) async fn stored_image_response(
+    conn: &mut AsyncMysqlConnection,
+    media: &Media,
+    image: &StoredImage,
+    size: ImageSize,
+    accept: Option<&Accept>,
+    conditional: &ConditionalHeaders,
+) -> AppResult<CachedImage> {
+    let fallback = needs_jpeg_fallback(accept, &image.mime);
+    let mut variant_hash = size.variant_hash(&image.hash);
+    if fallback {
+        variant_hash.push_str("-jpeg");
+    }
+    if let Some(unchanged) = CachedImage::unchanged(&variant_hash, conditional) {
+        return Ok(unchanged);
+    }
+
+    let fallback_key = size.jpeg_fallback_key(&image.key);
+    if fallback && let Some(data) = media.get(conn, &fallback_key, "image/jpeg").await? {
+        return Ok(CachedImage::new(
+            data,
+            "image/jpeg",
+            &variant_hash,
+            conditional,
+        ));
+    }
+
+    let missing = || {
+        error!("Image data {} is missing from the media store", image.key);
+        AppError::NotFound
+    };
+    let variant_key = size.variant_key(&image.key);
+    let data = match media.get(conn, &variant_key, &image.mime).await? {
+        Some(data) => data,
+        None if size == ImageSize::Full => return Err(missing()),
+        None => {
+            let full = media
+                .get(conn, &image.key, &image.mime)
+                .await?
+                .ok_or_else(missing)?;
+            let variant = stored_image_variant(&full, &image.mime, size)?;
+            if let Err(e) = media.put(conn, &variant_key, &variant, &image.mime).await {
+                error!("Error saving image variant {}: {}", variant_key, e);
+            }
+            variant
+        }
+    };
+
+    if fallback {
+        let data = transcode_to_jpeg(&data, &image.mime)?;
+        if let Err(e) = media.put(conn, &fallback_key, &data, "image/jpeg").await {
+            error!("Error saving JPEG copy {}: {}", fallback_key, e);
+        }
+        return Ok(CachedImage::new(
+            data,
+            "image/jpeg",
+            &variant_hash,
+            conditional,
+        ));
+    }
+    Ok(CachedImage::new(
+        data,
+        &image.mime,
+        &variant_hash,
+        conditional,
+    ))
+}
+
+/// Check an image with the same rules as offer/blog uploads, without
+/// storing or recompressing it, so the admin UI can give early feedback.
+#[post("/admin/api/images/validate", data = "<upload>")]
+pub async fn validate_image(
+    admin: AdminUser,
+    image_settings: &State<ImageSettings>,
+    upload: Form<AdminValidateImageMultipart<'_>>,
+) -> AppResult<Json<ImageValidationResponse>> {
+    admin.require(Permission::ManageContent)?;
+
+    let response = match validate_image_upload(&upload.image, image_settings).await? {
+        Ok((width, height)) => ImageValidationResponse {
+            ok: true,
+            reason: None,
+            width: Some(width),
+            height: Some(height),
+        },
+        Err(rejection) => {
+            debug!("Image validation rejected upload: {}", rejection.reason());
+            ImageValidationResponse {
+                ok: false,
+                reason: Some(rejection.reason().to_string()),
+                width: None,
+                height: None,
+            }
+        }
+    };
+
+    Ok(Json(response))
+}
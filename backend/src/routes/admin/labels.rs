@@ -0,0 +1,266 @@
+// Labels for sorting messages into categories
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AdminLabelRequest, Label, MessageLabelsRequest, NewLabel, NewMessageLabel, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{labels, message_labels, messages};
+
+const MAX_LABEL_NAME_LEN: usize = 64;
+/// Color of labels created without one
+const DEFAULT_LABEL_COLOR: &str = "#6b7280";
+
+/// Trimmed label name and color, validated
+fn label_fields(request: &AdminLabelRequest) -> AppResult<NewLabel> {
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_LABEL_NAME_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Label name must be between 1 and {MAX_LABEL_NAME_LEN} characters."
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(AppError::InvalidInput(
+            "Label name must not contain control characters.".to_string(),
+        ));
+    }
+
+    let color = match request.color.as_deref().map(str::trim) {
+        None | Some("") => DEFAULT_LABEL_COLOR.to_string(),
+        Some(color) => {
+            let is_hex = color.len() == 7
+                && color.starts_with('#')
+                && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+            if !is_hex {
+                return Err(AppError::InvalidInput(
+                    "Label color must be a hex color like #2563eb.".to_string(),
+                ));
+            }
+            color.to_ascii_lowercase()
+        }
+    };
+
+    Ok(NewLabel {
+        name: name.to_string(),
+        color,
+    })
+}
+
+fn map_label_write_error(error: diesel::result::Error) -> AppError {
+    match error {
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        ) => AppError::InvalidInput("A label with this name already exists.".to_string()),
+        other => AppError::from(other),
+    }
+}
+
+/// Labels of a message, by name
+pub(crate) async fn load_message_labels(
+    db: &mut Connection<MessagesDB>,
+    message_id: i64,
+) -> AppResult<Vec<Label>> {
+    Ok(labels::table
+        .inner_join(message_labels::table)
+        .filter(message_labels::message_id.eq(message_id))
+        .order(labels::name.asc())
+        .select(Label::as_select())
+        .load(db)
+        .await?)
+}
+
+#[get("/admin/api/labels")]
+pub async fn list_labels(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<Label>>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let labels = labels::table
+        .order(labels::name.asc())
+        .select(Label::as_select())
+        .load(&mut db)
+        .await?;
+    Ok(Json(labels))
+}
+
+#[post("/admin/api/labels", format = "json", data = "<request>")]
+pub async fn create_label(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminLabelRequest>,
+) -> AppResult<Json<Label>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let new_label = label_fields(&request)?;
+    diesel::insert_into(labels::table)
+        .values(&new_label)
+        .execute(&mut db)
+        .await
+        .map_err(map_label_write_error)?;
+
+    let label = labels::table
+        .filter(labels::name.eq(&new_label.name))
+        .select(Label::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "label.create", "label", label.id).await;
+    info!("Admin '{}' created label '{}'", admin.username, label.name);
+    Ok(Json(label))
+}
+
+#[put("/admin/api/labels/<id>", format = "json", data = "<request>")]
+pub async fn update_label(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminLabelRequest>,
+) -> AppResult<Json<Label>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let fields = label_fields(&request)?;
+    let updated = diesel::update(labels::table.find(id))
+        .set((
+            labels::name.eq(&fields.name),
+            labels::color.eq(&fields.color),
+        ))
+        .execute(&mut db)
+        .await
+        .map_err(map_label_write_error)?;
+    if updated == 0 {
+        let exists: i64 = labels::table.find(id).count().get_result(&mut db).await?;
+        if exists == 0 {
+            return Err(AppError::NotFound);
+        }
+    }
+
+    let label = labels::table
+        .find(id)
+        .select(Label::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "label.update", "label", id).await;
+    info!("Admin '{}' updated label {}", admin.username, id);
+    Ok(Json(label))
+}
+
+/// Delete a label and remove it from every message
+#[delete("/admin/api/labels/<id>")]
+pub async fn delete_label(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageMessages)?;
+
+    let deleted = diesel::delete(labels::table.find(id))
+        .execute(&mut db)
+        .await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "label.delete", "label", id).await;
+    info!("Admin '{}' deleted label {}", admin.username, id);
+    Ok(Status::Ok)
+}
+
+/// Replace the labels of an active message
+#[put("/admin/api/messages/<id>/labels", format = "json", data = "<request>")]
+pub async fn set_message_labels(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<MessageLabelsRequest>,
+) -> AppResult<Json<Vec<Label>>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let exists: i64 = messages::table.find(id).count().get_result(&mut db).await?;
+    if exists == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    let mut label_ids = request.label_ids.clone();
+    label_ids.sort_unstable();
+    label_ids.dedup();
+    let known: i64 = labels::table
+        .filter(labels::id.eq_any(&label_ids))
+        .count()
+        .get_result(&mut db)
+        .await?;
+    if known as usize != label_ids.len() {
+        return Err(AppError::InvalidInput("Unknown label id.".to_string()));
+    }
+
+    let assignments: Vec<NewMessageLabel> = label_ids
+        .iter()
+        .map(|&label_id| NewMessageLabel {
+            message_id: id,
+            label_id,
+        })
+        .collect();
+    db.transaction(|mut conn| {
+        Box::pin(async move {
+            diesel::delete(message_labels::table.filter(message_labels::message_id.eq(id)))
+                .execute(&mut conn)
+                .await?;
+            if !assignments.is_empty() {
+                diesel::insert_into(message_labels::table)
+                    .values(&assignments)
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok::<_, diesel::result::Error>(())
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!("Error setting labels of message {}: {}", id, e);
+        AppError::from(e)
+    })?;
+
+    record_audit(&mut db, &admin, "message.label", "message", id).await;
+    Ok(Json(load_message_labels(&mut db, id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str, color: Option<&str>) -> AdminLabelRequest {
+        AdminLabelRequest {
+            name: name.to_string(),
+            color: color.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_label_fields() {
+        let label = label_fields(&request(" support ", None)).unwrap();
+        assert_eq!(label.name, "support");
+        assert_eq!(label.color, DEFAULT_LABEL_COLOR);
+        assert_eq!(
+            label_fields(&request("sales", Some("#16A34A")))
+                .unwrap()
+                .color,
+            "#16a34a"
+        );
+
+        assert!(label_fields(&request("  ", None)).is_err());
+        assert!(label_fields(&request(&"a".repeat(MAX_LABEL_NAME_LEN + 1), None)).is_err());
+        assert!(label_fields(&request("a\nb", None)).is_err());
+        assert!(label_fields(&request("sales", Some("green"))).is_err());
+        assert!(label_fields(&request("sales", Some("#16a34"))).is_err());
+    }
+}
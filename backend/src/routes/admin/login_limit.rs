@@ -0,0 +1,286 @@
+// Brute-force protection for admin login, tracked per client IP in Redis
+
+use redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
+use rocket::State;
+use serde_json::json;
+use std::net::IpAddr;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::mailer::Mailer;
+
+const FAILURES_PREFIX: &str = "admin_login_failures:";
+const LOCK_PREFIX: &str = "admin_login_lock:";
+/// Failed logins from all clients in the current alert window
+const TOTAL_FAILURES_KEY: &str = "admin_login_failures_total";
+/// Time allowed for posting an alert to `ADMIN_LOGIN_ALERT_WEBHOOK_URL`
+const ALERT_WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Notifies site owners, by email and/or webhook, when failed admin logins
+/// from all clients exceed `ADMIN_LOGIN_ALERT_THRESHOLD` within the alert
+/// window. At most one alert is sent per window.
+#[derive(Clone)]
+pub struct LoginAlerts {
+    threshold: u64,
+    window_secs: u64,
+    emails: Vec<String>,
+    webhook_url: Option<String>,
+    mailer: Mailer,
+    http: reqwest::Client,
+}
+
+impl LoginAlerts {
+    pub fn from_config(config: &AppConfig, mailer: Mailer) -> Self {
+        LoginAlerts {
+            threshold: config.admin_login_alert_threshold,
+            window_secs: config.admin_login_alert_window_secs.max(1),
+            emails: config
+                .admin_login_alert_emails
+                .split(',')
+                .map(str::trim)
+                .filter(|email| !email.is_empty())
+                .map(str::to_string)
+                .collect(),
+            webhook_url: config
+                .admin_login_alert_webhook_url
+                .as_deref()
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string),
+            mailer,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Count a failure towards the alert window and send the alert in the
+    /// background once the threshold is crossed
+    async fn record_failure(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        client_ip: Option<IpAddr>,
+    ) -> AppResult<()> {
+        if self.threshold == 0 {
+            return Ok(());
+        }
+
+        // The window starts with the counter, so the key never lives on
+        // without an expiry
+        let window = SetOptions::default()
+            .conditional_set(ExistenceCheck::NX)
+            .with_expiration(SetExpiry::EX(self.window_secs));
+        let (failures,): (u64,) = redis::pipe()
+            .atomic()
+            .set_options(TOTAL_FAILURES_KEY, 0, window)
+            .ignore()
+            .incr(TOTAL_FAILURES_KEY, 1)
+            .query_async(conn)
+            .await?;
+
+        if crosses_threshold(failures, self.threshold) {
+            let alerts = self.clone();
+            let client = client_key(client_ip);
+            rocket::tokio::spawn(async move { alerts.send(failures, &client).await });
+        }
+        Ok(())
+    }
+
+    async fn send(&self, failures: u64, last_client: &str) {
+        let text = format!(
+            "{failures} failed admin logins within {} minutes, the latest from {last_client}.",
+            self.window_secs.div_ceil(60)
+        );
+        warn!("Login alert: {}", text);
+
+        for email in &self.emails {
+            if !self.mailer.is_enabled() {
+                warn!(
+                    "Cannot email login alert to {}: SMTP is not configured",
+                    email
+                );
+                break;
+            }
+            match self
+                .mailer
+                .send(email, "Failed admin logins", format!("{text}\n"))
+                .await
+            {
+                Ok(()) => info!("Sent login alert to {}", email),
+                Err(e) => error!("Failed to email login alert to {}: {}", email, e),
+            }
+        }
+
+        if let Some(url) = &self.webhook_url {
+            let payload = json!({
+                "event": "admin_login_failures",
+                "failures": failures,
+                "window_secs": self.window_secs,
+                "last_client_ip": last_client,
+                "text": text,
+            });
+            match self
+                .http
+                .post(url)
+                .timeout(Duration::from_secs(ALERT_WEBHOOK_TIMEOUT_SECS))
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                Ok(_) => info!("Sent login alert webhook"),
+                Err(e) => error!("Login alert webhook failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Alert exactly once per window, on the first failure above the threshold
+fn crosses_threshold(failures: u64, threshold: u64) -> bool {
+    threshold > 0 && failures == threshold + 1
+}
+
+fn client_key(client_ip: Option<IpAddr>) -> String {
+    client_ip
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Lockout after `failures` failed attempts: none below the limit, then
+/// doubling from the backoff delay for every further failure, capped at the
+/// lockout window. A limit of zero disables lockouts.
+fn lockout_secs(
+    failures: u64,
+    max_failures: u64,
+    backoff_secs: u64,
+    window_secs: u64,
+) -> Option<u64> {
+    if max_failures == 0 || failures < max_failures {
+        return None;
+    }
+
+    let exponent = (failures - max_failures).min(32) as u32;
+    Some(backoff_secs.saturating_mul(1 << exponent).min(window_secs))
+}
+
+/// Fail with 429 while the client is locked out
+pub async fn ensure_login_allowed(
+    redis: &State<redis::Client>,
+    client_ip: Option<IpAddr>,
+) -> AppResult<()> {
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let ttl_secs: i64 = conn
+        .ttl(format!("{LOCK_PREFIX}{}", client_key(client_ip)))
+        .await?;
+
+    match u64::try_from(ttl_secs) {
+        Ok(retry_after_secs) if retry_after_secs > 0 => {
+            Err(AppError::TooManyRequests { retry_after_secs })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Count a failed login. Failures are forgotten after a lockout window
+/// without new attempts.
+pub async fn record_login_failure(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    alerts: &LoginAlerts,
+    client_ip: Option<IpAddr>,
+) -> AppResult<()> {
+    let client = client_key(client_ip);
+    let failures_key = format!("{FAILURES_PREFIX}{client}");
+    let window_secs = i64::try_from(config.admin_login_lockout_secs).unwrap_or(i64::MAX);
+
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let (failures,): (u64,) = redis::pipe()
+        .atomic()
+        .incr(&failures_key, 1)
+        .expire(&failures_key, window_secs)
+        .ignore()
+        .query_async(&mut conn)
+        .await?;
+
+    if let Some(lock_secs) = lockout_secs(
+        failures,
+        config.admin_login_max_failures,
+        config.admin_login_backoff_secs,
+        config.admin_login_lockout_secs,
+    ) {
+        warn!(
+            "Locking out admin login from {} for {}s after {} failures",
+            client, lock_secs, failures
+        );
+        let _: () = conn
+            .set_ex(format!("{LOCK_PREFIX}{client}"), "1", lock_secs)
+            .await?;
+    }
+
+    alerts.record_failure(&mut conn, client_ip).await
+}
+
+pub async fn clear_login_failures(
+    redis: &State<redis::Client>,
+    client_ip: Option<IpAddr>,
+) -> AppResult<()> {
+    let client = client_key(client_ip);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let _: usize = conn
+        .del(&[
+            format!("{FAILURES_PREFIX}{client}"),
+            format!("{LOCK_PREFIX}{client}"),
+        ])
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_lockout_below_limit() {
+        assert_eq!(lockout_secs(0, 5, 30, 900), None);
+        assert_eq!(lockout_secs(4, 5, 30, 900), None);
+    }
+
+    #[test]
+    fn test_lockout_doubles_up_to_window() {
+        assert_eq!(lockout_secs(5, 5, 30, 900), Some(30));
+        assert_eq!(lockout_secs(6, 5, 30, 900), Some(60));
+        assert_eq!(lockout_secs(8, 5, 30, 900), Some(240));
+        assert_eq!(lockout_secs(10, 5, 30, 900), Some(900));
+        assert_eq!(lockout_secs(500, 5, 30, 900), Some(900));
+    }
+
+    #[test]
+    fn test_zero_limit_disables_lockout() {
+        assert_eq!(lockout_secs(100, 0, 30, 900), None);
+    }
+
+    #[test]
+    fn test_alert_once_above_threshold() {
+        assert!(!crosses_threshold(20, 20));
+        assert!(crosses_threshold(21, 20));
+        assert!(!crosses_threshold(22, 20));
+        assert!(!crosses_threshold(1, 0));
+    }
+
+    #[test]
+    fn test_too_many_requests_sets_retry_after() {
+        let rocket = rocket::build().mount("/", routes![locked]);
+        let client = rocket::local::blocking::Client::tracked(rocket).unwrap();
+        let response = client.get("/locked").dispatch();
+
+        assert_eq!(response.status(), rocket::http::Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("42"));
+    }
+
+    #[get("/locked")]
+    fn locked() -> AppResult<()> {
+        Err(AppError::TooManyRequests {
+            retry_after_secs: 42,
+        })
+    }
+}
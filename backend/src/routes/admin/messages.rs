@@ -1,39 +1,133 @@
 // Active message management endpoints
 
+use diesel::mysql::Mysql;
+use diesel::sql_types::{Double, Text};
 use rocket::State;
-use rocket::http::{CookieJar, Status};
+use rocket::http::{Accept, ContentType, Header, Status};
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
 use tracing::{error, info, warn};
 
-use crate::db::MessagesDB;
+use crate::db::{MessagesDB, last_insert_id};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    ArchiveAction, ArchiveRequest, ArchivedMessage, ContactMessage, Message, PaginatedMessages,
+    ArchiveAction, ArchiveOutcome, ArchiveRequest, ArchivedMessage, Message, MessageAttachmentInfo,
+    MessageDetail, MessageGroup, MessageReadRequest, MessageReply, MessageState, MessageThread,
+    PaginatedMessageGroups, PaginatedMessages, Permission, UnreadMessageCount,
 };
-use crate::routes::admin::auth::is_admin_authenticated;
-use crate::schema::{messages, messages_archive};
+use crate::notifier::ChatNotifier;
+use crate::routes::admin::archive::restore_archived;
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::labels::load_message_labels;
+use crate::routes::admin::notes::load_notes;
+use crate::routes::admin::replies::load_replies;
+use crate::schema::{
+    message_attachments, message_labels, message_replies, messages, messages_archive,
+};
+use crate::utils::{search_query, validate_email};
+use crate::webhooks::enqueue_webhook_event;
+
+/// Message list response: plain rows, or rows grouped by sender email
+#[derive(Responder)]
+pub enum MessagesResponse {
+    Messages(Json<PaginatedMessages>),
+    Groups(Json<PaginatedMessageGroups>),
+}
+
+/// Messages whose spam score lies within the optional bounds, that carry
+/// the given label and that were sent from the given offer, if any
+fn filtered_messages<'a>(
+    min_spam_score: Option<i32>,
+    max_spam_score: Option<i32>,
+    label: Option<i64>,
+    offer: Option<i64>,
+) -> messages::BoxedQuery<'a, diesel::mysql::Mysql> {
+    let mut query = messages::table.into_boxed();
+    if let Some(min) = min_spam_score {
+        query = query.filter(messages::spam_score.ge(min));
+    }
+    if let Some(max) = max_spam_score {
+        query = query.filter(messages::spam_score.le(max));
+    }
+    if let Some(label) = label {
+        query = query.filter(
+            messages::id.eq_any(
+                message_labels::table
+                    .filter(message_labels::label_id.eq(label))
+                    .select(message_labels::message_id),
+            ),
+        );
+    }
+    if let Some(offer) = offer {
+        query = query.filter(messages::offer_id.eq(offer));
+    }
+    query
+}
 
-#[get("/admin/api/messages?<page>&<limit>")]
+/// `MATCH ... AGAINST` over the FULLTEXT index on name, email, subject and
+/// message. Positive for matching messages, higher for better matches.
+fn search_relevance(
+    query: &str,
+) -> Box<dyn BoxableExpression<messages::table, Mysql, SqlType = Double>> {
+    Box::new(
+        diesel::dsl::sql::<Double>("MATCH (name, email, subject, message) AGAINST (")
+            .bind::<Text, _>(query.to_string())
+            .sql(" IN NATURAL LANGUAGE MODE)"),
+    )
+}
+
+#[get(
+    "/admin/api/messages?<page>&<limit>&<group_by_email>&<min_spam_score>&<max_spam_score>&<label>&<offer_id>"
+)]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_messages(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
     page: Option<i64>,
     limit: Option<i64>,
-) -> AppResult<Json<PaginatedMessages>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    group_by_email: Option<bool>,
+    min_spam_score: Option<i32>,
+    max_spam_score: Option<i32>,
+    label: Option<i64>,
+    offer_id: Option<i64>,
+) -> AppResult<MessagesResponse> {
+    admin.require(Permission::ManageMessages)?;
 
     let page = page.unwrap_or(1);
     let limit = limit.unwrap_or(10);
     let offset = (page - 1) * limit;
 
-    let total_count: i64 = messages::table
+    if group_by_email.unwrap_or(false) {
+        let all_messages = filtered_messages(min_spam_score, max_spam_score, label, offer_id)
+            .select(Message::as_select())
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error loading messages for grouping: {}", e);
+                AppError::from(e)
+            })?;
+
+        let groups = MessageGroup::group_by_email(all_messages);
+        let total = groups.len() as i64;
+        let data: Vec<MessageGroup> = groups
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+
+        info!("Retrieved {} message groups (page {})", data.len(), page);
+
+        return Ok(MessagesResponse::Groups(Json(PaginatedMessageGroups {
+            data,
+            total,
+            page,
+            limit,
+        })));
+    }
+
+    let total_count: i64 = filtered_messages(min_spam_score, max_spam_score, label, offer_id)
         .count()
         .get_result(&mut db)
         .await
@@ -42,7 +136,7 @@ pub async fn get_messages(
             AppError::from(e)
         })?;
 
-    let results = messages::table
+    let results = filtered_messages(min_spam_score, max_spam_score, label, offer_id)
         .order(messages::created_at.desc())
         .limit(limit)
         .offset(offset)
@@ -61,12 +155,49 @@ pub async fn get_messages(
         (total_count + limit - 1) / limit
     );
 
-    Ok(Json(PaginatedMessages {
+    Ok(MessagesResponse::Messages(Json(PaginatedMessages {
         data: results,
         total: total_count,
         page,
         limit,
-    }))
+    })))
+}
+
+/// What an archive or restore request has to do, given where the message
+/// currently lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveStep {
+    Move,
+    AlreadyDone,
+    Missing,
+}
+
+fn archive_step(action: &ArchiveAction, is_active: bool, is_archived: bool) -> ArchiveStep {
+    let (source_present, target_present) = match action {
+        ArchiveAction::Archive => (is_active, is_archived),
+        ArchiveAction::Restore => (is_archived, is_active),
+    };
+
+    if source_present {
+        ArchiveStep::Move
+    } else if target_present {
+        ArchiveStep::AlreadyDone
+    } else {
+        ArchiveStep::Missing
+    }
+}
+
+/// Archive endpoint response: the outcome as JSON when the client accepts
+/// it, otherwise a bare status for older clients
+#[derive(Responder)]
+pub enum ArchiveResponse {
+    Outcome(Json<ArchiveOutcome>),
+    Status(Status),
+}
+
+/// Whether the client asked for a JSON response body
+fn wants_json(accept: Option<&Accept>) -> bool {
+    accept.is_some_and(|accept| accept.iter().any(|media_type| media_type.is_json()))
 }
 
 #[post(
@@ -76,15 +207,13 @@ pub async fn get_messages(
 )]
 pub async fn archive_message(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    notifier: &State<ChatNotifier>,
+    accept: Option<&Accept>,
     id: i64,
     request: Json<ArchiveRequest>,
-) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+) -> AppResult<ArchiveResponse> {
+    admin.require(Permission::ManageMessages)?;
 
     let action = match request.action.as_str() {
         "archive" => ArchiveAction::Archive,
@@ -95,93 +224,122 @@ pub async fn archive_message(
         }
     };
 
-    match action {
-        ArchiveAction::Archive => {
-            // Get the message first
-            let message: Message = messages::table
-                .find(id)
-                .select(Message::as_select())
-                .first(&mut db)
-                .await
-                .map_err(|e| {
-                    error!("Error retrieving message for archiving: {}", e);
-                    AppError::NotFound
-                })?;
+    let message: Option<Message> = messages::table
+        .find(id)
+        .select(Message::as_select())
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error retrieving message {}: {}", id, e);
+            AppError::from(e)
+        })?;
 
+    // The most recent archived record for the original id
+    let archived: Option<ArchivedMessage> = messages_archive::table
+        .filter(messages_archive::original_id.eq(id))
+        .order(messages_archive::archived_at.desc())
+        .select(ArchivedMessage::as_select())
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error retrieving archived message {}: {}", id, e);
+            AppError::from(e)
+        })?;
+
+    let step = archive_step(&action, message.is_some(), archived.is_some());
+    let outcome = match (step, action) {
+        (ArchiveStep::Missing, _) => {
+            error!("Message {} not found for archive request", id);
+            return Err(AppError::NotFound);
+        }
+        (ArchiveStep::AlreadyDone, ArchiveAction::Archive) => ArchiveOutcome {
+            state: MessageState::Archived,
+            message_id: id,
+            archive_id: archived.map(|entry| entry.id),
+            noop: true,
+        },
+        (ArchiveStep::AlreadyDone, ArchiveAction::Restore) => ArchiveOutcome {
+            state: MessageState::Active,
+            message_id: id,
+            archive_id: None,
+            noop: true,
+        },
+        (ArchiveStep::Move, ArchiveAction::Archive) => {
+            let Some(message) = message else {
+                return Err(AppError::NotFound);
+            };
+
+            let event_data = serde_json::json!({ "message": message });
             // Create archived message
-            let archived_message = message.into_archived();
+            let archived_message = message.clone().into_archived();
 
             // Start transaction: insert into archive, then delete original
-            db.transaction(|mut conn| {
-                Box::pin(async move {
-                    diesel::insert_into(messages_archive::table)
-                        .values(&archived_message)
-                        .execute(&mut conn)
-                        .await?;
-
-                    diesel::delete(messages::table.find(id))
-                        .execute(&mut conn)
-                        .await?;
-
-                    Ok::<_, diesel::result::Error>(())
-                })
-            })
-            .await
-            .map_err(|e| {
-                error!("Error archiving message in transaction: {}", e);
-                AppError::from(e)
-            })?;
+            let archive_id = db
+                .transaction(|mut conn| {
+                    Box::pin(async move {
+                        diesel::insert_into(messages_archive::table)
+                            .values(&archived_message)
+                            .execute(&mut conn)
+                            .await?;
 
-            info!("Message {} archived successfully", id);
-            Ok(Status::Ok)
-        }
-        ArchiveAction::Restore => {
-            // Find the most recent archived record for the original id
-            let archived: ArchivedMessage = messages_archive::table
-                .filter(messages_archive::original_id.eq(id))
-                .order(messages_archive::archived_at.desc())
-                .select(ArchivedMessage::as_select())
-                .first(&mut db)
+                        let archive_id: u64 = diesel::select(last_insert_id())
+                            .get_result(&mut conn)
+                            .await?;
+
+                        diesel::delete(messages::table.find(id))
+                            .execute(&mut conn)
+                            .await?;
+
+                        Ok::<_, diesel::result::Error>(archive_id as i64)
+                    })
+                })
                 .await
                 .map_err(|e| {
-                    error!("Error retrieving archived message for restoration: {}", e);
-                    AppError::NotFound
+                    error!("Error archiving message in transaction: {}", e);
+                    AppError::from(e)
                 })?;
 
-            // Convert back to regular message (attempt to restore original id)
-            let message = ContactMessage {
-                id: Some(archived.original_id),
-                name: archived.name,
-                email: archived.email,
-                phone: archived.phone,
-                subject: archived.subject,
-                message: archived.message,
+            notifier.message_archived(&message);
+            enqueue_webhook_event(&mut db, "message.archived", event_data).await;
+            info!("Message {} archived successfully", id);
+            ArchiveOutcome {
+                state: MessageState::Archived,
+                message_id: id,
+                archive_id: Some(archive_id),
+                noop: false,
+            }
+        }
+        (ArchiveStep::Move, ArchiveAction::Restore) => {
+            let Some(archived) = archived else {
+                return Err(AppError::NotFound);
             };
 
-            // Start transaction: insert back into messages, delete archive record
-            db.transaction(|mut conn| {
-                Box::pin(async move {
-                    diesel::insert_into(messages::table)
-                        .values(&message)
-                        .execute(&mut conn)
-                        .await?;
-
-                    diesel::delete(messages_archive::table.find(archived.id))
-                        .execute(&mut conn)
-                        .await?;
-
-                    Ok::<_, diesel::result::Error>(())
-                })
-            })
-            .await
-            .map_err(|e| {
-                error!("Error restoring message in transaction: {}", e);
-                AppError::from(e)
-            })?;
+            let message_id = restore_archived(&mut db, archived).await?;
 
             info!("Message {} restored from archive successfully", id);
-            Ok(Status::Ok)
+            ArchiveOutcome {
+                state: MessageState::Active,
+                message_id,
+                archive_id: None,
+                noop: false,
+            }
         }
+    };
+
+    if !outcome.noop {
+        let action = match outcome.state {
+            MessageState::Archived => "message.archive",
+            MessageState::Active => "message.restore",
+        };
+        record_audit(&mut db, &admin, action, "message", id).await;
+    }
+
+    if wants_json(accept) {
+        Ok(ArchiveResponse::Outcome(Json(outcome)))
+    } else {
+        Ok(ArchiveResponse::Status(Status::Ok))
     }
 }
 
@@ -189,16 +347,359 @@ pub async fn archive_message(
 #[delete("/admin/api/messages/<id>")]
 pub async fn delete_message(
     db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    notifier: &State<ChatNotifier>,
+    accept: Option<&Accept>,
     id: i64,
-) -> AppResult<Status> {
+) -> AppResult<ArchiveResponse> {
     info!("Request to delete (archive) message {}", id);
     // Instead of deleting, archive the message
     let archive_request = Json(ArchiveRequest {
         action: "archive".to_string(),
     });
 
-    archive_message(db, redis, cookies, remote_addr, id, archive_request).await
+    archive_message(db, admin, notifier, accept, id, archive_request).await
+}
+
+/// Active messages matching a full-text query, best matches first
+#[get("/admin/api/messages/search?<q>&<page>&<limit>")]
+pub async fn search_messages(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    q: &str,
+    page: Option<i64>,
+    limit: Option<i64>,
+) -> AppResult<Json<PaginatedMessages>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let query = search_query(q)?;
+    let page = page.unwrap_or(1);
+    let limit = limit.unwrap_or(10);
+    let offset = (page - 1) * limit;
+
+    let total: i64 = messages::table
+        .filter(search_relevance(query).gt(0.0))
+        .count()
+        .get_result(&mut db)
+        .await?;
+    let results = messages::table
+        .filter(search_relevance(query).gt(0.0))
+        .order((search_relevance(query).desc(), messages::created_at.desc()))
+        .limit(limit)
+        .offset(offset)
+        .select(Message::as_select())
+        .load(&mut db)
+        .await?;
+
+    info!(
+        "Search matched {} messages (page {}, {} on page)",
+        total,
+        page,
+        results.len()
+    );
+    Ok(Json(PaginatedMessages {
+        data: results,
+        total,
+        page,
+        limit,
+    }))
+}
+
+/// Every active and archived message from one email address, oldest first,
+/// with the replies sent to each
+#[get("/admin/api/messages/threads?<email>")]
+pub async fn get_message_thread(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    email: &str,
+) -> AppResult<Json<MessageThread>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let email = email.trim();
+    if !validate_email(email) {
+        return Err(AppError::InvalidInput(
+            "Valid email is required".to_string(),
+        ));
+    }
+
+    let active = messages::table
+        .filter(messages::email.eq(email))
+        .select(Message::as_select())
+        .load(&mut db)
+        .await?;
+    let archived = messages_archive::table
+        .filter(messages_archive::email.eq(email))
+        .select(ArchivedMessage::as_select())
+        .load(&mut db)
+        .await?;
+    let message_ids: Vec<i64> = active
+        .iter()
+        .map(|message| message.id)
+        .chain(archived.iter().map(|archived| archived.original_id))
+        .collect();
+    let replies = message_replies::table
+        .filter(message_replies::message_id.eq_any(&message_ids))
+        .order(message_replies::id.asc())
+        .select(MessageReply::as_select())
+        .load(&mut db)
+        .await?;
+
+    let thread = MessageThread::assemble(email.to_string(), active, archived, replies);
+    info!(
+        "Retrieved thread of {} messages for one sender",
+        thread.messages.len()
+    );
+    Ok(Json(thread))
+}
+
+/// A single active message with its attachments and reply history
+#[get("/admin/api/messages/<id>")]
+pub async fn get_message(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<MessageDetail>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let message = messages::table
+        .find(id)
+        .select(Message::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let attachments = message_attachments::table
+        .filter(message_attachments::message_id.eq(id))
+        .order(message_attachments::id.asc())
+        .select(MessageAttachmentInfo::as_select())
+        .load(&mut db)
+        .await?;
+    let replies = load_replies(&mut db, id).await?;
+    let notes = load_notes(&mut db, id).await?;
+    let labels = load_message_labels(&mut db, id).await?;
+
+    Ok(Json(MessageDetail {
+        message,
+        attachments,
+        replies,
+        notes,
+        labels,
+    }))
+}
+
+/// Mark a message as read, or as new again with `{ "read": false }`. A
+/// message keeps the time it was first read.
+#[patch("/admin/api/messages/<id>/read", format = "json", data = "<request>")]
+pub async fn mark_message_read(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<MessageReadRequest>,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageMessages)?;
+
+    let exists: i64 = messages::table.find(id).count().get_result(&mut db).await?;
+    if exists == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    if request.read {
+        diesel::update(messages::table.find(id).filter(messages::read_at.is_null()))
+            .set(messages::read_at.eq(chrono::Utc::now().naive_utc()))
+            .execute(&mut db)
+            .await?;
+    } else {
+        diesel::update(messages::table.find(id))
+            .set(messages::read_at.eq(None::<chrono::NaiveDateTime>))
+            .execute(&mut db)
+            .await?;
+    }
+
+    info!(
+        "Message {} marked as {}",
+        id,
+        if request.read { "read" } else { "unread" }
+    );
+    Ok(Status::Ok)
+}
+
+/// Number of active messages not read yet, for the admin badge
+#[get("/admin/api/messages/unread-count")]
+pub async fn get_unread_message_count(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<UnreadMessageCount>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let unread: i64 = messages::table
+        .filter(messages::read_at.is_null())
+        .count()
+        .get_result(&mut db)
+        .await?;
+    Ok(Json(UnreadMessageCount { unread }))
+}
+
+/// Attachments of a message. Archived messages are addressed by their
+/// original id.
+#[get("/admin/api/messages/<id>/attachments")]
+pub async fn list_message_attachments(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<Vec<MessageAttachmentInfo>>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let attachments = message_attachments::table
+        .filter(message_attachments::message_id.eq(id))
+        .order(message_attachments::id.asc())
+        .select(MessageAttachmentInfo::as_select())
+        .load(&mut db)
+        .await?;
+    Ok(Json(attachments))
+}
+
+/// An attachment served as a download
+#[derive(Responder)]
+pub struct AttachmentDownload {
+    data: Vec<u8>,
+    content_type: ContentType,
+    disposition: Header<'static>,
+    nosniff: Header<'static>,
+}
+
+/// `Content-Disposition` value with everything but plain ASCII replaced in
+/// the file name
+fn attachment_disposition(filename: &str) -> String {
+    let filename: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("attachment; filename=\"{filename}\"")
+}
+
+#[get("/admin/api/messages/<id>/attachments/<attachment_id>")]
+pub async fn download_message_attachment(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    attachment_id: i64,
+) -> AppResult<AttachmentDownload> {
+    admin.require(Permission::ManageMessages)?;
+
+    let (filename, content_type, data): (String, String, Vec<u8>) = message_attachments::table
+        .find(attachment_id)
+        .filter(message_attachments::message_id.eq(id))
+        .select((
+            message_attachments::filename,
+            message_attachments::content_type,
+            message_attachments::data,
+        ))
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    info!(
+        "Admin '{}' downloaded attachment {} of message {}",
+        admin.username, attachment_id, id
+    );
+    Ok(AttachmentDownload {
+        data,
+        content_type: ContentType::parse_flexible(&content_type).unwrap_or(ContentType::Binary),
+        disposition: Header::new("Content-Disposition", attachment_disposition(&filename)),
+        nosniff: Header::new("X-Content-Type-Options", "nosniff"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_relevance_binds_query() {
+        let query = messages::table
+            .filter(search_relevance("drop table").gt(0.0))
+            .order(search_relevance("drop table").desc())
+            .select(messages::id);
+        let sql = diesel::debug_query::<Mysql, _>(&query).to_string();
+        assert!(sql.contains("MATCH (name, email, subject, message) AGAINST (?"));
+        assert!(!sql.contains("AGAINST (drop"));
+    }
+
+    #[test]
+    fn test_archive_step_reports_state_change() {
+        assert_eq!(
+            archive_step(&ArchiveAction::Archive, true, false),
+            ArchiveStep::Move
+        );
+        assert_eq!(
+            archive_step(&ArchiveAction::Restore, false, true),
+            ArchiveStep::Move
+        );
+    }
+
+    #[test]
+    fn test_archive_step_reports_noop() {
+        assert_eq!(
+            archive_step(&ArchiveAction::Archive, false, true),
+            ArchiveStep::AlreadyDone
+        );
+        assert_eq!(
+            archive_step(&ArchiveAction::Restore, true, false),
+            ArchiveStep::AlreadyDone
+        );
+    }
+
+    #[test]
+    fn test_archive_step_missing_message() {
+        assert_eq!(
+            archive_step(&ArchiveAction::Archive, false, false),
+            ArchiveStep::Missing
+        );
+        assert_eq!(
+            archive_step(&ArchiveAction::Restore, false, false),
+            ArchiveStep::Missing
+        );
+    }
+
+    #[test]
+    fn test_archive_outcome_serialization() {
+        let outcome = ArchiveOutcome {
+            state: MessageState::Archived,
+            message_id: 7,
+            archive_id: Some(3),
+            noop: true,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&outcome).unwrap(),
+            serde_json::json!({
+                "state": "archived",
+                "message_id": 7,
+                "archive_id": 3,
+                "noop": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_wants_json() {
+        assert!(wants_json(Some(&Accept::JSON)));
+        assert!(!wants_json(Some(&Accept::HTML)));
+        assert!(!wants_json(None));
+    }
+
+    #[test]
+    fn test_attachment_disposition_sanitizes_file_name() {
+        assert_eq!(
+            attachment_disposition("cenová ponuka\".pdf"),
+            "attachment; filename=\"cenov_ ponuka_.pdf\""
+        );
+    }
 }
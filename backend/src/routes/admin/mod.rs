@@ -1,27 +1,101 @@
 // Admin routes module
 
+pub mod api_keys;
 pub mod archive;
+pub mod audit;
 pub mod auth;
 pub mod banner;
 pub mod blog;
+pub mod blog_authors;
+pub mod blog_bundle;
+pub mod blog_images;
+pub mod blog_preview;
+pub mod blog_revisions;
+pub mod blog_seo;
+pub mod bulk;
+pub mod email_templates;
+pub mod export;
+pub mod gdpr;
+pub mod images;
+pub mod labels;
+pub mod login_limit;
 pub mod messages;
+pub mod notes;
+pub mod offer_clicks;
+pub mod offer_images;
+pub mod offer_import;
 pub mod offers;
+pub mod password_reset;
+pub mod remember_me;
+pub mod replies;
+pub mod sessions;
+pub mod tags;
+pub mod trash;
 pub mod users;
+pub mod webhooks;
 
 // Re-export commonly used items for convenience
-pub use archive::{get_archived_messages, permanently_delete_archived_message};
-pub use auth::{admin_login, admin_logout, admin_status};
+pub use api_keys::{create_api_key, list_api_keys, revoke_api_key, update_api_key};
+pub use archive::{
+    get_archived_messages, permanently_delete_archived_message, restore_archived_message,
+};
+pub use audit::get_audit_log;
+pub use auth::{
+    admin_login, admin_logout, admin_oidc_callback, admin_oidc_login, admin_refresh, admin_status,
+};
 pub use banner::{delete_banner, get_active_banner, get_admin_banner, upsert_banner};
 pub use blog::{
-    create_blog_post, delete_blog_post, get_blog_post_by_slug, get_blog_post_image,
-    list_all_blog_posts, list_blog_posts, update_blog_post,
+    archive_blog_post, create_blog_post, delete_blog_post, duplicate_blog_post, feature_blog_post,
+    get_admin_blog_post, get_blog_post_by_slug, get_blog_post_image, get_blog_post_image_by_slug,
+    list_all_blog_posts, list_blog_posts, list_featured_blog_posts, list_popular_blog_posts,
+    patch_blog_post, preview_blog_post, restore_blog_post, update_blog_post,
+};
+pub use blog_authors::list_blog_authors;
+pub use blog_bundle::{export_blog_posts, import_blog_posts};
+pub use blog_images::{
+    delete_blog_post_image, get_blog_post_image_file, list_blog_post_images,
+    reorder_blog_post_images, upload_blog_post_image,
+};
+pub use blog_preview::{create_blog_preview_link, get_blog_post_preview};
+pub use blog_revisions::{
+    diff_blog_post_revisions, list_blog_post_revisions, restore_blog_post_revision,
 };
-pub use messages::{archive_message, delete_message, get_messages};
+pub use blog_seo::get_blog_post_meta;
+pub use bulk::bulk_update_messages;
+pub use email_templates::{list_email_templates, update_email_template};
+pub use export::export_messages;
+pub use gdpr::{gdpr_erase, privacy_erase, privacy_export};
+pub use images::validate_image;
+pub use labels::{create_label, delete_label, list_labels, set_message_labels, update_label};
+pub use messages::{
+    archive_message, delete_message, download_message_attachment, get_message, get_message_thread,
+    get_messages, get_unread_message_count, list_message_attachments, mark_message_read,
+    search_messages,
+};
+pub use notes::{create_message_note, delete_message_note, list_message_notes};
+pub use offer_clicks::{follow_offer_link, get_offer_click_stats};
+pub use offer_images::{
+    delete_offer_image, get_offer_image_file, list_offer_images, reorder_offer_images,
+    set_primary_offer_image, upload_offer_image,
+};
+pub use offer_import::import_offers;
 pub use offers::{
-    create_offer, delete_offer, get_offer_by_slug, get_offer_image, list_offers, update_offer,
+    create_offer, delete_offer, get_offer_by_slug, get_offer_image, list_all_offers, list_offers,
+    patch_offer, reorder_offers, update_offer,
+};
+pub use password_reset::{confirm_password_reset, request_password_reset};
+pub use replies::reply_to_message;
+pub use sessions::{list_admin_sessions, revoke_admin_session, revoke_all_admin_sessions};
+pub use tags::{create_tag, delete_tag, list_tags, update_tag};
+pub use trash::{
+    list_trash, permanently_delete_blog_post, permanently_delete_offer, restore_trashed_blog_post,
+    restore_trashed_offer,
 };
 pub use users::{
-    accept_admin_invite, admin_setup, create_admin_invite, create_admin_user, delete_admin_invite,
-    delete_admin_user, get_admin_invite_status, list_admin_invites, list_admin_users,
-    update_admin_user,
+    accept_admin_invite, admin_setup, change_admin_password, create_admin_invite,
+    create_admin_user, delete_admin_invite, delete_admin_user, get_admin_invite_status,
+    list_admin_invites, list_admin_users, update_admin_user,
+};
+pub use webhooks::{
+    create_webhook, delete_webhook, list_webhook_deliveries, list_webhooks, update_webhook,
 };
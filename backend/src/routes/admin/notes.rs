@@ -0,0 +1,153 @@
+// Internal staff notes on contact messages
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::info;
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::models::{AdminMessageNoteRequest, MessageNote, NewMessageNote, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{message_notes, messages};
+
+const MAX_NOTE_LEN: usize = 5_000;
+
+/// Trimmed note body, rejected when empty or too long
+fn note_body(body: &str) -> AppResult<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(AppError::InvalidInput("Note text is required.".to_string()));
+    }
+    if body.chars().count() > MAX_NOTE_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Note must be at most {MAX_NOTE_LEN} characters."
+        )));
+    }
+    Ok(body.to_string())
+}
+
+/// Notes on a message, oldest first
+pub(crate) async fn load_notes(
+    db: &mut Connection<MessagesDB>,
+    message_id: i64,
+) -> AppResult<Vec<MessageNote>> {
+    Ok(message_notes::table
+        .filter(message_notes::message_id.eq(message_id))
+        .order(message_notes::id.asc())
+        .select(MessageNote::as_select())
+        .load(db)
+        .await?)
+}
+
+/// Notes on a message; archived messages are looked up by their
+/// `original_id`
+#[get("/admin/api/messages/<id>/notes")]
+pub async fn list_message_notes(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<Vec<MessageNote>>> {
+    admin.require(Permission::ManageMessages)?;
+    Ok(Json(load_notes(&mut db, id).await?))
+}
+
+/// Add a note to an active message, signed by the current admin
+#[post("/admin/api/messages/<id>/notes", format = "json", data = "<request>")]
+pub async fn create_message_note(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminMessageNoteRequest>,
+) -> AppResult<Json<MessageNote>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let exists: i64 = messages::table.find(id).count().get_result(&mut db).await?;
+    if exists == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    let note = NewMessageNote {
+        message_id: id,
+        admin_user_id: Some(admin.id),
+        admin_username: admin.username.clone(),
+        body: note_body(&request.body)?,
+    };
+    let note_id = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                diesel::insert_into(message_notes::table)
+                    .values(&note)
+                    .execute(&mut conn)
+                    .await?;
+                let note_id: u64 = diesel::select(last_insert_id())
+                    .get_result(&mut conn)
+                    .await?;
+                Ok::<_, diesel::result::Error>(note_id as i64)
+            })
+        })
+        .await?;
+
+    let note = message_notes::table
+        .find(note_id)
+        .select(MessageNote::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "message.note", "message", id).await;
+    info!("Admin '{}' added a note to message {}", admin.username, id);
+    Ok(Json(note))
+}
+
+/// Delete a note. Admins may delete their own notes; deleting someone
+/// else's needs the delete permission.
+#[delete("/admin/api/messages/<id>/notes/<note_id>")]
+pub async fn delete_message_note(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    note_id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageMessages)?;
+
+    let note = message_notes::table
+        .find(note_id)
+        .filter(message_notes::message_id.eq(id))
+        .select(MessageNote::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    if note.admin_user_id != Some(admin.id) {
+        admin.require(Permission::DeleteMessages)?;
+    }
+
+    diesel::delete(message_notes::table.find(note_id))
+        .execute(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "message.note_delete", "message", id).await;
+    info!(
+        "Admin '{}' deleted note {} on message {}",
+        admin.username, note_id, id
+    );
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_body() {
+        assert_eq!(
+            note_body("  Zavolať v pondelok \n").unwrap(),
+            "Zavolať v pondelok"
+        );
+        assert!(note_body(" \n ").is_err());
+        assert!(note_body(&"a".repeat(MAX_NOTE_LEN)).is_ok());
+        assert!(note_body(&"a".repeat(MAX_NOTE_LEN + 1)).is_err());
+    }
+}
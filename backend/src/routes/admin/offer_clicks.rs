@@ -0,0 +1,218 @@
+// Click tracking for offer links: visitors are sent through a redirect that
+// records the click, and admins get the clicks per offer by day
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use diesel::dsl::{count_star, sql};
+use diesel::sql_types::{BigInt, Date};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{DailyOfferClicks, NewOfferClick, OfferClickStats, Permission};
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::offers::currently_active;
+use crate::routes::client_ip::ClientIp;
+use crate::schema::{offer_clicks, offers};
+use crate::utils::{DateRange, anonymize_ip};
+
+/// Length of `offer_clicks.referrer`; longer referrers are cut off
+const MAX_REFERRER_CHARS: usize = 512;
+
+/// The request's `Referer` header, recorded with clicks
+pub struct Referrer(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Referrer {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let referrer = req
+            .headers()
+            .get_one("Referer")
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.chars().take(MAX_REFERRER_CHARS).collect());
+        Outcome::Success(Referrer(referrer))
+    }
+}
+
+/// Redirect to an offer's link that, like the `noreferrer` links it
+/// replaces, does not tell the offer's site which page the visitor came from
+#[derive(Responder)]
+pub struct OfferLinkRedirect {
+    redirect: Redirect,
+    referrer_policy: Header<'static>,
+}
+
+/// Record a click on an offer's link and redirect to it. Failing to record
+/// the click does not keep the visitor from the link.
+#[get("/api/offers/<id>/go")]
+pub async fn follow_offer_link(
+    mut db: Connection<MessagesDB>,
+    client_ip: ClientIp,
+    referrer: Referrer,
+    id: i64,
+) -> AppResult<OfferLinkRedirect> {
+    let link: Option<String> = offers::table
+        .find(id)
+        .filter(currently_active(Utc::now().naive_utc()))
+        .select(offers::link)
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching link of offer {}: {}", id, e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound)?;
+    let link = link
+        .map(|link| link.trim().to_string())
+        .filter(|link| !link.is_empty())
+        .ok_or(AppError::NotFound)?;
+
+    let click = NewOfferClick {
+        offer_id: id,
+        referrer: referrer.0,
+        ip_address: client_ip.0.map(|ip| anonymize_ip(ip).to_string()),
+    };
+    if let Err(e) = diesel::insert_into(offer_clicks::table)
+        .values(&click)
+        .execute(&mut db)
+        .await
+    {
+        error!("Error recording click on offer {}: {}", id, e);
+    }
+
+    Ok(OfferLinkRedirect {
+        redirect: Redirect::found(link),
+        referrer_policy: Header::new("Referrer-Policy", "no-referrer"),
+    })
+}
+
+/// Group `(offer id, day, clicks)` rows into per-offer stats, most clicked
+/// first
+fn to_click_stats(
+    rows: Vec<(i64, NaiveDate, i64)>,
+    mut offers: HashMap<i64, (String, String)>,
+) -> Vec<OfferClickStats> {
+    let mut stats: Vec<OfferClickStats> = Vec::new();
+    let mut positions: HashMap<i64, usize> = HashMap::new();
+    for (offer_id, date, clicks) in rows {
+        let position = *positions.entry(offer_id).or_insert_with(|| {
+            let (title, slug) = offers.remove(&offer_id).unwrap_or_default();
+            stats.push(OfferClickStats {
+                offer_id,
+                title,
+                slug,
+                total: 0,
+                daily: Vec::new(),
+            });
+            stats.len() - 1
+        });
+        let offer = &mut stats[position];
+        offer.total += clicks;
+        offer.daily.push(DailyOfferClicks { date, clicks });
+    }
+
+    for offer in &mut stats {
+        offer.daily.sort_by_key(|day| day.date);
+    }
+    stats.sort_by(|a, b| b.total.cmp(&a.total).then(a.offer_id.cmp(&b.offer_id)));
+    stats
+}
+
+/// Clicks on offer links per offer and day (UTC), optionally limited to
+/// `from`..=`to` (`YYYY-MM-DD`). Offers without clicks are left out.
+#[get("/admin/api/offers/clicks?<from>&<to>")]
+pub async fn get_offer_click_stats(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Json<Vec<OfferClickStats>>> {
+    admin.require(Permission::ManageContent)?;
+    let range = DateRange::parse(from, to)?;
+
+    let mut query = offer_clicks::table
+        .group_by(sql::<(BigInt, Date)>(
+            "offer_clicks.offer_id, DATE(offer_clicks.clicked_at)",
+        ))
+        .select((
+            sql::<BigInt>("offer_clicks.offer_id"),
+            sql::<Date>("DATE(offer_clicks.clicked_at)"),
+            count_star(),
+        ))
+        .into_boxed();
+    if let Some(from) = range.from {
+        query = query.filter(offer_clicks::clicked_at.ge(from));
+    }
+    if let Some(until) = range.until {
+        query = query.filter(offer_clicks::clicked_at.lt(until));
+    }
+    let rows: Vec<(i64, NaiveDate, i64)> = query.load(&mut db).await.map_err(|e| {
+        error!("Error loading offer click stats: {}", e);
+        AppError::from(e)
+    })?;
+
+    let mut ids: Vec<i64> = rows.iter().map(|(offer_id, _, _)| *offer_id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let titles: Vec<(i64, String, String)> = offers::table
+        .filter(offers::id.eq_any(&ids))
+        .select((offers::id, offers::title, offers::slug))
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading offers for click stats: {}", e);
+            AppError::from(e)
+        })?;
+
+    Ok(Json(to_click_stats(
+        rows,
+        titles
+            .into_iter()
+            .map(|(id, title, slug)| (id, (title, slug)))
+            .collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_to_click_stats() {
+        let offers = HashMap::from([
+            (1, ("Fasády".to_string(), "fasady".to_string())),
+            (2, ("Strechy".to_string(), "strechy".to_string())),
+        ]);
+        let stats = to_click_stats(
+            vec![
+                (1, day("2026-03-02"), 2),
+                (2, day("2026-03-01"), 4),
+                (1, day("2026-03-01"), 1),
+            ],
+            offers,
+        );
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].offer_id, 2);
+        assert_eq!(stats[0].total, 4);
+        assert_eq!(stats[1].title, "Fasády");
+        assert_eq!(stats[1].total, 3);
+        let dates: Vec<NaiveDate> = stats[1].daily.iter().map(|d| d.date).collect();
+        assert_eq!(dates, [day("2026-03-01"), day("2026-03-02")]);
+    }
+}
@@ -0,0 +1,446 @@
+// Image galleries of offers
+
+use std::collections::HashMap;
+
+use rocket::State;
+use rocket::form::Form;
+use rocket::http::{Accept, Status};
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::media::{Media, StoredImage};
+use crate::models::{
+    AdminOfferImageOrderRequest, AdminUploadOfferImageMultipart, NewOfferImage, OfferImageDto,
+    OfferImageInfo, Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::images::{CachedImage, stored_image_response};
+use crate::routes::feeds::ConditionalHeaders;
+use crate::schema::{offer_images, offers};
+use crate::utils::{ImageSettings, ImageSize, process_image_upload};
+
+/// Media store keys of offer images start with this
+pub(crate) const MEDIA_PREFIX: &str = "offers";
+
+pub(crate) const MAX_IMAGES_PER_OFFER: i64 = 20;
+
+/// Public URL of an image; it does not change when images are reordered
+pub(crate) fn image_url(offer_id: i64, image_id: i64) -> String {
+    format!("/api/offers/{offer_id}/images/{image_id}")
+}
+
+fn to_image_dto(image: OfferImageInfo) -> OfferImageDto {
+    OfferImageDto {
+        url: image_url(image.offer_id, image.id),
+        image,
+    }
+}
+
+/// Check that `requested` lists each of the `current` image ids exactly once
+fn check_image_order(current: &[i64], requested: &[i64]) -> AppResult<()> {
+    let mut current = current.to_vec();
+    let mut requested = requested.to_vec();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(AppError::InvalidInput(
+            "The new order must list every image of the offer exactly once.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `current` image ids in display order with `image_id` moved to the front
+fn primary_first(current: &[i64], image_id: i64) -> Option<Vec<i64>> {
+    if !current.contains(&image_id) {
+        return None;
+    }
+    let mut order = vec![image_id];
+    order.extend(current.iter().copied().filter(|id| *id != image_id));
+    Some(order)
+}
+
+/// Images of the given offers by offer id, each in display order with the
+/// primary image first
+pub(crate) async fn load_offer_images(
+    conn: &mut AsyncMysqlConnection,
+    offer_ids: &[i64],
+) -> Result<HashMap<i64, Vec<OfferImageDto>>, diesel::result::Error> {
+    let mut by_offer: HashMap<i64, Vec<OfferImageDto>> = HashMap::new();
+    if offer_ids.is_empty() {
+        return Ok(by_offer);
+    }
+
+    let rows = offer_images::table
+        .filter(offer_images::offer_id.eq_any(offer_ids))
+        .order((
+            offer_images::offer_id.asc(),
+            offer_images::position.asc(),
+            offer_images::id.asc(),
+        ))
+        .select(OfferImageInfo::as_select())
+        .load(conn)
+        .await?;
+    for image in rows {
+        by_offer
+            .entry(image.offer_id)
+            .or_default()
+            .push(to_image_dto(image));
+    }
+    Ok(by_offer)
+}
+
+/// An image of an offer put into the media store, ready to be inserted
+pub(crate) fn new_image(owner_id: i64, image: &StoredImage, position: i32) -> NewOfferImage {
+    NewOfferImage {
+        offer_id: owner_id,
+        storage_key: image.key.clone(),
+        image_mime: image.mime.clone(),
+        image_hash: image.hash.clone(),
+        position,
+    }
+}
+
+/// Make an image the primary image of an offer, replacing the current one
+/// or adding it in front when the offer has no images. Returns the storage
+/// key of the replaced image, to be released once the change is committed.
+pub(crate) async fn set_primary_image(
+    conn: &mut AsyncMysqlConnection,
+    offer_id: i64,
+    image: &StoredImage,
+) -> Result<Option<String>, diesel::result::Error> {
+    let primary: Option<(i64, String)> = offer_images::table
+        .filter(offer_images::offer_id.eq(offer_id))
+        .order((offer_images::position.asc(), offer_images::id.asc()))
+        .select((offer_images::id, offer_images::storage_key))
+        .first(conn)
+        .await
+        .optional()?;
+
+    match primary {
+        Some((primary_id, replaced_key)) => {
+            diesel::update(offer_images::table.find(primary_id))
+                .set((
+                    offer_images::storage_key.eq(&image.key),
+                    offer_images::image_mime.eq(&image.mime),
+                    offer_images::image_hash.eq(&image.hash),
+                ))
+                .execute(conn)
+                .await?;
+            Ok(Some(replaced_key))
+        }
+        None => {
+            diesel::insert_into(offer_images::table)
+                .values(&new_image(offer_id, image, 0))
+                .execute(conn)
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Save `image_ids` of an offer in that order. Run it inside a transaction.
+async fn save_image_order(
+    conn: &mut AsyncMysqlConnection,
+    image_ids: Vec<i64>,
+) -> Result<(), diesel::result::Error> {
+    for (position, image_id) in image_ids.into_iter().enumerate() {
+        diesel::update(offer_images::table.find(image_id))
+            .set(offer_images::position.eq(position as i32))
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn require_offer(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<()> {
+    offers::table
+        .find(id)
+        .filter(offers::deleted_at.is_null())
+        .select(offers::id)
+        .first::<i64>(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    Ok(())
+}
+
+async fn offer_images(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Vec<OfferImageDto>> {
+    Ok(load_offer_images(db, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default())
+}
+
+async fn offer_image_ids(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Vec<i64>> {
+    Ok(offer_images(db, id)
+        .await?
+        .iter()
+        .map(|image| image.image.id)
+        .collect())
+}
+
+/// Images of an offer in display order, inactive offers included
+#[get("/admin/api/offers/<id>/images")]
+pub async fn list_offer_images(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Json<Vec<OfferImageDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_offer(&mut db, id).await?;
+    Ok(Json(offer_images(&mut db, id).await?))
+}
+
+/// Add an image after the existing images of an offer
+#[post("/admin/api/offers/<id>/images", data = "<upload>")]
+pub async fn upload_offer_image(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
+    id: i64,
+    upload: Form<AdminUploadOfferImageMultipart<'_>>,
+) -> AppResult<Json<OfferImageDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_offer(&mut db, id).await?;
+    let (count, last_position): (i64, Option<i32>) = offer_images::table
+        .filter(offer_images::offer_id.eq(id))
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::max(offer_images::position),
+        ))
+        .first(&mut db)
+        .await?;
+    if count >= MAX_IMAGES_PER_OFFER {
+        return Err(AppError::InvalidInput(format!(
+            "An offer can have at most {MAX_IMAGES_PER_OFFER} images."
+        )));
+    }
+
+    let image = process_image_upload(Some(upload.into_inner().image), image_settings)
+        .await?
+        .ok_or_else(|| AppError::InvalidInput("No image was uploaded.".to_string()))?;
+    let stored = media.store_image(&mut db, MEDIA_PREFIX, &image).await?;
+    let new_image = new_image(
+        id,
+        &stored,
+        last_position.map_or(0, |position| position + 1),
+    );
+    let saved = db
+        .transaction(|conn| {
+            Box::pin(async move {
+                diesel::insert_into(offer_images::table)
+                    .values(&new_image)
+                    .execute(conn)
+                    .await?;
+                let image_id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+                Ok::<_, diesel::result::Error>(image_id as i64)
+            })
+        })
+        .await;
+    let image_id = match saved {
+        Ok(image_id) => image_id,
+        Err(e) => {
+            error!("Error saving an image of offer {}: {}", id, e);
+            media.release_images(&mut db, &[stored.key]).await;
+            return Err(e.into());
+        }
+    };
+
+    let image = offer_images::table
+        .find(image_id)
+        .select(OfferImageInfo::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "offer.image_upload", "offer", id).await;
+    info!(
+        "Admin '{}' added image {} to offer {}",
+        admin.username, image_id, id
+    );
+    Ok(Json(to_image_dto(image)))
+}
+
+/// Put the images of an offer in the given order; the first becomes the
+/// primary image
+#[put(
+    "/admin/api/offers/<id>/images/order",
+    format = "json",
+    data = "<request>"
+)]
+pub async fn reorder_offer_images(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminOfferImageOrderRequest>,
+) -> AppResult<Json<Vec<OfferImageDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_offer(&mut db, id).await?;
+    let current = offer_image_ids(&mut db, id).await?;
+    let image_ids = request.into_inner().image_ids;
+    check_image_order(&current, &image_ids)?;
+
+    db.transaction(|conn| Box::pin(save_image_order(conn, image_ids)))
+        .await
+        .map_err(|e| {
+            error!("Error reordering the images of offer {}: {}", id, e);
+            AppError::from(e)
+        })?;
+
+    record_audit(&mut db, &admin, "offer.image_reorder", "offer", id).await;
+    info!(
+        "Admin '{}' reordered the images of offer {}",
+        admin.username, id
+    );
+    Ok(Json(offer_images(&mut db, id).await?))
+}
+
+/// Make an image the primary image of its offer, keeping the order of the
+/// others
+#[post("/admin/api/offers/<id>/images/<image_id>/primary")]
+pub async fn set_primary_offer_image(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    image_id: i64,
+) -> AppResult<Json<Vec<OfferImageDto>>> {
+    admin.require(Permission::ManageContent)?;
+
+    require_offer(&mut db, id).await?;
+    let current = offer_image_ids(&mut db, id).await?;
+    let image_ids = primary_first(&current, image_id).ok_or(AppError::NotFound)?;
+
+    db.transaction(|conn| Box::pin(save_image_order(conn, image_ids)))
+        .await
+        .map_err(|e| {
+            error!(
+                "Error setting image {} as primary image of offer {}: {}",
+                image_id, id, e
+            );
+            AppError::from(e)
+        })?;
+
+    record_audit(&mut db, &admin, "offer.image_primary", "offer", id).await;
+    info!(
+        "Admin '{}' made image {} the primary image of offer {}",
+        admin.username, image_id, id
+    );
+    Ok(Json(offer_images(&mut db, id).await?))
+}
+
+/// Delete an image of an offer; the next image becomes the primary image
+/// when it was the first
+#[delete("/admin/api/offers/<id>/images/<image_id>")]
+pub async fn delete_offer_image(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    media: &State<Media>,
+    id: i64,
+    image_id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let image_row = offer_images::table
+        .find(image_id)
+        .filter(offer_images::offer_id.eq(id));
+    let storage_key: String = image_row
+        .select(offer_images::storage_key)
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let deleted = diesel::delete(image_row)
+        .execute(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error deleting image {} of offer {}: {}", image_id, id, e);
+            AppError::from(e)
+        })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    media.release_images(&mut db, &[storage_key]).await;
+
+    record_audit(&mut db, &admin, "offer.image_delete", "offer", id).await;
+    info!(
+        "Admin '{}' deleted image {} of offer {}",
+        admin.username, image_id, id
+    );
+    Ok(Status::Ok)
+}
+
+/// An image of an offer in the requested size
+pub(crate) async fn offer_image_response(
+    db: &mut Connection<MessagesDB>,
+    media: &Media,
+    id: i64,
+    image_id: i64,
+    size: ImageSize,
+    accept: Option<&Accept>,
+    conditional: &ConditionalHeaders,
+) -> AppResult<CachedImage> {
+    let (key, mime, hash) = offer_images::table
+        .find(image_id)
+        .filter(offer_images::offer_id.eq(id))
+        .select((
+            offer_images::storage_key,
+            offer_images::image_mime,
+            offer_images::image_hash,
+        ))
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+    let image = StoredImage { key, mime, hash };
+    stored_image_response(db, media, &image, size, accept, conditional).await
+}
+
+/// An image of an offer at its stable URL, in the size given by `size`
+#[get("/api/offers/<id>/images/<image_id>?<size>")]
+pub async fn get_offer_image_file(
+    mut db: Connection<MessagesDB>,
+    media: &State<Media>,
+    conditional: ConditionalHeaders,
+    accept: Option<&Accept>,
+    id: i64,
+    image_id: i64,
+    size: Option<&str>,
+) -> AppResult<CachedImage> {
+    let size = ImageSize::parse(size)?;
+    offer_image_response(&mut db, media, id, image_id, size, accept, &conditional).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_url() {
+        assert_eq!(image_url(7, 42), "/api/offers/7/images/42");
+    }
+
+    #[test]
+    fn test_check_image_order() {
+        assert!(check_image_order(&[1, 2, 3], &[3, 1, 2]).is_ok());
+        assert!(check_image_order(&[], &[]).is_ok());
+        assert!(check_image_order(&[1, 2, 3], &[1, 2]).is_err());
+        assert!(check_image_order(&[1, 2], &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_primary_first() {
+        assert_eq!(primary_first(&[4, 2, 9], 9), Some(vec![9, 4, 2]));
+        assert_eq!(primary_first(&[4, 2, 9], 4), Some(vec![4, 2, 9]));
+        assert_eq!(primary_first(&[4, 2, 9], 5), None);
+    }
+}
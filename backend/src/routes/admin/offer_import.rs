@@ -0,0 +1,594 @@
+// Bulk creation of offers from a CSV file, e.g. a spreadsheet export
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use rocket::State;
+use rocket::form::Form;
+use rocket::http::ContentType;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info, warn};
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::media::Media;
+use crate::models::{
+    AdminImportOffersMultipart, ImportedOffer, NewOffer, OfferImportError, OfferImportReport,
+    Permission,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::offer_images::{MEDIA_PREFIX, set_primary_image};
+use crate::routes::admin::offers::{coordinates, resolve_slug};
+use crate::schema::offers;
+use crate::utils::{
+    ImageSettings, ProcessedImage, is_http_url, is_public_ip, next_free_slug, process_image_bytes,
+    read_upload,
+};
+
+const MAX_IMPORT_ROWS: usize = 500;
+/// Time allowed for downloading each image
+const IMAGE_FETCH_TIMEOUT_SECS: u64 = 15;
+/// Time allowed for downloading all images of an import; rows reached after
+/// it has passed are rejected
+const IMPORT_FETCH_BUDGET_SECS: u64 = 120;
+/// Redirects followed when downloading an image
+const MAX_IMAGE_REDIRECTS: usize = 5;
+
+/// Columns of an import file. Names are matched case-insensitively, and
+/// columns may come in any order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Title,
+    Slug,
+    Excerpt,
+    Description,
+    Link,
+    Latitude,
+    Longitude,
+    ImageUrl,
+}
+
+impl Column {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "title" => Some(Column::Title),
+            "slug" => Some(Column::Slug),
+            "excerpt" => Some(Column::Excerpt),
+            "description" | "content" => Some(Column::Description),
+            "link" => Some(Column::Link),
+            "latitude" | "lat" => Some(Column::Latitude),
+            "longitude" | "lng" | "lon" => Some(Column::Longitude),
+            "image_url" | "image" => Some(Column::ImageUrl),
+            _ => None,
+        }
+    }
+}
+
+/// A data row of an import file with the line of the file it starts on,
+/// counting the header as line 1
+#[derive(Debug, Clone, Default, PartialEq)]
+struct OfferRow {
+    row: usize,
+    title: String,
+    slug: Option<String>,
+    excerpt: Option<String>,
+    description: Option<String>,
+    link: Option<String>,
+    latitude: Option<String>,
+    longitude: Option<String>,
+    image_url: Option<String>,
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::InvalidInput(message.into())
+}
+
+/// Line of `text` a record starts on. The csv crate positions a record
+/// where it started reading, which can be before the line break that ended
+/// the previous record and before blank lines, so those are skipped here.
+fn record_line(text: &str, record: &csv::StringRecord) -> usize {
+    let start = record
+        .position()
+        .and_then(|position| usize::try_from(position.byte()).ok())
+        .unwrap_or(0)
+        .min(text.len());
+    let rest = &text[start..];
+    let breaks = rest.len() - rest.trim_start_matches(['\r', '\n']).len();
+    text[..start + breaks].matches('\n').count() + 1
+}
+
+/// Split CSV text into records of fields with the line each starts on.
+/// Records whose fields are all empty are skipped.
+fn parse_csv(text: &str, delimiter: u8) -> AppResult<Vec<(usize, Vec<String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map_or(1, |position| position.line());
+            invalid(format!("The CSV file cannot be read at line {line}."))
+        })?;
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        records.push((
+            record_line(text, &record),
+            record.iter().map(str::to_string).collect(),
+        ));
+    }
+    Ok(records)
+}
+
+/// Spreadsheets in locales with a decimal comma, Slovak among them, export
+/// CSV separated by semicolons; detect them from the header line
+fn detect_delimiter(text: &str) -> u8 {
+    let header = text.lines().next().unwrap_or_default();
+    if header.contains(';') && !header.contains(',') {
+        b';'
+    } else {
+        b','
+    }
+}
+
+/// Read the rows of an import file. The first record is the header and must
+/// have a `title` column; unknown columns fail the whole import, so that a
+/// misspelt column is not silently dropped.
+fn read_rows(data: &[u8]) -> AppResult<Vec<OfferRow>> {
+    let text = std::str::from_utf8(data).map_err(|_| invalid("The CSV file must be UTF-8."))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = parse_csv(text, detect_delimiter(text))?.into_iter();
+
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| invalid("The CSV file is empty."))?;
+    let mut columns = Vec::with_capacity(header.len());
+    for name in &header {
+        let column = Column::parse(name).ok_or_else(|| {
+            invalid(format!(
+                "Unknown column '{}'. Known columns: title, slug, excerpt, description, link, \
+                 latitude, longitude, image_url.",
+                name.trim()
+            ))
+        })?;
+        if columns.contains(&column) {
+            return Err(invalid(format!("Column '{}' is given twice.", name.trim())));
+        }
+        columns.push(column);
+    }
+    if !columns.contains(&Column::Title) {
+        return Err(invalid("The CSV file must have a title column."));
+    }
+
+    let mut rows = Vec::new();
+    for (line, record) in records {
+        if rows.len() == MAX_IMPORT_ROWS {
+            return Err(invalid(format!(
+                "At most {MAX_IMPORT_ROWS} offers can be imported at once."
+            )));
+        }
+        let mut row = OfferRow {
+            row: line,
+            ..OfferRow::default()
+        };
+        for (column, value) in columns.iter().zip(record) {
+            let value = value.trim().to_string();
+            let optional = Some(value.clone()).filter(|value| !value.is_empty());
+            match column {
+                Column::Title => row.title = value,
+                Column::Slug => row.slug = optional,
+                Column::Excerpt => row.excerpt = optional,
+                Column::Description => row.description = optional,
+                Column::Link => row.link = optional,
+                Column::Latitude => row.latitude = optional,
+                Column::Longitude => row.longitude = optional,
+                Column::ImageUrl => row.image_url = optional,
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// A coordinate of a row; a decimal comma is accepted
+fn parse_coordinate(value: Option<&str>, name: &str) -> AppResult<Option<f64>> {
+    value
+        .map(|value| {
+            value
+                .replace(',', ".")
+                .parse::<f64>()
+                .map_err(|_| invalid(format!("{name} must be a number.")))
+        })
+        .transpose()
+}
+
+/// Resolves host names of image URLs to their public addresses only, so an
+/// import file cannot make the server fetch from its own network
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = rocket::tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Whether an image may be downloaded from `url`: http(s), and when the host
+/// is an IP address, a public one. Host names are checked when resolved.
+fn is_fetchable_url(url: &reqwest::Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url.host_str().is_some_and(|host| {
+            host.trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .map_or(true, is_public_ip)
+        })
+}
+
+/// HTTP client for image downloads, which only connects to public addresses,
+/// also after redirects
+fn image_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(IMAGE_FETCH_TIMEOUT_SECS))
+        .dns_resolver(std::sync::Arc::new(PublicResolver))
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_IMAGE_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if !is_fetchable_url(attempt.url()) {
+                attempt.error("redirect to a URL that is not public")
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+}
+
+/// Download and process the image of a row like an upload, giving up at
+/// `deadline`
+async fn fetch_image(
+    http: &reqwest::Client,
+    image_settings: &ImageSettings,
+    url: &str,
+    deadline: Instant,
+) -> AppResult<ProcessedImage> {
+    if !is_http_url(url) {
+        return Err(invalid("image_url must be an http(s) URL."));
+    }
+    let parsed = reqwest::Url::parse(url)
+        .ok()
+        .filter(is_fetchable_url)
+        .ok_or_else(|| invalid("image_url must be a public http(s) URL."))?;
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Err(invalid(
+            "The import ran out of time for downloading images; import this row again.",
+        ));
+    }
+    let failed = |e: reqwest::Error| {
+        warn!("Error downloading offer image {}: {}", url, e);
+        invalid("Could not download the image.")
+    };
+    let mut response = http
+        .get(parsed)
+        .timeout(remaining.min(Duration::from_secs(IMAGE_FETCH_TIMEOUT_SECS)))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(failed)?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(ContentType::parse_flexible)
+        .filter(|content_type| content_type.top() == "image")
+        .or_else(|| {
+            url.split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit_once('.'))
+                .and_then(|(_, extension)| ContentType::from_extension(extension))
+        });
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(failed)? {
+        if (data.len() + chunk.len()) as u64 > image_settings.max_upload_bytes {
+            return Err(invalid("Image file is too large"));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    process_image_bytes(&data, content_type.as_ref(), image_settings)
+}
+
+/// Validate a row and download its image, which becomes the primary image
+/// of the offer. `taken` holds the slugs of the rows before it, which are not
+/// in the database yet.
+async fn prepare_row(
+    db: &mut Connection<MessagesDB>,
+    http: &reqwest::Client,
+    image_settings: &ImageSettings,
+    row: OfferRow,
+    taken: &[String],
+    deadline: Instant,
+) -> AppResult<(NewOffer, Option<ProcessedImage>)> {
+    if row.title.is_empty() {
+        return Err(invalid("title is required."));
+    }
+    if let Some(link) = &row.link
+        && !is_http_url(link)
+    {
+        return Err(invalid("link must be an http(s) URL."));
+    }
+    let (latitude, longitude) = coordinates(
+        parse_coordinate(row.latitude.as_deref(), "Latitude")?,
+        parse_coordinate(row.longitude.as_deref(), "Longitude")?,
+    )?;
+
+    let mut slug = resolve_slug(db, row.slug.as_deref(), &row.title, None).await?;
+    if taken.contains(&slug) {
+        if row.slug.is_some() {
+            return Err(invalid(format!(
+                "The slug '{slug}' is used by an earlier row."
+            )));
+        }
+        slug = next_free_slug(&slug, taken);
+    }
+
+    let image = match &row.image_url {
+        Some(url) => Some(fetch_image(http, image_settings, url, deadline).await?),
+        None => None,
+    };
+
+    let offer = NewOffer {
+        title: row.title,
+        slug,
+        excerpt: row.excerpt,
+        content: row.description,
+        link: row.link,
+        latitude,
+        longitude,
+        sort_order: 0,
+        active: true,
+        starts_at: None,
+        ends_at: None,
+    };
+    Ok((offer, image))
+}
+
+/// The message reported for a rejected row, or the error itself when it is
+/// not caused by the row, such as a database failure
+fn row_error(e: AppError) -> AppResult<String> {
+    match e {
+        AppError::InvalidInput(message) => Ok(message),
+        AppError::UnsupportedMediaType => {
+            Ok("The image must be a JPEG, PNG, GIF or WebP file.".to_string())
+        }
+        AppError::SlugConflict {
+            slug,
+            suggested_slug,
+        } => Ok(match suggested_slug {
+            Some(suggested) => {
+                format!("The slug '{slug}' is already in use; '{suggested}' is free.")
+            }
+            None => format!("The slug '{slug}' is already in use."),
+        }),
+        other => Err(other),
+    }
+}
+
+/// Create offers from an uploaded CSV file with a header row. Each row is
+/// validated and its image downloaded; the valid rows are created in one
+/// transaction, placed first in the file's order, and the invalid ones are
+/// reported with their row number.
+#[post("/admin/api/offers/import", data = "<upload>")]
+pub async fn import_offers(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
+    upload: Form<AdminImportOffersMultipart<'_>>,
+) -> AppResult<Json<OfferImportReport>> {
+    admin.require(Permission::ManageContent)?;
+
+    let rows = read_rows(&read_upload(&upload.file).await?)?;
+    if rows.is_empty() {
+        return Err(invalid("The CSV file does not contain any offers."));
+    }
+    let http = image_client().map_err(|e| {
+        error!("Error creating the HTTP client for offer images: {}", e);
+        invalid("Images cannot be downloaded right now.")
+    })?;
+
+    let deadline = Instant::now() + Duration::from_secs(IMPORT_FETCH_BUDGET_SECS);
+
+    let mut prepared = Vec::with_capacity(rows.len());
+    let mut errors = Vec::new();
+    let mut slugs: Vec<String> = Vec::new();
+    for row in rows {
+        let number = row.row;
+        match prepare_row(&mut db, &http, image_settings, row, &slugs, deadline).await {
+            Ok((offer, image)) => {
+                slugs.push(offer.slug.clone());
+                prepared.push((number, offer, image));
+            }
+            Err(e) => errors.push(OfferImportError {
+                row: number,
+                message: row_error(e)?,
+            }),
+        }
+    }
+
+    // Images go to the media store first and are released again when the
+    // import fails
+    let mut stored_keys = Vec::new();
+    let mut ready = Vec::with_capacity(prepared.len());
+    for (row, offer, image) in prepared {
+        let image = match image {
+            Some(image) => match media.store_image(&mut db, MEDIA_PREFIX, &image).await {
+                Ok(stored) => {
+                    stored_keys.push(stored.key.clone());
+                    Some(stored)
+                }
+                Err(e) => {
+                    media.release_images(&mut db, &stored_keys).await;
+                    return Err(e);
+                }
+            },
+            None => None,
+        };
+        ready.push((row, offer, image));
+    }
+
+    let imported = db
+        .transaction(|conn| {
+            Box::pin(async move {
+                let first_position: Option<i32> = offers::table
+                    .select(diesel::dsl::min(offers::sort_order))
+                    .first(conn)
+                    .await?;
+                let first_position = first_position.unwrap_or(0) - ready.len() as i32;
+
+                let mut imported = Vec::with_capacity(ready.len());
+                for (position, (row, mut offer, image)) in ready.into_iter().enumerate() {
+                    offer.sort_order = first_position + position as i32;
+                    diesel::insert_into(offers::table)
+                        .values(&offer)
+                        .execute(conn)
+                        .await?;
+                    let id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+                    if let Some(image) = &image {
+                        set_primary_image(conn, id as i64, image).await?;
+                    }
+                    imported.push(ImportedOffer {
+                        row,
+                        id: id as i64,
+                        title: offer.title,
+                        slug: offer.slug,
+                    });
+                }
+                Ok::<_, diesel::result::Error>(imported)
+            })
+        })
+        .await;
+    let imported = match imported {
+        Ok(imported) => imported,
+        Err(e) => {
+            error!("Error importing offers: {}", e);
+            media.release_images(&mut db, &stored_keys).await;
+            return Err(e.into());
+        }
+    };
+
+    for offer in &imported {
+        record_audit(&mut db, &admin, "offer.import", "offer", offer.id).await;
+    }
+    info!(
+        "Admin '{}' imported {} offers, {} rows rejected",
+        admin.username,
+        imported.len(),
+        errors.len()
+    );
+    Ok(Json(OfferImportReport { imported, errors }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "title,description\r\n\"Fasáda, omietka\",\"Riadok 1\nRiadok 2 \"\"X\"\"\"\r\n\r\nStrecha,\n";
+        let records = parse_csv(text, b',').unwrap();
+        assert_eq!(
+            records.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            [1, 2, 5]
+        );
+        assert_eq!(
+            records
+                .into_iter()
+                .map(|(_, fields)| fields)
+                .collect::<Vec<_>>(),
+            vec![
+                vec!["title", "description"],
+                vec!["Fasáda, omietka", "Riadok 1\nRiadok 2 \"X\""],
+                vec!["Strecha", ""],
+            ]
+        );
+
+        let records = parse_csv("a;b\n;\n\n1;2", b';').unwrap();
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (4, vec!["1".to_string(), "2".to_string()])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_rows() {
+        let data =
+            "\u{feff}Title;Lat;Lng;Image\nFasáda;48,15;17,1;\n;;;https://example.com/a.jpg\n";
+        let rows = read_rows(data.as_bytes()).unwrap();
+        assert_eq!(
+            rows[0],
+            OfferRow {
+                row: 2,
+                title: "Fasáda".to_string(),
+                latitude: Some("48,15".to_string()),
+                longitude: Some("17,1".to_string()),
+                ..OfferRow::default()
+            }
+        );
+        assert_eq!(rows[1].row, 3);
+
+        let rows = read_rows(b"title,description\nA,\"1\n2\"\nB,3\n").unwrap();
+        assert_eq!(rows[1].row, 4);
+        assert_eq!(rows[1].title, "");
+        assert_eq!(
+            rows[1].image_url.as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+
+        assert!(read_rows(b"title,price\nA,1").is_err());
+        assert!(read_rows(b"slug\na").is_err());
+        assert!(read_rows(b"title,Title\na,b").is_err());
+        assert!(read_rows(b"").is_err());
+    }
+
+    #[test]
+    fn test_is_fetchable_url() {
+        let fetchable = |url: &str| is_fetchable_url(&reqwest::Url::parse(url).unwrap());
+
+        assert!(fetchable("https://example.com/a.jpg"));
+        assert!(fetchable("http://93.184.215.14/a.jpg"));
+        assert!(!fetchable("http://127.0.0.1/a.jpg"));
+        assert!(!fetchable("http://0x7f.1/a.jpg"));
+        assert!(!fetchable("http://169.254.169.254/latest/meta-data"));
+        assert!(!fetchable("http://[::1]:8000/a.jpg"));
+        assert!(!fetchable("ftp://example.com/a.jpg"));
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        assert_eq!(
+            parse_coordinate(Some("48,15"), "Latitude").unwrap(),
+            Some(48.15)
+        );
+        assert_eq!(parse_coordinate(None, "Latitude").unwrap(), None);
+        assert!(parse_coordinate(Some("north"), "Latitude").is_err());
+    }
+}
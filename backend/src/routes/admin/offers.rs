@@ -1,172 +1,604 @@
 // Offer management endpoints (admin and public)
 
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::mysql::Mysql;
+use diesel::sql_types::{Bool, Double, Nullable, Text};
 use rocket::State;
 use rocket::form::Form;
-use rocket::http::{ContentType, CookieJar, Status};
+use rocket::http::{Accept, Status};
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
 use tracing::{error, info};
 
-use crate::db::MessagesDB;
+use crate::config::AppConfig;
+use crate::db::{MessagesDB, last_insert_id};
 use crate::error::{AppError, AppResult};
+use crate::media::Media;
 use crate::models::{
-    AdminCreateOfferMultipart, AdminUpdateOfferMultipart, NewOffer, Offer, OfferDto,
+    AdminCreateOfferMultipart, AdminOfferOrderRequest, AdminPatchOfferRequest,
+    AdminUpdateOfferMultipart, NewOffer, Offer, OfferChangeset, OfferDto, OfferImageDto,
+    PaginatedOffers, Permission,
 };
-use crate::routes::admin::auth::is_admin_authenticated;
-use crate::schema::offers;
-use crate::utils::process_image_upload;
+use crate::notifier::ChatNotifier;
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::images::CachedImage;
+use crate::routes::admin::offer_images::{
+    MEDIA_PREFIX, load_offer_images, offer_image_response, set_primary_image,
+};
+use crate::routes::feeds::ConditionalHeaders;
+use crate::schema::{offer_images, offers};
+use crate::utils::{
+    ImageSettings, ImageSize, ShortLinkTarget, contains_pattern, map_slug_write_error,
+    next_free_slug, normalize_slug, process_image_upload, search_query, slugify,
+    validate_not_empty,
+};
+use crate::webhooks::enqueue_webhook_event;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 50;
+
+type OfferFilter = Box<dyn BoxableExpression<offers::table, Mysql, SqlType = Nullable<Bool>>>;
+
+/// Offers shown on the public site at `now`: not in the trash, active and
+/// within their time window, where one is set
+pub(crate) fn currently_active(now: NaiveDateTime) -> OfferFilter {
+    Box::new(
+        offers::deleted_at
+            .is_null()
+            .and(offers::active.eq(true))
+            .and(offers::starts_at.is_null().or(offers::starts_at.le(now)))
+            .and(offers::ends_at.is_null().or(offers::ends_at.gt(now))),
+    )
+}
+
+/// Offers shown in the admin list for a `status` filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StatusFilter {
+    /// Shown on the public site now
+    Active,
+    /// Active, but their window has not started yet
+    Scheduled,
+    /// Their window has ended
+    Expired,
+    /// Switched off
+    Inactive,
+}
+
+impl StatusFilter {
+    fn matching(self, now: NaiveDateTime) -> OfferFilter {
+        match self {
+            StatusFilter::Active => currently_active(now),
+            StatusFilter::Scheduled => Box::new(
+                offers::active
+                    .eq(true)
+                    .and(offers::starts_at.gt(now))
+                    .and(offers::ends_at.is_null().or(offers::ends_at.gt(now))),
+            ),
+            StatusFilter::Expired => Box::new(offers::ends_at.le(now)),
+            StatusFilter::Inactive => Box::new(offers::active.eq(false).nullable()),
+        }
+    }
+}
+
+/// Status filter of the admin list; all offers when omitted
+fn status_filter(status: Option<&str>) -> AppResult<Option<StatusFilter>> {
+    match status.map(str::trim).filter(|status| !status.is_empty()) {
+        None | Some("all") => Ok(None),
+        Some("active") => Ok(Some(StatusFilter::Active)),
+        Some("scheduled") => Ok(Some(StatusFilter::Scheduled)),
+        Some("expired") => Ok(Some(StatusFilter::Expired)),
+        Some("inactive") => Ok(Some(StatusFilter::Inactive)),
+        Some(_) => Err(AppError::InvalidInput(
+            "Status must be one of: all, active, scheduled, expired, inactive.".to_string(),
+        )),
+    }
+}
+
+/// A time of the offer window from the forms: RFC 3339, or a date and time
+/// without an offset taken as UTC. Blank means no limit.
+fn window_time(value: Option<&str>, field: &str) -> AppResult<Option<NaiveDateTime>> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .map(Some)
+        .map_err(|_| {
+            AppError::InvalidInput(format!("{field} must be a time like 2026-03-02T09:30:00Z."))
+        })
+}
+
+/// Validate the time window of an offer; it must end after it starts
+fn time_window(
+    starts_at: Option<&str>,
+    ends_at: Option<&str>,
+) -> AppResult<(Option<NaiveDateTime>, Option<NaiveDateTime>)> {
+    let starts_at = window_time(starts_at, "Start time")?;
+    let ends_at = window_time(ends_at, "End time")?;
+    check_window(starts_at, ends_at)?;
+    Ok((starts_at, ends_at))
+}
+
+/// Check that a time window ends after it starts
+fn check_window(starts_at: Option<NaiveDateTime>, ends_at: Option<NaiveDateTime>) -> AppResult<()> {
+    if let (Some(start), Some(end)) = (starts_at, ends_at)
+        && end <= start
+    {
+        return Err(AppError::InvalidInput(
+            "End time must be after the start time.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a partial update of `existing` and turn it into the columns to
+/// change. Coordinates and the time window are checked together with the
+/// values that are kept; the slug is resolved separately.
+fn offer_changes(existing: &Offer, patch: AdminPatchOfferRequest) -> AppResult<OfferChangeset> {
+    if patch
+        .title
+        .as_deref()
+        .is_some_and(|title| !validate_not_empty(title))
+    {
+        return Err(AppError::InvalidInput(
+            "Title must not be empty.".to_string(),
+        ));
+    }
+    let (latitude, longitude) = coordinates(
+        patch.latitude.unwrap_or(existing.latitude),
+        patch.longitude.unwrap_or(existing.longitude),
+    )?;
+    let starts_at = match patch.starts_at {
+        Some(value) => Some(window_time(value.as_deref(), "Start time")?),
+        None => None,
+    };
+    let ends_at = match patch.ends_at {
+        Some(value) => Some(window_time(value.as_deref(), "End time")?),
+        None => None,
+    };
+    check_window(
+        starts_at.unwrap_or(existing.starts_at),
+        ends_at.unwrap_or(existing.ends_at),
+    )?;
+
+    Ok(OfferChangeset {
+        title: patch.title,
+        slug: None,
+        excerpt: patch.excerpt,
+        content: patch.content,
+        link: patch.link,
+        latitude: patch.latitude.map(|_| latitude),
+        longitude: patch.longitude.map(|_| longitude),
+        active: patch.active,
+        starts_at,
+        ends_at,
+    })
+}
+
+/// `MATCH ... AGAINST` over the FULLTEXT index of offers
+pub(crate) fn offer_search_relevance(
+    query: &str,
+) -> Box<dyn BoxableExpression<offers::table, Mysql, SqlType = Double>> {
+    Box::new(
+        diesel::dsl::sql::<Double>("MATCH (title, excerpt, content) AGAINST (")
+            .bind::<Text, _>(query.to_string())
+            .sql(" IN NATURAL LANGUAGE MODE)"),
+    )
+}
+
+/// Offers currently shown, optionally only those matching a search query
+fn public_offers<'a>(query: Option<&str>, now: NaiveDateTime) -> offers::BoxedQuery<'a, Mysql> {
+    let mut filtered = offers::table.filter(currently_active(now)).into_boxed();
+    if let Some(query) = query {
+        filtered = filtered.filter(offer_search_relevance(query).gt(0.0));
+    }
+    filtered
+}
+
+/// Offers for the admin list, optionally by status and title; offers in the
+/// trash are listed with the trash
+fn admin_offers<'a>(
+    status: Option<StatusFilter>,
+    title: Option<&str>,
+    now: NaiveDateTime,
+) -> offers::BoxedQuery<'a, Mysql> {
+    let mut filtered = offers::table
+        .filter(offers::deleted_at.is_null())
+        .into_boxed();
+    if let Some(status) = status {
+        filtered = filtered.filter(status.matching(now));
+    }
+    if let Some(title) = title {
+        filtered = filtered.filter(offers::title.like(contains_pattern(title)));
+    }
+    filtered
+}
+
+fn to_offer_dto(offer: Offer, images: Vec<OfferImageDto>) -> OfferDto {
+    OfferDto {
+        id: offer.id,
+        title: offer.title,
+        slug: offer.slug,
+        excerpt: offer.excerpt,
+        content: offer.content,
+        link: offer.link,
+        image_mime: images
+            .as_slice()
+            .first()
+            .map(|primary| primary.image.image_mime.clone()),
+        image_url: images.as_slice().first().map(|primary| primary.url.clone()),
+        images,
+        created_at: offer.created_at,
+        latitude: offer.latitude,
+        longitude: offer.longitude,
+        sort_order: offer.sort_order,
+        active: offer.active,
+        starts_at: offer.starts_at,
+        ends_at: offer.ends_at,
+        updated_at: offer.updated_at,
+        short_code: ShortLinkTarget::Offer(offer.id).short_code(),
+    }
+}
+
+/// Offers with their images
+async fn to_offer_dtos(
+    db: &mut Connection<MessagesDB>,
+    offers: Vec<Offer>,
+) -> AppResult<Vec<OfferDto>> {
+    let ids: Vec<i64> = offers.iter().map(|offer| offer.id).collect();
+    let mut images = load_offer_images(db, &ids).await.map_err(|e| {
+        error!("Error loading offer images: {}", e);
+        AppError::from(e)
+    })?;
+    Ok(offers
+        .into_iter()
+        .map(|offer| {
+            let offer_images = images.remove(&offer.id).unwrap_or_default();
+            to_offer_dto(offer, offer_images)
+        })
+        .collect())
+}
+
+/// An offer with its images
+async fn with_images(db: &mut Connection<MessagesDB>, offer: Offer) -> AppResult<OfferDto> {
+    let images = load_offer_images(db, &[offer.id])
+        .await?
+        .remove(&offer.id)
+        .unwrap_or_default();
+    Ok(to_offer_dto(offer, images))
+}
+
+/// Validate the map position of an offer. Latitude must be within
+/// -90..=90 and longitude within -180..=180, and a position needs both.
+pub(crate) fn coordinates(
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+) -> AppResult<(Option<f64>, Option<f64>)> {
+    if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat)) {
+        return Err(AppError::InvalidInput(
+            "Latitude must be between -90 and 90.".to_string(),
+        ));
+    }
+    if longitude.is_some_and(|lon| !(-180.0..=180.0).contains(&lon)) {
+        return Err(AppError::InvalidInput(
+            "Longitude must be between -180 and 180.".to_string(),
+        ));
+    }
+    if latitude.is_some() != longitude.is_some() {
+        return Err(AppError::InvalidInput(
+            "Latitude and longitude must be given together.".to_string(),
+        ));
+    }
+    Ok((latitude, longitude))
+}
+
+/// Check that `requested` lists each of the `current` offer ids exactly once
+fn check_offer_order(current: &[i64], requested: &[i64]) -> AppResult<()> {
+    let mut current = current.to_vec();
+    let mut requested = requested.to_vec();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(AppError::InvalidInput(
+            "The new order must list every offer exactly once.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The slug to save for an offer. A blank slug is generated from the title
+/// and made unique with a numeric suffix; a submitted slug that another
+/// offer already uses is rejected with a free alternative.
+pub(crate) async fn resolve_slug(
+    db: &mut Connection<MessagesDB>,
+    requested: Option<&str>,
+    title: &str,
+    exclude_id: Option<i64>,
+) -> AppResult<String> {
+    let requested = requested.map(str::trim).filter(|slug| !slug.is_empty());
+    let base = match requested {
+        Some(slug) => slug.to_string(),
+        None => Some(slugify(title))
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| "offer".to_string()),
+    };
+
+    let mut similar = offers::table
+        .filter(
+            offers::slug
+                .eq(base.clone())
+                .or(offers::slug.like(format!("{base}-%"))),
+        )
+        .select(offers::slug)
+        .into_boxed();
+    if let Some(id) = exclude_id {
+        similar = similar.filter(offers::id.ne(id));
+    }
+    let taken: Vec<String> = similar.load(db).await?;
+
+    let free = next_free_slug(&base, &taken);
+    if requested.is_some() && free != base {
+        return Err(AppError::SlugConflict {
+            slug: base,
+            suggested_slug: Some(free),
+        });
+    }
+    Ok(free)
+}
 
 #[post("/admin/api/offers", data = "<offer_form>")]
 pub async fn create_offer(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
+    notifier: &State<ChatNotifier>,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
     offer_form: Form<AdminCreateOfferMultipart<'_>>,
 ) -> AppResult<Json<OfferDto>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageContent)?;
 
     let offer = offer_form.into_inner();
+    let (latitude, longitude) = coordinates(offer.latitude, offer.longitude)?;
+    let (starts_at, ends_at) = time_window(offer.starts_at.as_deref(), offer.ends_at.as_deref())?;
+    let slug = resolve_slug(&mut db, offer.slug.as_deref(), &offer.title, None).await?;
 
-    // Process image if uploaded
-    let (image_bytes, image_mime) = match process_image_upload(offer.image).await? {
-        Some((bytes, mime)) => (Some(bytes), Some(mime)),
-        None => (None, None),
+    let primary_image = match process_image_upload(offer.image, image_settings).await? {
+        Some(image) => Some(media.store_image(&mut db, MEDIA_PREFIX, &image).await?),
+        None => None,
     };
+    let stored_key = primary_image.as_ref().map(|image| image.key.clone());
 
-    let new_offer = NewOffer {
+    let mut new_offer = NewOffer {
         title: offer.title,
-        slug: offer.slug,
+        slug,
         excerpt: offer.excerpt,
         content: offer.content,
         link: offer.link,
-        image: image_bytes,
-        image_mime,
-        latitude: offer.latitude,
-        longitude: offer.longitude,
+        latitude,
+        longitude,
+        sort_order: 0,
+        active: offer.active.unwrap_or(true),
+        starts_at,
+        ends_at,
+    };
+    let slug = new_offer.slug.clone();
+
+    // Insert and read the row back by its generated id on one connection
+    let inserted = db
+        .transaction(|conn| {
+            Box::pin(async move {
+                // New offers come first until they are moved
+                let first_position: Option<i32> = offers::table
+                    .select(diesel::dsl::min(offers::sort_order))
+                    .first(conn)
+                    .await?;
+                new_offer.sort_order = first_position.map_or(0, |position| position - 1);
+
+                diesel::insert_into(offers::table)
+                    .values(&new_offer)
+                    .execute(conn)
+                    .await?;
+                let id: u64 = diesel::select(last_insert_id()).get_result(conn).await?;
+                if let Some(image) = &primary_image {
+                    set_primary_image(conn, id as i64, image).await?;
+                }
+                offers::table
+                    .find(id as i64)
+                    .select(Offer::as_select())
+                    .first::<Offer>(conn)
+                    .await
+            })
+        })
+        .await;
+    let inserted = match inserted {
+        Ok(inserted) => inserted,
+        Err(e) => {
+            error!("Error inserting offer: {}", e);
+            if let Some(key) = stored_key {
+                media.release_images(&mut db, &[key]).await;
+            }
+            return Err(map_slug_write_error(e, &slug));
+        }
     };
 
-    // Insert
-    diesel::insert_into(offers::table)
-        .values(&new_offer)
+    record_audit(&mut db, &admin, "offer.create", "offer", inserted.id).await;
+    info!("Offer created successfully with id: {}", inserted.id);
+    let offer = with_images(&mut db, inserted).await?;
+    notifier.offer_created(&offer);
+    enqueue_webhook_event(
+        &mut db,
+        "offer.created",
+        serde_json::json!({ "offer": offer }),
+    )
+    .await;
+    Ok(Json(offer))
+}
+
+#[put("/admin/api/offers/<id>", data = "<update_form>")]
+pub async fn update_offer(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    image_settings: &State<ImageSettings>,
+    media: &State<Media>,
+    id: i64,
+    update_form: Form<AdminUpdateOfferMultipart<'_>>,
+) -> AppResult<Json<OfferDto>> {
+    admin.require(Permission::ManageContent)?;
+
+    let update_data = update_form.into_inner();
+    let (latitude, longitude) = coordinates(update_data.latitude, update_data.longitude)?;
+    let (starts_at, ends_at) = time_window(
+        update_data.starts_at.as_deref(),
+        update_data.ends_at.as_deref(),
+    )?;
+    let active = update_data.active.unwrap_or(true);
+    let target = offers::table.find(id);
+
+    // Check if offer exists; offers in the trash must be restored first
+    let _existing_offer: Offer = offers::table
+        .find(id)
+        .filter(offers::deleted_at.is_null())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error checking for existing offer {}: {}", id, e);
+            AppError::NotFound
+        })?;
+    let slug = resolve_slug(
+        &mut db,
+        update_data.slug.as_deref(),
+        &update_data.title,
+        Some(id),
+    )
+    .await?;
+
+    // A new image replaces the primary image; without one the images are kept
+    let primary_image = process_image_upload(update_data.image, image_settings).await?;
+    diesel::update(target)
+        .set((
+            offers::title.eq(&update_data.title),
+            offers::slug.eq(&slug),
+            offers::excerpt.eq(&update_data.excerpt),
+            offers::content.eq(&update_data.content),
+            offers::link.eq(&update_data.link),
+            offers::latitude.eq(latitude),
+            offers::longitude.eq(longitude),
+            offers::active.eq(active),
+            offers::starts_at.eq(starts_at),
+            offers::ends_at.eq(ends_at),
+        ))
         .execute(&mut db)
         .await
         .map_err(|e| {
-            error!("Error inserting offer: {}", e);
-            AppError::from(e)
+            error!("Error updating offer {}: {}", id, e);
+            map_slug_write_error(e, &slug)
         })?;
 
-    // Retrieve inserted row by slug (slug should be unique)
-    let inserted: Offer = offers::table
-        .filter(offers::slug.eq(&new_offer.slug))
+    if let Some(image) = primary_image {
+        let stored = media.store_image(&mut db, MEDIA_PREFIX, &image).await?;
+        let stored_key = stored.key.clone();
+        let saved = db
+            .transaction(|conn| Box::pin(async move { set_primary_image(conn, id, &stored).await }))
+            .await;
+        match saved {
+            Ok(replaced) => media.release_images(&mut db, replaced.as_slice()).await,
+            Err(e) => {
+                error!("Error saving the primary image of offer {}: {}", id, e);
+                media.release_images(&mut db, &[stored_key]).await;
+                return Err(e.into());
+            }
+        }
+    }
+
+    // Reload for the timestamps set by the database
+    let updated: Offer = target
         .select(Offer::as_select())
         .first(&mut db)
         .await
         .map_err(|e| {
-            error!("Error fetching created offer: {}", e);
+            error!("Error fetching updated offer {}: {}", id, e);
             AppError::from(e)
         })?;
 
-    let dto = OfferDto {
-        id: inserted.id,
-        title: inserted.title,
-        slug: inserted.slug,
-        excerpt: inserted.excerpt,
-        content: inserted.content,
-        link: inserted.link,
-        image_mime: inserted.image_mime,
-        created_at: inserted.created_at,
-        latitude: inserted.latitude,
-        longitude: inserted.longitude,
-    };
-
-    info!("Offer created successfully with id: {}", inserted.id);
-    Ok(Json(dto))
+    record_audit(&mut db, &admin, "offer.update", "offer", id).await;
+    info!("Offer {} updated successfully", id);
+    Ok(Json(with_images(&mut db, updated).await?))
 }
 
-#[put("/admin/api/offers/<id>", data = "<update_form>")]
-pub async fn update_offer(
+/// Change only the fields given as JSON, e.g. `{ "active": false }`
+#[patch("/admin/api/offers/<id>", format = "json", data = "<request>")]
+pub async fn patch_offer(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
     id: i64,
-    update_form: Form<AdminUpdateOfferMultipart<'_>>,
-) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    request: Json<AdminPatchOfferRequest>,
+) -> AppResult<Json<OfferDto>> {
+    admin.require(Permission::ManageContent)?;
 
-    let update_data = update_form.into_inner();
+    let patch = request.into_inner();
     let target = offers::table.find(id);
 
-    // Check if offer exists
-    let _existing_offer: Offer = offers::table.find(id).first(&mut db).await.map_err(|e| {
-        error!("Error checking for existing offer {}: {}", id, e);
-        AppError::NotFound
-    })?;
+    // Offers in the trash must be restored first
+    let existing: Offer = target
+        .filter(offers::deleted_at.is_null())
+        .select(Offer::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error checking for existing offer {}: {}", id, e);
+            AppError::NotFound
+        })?;
 
-    let update_values = match process_image_upload(update_data.image).await? {
-        Some((buffer, ct_string)) => {
-            // Update with new image
-            diesel::update(target)
-                .set((
-                    offers::title.eq(&update_data.title),
-                    offers::slug.eq(&update_data.slug),
-                    offers::excerpt.eq(&update_data.excerpt),
-                    offers::content.eq(&update_data.content),
-                    offers::link.eq(&update_data.link),
-                    offers::image.eq(buffer),
-                    offers::image_mime.eq(Some(ct_string)),
-                    offers::latitude.eq(update_data.latitude),
-                    offers::longitude.eq(update_data.longitude),
-                ))
-                .execute(&mut db)
-                .await
-        }
-        None => {
-            // No new image provided - keep existing image
-            diesel::update(target)
-                .set((
-                    offers::title.eq(&update_data.title),
-                    offers::slug.eq(&update_data.slug),
-                    offers::excerpt.eq(&update_data.excerpt),
-                    offers::content.eq(&update_data.content),
-                    offers::link.eq(&update_data.link),
-                    offers::latitude.eq(update_data.latitude),
-                    offers::longitude.eq(update_data.longitude),
-                ))
-                .execute(&mut db)
-                .await
-        }
-    };
+    let requested_slug = patch.slug.clone();
+    let mut changes = offer_changes(&existing, patch)?;
+    if let Some(requested) = requested_slug {
+        let title = changes.title.as_deref().unwrap_or(&existing.title);
+        changes.slug = Some(resolve_slug(&mut db, Some(&requested), title, Some(id)).await?);
+    }
 
-    update_values.map_err(|e| {
-        error!("Error updating offer {}: {}", id, e);
-        AppError::from(e)
-    })?;
+    if changes != OfferChangeset::default() {
+        let slug = changes.slug.clone().unwrap_or(existing.slug);
+        diesel::update(target)
+            .set(&changes)
+            .execute(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error patching offer {}: {}", id, e);
+                map_slug_write_error(e, &slug)
+            })?;
+    }
 
-    info!("Offer {} updated successfully", id);
-    Ok(Status::Ok)
+    // Reload for the timestamps set by the database
+    let updated: Offer = target
+        .select(Offer::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error fetching updated offer {}: {}", id, e);
+            AppError::from(e)
+        })?;
+
+    record_audit(&mut db, &admin, "offer.update", "offer", id).await;
+    info!("Offer {} patched successfully", id);
+    Ok(Json(with_images(&mut db, updated).await?))
 }
 
+/// Move an offer to the trash, from where it can be restored or deleted
+/// permanently
 #[delete("/admin/api/offers/<id>")]
 pub async fn delete_offer(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: AdminUser,
     id: i64,
 ) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageContent)?;
 
-    diesel::delete(offers::table.find(id))
+    diesel::update(offers::table.find(id).filter(offers::deleted_at.is_null()))
+        .set(offers::deleted_at.eq(Utc::now().naive_utc()))
         .execute(&mut db)
         .await
         .map_err(|e| {
@@ -174,14 +606,49 @@ pub async fn delete_offer(
             AppError::from(e)
         })?;
 
-    info!("Offer {} deleted successfully", id);
+    record_audit(&mut db, &admin, "offer.delete", "offer", id).await;
+    info!("Offer {} moved to the trash", id);
     Ok(Status::Ok)
 }
 
-#[get("/api/offers")]
-pub async fn list_offers(mut db: Connection<MessagesDB>) -> AppResult<Json<Vec<OfferDto>>> {
-    let results: Vec<Offer> = offers::table
-        .order(offers::created_at.desc())
+/// Offers shown now, in the order set by admins, or best matches first when
+/// searching `q` in their title, excerpt and content
+#[get("/api/offers?<page>&<limit>&<q>")]
+pub async fn list_offers(
+    mut db: Connection<MessagesDB>,
+    page: Option<i64>,
+    limit: Option<i64>,
+    q: Option<&str>,
+) -> AppResult<Json<PaginatedOffers>> {
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let query = match q.map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => Some(search_query(q)?),
+        None => None,
+    };
+
+    let now = Utc::now().naive_utc();
+    let total: i64 = public_offers(query, now)
+        .count()
+        .get_result(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error counting offers: {}", e);
+            AppError::from(e)
+        })?;
+
+    let filtered = public_offers(query, now);
+    let ordered = match query {
+        Some(query) => filtered.order((
+            offer_search_relevance(query).desc(),
+            offers::sort_order.asc(),
+        )),
+        None => filtered.order(offers::sort_order.asc()),
+    };
+    let results: Vec<Offer> = ordered
+        .then_order_by((offers::created_at.desc(), offers::id.desc()))
+        .limit(limit)
+        .offset((page - 1) * limit)
         .select(Offer::as_select())
         .load(&mut db)
         .await
@@ -190,33 +657,128 @@ pub async fn list_offers(mut db: Connection<MessagesDB>) -> AppResult<Json<Vec<O
             AppError::from(e)
         })?;
 
-    let dtos: Vec<OfferDto> = results
-        .into_iter()
-        .map(|o| OfferDto {
-            id: o.id,
-            title: o.title,
-            slug: o.slug,
-            excerpt: o.excerpt,
-            content: o.content,
-            link: o.link,
-            image_mime: o.image_mime,
-            created_at: o.created_at,
-            latitude: o.latitude,
-            longitude: o.longitude,
+    info!("Retrieved {} of {} offers", results.len(), total);
+    Ok(Json(PaginatedOffers {
+        data: to_offer_dtos(&mut db, results).await?,
+        total,
+        page,
+        limit,
+    }))
+}
+
+/// Offers for the admin list in display order, optionally filtered by
+/// `status` (`active`, `scheduled`, `expired` or `inactive`) and by `q` in
+/// the title
+#[get("/admin/api/offers?<page>&<limit>&<status>&<q>")]
+pub async fn list_all_offers(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    page: Option<i64>,
+    limit: Option<i64>,
+    status: Option<&str>,
+    q: Option<&str>,
+) -> AppResult<Json<PaginatedOffers>> {
+    admin.require(Permission::ManageContent)?;
+
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let status = status_filter(status)?;
+    let title = q.map(str::trim).filter(|q| !q.is_empty());
+    let now = Utc::now().naive_utc();
+
+    let total: i64 = admin_offers(status, title, now)
+        .count()
+        .get_result(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error counting offers: {}", e);
+            AppError::from(e)
+        })?;
+
+    let results: Vec<Offer> = admin_offers(status, title, now)
+        .order((
+            offers::sort_order.asc(),
+            offers::created_at.desc(),
+            offers::id.desc(),
+        ))
+        .limit(limit)
+        .offset((page - 1) * limit)
+        .select(Offer::as_select())
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading all offers: {}", e);
+            AppError::from(e)
+        })?;
+
+    info!("Retrieved {} of {} offers", results.len(), total);
+    Ok(Json(PaginatedOffers {
+        data: to_offer_dtos(&mut db, results).await?,
+        total,
+        page,
+        limit,
+    }))
+}
+
+/// Set the display order of all offers at once
+#[put("/admin/api/offers/reorder", format = "json", data = "<request>")]
+pub async fn reorder_offers(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminOfferOrderRequest>,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let current: Vec<i64> = offers::table
+        .filter(offers::deleted_at.is_null())
+        .select(offers::id)
+        .load(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error loading offers to reorder: {}", e);
+            AppError::from(e)
+        })?;
+    let offer_ids = request.into_inner().offer_ids;
+    check_offer_order(&current, &offer_ids)?;
+
+    db.transaction(|conn| {
+        Box::pin(async move {
+            for (position, offer_id) in offer_ids.into_iter().enumerate() {
+                diesel::update(offers::table.find(offer_id))
+                    .set(offers::sort_order.eq(position as i32))
+                    .execute(conn)
+                    .await?;
+            }
+            Ok::<_, diesel::result::Error>(())
         })
-        .collect();
+    })
+    .await
+    .map_err(|e| {
+        error!("Error reordering offers: {}", e);
+        AppError::from(e)
+    })?;
 
-    info!("Retrieved {} offers", dtos.len());
-    Ok(Json(dtos))
+    record_audit(&mut db, &admin, "offer.reorder", "offer", "all").await;
+    info!("Admin '{}' reordered offers", admin.username);
+    Ok(Status::Ok)
 }
 
+/// An offer by slug while it is shown on the public site
 #[get("/api/offers/<slug>")]
 pub async fn get_offer_by_slug(
     mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
     slug: String,
 ) -> AppResult<Json<OfferDto>> {
+    let slug = if config.normalize_slugs {
+        normalize_slug(&slug)
+    } else {
+        slug
+    };
+
     let offer: Offer = offers::table
         .filter(offers::slug.eq(&slug))
+        .filter(currently_active(Utc::now().naive_utc()))
         .select(Offer::as_select())
         .first(&mut db)
         .await
@@ -225,38 +787,173 @@ pub async fn get_offer_by_slug(
             AppError::NotFound
         })?;
 
-    Ok(Json(OfferDto {
-        id: offer.id,
-        title: offer.title,
-        slug: offer.slug,
-        excerpt: offer.excerpt,
-        content: offer.content,
-        link: offer.link,
-        image_mime: offer.image_mime,
-        created_at: offer.created_at,
-        latitude: offer.latitude,
-        longitude: offer.longitude,
-    }))
+    Ok(Json(with_images(&mut db, offer).await?))
 }
 
-#[get("/api/offers/<id>/image")]
+/// The primary image of an offer, the first of its images, in the size
+/// given by `size`
+#[get("/api/offers/<id>/image?<size>")]
 pub async fn get_offer_image(
     mut db: Connection<MessagesDB>,
+    media: &State<Media>,
+    conditional: ConditionalHeaders,
+    accept: Option<&Accept>,
     id: i64,
-) -> AppResult<(ContentType, Vec<u8>)> {
-    let offer: Offer = offers::table.find(id).first(&mut db).await.map_err(|e| {
-        error!("Error fetching offer {} for image: {}", id, e);
-        AppError::NotFound
-    })?;
+    size: Option<&str>,
+) -> AppResult<CachedImage> {
+    let size = ImageSize::parse(size)?;
+    let image_id: i64 = offer_images::table
+        .filter(offer_images::offer_id.eq(id))
+        .order((offer_images::position.asc(), offer_images::id.asc()))
+        .select(offer_images::id)
+        .first(&mut db)
+        .await
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching the primary image of offer {}: {}", id, e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound)?;
 
-    if let Some(image_bytes) = offer.image {
-        let content_type = offer
-            .image_mime
-            .and_then(|m| ContentType::parse_flexible(&m))
-            .unwrap_or(ContentType::JPEG);
+    offer_image_response(&mut db, media, id, image_id, size, accept, &conditional).await
+}
 
-        Ok((content_type, image_bytes))
-    } else {
-        Err(AppError::NotFound)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_offer_order() {
+        assert!(check_offer_order(&[1, 2, 3], &[3, 1, 2]).is_ok());
+        assert!(check_offer_order(&[], &[]).is_ok());
+        assert!(check_offer_order(&[1, 2, 3], &[1, 2]).is_err());
+        assert!(check_offer_order(&[1, 2], &[1, 1]).is_err());
+        assert!(check_offer_order(&[1, 2], &[1, 2, 9]).is_err());
+    }
+
+    #[test]
+    fn test_time_window() {
+        let start =
+            NaiveDateTime::parse_from_str("2026-03-02 09:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let end =
+            NaiveDateTime::parse_from_str("2026-03-31 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        assert_eq!(
+            time_window(Some("2026-03-02T10:30:00+01:00"), Some("2026-03-31T00:00")).ok(),
+            Some((Some(start), Some(end)))
+        );
+        assert_eq!(
+            time_window(Some("2026-03-02T09:30:00Z"), Some(" ")).ok(),
+            Some((Some(start), None))
+        );
+        assert_eq!(time_window(None, None).ok(), Some((None, None)));
+        assert!(time_window(Some("March 2"), None).is_err());
+        assert!(time_window(Some("2026-03-31T00:00"), Some("2026-03-02T09:30")).is_err());
+        assert!(time_window(Some("2026-03-02T09:30"), Some("2026-03-02T09:30")).is_err());
+    }
+
+    fn offer() -> Offer {
+        let created_at =
+            NaiveDateTime::parse_from_str("2026-03-01 08:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        Offer {
+            id: 7,
+            title: "Guided hike".to_string(),
+            slug: "guided-hike".to_string(),
+            excerpt: Some("A day in the mountains".to_string()),
+            content: None,
+            link: None,
+            created_at,
+            latitude: Some(49.0742),
+            longitude: Some(20.2988),
+            sort_order: 0,
+            active: true,
+            starts_at: None,
+            ends_at: Some(
+                NaiveDateTime::parse_from_str("2026-04-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            ),
+            deleted_at: None,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_offer_changes() {
+        let toggle = AdminPatchOfferRequest {
+            active: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            offer_changes(&offer(), toggle).ok(),
+            Some(OfferChangeset {
+                active: Some(false),
+                ..Default::default()
+            })
+        );
+
+        let cleared = AdminPatchOfferRequest {
+            excerpt: Some(None),
+            latitude: Some(None),
+            longitude: Some(None),
+            ..Default::default()
+        };
+        assert_eq!(
+            offer_changes(&offer(), cleared).ok(),
+            Some(OfferChangeset {
+                excerpt: Some(None),
+                latitude: Some(None),
+                longitude: Some(None),
+                ..Default::default()
+            })
+        );
+
+        // Checked against the values that are kept
+        let half_position = AdminPatchOfferRequest {
+            latitude: Some(None),
+            ..Default::default()
+        };
+        assert!(offer_changes(&offer(), half_position).is_err());
+        let late_start = AdminPatchOfferRequest {
+            starts_at: Some(Some("2026-05-01T00:00:00Z".to_string())),
+            ..Default::default()
+        };
+        assert!(offer_changes(&offer(), late_start).is_err());
+        let blank_title = AdminPatchOfferRequest {
+            title: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert!(offer_changes(&offer(), blank_title).is_err());
+    }
+
+    #[test]
+    fn test_status_filter() {
+        assert_eq!(status_filter(None).ok(), Some(None));
+        assert_eq!(status_filter(Some("all")).ok(), Some(None));
+        assert_eq!(
+            status_filter(Some("expired")).ok(),
+            Some(Some(StatusFilter::Expired))
+        );
+        assert_eq!(
+            status_filter(Some(" inactive ")).ok(),
+            Some(Some(StatusFilter::Inactive))
+        );
+        assert!(status_filter(Some("draft")).is_err());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        assert_eq!(
+            coordinates(Some(49.0742), Some(20.2988)).ok(),
+            Some((Some(49.0742), Some(20.2988)))
+        );
+        assert_eq!(
+            coordinates(Some(-90.0), Some(180.0)).ok(),
+            Some((Some(-90.0), Some(180.0)))
+        );
+        assert_eq!(coordinates(None, None).ok(), Some((None, None)));
+        assert!(coordinates(Some(90.5), Some(20.0)).is_err());
+        assert!(coordinates(Some(49.0), Some(-180.5)).is_err());
+        assert!(coordinates(Some(f64::NAN), Some(20.0)).is_err());
+        assert!(coordinates(Some(49.0), None).is_err());
+        assert!(coordinates(None, Some(20.0)).is_err());
     }
 }
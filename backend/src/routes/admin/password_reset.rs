@@ -0,0 +1,261 @@
+// Forgot-password flow: short-lived reset tokens delivered by email
+
+use redis::{ExistenceCheck, SetExpiry, SetOptions};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::mailer::Mailer;
+use crate::models::{
+    AdminPasswordReset, AdminPasswordResetConfirmRequest, AdminPasswordResetRequest, AdminUser,
+    NewAdminPasswordReset,
+};
+use crate::password::hash_password;
+use crate::routes::admin::login_limit::ensure_login_allowed;
+use crate::routes::admin::sessions::{hash_token, revoke_user_sessions};
+use crate::routes::admin::users::validate_password;
+use crate::routes::client_ip::ClientIp;
+use crate::schema::{admin_password_resets, admin_users};
+
+const RESET_TTL_MINUTES: i64 = 30;
+const REQUESTS_BY_IP_PREFIX: &str = "admin_password_reset_ip:";
+const REQUESTS_BY_USER_PREFIX: &str = "admin_password_reset_user:";
+
+fn reset_link(public_base_url: &str, token: &str) -> String {
+    format!(
+        "{}/admin/password-reset?token={token}",
+        public_base_url.trim_end_matches('/')
+    )
+}
+
+/// Count a request under `key` in a window of `window_secs` starting with
+/// the first one. Returns the requests so far and the seconds left in the
+/// window.
+async fn count_request(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+    window_secs: u64,
+) -> AppResult<(u64, i64)> {
+    let window = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(window_secs.max(1)));
+    Ok(redis::pipe()
+        .atomic()
+        .set_options(key, 0, window)
+        .ignore()
+        .incr(key, 1)
+        .ttl(key)
+        .query_async(conn)
+        .await?)
+}
+
+/// Whether `requests` are over a limit, where a limit of zero disables it
+fn over_limit(requests: u64, max_requests: u64) -> bool {
+    max_requests > 0 && requests > max_requests
+}
+
+async fn delete_expired_resets(db: &mut Connection<MessagesDB>) -> AppResult<()> {
+    diesel::delete(
+        admin_password_resets::table
+            .filter(admin_password_resets::expires_at.lt(chrono::Utc::now().naive_utc())),
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Start a password reset. Always answers 200 so the endpoint does not reveal
+/// which usernames exist, and sends the email in the background so the
+/// response time does not either. Without SMTP, or for users without an
+/// email address, the reset link is written to the server log instead.
+/// Clients over `ADMIN_PASSWORD_RESET_MAX_PER_IP` get 429; requests for a
+/// username over `ADMIN_PASSWORD_RESET_MAX_PER_USER` are ignored.
+#[post("/admin/password-reset/request", format = "json", data = "<request>")]
+pub async fn request_password_reset(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    mailer: &State<Mailer>,
+    client_ip: ClientIp,
+    request: Json<AdminPasswordResetRequest>,
+) -> AppResult<Status> {
+    ensure_login_allowed(redis, client_ip.0).await?;
+
+    let window_secs = config.admin_password_reset_window_secs;
+    let client = client_ip
+        .0
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let (requests, ttl_secs) = count_request(
+        &mut conn,
+        &format!("{REQUESTS_BY_IP_PREFIX}{client}"),
+        window_secs,
+    )
+    .await?;
+    if over_limit(requests, config.admin_password_reset_max_per_ip) {
+        warn!(
+            "Rate limited password reset request {} from {}",
+            requests, client
+        );
+        return Err(AppError::TooManyRequests {
+            retry_after_secs: u64::try_from(ttl_secs).unwrap_or(1).max(1),
+        });
+    }
+    // Counted for unknown usernames too, so the limit reveals nothing
+    let username = request.username.trim();
+    let (requests, _) = count_request(
+        &mut conn,
+        &format!("{REQUESTS_BY_USER_PREFIX}{}", username.to_lowercase()),
+        window_secs,
+    )
+    .await?;
+    if over_limit(requests, config.admin_password_reset_max_per_user) {
+        warn!(
+            "Ignored password reset request {} for admin '{}'",
+            requests, username
+        );
+        return Ok(Status::Ok);
+    }
+
+    delete_expired_resets(&mut db).await?;
+
+    let Some(user) = admin_users::table
+        .filter(admin_users::username.eq(username))
+        .select(AdminUser::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+    else {
+        info!("Password reset requested for unknown admin '{}'", username);
+        return Ok(Status::Ok);
+    };
+
+    let token = Uuid::new_v4().simple().to_string();
+    let reset = NewAdminPasswordReset {
+        user_id: user.id,
+        token_hash: hash_token(&token),
+        expires_at: (chrono::Utc::now() + chrono::Duration::minutes(RESET_TTL_MINUTES)).naive_utc(),
+    };
+
+    // Only the newest link stays valid
+    db.transaction(|conn| {
+        Box::pin(async move {
+            diesel::delete(
+                admin_password_resets::table.filter(admin_password_resets::user_id.eq(user.id)),
+            )
+            .execute(conn)
+            .await?;
+
+            diesel::insert_into(admin_password_resets::table)
+                .values(&reset)
+                .execute(conn)
+                .await?;
+
+            Ok::<_, diesel::result::Error>(())
+        })
+    })
+    .await?;
+
+    let link = reset_link(&config.public_base_url, &token);
+    match user.email {
+        Some(email) if mailer.is_enabled() => {
+            let body = format!(
+                "Hello {},\n\nopen the following link to choose a new password:\n\n{link}\n\n\
+                 The link expires in {RESET_TTL_MINUTES} minutes. If you did not ask for a \
+                 password reset, ignore this email.\n",
+                user.username
+            );
+            let mailer = mailer.inner().clone();
+            rocket::tokio::spawn(async move {
+                if let Err(e) = mailer.send(&email, "Admin password reset", body).await {
+                    error!(
+                        "Failed to send password reset email to admin '{}': {}",
+                        user.username, e
+                    );
+                } else {
+                    info!("Sent password reset email to admin '{}'", user.username);
+                }
+            });
+        }
+        _ => {
+            warn!(
+                "Password reset link for admin '{}' (not emailed): {}",
+                user.username, link
+            );
+        }
+    }
+
+    Ok(Status::Ok)
+}
+
+/// Set the new password and sign the user out of every session
+#[post("/admin/password-reset/confirm", format = "json", data = "<request>")]
+pub async fn confirm_password_reset(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    request: Json<AdminPasswordResetConfirmRequest>,
+) -> AppResult<Status> {
+    delete_expired_resets(&mut db).await?;
+    validate_password(&request.password)?;
+
+    let reset = admin_password_resets::table
+        .filter(admin_password_resets::token_hash.eq(hash_token(request.token.trim())))
+        .select(AdminPasswordReset::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    let password_hash = hash_password(&request.password)?;
+    let user_id = reset.user_id;
+    db.transaction(|conn| {
+        Box::pin(async move {
+            diesel::update(admin_users::table.find(user_id))
+                .set(admin_users::password_hash.eq(password_hash))
+                .execute(conn)
+                .await?;
+
+            diesel::delete(
+                admin_password_resets::table.filter(admin_password_resets::user_id.eq(user_id)),
+            )
+            .execute(conn)
+            .await?;
+
+            Ok::<_, diesel::result::Error>(())
+        })
+    })
+    .await?;
+    revoke_user_sessions(redis, user_id, None).await?;
+
+    info!("Admin user {} reset their password", user_id);
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_link() {
+        assert_eq!(
+            reset_link("https://example.com/", "abc"),
+            "https://example.com/admin/password-reset?token=abc"
+        );
+        assert_eq!(reset_link("", "abc"), "/admin/password-reset?token=abc");
+    }
+
+    #[test]
+    fn test_over_limit() {
+        assert!(!over_limit(3, 3));
+        assert!(over_limit(4, 3));
+        assert!(!over_limit(100, 0));
+    }
+}
@@ -0,0 +1,294 @@
+// Long-lived "remember me" tokens that start a fresh session once the short
+// session has expired. Each token is single-use and replaced on redemption.
+
+use redis::AsyncCommands;
+use rocket::State;
+use rocket::http::{Cookie, CookieJar};
+use std::net::IpAddr;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::error::AppResult;
+use crate::routes::admin::sessions::{
+    UserAgent, cookie_same_site, hash_token, start_admin_session,
+};
+
+const REMEMBER_PREFIX: &str = "admin_remember:";
+const USED_REMEMBER_PREFIX: &str = "admin_remember_used:";
+const USER_REMEMBER_PREFIX: &str = "admin_user_remember:";
+/// Requests fired in parallel with the same cookie may all try to redeem it;
+/// reuse this soon after rotation is not treated as theft
+const REUSE_GRACE_SECS: i64 = 60;
+/// The cookie is only sent to admin endpoints
+const COOKIE_PATH: &str = "/admin";
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct RememberMeToken {
+    user_id: i64,
+    /// Unix timestamp; rotation keeps the expiry of the original login
+    expires_at: i64,
+}
+
+/// Marker left behind for a redeemed token, to detect replays
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct UsedRememberMeToken {
+    user_id: i64,
+    rotated_at: i64,
+}
+
+fn remember_key(token_hash: &str) -> String {
+    format!("{REMEMBER_PREFIX}{token_hash}")
+}
+
+fn used_remember_key(token_hash: &str) -> String {
+    format!("{USED_REMEMBER_PREFIX}{token_hash}")
+}
+
+fn user_remember_key(user_id: i64) -> String {
+    format!("{USER_REMEMBER_PREFIX}{user_id}")
+}
+
+/// Seconds until `expires_at`, or `None` once it has passed
+fn remaining_secs(expires_at: i64, now: i64) -> Option<u64> {
+    u64::try_from(expires_at.saturating_sub(now))
+        .ok()
+        .filter(|secs| *secs > 0)
+}
+
+fn remember_me_cookie(config: &AppConfig, token: String, ttl_secs: u64) -> Cookie<'static> {
+    let mut cookie = Cookie::new(config.admin_remember_me_cookie_name.clone(), token);
+    cookie.set_http_only(true);
+    cookie.set_secure(config.admin_session_cookie_secure);
+    cookie.set_same_site(cookie_same_site(&config.admin_session_cookie_same_site));
+    cookie.set_path(COOKIE_PATH);
+    cookie.set_max_age(rocket::time::Duration::seconds(
+        i64::try_from(ttl_secs).unwrap_or(i64::MAX),
+    ));
+    cookie
+}
+
+fn clear_remember_me_cookie(cookies: &CookieJar<'_>, config: &AppConfig) {
+    let mut cookie = Cookie::from(config.admin_remember_me_cookie_name.clone());
+    cookie.set_path(COOKIE_PATH);
+    cookies.remove(cookie);
+}
+
+async fn store_remember_me_token(
+    redis: &redis::Client,
+    config: &AppConfig,
+    cookies: &CookieJar<'_>,
+    user_id: i64,
+    expires_at: i64,
+) -> AppResult<()> {
+    let Some(ttl_secs) = remaining_secs(expires_at, chrono::Utc::now().timestamp()) else {
+        clear_remember_me_cookie(cookies, config);
+        return Ok(());
+    };
+
+    let token = Uuid::new_v4().to_string();
+    let token_hash = hash_token(&token);
+    let payload = serde_json::to_string(&RememberMeToken {
+        user_id,
+        expires_at,
+    })?;
+    let index_key = user_remember_key(user_id);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let _: () = redis::pipe()
+        .set_ex(remember_key(&token_hash), payload, ttl_secs)
+        .sadd(&index_key, &token_hash)
+        .expire(
+            &index_key,
+            i64::try_from(config.admin_remember_me_secs).unwrap_or(i64::MAX),
+        )
+        .query_async(&mut conn)
+        .await?;
+
+    cookies.add(remember_me_cookie(config, token, ttl_secs));
+    Ok(())
+}
+
+/// Issue a remember-me token after a login that asked for it. Does nothing
+/// when `ADMIN_REMEMBER_ME_SECS` is zero.
+pub(crate) async fn issue_remember_me_token(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    cookies: &CookieJar<'_>,
+    user_id: i64,
+) -> AppResult<()> {
+    if config.admin_remember_me_secs == 0 {
+        return Ok(());
+    }
+
+    let expires_at = chrono::Utc::now()
+        .timestamp()
+        .saturating_add(i64::try_from(config.admin_remember_me_secs).unwrap_or(i64::MAX));
+    store_remember_me_token(redis, config, cookies, user_id, expires_at).await
+}
+
+/// Redeem the remember-me cookie for a new session, rotating the token.
+/// Replaying an already redeemed token revokes every remember-me token of
+/// its user. Returns the id of the user signed in again.
+pub(crate) async fn resume_remembered_session(
+    cookies: &CookieJar<'_>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    client_ip: Option<IpAddr>,
+    user_agent: UserAgent,
+) -> AppResult<Option<i64>> {
+    if config.admin_remember_me_secs == 0 {
+        return Ok(None);
+    }
+    let Some(token) = cookies
+        .get(&config.admin_remember_me_cookie_name)
+        .map(|cookie| cookie.value().to_string())
+    else {
+        return Ok(None);
+    };
+
+    let token_hash = hash_token(&token);
+    let now = chrono::Utc::now().timestamp();
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let payload: Option<String> = conn.get_del(remember_key(&token_hash)).await?;
+
+    let Some(payload) = payload else {
+        let used: Option<String> = conn.get(used_remember_key(&token_hash)).await?;
+        match used.and_then(|used| serde_json::from_str::<UsedRememberMeToken>(&used).ok()) {
+            Some(used) if now.saturating_sub(used.rotated_at) <= REUSE_GRACE_SECS => {}
+            Some(used) => {
+                warn!(
+                    "Replayed remember-me token of admin user {} from {:?}, revoking all",
+                    used.user_id, client_ip
+                );
+                revoke_user_remember_me_tokens(redis, used.user_id).await?;
+                clear_remember_me_cookie(cookies, config);
+            }
+            None => clear_remember_me_cookie(cookies, config),
+        }
+        return Ok(None);
+    };
+
+    let remembered: RememberMeToken = serde_json::from_str(&payload)?;
+    let _: usize = conn
+        .srem(user_remember_key(remembered.user_id), &token_hash)
+        .await?;
+    let Some(ttl_secs) = remaining_secs(remembered.expires_at, now) else {
+        clear_remember_me_cookie(cookies, config);
+        return Ok(None);
+    };
+    let used = serde_json::to_string(&UsedRememberMeToken {
+        user_id: remembered.user_id,
+        rotated_at: now,
+    })?;
+    let _: () = conn
+        .set_ex(used_remember_key(&token_hash), used, ttl_secs)
+        .await?;
+
+    store_remember_me_token(
+        redis,
+        config,
+        cookies,
+        remembered.user_id,
+        remembered.expires_at,
+    )
+    .await?;
+    start_admin_session(
+        redis,
+        config,
+        cookies,
+        remembered.user_id,
+        client_ip,
+        user_agent,
+    )
+    .await?;
+
+    info!(
+        "Resumed remembered session of admin user {} from {:?}",
+        remembered.user_id, client_ip
+    );
+    Ok(Some(remembered.user_id))
+}
+
+/// Drop the remember-me token behind the request's cookie, e.g. on logout
+pub(crate) async fn forget_remember_me_token(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    cookies: &CookieJar<'_>,
+) -> AppResult<()> {
+    let Some(token) = cookies
+        .get(&config.admin_remember_me_cookie_name)
+        .map(|cookie| cookie.value().to_string())
+    else {
+        return Ok(());
+    };
+
+    let token_hash = hash_token(&token);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let payload: Option<String> = conn.get_del(remember_key(&token_hash)).await?;
+    if let Some(remembered) =
+        payload.and_then(|payload| serde_json::from_str::<RememberMeToken>(&payload).ok())
+    {
+        let _: usize = conn
+            .srem(user_remember_key(remembered.user_id), &token_hash)
+            .await?;
+    }
+    clear_remember_me_cookie(cookies, config);
+    Ok(())
+}
+
+/// Revoke every remember-me token of a user
+pub(crate) async fn revoke_user_remember_me_tokens(
+    redis: &redis::Client,
+    user_id: i64,
+) -> AppResult<()> {
+    let index_key = user_remember_key(user_id);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let token_hashes: Vec<String> = conn.smembers(&index_key).await?;
+    for token_hash in &token_hashes {
+        let _: usize = conn.del(remember_key(token_hash)).await?;
+    }
+    let _: usize = conn.del(&index_key).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(overrides: serde_json::Value) -> AppConfig {
+        let mut config = serde_json::json!({
+            "database_url": "mysql://localhost/test",
+            "redis_url": "redis://localhost",
+        });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        serde_json::from_value(config).expect("valid config")
+    }
+
+    #[test]
+    fn test_remaining_secs() {
+        assert_eq!(remaining_secs(1_000, 400), Some(600));
+        assert_eq!(remaining_secs(1_000, 1_000), None);
+        assert_eq!(remaining_secs(1_000, 2_000), None);
+    }
+
+    #[test]
+    fn test_remember_me_cookie_attributes() {
+        let config = config(serde_json::json!({
+            "admin_session_cookie_secure": true,
+        }));
+        let cookie = remember_me_cookie(&config, "t".into(), 3_600);
+
+        assert_eq!(cookie.name(), "admin_remember");
+        assert_eq!(cookie.path(), Some("/admin"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(
+            cookie.max_age(),
+            Some(rocket::time::Duration::seconds(3_600))
+        );
+        assert_eq!(config.admin_remember_me_secs, 60 * 60 * 24 * 30);
+    }
+}
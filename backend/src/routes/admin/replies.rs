@@ -0,0 +1,161 @@
+// Email replies to contact messages
+
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::db::{MessagesDB, last_insert_id};
+use crate::error::{AppError, AppResult};
+use crate::mailer::Mailer;
+use crate::models::{AdminMessageReplyRequest, Message, MessageReply, NewMessageReply, Permission};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{message_replies, messages};
+
+const MAX_REPLY_SUBJECT_LEN: usize = 255;
+const MAX_REPLY_BODY_LEN: usize = 10_000;
+/// Subject used when neither the reply nor the message has one
+const DEFAULT_REPLY_SUBJECT: &str = "Re: Vaša správa";
+
+/// Subject of a reply: the one given by the admin, or `Re:` followed by the
+/// subject of the message
+fn reply_subject(custom: Option<&str>, original: Option<&str>) -> AppResult<String> {
+    let subject = match custom.map(str::trim).filter(|subject| !subject.is_empty()) {
+        Some(subject) => subject.to_string(),
+        None => match original
+            .map(str::trim)
+            .filter(|subject| !subject.is_empty())
+        {
+            Some(subject) if subject.to_lowercase().starts_with("re:") => subject.to_string(),
+            Some(subject) => format!("Re: {subject}"),
+            None => DEFAULT_REPLY_SUBJECT.to_string(),
+        },
+    };
+
+    if subject.chars().count() > MAX_REPLY_SUBJECT_LEN || subject.contains(['\r', '\n']) {
+        return Err(AppError::InvalidInput(format!(
+            "Reply subject must be a single line of at most {MAX_REPLY_SUBJECT_LEN} characters."
+        )));
+    }
+    Ok(subject)
+}
+
+/// Replies sent to a message, oldest first
+pub(crate) async fn load_replies(
+    db: &mut Connection<MessagesDB>,
+    message_id: i64,
+) -> AppResult<Vec<MessageReply>> {
+    Ok(message_replies::table
+        .filter(message_replies::message_id.eq(message_id))
+        .order(message_replies::id.asc())
+        .select(MessageReply::as_select())
+        .load(db)
+        .await?)
+}
+
+/// Email a reply to the sender of a message, store it and mark the message
+/// as answered. Nothing is stored when the email cannot be sent.
+#[post("/admin/api/messages/<id>/reply", format = "json", data = "<request>")]
+pub async fn reply_to_message(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    mailer: &State<Mailer>,
+    id: i64,
+    request: Json<AdminMessageReplyRequest>,
+) -> AppResult<Json<MessageReply>> {
+    admin.require(Permission::ManageMessages)?;
+
+    let message = messages::table
+        .find(id)
+        .select(Message::as_select())
+        .first(&mut db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    let body = request.body.trim();
+    if body.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Reply body is required.".to_string(),
+        ));
+    }
+    if body.chars().count() > MAX_REPLY_BODY_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Reply body must be at most {MAX_REPLY_BODY_LEN} characters."
+        )));
+    }
+    let subject = reply_subject(request.subject.as_deref(), message.subject.as_deref())?;
+
+    mailer
+        .send(message.email.trim(), &subject, body.to_string())
+        .await?;
+
+    let reply = NewMessageReply {
+        message_id: id,
+        admin_user_id: Some(admin.id),
+        admin_username: admin.username.clone(),
+        subject,
+        body: body.to_string(),
+    };
+    let reply_id = db
+        .transaction(|mut conn| {
+            Box::pin(async move {
+                diesel::insert_into(message_replies::table)
+                    .values(&reply)
+                    .execute(&mut conn)
+                    .await?;
+                let reply_id: u64 = diesel::select(last_insert_id())
+                    .get_result(&mut conn)
+                    .await?;
+
+                let now = chrono::Utc::now().naive_utc();
+                diesel::update(messages::table.find(id))
+                    .set(messages::answered_at.eq(now))
+                    .execute(&mut conn)
+                    .await?;
+                diesel::update(messages::table.find(id).filter(messages::read_at.is_null()))
+                    .set(messages::read_at.eq(now))
+                    .execute(&mut conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(reply_id as i64)
+            })
+        })
+        .await
+        .map_err(|e| {
+            error!("Reply to message {} was sent but not stored: {}", id, e);
+            AppError::from(e)
+        })?;
+
+    let reply = message_replies::table
+        .find(reply_id)
+        .select(MessageReply::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "message.reply", "message", id).await;
+    info!("Admin '{}' replied to message {}", admin.username, id);
+    Ok(Json(reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_subject() {
+        assert_eq!(reply_subject(None, Some(" Ponuka ")).unwrap(), "Re: Ponuka");
+        assert_eq!(
+            reply_subject(Some(""), Some("RE: Ponuka")).unwrap(),
+            "RE: Ponuka"
+        );
+        assert_eq!(
+            reply_subject(Some(" Termín "), Some("Ponuka")).unwrap(),
+            "Termín"
+        );
+        assert_eq!(reply_subject(None, None).unwrap(), DEFAULT_REPLY_SUBJECT);
+        assert!(reply_subject(Some("a\r\nBcc: x@example.com"), None).is_err());
+    }
+}
@@ -0,0 +1,694 @@
+// Admin session storage and session management endpoints
+
+use redis::AsyncCommands;
+use rocket::State;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::AdminSessionDto;
+use crate::routes::admin::auth::AdminUser;
+use crate::routes::admin::remember_me::revoke_user_remember_me_tokens;
+use crate::utils::ip_in_network;
+
+const SESSION_PREFIX: &str = "admin_session:";
+const USER_SESSIONS_PREFIX: &str = "admin_user_sessions:";
+
+/// The request's `User-Agent` header, recorded with new sessions
+#[derive(Clone)]
+pub struct UserAgent(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let user_agent = req
+            .headers()
+            .get_one("User-Agent")
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+        Outcome::Success(UserAgent(user_agent))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct AdminSessionData {
+    /// Public identifier used to revoke the session without exposing its token
+    #[serde(default)]
+    id: String,
+    pub(crate) user_id: i64,
+    ip_address: Option<String>,
+    #[serde(default)]
+    user_agent: Option<String>,
+    /// Unix timestamp of the login. Sessions stored before this field existed
+    /// start their absolute lifetime on first use.
+    #[serde(default = "unix_now")]
+    created_at: i64,
+}
+
+/// How strictly a session is bound to the IP address it was created from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IpPinning {
+    /// Any address may use the session
+    Off,
+    /// Only the exact address
+    Strict,
+    /// Addresses in the same /24 (IPv4) or /64 (IPv6) network, so that
+    /// admins on rotating mobile addresses stay signed in
+    Subnet,
+}
+
+impl IpPinning {
+    fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => IpPinning::Off,
+            "subnet" => IpPinning::Subnet,
+            "strict" => IpPinning::Strict,
+            other => {
+                warn!("Unknown session IP pinning mode '{}', using strict", other);
+                IpPinning::Strict
+            }
+        }
+    }
+
+    fn allows(self, saved_ip: &str, current_ip: Option<IpAddr>) -> bool {
+        let Some(current_ip) = current_ip else {
+            return self == IpPinning::Off;
+        };
+
+        match self {
+            IpPinning::Off => true,
+            IpPinning::Strict => saved_ip == current_ip.to_string(),
+            IpPinning::Subnet => match saved_ip.parse::<IpAddr>() {
+                Ok(saved @ IpAddr::V4(_)) => ip_in_network(current_ip, saved, 24),
+                Ok(saved @ IpAddr::V6(_)) => ip_in_network(current_ip, saved, 64),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+/// How strictly a session is bound to the User-Agent it was created with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UserAgentBinding {
+    /// Any User-Agent may use the session
+    Off,
+    /// Same browser family and operating system, so that browser updates
+    /// do not sign admins out
+    Loose,
+    /// Only the exact same User-Agent
+    Strict,
+}
+
+impl UserAgentBinding {
+    fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => UserAgentBinding::Off,
+            "loose" => UserAgentBinding::Loose,
+            "strict" => UserAgentBinding::Strict,
+            other => {
+                warn!(
+                    "Unknown session User-Agent binding mode '{}', using loose",
+                    other
+                );
+                UserAgentBinding::Loose
+            }
+        }
+    }
+
+    fn allows(self, saved: &str, current: Option<&str>) -> bool {
+        match self {
+            UserAgentBinding::Off => true,
+            UserAgentBinding::Strict => current == Some(saved),
+            UserAgentBinding::Loose => current
+                .is_some_and(|current| user_agent_family(current) == user_agent_family(saved)),
+        }
+    }
+}
+
+/// Browser family and operating system of a User-Agent, ignoring versions
+fn user_agent_family(user_agent: &str) -> (&'static str, &'static str) {
+    let browser = if user_agent.contains("Edg") {
+        "edge"
+    } else if user_agent.contains("OPR/") || user_agent.contains("Opera") {
+        "opera"
+    } else if user_agent.contains("Firefox/") || user_agent.contains("FxiOS/") {
+        "firefox"
+    } else if user_agent.contains("Chrome/") || user_agent.contains("CriOS/") {
+        "chrome"
+    } else if user_agent.contains("Safari/") {
+        "safari"
+    } else {
+        "other"
+    };
+
+    let os = if user_agent.contains("Windows") {
+        "windows"
+    } else if user_agent.contains("Android") {
+        "android"
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPad") {
+        "ios"
+    } else if user_agent.contains("Mac OS X") || user_agent.contains("Macintosh") {
+        "macos"
+    } else if user_agent.contains("CrOS") {
+        "chromeos"
+    } else if user_agent.contains("Linux") {
+        "linux"
+    } else {
+        "other"
+    };
+
+    (browser, os)
+}
+
+fn unix_now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// SHA-256 of a session token. Only the hash is stored in Redis, so a leaked
+/// Redis dump does not contain usable session cookies.
+pub(crate) fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn session_key(token_hash: &str) -> String {
+    format!("{SESSION_PREFIX}{token_hash}")
+}
+
+/// Redis set holding the token hashes of every session a user has started
+fn user_sessions_key(user_id: i64) -> String {
+    format!("{USER_SESSIONS_PREFIX}{user_id}")
+}
+
+fn secs_i64(secs: u64) -> i64 {
+    i64::try_from(secs).unwrap_or(i64::MAX)
+}
+
+/// Seconds a session may live from `now`: the idle timeout, capped by what is
+/// left of its absolute lifetime. `None` once the absolute lifetime is over.
+fn session_ttl(created_at: i64, now: i64, idle_secs: u64, max_lifetime_secs: u64) -> Option<u64> {
+    let age = u64::try_from(now.saturating_sub(created_at)).unwrap_or(0);
+    let remaining = max_lifetime_secs
+        .checked_sub(age)
+        .filter(|secs| *secs > 0)?;
+    Some(idle_secs.min(remaining))
+}
+
+pub(crate) fn cookie_same_site(value: &str) -> SameSite {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "strict" => SameSite::Strict,
+        "none" => SameSite::None,
+        "lax" => SameSite::Lax,
+        other => {
+            warn!(
+                "Unknown session cookie SameSite mode '{}', using Lax",
+                other
+            );
+            SameSite::Lax
+        }
+    }
+}
+
+/// Build the session cookie from the configured attributes. Its max-age
+/// follows the session TTL unless a shorter one is configured; a configured
+/// max-age of zero makes it a browser-session cookie.
+fn session_cookie(config: &AppConfig, token: String, ttl_secs: u64) -> Cookie<'static> {
+    let mut cookie = Cookie::new(config.admin_session_cookie_name.clone(), token);
+    cookie.set_http_only(true);
+    cookie.set_secure(config.admin_session_cookie_secure);
+    cookie.set_same_site(cookie_same_site(&config.admin_session_cookie_same_site));
+    cookie.set_path("/");
+    match config.admin_session_cookie_max_age_secs {
+        Some(0) => {}
+        Some(max_age_secs) => cookie.set_max_age(rocket::time::Duration::seconds(secs_i64(
+            max_age_secs.min(ttl_secs),
+        ))),
+        None => cookie.set_max_age(rocket::time::Duration::seconds(secs_i64(ttl_secs))),
+    }
+    cookie
+}
+
+/// The session token from the request's session cookie, if any
+pub(crate) fn session_token(cookies: &CookieJar<'_>, config: &AppConfig) -> Option<String> {
+    cookies
+        .get(&config.admin_session_cookie_name)
+        .map(|cookie| cookie.value().to_string())
+}
+
+pub(crate) fn clear_session_cookie(cookies: &CookieJar<'_>, config: &AppConfig) {
+    cookies.remove(Cookie::from(config.admin_session_cookie_name.clone()));
+}
+
+async fn store_session(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    token_hash: &str,
+    session: &AdminSessionData,
+    ttl_secs: u64,
+) -> AppResult<()> {
+    let payload = serde_json::to_string(session)?;
+    let index_key = user_sessions_key(session.user_id);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let _: () = redis::pipe()
+        .set_ex(session_key(token_hash), payload, ttl_secs)
+        .sadd(&index_key, token_hash)
+        .expire(&index_key, secs_i64(config.admin_session_max_lifetime_secs))
+        .query_async(&mut conn)
+        .await?;
+    Ok(())
+}
+
+async fn load_session(
+    conn: &mut redis::aio::MultiplexedConnection,
+    token_hash: &str,
+) -> AppResult<Option<AdminSessionData>> {
+    let payload: Option<String> = conn.get(session_key(token_hash)).await?;
+    match payload {
+        Some(payload) => Ok(Some(serde_json::from_str(&payload)?)),
+        None => Ok(None),
+    }
+}
+
+async fn delete_stored_session(redis: &redis::Client, token_hash: &str) -> AppResult<()> {
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    if let Some(session) = load_session(&mut conn, token_hash).await? {
+        let _: usize = conn
+            .srem(user_sessions_key(session.user_id), token_hash)
+            .await?;
+    }
+    let _: usize = conn.del(session_key(token_hash)).await?;
+    Ok(())
+}
+
+/// Delete the session identified by a raw cookie token
+pub(crate) async fn delete_session(redis: &State<redis::Client>, token: &str) -> AppResult<()> {
+    delete_stored_session(redis, &hash_token(token)).await
+}
+
+/// Load every live session of a user as `(token_hash, session, ttl_secs)`,
+/// dropping index entries whose session has already expired
+async fn list_user_sessions(
+    redis: &redis::Client,
+    user_id: i64,
+) -> AppResult<Vec<(String, AdminSessionData, i64)>> {
+    let index_key = user_sessions_key(user_id);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let token_hashes: Vec<String> = conn.smembers(&index_key).await?;
+
+    let mut sessions = Vec::with_capacity(token_hashes.len());
+    for token_hash in token_hashes {
+        let session = load_session(&mut conn, &token_hash).await?;
+        let ttl_secs: i64 = conn.ttl(session_key(&token_hash)).await?;
+        match session {
+            Some(session) if ttl_secs > 0 => sessions.push((token_hash, session, ttl_secs)),
+            _ => {
+                let _: usize = conn.srem(&index_key, &token_hash).await?;
+            }
+        }
+    }
+
+    Ok(sessions)
+}
+
+/// Revoke every session and remember-me token of a user, e.g. after their
+/// password changed, keeping only the session behind `keep_token`.
+/// Returns the number of revoked sessions.
+pub(crate) async fn revoke_user_sessions(
+    redis: &redis::Client,
+    user_id: i64,
+    keep_token: Option<&str>,
+) -> AppResult<usize> {
+    let keep_token_hash = keep_token.map(hash_token);
+    let mut revoked = 0;
+    for (token_hash, _, _) in list_user_sessions(redis, user_id).await? {
+        if keep_token_hash.as_deref() != Some(token_hash.as_str()) {
+            delete_stored_session(redis, &token_hash).await?;
+            revoked += 1;
+        }
+    }
+    revoke_user_remember_me_tokens(redis, user_id).await?;
+    Ok(revoked)
+}
+
+/// Remove expired sessions from every user's session index.
+/// Redis expires the sessions themselves, but not the index entries.
+pub async fn prune_expired_sessions(redis: &redis::Client) -> AppResult<usize> {
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+
+    let mut index_keys: Vec<String> = Vec::new();
+    let mut keys = conn
+        .scan_match::<_, String>(format!("{USER_SESSIONS_PREFIX}*"))
+        .await?;
+    while let Some(key) = keys.next_item().await {
+        index_keys.push(key);
+    }
+    drop(keys);
+
+    let mut removed = 0;
+    for index_key in index_keys {
+        let token_hashes: Vec<String> = conn.smembers(&index_key).await?;
+        for token_hash in token_hashes {
+            let exists: bool = conn.exists(session_key(&token_hash)).await?;
+            if !exists {
+                let _: usize = conn.srem(&index_key, &token_hash).await?;
+                removed += 1;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+pub async fn start_admin_session(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    cookies: &CookieJar<'_>,
+    user_id: i64,
+    client_ip: Option<IpAddr>,
+    user_agent: UserAgent,
+) -> AppResult<()> {
+    let token = Uuid::new_v4().to_string();
+    let session = AdminSessionData {
+        id: Uuid::new_v4().simple().to_string(),
+        user_id,
+        ip_address: client_ip.map(|ip| ip.to_string()),
+        user_agent: user_agent.0,
+        created_at: unix_now(),
+    };
+    let ttl_secs = config
+        .admin_session_idle_secs
+        .min(config.admin_session_max_lifetime_secs);
+
+    store_session(redis, config, &hash_token(&token), &session, ttl_secs).await?;
+    cookies.add(session_cookie(config, token, ttl_secs));
+
+    Ok(())
+}
+
+/// Load the session behind the cookie and slide its expiry forward, so that
+/// active admins stay signed in until the absolute lifetime runs out.
+/// Returns the session together with its new TTL in seconds.
+pub(crate) async fn touch_session(
+    cookies: &CookieJar<'_>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    client_ip: Option<IpAddr>,
+    user_agent: &UserAgent,
+) -> AppResult<Option<(AdminSessionData, u64)>> {
+    let Some(token) = session_token(cookies, config) else {
+        return Ok(None);
+    };
+
+    let token_hash = hash_token(&token);
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let Some(session) = load_session(&mut conn, &token_hash).await? else {
+        return Ok(None);
+    };
+
+    let pinning = IpPinning::parse(&config.admin_session_ip_pinning);
+    if let Some(saved_ip) = &session.ip_address {
+        if client_ip.is_none() && pinning != IpPinning::Off {
+            debug!("Session has IP but request has none");
+            return Ok(None);
+        }
+        if !pinning.allows(saved_ip, client_ip) {
+            warn!(
+                "Admin session IP mismatch ({:?} pinning): {} vs {:?}",
+                pinning, saved_ip, client_ip
+            );
+            return Ok(None);
+        }
+    }
+
+    let binding = UserAgentBinding::parse(&config.admin_session_user_agent_binding);
+    if let Some(saved_user_agent) = &session.user_agent
+        && !binding.allows(saved_user_agent, user_agent.0.as_deref())
+    {
+        warn!(
+            "Admin session User-Agent mismatch ({:?} binding): {} vs {:?}",
+            binding, saved_user_agent, user_agent.0
+        );
+        return Ok(None);
+    }
+
+    let Some(ttl_secs) = session_ttl(
+        session.created_at,
+        unix_now(),
+        config.admin_session_idle_secs,
+        config.admin_session_max_lifetime_secs,
+    ) else {
+        info!("Admin session reached its maximum lifetime");
+        delete_stored_session(redis, &token_hash).await?;
+        clear_session_cookie(cookies, config);
+        return Ok(None);
+    };
+
+    let _: bool = conn
+        .expire(session_key(&token_hash), secs_i64(ttl_secs))
+        .await?;
+    cookies.add(session_cookie(config, token, ttl_secs));
+
+    Ok(Some((session, ttl_secs)))
+}
+
+fn timestamp_to_datetime(timestamp: i64) -> chrono::NaiveDateTime {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|datetime| datetime.naive_utc())
+        .unwrap_or_default()
+}
+
+fn to_session_dto(
+    token_hash: &str,
+    session: AdminSessionData,
+    ttl_secs: i64,
+    now: i64,
+    current_token_hash: Option<&str>,
+) -> AdminSessionDto {
+    AdminSessionDto {
+        id: session.id,
+        created_at: timestamp_to_datetime(session.created_at),
+        expires_at: timestamp_to_datetime(now.saturating_add(ttl_secs)),
+        ip_address: session.ip_address,
+        user_agent: session.user_agent,
+        current: current_token_hash == Some(token_hash),
+    }
+}
+
+/// List the signed-in admin's active sessions, newest first
+#[get("/admin/api/sessions")]
+pub async fn list_admin_sessions(
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<AdminSessionDto>>> {
+    let current_token_hash = session_token(cookies, config).map(|token| hash_token(&token));
+    let now = unix_now();
+
+    let mut sessions: Vec<AdminSessionDto> = list_user_sessions(redis, admin.id)
+        .await?
+        .into_iter()
+        .map(|(token_hash, session, ttl_secs)| {
+            to_session_dto(
+                &token_hash,
+                session,
+                ttl_secs,
+                now,
+                current_token_hash.as_deref(),
+            )
+        })
+        .collect();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+
+    Ok(Json(sessions))
+}
+
+/// Revoke one of the signed-in admin's sessions by its public id
+#[delete("/admin/api/sessions/<id>")]
+pub async fn revoke_admin_session(
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    admin: AdminUser,
+    id: &str,
+) -> AppResult<Status> {
+    let token_hash = list_user_sessions(redis, admin.id)
+        .await?
+        .into_iter()
+        .find(|(_, session, _)| !session.id.is_empty() && session.id == id)
+        .map(|(token_hash, _, _)| token_hash)
+        .ok_or(AppError::NotFound)?;
+
+    delete_stored_session(redis, &token_hash).await?;
+    if session_token(cookies, config).is_some_and(|token| hash_token(&token) == token_hash) {
+        clear_session_cookie(cookies, config);
+    }
+
+    info!("Admin '{}' revoked session {}", admin.username, id);
+    Ok(Status::Ok)
+}
+
+/// Log out everywhere: revoke all of the signed-in admin's sessions,
+/// including the current one, and their remember-me tokens
+#[delete("/admin/api/sessions")]
+pub async fn revoke_all_admin_sessions(
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    admin: AdminUser,
+) -> AppResult<Status> {
+    let revoked = revoke_user_sessions(redis, admin.id, None).await?;
+    clear_session_cookie(cookies, config);
+
+    info!(
+        "Admin '{}' revoked all {} sessions",
+        admin.username, revoked
+    );
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_ttl_slides_until_max_lifetime() {
+        let day = 60 * 60 * 24;
+        assert_eq!(session_ttl(0, 10, day, 7 * day), Some(day));
+        assert_eq!(session_ttl(0, 6 * day as i64, day, 7 * day), Some(day));
+        assert_eq!(session_ttl(0, 7 * day as i64 - 60, day, 7 * day), Some(60));
+        assert_eq!(session_ttl(0, 7 * day as i64, day, 7 * day), None);
+        assert_eq!(session_ttl(100, 50, day, 7 * day), Some(day));
+    }
+
+    fn cookie_config(overrides: serde_json::Value) -> AppConfig {
+        let mut config = serde_json::json!({
+            "database_url": "mysql://localhost/test",
+            "redis_url": "redis://localhost",
+        });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        serde_json::from_value(config).expect("valid config")
+    }
+
+    #[test]
+    fn test_session_cookie_defaults() {
+        let cookie = session_cookie(&cookie_config(serde_json::json!({})), "t".into(), 600);
+        assert_eq!(cookie.name(), "admin_auth");
+        assert_eq!(cookie.secure(), Some(false));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(cookie.max_age(), Some(rocket::time::Duration::seconds(600)));
+    }
+
+    #[test]
+    fn test_session_cookie_uses_configured_attributes() {
+        let config = cookie_config(serde_json::json!({
+            "admin_session_cookie_name": "__Host-admin",
+            "admin_session_cookie_secure": true,
+            "admin_session_cookie_same_site": "Strict",
+            "admin_session_cookie_max_age_secs": 60,
+        }));
+        let cookie = session_cookie(&config, "t".into(), 600);
+        assert_eq!(cookie.name(), "__Host-admin");
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        assert_eq!(cookie.max_age(), Some(rocket::time::Duration::seconds(60)));
+
+        let config = cookie_config(serde_json::json!({
+            "admin_session_cookie_max_age_secs": 0,
+        }));
+        assert_eq!(session_cookie(&config, "t".into(), 600).max_age(), None);
+    }
+
+    #[test]
+    fn test_token_hash_is_stable_sha256() {
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(session_key(&hash_token("abc")).ends_with(&hash_token("abc")));
+        assert!(!session_key(&hash_token("abc")).contains(":abc"));
+    }
+
+    #[test]
+    fn test_ip_pinning_modes() {
+        let ip = |value: &str| Some(value.parse::<IpAddr>().unwrap());
+
+        assert!(IpPinning::Strict.allows("198.51.100.7", ip("198.51.100.7")));
+        assert!(!IpPinning::Strict.allows("198.51.100.7", ip("198.51.100.8")));
+
+        assert!(IpPinning::Subnet.allows("198.51.100.7", ip("198.51.100.200")));
+        assert!(!IpPinning::Subnet.allows("198.51.100.7", ip("198.51.101.7")));
+        assert!(IpPinning::Subnet.allows("2001:db8:0:1::1", ip("2001:db8:0:1::ff")));
+        assert!(!IpPinning::Subnet.allows("2001:db8:0:1::1", ip("2001:db8:0:2::1")));
+
+        assert!(IpPinning::Off.allows("198.51.100.7", ip("203.0.113.1")));
+        assert!(IpPinning::Off.allows("198.51.100.7", None));
+        assert!(!IpPinning::Strict.allows("198.51.100.7", None));
+
+        assert_eq!(IpPinning::parse("Subnet"), IpPinning::Subnet);
+        assert_eq!(IpPinning::parse("bogus"), IpPinning::Strict);
+    }
+
+    #[test]
+    fn test_user_agent_binding_modes() {
+        let chrome_121 = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36";
+        let chrome_122 = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36";
+        let chrome_linux = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36";
+        let firefox =
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:123.0) Gecko/20100101 Firefox/123.0";
+
+        assert!(UserAgentBinding::Loose.allows(chrome_121, Some(chrome_122)));
+        assert!(!UserAgentBinding::Loose.allows(chrome_121, Some(chrome_linux)));
+        assert!(!UserAgentBinding::Loose.allows(chrome_121, Some(firefox)));
+        assert!(!UserAgentBinding::Loose.allows(chrome_121, Some("curl/8.0")));
+        assert!(!UserAgentBinding::Loose.allows(chrome_121, None));
+
+        assert!(UserAgentBinding::Strict.allows(chrome_121, Some(chrome_121)));
+        assert!(!UserAgentBinding::Strict.allows(chrome_121, Some(chrome_122)));
+        assert!(UserAgentBinding::Off.allows(chrome_121, None));
+
+        assert_eq!(UserAgentBinding::parse("Strict"), UserAgentBinding::Strict);
+        assert_eq!(UserAgentBinding::parse("bogus"), UserAgentBinding::Loose);
+    }
+
+    #[test]
+    fn test_legacy_session_payload_still_loads() {
+        let session: AdminSessionData =
+            serde_json::from_str(r#"{"user_id":7,"ip_address":"127.0.0.1"}"#).unwrap();
+        assert_eq!(session.user_id, 7);
+        assert!(session.id.is_empty());
+        assert!(session.user_agent.is_none());
+        assert!(session.created_at > 0);
+    }
+
+    #[test]
+    fn test_session_dto_marks_current_session() {
+        let session = AdminSessionData {
+            id: "abc".to_string(),
+            user_id: 1,
+            ip_address: Some("203.0.113.7".to_string()),
+            user_agent: Some("curl/8.0".to_string()),
+            created_at: 1_700_000_000,
+        };
+
+        let dto = to_session_dto("token", session.clone(), 60, 1_700_000_100, Some("token"));
+        assert!(dto.current);
+        assert_eq!(dto.id, "abc");
+        assert_eq!(dto.created_at.and_utc().timestamp(), 1_700_000_000);
+        assert_eq!(dto.expires_at.and_utc().timestamp(), 1_700_000_160);
+
+        let other = to_session_dto("token", session, 60, 1_700_000_100, Some("other"));
+        assert!(!other.current);
+    }
+}
@@ -0,0 +1,321 @@
+// Blog post tags
+
+use std::collections::HashMap;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::AsyncMysqlConnection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::info;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{AdminTagRequest, NewBlogPostTag, NewTag, Permission, Tag, TagUsage};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{blog_post_tags, tags};
+
+const MAX_TAG_LEN: usize = 64;
+const MAX_TAGS_PER_POST: usize = 20;
+
+/// URL-safe form of a tag name: lowercase letters and digits, with every
+/// other run of characters replaced by a single dash
+pub(crate) fn tag_slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Trimmed tag name and its slug, validated
+fn tag_fields(name: &str, slug: Option<&str>) -> AppResult<NewTag> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TAG_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Tag names must be between 1 and {MAX_TAG_LEN} characters."
+        )));
+    }
+    if name.contains(',') || name.chars().any(char::is_control) {
+        return Err(AppError::InvalidInput(
+            "Tag names must not contain commas or control characters.".to_string(),
+        ));
+    }
+
+    let slug = match slug.map(str::trim).filter(|slug| !slug.is_empty()) {
+        Some(slug) if tag_slug(slug) != slug => {
+            return Err(AppError::InvalidInput(
+                "Tag slugs may only contain lowercase letters, digits and single dashes."
+                    .to_string(),
+            ));
+        }
+        Some(slug) => slug.to_string(),
+        None => tag_slug(name),
+    };
+    if slug.is_empty() || slug.chars().count() > MAX_TAG_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Tag slugs must be between 1 and {MAX_TAG_LEN} characters."
+        )));
+    }
+
+    Ok(NewTag {
+        name: name.to_string(),
+        slug,
+    })
+}
+
+/// Parse the comma-separated tags of the blog forms, without duplicates
+pub(crate) fn parse_tag_list(raw: &str) -> AppResult<Vec<NewTag>> {
+    let mut parsed: Vec<NewTag> = Vec::new();
+    for name in raw.split(',').filter(|name| !name.trim().is_empty()) {
+        let tag = tag_fields(name, None)?;
+        if !parsed.iter().any(|known| known.slug == tag.slug) {
+            parsed.push(tag);
+        }
+    }
+    if parsed.len() > MAX_TAGS_PER_POST {
+        return Err(AppError::InvalidInput(format!(
+            "A post can have at most {MAX_TAGS_PER_POST} tags."
+        )));
+    }
+    Ok(parsed)
+}
+
+fn map_tag_write_error(error: diesel::result::Error) -> AppError {
+    match error {
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        ) => AppError::InvalidInput("A tag with this name or slug already exists.".to_string()),
+        other => AppError::from(other),
+    }
+}
+
+/// Replace the tags of a blog post, creating tags that do not exist yet.
+/// Run it inside a transaction.
+pub(crate) async fn set_post_tags(
+    conn: &mut AsyncMysqlConnection,
+    post_id: i64,
+    new_tags: &[NewTag],
+) -> Result<(), diesel::result::Error> {
+    diesel::delete(blog_post_tags::table.filter(blog_post_tags::blog_post_id.eq(post_id)))
+        .execute(conn)
+        .await?;
+    if new_tags.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_or_ignore_into(tags::table)
+        .values(new_tags)
+        .execute(conn)
+        .await?;
+
+    // A renamed tag keeps its slug, so match existing tags by either
+    let names: Vec<&str> = new_tags.iter().map(|tag| tag.name.as_str()).collect();
+    let slugs: Vec<&str> = new_tags.iter().map(|tag| tag.slug.as_str()).collect();
+    let mut tag_ids: Vec<i64> = tags::table
+        .filter(tags::slug.eq_any(&slugs).or(tags::name.eq_any(&names)))
+        .select(tags::id)
+        .load(conn)
+        .await?;
+    tag_ids.sort_unstable();
+    tag_ids.dedup();
+
+    let assignments: Vec<NewBlogPostTag> = tag_ids
+        .into_iter()
+        .map(|tag_id| NewBlogPostTag {
+            blog_post_id: post_id,
+            tag_id,
+        })
+        .collect();
+    diesel::insert_into(blog_post_tags::table)
+        .values(&assignments)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Tags of the given blog posts by post id, each sorted by name
+pub(crate) async fn load_post_tags(
+    conn: &mut AsyncMysqlConnection,
+    post_ids: &[i64],
+) -> Result<HashMap<i64, Vec<Tag>>, diesel::result::Error> {
+    let mut by_post: HashMap<i64, Vec<Tag>> = HashMap::new();
+    if post_ids.is_empty() {
+        return Ok(by_post);
+    }
+
+    let rows: Vec<(i64, Tag)> = blog_post_tags::table
+        .inner_join(tags::table)
+        .filter(blog_post_tags::blog_post_id.eq_any(post_ids))
+        .order(tags::name.asc())
+        .select((blog_post_tags::blog_post_id, Tag::as_select()))
+        .load(conn)
+        .await?;
+    for (post_id, tag) in rows {
+        by_post.entry(post_id).or_default().push(tag);
+    }
+    Ok(by_post)
+}
+
+async fn find_tag(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Tag> {
+    tags::table
+        .find(id)
+        .select(Tag::as_select())
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+/// All tags by name, with the number of posts using each
+#[get("/admin/api/tags")]
+pub async fn list_tags(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<TagUsage>>> {
+    admin.require(Permission::ManageContent)?;
+
+    let all_tags = tags::table
+        .order(tags::name.asc())
+        .select(Tag::as_select())
+        .load(&mut db)
+        .await?;
+    let counts: HashMap<i64, i64> = blog_post_tags::table
+        .group_by(blog_post_tags::tag_id)
+        .select((blog_post_tags::tag_id, diesel::dsl::count_star()))
+        .load::<(i64, i64)>(&mut db)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(Json(
+        all_tags
+            .into_iter()
+            .map(|tag| TagUsage {
+                post_count: counts.get(&tag.id).copied().unwrap_or(0),
+                tag,
+            })
+            .collect(),
+    ))
+}
+
+#[post("/admin/api/tags", format = "json", data = "<request>")]
+pub async fn create_tag(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminTagRequest>,
+) -> AppResult<Json<Tag>> {
+    admin.require(Permission::ManageContent)?;
+
+    let new_tag = tag_fields(&request.name, request.slug.as_deref())?;
+    diesel::insert_into(tags::table)
+        .values(&new_tag)
+        .execute(&mut db)
+        .await
+        .map_err(map_tag_write_error)?;
+
+    let tag = tags::table
+        .filter(tags::slug.eq(&new_tag.slug))
+        .select(Tag::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "tag.create", "tag", tag.id).await;
+    info!("Admin '{}' created tag '{}'", admin.username, tag.name);
+    Ok(Json(tag))
+}
+
+#[put("/admin/api/tags/<id>", format = "json", data = "<request>")]
+pub async fn update_tag(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminTagRequest>,
+) -> AppResult<Json<Tag>> {
+    admin.require(Permission::ManageContent)?;
+
+    let current = find_tag(&mut db, id).await?;
+    // Renaming keeps the slug, so links to the tag keep working
+    let fields = tag_fields(
+        &request.name,
+        Some(request.slug.as_deref().unwrap_or(&current.slug)),
+    )?;
+    diesel::update(tags::table.find(id))
+        .set((tags::name.eq(&fields.name), tags::slug.eq(&fields.slug)))
+        .execute(&mut db)
+        .await
+        .map_err(map_tag_write_error)?;
+
+    let tag = find_tag(&mut db, id).await?;
+    record_audit(&mut db, &admin, "tag.update", "tag", id).await;
+    info!("Admin '{}' updated tag {}", admin.username, id);
+    Ok(Json(tag))
+}
+
+/// Delete a tag and remove it from every post
+#[delete("/admin/api/tags/<id>")]
+pub async fn delete_tag(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let deleted = diesel::delete(tags::table.find(id))
+        .execute(&mut db)
+        .await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "tag.delete", "tag", id).await;
+    info!("Admin '{}' deleted tag {}", admin.username, id);
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_slug() {
+        assert_eq!(tag_slug(" Rust "), "rust");
+        assert_eq!(tag_slug("Zateplenie fasády"), "zateplenie-fasády");
+        assert_eq!(tag_slug("C++ / tipy!"), "c-tipy");
+        assert_eq!(tag_slug("--"), "");
+    }
+
+    #[test]
+    fn test_tag_fields() {
+        let tag = tag_fields(" Tipy a triky ", None).unwrap();
+        assert_eq!(tag.name, "Tipy a triky");
+        assert_eq!(tag.slug, "tipy-a-triky");
+        assert_eq!(
+            tag_fields("Rust", Some("rust-lang")).unwrap().slug,
+            "rust-lang"
+        );
+
+        assert!(tag_fields("  ", None).is_err());
+        assert!(tag_fields("!!!", None).is_err());
+        assert!(tag_fields("a,b", None).is_err());
+        assert!(tag_fields(&"a".repeat(MAX_TAG_LEN + 1), None).is_err());
+        assert!(tag_fields("Rust", Some("Rust Lang")).is_err());
+    }
+
+    #[test]
+    fn test_parse_tag_list() {
+        let tags = parse_tag_list(" Rust, tipy,,rust , ").unwrap();
+        let slugs: Vec<&str> = tags.iter().map(|tag| tag.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["rust", "tipy"]);
+        assert!(parse_tag_list("").unwrap().is_empty());
+
+        let too_many: Vec<String> = (0..=MAX_TAGS_PER_POST).map(|i| format!("t{i}")).collect();
+        assert!(parse_tag_list(&too_many.join(",")).is_err());
+    }
+}
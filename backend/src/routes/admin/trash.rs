@@ -0,0 +1,276 @@
+// Trash for deleted offers and blog posts: deleted content stays in the
+// trash until it is restored or deleted permanently
+
+use chrono::NaiveDateTime;
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::media::Media;
+use crate::models::{PaginatedTrash, Permission, TrashItem, TrashItemKind};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{blog_post_images, blog_posts, offer_images, offers};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+/// Kinds listed for a `kind` filter; both when omitted
+fn kind_filter(kind: Option<&str>) -> AppResult<Option<TrashItemKind>> {
+    match kind.map(str::trim).filter(|kind| !kind.is_empty()) {
+        None | Some("all") => Ok(None),
+        Some("offer") => Ok(Some(TrashItemKind::Offer)),
+        Some("blog_post") => Ok(Some(TrashItemKind::BlogPost)),
+        Some(_) => Err(AppError::InvalidInput(
+            "Kind must be one of: all, offer, blog_post.".to_string(),
+        )),
+    }
+}
+
+fn to_items(
+    kind: TrashItemKind,
+    rows: Vec<(i64, String, String, Option<NaiveDateTime>)>,
+) -> impl Iterator<Item = TrashItem> {
+    rows.into_iter()
+        .filter_map(move |(id, title, slug, deleted_at)| {
+            Some(TrashItem {
+                kind,
+                id,
+                title,
+                slug,
+                deleted_at: deleted_at?,
+            })
+        })
+}
+
+/// Offers and blog posts in the trash, most recently deleted first. Each
+/// kind is only listed while its feature is enabled.
+#[get("/admin/api/trash?<page>&<limit>&<kind>")]
+pub async fn list_trash(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    config: &State<AppConfig>,
+    page: Option<i64>,
+    limit: Option<i64>,
+    kind: Option<&str>,
+) -> AppResult<Json<PaginatedTrash>> {
+    admin.require(Permission::ManageContent)?;
+
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let kind = kind_filter(kind)?;
+
+    let mut items = Vec::new();
+    if config.features.offers && kind != Some(TrashItemKind::BlogPost) {
+        let rows = offers::table
+            .filter(offers::deleted_at.is_not_null())
+            .select((offers::id, offers::title, offers::slug, offers::deleted_at))
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error loading offers in the trash: {}", e);
+                AppError::from(e)
+            })?;
+        items.extend(to_items(TrashItemKind::Offer, rows));
+    }
+    if config.features.blog && kind != Some(TrashItemKind::Offer) {
+        let rows = blog_posts::table
+            .filter(blog_posts::deleted_at.is_not_null())
+            .select((
+                blog_posts::id,
+                blog_posts::title,
+                blog_posts::slug,
+                blog_posts::deleted_at,
+            ))
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error loading blog posts in the trash: {}", e);
+                AppError::from(e)
+            })?;
+        items.extend(to_items(TrashItemKind::BlogPost, rows));
+    }
+
+    items.sort_by(|a, b| {
+        b.deleted_at
+            .cmp(&a.deleted_at)
+            .then_with(|| b.id.cmp(&a.id))
+    });
+    let total = items.len() as i64;
+    let data = items
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .collect();
+
+    Ok(Json(PaginatedTrash {
+        data,
+        total,
+        page,
+        limit,
+    }))
+}
+
+#[post("/admin/api/trash/offers/<id>/restore")]
+pub async fn restore_trashed_offer(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let restored = diesel::update(
+        offers::table
+            .find(id)
+            .filter(offers::deleted_at.is_not_null()),
+    )
+    .set(offers::deleted_at.eq(None::<NaiveDateTime>))
+    .execute(&mut db)
+    .await
+    .map_err(|e| {
+        error!("Error restoring offer {}: {}", id, e);
+        AppError::from(e)
+    })?;
+    if restored == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "offer.restore", "offer", id).await;
+    info!("Offer {} restored from the trash", id);
+    Ok(Status::Ok)
+}
+
+/// Delete an offer in the trash for good, with its clicks and images
+#[delete("/admin/api/trash/offers/<id>")]
+pub async fn permanently_delete_offer(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    media: &State<Media>,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    // The image rows go with the offer; their data is released afterwards
+    let storage_keys: Vec<String> = offer_images::table
+        .filter(offer_images::offer_id.eq(id))
+        .select(offer_images::storage_key)
+        .load(&mut db)
+        .await?;
+    let deleted = diesel::delete(
+        offers::table
+            .find(id)
+            .filter(offers::deleted_at.is_not_null()),
+    )
+    .execute(&mut db)
+    .await
+    .map_err(|e| {
+        error!("Error permanently deleting offer {}: {}", id, e);
+        AppError::from(e)
+    })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    media.release_images(&mut db, &storage_keys).await;
+
+    record_audit(&mut db, &admin, "offer.permanent_delete", "offer", id).await;
+    info!("Offer {} permanently deleted", id);
+    Ok(Status::Ok)
+}
+
+#[post("/admin/api/trash/blog/<id>/restore")]
+pub async fn restore_trashed_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let restored = diesel::update(
+        blog_posts::table
+            .find(id)
+            .filter(blog_posts::deleted_at.is_not_null()),
+    )
+    .set(blog_posts::deleted_at.eq(None::<NaiveDateTime>))
+    .execute(&mut db)
+    .await
+    .map_err(|e| {
+        error!("Error restoring blog post {}: {}", id, e);
+        AppError::from(e)
+    })?;
+    if restored == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "blog_post.restore", "blog_post", id).await;
+    info!("Blog post {} restored from the trash", id);
+    Ok(Status::Ok)
+}
+
+/// Delete a post in the trash for good, with its tags, images and revisions
+#[delete("/admin/api/trash/blog/<id>")]
+pub async fn permanently_delete_blog_post(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    media: &State<Media>,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageContent)?;
+
+    let storage_keys: Vec<String> = blog_post_images::table
+        .filter(blog_post_images::blog_post_id.eq(id))
+        .select(blog_post_images::storage_key)
+        .load(&mut db)
+        .await?;
+    let deleted = diesel::delete(
+        blog_posts::table
+            .find(id)
+            .filter(blog_posts::deleted_at.is_not_null()),
+    )
+    .execute(&mut db)
+    .await
+    .map_err(|e| {
+        error!("Error permanently deleting blog post {}: {}", id, e);
+        AppError::from(e)
+    })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    media.release_images(&mut db, &storage_keys).await;
+
+    record_audit(
+        &mut db,
+        &admin,
+        "blog_post.permanent_delete",
+        "blog_post",
+        id,
+    )
+    .await;
+    info!("Blog post {} permanently deleted", id);
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_filter() {
+        assert_eq!(kind_filter(None).unwrap(), None);
+        assert_eq!(kind_filter(Some("all")).unwrap(), None);
+        assert_eq!(
+            kind_filter(Some("offer")).unwrap(),
+            Some(TrashItemKind::Offer)
+        );
+        assert_eq!(
+            kind_filter(Some(" blog_post ")).unwrap(),
+            Some(TrashItemKind::BlogPost)
+        );
+        assert!(kind_filter(Some("message")).is_err());
+    }
+}
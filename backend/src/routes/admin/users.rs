@@ -1,24 +1,30 @@
-use bcrypt::{DEFAULT_COST, hash};
 use rocket::State;
 use rocket::http::{CookieJar, Status};
 use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
-use std::net::SocketAddr;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::config::AppConfig;
 use crate::db::MessagesDB;
 use crate::error::{AppError, AppResult};
+use crate::mailer::Mailer;
 use crate::models::{
-    AdminAcceptInviteRequest, AdminCreateInviteRequest, AdminCreateUserRequest, AdminSetupRequest,
-    AdminUpdateUserRequest, AdminUser, AdminUserDto, AdminUserInvite, AdminUserInviteDto,
-    NewAdminUser, NewAdminUserInvite,
+    AdminAcceptInviteRequest, AdminChangePasswordRequest, AdminCreateInviteRequest,
+    AdminCreateUserRequest, AdminRole, AdminSetupRequest, AdminUpdateUserRequest, AdminUser,
+    AdminUserDto, AdminUserInvite, AdminUserInviteDto, NewAdminUser, NewAdminUserInvite,
+    Permission,
 };
-use crate::routes::admin::auth::{
-    get_authenticated_user_id, has_admin_users, is_admin_authenticated, start_admin_session,
+use crate::password::{hash_password, verify_password};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::{self, has_admin_users};
+use crate::routes::admin::sessions::{
+    UserAgent, revoke_user_sessions, session_token, start_admin_session,
 };
+use crate::routes::client_ip::ClientIp;
 use crate::schema::{admin_user_invites, admin_users};
+use crate::utils::validate_email;
 
 const INVITE_TTL_HOURS: i64 = 72;
 
@@ -26,6 +32,8 @@ fn to_user_dto(user: AdminUser) -> AdminUserDto {
     AdminUserDto {
         id: user.id,
         username: user.username,
+        role: user.role,
+        email: user.email,
         created_at: user.created_at,
         updated_at: user.updated_at,
     }
@@ -44,7 +52,52 @@ fn normalize_username(username: &str) -> AppResult<String> {
     Ok(trimmed.to_string())
 }
 
-fn validate_password(password: &str) -> AppResult<()> {
+/// Parse a requested role, defaulting to superadmin when none is given
+fn parse_role(role: Option<&str>) -> AppResult<AdminRole> {
+    match role.map(str::trim).filter(|role| !role.is_empty()) {
+        None => Ok(AdminRole::Superadmin),
+        Some(role) => AdminRole::parse(role).ok_or_else(|| {
+            AppError::InvalidInput(
+                "Role must be one of editor, moderator or superadmin.".to_string(),
+            )
+        }),
+    }
+}
+
+/// Refuse changes that would leave no superadmin to manage users
+async fn ensure_other_superadmin(
+    db: &mut Connection<MessagesDB>,
+    user: &AdminUser,
+) -> AppResult<()> {
+    if user.role() != Some(AdminRole::Superadmin) {
+        return Ok(());
+    }
+
+    let superadmins: i64 = admin_users::table
+        .filter(admin_users::role.eq(AdminRole::Superadmin.as_str()))
+        .count()
+        .get_result(db)
+        .await?;
+    if superadmins <= 1 {
+        return Err(AppError::InvalidInput(
+            "At least one superadmin must remain.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Optional contact address used for password reset emails
+fn normalize_email(email: Option<&str>) -> AppResult<Option<String>> {
+    match email.map(str::trim).filter(|email| !email.is_empty()) {
+        None => Ok(None),
+        Some(email) if validate_email(email) => Ok(Some(email.to_string())),
+        Some(_) => Err(AppError::InvalidInput(
+            "Email address is invalid.".to_string(),
+        )),
+    }
+}
+
+pub(crate) fn validate_password(password: &str) -> AppResult<()> {
     if password.len() < 8 {
         return Err(AppError::InvalidInput(
             "Password must be at least 8 characters long.".to_string(),
@@ -57,8 +110,11 @@ fn to_invite_dto(invite: AdminUserInvite) -> AdminUserInviteDto {
     AdminUserInviteDto {
         id: invite.id,
         username: invite.username,
+        role: invite.role,
         token: invite.token.clone(),
         invite_path: format!("/admin/invite?token={}", invite.token),
+        email: invite.email,
+        email_sent: false,
         expires_at: invite.expires_at,
         created_at: invite.created_at,
     }
@@ -88,8 +144,10 @@ fn map_user_write_error(error: diesel::result::Error) -> AppError {
 pub async fn admin_setup(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
     cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     setup: Json<AdminSetupRequest>,
 ) -> AppResult<Json<AdminUserDto>> {
     if has_admin_users(&mut db).await? {
@@ -101,10 +159,12 @@ pub async fn admin_setup(
     let username = normalize_username(&setup.username)?;
     validate_password(&setup.password)?;
 
-    let password_hash = hash(&setup.password, DEFAULT_COST)?;
+    let password_hash = hash_password(&setup.password)?;
     let new_user = NewAdminUser {
         username: username.clone(),
         password_hash,
+        role: AdminRole::Superadmin.as_str().to_string(),
+        email: None,
     };
 
     diesel::insert_into(admin_users::table)
@@ -119,7 +179,15 @@ pub async fn admin_setup(
         .first(&mut db)
         .await?;
 
-    start_admin_session(redis, cookies, created_user.id, remote_addr).await?;
+    start_admin_session(
+        redis,
+        config,
+        cookies,
+        created_user.id,
+        client_ip.0,
+        user_agent,
+    )
+    .await?;
     info!("Initial admin user '{}' created", created_user.username);
 
     Ok(Json(to_user_dto(created_user)))
@@ -128,13 +196,9 @@ pub async fn admin_setup(
 #[get("/admin/api/users/invites")]
 pub async fn list_admin_invites(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
 ) -> AppResult<Json<Vec<AdminUserInviteDto>>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageUsers)?;
 
     delete_expired_invites(&mut db).await?;
 
@@ -147,20 +211,63 @@ pub async fn list_admin_invites(
     Ok(Json(invites.into_iter().map(to_invite_dto).collect()))
 }
 
+/// Email the single-use signup link of a new invite. Returns whether it was
+/// sent; without SMTP the link is only returned to the inviting admin.
+async fn send_invite_email(
+    mailer: &Mailer,
+    public_base_url: &str,
+    invite: &AdminUserInvite,
+) -> bool {
+    let Some(email) = invite.email.as_deref() else {
+        return false;
+    };
+    if !mailer.is_enabled() {
+        warn!(
+            "Invite for '{}' not emailed, SMTP is not configured",
+            invite.username
+        );
+        return false;
+    }
+
+    let link = format!(
+        "{}/admin/invite?token={}",
+        public_base_url.trim_end_matches('/'),
+        invite.token
+    );
+    let body = format!(
+        "Hello {},\n\nyou have been invited to administer the website. Open the following \
+         link to set your password:\n\n{link}\n\nThe link can be used once and expires in \
+         {INVITE_TTL_HOURS} hours.\n",
+        invite.username
+    );
+    match mailer.send(email, "Admin account invitation", body).await {
+        Ok(()) => {
+            info!("Sent invite email to '{}'", invite.username);
+            true
+        }
+        Err(e) => {
+            error!(
+                "Failed to send invite email to '{}': {}",
+                invite.username, e
+            );
+            false
+        }
+    }
+}
+
 #[post("/admin/api/users/invites", format = "json", data = "<request>")]
 pub async fn create_admin_invite(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    config: &State<AppConfig>,
+    mailer: &State<Mailer>,
+    admin: auth::AdminUser,
     request: Json<AdminCreateInviteRequest>,
 ) -> AppResult<Json<AdminUserInviteDto>> {
-    let current_user_id = get_authenticated_user_id(cookies, &mut db, redis, remote_addr).await?;
-    let Some(current_user_id) = current_user_id else {
-        return Err(AppError::Unauthorized);
-    };
+    admin.require(Permission::ManageUsers)?;
 
     let username = normalize_username(&request.username)?;
+    let role = parse_role(request.role.as_deref())?;
+    let email = normalize_email(request.email.as_deref())?;
     delete_expired_invites(&mut db).await?;
 
     let existing_user: Option<i64> = admin_users::table
@@ -180,7 +287,9 @@ pub async fn create_admin_invite(
         username: username.clone(),
         token: token.clone(),
         expires_at: chrono::Utc::now().naive_utc() + chrono::Duration::hours(INVITE_TTL_HOURS),
-        created_by: Some(current_user_id),
+        created_by: Some(admin.id),
+        role: role.as_str().to_string(),
+        email,
     };
 
     diesel::insert_into(admin_user_invites::table)
@@ -195,25 +304,35 @@ pub async fn create_admin_invite(
         .first(&mut db)
         .await?;
 
-    Ok(Json(to_invite_dto(created_invite)))
+    record_audit(
+        &mut db,
+        &admin,
+        "invite.create",
+        "admin_user_invite",
+        created_invite.id,
+    )
+    .await;
+
+    let email_sent = send_invite_email(mailer, &config.public_base_url, &created_invite).await;
+    Ok(Json(AdminUserInviteDto {
+        email_sent,
+        ..to_invite_dto(created_invite)
+    }))
 }
 
 #[delete("/admin/api/users/invites/<id>")]
 pub async fn delete_admin_invite(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
     id: i64,
 ) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageUsers)?;
 
     diesel::delete(admin_user_invites::table.find(id))
         .execute(&mut db)
         .await?;
 
+    record_audit(&mut db, &admin, "invite.delete", "admin_user_invite", id).await;
     Ok(Status::Ok)
 }
 
@@ -238,8 +357,10 @@ pub async fn get_admin_invite_status(
 pub async fn accept_admin_invite(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
     cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
     request: Json<AdminAcceptInviteRequest>,
 ) -> AppResult<Json<AdminUserDto>> {
     delete_expired_invites(&mut db).await?;
@@ -254,7 +375,9 @@ pub async fn accept_admin_invite(
 
     let new_user = NewAdminUser {
         username: invite.username.clone(),
-        password_hash: hash(&request.password, DEFAULT_COST)?,
+        password_hash: hash_password(&request.password)?,
+        role: invite.role.clone(),
+        email: invite.email.clone(),
     };
 
     db.transaction(|conn| {
@@ -280,20 +403,24 @@ pub async fn accept_admin_invite(
         .first(&mut db)
         .await?;
 
-    start_admin_session(redis, cookies, created_user.id, remote_addr).await?;
+    start_admin_session(
+        redis,
+        config,
+        cookies,
+        created_user.id,
+        client_ip.0,
+        user_agent,
+    )
+    .await?;
     Ok(Json(to_user_dto(created_user)))
 }
 
 #[get("/admin/api/users")]
 pub async fn list_admin_users(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
 ) -> AppResult<Json<Vec<AdminUserDto>>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageUsers)?;
 
     let users = admin_users::table
         .order(admin_users::created_at.asc())
@@ -307,21 +434,20 @@ pub async fn list_admin_users(
 #[post("/admin/api/users", format = "json", data = "<request>")]
 pub async fn create_admin_user(
     mut db: Connection<MessagesDB>,
-    redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
     request: Json<AdminCreateUserRequest>,
 ) -> AppResult<Json<AdminUserDto>> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageUsers)?;
 
     let username = normalize_username(&request.username)?;
     validate_password(&request.password)?;
+    let role = parse_role(request.role.as_deref())?;
 
     let new_user = NewAdminUser {
         username: username.clone(),
-        password_hash: hash(&request.password, DEFAULT_COST)?,
+        password_hash: hash_password(&request.password)?,
+        role: role.as_str().to_string(),
+        email: normalize_email(request.email.as_deref())?,
     };
 
     diesel::insert_into(admin_users::table)
@@ -336,6 +462,14 @@ pub async fn create_admin_user(
         .first(&mut db)
         .await?;
 
+    record_audit(
+        &mut db,
+        &admin,
+        "admin_user.create",
+        "admin_user",
+        created_user.id,
+    )
+    .await;
     Ok(Json(to_user_dto(created_user)))
 }
 
@@ -343,14 +477,13 @@ pub async fn create_admin_user(
 pub async fn update_admin_user(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
+    config: &State<AppConfig>,
     cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
     id: i64,
     request: Json<AdminUpdateUserRequest>,
 ) -> AppResult<Status> {
-    if !is_admin_authenticated(cookies, &mut db, redis, remote_addr).await? {
-        return Err(AppError::Unauthorized);
-    }
+    admin.require(Permission::ManageUsers)?;
 
     let username = normalize_username(&request.username)?;
     let existing: AdminUser = admin_users::table
@@ -360,28 +493,57 @@ pub async fn update_admin_user(
         .await
         .map_err(|_| AppError::NotFound)?;
 
+    // Keep the current role unless a new one is given
+    let role = match request.role.as_deref() {
+        Some(role) => parse_role(Some(role))?,
+        None => existing.role().unwrap_or(AdminRole::Superadmin),
+    };
+    if role != AdminRole::Superadmin {
+        ensure_other_superadmin(&mut db, &existing).await?;
+    }
+
+    let email = match request.email.as_deref() {
+        Some(email) => normalize_email(Some(email))?,
+        None => existing.email.clone(),
+    };
+
     match request.password.as_deref().map(str::trim) {
         Some(password) if !password.is_empty() => {
             validate_password(password)?;
-            let password_hash = hash(password, DEFAULT_COST)?;
+            let password_hash = hash_password(password)?;
             diesel::update(admin_users::table.find(id))
                 .set((
                     admin_users::username.eq(&username),
                     admin_users::password_hash.eq(password_hash),
+                    admin_users::role.eq(role.as_str()),
+                    admin_users::email.eq(&email),
                 ))
                 .execute(&mut db)
                 .await
                 .map_err(map_user_write_error)?;
+
+            // Sign the user out everywhere, except an admin editing themselves
+            let keep_token = session_token(cookies, config).filter(|_| admin.id == id);
+            let revoked = revoke_user_sessions(redis, id, keep_token.as_deref()).await?;
+            info!(
+                "Revoked {} sessions of admin user '{}' after a password change",
+                revoked, existing.username
+            );
         }
         _ => {
             diesel::update(admin_users::table.find(id))
-                .set(admin_users::username.eq(&username))
+                .set((
+                    admin_users::username.eq(&username),
+                    admin_users::role.eq(role.as_str()),
+                    admin_users::email.eq(&email),
+                ))
                 .execute(&mut db)
                 .await
                 .map_err(map_user_write_error)?;
         }
     }
 
+    record_audit(&mut db, &admin, "admin_user.update", "admin_user", id).await;
     info!("Admin user '{}' updated", existing.username);
     Ok(Status::Ok)
 }
@@ -390,16 +552,12 @@ pub async fn update_admin_user(
 pub async fn delete_admin_user(
     mut db: Connection<MessagesDB>,
     redis: &State<redis::Client>,
-    cookies: &CookieJar<'_>,
-    remote_addr: Option<SocketAddr>,
+    admin: auth::AdminUser,
     id: i64,
 ) -> AppResult<Status> {
-    let current_user_id = get_authenticated_user_id(cookies, &mut db, redis, remote_addr).await?;
-    let Some(current_user_id) = current_user_id else {
-        return Err(AppError::Unauthorized);
-    };
+    admin.require(Permission::ManageUsers)?;
 
-    if current_user_id == id {
+    if admin.id == id {
         return Err(AppError::InvalidInput(
             "You cannot delete the currently signed-in user.".to_string(),
         ));
@@ -412,6 +570,14 @@ pub async fn delete_admin_user(
         ));
     }
 
+    let target: AdminUser = admin_users::table
+        .find(id)
+        .select(AdminUser::as_select())
+        .first(&mut db)
+        .await
+        .map_err(|_| AppError::NotFound)?;
+    ensure_other_superadmin(&mut db, &target).await?;
+
     diesel::delete(admin_users::table.find(id))
         .execute(&mut db)
         .await
@@ -419,6 +585,59 @@ pub async fn delete_admin_user(
             error!("Error deleting admin user {}: {}", id, e);
             AppError::from(e)
         })?;
+    revoke_user_sessions(redis, id, None).await?;
+
+    record_audit(&mut db, &admin, "admin_user.delete", "admin_user", id).await;
+    Ok(Status::Ok)
+}
+
+/// Change the signed-in admin's own password. Available to every role.
+/// Signs the admin out of every other session.
+#[post("/admin/api/password", format = "json", data = "<request>")]
+pub async fn change_admin_password(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    admin: auth::AdminUser,
+    request: Json<AdminChangePasswordRequest>,
+) -> AppResult<Status> {
+    if !verify_password(&request.current_password, &admin.password_hash) {
+        warn!(
+            "Admin '{}' entered a wrong current password",
+            admin.username
+        );
+        return Err(AppError::InvalidInput(
+            "Current password is incorrect.".to_string(),
+        ));
+    }
+
+    validate_password(&request.new_password)?;
+    if request.new_password == request.current_password {
+        return Err(AppError::InvalidInput(
+            "New password must differ from the current one.".to_string(),
+        ));
+    }
 
+    let password_hash = hash_password(&request.new_password)?;
+    diesel::update(admin_users::table.find(admin.id))
+        .set(admin_users::password_hash.eq(password_hash))
+        .execute(&mut db)
+        .await?;
+    let revoked =
+        revoke_user_sessions(redis, admin.id, session_token(cookies, config).as_deref()).await?;
+
+    record_audit(
+        &mut db,
+        &admin,
+        "admin_user.change_password",
+        "admin_user",
+        admin.id,
+    )
+    .await;
+    info!(
+        "Admin '{}' changed their password, revoked {} other sessions",
+        admin.username, revoked
+    );
     Ok(Status::Ok)
 }
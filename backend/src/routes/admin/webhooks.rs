@@ -0,0 +1,242 @@
+// Webhook registrations and their delivery log
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AdminWebhookRequest, NewWebhook, PaginatedWebhookDeliveries, Permission, Webhook,
+    WebhookCreatedResponse, WebhookDelivery, WebhookDto,
+};
+use crate::routes::admin::audit::record_audit;
+use crate::routes::admin::auth::AdminUser;
+use crate::schema::{webhook_deliveries, webhooks};
+use crate::utils::is_http_url;
+use crate::webhooks::WEBHOOK_EVENTS;
+
+const MAX_WEBHOOK_URL_LEN: usize = 2048;
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+fn validate_url(url: &str) -> AppResult<String> {
+    let url = url.trim();
+    if !is_http_url(url) || url.len() > MAX_WEBHOOK_URL_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Webhook URL must be an http(s) URL of at most {MAX_WEBHOOK_URL_LEN} characters."
+        )));
+    }
+    Ok(url.to_string())
+}
+
+/// Validate subscribed events, keeping them in a stable order
+fn validate_events(events: &[String]) -> AppResult<String> {
+    let mut names = Vec::new();
+    for event in events {
+        let event = event.trim();
+        if !WEBHOOK_EVENTS.contains(&event) {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown webhook event '{event}'. Known events: {}.",
+                WEBHOOK_EVENTS.join(", ")
+            )));
+        }
+        names.push(event);
+    }
+    if names.is_empty() {
+        return Err(AppError::InvalidInput(
+            "A webhook must subscribe to at least one event.".to_string(),
+        ));
+    }
+
+    names.sort_unstable();
+    names.dedup();
+    Ok(names.join(","))
+}
+
+async fn find_webhook(db: &mut Connection<MessagesDB>, id: i64) -> AppResult<Webhook> {
+    webhooks::table
+        .find(id)
+        .select(Webhook::as_select())
+        .first(db)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+#[get("/admin/api/webhooks")]
+pub async fn list_webhooks(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+) -> AppResult<Json<Vec<WebhookDto>>> {
+    admin.require(Permission::ManageUsers)?;
+
+    let webhooks = webhooks::table
+        .order(webhooks::id.asc())
+        .select(Webhook::as_select())
+        .load(&mut db)
+        .await?;
+    Ok(Json(webhooks.into_iter().map(WebhookDto::from).collect()))
+}
+
+/// Register a webhook. The signing secret is only returned here.
+#[post("/admin/api/webhooks", format = "json", data = "<request>")]
+pub async fn create_webhook(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    request: Json<AdminWebhookRequest>,
+) -> AppResult<Json<WebhookCreatedResponse>> {
+    admin.require(Permission::ManageUsers)?;
+
+    let new_webhook = NewWebhook {
+        url: validate_url(&request.url)?,
+        secret: format!("whsec_{}", Uuid::new_v4().simple()),
+        events: validate_events(&request.events)?,
+        is_active: request.is_active.unwrap_or(true),
+    };
+    diesel::insert_into(webhooks::table)
+        .values(&new_webhook)
+        .execute(&mut db)
+        .await?;
+
+    let webhook = webhooks::table
+        .filter(webhooks::secret.eq(&new_webhook.secret))
+        .select(Webhook::as_select())
+        .first(&mut db)
+        .await?;
+
+    record_audit(&mut db, &admin, "webhook.create", "webhook", webhook.id).await;
+    info!(
+        "Admin '{}' registered webhook {} for {}",
+        admin.username, webhook.id, webhook.events
+    );
+    Ok(Json(WebhookCreatedResponse {
+        secret: new_webhook.secret,
+        webhook: webhook.into(),
+    }))
+}
+
+#[put("/admin/api/webhooks/<id>", format = "json", data = "<request>")]
+pub async fn update_webhook(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    request: Json<AdminWebhookRequest>,
+) -> AppResult<Json<WebhookDto>> {
+    admin.require(Permission::ManageUsers)?;
+
+    let url = validate_url(&request.url)?;
+    let events = validate_events(&request.events)?;
+    let current = find_webhook(&mut db, id).await?;
+    diesel::update(webhooks::table.find(id))
+        .set((
+            webhooks::url.eq(url),
+            webhooks::events.eq(events),
+            webhooks::is_active.eq(request.is_active.unwrap_or(current.is_active)),
+            webhooks::updated_at.eq(chrono::Utc::now().naive_utc()),
+        ))
+        .execute(&mut db)
+        .await?;
+
+    let webhook = find_webhook(&mut db, id).await?;
+    record_audit(&mut db, &admin, "webhook.update", "webhook", id).await;
+    info!("Admin '{}' updated webhook {}", admin.username, id);
+    Ok(Json(webhook.into()))
+}
+
+/// Delete a webhook together with its delivery log
+#[delete("/admin/api/webhooks/<id>")]
+pub async fn delete_webhook(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+) -> AppResult<Status> {
+    admin.require(Permission::ManageUsers)?;
+
+    let deleted = diesel::delete(webhooks::table.find(id))
+        .execute(&mut db)
+        .await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_audit(&mut db, &admin, "webhook.delete", "webhook", id).await;
+    info!("Admin '{}' deleted webhook {}", admin.username, id);
+    Ok(Status::Ok)
+}
+
+/// Delivery log of a webhook, newest first
+#[get("/admin/api/webhooks/<id>/deliveries?<page>&<limit>")]
+pub async fn list_webhook_deliveries(
+    mut db: Connection<MessagesDB>,
+    admin: AdminUser,
+    id: i64,
+    page: Option<i64>,
+    limit: Option<i64>,
+) -> AppResult<Json<PaginatedWebhookDeliveries>> {
+    admin.require(Permission::ManageUsers)?;
+
+    find_webhook(&mut db, id).await?;
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let total: i64 = webhook_deliveries::table
+        .filter(webhook_deliveries::webhook_id.eq(id))
+        .count()
+        .get_result(&mut db)
+        .await?;
+    let data = webhook_deliveries::table
+        .filter(webhook_deliveries::webhook_id.eq(id))
+        .order(webhook_deliveries::id.desc())
+        .limit(limit)
+        .offset((page - 1) * limit)
+        .select(WebhookDelivery::as_select())
+        .load(&mut db)
+        .await?;
+
+    Ok(Json(PaginatedWebhookDeliveries {
+        data,
+        total,
+        page,
+        limit,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert_eq!(
+            validate_url(" https://hooks.example.com/kerdik ").unwrap(),
+            "https://hooks.example.com/kerdik"
+        );
+        assert!(validate_url("http://localhost:9000").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("https://").is_err());
+        assert!(validate_url("https://exa mple.com").is_err());
+        assert!(validate_url(&format!("https://example.com/{}", "a".repeat(2048))).is_err());
+    }
+
+    #[test]
+    fn test_validate_events() {
+        assert_eq!(
+            validate_events(&events(&[
+                "offer.created",
+                "message.created",
+                "offer.created"
+            ]))
+            .unwrap(),
+            "message.created,offer.created"
+        );
+        assert!(validate_events(&[]).is_err());
+        assert!(validate_events(&events(&["message.deleted"])).is_err());
+    }
+}
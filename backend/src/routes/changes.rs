@@ -0,0 +1,139 @@
+// Content change summary for client-side cache coordination
+
+use chrono::{NaiveDateTime, Utc};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::routes::admin::blog::publicly_visible;
+use crate::routes::admin::offers::currently_active;
+use crate::schema::{blog_posts, offers};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct EntityChanges {
+    pub last_modified: Option<NaiveDateTime>,
+    pub count: i64,
+}
+
+/// Cheap fingerprint of the public content lists. Clients compare it with
+/// the previous response and skip refetching when nothing changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ContentChanges {
+    pub last_modified: Option<NaiveDateTime>,
+    pub offers: EntityChanges,
+    pub blog_posts: EntityChanges,
+}
+
+impl ContentChanges {
+    pub fn new(offers: EntityChanges, blog_posts: EntityChanges) -> Self {
+        ContentChanges {
+            last_modified: offers.last_modified.max(blog_posts.last_modified),
+            offers,
+            blog_posts,
+        }
+    }
+}
+
+#[get("/api/changes")]
+pub async fn get_changes(mut db: Connection<MessagesDB>) -> AppResult<Json<ContentChanges>> {
+    let (offers_modified, offers_count): (Option<NaiveDateTime>, i64) = offers::table
+        .filter(currently_active(Utc::now().naive_utc()))
+        .select((
+            diesel::dsl::max(offers::updated_at),
+            diesel::dsl::count_star(),
+        ))
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error summarizing offer changes: {}", e);
+            AppError::from(e)
+        })?;
+
+    let (posts_modified, posts_count): (Option<NaiveDateTime>, i64) = blog_posts::table
+        .filter(publicly_visible())
+        .select((
+            diesel::dsl::max(blog_posts::updated_at),
+            diesel::dsl::count_star(),
+        ))
+        .first(&mut db)
+        .await
+        .map_err(|e| {
+            error!("Error summarizing blog post changes: {}", e);
+            AppError::from(e)
+        })?;
+
+    Ok(Json(ContentChanges::new(
+        EntityChanges {
+            last_modified: offers_modified,
+            count: offers_count,
+        },
+        EntityChanges {
+            last_modified: posts_modified,
+            count: posts_count,
+        },
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").expect("Failed to parse datetime")
+    }
+
+    fn entity(last_modified: Option<&str>, count: i64) -> EntityChanges {
+        EntityChanges {
+            last_modified: last_modified.map(timestamp),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_content_changes_uses_latest_timestamp() {
+        let changes = ContentChanges::new(
+            entity(Some("2024-01-01 12:00:00"), 3),
+            entity(Some("2024-02-01 08:30:00"), 5),
+        );
+
+        assert_eq!(
+            changes.last_modified,
+            Some(timestamp("2024-02-01 08:30:00"))
+        );
+        assert_eq!(changes.offers.count, 3);
+        assert_eq!(changes.blog_posts.count, 5);
+    }
+
+    #[test]
+    fn test_content_changes_advances_after_update() {
+        let before = ContentChanges::new(
+            entity(Some("2024-01-01 12:00:00"), 3),
+            entity(Some("2024-01-02 12:00:00"), 5),
+        );
+        // An offer is edited: its updated_at moves past everything else.
+        let after = ContentChanges::new(
+            entity(Some("2024-01-03 09:00:00"), 3),
+            entity(Some("2024-01-02 12:00:00"), 5),
+        );
+
+        assert!(after.last_modified > before.last_modified);
+    }
+
+    #[test]
+    fn test_content_changes_with_empty_tables() {
+        let changes = ContentChanges::new(entity(None, 0), entity(Some("2024-01-01 12:00:00"), 1));
+        assert_eq!(
+            changes.last_modified,
+            Some(timestamp("2024-01-01 12:00:00"))
+        );
+
+        let empty = ContentChanges::new(entity(None, 0), entity(None, 0));
+        assert_eq!(empty.last_modified, None);
+    }
+}
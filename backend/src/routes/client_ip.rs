@@ -0,0 +1,133 @@
+// Client IP resolution that is aware of trusted reverse proxies
+
+use rocket::request::{FromRequest, Outcome, Request};
+use std::net::IpAddr;
+
+use crate::config::AppConfig;
+use crate::utils::ip_in_list;
+
+/// The address of the client that made the request. Forwarding headers
+/// (`X-Forwarded-For`, `X-Real-IP`) are only honoured when the connection
+/// comes from one of the configured trusted proxies, so clients cannot
+/// spoof their address by sending the headers themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+fn resolve_client_ip(
+    remote: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    real_ip: Option<&str>,
+    trusted_proxies: &str,
+) -> Option<IpAddr> {
+    let remote = remote?;
+    if !ip_in_list(trusted_proxies, remote) {
+        return Some(remote);
+    }
+
+    if let Some(forwarded_for) = forwarded_for {
+        let hops: Vec<IpAddr> = forwarded_for
+            .split(',')
+            .filter_map(|hop| hop.trim().parse().ok())
+            .collect();
+
+        // Proxies append the address they received the request from, so the
+        // client is the right-most hop that is not a trusted proxy itself
+        if let Some(client) = hops
+            .iter()
+            .rev()
+            .find(|hop| !ip_in_list(trusted_proxies, **hop))
+            .or(hops.first())
+        {
+            return Some(*client);
+        }
+    }
+
+    real_ip
+        .and_then(|value| value.trim().parse().ok())
+        .or(Some(remote))
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientIp {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let remote = req.remote().map(|addr| addr.ip());
+        let trusted_proxies = req
+            .rocket()
+            .state::<AppConfig>()
+            .map(|config| config.trusted_proxies.as_str())
+            .unwrap_or_default();
+
+        Outcome::Success(ClientIp(resolve_client_ip(
+            remote,
+            req.headers().get_one("X-Forwarded-For"),
+            req.headers().get_one("X-Real-IP"),
+            trusted_proxies,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn test_headers_ignored_from_untrusted_peer() {
+        assert_eq!(
+            resolve_client_ip(
+                ip("203.0.113.7"),
+                Some("198.51.100.1"),
+                Some("198.51.100.2"),
+                "10.0.0.0/8"
+            ),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.2"), Some("198.51.100.1"), None, ""),
+            ip("10.0.0.2")
+        );
+    }
+
+    #[test]
+    fn test_forwarded_for_from_trusted_proxy() {
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.2"), Some("198.51.100.1"), None, "10.0.0.0/8"),
+            ip("198.51.100.1")
+        );
+        // A client-supplied header is prepended to, never replaced
+        assert_eq!(
+            resolve_client_ip(
+                ip("10.0.0.2"),
+                Some("1.2.3.4, 198.51.100.1, 10.0.0.3"),
+                None,
+                "10.0.0.0/8"
+            ),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.2"), Some("10.0.0.9"), None, "10.0.0.0/8"),
+            ip("10.0.0.9")
+        );
+    }
+
+    #[test]
+    fn test_real_ip_and_fallback_from_trusted_proxy() {
+        assert_eq!(
+            resolve_client_ip(ip("127.0.0.1"), None, Some("198.51.100.1"), "127.0.0.1"),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("127.0.0.1"), Some("garbage"), Some("nope"), "127.0.0.1"),
+            ip("127.0.0.1")
+        );
+        assert_eq!(
+            resolve_client_ip(None, Some("198.51.100.1"), None, "*"),
+            None
+        );
+    }
+}
@@ -1,69 +1,202 @@
-// Contact form submission route handler
+// Contact form submission route handlers
 
+use rocket::State;
 use rocket::form::Form;
+use rocket::fs::TempFile;
 use rocket::http::Status;
+use rocket::response::Redirect;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
 use rocket_db_pools::Connection;
 use rocket_db_pools::diesel::prelude::*;
 use tracing::{debug, error, warn};
 
-use crate::db::MessagesDB;
+use crate::captcha::{CaptchaSettings, CaptchaVerifier};
+use crate::config::AppConfig;
+use crate::db::{MessagesDB, last_insert_id};
 use crate::error::{AppError, AppResult};
-use crate::models::{ContactMessage, ContactMessageForm};
-use crate::schema::messages;
-use crate::utils::{validate_email, validate_not_empty};
+use crate::mailer::Mailer;
+use crate::models::{
+    ContactFieldError, ContactMessage, ContactMessageForm, ContactMessageMultipart,
+    ContactRequiredFields, ContactSubmitResponse, Message, NewMessageAttachment,
+};
+use crate::notifier::ChatNotifier;
+use crate::routes::admin::email_templates::send_contact_auto_reply;
+use crate::routes::client_ip::ClientIp;
+use crate::routes::contact_limit::ensure_contact_allowed;
+use crate::schema::{message_attachments, messages, offers};
+use crate::spam::{SpamInput, SpamScorer};
+use crate::utils::{attachment_type, read_upload};
+use crate::webhooks::enqueue_webhook_event;
 
-/// Handle contact form submission
-#[post("/contact/message", data = "<form>")]
-pub async fn submit_message(
-    mut db: Connection<MessagesDB>,
-    form: Form<ContactMessageForm>,
-) -> AppResult<Status> {
-    let data = form.into_inner();
-
-    // Check honeypot field to detect bots
-    if data.is_bot() {
-        warn!("Bot detected in contact form submission");
-        return Err(AppError::InvalidInput(
-            "Bot submission rejected".to_string(),
-        ));
+/// How far back identical messages count as duplicates
+const DUPLICATE_WINDOW_HOURS: i64 = 24;
+/// Longest kept attachment file name, without the extension
+const MAX_ATTACHMENT_NAME_LEN: usize = 100;
+
+/// An uploaded attachment that passed validation
+struct Attachment {
+    filename: String,
+    content_type: &'static str,
+    data: Vec<u8>,
+}
+
+fn attachment_error(message: &'static str) -> ContactFieldError {
+    ContactFieldError {
+        field: "attachment",
+        message,
     }
+}
 
-    // Validate inputs
-    if !validate_not_empty(&data.name) {
-        debug!("Contact form validation failed: empty name");
-        return Err(AppError::InvalidInput("Name is required".to_string()));
+/// Check the size and type of an uploaded attachment. The stored file name
+/// is the sanitized upload name with an extension matching the detected type.
+async fn read_attachment(
+    config: &AppConfig,
+    file: Option<TempFile<'_>>,
+) -> AppResult<Result<Option<Attachment>, ContactFieldError>> {
+    let Some(file) = file else {
+        return Ok(Ok(None));
+    };
+    if config.contact_attachment_max_bytes == 0 {
+        return Ok(Err(attachment_error("Attachments are not accepted")));
+    }
+    if file.len() > config.contact_attachment_max_bytes {
+        return Ok(Err(attachment_error("Attachment is too large")));
     }
 
-    if !validate_not_empty(&data.message) {
-        debug!("Contact form validation failed: empty message");
-        return Err(AppError::InvalidInput("Message is required".to_string()));
+    let data = read_upload(&file).await?;
+    let Some((content_type, extension)) = attachment_type(&data) else {
+        return Ok(Err(attachment_error("Attachment type is not allowed")));
+    };
+    let stem: String = file
+        .name()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("attachment")
+        .chars()
+        .take(MAX_ATTACHMENT_NAME_LEN)
+        .collect();
+
+    Ok(Ok(Some(Attachment {
+        filename: format!("{stem}.{extension}"),
+        content_type,
+        data,
+    })))
+}
+
+/// Append a query parameter to a configured path that may already have some
+fn with_query(path: &str, key: &str, value: &str) -> String {
+    let separator = if path.contains('?') { '&' } else { '?' };
+    format!("{path}{separator}{key}={value}")
+}
+
+/// Redirect target for the outcome of a form submission:
+/// `CONTACT_SUCCESS_PATH` with `sent=1`, or `CONTACT_ERROR_PATH` with `error=`
+/// set to the first invalid field, `rejected`, `captcha`, `rate_limited` or
+/// `server`
+fn contact_redirect_target(config: &AppConfig, error: Option<&str>) -> String {
+    match error {
+        Some(error) => with_query(&config.contact_error_path, "error", error),
+        None => with_query(&config.contact_success_path, "sent", "1"),
     }
+}
 
-    if !validate_email(&data.email) {
-        debug!("Contact form validation failed: invalid email");
-        return Err(AppError::InvalidInput(
-            "Valid email is required".to_string(),
-        ));
+/// Error code passed to the error page when a submission fails on our side
+fn failure_code(error: &AppError) -> &'static str {
+    match error {
+        AppError::TooManyRequests { .. } => "rate_limited",
+        _ => "server",
     }
+}
+
+/// Score a submission, counting identical messages received recently
+async fn spam_score(
+    db: &mut Connection<MessagesDB>,
+    scorer: &SpamScorer,
+    data: &ContactMessageForm,
+) -> AppResult<u32> {
+    let now = chrono::Utc::now();
+    let recent_duplicates: i64 = messages::table
+        .filter(messages::message.eq(&data.message))
+        .filter(
+            messages::created_at
+                .gt((now - chrono::Duration::hours(DUPLICATE_WINDOW_HOURS)).naive_utc()),
+        )
+        .count()
+        .get_result(db)
+        .await?;
+
+    Ok(scorer.score(&SpamInput {
+        form: data,
+        received_at_ms: now.timestamp_millis(),
+        recent_duplicates,
+    }))
+}
+
+/// Check that the offer an inquiry is about exists and is not in the trash
+async fn offer_error(
+    db: &mut Connection<MessagesDB>,
+    data: &ContactMessageForm,
+) -> AppResult<Option<ContactFieldError>> {
+    let Some(offer_id) = data.offer_id else {
+        return Ok(None);
+    };
+    let found: i64 = offers::table
+        .find(offer_id)
+        .filter(offers::deleted_at.is_null())
+        .count()
+        .get_result(db)
+        .await?;
+    Ok((found == 0).then_some(ContactFieldError {
+        field: "offer_id",
+        message: "Offer does not exist",
+    }))
+}
 
-    // Insert message into database
+/// Store the message with its attachment and acknowledge it to the sender
+async fn save_message(
+    db: &mut Connection<MessagesDB>,
+    mailer: &Mailer,
+    notifier: &ChatNotifier,
+    data: ContactMessageForm,
+    spam_score: u32,
+    attachment: Option<Attachment>,
+) -> AppResult<()> {
+    let reply_to = data.clone();
     let result = db
         .transaction(|mut conn| {
             Box::pin(async move {
                 diesel::insert_into(messages::table)
-                    .values(ContactMessage::from(data))
+                    .values(ContactMessage::from_form(data, spam_score))
                     .execute(&mut conn)
                     .await?;
+                let message_id: u64 = diesel::select(last_insert_id())
+                    .get_result(&mut conn)
+                    .await?;
+
+                if let Some(attachment) = attachment {
+                    diesel::insert_into(message_attachments::table)
+                        .values(NewMessageAttachment {
+                            message_id: message_id as i64,
+                            filename: attachment.filename,
+                            content_type: attachment.content_type.to_string(),
+                            size_bytes: attachment.data.len() as i64,
+                            data: attachment.data,
+                        })
+                        .execute(&mut conn)
+                        .await?;
+                }
 
-                Ok::<_, diesel::result::Error>(())
+                Ok::<_, diesel::result::Error>(message_id as i64)
             })
         })
         .await;
 
     match result {
-        Ok(_) => {
+        Ok(message_id) => {
             debug!("Contact message saved successfully");
-            Ok(Status::Ok)
+            send_contact_auto_reply(db, mailer, &reply_to).await;
+            notify_message_created(db, notifier, message_id).await;
+            Ok(())
         }
         Err(e) => {
             error!("Failed to save contact message: {}", e);
@@ -71,3 +204,307 @@ pub async fn submit_message(
         }
     }
 }
+
+/// Announce a saved message in chat and queue its `message.created`
+/// webhook event
+async fn notify_message_created(
+    db: &mut Connection<MessagesDB>,
+    notifier: &ChatNotifier,
+    message_id: i64,
+) {
+    let message = messages::table
+        .find(message_id)
+        .select(Message::as_select())
+        .first(db)
+        .await;
+    match message {
+        Ok(message) => {
+            notifier.message_created(&message);
+            enqueue_webhook_event(
+                db,
+                "message.created",
+                serde_json::json!({ "message": message }),
+            )
+            .await
+        }
+        Err(e) => error!(
+            "Failed to load message {} for notifications: {}",
+            message_id, e
+        ),
+    }
+}
+
+/// Handle contact form submission, URL-encoded or multipart with an optional
+/// `attachment`
+#[post("/contact/message", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_message(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    captcha: &State<CaptchaVerifier>,
+    scorer: &State<SpamScorer>,
+    mailer: &State<Mailer>,
+    notifier: &State<ChatNotifier>,
+    client_ip: ClientIp,
+    form: Form<ContactMessageMultipart<'_>>,
+) -> Redirect {
+    let (data, attachment) = form.into_inner().into_parts();
+    let outcome = process_form_submission(
+        &mut db, redis, config, captcha, scorer, mailer, notifier, client_ip, data, attachment,
+    )
+    .await;
+
+    let error = match outcome {
+        Ok(error) => error,
+        Err(e) => {
+            if e.should_log_as_error() {
+                error!("Contact form submission failed: {}", e);
+            }
+            Some(failure_code(&e))
+        }
+    };
+    Redirect::to(contact_redirect_target(config, error))
+}
+
+/// Check and store a form submission. Returns the error code to redirect
+/// with, or `None` once the message is saved.
+#[allow(clippy::too_many_arguments)]
+async fn process_form_submission(
+    db: &mut Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    captcha: &CaptchaVerifier,
+    scorer: &SpamScorer,
+    mailer: &Mailer,
+    notifier: &ChatNotifier,
+    client_ip: ClientIp,
+    data: ContactMessageForm,
+    attachment: Option<TempFile<'_>>,
+) -> AppResult<Option<&'static str>> {
+    ensure_contact_allowed(redis, config, client_ip.0).await?;
+
+    let score = spam_score(db, scorer, &data).await?;
+    if scorer.rejects(score) {
+        warn!("Contact form submission rejected as spam (score {})", score);
+        return Ok(Some("rejected"));
+    }
+
+    if let Err(errors) = data.validate(ContactRequiredFields::parse(
+        &config.contact_required_fields,
+    )) {
+        debug!("Contact form validation failed: {:?}", errors);
+        return Ok(Some(errors[0].field));
+    }
+    if let Some(error) = offer_error(db, &data).await? {
+        debug!("Contact form offer rejected: {:?}", data.offer_id);
+        return Ok(Some(error.field));
+    }
+
+    let attachment = match read_attachment(config, attachment).await? {
+        Ok(attachment) => attachment,
+        Err(error) => {
+            debug!("Contact form attachment rejected: {}", error.message);
+            return Ok(Some(error.field));
+        }
+    };
+
+    if !captcha
+        .verify(data.captcha_token.as_deref(), client_ip.0)
+        .await?
+    {
+        return Ok(Some("captcha"));
+    }
+
+    save_message(db, mailer, notifier, data, score, attachment).await?;
+    Ok(None)
+}
+
+/// JSON variant of the contact form for submissions via `fetch`. Invalid
+/// submissions get 422 with every failing field listed.
+#[post("/api/contact", format = "json", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_message_json(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    captcha: &State<CaptchaVerifier>,
+    scorer: &State<SpamScorer>,
+    mailer: &State<Mailer>,
+    notifier: &State<ChatNotifier>,
+    client_ip: ClientIp,
+    data: Json<ContactMessageForm>,
+) -> AppResult<Custom<Json<ContactSubmitResponse>>> {
+    process_api_submission(
+        &mut db,
+        redis,
+        config,
+        captcha,
+        scorer,
+        mailer,
+        notifier,
+        client_ip,
+        data.into_inner(),
+        None,
+    )
+    .await
+}
+
+/// Multipart variant of the JSON endpoint, for submissions with an
+/// `attachment`. Responds the same way.
+#[post("/api/contact", format = "multipart/form-data", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_message_multipart(
+    mut db: Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &State<AppConfig>,
+    captcha: &State<CaptchaVerifier>,
+    scorer: &State<SpamScorer>,
+    mailer: &State<Mailer>,
+    notifier: &State<ChatNotifier>,
+    client_ip: ClientIp,
+    form: Form<ContactMessageMultipart<'_>>,
+) -> AppResult<Custom<Json<ContactSubmitResponse>>> {
+    let (data, attachment) = form.into_inner().into_parts();
+    process_api_submission(
+        &mut db, redis, config, captcha, scorer, mailer, notifier, client_ip, data, attachment,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_api_submission(
+    db: &mut Connection<MessagesDB>,
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    captcha: &CaptchaVerifier,
+    scorer: &SpamScorer,
+    mailer: &Mailer,
+    notifier: &ChatNotifier,
+    client_ip: ClientIp,
+    data: ContactMessageForm,
+    attachment: Option<TempFile<'_>>,
+) -> AppResult<Custom<Json<ContactSubmitResponse>>> {
+    ensure_contact_allowed(redis, config, client_ip.0).await?;
+
+    let score = spam_score(db, scorer, &data).await?;
+    if scorer.rejects(score) {
+        warn!("JSON contact submission rejected as spam (score {})", score);
+        return Ok(Custom(
+            Status::BadRequest,
+            Json(ContactSubmitResponse::rejected("Submission rejected")),
+        ));
+    }
+
+    if let Err(errors) = data.validate(ContactRequiredFields::parse(
+        &config.contact_required_fields,
+    )) {
+        debug!("JSON contact validation failed: {:?}", errors);
+        return Ok(Custom(
+            Status::UnprocessableEntity,
+            Json(ContactSubmitResponse::invalid(errors)),
+        ));
+    }
+    if let Some(error) = offer_error(db, &data).await? {
+        debug!("JSON contact offer rejected: {:?}", data.offer_id);
+        return Ok(Custom(
+            Status::UnprocessableEntity,
+            Json(ContactSubmitResponse::invalid(vec![error])),
+        ));
+    }
+
+    let attachment = match read_attachment(config, attachment).await? {
+        Ok(attachment) => attachment,
+        Err(error) => {
+            return Ok(Custom(
+                Status::UnprocessableEntity,
+                Json(ContactSubmitResponse::invalid(vec![error])),
+            ));
+        }
+    };
+
+    if !captcha
+        .verify(data.captcha_token.as_deref(), client_ip.0)
+        .await?
+    {
+        return Ok(Custom(
+            Status::UnprocessableEntity,
+            Json(ContactSubmitResponse::invalid(vec![ContactFieldError {
+                field: "captcha_token",
+                message: "CAPTCHA verification failed",
+            }])),
+        ));
+    }
+
+    save_message(db, mailer, notifier, data, score, attachment).await?;
+    Ok(Custom(Status::Ok, Json(ContactSubmitResponse::sent())))
+}
+
+/// CAPTCHA provider and site key for rendering the contact form widget
+#[get("/api/contact/captcha")]
+pub fn get_captcha_settings(captcha: &State<CaptchaVerifier>) -> Json<CaptchaSettings> {
+    Json(captcha.settings())
+}
+
+/// Optional fields the contact form must require, for marking them in the
+/// form
+#[get("/api/contact/fields")]
+pub fn get_required_fields(config: &State<AppConfig>) -> Json<ContactRequiredFields> {
+    Json(ContactRequiredFields::parse(
+        &config.contact_required_fields,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(overrides: serde_json::Value) -> AppConfig {
+        let mut config = serde_json::json!({
+            "database_url": "mysql://localhost/test",
+            "redis_url": "redis://localhost",
+        });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        serde_json::from_value(config).expect("valid config")
+    }
+
+    #[test]
+    fn test_contact_redirect_targets() {
+        let defaults = config(serde_json::json!({}));
+        assert_eq!(contact_redirect_target(&defaults, None), "/contact?sent=1");
+        assert_eq!(
+            contact_redirect_target(&defaults, Some("email")),
+            "/contact?error=email"
+        );
+
+        let custom = config(serde_json::json!({
+            "contact_success_path": "/contact/thanks",
+            "contact_error_path": "/contact/error?lang=sk",
+        }));
+        assert_eq!(
+            contact_redirect_target(&custom, None),
+            "/contact/thanks?sent=1"
+        );
+        assert_eq!(
+            contact_redirect_target(&custom, Some("server")),
+            "/contact/error?lang=sk&error=server"
+        );
+    }
+
+    #[test]
+    fn test_failure_code() {
+        assert_eq!(
+            failure_code(&AppError::TooManyRequests {
+                retry_after_secs: 60
+            }),
+            "rate_limited"
+        );
+        assert_eq!(
+            failure_code(&AppError::Database(diesel::result::Error::NotFound)),
+            "server"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+// Per-IP rate limit for contact form submissions, counted in Redis
+
+use redis::{ExistenceCheck, SetExpiry, SetOptions};
+use rocket::State;
+use std::net::IpAddr;
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+
+const SUBMISSIONS_PREFIX: &str = "contact_submissions:";
+
+/// Seconds to wait when `submissions` exceed the limit of the current window,
+/// whose key expires in `ttl_secs`. A limit of zero disables rate limiting.
+fn retry_after_secs(submissions: u64, max_submissions: u64, ttl_secs: i64) -> Option<u64> {
+    if max_submissions == 0 || submissions <= max_submissions {
+        return None;
+    }
+    Some(u64::try_from(ttl_secs).unwrap_or(1).max(1))
+}
+
+/// Count a submission from the client and fail with 429 once it has sent
+/// more than `CONTACT_RATE_LIMIT_MAX` within the current window
+pub async fn ensure_contact_allowed(
+    redis: &State<redis::Client>,
+    config: &AppConfig,
+    client_ip: Option<IpAddr>,
+) -> AppResult<()> {
+    if config.contact_rate_limit_max == 0 {
+        return Ok(());
+    }
+
+    let client = client_ip
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let key = format!("{SUBMISSIONS_PREFIX}{client}");
+    let window = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(config.contact_rate_limit_window_secs.max(1)));
+
+    // The window starts with the counter, so a key never lives on without
+    // an expiry
+    let mut conn = redis.get_multiplexed_async_connection().await?;
+    let (submissions, ttl_secs): (u64, i64) = redis::pipe()
+        .atomic()
+        .set_options(&key, 0, window)
+        .ignore()
+        .incr(&key, 1)
+        .ttl(&key)
+        .query_async(&mut conn)
+        .await?;
+
+    match retry_after_secs(submissions, config.contact_rate_limit_max, ttl_secs) {
+        Some(retry_after_secs) => {
+            warn!(
+                "Rate limited contact submission {} from {}",
+                submissions, client
+            );
+            Err(AppError::TooManyRequests { retry_after_secs })
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_only_above_limit() {
+        assert_eq!(retry_after_secs(3, 3, 500), None);
+        assert_eq!(retry_after_secs(4, 3, 500), Some(500));
+        assert_eq!(retry_after_secs(4, 3, -1), Some(1));
+        assert_eq!(retry_after_secs(100, 0, 500), None);
+    }
+}
@@ -0,0 +1,441 @@
+// RSS and Atom feeds of published blog posts
+
+use std::io::Cursor;
+
+use chrono::{NaiveDateTime, SecondsFormat};
+use rocket::State;
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::markdown::render_markdown;
+use crate::models::{BlogFeedEntry, Tag};
+use crate::routes::admin::blog::publicly_visible;
+use crate::routes::admin::tags::load_post_tags;
+use crate::schema::blog_posts;
+use crate::utils::ShortLinkTarget;
+
+/// Newest posts included in a feed
+const FEED_ITEM_LIMIT: i64 = 20;
+/// Format of `Last-Modified` and `If-Modified-Since`
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+const FEED_CACHE_CONTROL: &str = "public, max-age=300";
+
+/// Escape text for use in XML element content and attribute values
+pub(crate) fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// `Last-Modified` value of a timestamp stored in UTC
+pub(crate) fn http_date(timestamp: NaiveDateTime) -> String {
+    timestamp.format(HTTP_DATE_FORMAT).to_string()
+}
+
+fn rfc2822(timestamp: NaiveDateTime) -> String {
+    timestamp.and_utc().to_rfc2822()
+}
+
+pub(crate) fn rfc3339(timestamp: NaiveDateTime) -> String {
+    timestamp
+        .and_utc()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// The feed's metadata, from the site settings
+struct FeedChannel<'a> {
+    title: &'a str,
+    description: &'a str,
+    site_url: &'a str,
+}
+
+struct FeedItem {
+    title: String,
+    url: String,
+    excerpt: Option<String>,
+    content_html: String,
+    tags: Vec<Tag>,
+    published: NaiveDateTime,
+    updated: NaiveDateTime,
+}
+
+/// Newest update among the items, or the Unix epoch for an empty feed
+fn feed_updated(items: &[FeedItem]) -> NaiveDateTime {
+    items
+        .iter()
+        .map(|item| item.updated)
+        .max()
+        .unwrap_or_default()
+}
+
+fn rss_xml(channel: &FeedChannel, items: &[FeedItem]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n",
+    );
+    xml.push_str(&format!(
+        "<title>{}</title>\n<link>{}/blog</link>\n<description>{}</description>\n\
+         <language>sk</language>\n<lastBuildDate>{}</lastBuildDate>\n\
+         <atom:link href=\"{}/feed.xml\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        xml_escape(channel.title),
+        xml_escape(channel.site_url),
+        xml_escape(channel.description),
+        rfc2822(feed_updated(items)),
+        xml_escape(channel.site_url),
+    ));
+
+    for item in items {
+        xml.push_str(&format!(
+            "<item>\n<title>{}</title>\n<link>{}</link>\n\
+             <guid isPermaLink=\"true\">{}</guid>\n<pubDate>{}</pubDate>\n\
+             <description>{}</description>\n",
+            xml_escape(&item.title),
+            xml_escape(&item.url),
+            xml_escape(&item.url),
+            rfc2822(item.published),
+            xml_escape(&item.content_html),
+        ));
+        for tag in &item.tags {
+            xml.push_str(&format!("<category>{}</category>\n", xml_escape(&tag.name)));
+        }
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn atom_xml(channel: &FeedChannel, items: &[FeedItem]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"sk\">\n",
+    );
+    xml.push_str(&format!(
+        "<title>{}</title>\n<subtitle>{}</subtitle>\n<id>{}/blog</id>\n\
+         <link href=\"{}/blog\"/>\n<link rel=\"self\" href=\"{}/atom.xml\"/>\n\
+         <updated>{}</updated>\n<author><name>{}</name></author>\n",
+        xml_escape(channel.title),
+        xml_escape(channel.description),
+        xml_escape(channel.site_url),
+        xml_escape(channel.site_url),
+        xml_escape(channel.site_url),
+        rfc3339(feed_updated(items)),
+        xml_escape(channel.title),
+    ));
+
+    for item in items {
+        xml.push_str(&format!(
+            "<entry>\n<title>{}</title>\n<id>{}</id>\n<link href=\"{}\"/>\n\
+             <published>{}</published>\n<updated>{}</updated>\n",
+            xml_escape(&item.title),
+            xml_escape(&item.url),
+            xml_escape(&item.url),
+            rfc3339(item.published),
+            rfc3339(item.updated),
+        ));
+        if let Some(excerpt) = &item.excerpt {
+            xml.push_str(&format!("<summary>{}</summary>\n", xml_escape(excerpt)));
+        }
+        xml.push_str(&format!(
+            "<content type=\"html\">{}</content>\n",
+            xml_escape(&item.content_html)
+        ));
+        for tag in &item.tags {
+            xml.push_str(&format!(
+                "<category term=\"{}\" label=\"{}\"/>\n",
+                xml_escape(&tag.slug),
+                xml_escape(&tag.name),
+            ));
+        }
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Strong `ETag` of a response body
+pub(crate) fn body_etag(body: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(body.as_bytes()));
+    format!("\"{}\"", &digest[..32])
+}
+
+/// `If-None-Match` and `If-Modified-Since` of a request
+pub struct ConditionalHeaders {
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+}
+
+impl ConditionalHeaders {
+    /// Whether the client's copy is current. `If-Modified-Since` is only
+    /// used when the request has no `If-None-Match`.
+    pub fn is_fresh(&self, etag: &str, last_modified: NaiveDateTime) -> bool {
+        if self.if_none_match.is_some() {
+            return self.matches_etag(etag);
+        }
+
+        self.if_modified_since
+            .as_deref()
+            .and_then(|since| NaiveDateTime::parse_from_str(since.trim(), HTTP_DATE_FORMAT).ok())
+            .is_some_and(|since| last_modified.and_utc().timestamp() <= since.and_utc().timestamp())
+    }
+
+    /// Whether `If-None-Match` lists `etag`, compared weakly
+    pub fn matches_etag(&self, etag: &str) -> bool {
+        self.if_none_match.as_deref().is_some_and(|if_none_match| {
+            if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ConditionalHeaders {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, ()> {
+        let headers = request.headers();
+        Outcome::Success(ConditionalHeaders {
+            if_none_match: headers.get_one("If-None-Match").map(str::to_string),
+            if_modified_since: headers.get_one("If-Modified-Since").map(str::to_string),
+        })
+    }
+}
+
+/// A cacheable XML document, answered with `304 Not Modified` when the
+/// client already has it
+pub struct CachedXml {
+    body: String,
+    content_type: ContentType,
+    etag: String,
+    last_modified: NaiveDateTime,
+    not_modified: bool,
+}
+
+impl CachedXml {
+    pub fn new(
+        body: String,
+        content_type: ContentType,
+        last_modified: NaiveDateTime,
+        conditional: &ConditionalHeaders,
+    ) -> Self {
+        let etag = body_etag(&body);
+        let not_modified = conditional.is_fresh(&etag, last_modified);
+        CachedXml {
+            body,
+            content_type,
+            etag,
+            last_modified,
+            not_modified,
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for CachedXml {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .raw_header("ETag", self.etag)
+            .raw_header("Last-Modified", http_date(self.last_modified))
+            .raw_header("Cache-Control", FEED_CACHE_CONTROL);
+        if self.not_modified {
+            response.status(Status::NotModified);
+        } else {
+            response
+                .header(self.content_type)
+                .sized_body(self.body.len(), Cursor::new(self.body));
+        }
+        response.ok()
+    }
+}
+
+/// Newest published posts with absolute links
+async fn load_feed_items(
+    db: &mut Connection<MessagesDB>,
+    site_url: &str,
+) -> AppResult<Vec<FeedItem>> {
+    let posts: Vec<BlogFeedEntry> = blog_posts::table
+        .filter(publicly_visible())
+        .order((blog_posts::created_at.desc(), blog_posts::id.desc()))
+        .limit(FEED_ITEM_LIMIT)
+        .select(BlogFeedEntry::as_select())
+        .load(db)
+        .await
+        .map_err(|e| {
+            error!("Error loading blog posts for the feed: {}", e);
+            AppError::from(e)
+        })?;
+
+    let ids: Vec<i64> = posts.iter().map(|post| post.id).collect();
+    let mut post_tags = load_post_tags(db, &ids).await?;
+    Ok(posts
+        .into_iter()
+        .map(|post| FeedItem {
+            url: format!(
+                "{site_url}{}",
+                ShortLinkTarget::BlogPost(post.id).page_path(&post.slug)
+            ),
+            content_html: render_markdown(&post.content),
+            tags: post_tags.remove(&post.id).unwrap_or_default(),
+            title: post.title,
+            excerpt: post.excerpt,
+            published: post.created_at,
+            updated: post.updated_at,
+        })
+        .collect())
+}
+
+fn channel(config: &AppConfig) -> FeedChannel<'_> {
+    FeedChannel {
+        title: &config.site_title,
+        description: &config.site_description,
+        site_url: config.site_url(),
+    }
+}
+
+/// RSS 2.0 feed of the newest published posts
+#[get("/feed.xml")]
+pub async fn rss_feed(
+    mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    conditional: ConditionalHeaders,
+) -> AppResult<CachedXml> {
+    let items = load_feed_items(&mut db, config.site_url()).await?;
+    Ok(CachedXml::new(
+        rss_xml(&channel(config), &items),
+        ContentType::new("application", "rss+xml").with_params(("charset", "utf-8")),
+        feed_updated(&items),
+        &conditional,
+    ))
+}
+
+/// Atom feed of the newest published posts
+#[get("/atom.xml")]
+pub async fn atom_feed(
+    mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    conditional: ConditionalHeaders,
+) -> AppResult<CachedXml> {
+    let items = load_feed_items(&mut db, config.site_url()).await?;
+    Ok(CachedXml::new(
+        atom_xml(&channel(config), &items),
+        ContentType::new("application", "atom+xml").with_params(("charset", "utf-8")),
+        feed_updated(&items),
+        &conditional,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn items() -> Vec<FeedItem> {
+        vec![FeedItem {
+            title: "Zateplenie & fasády".to_string(),
+            url: "https://example.com/blog/zateplenie".to_string(),
+            excerpt: Some("Krátky <úvod>".to_string()),
+            content_html: "<p>Obsah</p>\n".to_string(),
+            tags: vec![Tag {
+                id: 1,
+                name: "Tipy".to_string(),
+                slug: "tipy".to_string(),
+                created_at: timestamp("2026-01-01 00:00:00"),
+            }],
+            published: timestamp("2026-03-01 08:00:00"),
+            updated: timestamp("2026-03-02 09:30:00"),
+        }]
+    }
+
+    fn test_channel() -> FeedChannel<'static> {
+        FeedChannel {
+            title: "MK-SBD",
+            description: "Blog",
+            site_url: "https://example.com",
+        }
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_rss_xml() {
+        let xml = rss_xml(&test_channel(), &items());
+        assert!(xml.contains("<title>Zateplenie &amp; fasády</title>"));
+        assert!(
+            xml.contains("<guid isPermaLink=\"true\">https://example.com/blog/zateplenie</guid>")
+        );
+        assert!(xml.contains("<pubDate>Sun, 1 Mar 2026 08:00:00 +0000</pubDate>"));
+        assert!(xml.contains("<description>&lt;p&gt;Obsah&lt;/p&gt;\n</description>"));
+        assert!(xml.contains("<category>Tipy</category>"));
+        assert!(xml.contains("<lastBuildDate>Mon, 2 Mar 2026 09:30:00 +0000</lastBuildDate>"));
+    }
+
+    #[test]
+    fn test_atom_xml() {
+        let xml = atom_xml(&test_channel(), &items());
+        assert!(xml.contains("<updated>2026-03-02T09:30:00Z</updated>\n<author>"));
+        assert!(xml.contains("<summary>Krátky &lt;úvod&gt;</summary>"));
+        assert!(xml.contains("<category term=\"tipy\" label=\"Tipy\"/>"));
+        assert!(xml.contains("<link rel=\"self\" href=\"https://example.com/atom.xml\"/>"));
+
+        let empty = atom_xml(&test_channel(), &[]);
+        assert!(empty.contains("<updated>1970-01-01T00:00:00Z</updated>"));
+        assert!(!empty.contains("<entry>"));
+    }
+
+    #[test]
+    fn test_conditional_headers() {
+        let last_modified = timestamp("2026-03-02 09:30:00");
+        let headers =
+            |if_none_match: Option<&str>, if_modified_since: Option<&str>| ConditionalHeaders {
+                if_none_match: if_none_match.map(str::to_string),
+                if_modified_since: if_modified_since.map(str::to_string),
+            };
+        let etag = "\"abc\"";
+
+        assert!(headers(Some("\"abc\""), None).is_fresh(etag, last_modified));
+        assert!(headers(Some("\"x\", W/\"abc\""), None).is_fresh(etag, last_modified));
+        assert!(!headers(Some("\"x\""), None).is_fresh(etag, last_modified));
+        assert!(
+            !headers(Some("\"x\""), Some("Mon, 02 Mar 2026 09:30:00 GMT"))
+                .is_fresh(etag, last_modified)
+        );
+        assert!(headers(None, Some("Mon, 02 Mar 2026 09:30:00 GMT")).is_fresh(etag, last_modified));
+        assert!(
+            !headers(None, Some("Mon, 02 Mar 2026 09:29:59 GMT")).is_fresh(etag, last_modified)
+        );
+        assert!(!headers(None, Some("garbage")).is_fresh(etag, last_modified));
+        assert!(!headers(None, None).is_fresh(etag, last_modified));
+        assert!(headers(Some("*"), None).matches_etag(etag));
+        assert!(!headers(None, Some("Mon, 02 Mar 2026 09:30:00 GMT")).matches_etag(etag));
+        assert_eq!(http_date(last_modified), "Mon, 02 Mar 2026 09:30:00 GMT");
+    }
+}
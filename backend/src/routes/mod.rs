@@ -1,12 +1,20 @@
 // Routes module - organizes all HTTP route handlers
 
 pub mod admin;
+pub mod changes;
+pub mod client_ip;
 pub mod contact;
+pub mod contact_limit;
+pub mod feeds;
+pub mod search;
+pub mod short_links;
+pub mod sitemap;
 
+use rocket::Route;
 use rocket::fs::NamedFile;
 use std::path::PathBuf;
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, FeatureFlags};
 
 fn static_file_path(relative_path: &str) -> PathBuf {
     let config = AppConfig::load();
@@ -32,3 +40,163 @@ pub async fn blog_detail_page(_slug: &str) -> Option<NamedFile> {
 pub async fn not_found() -> Option<NamedFile> {
     NamedFile::open(static_file_path("404.html")).await.ok()
 }
+
+/// Routes of the optional feature groups that are enabled in the config
+pub fn feature_routes(features: &FeatureFlags) -> Vec<Route> {
+    let mut enabled = Vec::new();
+
+    if features.offers {
+        enabled.extend(routes![
+            admin::list_offers,
+            admin::list_all_offers,
+            admin::get_offer_by_slug,
+            admin::get_offer_image,
+            admin::get_offer_image_file,
+            admin::list_offer_images,
+            admin::upload_offer_image,
+            admin::reorder_offer_images,
+            admin::set_primary_offer_image,
+            admin::delete_offer_image,
+            admin::create_offer,
+            admin::delete_offer,
+            admin::update_offer,
+            admin::patch_offer,
+            admin::reorder_offers,
+            admin::follow_offer_link,
+            admin::get_offer_click_stats,
+            admin::import_offers,
+            admin::restore_trashed_offer,
+            admin::permanently_delete_offer,
+            offer_detail_page,
+        ]);
+    }
+
+    if features.blog {
+        enabled.extend(routes![
+            admin::list_blog_posts,
+            admin::list_all_blog_posts,
+            admin::get_admin_blog_post,
+            admin::list_popular_blog_posts,
+            admin::list_blog_authors,
+            admin::list_featured_blog_posts,
+            admin::get_blog_post_by_slug,
+            admin::get_blog_post_meta,
+            admin::get_blog_post_preview,
+            admin::get_blog_post_image,
+            admin::get_blog_post_image_by_slug,
+            admin::get_blog_post_image_file,
+            admin::list_blog_post_images,
+            admin::upload_blog_post_image,
+            admin::reorder_blog_post_images,
+            admin::delete_blog_post_image,
+            admin::create_blog_post,
+            admin::update_blog_post,
+            admin::patch_blog_post,
+            admin::preview_blog_post,
+            admin::list_blog_post_revisions,
+            admin::diff_blog_post_revisions,
+            admin::restore_blog_post_revision,
+            admin::duplicate_blog_post,
+            admin::create_blog_preview_link,
+            admin::export_blog_posts,
+            admin::import_blog_posts,
+            admin::archive_blog_post,
+            admin::restore_blog_post,
+            admin::feature_blog_post,
+            admin::delete_blog_post,
+            admin::restore_trashed_blog_post,
+            admin::permanently_delete_blog_post,
+            admin::list_tags,
+            admin::create_tag,
+            admin::update_tag,
+            admin::delete_tag,
+            feeds::rss_feed,
+            feeds::atom_feed,
+            blog_detail_page,
+        ]);
+    }
+
+    if features.offers || features.blog {
+        enabled.extend(routes![admin::list_trash]);
+    }
+
+    if features.banner {
+        enabled.extend(routes![
+            admin::get_active_banner,
+            admin::get_admin_banner,
+            admin::upsert_banner,
+            admin::delete_banner,
+        ]);
+    }
+
+    if features.short_links {
+        enabled.extend(routes![short_links::resolve_short_link]);
+    }
+
+    if features.content_changes {
+        enabled.extend(routes![changes::get_changes]);
+    }
+
+    enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mounted_paths(features: &FeatureFlags) -> Vec<String> {
+        let rocket = rocket::build().mount("/", feature_routes(features));
+        rocket
+            .routes()
+            .map(|route| route.uri.path().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_all_feature_routes_mounted_by_default() {
+        let paths = mounted_paths(&FeatureFlags::default());
+
+        assert!(paths.iter().any(|path| path == "/api/offers"));
+        assert!(paths.iter().any(|path| path == "/api/blog"));
+        assert!(paths.iter().any(|path| path == "/api/banner"));
+        assert!(paths.iter().any(|path| path == "/api/changes"));
+    }
+
+    #[test]
+    fn test_disabled_feature_routes_not_mounted() {
+        let features = FeatureFlags {
+            blog: false,
+            ..FeatureFlags::default()
+        };
+        let paths = mounted_paths(&features);
+
+        assert!(!paths.iter().any(|path| path.contains("/blog")));
+        assert!(!paths.iter().any(|path| path == "/feed.xml"));
+        assert!(paths.iter().any(|path| path == "/api/offers"));
+    }
+
+    #[test]
+    fn test_disabled_feature_returns_not_found() {
+        use rocket::http::Status;
+        use rocket::local::blocking::Client;
+
+        let features = FeatureFlags {
+            offers: false,
+            blog: false,
+            banner: false,
+            short_links: false,
+            content_changes: false,
+        };
+        let rocket = rocket::build().mount("/", feature_routes(&features));
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+
+        assert_eq!(
+            client.get("/api/changes").dispatch().status(),
+            Status::NotFound
+        );
+        assert_eq!(
+            client.get("/api/offers").dispatch().status(),
+            Status::NotFound
+        );
+    }
+}
@@ -0,0 +1,238 @@
+// Site-wide search over published blog posts and offers
+
+use chrono::Utc;
+use rocket::State;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::markdown::markdown_to_plain_text;
+use crate::models::{SearchResult, SearchResultKind};
+use crate::routes::admin::blog::{blog_search_relevance, publicly_visible};
+use crate::routes::admin::offers::{currently_active, offer_search_relevance};
+use crate::routes::feeds::xml_escape;
+use crate::schema::{blog_posts, offers};
+use crate::utils::{ShortLinkTarget, search_query};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 10;
+const MAX_SEARCH_LIMIT: i64 = 50;
+/// Characters of text in a snippet
+const SNIPPET_LEN: usize = 160;
+/// Characters kept before the first match
+const SNIPPET_LEAD: usize = 40;
+
+/// A title, slug, Markdown text and relevance loaded for a search
+type Hit = (i64, String, String, Option<String>, Option<String>, f64);
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Lowercased words of a query, longest first so that longer terms are
+/// highlighted whole
+fn query_terms(query: &str) -> Vec<Vec<char>> {
+    let mut terms: Vec<Vec<char>> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().map(lowercase).collect())
+        .collect();
+    terms.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    terms.dedup();
+    terms
+}
+
+/// Length of the term that starts at `at` in `text`, if any
+fn term_at(text: &[char], at: usize, terms: &[Vec<char>]) -> Option<usize> {
+    terms
+        .iter()
+        .find(|term| {
+            text.get(at..at + term.len()).is_some_and(|candidate| {
+                candidate
+                    .iter()
+                    .zip(term.iter())
+                    .all(|(c, t)| lowercase(*c) == *t)
+            })
+        })
+        .map(Vec::len)
+}
+
+/// About `SNIPPET_LEN` characters of `text` from shortly before the first
+/// term, escaped, with each term wrapped in `<mark>`. Starts at the
+/// beginning when no term occurs in the text.
+fn snippet(text: &str, terms: &[Vec<char>]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let first = (0..chars.len()).find(|&at| term_at(&chars, at, terms).is_some());
+
+    let mut start = first.map_or(0, |at| at.saturating_sub(SNIPPET_LEAD));
+    // Begin at the start of a word
+    if let Some(at) = first
+        && start > 0
+        && let Some(space) = chars[start..at].iter().position(|c| c.is_whitespace())
+    {
+        start += space + 1;
+    }
+    let mut end = (start + SNIPPET_LEN).min(chars.len());
+    if end < chars.len()
+        && let Some(space) = chars[start..end].iter().rposition(|c| c.is_whitespace())
+        && space > 0
+    {
+        end = start + space;
+    }
+
+    let mut html = String::new();
+    if start > 0 {
+        html.push('…');
+    }
+    let mut at = start;
+    while at < end {
+        match term_at(&chars, at, terms).filter(|len| at + len <= end) {
+            Some(len) => {
+                let term: String = chars[at..at + len].iter().collect();
+                html.push_str(&format!("<mark>{}</mark>", xml_escape(&term)));
+                at += len;
+            }
+            None => {
+                html.push_str(&xml_escape(&chars[at].to_string()));
+                at += 1;
+            }
+        }
+    }
+    if end < chars.len() {
+        html.push('…');
+    }
+    html
+}
+
+fn to_result(kind: SearchResultKind, hit: Hit, terms: &[Vec<char>]) -> SearchResult {
+    let (id, title, slug, excerpt, content, score) = hit;
+    let markdown = [excerpt, content]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let url = match kind {
+        SearchResultKind::BlogPost => ShortLinkTarget::BlogPost(id).page_path(&slug),
+        SearchResultKind::Offer => ShortLinkTarget::Offer(id).page_path(&slug),
+    };
+    SearchResult {
+        kind,
+        id,
+        title,
+        slug,
+        url,
+        snippet_html: snippet(&markdown_to_plain_text(&markdown), terms),
+        score,
+    }
+}
+
+/// Published blog posts and offers matching `q` in their title, excerpt or
+/// content, best matches first. Content types whose feature is disabled
+/// are not searched.
+#[get("/api/search?<q>&<limit>")]
+pub async fn site_search(
+    mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    q: Option<&str>,
+    limit: Option<i64>,
+) -> AppResult<Json<Vec<SearchResult>>> {
+    let query = search_query(q.unwrap_or_default())?;
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let terms = query_terms(query);
+    let mut results = Vec::new();
+
+    if config.features.blog {
+        let hits: Vec<Hit> = blog_posts::table
+            .filter(publicly_visible())
+            .filter(blog_search_relevance(query).gt(0.0))
+            .order(blog_search_relevance(query).desc())
+            .limit(limit)
+            .select((
+                blog_posts::id,
+                blog_posts::title,
+                blog_posts::slug,
+                blog_posts::excerpt,
+                blog_posts::content.nullable(),
+                blog_search_relevance(query),
+            ))
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error searching blog posts: {}", e);
+                AppError::from(e)
+            })?;
+        results.extend(
+            hits.into_iter()
+                .map(|hit| to_result(SearchResultKind::BlogPost, hit, &terms)),
+        );
+    }
+
+    if config.features.offers {
+        let hits: Vec<Hit> = offers::table
+            .filter(currently_active(Utc::now().naive_utc()))
+            .filter(offer_search_relevance(query).gt(0.0))
+            .order(offer_search_relevance(query).desc())
+            .limit(limit)
+            .select((
+                offers::id,
+                offers::title,
+                offers::slug,
+                offers::excerpt,
+                offers::content,
+                offer_search_relevance(query),
+            ))
+            .load(&mut db)
+            .await
+            .map_err(|e| {
+                error!("Error searching offers: {}", e);
+                AppError::from(e)
+            })?;
+        results.extend(
+            hits.into_iter()
+                .map(|hit| to_result(SearchResultKind::Offer, hit, &terms)),
+        );
+    }
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit as usize);
+    Ok(Json(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_terms() {
+        let terms = query_terms("Zateplenie  fasády, zateplenie");
+        let words: Vec<String> = terms.iter().map(|t| t.iter().collect()).collect();
+        assert_eq!(words, vec!["zateplenie", "fasády"]);
+    }
+
+    #[test]
+    fn test_snippet_highlights_terms() {
+        let terms = query_terms("fasáda");
+        assert_eq!(
+            snippet("Nová Fasáda & strecha", &terms),
+            "Nová <mark>Fasáda</mark> &amp; strecha"
+        );
+        assert_eq!(snippet("Bez zhody", &terms), "Bez zhody");
+    }
+
+    #[test]
+    fn test_snippet_starts_near_first_match() {
+        let terms = query_terms("strecha");
+        let text = format!("{} strecha {}", "slovo ".repeat(30), "koniec ".repeat(40));
+        let snippet = snippet(&text, &terms);
+
+        assert!(snippet.starts_with("…slovo"));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("<mark>strecha</mark>"));
+        assert!(snippet.chars().count() <= SNIPPET_LEN + "<mark></mark>".len() + 2);
+    }
+}
@@ -0,0 +1,43 @@
+// Short link redirects for compact offer and blog post URLs
+
+use chrono::Utc;
+use rocket::response::Redirect;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::routes::admin::blog::publicly_visible;
+use crate::routes::admin::offers::currently_active;
+use crate::schema::{blog_posts, offers};
+use crate::utils::ShortLinkTarget;
+
+#[get("/s/<code>")]
+pub async fn resolve_short_link(mut db: Connection<MessagesDB>, code: &str) -> AppResult<Redirect> {
+    let target = ShortLinkTarget::from_short_code(code).ok_or(AppError::NotFound)?;
+
+    let slug: Option<String> = match target {
+        ShortLinkTarget::Offer(id) => offers::table
+            .find(id)
+            .filter(currently_active(Utc::now().naive_utc()))
+            .select(offers::slug)
+            .first(&mut db)
+            .await
+            .optional(),
+        ShortLinkTarget::BlogPost(id) => blog_posts::table
+            .find(id)
+            .filter(publicly_visible())
+            .select(blog_posts::slug)
+            .first(&mut db)
+            .await
+            .optional(),
+    }
+    .map_err(|e| {
+        error!("Error resolving short link '{}': {}", code, e);
+        AppError::from(e)
+    })?;
+
+    let slug = slug.ok_or(AppError::NotFound)?;
+    Ok(Redirect::found(target.page_path(&slug)))
+}
@@ -0,0 +1,167 @@
+// XML sitemap of the public pages, offers and blog posts
+
+use chrono::{NaiveDateTime, Utc};
+use rocket::State;
+use rocket::http::ContentType;
+use rocket_db_pools::Connection;
+use rocket_db_pools::diesel::prelude::*;
+use tracing::error;
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::error::{AppError, AppResult};
+use crate::routes::admin::blog::publicly_visible;
+use crate::routes::admin::offers::currently_active;
+use crate::routes::feeds::{CachedXml, ConditionalHeaders, rfc3339, xml_escape};
+use crate::schema::{blog_posts, offers};
+use crate::utils::ShortLinkTarget;
+
+struct SitemapEntry {
+    loc: String,
+    lastmod: Option<NaiveDateTime>,
+}
+
+/// Entries for the configured comma-separated static page paths
+fn static_pages(paths: &str, site_url: &str) -> Vec<SitemapEntry> {
+    paths
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| SitemapEntry {
+            loc: format!("{site_url}/{}", path.trim_start_matches('/')),
+            lastmod: None,
+        })
+        .collect()
+}
+
+fn sitemap_xml(entries: &[SitemapEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for entry in entries {
+        xml.push_str(&format!("<url>\n<loc>{}</loc>\n", xml_escape(&entry.loc)));
+        if let Some(lastmod) = entry.lastmod {
+            xml.push_str(&format!("<lastmod>{}</lastmod>\n", rfc3339(lastmod)));
+        }
+        xml.push_str("</url>\n");
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// Detail pages of the content types enabled in the config
+async fn content_pages(
+    db: &mut Connection<MessagesDB>,
+    config: &AppConfig,
+    site_url: &str,
+) -> AppResult<Vec<SitemapEntry>> {
+    let mut entries = Vec::new();
+
+    if config.features.offers {
+        let rows: Vec<(i64, String, NaiveDateTime)> = offers::table
+            .filter(currently_active(Utc::now().naive_utc()))
+            .order(offers::id.asc())
+            .select((offers::id, offers::slug, offers::updated_at))
+            .load(db)
+            .await
+            .map_err(|e| {
+                error!("Error loading offers for the sitemap: {}", e);
+                AppError::from(e)
+            })?;
+        entries.extend(rows.into_iter().map(|(id, slug, updated_at)| SitemapEntry {
+            loc: format!("{site_url}{}", ShortLinkTarget::Offer(id).page_path(&slug)),
+            lastmod: Some(updated_at),
+        }));
+    }
+
+    if config.features.blog {
+        let rows: Vec<(i64, String, NaiveDateTime)> = blog_posts::table
+            .filter(publicly_visible())
+            .order(blog_posts::id.asc())
+            .select((blog_posts::id, blog_posts::slug, blog_posts::updated_at))
+            .load(db)
+            .await
+            .map_err(|e| {
+                error!("Error loading blog posts for the sitemap: {}", e);
+                AppError::from(e)
+            })?;
+        entries.extend(rows.into_iter().map(|(id, slug, updated_at)| SitemapEntry {
+            loc: format!(
+                "{site_url}{}",
+                ShortLinkTarget::BlogPost(id).page_path(&slug)
+            ),
+            lastmod: Some(updated_at),
+        }));
+    }
+
+    Ok(entries)
+}
+
+/// Sitemap of the static pages from `SITEMAP_STATIC_PAGES`, all offers and
+/// every published blog post
+#[get("/sitemap.xml")]
+pub async fn get_sitemap(
+    mut db: Connection<MessagesDB>,
+    config: &State<AppConfig>,
+    conditional: ConditionalHeaders,
+) -> AppResult<CachedXml> {
+    let site_url = config.site_url();
+    let mut entries = static_pages(&config.sitemap_static_pages, site_url);
+    entries.extend(content_pages(&mut db, config, site_url).await?);
+
+    let last_modified = entries
+        .iter()
+        .filter_map(|entry| entry.lastmod)
+        .max()
+        .unwrap_or_default();
+    Ok(CachedXml::new(
+        sitemap_xml(&entries),
+        ContentType::XML,
+        last_modified,
+        &conditional,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_pages() {
+        let pages = static_pages(" /, about ,/blog,,", "https://example.com");
+        let locs: Vec<&str> = pages.iter().map(|page| page.loc.as_str()).collect();
+        assert_eq!(
+            locs,
+            vec![
+                "https://example.com/",
+                "https://example.com/about",
+                "https://example.com/blog"
+            ]
+        );
+        assert!(pages.iter().all(|page| page.lastmod.is_none()));
+    }
+
+    #[test]
+    fn test_sitemap_xml() {
+        let entries = vec![
+            SitemapEntry {
+                loc: "https://example.com/".to_string(),
+                lastmod: None,
+            },
+            SitemapEntry {
+                loc: "https://example.com/blog/a&b".to_string(),
+                lastmod: Some(
+                    NaiveDateTime::parse_from_str("2026-03-02 09:30:00", "%Y-%m-%d %H:%M:%S")
+                        .unwrap(),
+                ),
+            },
+        ];
+        let xml = sitemap_xml(&entries);
+        assert!(xml.contains("<url>\n<loc>https://example.com/</loc>\n</url>"));
+        assert!(xml.contains(
+            "<loc>https://example.com/blog/a&amp;b</loc>\n<lastmod>2026-03-02T09:30:00Z</lastmod>"
+        ));
+        assert!(xml.ends_with("</urlset>\n"));
+    }
+}
@@ -0,0 +1,400 @@
+// Allow-list sanitizing of blog Markdown before it is saved. Rendered posts
+// are cleaned again by `markdown::render_markdown`; this keeps unsafe HTML
+// out of the stored source, which the frontend renders itself for excerpts.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::markdown::{MARKDOWN_OPTIONS, markdown_to_plain_text};
+
+/// Schemes allowed in links, images and URL attributes; URLs without a
+/// scheme are relative and always allowed
+const SAFE_URL_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+/// Attributes whose values are loaded or followed as URLs
+const URL_ATTRIBUTES: [&str; 9] = [
+    "href",
+    "src",
+    "cite",
+    "action",
+    "formaction",
+    "poster",
+    "background",
+    "data",
+    "xlink:href",
+];
+/// Tags that can run scripts or change how the page loads; never kept,
+/// whatever the configured policy says
+const FORBIDDEN_TAGS: [&str; 16] = [
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "base", "meta",
+    "link", "form", "svg", "math", "template", "noscript",
+];
+/// Tags removed together with their content
+const CONTENT_TAGS: [&str; 13] = [
+    "script", "style", "iframe", "object", "embed", "svg", "math", "template", "noscript",
+    "textarea", "title", "xmp", "noembed",
+];
+/// Element put between the HTML pieces of a post while they are cleaned
+/// together, to split the cleaned HTML back into pieces
+const PIECE_MARKER_TAG: &str = "sanitize-piece";
+
+/// The raw HTML tags and attributes kept in blog posts
+pub struct HtmlPolicy {
+    tags: HashSet<String>,
+    attributes: HashSet<String>,
+}
+
+impl HtmlPolicy {
+    pub fn from_config(config: &AppConfig) -> Self {
+        HtmlPolicy::new(
+            &config.blog_allowed_html_tags,
+            &config.blog_allowed_html_attributes,
+        )
+    }
+
+    /// Policy from comma-separated tag and attribute names. Script-capable
+    /// tags, event handlers and `srcset` are left out with a warning.
+    fn new(tags: &str, attributes: &str) -> Self {
+        let names = |list: &str| -> HashSet<String> {
+            list.split(',')
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        };
+        let mut tags = names(tags);
+        let mut attributes = names(attributes);
+
+        tags.retain(|tag| {
+            let allowed = !FORBIDDEN_TAGS.contains(&tag.as_str());
+            if !allowed {
+                warn!("HTML tag '{}' cannot be allowed in blog posts", tag);
+            }
+            allowed
+        });
+        attributes.retain(|attribute| {
+            let allowed = !attribute.starts_with("on")
+                && !matches!(attribute.as_str(), "srcdoc" | "srcset" | "formaction");
+            if !allowed {
+                warn!(
+                    "HTML attribute '{}' cannot be allowed in blog posts",
+                    attribute
+                );
+            }
+            allowed
+        });
+        HtmlPolicy { tags, attributes }
+    }
+
+    /// Ammonia cleaner for the policy, also keeping the `extra_tags`
+    fn cleaner<'a>(&'a self, extra_tags: &[&'a str]) -> ammonia::Builder<'a> {
+        let tags: HashSet<&str> = self
+            .tags
+            .iter()
+            .map(String::as_str)
+            .chain(extra_tags.iter().copied())
+            .collect();
+        let clean_content_tags = CONTENT_TAGS
+            .into_iter()
+            .filter(|tag| !tags.contains(tag))
+            .collect();
+
+        let mut cleaner = ammonia::Builder::empty();
+        cleaner
+            .tags(tags)
+            .clean_content_tags(clean_content_tags)
+            .generic_attributes(self.attributes.iter().map(String::as_str).collect())
+            .tag_attributes(HashMap::new())
+            .url_schemes(SAFE_URL_SCHEMES.into_iter().collect())
+            .attribute_filter(|_, attribute, value| {
+                // Ammonia checks URLs only in the attributes it knows for
+                // each element, e.g. not `cite` or `background`
+                let unsafe_url = URL_ATTRIBUTES.contains(&attribute) && !is_safe_url(value);
+                (!unsafe_url).then_some(value.into())
+            })
+            .link_rel(None);
+        cleaner
+    }
+}
+
+/// Whether a decoded URL is relative or uses a safe scheme. Browsers ignore
+/// whitespace and control characters in schemes, so they are ignored here.
+fn is_safe_url(url: &str) -> bool {
+    let cleaned: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    match cleaned.find([':', '/', '?', '#']) {
+        Some(at) if cleaned[at..].starts_with(':') => {
+            SAFE_URL_SCHEMES.contains(&cleaned[..at].to_ascii_lowercase().as_str())
+        }
+        _ => true,
+    }
+}
+
+/// Clean a piece of raw HTML: tags and attributes outside the policy are
+/// dropped, dangerous elements are removed with their content, and comments
+/// and other markup are removed
+fn sanitize_html(html: &str, policy: &HtmlPolicy) -> String {
+    policy.cleaner(&[]).clean(html).to_string()
+}
+
+/// Clean the raw HTML pieces of a post together, so a tag opened in one
+/// piece and closed in a later one stays as written. Falls back to cleaning
+/// the pieces one by one when the cleaned HTML cannot be split back.
+fn sanitize_html_pieces(pieces: &[&str], policy: &HtmlPolicy) -> Vec<String> {
+    let markers: Vec<String> = (0..pieces.len())
+        .map(|index| format!("{PIECE_MARKER_TAG}-{index}"))
+        .collect();
+    let can_mark = pieces
+        .iter()
+        .all(|piece| !piece.to_ascii_lowercase().contains(PIECE_MARKER_TAG));
+    if can_mark {
+        let joined: String = pieces
+            .iter()
+            .zip(&markers)
+            .map(|(piece, marker)| format!("{piece}<{marker}></{marker}>"))
+            .collect();
+        let marker_tags: Vec<&str> = markers.iter().map(String::as_str).collect();
+        let clean = policy.cleaner(&marker_tags).clean(&joined).to_string();
+        if let Some(split) = split_pieces(&clean, &markers) {
+            return split;
+        }
+    }
+    pieces
+        .iter()
+        .map(|piece| sanitize_html(piece, policy))
+        .collect()
+}
+
+/// Split HTML cleaned with a marker element after each piece back into the
+/// pieces. A marker removed together with an element leaves its piece empty,
+/// and the cleaned HTML of that piece goes to the next piece whose marker
+/// is left. Returns `None` when the markers were moved out of order.
+fn split_pieces(clean: &str, markers: &[String]) -> Option<Vec<String>> {
+    let mut split = vec![String::new(); markers.len()];
+    let mut rest = clean;
+    for (piece, marker) in split.iter_mut().zip(markers) {
+        let element = format!("<{marker}></{marker}>");
+        if let Some(at) = rest.find(&element) {
+            *piece = rest[..at].to_string();
+            rest = &rest[at + element.len()..];
+        }
+    }
+    // Past the last marker are only the end tags of elements left open,
+    // unless the marker itself was removed
+    if let (Some(last), Some(marker)) = (split.last_mut(), markers.last())
+        && !clean.contains(&format!("<{marker}>"))
+    {
+        *last = rest.to_string();
+    }
+
+    let marker_start = format!("<{PIECE_MARKER_TAG}");
+    let moved = split.iter().any(|piece| piece.contains(&marker_start));
+    (!moved).then_some(split)
+}
+
+/// `text` with ASCII punctuation escaped, so it is shown as is in Markdown
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `<` in Markdown text written as a character reference, which is shown
+/// the same but cannot start a tag once the HTML around it is removed.
+/// Every `<` is escaped since the parser may split text right after it;
+/// `previous` is the character before the text.
+fn escape_less_than(text: &str, previous: Option<char>) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut previous = previous;
+    for c in text.chars() {
+        if c == '<' && previous != Some('\\') {
+            escaped.push_str("&lt;");
+        } else {
+            escaped.push(c);
+        }
+        previous = Some(c);
+    }
+    escaped
+}
+
+/// Sanitize the raw HTML and link targets in Markdown, keeping everything
+/// else as written. Links and images with unsafe URLs are replaced by their
+/// text, and unsafe reference definitions are removed.
+pub fn sanitize_markdown(source: &str, policy: &HtmlPolicy) -> String {
+    let parser = Parser::new_ext(source, MARKDOWN_OPTIONS);
+    let mut edits: Vec<(Range<usize>, String)> = parser
+        .reference_definitions()
+        .iter()
+        .filter(|(_, definition)| !is_safe_url(&definition.dest))
+        .map(|(_, definition)| (definition.span.clone(), String::new()))
+        .collect();
+
+    let mut html = Vec::new();
+    let mut replaced_until = 0;
+    let mut in_code_block = false;
+    for (event, range) in parser.into_offset_iter() {
+        if range.start < replaced_until {
+            continue;
+        }
+        match event {
+            Event::Start(Tag::HtmlBlock) => {
+                replaced_until = range.end;
+                html.push(range);
+            }
+            Event::Html(_) | Event::InlineHtml(_) => html.push(range),
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. })
+                if !is_safe_url(&dest_url) =>
+            {
+                replaced_until = range.end;
+                let text = markdown_to_plain_text(&source[range.clone()]);
+                edits.push((range, escape_markdown(&text)));
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(_) if !in_code_block => {
+                let text = &source[range.clone()];
+                if text.contains('<') {
+                    let previous = source[..range.start].chars().next_back();
+                    edits.push((range, escape_less_than(text, previous)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let pieces: Vec<&str> = html.iter().map(|range| &source[range.clone()]).collect();
+    let clean_pieces = sanitize_html_pieces(&pieces, policy);
+    edits.extend(html.into_iter().zip(clean_pieces));
+
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut clean = String::with_capacity(source.len());
+    let mut at = 0;
+    for (range, replacement) in edits {
+        if range.start < at {
+            continue;
+        }
+        clean.push_str(&source[at..range.start]);
+        clean.push_str(&replacement);
+        at = range.end;
+    }
+    clean.push_str(&source[at..]);
+    clean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> HtmlPolicy {
+        HtmlPolicy::new("p, a, img, details, summary, span", "href, src, alt, title")
+    }
+
+    #[test]
+    fn test_policy_leaves_out_dangerous_names() {
+        let policy = HtmlPolicy::new("P, script, iframe", "href, onclick, srcset");
+        assert!(policy.tags.contains("p"));
+        assert!(!policy.tags.contains("script"));
+        assert!(!policy.tags.contains("iframe"));
+        assert!(policy.attributes.contains("href"));
+        assert!(!policy.attributes.contains("onclick"));
+        assert!(!policy.attributes.contains("srcset"));
+    }
+
+    #[test]
+    fn test_is_safe_url() {
+        assert!(is_safe_url("https://example.com/a"));
+        assert!(is_safe_url("/api/blog/7/images/3"));
+        assert!(is_safe_url("#nadpis"));
+        assert!(is_safe_url("mailto:info@example.com"));
+        assert!(is_safe_url("a/b:c"));
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" JaVa\tScRiPt:alert(1)"));
+        assert!(!is_safe_url("data:text/html,x"));
+    }
+
+    #[test]
+    fn test_sanitize_html_checks_decoded_urls() {
+        assert_eq!(
+            sanitize_html(r#"<a href="&#106;ava&#x73;cript&colon;x">x</a>"#, &policy()),
+            "<a>x</a>"
+        );
+        let policy = HtmlPolicy::new("q", "cite");
+        assert_eq!(
+            sanitize_html(r#"<q cite="java&#x73;cript:x">a</q>"#, &policy),
+            "<q>a</q>"
+        );
+        assert_eq!(
+            sanitize_html(r#"<q cite="https://example.com">a</q>"#, &policy),
+            r#"<q cite="https://example.com">a</q>"#
+        );
+    }
+
+    #[test]
+    fn test_sanitize_html() {
+        let policy = policy();
+        assert_eq!(
+            sanitize_html(
+                r#"<p class="x" title='a "b"'>text</p><script>alert(1)</script>"#,
+                &policy
+            ),
+            r#"<p title="a &quot;b&quot;">text</p>"#
+        );
+        assert_eq!(
+            sanitize_html("<img src=x onerror=alert(1)>", &policy),
+            r#"<img src="x">"#
+        );
+        assert_eq!(
+            sanitize_html(r#"<a href="&#106;avascript:alert(1)">x</a>"#, &policy),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize_html("<b>tučné</b> <!-- poznámka -->a < b", &policy),
+            "tučné a &lt; b"
+        );
+        assert_eq!(sanitize_html("<span", &policy), "");
+    }
+
+    #[test]
+    fn test_sanitize_markdown_keeps_markdown() {
+        let policy = policy();
+        let source = "# Nadpis\n\n**Tučný** text a [odkaz](https://example.com).\n\n\
+            ![Fasáda](/api/blog/7/images/3)\n\n```html\n<script>kód</script>\n```\n";
+        assert_eq!(sanitize_markdown(source, &policy), source);
+    }
+
+    #[test]
+    fn test_sanitize_markdown_cleans_html_and_links() {
+        let policy = policy();
+        assert_eq!(
+            sanitize_markdown(
+                "<details>\n<summary onclick=\"x()\">Viac</summary>\n<script>\nalert(1)\n</script>\n</details>\n\nText <span onmouseover=\"x()\">a</span>",
+                &policy
+            ),
+            "<details>\n<summary>Viac</summary>\n\n</details>\n\nText <span>a</span>"
+        );
+        assert_eq!(
+            sanitize_markdown(
+                "[klik](javascript:alert(1)) a [ref]\n\n[ref]: javascript:x\n",
+                &policy
+            ),
+            "klik a \\[ref\\]\n\n\n"
+        );
+        assert_eq!(
+            sanitize_markdown("<<script>script> a `<b>` \\<b>", &policy),
+            "&lt;script> a `<b>` \\<b>"
+        );
+        assert_eq!(
+            sanitize_markdown("a <span>b <script>c</script></span>", &policy),
+            "a <span>b c</span>"
+        );
+    }
+}
@@ -7,9 +7,19 @@ diesel::table! {
         slug -> Text,
         excerpt -> Nullable<Text>,
         content -> Text,
-        image -> Nullable<Binary>,
-        image_mime -> Nullable<Varchar>,
+        word_count -> Unsigned<Integer>,
+        reading_time_minutes -> Unsigned<Integer>,
+        meta_title -> Nullable<Varchar>,
+        meta_description -> Nullable<Varchar>,
+        canonical_url -> Nullable<Varchar>,
+        og_image -> Nullable<Varchar>,
+        author_id -> Nullable<BigInt>,
         published -> Bool,
+        featured -> Bool,
+        featured_order -> Integer,
+        archived_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        view_count -> BigInt,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
@@ -24,6 +34,11 @@ diesel::table! {
         subject -> Nullable<Text>,
         message -> Text,
         created_at -> Timestamp,
+        preferred_contact -> Nullable<Varchar>,
+        spam_score -> Integer,
+        read_at -> Nullable<Timestamp>,
+        answered_at -> Nullable<Timestamp>,
+        offer_id -> Nullable<BigInt>,
     }
 }
 
@@ -38,6 +53,11 @@ diesel::table! {
         message -> Text,
         created_at -> Timestamp,
         archived_at -> Timestamp,
+        preferred_contact -> Nullable<Varchar>,
+        spam_score -> Integer,
+        read_at -> Nullable<Timestamp>,
+        answered_at -> Nullable<Timestamp>,
+        offer_id -> Nullable<BigInt>,
     }
 }
 
@@ -49,11 +69,15 @@ diesel::table! {
         excerpt -> Nullable<Text>,
         content -> Nullable<Text>,
         link -> Nullable<Text>,
-        image -> Nullable<Binary>,
-        image_mime -> Nullable<Varchar>,
         created_at -> Timestamp,
         latitude -> Nullable<Double>,
         longitude -> Nullable<Double>,
+        sort_order -> Integer,
+        active -> Bool,
+        starts_at -> Nullable<Timestamp>,
+        ends_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
     }
 }
 
@@ -64,6 +88,18 @@ diesel::table! {
         password_hash -> Varchar,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        role -> Varchar,
+        email -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    admin_password_resets (id) {
+        id -> BigInt,
+        user_id -> BigInt,
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
     }
 }
 
@@ -75,6 +111,8 @@ diesel::table! {
         expires_at -> Timestamp,
         created_at -> Timestamp,
         created_by -> Nullable<BigInt>,
+        role -> Varchar,
+        email -> Nullable<Varchar>,
     }
 }
 
@@ -92,12 +130,237 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    message_attachments (id) {
+        id -> BigInt,
+        message_id -> BigInt,
+        filename -> Varchar,
+        content_type -> Varchar,
+        size_bytes -> BigInt,
+        data -> Binary,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    message_notes (id) {
+        id -> BigInt,
+        message_id -> BigInt,
+        admin_user_id -> Nullable<BigInt>,
+        admin_username -> Varchar,
+        body -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    message_replies (id) {
+        id -> BigInt,
+        message_id -> BigInt,
+        admin_user_id -> Nullable<BigInt>,
+        admin_username -> Varchar,
+        subject -> Varchar,
+        body -> Text,
+        sent_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    labels (id) {
+        id -> BigInt,
+        name -> Varchar,
+        color -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    message_labels (message_id, label_id) {
+        message_id -> BigInt,
+        label_id -> BigInt,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> BigInt,
+        name -> Varchar,
+        slug -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    blog_post_tags (blog_post_id, tag_id) {
+        blog_post_id -> BigInt,
+        tag_id -> BigInt,
+    }
+}
+
+diesel::table! {
+    blog_post_images (id) {
+        id -> BigInt,
+        blog_post_id -> BigInt,
+        storage_key -> Varchar,
+        image_mime -> Varchar,
+        image_hash -> Varchar,
+        position -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    blog_post_revisions (id) {
+        id -> BigInt,
+        blog_post_id -> BigInt,
+        title -> Text,
+        slug -> Text,
+        excerpt -> Nullable<Text>,
+        content -> Text,
+        tags -> Text,
+        published -> Bool,
+        admin_user_id -> Nullable<BigInt>,
+        admin_username -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    email_templates (id) {
+        id -> BigInt,
+        name -> Varchar,
+        subject -> Varchar,
+        body -> Text,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> BigInt,
+        user_id -> BigInt,
+        name -> Varchar,
+        key_prefix -> Varchar,
+        key_hash -> Varchar,
+        scopes -> Varchar,
+        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> BigInt,
+        admin_user_id -> Nullable<BigInt>,
+        admin_username -> Varchar,
+        action -> Varchar,
+        entity_type -> Varchar,
+        entity_id -> Varchar,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> BigInt,
+        url -> Varchar,
+        secret -> Varchar,
+        events -> Varchar,
+        is_active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> BigInt,
+        webhook_id -> BigInt,
+        event -> Varchar,
+        payload -> Mediumtext,
+        status -> Varchar,
+        attempts -> Integer,
+        response_status -> Nullable<Integer>,
+        error -> Nullable<Text>,
+        next_attempt_at -> Timestamp,
+        created_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    offer_clicks (id) {
+        id -> BigInt,
+        offer_id -> BigInt,
+        clicked_at -> Timestamp,
+        referrer -> Nullable<Varchar>,
+        ip_address -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    offer_images (id) {
+        id -> BigInt,
+        offer_id -> BigInt,
+        storage_key -> Varchar,
+        image_mime -> Varchar,
+        image_hash -> Varchar,
+        position -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    media_blobs (storage_key) {
+        storage_key -> Varchar,
+        data -> Binary,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(admin_password_resets -> admin_users (user_id));
+diesel::joinable!(api_keys -> admin_users (user_id));
+diesel::joinable!(audit_log -> admin_users (admin_user_id));
+diesel::joinable!(blog_posts -> admin_users (author_id));
+diesel::joinable!(blog_post_images -> blog_posts (blog_post_id));
+diesel::joinable!(blog_post_revisions -> blog_posts (blog_post_id));
+diesel::joinable!(blog_post_tags -> blog_posts (blog_post_id));
+diesel::joinable!(blog_post_tags -> tags (tag_id));
+diesel::joinable!(message_labels -> labels (label_id));
+diesel::joinable!(message_notes -> admin_users (admin_user_id));
+diesel::joinable!(message_replies -> admin_users (admin_user_id));
+diesel::joinable!(offer_clicks -> offers (offer_id));
+diesel::joinable!(offer_images -> offers (offer_id));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    admin_password_resets,
     admin_user_invites,
     admin_users,
+    api_keys,
+    audit_log,
     banners,
+    blog_post_images,
+    blog_post_revisions,
+    blog_post_tags,
     blog_posts,
+    email_templates,
+    labels,
+    media_blobs,
+    message_attachments,
+    message_labels,
+    message_notes,
+    message_replies,
     messages,
     messages_archive,
+    offer_clicks,
+    offer_images,
     offers,
+    tags,
+    webhook_deliveries,
+    webhooks,
 );
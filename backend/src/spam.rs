@@ -0,0 +1,282 @@
+// Spam scoring for contact submissions. Each rule adds points; submissions
+// reaching `SPAM_REJECT_SCORE` are rejected, the rest are stored with their
+// score so admins can filter on it.
+
+use crate::config::AppConfig;
+use crate::models::ContactMessageForm;
+
+/// Points for a filled-in honeypot field; enough to reject on its own with
+/// the default threshold
+const HONEYPOT_SCORE: u32 = 100;
+const KEYWORD_SCORE: u32 = 20;
+const MAX_KEYWORD_SCORE: u32 = 60;
+/// Links beyond this many are suspicious
+const FREE_LINKS: usize = 1;
+const LINK_SCORE: u32 = 15;
+const MAX_LINK_SCORE: u32 = 60;
+const TOO_FAST_SCORE: u32 = 50;
+/// Submissions without the hidden timestamp (no JavaScript, or a bot posting
+/// directly) are only slightly suspicious
+const MISSING_TIMESTAMP_SCORE: u32 = 10;
+const DUPLICATE_SCORE: u32 = 40;
+const MAX_DUPLICATE_SCORE: u32 = 80;
+
+/// A submission together with the facts the scorer cannot derive from the
+/// form alone
+pub struct SpamInput<'a> {
+    pub form: &'a ContactMessageForm,
+    /// Unix timestamp in milliseconds of when the submission arrived
+    pub received_at_ms: i64,
+    /// Recent stored messages with the same text
+    pub recent_duplicates: i64,
+}
+
+/// A single spam heuristic
+pub trait SpamRule: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn score(&self, input: &SpamInput<'_>) -> u32;
+}
+
+/// The hidden `company` field is only ever filled in by bots
+struct HoneypotRule;
+
+impl SpamRule for HoneypotRule {
+    fn name(&self) -> &'static str {
+        "honeypot"
+    }
+
+    fn score(&self, input: &SpamInput<'_>) -> u32 {
+        if input.form.is_bot() {
+            HONEYPOT_SCORE
+        } else {
+            0
+        }
+    }
+}
+
+struct KeywordRule {
+    keywords: Vec<String>,
+}
+
+impl SpamRule for KeywordRule {
+    fn name(&self) -> &'static str {
+        "keywords"
+    }
+
+    fn score(&self, input: &SpamInput<'_>) -> u32 {
+        let text = format!(
+            "{} {}",
+            input.form.subject.as_deref().unwrap_or_default(),
+            input.form.message
+        )
+        .to_lowercase();
+        let hits = self
+            .keywords
+            .iter()
+            .filter(|keyword| text.contains(keyword.as_str()))
+            .count();
+        (hits as u32)
+            .saturating_mul(KEYWORD_SCORE)
+            .min(MAX_KEYWORD_SCORE)
+    }
+}
+
+struct LinkRule;
+
+impl LinkRule {
+    fn count_links(text: &str) -> usize {
+        let text = text.to_lowercase();
+        ["http://", "https://", "www."]
+            .iter()
+            .map(|marker| text.matches(marker).count())
+            .sum::<usize>()
+            // "https://www." is one link, not two
+            .saturating_sub(text.matches("://www.").count())
+    }
+}
+
+impl SpamRule for LinkRule {
+    fn name(&self) -> &'static str {
+        "links"
+    }
+
+    fn score(&self, input: &SpamInput<'_>) -> u32 {
+        let extra = Self::count_links(&input.form.message).saturating_sub(FREE_LINKS);
+        (extra as u32)
+            .saturating_mul(LINK_SCORE)
+            .min(MAX_LINK_SCORE)
+    }
+}
+
+/// Humans need a few seconds to fill in the form; the frontend records when
+/// the form was rendered in the hidden `form_started_at` field
+struct TimingRule {
+    min_fill_ms: i64,
+}
+
+impl SpamRule for TimingRule {
+    fn name(&self) -> &'static str {
+        "timing"
+    }
+
+    fn score(&self, input: &SpamInput<'_>) -> u32 {
+        match input.form.form_started_at {
+            None => MISSING_TIMESTAMP_SCORE,
+            Some(started_at) if input.received_at_ms - started_at < self.min_fill_ms => {
+                TOO_FAST_SCORE
+            }
+            Some(_) => 0,
+        }
+    }
+}
+
+struct DuplicateRule;
+
+impl SpamRule for DuplicateRule {
+    fn name(&self) -> &'static str {
+        "duplicates"
+    }
+
+    fn score(&self, input: &SpamInput<'_>) -> u32 {
+        u32::try_from(input.recent_duplicates.max(0))
+            .unwrap_or(u32::MAX)
+            .saturating_mul(DUPLICATE_SCORE)
+            .min(MAX_DUPLICATE_SCORE)
+    }
+}
+
+/// Sums the scores of all configured rules
+pub struct SpamScorer {
+    rules: Vec<Box<dyn SpamRule>>,
+    reject_score: u32,
+}
+
+impl SpamScorer {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let keywords = config
+            .spam_keywords
+            .split(',')
+            .map(|keyword| keyword.trim().to_lowercase())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+
+        SpamScorer {
+            rules: vec![
+                Box::new(HoneypotRule),
+                Box::new(KeywordRule { keywords }),
+                Box::new(LinkRule),
+                Box::new(TimingRule {
+                    min_fill_ms: i64::try_from(config.spam_min_fill_secs)
+                        .unwrap_or(i64::MAX)
+                        .saturating_mul(1000),
+                }),
+                Box::new(DuplicateRule),
+            ],
+            reject_score: config.spam_reject_score,
+        }
+    }
+
+    pub fn score(&self, input: &SpamInput<'_>) -> u32 {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let score = rule.score(input);
+                if score > 0 {
+                    tracing::debug!("Spam rule '{}' scored {}", rule.name(), score);
+                }
+                score
+            })
+            .fold(0u32, u32::saturating_add)
+    }
+
+    /// Whether a submission with this score is refused outright. A zero
+    /// threshold never rejects.
+    pub fn rejects(&self, score: u32) -> bool {
+        self.reject_score > 0 && score >= self.reject_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(overrides: serde_json::Value) -> AppConfig {
+        let mut config = serde_json::json!({
+            "database_url": "mysql://localhost/test",
+            "redis_url": "redis://localhost",
+        });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        serde_json::from_value(config).expect("valid config")
+    }
+
+    fn form(message: &str, form_started_at: Option<i64>) -> ContactMessageForm {
+        ContactMessageForm {
+            company: None,
+            name: "Jana".to_string(),
+            email: "jana@example.com".to_string(),
+            phone: None,
+            subject: None,
+            message: message.to_string(),
+            preferred_contact: None,
+            offer_id: None,
+            captcha_token: None,
+            form_started_at,
+        }
+    }
+
+    fn input(form: &ContactMessageForm, recent_duplicates: i64) -> SpamInput<'_> {
+        SpamInput {
+            form,
+            received_at_ms: 60_000,
+            recent_duplicates,
+        }
+    }
+
+    #[test]
+    fn test_legitimate_submission_scores_zero() {
+        let scorer = SpamScorer::from_config(&config(serde_json::json!({})));
+        let form = form("Dobrý deň, mám záujem o ponuku.", Some(10_000));
+        assert_eq!(scorer.score(&input(&form, 0)), 0);
+    }
+
+    #[test]
+    fn test_rules_add_up() {
+        let scorer = SpamScorer::from_config(&config(serde_json::json!({
+            "spam_keywords": "casino, Backlinks",
+        })));
+        let form = form(
+            "Best CASINO backlinks: https://www.a.test http://b.test www.c.test",
+            Some(59_000),
+        );
+        // 2 keywords + 2 extra links + too fast + 1 duplicate
+        assert_eq!(scorer.score(&input(&form, 1)), 40 + 30 + 50 + 40);
+    }
+
+    #[test]
+    fn test_honeypot_and_threshold() {
+        let scorer = SpamScorer::from_config(&config(serde_json::json!({})));
+        let mut bot = form("Hello", Some(10_000));
+        bot.company = Some("Acme".to_string());
+        let score = scorer.score(&input(&bot, 0));
+        assert!(scorer.rejects(score));
+        assert!(!scorer.rejects(MISSING_TIMESTAMP_SCORE));
+
+        let lenient = SpamScorer::from_config(&config(serde_json::json!({
+            "spam_reject_score": 0,
+        })));
+        assert!(!lenient.rejects(score));
+    }
+
+    #[test]
+    fn test_missing_timestamp_and_duplicate_caps() {
+        let scorer = SpamScorer::from_config(&config(serde_json::json!({})));
+        let form = form("Hello", None);
+        assert_eq!(
+            scorer.score(&input(&form, 5)),
+            MISSING_TIMESTAMP_SCORE + MAX_DUPLICATE_SCORE
+        );
+    }
+}
@@ -1,39 +1,231 @@
 // Utility functions for common operations
 
-use image::{GenericImageView, ImageFormat, ImageReader, imageops::FilterType};
+use chrono::{NaiveDate, NaiveDateTime};
+use hmac::{Hmac, Mac};
+use image::metadata::Orientation;
+use image::{
+    DynamicImage, GenericImageView, ImageDecoder, ImageFormat, ImageReader, imageops::FilterType,
+};
+use rocket::fs::TempFile;
+use rocket::http::{Accept, ContentType, MediaType};
 use rocket::tokio::io::AsyncReadExt;
-use rocket::{fs::TempFile, http::ContentType};
+use sha2::{Digest, Sha256};
 use std::io::Cursor;
+use std::net::IpAddr;
 
+use crate::config::AppConfig;
 use crate::error::{AppError, AppResult};
 
 /// Maximum dimension (width or height) for uploaded images
 const MAX_IMAGE_DIMENSION: u32 = 1920;
 /// JPEG quality for compression (0-100)
 const JPEG_QUALITY: u8 = 85;
+/// Maximum dimension of the `thumb` variant of an image
+const THUMB_DIMENSION: u32 = 320;
+/// Maximum dimension of the `medium` variant of an image
+const MEDIUM_DIMENSION: u32 = 960;
+/// Longest accepted search query
+const MAX_SEARCH_QUERY_LEN: usize = 200;
 
-/// Validate and process an uploaded image file with compression and resizing
-pub async fn process_image_upload<'r>(
-    temp_file: Option<TempFile<'r>>,
-) -> AppResult<Option<(Vec<u8>, String)>> {
-    let temp_file = match temp_file {
-        Some(file) => file,
-        None => return Ok(None),
-    };
+/// Variant of a stored image, chosen with the `size` parameter of the image
+/// endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSize {
+    Thumb,
+    Medium,
+    Full,
+}
+
+impl ImageSize {
+    /// Parse the `size` parameter. Defaults to the full image.
+    pub fn parse(value: Option<&str>) -> AppResult<Self> {
+        match value.map(str::trim).filter(|value| !value.is_empty()) {
+            None | Some("full") => Ok(ImageSize::Full),
+            Some("medium") => Ok(ImageSize::Medium),
+            Some("thumb") => Ok(ImageSize::Thumb),
+            Some(_) => Err(AppError::InvalidInput(
+                "Size must be one of thumb, medium or full.".to_string(),
+            )),
+        }
+    }
+
+    fn max_dimension(self) -> u32 {
+        match self {
+            ImageSize::Thumb => THUMB_DIMENSION,
+            ImageSize::Medium => MEDIUM_DIMENSION,
+            ImageSize::Full => MAX_IMAGE_DIMENSION,
+        }
+    }
+
+    /// Hash of this variant of an image with the given hash, served as its
+    /// `ETag`. Variants are derived from the full image, so they change
+    /// together with it.
+    pub fn variant_hash(self, image_hash: &str) -> String {
+        match self {
+            ImageSize::Thumb => format!("{image_hash}-thumb"),
+            ImageSize::Medium => format!("{image_hash}-medium"),
+            ImageSize::Full => image_hash.to_string(),
+        }
+    }
+
+    /// Media store key of this variant of the image stored under `key`
+    pub fn variant_key(self, key: &str) -> String {
+        match self {
+            ImageSize::Thumb => format!("{key}.thumb"),
+            ImageSize::Medium => format!("{key}.medium"),
+            ImageSize::Full => key.to_string(),
+        }
+    }
+
+    /// Media store key of the JPEG copy of this variant, saved for clients
+    /// that do not accept the type the image is stored in
+    pub fn jpeg_fallback_key(self, key: &str) -> String {
+        format!("{}.jpeg", self.variant_key(key))
+    }
+}
+
+/// Format images are stored in, chosen with `IMAGE_OUTPUT_FORMAT`. JPEG stays
+/// the default: the `image` crate encodes WebP only losslessly, which makes
+/// photos larger than JPEG at `JPEG_QUALITY`. AVIF is not offered, as its
+/// encoder is slow enough to stall uploads and adds a large dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageOutputFormat {
+    Jpeg,
+    /// Lossless WebP, which keeps transparency
+    WebP,
+}
+
+impl ImageOutputFormat {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Some(ImageOutputFormat::Jpeg),
+            "webp" => Some(ImageOutputFormat::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ImageOutputFormat::Jpeg => "image/jpeg",
+            ImageOutputFormat::WebP => "image/webp",
+        }
+    }
+
+    /// Format of stored image data of the given type
+    fn of_mime(mime: &str) -> Self {
+        if mime.eq_ignore_ascii_case("image/webp") {
+            ImageOutputFormat::WebP
+        } else {
+            ImageOutputFormat::Jpeg
+        }
+    }
+}
+
+/// How uploaded images are checked and processed
+#[derive(Debug, Clone)]
+pub struct ImageSettings {
+    pub output_format: ImageOutputFormat,
+    /// Largest accepted image file in bytes
+    pub max_upload_bytes: u64,
+    /// Largest accepted width or height, and largest number of pixels, of
+    /// an uploaded image. Checked before decoding so that small files
+    /// expanding into huge bitmaps are rejected.
+    pub max_source_dimension: u32,
+    pub max_source_pixels: u64,
+}
+
+impl ImageSettings {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let output_format =
+            ImageOutputFormat::parse(&config.image_output_format).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Unknown IMAGE_OUTPUT_FORMAT '{}', storing images as JPEG",
+                    config.image_output_format
+                );
+                ImageOutputFormat::Jpeg
+            });
+        ImageSettings {
+            output_format,
+            max_upload_bytes: config.image_max_upload_bytes,
+            max_source_dimension: config.image_max_source_dimension,
+            max_source_pixels: config.image_max_source_pixels,
+        }
+    }
+}
+
+/// A processed image with its smaller variants, all of type `mime`
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub mime: String,
+    pub thumb: Vec<u8>,
+    pub medium: Vec<u8>,
+}
+
+/// Reason an uploaded image was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageRejection {
+    UnsupportedFormat,
+    /// The file content is not an image of the type it was sent as
+    TypeMismatch,
+    TooLarge,
+    TooManyPixels,
+    Corrupt,
+}
 
-    let content_type = temp_file.content_type().cloned().or_else(|| {
+impl ImageRejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            ImageRejection::UnsupportedFormat => "unsupported_format",
+            ImageRejection::TypeMismatch => "type_mismatch",
+            ImageRejection::TooLarge => "too_large",
+            ImageRejection::TooManyPixels => "too_many_pixels",
+            ImageRejection::Corrupt => "corrupt",
+        }
+    }
+}
+
+impl From<ImageRejection> for AppError {
+    fn from(rejection: ImageRejection) -> Self {
+        match rejection {
+            ImageRejection::UnsupportedFormat => AppError::UnsupportedMediaType,
+            ImageRejection::TypeMismatch => {
+                AppError::InvalidInput("Image content does not match its file type".to_string())
+            }
+            ImageRejection::TooLarge => {
+                AppError::InvalidInput("Image file is too large".to_string())
+            }
+            ImageRejection::TooManyPixels => {
+                AppError::InvalidInput("Image dimensions are too large".to_string())
+            }
+            ImageRejection::Corrupt => AppError::InvalidInput("Failed to decode image".to_string()),
+        }
+    }
+}
+
+/// Determine the content type of an upload from its header or file extension
+fn upload_content_type(temp_file: &TempFile<'_>) -> Option<ContentType> {
+    temp_file.content_type().cloned().or_else(|| {
         temp_file
             .name()
             .and_then(|n| n.split('.').next_back()) // Get extension
             .and_then(ContentType::from_extension)
-    });
+    })
+}
 
-    // Validate against allowed list
-    let final_ct = content_type
-        .filter(|ct| ct.is_jpeg() || ct.is_png() || ct.is_gif())
-        .ok_or(AppError::UnsupportedMediaType)?;
+/// Map an allowed image content type to its decoder format
+fn image_format(content_type: Option<&ContentType>) -> Result<ImageFormat, ImageRejection> {
+    match content_type {
+        Some(ct) if ct.is_png() => Ok(ImageFormat::Png),
+        Some(ct) if ct.is_gif() => Ok(ImageFormat::Gif),
+        Some(ct) if ct.is_jpeg() => Ok(ImageFormat::Jpeg),
+        Some(ct) if ct.is_webp() => Ok(ImageFormat::WebP),
+        _ => Err(ImageRejection::UnsupportedFormat),
+    }
+}
 
-    // Read the file into a buffer
+/// Read an uploaded file into memory
+pub(crate) async fn read_upload(temp_file: &TempFile<'_>) -> AppResult<Vec<u8>> {
     let mut buffer = Vec::new();
     let mut file = temp_file.open().await.map_err(|e| {
         tracing::error!("Failed to open uploaded file: {}", e);
@@ -45,84 +237,444 @@ pub async fn process_image_upload<'r>(
         AppError::Io(e)
     })?;
 
-    // Process and compress the image
-    let (compressed_buffer, mime_type) = compress_image(buffer, &final_ct)?;
+    Ok(buffer)
+}
 
-    tracing::info!(
-        "Image processed: original type={}, final type={}, size={} bytes",
-        final_ct,
-        mime_type,
-        compressed_buffer.len()
-    );
+/// Check that the magic bytes of `buffer` are those of `format`, so a file
+/// is only decoded as the type it was uploaded as
+fn check_magic_bytes(buffer: &[u8], format: ImageFormat) -> Result<(), ImageRejection> {
+    match image::guess_format(buffer) {
+        Ok(detected) if detected == format => Ok(()),
+        detected => {
+            tracing::warn!(
+                "Rejected image sent as {:?} with content of type {:?}",
+                format,
+                detected.ok()
+            );
+            Err(ImageRejection::TypeMismatch)
+        }
+    }
+}
+
+/// Check the size, type and dimensions of an uploaded image and decode it.
+/// The EXIF orientation is applied to the pixels, as the metadata itself is
+/// not kept when the image is encoded again.
+fn decode_image(
+    buffer: &[u8],
+    format: ImageFormat,
+    settings: &ImageSettings,
+) -> Result<DynamicImage, ImageRejection> {
+    if buffer.len() as u64 > settings.max_upload_bytes {
+        return Err(ImageRejection::TooLarge);
+    }
+    check_magic_bytes(buffer, format)?;
 
-    Ok(Some((compressed_buffer, mime_type)))
+    let mut decoder = ImageReader::with_format(Cursor::new(buffer), format)
+        .into_decoder()
+        .map_err(|e| {
+            tracing::debug!("Failed to read image header: {}", e);
+            ImageRejection::Corrupt
+        })?;
+    let (width, height) = decoder.dimensions();
+    if width.max(height) > settings.max_source_dimension
+        || u64::from(width) * u64::from(height) > settings.max_source_pixels
+    {
+        tracing::warn!("Rejected image with {}x{} pixels", width, height);
+        return Err(ImageRejection::TooManyPixels);
+    }
+
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder).map_err(|e| {
+        tracing::error!("Failed to decode image: {}", e);
+        ImageRejection::Corrupt
+    })?;
+    img.apply_orientation(orientation);
+    Ok(img)
 }
 
-/// Compress and resize an image if necessary
-fn compress_image(buffer: Vec<u8>, content_type: &ContentType) -> AppResult<(Vec<u8>, String)> {
-    let image_format = if content_type.is_png() {
-        ImageFormat::Png
-    } else if content_type.is_gif() {
-        ImageFormat::Gif
-    } else if content_type.is_jpeg() {
-        ImageFormat::Jpeg
-    } else {
-        return Err(AppError::UnsupportedMediaType);
+/// Run the upload checks on an image without storing or recompressing it.
+/// Returns the image dimensions if it is acceptable.
+pub async fn validate_image_upload(
+    temp_file: &TempFile<'_>,
+    settings: &ImageSettings,
+) -> AppResult<Result<(u32, u32), ImageRejection>> {
+    let format = match image_format(upload_content_type(temp_file).as_ref()) {
+        Ok(format) => format,
+        Err(rejection) => return Ok(Err(rejection)),
     };
+    if temp_file.len() > settings.max_upload_bytes {
+        return Ok(Err(ImageRejection::TooLarge));
+    }
 
-    // Load the image
-    let img = ImageReader::with_format(Cursor::new(&buffer), image_format)
+    let buffer = read_upload(temp_file).await?;
+    Ok(decode_image(&buffer, format, settings).map(|img| img.dimensions()))
+}
+
+/// Validate and process an uploaded image file with compression and resizing
+pub async fn process_image_upload<'r>(
+    temp_file: Option<TempFile<'r>>,
+    settings: &ImageSettings,
+) -> AppResult<Option<ProcessedImage>> {
+    let temp_file = match temp_file {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+
+    // Validate against allowed list and size limit before reading the file
+    let content_type = upload_content_type(&temp_file);
+    image_format(content_type.as_ref())?;
+    if temp_file.len() > settings.max_upload_bytes {
+        return Err(ImageRejection::TooLarge.into());
+    }
+
+    let buffer = read_upload(&temp_file).await?;
+    process_image_bytes(&buffer, content_type.as_ref(), settings).map(Some)
+}
+
+/// Validate, compress and resize image data of the given type, e.g. a file
+/// taken from an uploaded archive, and generate its smaller variants
+pub fn process_image_bytes(
+    buffer: &[u8],
+    content_type: Option<&ContentType>,
+    settings: &ImageSettings,
+) -> AppResult<ProcessedImage> {
+    let format = image_format(content_type)?;
+    let img = decode_image(buffer, format, settings)?;
+    tracing::debug!("Original image dimensions: {:?}", img.dimensions());
+
+    let output = settings.output_format;
+    let img = fit_within(img, MAX_IMAGE_DIMENSION);
+    let data = encode_image(&img, output)?;
+    let medium = fit_within(img, MEDIUM_DIMENSION);
+    let thumb = encode_image(&fit_within(medium.clone(), THUMB_DIMENSION), output)?;
+    let medium = encode_image(&medium, output)?;
+
+    tracing::info!(
+        "Image processed: original format={:?}, final type={}, size={} bytes",
+        format,
+        output.mime(),
+        data.len()
+    );
+
+    Ok(ProcessedImage {
+        data,
+        mime: output.mime().to_string(),
+        thumb,
+        medium,
+    })
+}
+
+/// Decode stored image data of the given type. Stored images were checked
+/// and resized when they were uploaded, so the upload limits do not apply.
+fn decode_stored_image(data: &[u8], image_mime: &str) -> AppResult<DynamicImage> {
+    let format =
+        ImageFormat::from_mime_type(image_mime).ok_or(ImageRejection::UnsupportedFormat)?;
+    ImageReader::with_format(Cursor::new(data), format)
         .decode()
         .map_err(|e| {
-            tracing::error!("Failed to decode image: {}", e);
-            AppError::InvalidInput("Failed to decode image".to_string())
-        })?;
+            tracing::error!("Failed to decode stored image: {}", e);
+            ImageRejection::Corrupt.into()
+        })
+}
+
+/// Generate a variant of a stored image in its own format, for images stored
+/// before their variants were
+pub fn stored_image_variant(data: &[u8], image_mime: &str, size: ImageSize) -> AppResult<Vec<u8>> {
+    let img = decode_stored_image(data, image_mime)?;
+    encode_image(
+        &fit_within(img, size.max_dimension()),
+        ImageOutputFormat::of_mime(image_mime),
+    )
+}
+
+/// Re-encode stored image data as JPEG for clients that do not accept its
+/// type
+pub fn transcode_to_jpeg(data: &[u8], image_mime: &str) -> AppResult<Vec<u8>> {
+    let img = decode_stored_image(data, image_mime)?;
+    encode_image(&img, ImageOutputFormat::Jpeg)
+}
 
+/// Whether stored image data of type `mime` has to be sent as JPEG because
+/// the `Accept` header of the request does not allow its type
+pub fn needs_jpeg_fallback(accept: Option<&Accept>, mime: &str) -> bool {
+    !mime.eq_ignore_ascii_case("image/jpeg") && !accepts_image(accept, mime)
+}
+
+/// Whether the `Accept` header of a request allows an image of type `mime`.
+/// Requests without the header accept any type.
+fn accepts_image(accept: Option<&Accept>, mime: &str) -> bool {
+    let (Some(accept), Some(wanted)) = (accept, MediaType::parse_flexible(mime)) else {
+        return true;
+    };
+    accept
+        .iter()
+        .filter(|accepted| accepted.weight_or(1.0) > 0.0)
+        .map(|accepted| accepted.media_type())
+        .any(|accepted| {
+            (accepted.top() == "*" || accepted.top() == wanted.top())
+                && (accepted.sub() == "*" || accepted.sub() == wanted.sub())
+        })
+}
+
+/// Scale an image down to fit `max_dimension`, keeping its aspect ratio
+fn fit_within(img: DynamicImage, max_dimension: u32) -> DynamicImage {
     let (width, height) = img.dimensions();
-    tracing::debug!("Original image dimensions: {}x{}", width, height);
-
-    // Resize if image is too large
-    let img = if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
-        let (new_width, new_height) = if width > height {
-            let ratio = height as f32 / width as f32;
-            (
-                MAX_IMAGE_DIMENSION,
-                (MAX_IMAGE_DIMENSION as f32 * ratio) as u32,
-            )
-        } else {
-            let ratio = width as f32 / height as f32;
-            (
-                (MAX_IMAGE_DIMENSION as f32 * ratio) as u32,
-                MAX_IMAGE_DIMENSION,
-            )
-        };
+    if width <= max_dimension && height <= max_dimension {
+        return img;
+    }
 
-        tracing::info!(
-            "Resizing image from {}x{} to {}x{}",
-            width,
-            height,
-            new_width,
-            new_height
-        );
-        img.resize(new_width, new_height, FilterType::Lanczos3)
+    let (new_width, new_height) = if width > height {
+        let ratio = height as f32 / width as f32;
+        (max_dimension, (max_dimension as f32 * ratio) as u32)
     } else {
-        img
+        let ratio = width as f32 / height as f32;
+        ((max_dimension as f32 * ratio) as u32, max_dimension)
     };
 
+    tracing::debug!(
+        "Resizing image from {}x{} to {}x{}",
+        width,
+        height,
+        new_width,
+        new_height
+    );
+    img.resize(new_width, new_height, FilterType::Lanczos3)
+}
+
+/// Encode an image in the given format
+fn encode_image(img: &DynamicImage, format: ImageOutputFormat) -> AppResult<Vec<u8>> {
     let mut output_buffer = Vec::new();
     let mut cursor = Cursor::new(&mut output_buffer);
 
-    // Always convert to JPEG for consistent compression and storage
-    let rgb_img = image::DynamicImage::ImageRgb8(img.to_rgb8());
-    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, JPEG_QUALITY);
-    rgb_img.write_with_encoder(encoder).map_err(|e| {
-        tracing::error!("Failed to encode JPEG: {}", e);
+    let encoded = match format {
+        ImageOutputFormat::Jpeg => {
+            let rgb_img = DynamicImage::ImageRgb8(img.to_rgb8());
+            let encoder =
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, JPEG_QUALITY);
+            rgb_img.write_with_encoder(encoder)
+        }
+        ImageOutputFormat::WebP => {
+            // The encoder takes 8-bit RGB or RGBA only
+            let img = if img.color().has_alpha() {
+                DynamicImage::ImageRgba8(img.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(img.to_rgb8())
+            };
+            img.write_with_encoder(image::codecs::webp::WebPEncoder::new_lossless(&mut cursor))
+        }
+    };
+    encoded.map_err(|e| {
+        tracing::error!("Failed to encode {}: {}", format.mime(), e);
         AppError::InvalidInput("Failed to encode image".to_string())
     })?;
 
-    let mime_type = "image/jpeg";
+    Ok(output_buffer)
+}
+
+/// Hex SHA-256 of stored image data, kept next to the image and served as
+/// its `ETag`
+pub fn image_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// HMAC-SHA256 (RFC 2104) of `message`
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// HMAC-SHA256 (RFC 2104) of `message`, hex encoded
+pub fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    hmac_sha256(key, message)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Trimmed search query, required and at most `MAX_SEARCH_QUERY_LEN`
+/// characters long
+pub fn search_query(raw: &str) -> AppResult<&str> {
+    let query = raw.trim();
+    if query.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Search query is required.".to_string(),
+        ));
+    }
+    if query.chars().count() > MAX_SEARCH_QUERY_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Search query must be at most {MAX_SEARCH_QUERY_LEN} characters."
+        )));
+    }
+    Ok(query)
+}
+
+/// `LIKE` pattern matching `text` anywhere, with wildcards in it escaped
+pub fn contains_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Normalize a slug from a request path for lookup: trims whitespace and
+/// trailing slashes and lowercases it
+pub fn normalize_slug(slug: &str) -> String {
+    slug.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// Longest slug generated from a title
+const MAX_GENERATED_SLUG_LEN: usize = 96;
 
-    Ok((output_buffer, mime_type.to_string()))
+/// ASCII spelling of a Latin letter with diacritics, for slugs
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'č' | 'ć' | 'ç' => "c",
+        'ď' | 'đ' => "d",
+        'é' | 'è' | 'ê' | 'ë' | 'ě' | 'ē' | 'ę' => "e",
+        'í' | 'ì' | 'î' | 'ï' | 'ī' => "i",
+        'ĺ' | 'ľ' | 'ł' => "l",
+        'ň' | 'ń' | 'ñ' => "n",
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ő' | 'ø' => "o",
+        'ŕ' | 'ř' => "r",
+        'š' | 'ś' | 'ş' => "s",
+        'ť' | 'ţ' => "t",
+        'ú' | 'ù' | 'û' | 'ü' | 'ů' | 'ű' | 'ū' => "u",
+        'ý' | 'ÿ' => "y",
+        'ž' | 'ź' | 'ż' => "z",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        _ => return None,
+    })
+}
+
+/// URL slug of a title: transliterated to ASCII, lowercase, with every run
+/// of other characters replaced by a single dash
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if let Some(ascii) = transliterate(c) {
+            slug.push_str(ascii);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_GENERATED_SLUG_LEN {
+            break;
+        }
+    }
+    slug.truncate(MAX_GENERATED_SLUG_LEN);
+    slug.trim_end_matches('-').to_string()
+}
+
+/// `base`, or `base-2`, `base-3`, ... when the previous ones are taken
+pub fn next_free_slug(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|slug| slug == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("an unused suffix exists")
+}
+
+/// Map a failed write to `SlugConflict` when it hit the unique slug index;
+/// other unique indexes give a plain `Conflict`
+pub fn map_slug_write_error(error: diesel::result::Error, slug: &str) -> AppError {
+    match AppError::from(error) {
+        AppError::Conflict { field, .. } if field == "slug" => AppError::SlugConflict {
+            slug: slug.to_string(),
+            suggested_slug: None,
+        },
+        other => other,
+    }
+}
+
+/// Alphabet for base62 short link codes
+const SHORT_CODE_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Resource a short link code points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortLinkTarget {
+    Offer(i64),
+    BlogPost(i64),
+}
+
+impl ShortLinkTarget {
+    fn prefix(&self) -> char {
+        match self {
+            ShortLinkTarget::Offer(_) => 'o',
+            ShortLinkTarget::BlogPost(_) => 'b',
+        }
+    }
+
+    /// Encode as a short code: a type prefix followed by the base62 id.
+    /// Codes are derived from the id, so they are stable and never collide.
+    pub fn short_code(&self) -> String {
+        let (ShortLinkTarget::Offer(id) | ShortLinkTarget::BlogPost(id)) = *self;
+        let mut value = id.max(0) as u64;
+        let mut digits = Vec::new();
+        loop {
+            digits.push(SHORT_CODE_ALPHABET[(value % 62) as usize] as char);
+            value /= 62;
+            if value == 0 {
+                break;
+            }
+        }
+
+        std::iter::once(self.prefix())
+            .chain(digits.into_iter().rev())
+            .collect()
+    }
+
+    /// Decode a short code, rejecting unknown prefixes and non-canonical
+    /// encodings so that each resource has exactly one code
+    pub fn from_short_code(code: &str) -> Option<Self> {
+        let mut chars = code.chars();
+        let prefix = chars.next()?;
+        let digits = chars.as_str();
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut id: i64 = 0;
+        for byte in digits.bytes() {
+            let digit = SHORT_CODE_ALPHABET.iter().position(|&c| c == byte)?;
+            id = id.checked_mul(62)?.checked_add(digit as i64)?;
+        }
+
+        let target = match prefix {
+            'o' => ShortLinkTarget::Offer(id),
+            'b' => ShortLinkTarget::BlogPost(id),
+            _ => return None,
+        };
+
+        (target.short_code() == code).then_some(target)
+    }
+
+    /// Public page the short link redirects to
+    pub fn page_path(&self, slug: &str) -> String {
+        match self {
+            ShortLinkTarget::Offer(_) => format!("/offer/{slug}"),
+            ShortLinkTarget::BlogPost(_) => format!("/blog/{slug}"),
+        }
+    }
+}
+
+/// Whether `url` is an absolute http(s) URL with a host and no whitespace
+pub fn is_http_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    let has_host = ["https://", "http://"].iter().any(|scheme| {
+        lower
+            .strip_prefix(scheme)
+            .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+    });
+    has_host && !url.chars().any(char::is_whitespace)
 }
 
 /// Validate an email address format
@@ -140,9 +692,280 @@ pub fn validate_not_empty(s: &str) -> bool {
     !s.trim().is_empty()
 }
 
+/// Whether a string contains control characters. Line breaks and tabs are
+/// tolerated when `allow_line_breaks` is set, e.g. for multi-line text.
+pub fn has_control_chars(s: &str, allow_line_breaks: bool) -> bool {
+    s.chars()
+        .any(|c| c.is_control() && !(allow_line_breaks && matches!(c, '\n' | '\r' | '\t')))
+}
+
+/// Detect the type of a contact form attachment from its leading bytes.
+/// Returns the content type and file extension for PDFs, PNG, JPEG and WebP
+/// images, and UTF-8 plain text; anything else is refused.
+pub fn attachment_type(data: &[u8]) -> Option<(&'static str, &'static str)> {
+    if data.starts_with(b"%PDF-") {
+        Some(("application/pdf", "pdf"))
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("image/png", "png"))
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some(("image/jpeg", "jpg"))
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some(("image/webp", "webp"))
+    } else if std::str::from_utf8(data).is_ok_and(|text| !has_control_chars(text, true)) {
+        Some(("text/plain; charset=utf-8", "txt"))
+    } else {
+        None
+    }
+}
+
+/// Whether the first `prefix_len` bits of `ip` match `network`
+pub fn ip_in_network(ip: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
+    match (ip.to_canonical(), network.to_canonical()) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix_len <= 32 => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_len))
+                .unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix_len <= 128 => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_len))
+                .unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// An address with its host part zeroed: the last octet of IPv4 addresses
+/// and all but the first 48 bits of IPv6 addresses
+pub fn anonymize_ip(ip: IpAddr) -> IpAddr {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => IpAddr::V4((u32::from(ip) & 0xffff_ff00).into()),
+        IpAddr::V6(ip) => IpAddr::V6((u128::from(ip) & (u128::MAX << 80)).into()),
+    }
+}
+
+/// Check an IP against a comma-separated list of addresses, CIDR ranges such
+/// as `10.0.0.0/8`, or `*` for any address
+pub fn ip_in_list(list: &str, ip: IpAddr) -> bool {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            match entry.split_once('/') {
+                Some((network, prefix_len)) => {
+                    match (network.parse::<IpAddr>(), prefix_len.parse::<u8>()) {
+                        (Ok(network), Ok(prefix_len)) => ip_in_network(ip, network, prefix_len),
+                        _ => false,
+                    }
+                }
+                None => entry
+                    .parse::<IpAddr>()
+                    .is_ok_and(|entry| entry.to_canonical() == ip.to_canonical()),
+            }
+        })
+}
+
+/// Whether an address is reachable on the public internet, as opposed to
+/// loopback, private, link-local, shared or unspecified addresses that lead
+/// into the server's own network
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || first == 0
+                || (first == 100 && (64..128).contains(&second)))
+        }
+        IpAddr::V6(ip) => {
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.is_multicast())
+        }
+    }
+}
+
+/// Timestamp bounds from two optional `YYYY-MM-DD` dates: from the start of
+/// `from` up to, but excluding, the day after `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
+
+impl DateRange {
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> AppResult<Self> {
+        fn date(value: Option<&str>) -> AppResult<Option<NaiveDate>> {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                        AppError::InvalidInput("Dates must be given as YYYY-MM-DD.".to_string())
+                    })
+                })
+                .transpose()
+        }
+
+        let from = date(from)?;
+        let to = date(to)?;
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err(AppError::InvalidInput(
+                "The start date must not be after the end date.".to_string(),
+            ));
+        }
+
+        Ok(DateRange {
+            from: from.and_then(|day| day.and_hms_opt(0, 0, 0)),
+            until: to
+                .and_then(|day| day.succ_opt())
+                .and_then(|day| day.and_hms_opt(0, 0, 0)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_hmac_sha256_matches_rfc_4231() {
+        // Test cases 1 and 6 of RFC 4231
+        assert_eq!(
+            hmac_sha256_hex(&[0x0b; 20], b"Hi There"),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hmac_sha256_hex(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_search_query() {
+        assert_eq!(search_query("  okná  ").unwrap(), "okná");
+        assert!(search_query("   ").is_err());
+        assert!(search_query(&"x".repeat(MAX_SEARCH_QUERY_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_contains_pattern() {
+        assert_eq!(contains_pattern("fasáda"), "%fasáda%");
+        assert_eq!(contains_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+    }
+
+    #[test]
+    fn test_short_code_round_trip() {
+        for id in [0, 1, 61, 62, 3843, 3844, 1_000_000, i64::MAX] {
+            for target in [ShortLinkTarget::Offer(id), ShortLinkTarget::BlogPost(id)] {
+                assert_eq!(
+                    ShortLinkTarget::from_short_code(&target.short_code()),
+                    Some(target)
+                );
+            }
+        }
+
+        assert_eq!(ShortLinkTarget::Offer(62).short_code(), "o10");
+        assert_eq!(ShortLinkTarget::BlogPost(61).short_code(), "bz");
+    }
+
+    #[test]
+    fn test_anonymize_ip() {
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+
+        assert_eq!(anonymize_ip(ip("203.0.113.57")), ip("203.0.113.0"));
+        assert_eq!(anonymize_ip(ip("::ffff:203.0.113.57")), ip("203.0.113.0"));
+        assert_eq!(
+            anonymize_ip(ip("2001:db8:1234:5678::1")),
+            ip("2001:db8:1234::")
+        );
+    }
+
+    #[test]
+    fn test_ip_in_list() {
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+
+        assert!(ip_in_list("127.0.0.1, ::1", ip("::1")));
+        assert!(ip_in_list("10.0.0.0/8", ip("10.20.30.40")));
+        assert!(!ip_in_list("10.0.0.0/8", ip("11.0.0.1")));
+        assert!(ip_in_list("172.16.0.0/12", ip("::ffff:172.20.0.5")));
+        assert!(ip_in_list("2001:db8::/32", ip("2001:db8:1::1")));
+        assert!(!ip_in_list("2001:db8::/32", ip("10.0.0.1")));
+        assert!(ip_in_list("0.0.0.0/0", ip("203.0.113.7")));
+        assert!(ip_in_list("*", ip("203.0.113.7")));
+        assert!(!ip_in_list("10.0.0.0/40, bogus", ip("10.0.0.1")));
+        assert!(!ip_in_list("", ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+
+        assert!(is_public_ip(ip("93.184.215.14")));
+        assert!(is_public_ip(ip("2606:2800:21f:cb07:6820:80da:af6b:8b2c")));
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip(address)), "{address}");
+        }
+    }
+
+    #[test]
+    fn test_short_codes_are_unique() {
+        let codes: HashSet<String> = (1..5000)
+            .flat_map(|id| [ShortLinkTarget::Offer(id), ShortLinkTarget::BlogPost(id)])
+            .map(|target| target.short_code())
+            .collect();
+
+        assert_eq!(codes.len(), 2 * 4999);
+    }
+
+    #[test]
+    fn test_invalid_short_codes() {
+        assert_eq!(ShortLinkTarget::from_short_code(""), None);
+        assert_eq!(ShortLinkTarget::from_short_code("o"), None);
+        assert_eq!(ShortLinkTarget::from_short_code("x1"), None);
+        assert_eq!(ShortLinkTarget::from_short_code("o-1"), None);
+        assert_eq!(ShortLinkTarget::from_short_code("o01"), None);
+        assert_eq!(ShortLinkTarget::from_short_code("ozzzzzzzzzzzzz"), None);
+    }
+
+    #[test]
+    fn test_short_link_page_path() {
+        let offer = ShortLinkTarget::from_short_code("o1").unwrap();
+        assert_eq!(offer.page_path("spring-sale"), "/offer/spring-sale");
+
+        let post = ShortLinkTarget::from_short_code("b1").unwrap();
+        assert_eq!(post.page_path("hello-world"), "/blog/hello-world");
+    }
 
     #[test]
     fn test_validate_email() {
@@ -156,6 +979,321 @@ mod tests {
         assert!(!validate_email("   "));
     }
 
+    #[test]
+    fn test_has_control_chars() {
+        assert!(!has_control_chars("Plain text", false));
+        assert!(has_control_chars("line\nbreak", false));
+        assert!(!has_control_chars("line\r\nbreak\tand tab", true));
+        assert!(has_control_chars("null\0byte", true));
+        assert!(has_control_chars("escape\u{1b}[31m", true));
+    }
+
+    fn encode_test_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let img = DynamicImage::new_rgb8(width, height);
+        let mut buffer = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buffer), format)
+            .expect("Failed to encode test image");
+        buffer
+    }
+
+    #[test]
+    fn test_image_format_accepts_supported_types() {
+        assert_eq!(image_format(Some(&ContentType::PNG)), Ok(ImageFormat::Png));
+        assert_eq!(
+            image_format(Some(&ContentType::JPEG)),
+            Ok(ImageFormat::Jpeg)
+        );
+        assert_eq!(image_format(Some(&ContentType::GIF)), Ok(ImageFormat::Gif));
+        assert_eq!(
+            image_format(Some(&ContentType::WEBP)),
+            Ok(ImageFormat::WebP)
+        );
+    }
+
+    #[test]
+    fn test_image_format_rejects_unsupported_types() {
+        assert_eq!(
+            image_format(Some(&ContentType::PDF)),
+            Err(ImageRejection::UnsupportedFormat)
+        );
+        assert_eq!(image_format(None), Err(ImageRejection::UnsupportedFormat));
+    }
+
+    #[test]
+    fn test_decode_image_accepts_valid_image() {
+        let buffer = encode_test_image(40, 30, ImageFormat::Png);
+        let img = decode_image(&buffer, ImageFormat::Png, &jpeg_settings())
+            .expect("Image should be accepted");
+        assert_eq!(img.dimensions(), (40, 30));
+    }
+
+    #[test]
+    fn test_process_image_bytes_generates_variants() {
+        let buffer = encode_test_image(2400, 1200, ImageFormat::Png);
+        let processed = process_image_bytes(&buffer, Some(&ContentType::PNG), &jpeg_settings())
+            .expect("Image should be processed");
+        assert_eq!(processed.mime, "image/jpeg");
+
+        let dimensions = |data: &[u8]| {
+            decode_image(data, ImageFormat::Jpeg, &jpeg_settings())
+                .expect("Variant should decode")
+                .dimensions()
+        };
+        assert_eq!(dimensions(&processed.data), (1920, 960));
+        assert_eq!(dimensions(&processed.medium), (960, 480));
+        assert_eq!(dimensions(&processed.thumb), (320, 160));
+        assert_eq!(
+            stored_image_variant(&processed.data, "image/jpeg", ImageSize::Thumb)
+                .map(|data| dimensions(&data))
+                .ok(),
+            Some((320, 160))
+        );
+    }
+
+    fn settings(output_format: ImageOutputFormat) -> ImageSettings {
+        ImageSettings {
+            output_format,
+            max_upload_bytes: 10 * 1024 * 1024,
+            max_source_dimension: 10_000,
+            max_source_pixels: 50_000_000,
+        }
+    }
+
+    fn jpeg_settings() -> ImageSettings {
+        settings(ImageOutputFormat::Jpeg)
+    }
+
+    #[test]
+    fn test_process_image_bytes_as_webp() {
+        let buffer = encode_test_image(64, 48, ImageFormat::Png);
+        let settings = settings(ImageOutputFormat::WebP);
+        let processed = process_image_bytes(&buffer, Some(&ContentType::PNG), &settings)
+            .expect("Image should be processed");
+        assert_eq!(processed.mime, "image/webp");
+        assert_eq!(
+            decode_image(&processed.data, ImageFormat::WebP, &settings)
+                .expect("WebP should decode")
+                .dimensions(),
+            (64, 48)
+        );
+
+        let jpeg = transcode_to_jpeg(&processed.data, &processed.mime).expect("Should transcode");
+        assert!(decode_image(&jpeg, ImageFormat::Jpeg, &settings).is_ok());
+    }
+
+    #[test]
+    fn test_process_image_bytes_accepts_stored_webp() {
+        // A WebP image saved by the blog export goes back through the upload
+        // path on import
+        let buffer = encode_test_image(64, 48, ImageFormat::Png);
+        let webp = process_image_bytes(
+            &buffer,
+            Some(&ContentType::PNG),
+            &settings(ImageOutputFormat::WebP),
+        )
+        .expect("Image should be processed");
+
+        let processed = process_image_bytes(&webp.data, Some(&ContentType::WEBP), &jpeg_settings())
+            .expect("WebP should be accepted");
+        assert_eq!(processed.mime, "image/jpeg");
+        assert_eq!(
+            decode_image(&processed.data, ImageFormat::Jpeg, &jpeg_settings())
+                .expect("JPEG should decode")
+                .dimensions(),
+            (64, 48)
+        );
+        assert!(matches!(
+            process_image_bytes(&buffer, Some(&ContentType::WEBP), &jpeg_settings()),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_accepts_image() {
+        let accept = |header: &str| header.parse::<Accept>().expect("Valid Accept header");
+        let browser = accept("image/avif,image/webp,image/*;q=0.8,*/*;q=0.5");
+        assert!(accepts_image(Some(&browser), "image/webp"));
+        assert!(accepts_image(Some(&browser), "image/jpeg"));
+
+        let jpeg_only = accept("image/jpeg, image/png");
+        assert!(!accepts_image(Some(&jpeg_only), "image/webp"));
+        assert!(accepts_image(Some(&jpeg_only), "image/jpeg"));
+        assert!(!accepts_image(
+            Some(&accept("image/webp;q=0")),
+            "image/webp"
+        ));
+        assert!(accepts_image(None, "image/webp"));
+        assert!(needs_jpeg_fallback(Some(&jpeg_only), "image/webp"));
+        assert!(!needs_jpeg_fallback(
+            Some(&accept("image/png")),
+            "image/jpeg"
+        ));
+    }
+
+    #[test]
+    fn test_image_output_format() {
+        assert_eq!(
+            ImageOutputFormat::parse(" WebP "),
+            Some(ImageOutputFormat::WebP)
+        );
+        assert_eq!(
+            ImageOutputFormat::parse("jpg"),
+            Some(ImageOutputFormat::Jpeg)
+        );
+        assert_eq!(ImageOutputFormat::parse("avif"), None);
+    }
+
+    #[test]
+    fn test_image_size() {
+        assert_eq!(ImageSize::parse(None).ok(), Some(ImageSize::Full));
+        assert_eq!(
+            ImageSize::parse(Some(" thumb ")).ok(),
+            Some(ImageSize::Thumb)
+        );
+        assert_eq!(
+            ImageSize::parse(Some("medium")).ok(),
+            Some(ImageSize::Medium)
+        );
+        assert!(ImageSize::parse(Some("huge")).is_err());
+        assert_eq!(ImageSize::Full.variant_hash("abc"), "abc");
+        assert_eq!(ImageSize::Thumb.variant_hash("abc"), "abc-thumb");
+        assert_eq!(ImageSize::Full.variant_key("offers/1"), "offers/1");
+        assert_eq!(ImageSize::Medium.variant_key("offers/1"), "offers/1.medium");
+        assert_eq!(
+            ImageSize::Full.jpeg_fallback_key("offers/1"),
+            "offers/1.jpeg"
+        );
+        assert_eq!(
+            ImageSize::Thumb.jpeg_fallback_key("offers/1"),
+            "offers/1.thumb.jpeg"
+        );
+    }
+
+    #[test]
+    fn test_image_hash() {
+        let hash = image_hash(b"abc");
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(image_hash(b"abd"), hash);
+    }
+
+    #[test]
+    fn test_decode_image_rejects_oversized_file() {
+        let buffer = vec![0u8; 10 * 1024 * 1024 + 1];
+        assert_eq!(
+            decode_image(&buffer, ImageFormat::Png, &jpeg_settings()).err(),
+            Some(ImageRejection::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_decode_image_rejects_corrupt_data() {
+        let buffer = b"\x89PNG\r\n\x1a\ndefinitely not a png".to_vec();
+        assert_eq!(
+            decode_image(&buffer, ImageFormat::Png, &jpeg_settings()).err(),
+            Some(ImageRejection::Corrupt)
+        );
+    }
+
+    #[test]
+    fn test_decode_image_rejects_pixel_bomb() {
+        // A tiny GIF whose header claims a 20000x20000 canvas
+        let mut buffer = encode_test_image(1, 1, ImageFormat::Gif);
+        buffer[6..8].copy_from_slice(&20000u16.to_le_bytes());
+        buffer[8..10].copy_from_slice(&20000u16.to_le_bytes());
+
+        assert_eq!(
+            decode_image(&buffer, ImageFormat::Gif, &jpeg_settings()).err(),
+            Some(ImageRejection::TooManyPixels)
+        );
+
+        let wide = encode_test_image(200, 10, ImageFormat::Png);
+        let narrow = ImageSettings {
+            max_source_dimension: 100,
+            ..jpeg_settings()
+        };
+        assert_eq!(
+            decode_image(&wide, ImageFormat::Png, &narrow).err(),
+            Some(ImageRejection::TooManyPixels)
+        );
+    }
+
+    #[test]
+    fn test_decode_image_rejects_mismatched_type() {
+        let jpeg = encode_test_image(4, 4, ImageFormat::Jpeg);
+        assert_eq!(
+            decode_image(&jpeg, ImageFormat::Png, &jpeg_settings()).err(),
+            Some(ImageRejection::TypeMismatch)
+        );
+        assert_eq!(
+            process_image_bytes(&jpeg, Some(&ContentType::GIF), &jpeg_settings())
+                .err()
+                .map(|e| e.status()),
+            Some(rocket::http::Status::BadRequest)
+        );
+    }
+
+    #[test]
+    fn test_encoded_images_carry_no_exif() {
+        // A JPEG with an EXIF segment saying it is rotated by 90 degrees
+        let mut jpeg = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut jpeg);
+        let tiff = b"MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0";
+        image::ImageEncoder::set_exif_metadata(&mut encoder, tiff.to_vec())
+            .expect("JPEG takes EXIF");
+        DynamicImage::new_rgb8(40, 20)
+            .write_with_encoder(encoder)
+            .expect("Failed to encode test image");
+        assert!(jpeg.windows(6).any(|window| window == b"Exif\0\0"));
+
+        let processed = process_image_bytes(&jpeg, Some(&ContentType::JPEG), &jpeg_settings())
+            .expect("Image should be processed");
+        assert!(
+            !processed
+                .data
+                .windows(6)
+                .any(|window| window == b"Exif\0\0")
+        );
+        let dimensions = decode_image(&processed.data, ImageFormat::Jpeg, &jpeg_settings())
+            .expect("Output should decode")
+            .dimensions();
+        assert_eq!(dimensions, (20, 40));
+    }
+
+    #[test]
+    fn test_normalize_slug_variants_resolve_to_same_slug() {
+        let expected = "my-post";
+        assert_eq!(normalize_slug("my-post"), expected);
+        assert_eq!(normalize_slug("My-Post"), expected);
+        assert_eq!(normalize_slug("My-Post/"), expected);
+        assert_eq!(normalize_slug("MY-POST//"), expected);
+        assert_eq!(normalize_slug(" my-post "), expected);
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Zateplenie fasády"), "zateplenie-fasady");
+        assert_eq!(slugify("  Čo robiť, keď ŤAŽKO?  "), "co-robit-ked-tazko");
+        assert_eq!(slugify("Straße & Œuvre 2026"), "strasse-oeuvre-2026");
+        assert_eq!(slugify("--!!--"), "");
+        assert_eq!(slugify(&"a".repeat(200)).len(), MAX_GENERATED_SLUG_LEN);
+        assert!(!slugify(&format!("{} b", "a".repeat(95))).ends_with('-'));
+    }
+
+    #[test]
+    fn test_next_free_slug() {
+        let taken = |slugs: &[&str]| slugs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(next_free_slug("post", &[]), "post");
+        assert_eq!(next_free_slug("post", &taken(&["post-2"])), "post");
+        assert_eq!(next_free_slug("post", &taken(&["post"])), "post-2");
+        assert_eq!(
+            next_free_slug("post", &taken(&["post", "post-2", "post-4"])),
+            "post-3"
+        );
+    }
+
     #[test]
     fn test_validate_not_empty() {
         assert!(validate_not_empty("hello"));
@@ -164,4 +1302,42 @@ mod tests {
         assert!(!validate_not_empty("   "));
         assert!(!validate_not_empty("\t\n"));
     }
+
+    #[test]
+    fn test_attachment_type() {
+        assert_eq!(
+            attachment_type(b"%PDF-1.7 ..."),
+            Some(("application/pdf", "pdf"))
+        );
+        assert_eq!(
+            attachment_type(b"\x89PNG\r\n\x1a\n...."),
+            Some(("image/png", "png"))
+        );
+        assert_eq!(
+            attachment_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some(("image/webp", "webp"))
+        );
+        assert_eq!(
+            attachment_type("Dobrý deň,\r\n\tzdravím".as_bytes()),
+            Some(("text/plain; charset=utf-8", "txt"))
+        );
+        assert_eq!(attachment_type(b"MZ\x90\0\x03\0"), None);
+        assert_eq!(attachment_type(&[0xc3, 0x28]), None);
+    }
+
+    #[test]
+    fn test_date_range() {
+        let range = DateRange::parse(Some("2024-01-01"), Some("2024-01-31")).unwrap();
+        assert_eq!(range.until.unwrap().to_string(), "2024-02-01 00:00:00");
+        assert_eq!(range.from.unwrap().to_string(), "2024-01-01 00:00:00");
+        assert!(DateRange::parse(Some("2024-02-01"), Some("2024-01-01")).is_err());
+        assert!(DateRange::parse(Some("01.02.2024"), None).is_err());
+        assert_eq!(
+            DateRange::parse(None, Some(" ")).unwrap(),
+            DateRange {
+                from: None,
+                until: None
+            }
+        );
+    }
 }
@@ -0,0 +1,184 @@
+// Blog post view counting: reads are buffered in memory and added to
+// `blog_posts.view_count` in batches by a background task
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Orbit, Rocket};
+use rocket_db_pools::Database;
+use rocket_db_pools::diesel::prelude::*;
+use rocket_db_pools::diesel::{AsyncMysqlConnection, MysqlPool};
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::schema::blog_posts;
+
+/// Views of each post that are not written to the database yet
+#[derive(Clone)]
+pub struct BlogViewCounter {
+    enabled: bool,
+    pending: Arc<Mutex<HashMap<i64, i64>>>,
+}
+
+impl BlogViewCounter {
+    pub fn from_config(config: &AppConfig) -> Self {
+        BlogViewCounter {
+            enabled: config.blog_view_flush_interval_secs > 0,
+            pending: Arc::default(),
+        }
+    }
+
+    /// Count one read of a post
+    pub fn record(&self, post_id: i64) {
+        self.add(post_id, 1);
+    }
+
+    fn add(&self, post_id: i64, views: i64) {
+        if !self.enabled {
+            return;
+        }
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        *pending.entry(post_id).or_insert(0) += views;
+    }
+
+    /// Remove and return the buffered views
+    fn take(&self) -> HashMap<i64, i64> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Add the buffered views to the posts. Views of posts that could not be
+/// updated are kept for the next run. Returns the number of posts updated.
+async fn flush_views(
+    conn: &mut AsyncMysqlConnection,
+    counter: &BlogViewCounter,
+) -> Result<usize, diesel::result::Error> {
+    let mut updated = 0;
+    let mut last_error = None;
+    for (post_id, views) in counter.take() {
+        // Keep `updated_at`, which feeds and the sitemap report as the
+        // time of the last edit
+        let result = diesel::update(blog_posts::table.find(post_id))
+            .set((
+                blog_posts::view_count.eq(blog_posts::view_count + views),
+                blog_posts::updated_at.eq(blog_posts::updated_at),
+            ))
+            .execute(conn)
+            .await;
+        match result {
+            Ok(_) => updated += 1,
+            Err(e) => {
+                counter.add(post_id, views);
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) => Err(e),
+        None => Ok(updated),
+    }
+}
+
+async fn flush_with_pool(pool: &MysqlPool, counter: &BlogViewCounter) {
+    let mut conn = match pool.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Blog view flush could not get a connection: {}", e);
+            return;
+        }
+    };
+    match flush_views(&mut conn, counter).await {
+        Ok(0) => {}
+        Ok(posts) => info!("Saved view counts of {} blog posts", posts),
+        Err(e) => error!("Failed to save blog view counts: {}", e),
+    }
+}
+
+/// Writes the views buffered in `BlogViewCounter` every
+/// `BLOG_VIEW_FLUSH_INTERVAL_SECS` and once more on shutdown
+pub struct BlogViewFlush {
+    interval_secs: u64,
+}
+
+impl BlogViewFlush {
+    pub fn from_config(config: &AppConfig) -> Self {
+        BlogViewFlush {
+            interval_secs: config.blog_view_flush_interval_secs,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for BlogViewFlush {
+    fn info(&self) -> Info {
+        Info {
+            name: "Blog View Flush",
+            kind: Kind::Liftoff | Kind::Shutdown,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        if self.interval_secs == 0 {
+            return;
+        }
+
+        let Some(counter) = rocket.state::<BlogViewCounter>().cloned() else {
+            error!("Blog view flush disabled: no view counter");
+            return;
+        };
+        let Some(db) = MessagesDB::fetch(rocket) else {
+            error!("Blog view flush disabled: no database pool");
+            return;
+        };
+        let pool: MysqlPool = (**db).clone();
+
+        let mut interval = rocket::tokio::time::interval(Duration::from_secs(self.interval_secs));
+        rocket::tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                flush_with_pool(&pool, &counter).await;
+            }
+        });
+    }
+
+    async fn on_shutdown(&self, rocket: &Rocket<Orbit>) {
+        if let (Some(counter), Some(db)) =
+            (rocket.state::<BlogViewCounter>(), MessagesDB::fetch(rocket))
+        {
+            flush_with_pool(db, counter).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(enabled: bool) -> BlogViewCounter {
+        BlogViewCounter {
+            enabled,
+            pending: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn test_views_are_summed_until_taken() {
+        let views = counter(true);
+        views.record(1);
+        views.record(2);
+        views.record(1);
+
+        assert_eq!(views.take(), HashMap::from([(1, 2), (2, 1)]));
+        assert!(views.take().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_counter_ignores_views() {
+        let views = counter(false);
+        views.record(1);
+        assert!(views.take().is_empty());
+    }
+}
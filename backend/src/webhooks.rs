@@ -0,0 +1,369 @@
+// Outgoing webhooks: event payloads are queued in `webhook_deliveries` and
+// sent, signed and with retries, by a background dispatcher
+
+use std::time::{Duration, Instant};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Orbit, Rocket};
+use rocket_db_pools::diesel::prelude::*;
+use rocket_db_pools::diesel::{AsyncMysqlConnection, MysqlPool};
+use rocket_db_pools::{Connection, Database};
+use serde_json::json;
+use tracing::{error, info, warn};
+
+use crate::config::AppConfig;
+use crate::db::MessagesDB;
+use crate::models::{NewWebhookDelivery, Webhook, WebhookDelivery};
+use crate::schema::{webhook_deliveries, webhooks};
+use crate::utils::hmac_sha256_hex;
+
+/// Events webhooks can subscribe to
+pub const WEBHOOK_EVENTS: [&str; 3] = ["message.created", "message.archived", "offer.created"];
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_FAILED: &str = "failed";
+
+/// Deliveries sent per dispatcher run
+const DISPATCH_BATCH_SIZE: i64 = 50;
+/// Delay before the first retry; doubled after every further failure
+const RETRY_BASE_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 6 * 60 * 60;
+/// Longest stored error or response excerpt
+const MAX_ERROR_LEN: usize = 1000;
+/// Time beyond the request timeout a claimed delivery is held for before
+/// another dispatcher may send it, e.g. after a crash mid-send
+const CLAIM_MARGIN_SECS: i64 = 60;
+/// How often finished deliveries older than the retention period are removed
+const PRUNE_INTERVAL_SECS: u64 = 60 * 60;
+
+/// `X-Webhook-Signature` value for a payload
+pub fn signature_header(secret: &str, body: &str) -> String {
+    format!(
+        "sha256={}",
+        hmac_sha256_hex(secret.as_bytes(), body.as_bytes())
+    )
+}
+
+/// Seconds to wait before the next attempt after `attempts` failed ones
+fn retry_delay_secs(attempts: u32) -> i64 {
+    let doublings = attempts.saturating_sub(1).min(20);
+    (RETRY_BASE_SECS << doublings).min(MAX_RETRY_DELAY_SECS)
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_ERROR_LEN).collect()
+}
+
+/// Queue `event` for every active webhook subscribed to it. Failures are
+/// logged and never fail the request that triggered the event.
+pub async fn enqueue_webhook_event(
+    db: &mut Connection<MessagesDB>,
+    event: &str,
+    data: serde_json::Value,
+) {
+    let active: Vec<Webhook> = match webhooks::table
+        .filter(webhooks::is_active.eq(true))
+        .select(Webhook::as_select())
+        .load(db)
+        .await
+    {
+        Ok(active) => active,
+        Err(e) => {
+            error!("Failed to load webhooks for '{}': {}", event, e);
+            return;
+        }
+    };
+
+    let payload = json!({
+        "event": event,
+        "created_at": chrono::Utc::now().naive_utc(),
+        "data": data,
+    })
+    .to_string();
+    let deliveries: Vec<NewWebhookDelivery> = active
+        .iter()
+        .filter(|webhook| webhook.subscribes_to(event))
+        .map(|webhook| NewWebhookDelivery {
+            webhook_id: webhook.id,
+            event: event.to_string(),
+            payload: payload.clone(),
+        })
+        .collect();
+    if deliveries.is_empty() {
+        return;
+    }
+
+    if let Err(e) = diesel::insert_into(webhook_deliveries::table)
+        .values(&deliveries)
+        .execute(db)
+        .await
+    {
+        error!("Failed to queue '{}' webhook deliveries: {}", event, e);
+    }
+}
+
+/// Result of one delivery attempt
+enum Attempt {
+    Delivered(u16),
+    Failed { status: Option<u16>, error: String },
+}
+
+async fn attempt(http: &reqwest::Client, webhook: &Webhook, delivery: &WebhookDelivery) -> Attempt {
+    let response = http
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Event", &delivery.event)
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header(
+            "X-Webhook-Signature",
+            signature_header(&webhook.secret, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => {
+            Attempt::Delivered(response.status().as_u16())
+        }
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Attempt::Failed {
+                status: Some(status.as_u16()),
+                error: truncate(&format!("HTTP {status}: {body}")),
+            }
+        }
+        Err(e) => Attempt::Failed {
+            status: None,
+            error: truncate(&e.to_string()),
+        },
+    }
+}
+
+/// Take a due delivery for this dispatcher by moving its next attempt past
+/// the claim period, so other instances sharing the database skip it.
+/// Returns false when another instance took it first.
+async fn claim(
+    conn: &mut AsyncMysqlConnection,
+    delivery_id: i64,
+    claim_secs: i64,
+) -> Result<bool, diesel::result::Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let claimed = diesel::update(
+        webhook_deliveries::table
+            .find(delivery_id)
+            .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+            .filter(webhook_deliveries::next_attempt_at.le(now)),
+    )
+    .set(webhook_deliveries::next_attempt_at.eq(now + chrono::Duration::seconds(claim_secs)))
+    .execute(conn)
+    .await?;
+    Ok(claimed == 1)
+}
+
+/// Send every due delivery once and record the outcome. Each delivery is
+/// claimed before it is sent, holding it for `claim_secs`. Returns the number
+/// of deliveries attempted.
+async fn dispatch_due(
+    conn: &mut AsyncMysqlConnection,
+    http: &reqwest::Client,
+    max_attempts: u32,
+    claim_secs: i64,
+) -> Result<usize, diesel::result::Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let due: Vec<(WebhookDelivery, Webhook)> = webhook_deliveries::table
+        .inner_join(webhooks::table)
+        .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+        .filter(webhook_deliveries::next_attempt_at.le(now))
+        .order(webhook_deliveries::id.asc())
+        .limit(DISPATCH_BATCH_SIZE)
+        .select((WebhookDelivery::as_select(), Webhook::as_select()))
+        .load(conn)
+        .await?;
+
+    let mut attempted = 0;
+    for (delivery, webhook) in &due {
+        if !claim(conn, delivery.id, claim_secs).await? {
+            continue;
+        }
+        attempted += 1;
+        let attempts = delivery.attempts.max(0) as u32 + 1;
+        let outcome = if webhook.is_active {
+            attempt(http, webhook, delivery).await
+        } else {
+            Attempt::Failed {
+                status: None,
+                error: "Webhook is disabled".to_string(),
+            }
+        };
+
+        let now = chrono::Utc::now().naive_utc();
+        let target = webhook_deliveries::table.find(delivery.id);
+        match outcome {
+            Attempt::Delivered(status) => {
+                diesel::update(target)
+                    .set((
+                        webhook_deliveries::status.eq(STATUS_DELIVERED),
+                        webhook_deliveries::attempts.eq(attempts as i32),
+                        webhook_deliveries::response_status.eq(Some(i32::from(status))),
+                        webhook_deliveries::error.eq(None::<String>),
+                        webhook_deliveries::delivered_at.eq(Some(now)),
+                    ))
+                    .execute(conn)
+                    .await?;
+            }
+            Attempt::Failed { status, error } => {
+                let gave_up = !webhook.is_active || attempts >= max_attempts;
+                warn!(
+                    "Webhook delivery {} to webhook {} failed (attempt {}): {}",
+                    delivery.id, webhook.id, attempts, error
+                );
+                diesel::update(target)
+                    .set((
+                        webhook_deliveries::status.eq(if gave_up {
+                            STATUS_FAILED
+                        } else {
+                            STATUS_PENDING
+                        }),
+                        webhook_deliveries::attempts.eq(attempts as i32),
+                        webhook_deliveries::response_status.eq(status.map(i32::from)),
+                        webhook_deliveries::error.eq(Some(error)),
+                        webhook_deliveries::next_attempt_at
+                            .eq(now + chrono::Duration::seconds(retry_delay_secs(attempts))),
+                    ))
+                    .execute(conn)
+                    .await?;
+            }
+        }
+    }
+    Ok(attempted)
+}
+
+/// Remove delivered and failed deliveries created more than `retention_days`
+/// ago. Returns the number removed.
+async fn prune_finished(
+    conn: &mut AsyncMysqlConnection,
+    retention_days: u32,
+) -> Result<usize, diesel::result::Error> {
+    let cutoff = chrono::Utc::now().naive_utc() - chrono::Duration::days(i64::from(retention_days));
+    diesel::delete(
+        webhook_deliveries::table
+            .filter(webhook_deliveries::status.ne(STATUS_PENDING))
+            .filter(webhook_deliveries::created_at.lt(cutoff)),
+    )
+    .execute(conn)
+    .await
+}
+
+/// Background task sending queued webhook deliveries every
+/// `WEBHOOK_POLL_INTERVAL_SECS`, giving up after `WEBHOOK_MAX_ATTEMPTS`, and
+/// removing finished ones after `WEBHOOK_DELIVERY_RETENTION_DAYS`
+pub struct WebhookDispatcher {
+    interval_secs: u64,
+    max_attempts: u32,
+    timeout_secs: u64,
+    retention_days: u32,
+}
+
+impl WebhookDispatcher {
+    pub fn from_config(config: &AppConfig) -> Self {
+        WebhookDispatcher {
+            interval_secs: config.webhook_poll_interval_secs,
+            max_attempts: config.webhook_max_attempts.max(1),
+            timeout_secs: config.webhook_timeout_secs.max(1),
+            retention_days: config.webhook_delivery_retention_days,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for WebhookDispatcher {
+    fn info(&self) -> Info {
+        Info {
+            name: "Webhook Dispatcher",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        if self.interval_secs == 0 {
+            info!("Webhook dispatcher disabled");
+            return;
+        }
+
+        let Some(db) = MessagesDB::fetch(rocket) else {
+            error!("Webhook dispatcher disabled: no database pool");
+            return;
+        };
+        let pool: MysqlPool = (**db).clone();
+        let http = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .build()
+        {
+            Ok(http) => http,
+            Err(e) => {
+                error!("Webhook dispatcher disabled: {}", e);
+                return;
+            }
+        };
+
+        let max_attempts = self.max_attempts;
+        let claim_secs = i64::try_from(self.timeout_secs)
+            .unwrap_or(i64::MAX)
+            .saturating_add(CLAIM_MARGIN_SECS);
+        let retention_days = self.retention_days;
+        let mut last_pruned: Option<Instant> = None;
+        let mut interval = rocket::tokio::time::interval(Duration::from_secs(self.interval_secs));
+        rocket::tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                let mut conn = match pool.get().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        error!("Webhook dispatcher could not get a connection: {}", e);
+                        continue;
+                    }
+                };
+                match dispatch_due(&mut conn, &http, max_attempts, claim_secs).await {
+                    Ok(0) => {}
+                    Ok(sent) => info!("Attempted {} webhook deliveries", sent),
+                    Err(e) => error!("Webhook dispatch failed: {}", e),
+                }
+
+                let prune_due = last_pruned
+                    .is_none_or(|at| at.elapsed() >= Duration::from_secs(PRUNE_INTERVAL_SECS));
+                if retention_days > 0 && prune_due {
+                    last_pruned = Some(Instant::now());
+                    match prune_finished(&mut conn, retention_days).await {
+                        Ok(0) => {}
+                        Ok(removed) => info!("Removed {} old webhook deliveries", removed),
+                        Err(e) => error!("Failed to remove old webhook deliveries: {}", e),
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_header() {
+        assert_eq!(
+            signature_header("key", "{}"),
+            format!("sha256={}", hmac_sha256_hex(b"key", b"{}"))
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_cap() {
+        assert_eq!(retry_delay_secs(1), 30);
+        assert_eq!(retry_delay_secs(2), 60);
+        assert_eq!(retry_delay_secs(4), 240);
+        assert_eq!(retry_delay_secs(40), MAX_RETRY_DELAY_SECS);
+    }
+}
@@ -11,6 +11,7 @@ export async function checkAdminAuth(): Promise<void> {
         const onLogin = path === "/admin/login";
         const onSetup = path === "/admin/setup";
         const onInvite = path === "/admin/invite";
+        const onPasswordReset = path === "/admin/password-reset";
 
         if (status.setup_required) {
             if (!onSetup) {
@@ -26,7 +27,7 @@ export async function checkAdminAuth(): Promise<void> {
             return;
         }
 
-        if (onInvite) {
+        if (onInvite || onPasswordReset) {
             return;
         }
 
@@ -40,7 +41,8 @@ export async function checkAdminAuth(): Promise<void> {
         if (
             path !== "/admin/login" &&
             path !== "/admin/setup" &&
-            path !== "/admin/invite"
+            path !== "/admin/invite" &&
+            path !== "/admin/password-reset"
         ) {
             window.location.href = "/admin/login";
         }
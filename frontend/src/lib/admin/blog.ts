@@ -1,5 +1,17 @@
-import { api, type BlogPost } from "../../lib/api";
-import { escapeHtml, showConfirmDialog } from "./utils";
+import {
+    api,
+    type AdminBlogPostStatus,
+    type AdminBlogPostSummary,
+    type BlogPost,
+    type BlogPostImage,
+} from "../../lib/api";
+import {
+    escapeHtml,
+    showConfirmDialog,
+    suggestedSlug,
+    updatePaginationControls,
+} from "./utils";
+import type { PaginationState } from "./types";
 import Cropper from "cropperjs";
 import { setMarkdownEditorValue } from "./markdown-editor";
 
@@ -8,6 +20,14 @@ declare global {
     interface Window {
         editBlogPost?: (id: number) => void;
         deleteBlogPost?: (id: number) => Promise<void>;
+        duplicateBlogPost?: (id: number) => Promise<void>;
+        shareBlogPreview?: (id: number) => Promise<void>;
+        featureBlogPost?: (id: number, featured: boolean) => Promise<void>;
+        archiveBlogPost?: (id: number) => Promise<void>;
+        restoreBlogPost?: (id: number) => Promise<void>;
+        toggleBlogRevisions?: (id: number) => Promise<void>;
+        diffBlogRevision?: (id: number, revisionId: number) => Promise<void>;
+        restoreBlogRevision?: (id: number, revisionId: number) => void;
     }
 }
 
@@ -24,22 +44,45 @@ export interface BlogPageElements {
     postId: HTMLInputElement | null;
     postTitle: HTMLInputElement | null;
     postSlug: HTMLInputElement | null;
+    postTags: HTMLInputElement | null;
+    postMetaTitle: HTMLInputElement | null;
+    postMetaDescription: HTMLTextAreaElement | null;
+    postCanonicalUrl: HTMLInputElement | null;
+    postOgImage: HTMLInputElement | null;
     postExcerpt: HTMLTextAreaElement | null;
     postContent: HTMLTextAreaElement | null;
     postPublished: HTMLInputElement | null;
     postImage: HTMLInputElement | null;
     imagePreview: HTMLElement | null;
     imagePreviewImg: HTMLImageElement | null;
+    galleryPanel: HTMLElement | null;
+    galleryList: HTMLElement | null;
+    galleryUpload: HTMLInputElement | null;
     blogImageCropContainer: HTMLElement | null;
     blogImageCropPreview: HTMLImageElement | null;
     blogCropApply: HTMLButtonElement | null;
     blogCropCancel: HTMLButtonElement | null;
+    searchForm: HTMLFormElement | null;
+    searchInput: HTMLInputElement | null;
+    statusFilter: HTMLSelectElement | null;
+    importFile: HTMLInputElement | null;
+    paginationControls: HTMLElement | null;
+    prevPageBtn: HTMLButtonElement | null;
+    nextPageBtn: HTMLButtonElement | null;
+    currentPageNum: HTMLElement | null;
 }
 
+const POSTS_PER_PAGE = 12;
+
 export interface BlogFormData {
     id?: string;
     title: string;
     slug: string;
+    tags: string;
+    metaTitle: string;
+    metaDescription: string;
+    canonicalUrl: string;
+    ogImage: string;
     excerpt: string;
     content: string;
     published: boolean;
@@ -48,9 +91,13 @@ export interface BlogFormData {
 
 export class BlogPageController {
     private elements: BlogPageElements;
-    private postsData: BlogPost[] = [];
+    private postsData: AdminBlogPostSummary[] = [];
+    private currentPage = 1;
     private cropper: any = null;
     private croppedImageBlob: Blob | null = null;
+    private galleryPostId: number | null = null;
+    private galleryImages: BlogPostImage[] = [];
+    private galleryChanged = false;
 
     constructor(elements: BlogPageElements) {
         this.elements = elements;
@@ -76,12 +123,36 @@ export class BlogPageController {
 
         form?.addEventListener("submit", (e) => this.handleFormSubmit(e));
         refreshBtn?.addEventListener("click", () => this.loadPosts());
+        this.elements.searchForm?.addEventListener("submit", (event) => {
+            event.preventDefault();
+            this.currentPage = 1;
+            this.loadPosts();
+        });
+        this.elements.statusFilter?.addEventListener("change", () => {
+            this.currentPage = 1;
+            this.loadPosts();
+        });
+        this.elements.prevPageBtn?.addEventListener("click", () =>
+            this.changePage(-1),
+        );
+        this.elements.nextPageBtn?.addEventListener("click", () =>
+            this.changePage(1),
+        );
         addPostBtn?.addEventListener("click", () => this.openModal());
         modalClose?.addEventListener("click", () => this.closeModal());
         modalCancel?.addEventListener("click", () => this.closeModal());
         postImage?.addEventListener("change", () => this.handleImageChange());
         blogCropApply?.addEventListener("click", () => this.applyCrop());
         blogCropCancel?.addEventListener("click", () => this.cancelCrop());
+        this.elements.galleryUpload?.addEventListener("change", () =>
+            this.uploadGalleryImage(),
+        );
+        this.elements.galleryList?.addEventListener("click", (event) =>
+            this.handleGalleryClick(event),
+        );
+        this.elements.importFile?.addEventListener("change", () =>
+            this.importPosts(),
+        );
 
         this.setupWindowFunctions();
     }
@@ -92,7 +163,7 @@ export class BlogPageController {
         };
 
         window.deleteBlogPost = async (id: number) => {
-            showConfirmDialog("Zmazať príspevok?", async () => {
+            showConfirmDialog("Presunúť príspevok do koša?", async () => {
                 try {
                     await api.admin.deleteBlogPost(id);
                     await this.loadPosts();
@@ -102,6 +173,166 @@ export class BlogPageController {
                 }
             });
         };
+
+        window.duplicateBlogPost = async (id: number) => {
+            try {
+                const copy = await api.admin.duplicateBlogPost(id);
+                await this.loadPosts();
+                await this.editPost(copy.id);
+            } catch (error) {
+                console.error("Failed to duplicate blog post:", error);
+                alert("Chyba pri kopírovaní príspevku.");
+            }
+        };
+
+        window.shareBlogPreview = async (id: number) => {
+            try {
+                const link = await api.admin.createBlogPreviewLink(id);
+                const expiresAt = new Date(link.expires_at).toLocaleString(
+                    "sk-SK",
+                );
+                try {
+                    await navigator.clipboard.writeText(link.url);
+                    alert(
+                        `Odkaz na náhľad bol skopírovaný. Platí do ${expiresAt}.`,
+                    );
+                } catch {
+                    prompt(
+                        `Odkaz na náhľad (platí do ${expiresAt}):`,
+                        link.url,
+                    );
+                }
+            } catch (error) {
+                console.error("Failed to create blog preview link:", error);
+                alert("Chyba pri vytváraní odkazu na náhľad.");
+            }
+        };
+
+        window.featureBlogPost = async (id: number, featured: boolean) => {
+            let order = 0;
+            if (featured) {
+                const input = prompt(
+                    "Poradie medzi zvýraznenými článkami (menšie číslo je prvé):",
+                    "0",
+                );
+                if (input === null) return;
+                order = Number.parseInt(input, 10);
+                if (Number.isNaN(order)) {
+                    alert("Poradie musí byť celé číslo.");
+                    return;
+                }
+            }
+            try {
+                await api.admin.featureBlogPost(id, featured, order);
+                await this.loadPosts();
+            } catch (error) {
+                console.error("Failed to change featured blog post:", error);
+                alert("Chyba pri zmene zvýraznenia.");
+            }
+        };
+
+        window.archiveBlogPost = async (id: number) => {
+            try {
+                await api.admin.archiveBlogPost(id);
+                await this.loadPosts();
+            } catch (error) {
+                console.error("Failed to archive blog post:", error);
+                alert("Chyba pri archivovaní.");
+            }
+        };
+
+        window.restoreBlogPost = async (id: number) => {
+            try {
+                await api.admin.restoreBlogPost(id);
+                await this.loadPosts();
+            } catch (error) {
+                console.error("Failed to restore blog post:", error);
+                alert("Chyba pri obnovovaní príspevku.");
+            }
+        };
+
+        window.toggleBlogRevisions = (id: number) => this.toggleRevisions(id);
+
+        window.diffBlogRevision = (id: number, revisionId: number) =>
+            this.showRevisionDiff(id, revisionId);
+
+        window.restoreBlogRevision = (id: number, revisionId: number) => {
+            showConfirmDialog("Obnoviť túto verziu príspevku?", async () => {
+                try {
+                    await api.admin.restoreBlogPostRevision(id, revisionId);
+                    await this.loadPosts();
+                } catch (error) {
+                    console.error("Failed to restore blog post revision:", error);
+                    alert("Chyba pri obnovovaní verzie.");
+                }
+            });
+        };
+    }
+
+    private async toggleRevisions(id: number): Promise<void> {
+        const slot = document.querySelector<HTMLElement>(
+            `[data-revisions-for="${id}"]`,
+        );
+        if (!slot) return;
+        if (!slot.classList.contains("hidden")) {
+            slot.classList.add("hidden");
+            return;
+        }
+
+        slot.classList.remove("hidden");
+        slot.innerHTML =
+            '<p class="text-xs text-gray-400 m-0">Načítavam históriu...</p>';
+        try {
+            const revisions = await api.admin.getBlogPostRevisions(id);
+            slot.innerHTML = revisions.length
+                ? revisions
+                      .map(
+                          (revision) => `
+            <div class="py-2 border-b border-gray-100 last:border-0">
+              <div class="text-xs text-gray-500">${new Date(revision.created_at).toLocaleString("sk-SK")} · ${escapeHtml(revision.admin_username)}</div>
+              <div class="text-sm text-gray-800 break-words">${escapeHtml(revision.title)}</div>
+              <div class="flex gap-3 mt-1 text-xs">
+                <button onclick="window.diffBlogRevision && window.diffBlogRevision(${id}, ${revision.id})" class="text-primary hover:underline">Porovnať s aktuálnou</button>
+                <button onclick="window.restoreBlogRevision && window.restoreBlogRevision(${id}, ${revision.id})" class="text-primary hover:underline">Obnoviť</button>
+              </div>
+              <pre data-revision-diff="${revision.id}" class="hidden mt-2 p-2 bg-gray-50 rounded text-xs overflow-x-auto whitespace-pre-wrap"></pre>
+            </div>`,
+                      )
+                      .join("")
+                : '<p class="text-xs text-gray-400 m-0">Príspevok ešte nebol upravený.</p>';
+        } catch (error) {
+            console.error("Failed to load blog post revisions:", error);
+            slot.innerHTML =
+                '<p class="text-xs text-red-500 m-0">Chyba pri načítavaní histórie.</p>';
+        }
+    }
+
+    private async showRevisionDiff(
+        id: number,
+        revisionId: number,
+    ): Promise<void> {
+        const target = document.querySelector<HTMLElement>(
+            `[data-revision-diff="${revisionId}"]`,
+        );
+        if (!target) return;
+        if (!target.classList.contains("hidden")) {
+            target.classList.add("hidden");
+            return;
+        }
+
+        try {
+            const diff = await api.admin.diffBlogPostRevisions(id, revisionId);
+            const fields = diff.changed_fields.length
+                ? `Zmenené: ${diff.changed_fields.join(", ")}`
+                : "Bez zmien";
+            target.textContent = diff.content_diff
+                ? `${fields}\n\n${diff.content_diff}`
+                : fields;
+            target.classList.remove("hidden");
+        } catch (error) {
+            console.error("Failed to diff blog post revision:", error);
+            alert("Chyba pri porovnávaní verzií.");
+        }
     }
 
     async loadPosts(): Promise<void> {
@@ -112,7 +343,15 @@ export class BlogPageController {
         if (noPosts) noPosts.classList.add("hidden");
 
         try {
-            this.postsData = await api.admin.getAllBlogPosts();
+            const { data, total, limit } = await api.admin.getAllBlogPosts({
+                page: this.currentPage,
+                limit: POSTS_PER_PAGE,
+                status: (this.elements.statusFilter?.value ||
+                    "all") as AdminBlogPostStatus,
+                q: this.elements.searchInput?.value.trim() || undefined,
+            });
+            this.postsData = data;
+            this.updatePagination(total, limit);
 
             if (this.postsData.length === 0) {
                 if (noPosts) noPosts.classList.remove("hidden");
@@ -127,6 +366,30 @@ export class BlogPageController {
         }
     }
 
+    private updatePagination(total: number, limit: number): void {
+        const pagination: PaginationState = {
+            currentPage: this.currentPage,
+            totalPages: Math.ceil(total / limit),
+            totalItems: total,
+            itemsPerPage: limit,
+        };
+
+        updatePaginationControls(
+            pagination,
+            this.elements.currentPageNum,
+            this.elements.prevPageBtn,
+            this.elements.nextPageBtn,
+            this.elements.paginationControls,
+        );
+    }
+
+    private changePage(delta: number): void {
+        if (this.currentPage + delta < 1) return;
+        this.currentPage += delta;
+        this.loadPosts();
+        window.scrollTo({ top: 0, behavior: "smooth" });
+    }
+
     private renderPosts(): void {
         const { container } = this.elements;
         if (!container) return;
@@ -156,40 +419,62 @@ export class BlogPageController {
         }
     }
 
-    private renderPostCard(post: BlogPost): string {
+    private renderPostCard(post: AdminBlogPostSummary): string {
         const createdAt = new Date(post.created_at).toLocaleDateString("sk-SK");
-        const statusBadge = post.published
+        const badgeOnImage =
+            post.image_url && post.published && !post.archived_at;
+        const statusBadge = post.archived_at
+            ? '<span class="px-2 py-1 text-xs font-bold uppercase tracking-wider bg-gray-100 text-gray-500 rounded">Archivované</span>'
+            : post.published
             ? '<span class="absolute top-3 right-3 z-10 px-3 py-1.5 text-[10px] font-black uppercase tracking-[0.18em] bg-green-600 text-white rounded-full shadow-lg">Publikované</span>'
             : '<span class="px-2 py-1 text-xs font-bold uppercase tracking-wider bg-yellow-100 text-yellow-700 rounded">Koncept</span>';
 
         return `
       <div class="h-full bg-white border border-gray-200 rounded-2xl p-6 shadow-sm transition-all duration-300 hover:shadow-xl hover:border-primary/20 hover:-translate-y-1 flex flex-col">
         ${
-            post.image_mime
+            post.image_url
                 ? `
           <div class="relative mb-4 overflow-hidden rounded-lg">
-            <img src="${api.blog.getBlogPostImageUrl(post.id)}" alt="${escapeHtml(post.title)}" class="w-full h-48 object-cover">
-            ${post.published ? statusBadge : ""}
+            <img src="${post.image_url}?size=medium" alt="${escapeHtml(post.title)}" class="w-full h-48 object-cover">
+            ${badgeOnImage ? statusBadge : ""}
           </div>
         `
                 : ""
         }
         <div class="flex items-start justify-between gap-4 mb-3">
           <h3 class="m-0 text-lg font-bold text-gray-900 leading-tight break-words">${escapeHtml(post.title)}</h3>
-          ${post.image_mime && post.published ? "" : statusBadge}
+          ${badgeOnImage ? "" : statusBadge}
         </div>
         <p class="text-sm text-gray-500 mb-2"><strong>Slug:</strong> <code class="bg-gray-100 px-2 py-1 rounded text-xs break-all">${escapeHtml(post.slug)}</code></p>
         ${post.excerpt ? `<p class="text-sm text-gray-600 mb-4 line-clamp-2 break-words">${escapeHtml(post.excerpt)}</p>` : ""}
-        <div class="text-xs text-gray-400 mb-4">Vytvorené: ${createdAt}</div>
+        <div class="text-xs text-gray-400 mb-4">Vytvorené: ${createdAt} · Zobrazenia: ${post.view_count}${post.featured ? ` · Zvýraznené (poradie ${post.featured_order})` : ""}</div>
         <div class="mt-auto pt-4 flex gap-2">
           <button onclick="window.editBlogPost && window.editBlogPost(${post.id})" class="flex-1 px-3 py-2 bg-primary hover:bg-blue-600 text-white rounded-lg transition-colors flex items-center justify-center gap-2">
             <span class="icon-edit"></span>
             Upraviť
           </button>
+          <button onclick="window.toggleBlogRevisions && window.toggleBlogRevisions(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">
+            História
+          </button>
+          <button onclick="window.duplicateBlogPost && window.duplicateBlogPost(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">
+            Kopírovať
+          </button>
+          <button onclick="window.featureBlogPost && window.featureBlogPost(${post.id}, ${!post.featured})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">
+            ${post.featured ? "Nezvýrazniť" : "Zvýrazniť"}
+          </button>
+          <button onclick="window.shareBlogPreview && window.shareBlogPreview(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">
+            Náhľad
+          </button>
+          ${
+              post.archived_at
+                  ? `<button onclick="window.restoreBlogPost && window.restoreBlogPost(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">Obnoviť</button>`
+                  : `<button onclick="window.archiveBlogPost && window.archiveBlogPost(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-gray-100 text-gray-600 rounded-lg transition-colors text-sm">Archivovať</button>`
+          }
           <button onclick="window.deleteBlogPost && window.deleteBlogPost(${post.id})" class="px-3 py-2 bg-gray-50 hover:bg-red-50 text-gray-400 hover:text-red-500 rounded-lg transition-colors">
             <span class="icon-trash"></span>
           </button>
         </div>
+        <div data-revisions-for="${post.id}" class="hidden mt-4 pt-2 border-t border-gray-100"></div>
       </div>
     `;
     }
@@ -206,6 +491,11 @@ export class BlogPageController {
             postId,
             postTitle,
             postSlug,
+            postTags,
+            postMetaTitle,
+            postMetaDescription,
+            postCanonicalUrl,
+            postOgImage,
             postExcerpt,
             postContent,
             postPublished,
@@ -216,6 +506,11 @@ export class BlogPageController {
             id: postId?.value,
             title: postTitle?.value || "",
             slug: postSlug?.value || "",
+            tags: postTags?.value || "",
+            metaTitle: postMetaTitle?.value || "",
+            metaDescription: postMetaDescription?.value || "",
+            canonicalUrl: postCanonicalUrl?.value || "",
+            ogImage: postOgImage?.value || "",
             excerpt: postExcerpt?.value || "",
             content: postContent?.value || "",
             published: postPublished?.checked || false,
@@ -228,6 +523,12 @@ export class BlogPageController {
             const data = new FormData();
             data.append("title", formData.title);
             data.append("slug", formData.slug);
+            data.append("tags", formData.tags);
+            // Always sent, so clearing a field removes the override
+            data.append("meta_title", formData.metaTitle);
+            data.append("meta_description", formData.metaDescription);
+            data.append("canonical_url", formData.canonicalUrl);
+            data.append("og_image", formData.ogImage);
             if (formData.excerpt) data.append("excerpt", formData.excerpt);
             data.append("content", formData.content);
             data.append("published", formData.published ? "true" : "false");
@@ -239,33 +540,65 @@ export class BlogPageController {
                 } else if (formData.imageFile) {
                     data.append("image", formData.imageFile);
                 }
-                await api.admin.updateBlogPost(parseInt(formData.id, 10), data);
-            } else {
-                // Create new post
-                if (this.croppedImageBlob) {
-                    data.append("image", this.croppedImageBlob, "image.jpg");
-                } else if (formData.imageFile) {
-                    data.append("image", formData.imageFile);
-                }
-                await api.admin.createBlogPost(data);
+                const updated = await api.admin.updateBlogPost(
+                    parseInt(formData.id, 10),
+                    data,
+                );
+                // Show the saved post in place, keeping the page and order
+                this.postsData = this.postsData.map((post) =>
+                    post.id === updated.id ? updated : post,
+                );
+                this.closeModal();
+                this.renderPosts();
+                return;
             }
 
+            // Create new post
+            if (this.croppedImageBlob) {
+                data.append("image", this.croppedImageBlob, "image.jpg");
+            } else if (formData.imageFile) {
+                data.append("image", formData.imageFile);
+            }
+            await api.admin.createBlogPost(data);
+
             this.closeModal();
             await this.loadPosts();
         } catch (error) {
             console.error("Failed to save blog post:", error);
+            const suggested = suggestedSlug(error);
+            if (suggested !== null) {
+                if (this.elements.postSlug) {
+                    this.elements.postSlug.value = suggested;
+                }
+                alert(
+                    `Adresa "${formData.slug}" je už použitá. Navrhujeme "${suggested}", uložte znova.`,
+                );
+                return;
+            }
             alert("Chyba pri ukladaní.");
         }
     }
 
-    private editPost(id: number): void {
-        const post = this.postsData.find((x) => x.id === id);
-        if (!post) return;
+    private async editPost(id: number): Promise<void> {
+        // The list leaves out the content, so load the full post
+        let post: BlogPost;
+        try {
+            post = await api.admin.getBlogPost(id);
+        } catch (error) {
+            console.error("Failed to load blog post:", error);
+            alert("Chyba pri načítavaní príspevku.");
+            return;
+        }
 
         const {
             postId,
             postTitle,
             postSlug,
+            postTags,
+            postMetaTitle,
+            postMetaDescription,
+            postCanonicalUrl,
+            postOgImage,
             postExcerpt,
             postContent,
             postPublished,
@@ -277,17 +610,168 @@ export class BlogPageController {
         if (postId) postId.value = String(post.id);
         if (postTitle) postTitle.value = post.title;
         if (postSlug) postSlug.value = post.slug;
+        if (postTags) postTags.value = post.tags.map((tag) => tag.name).join(", ");
+        if (postMetaTitle) postMetaTitle.value = post.meta_title || "";
+        if (postMetaDescription)
+            postMetaDescription.value = post.meta_description || "";
+        if (postCanonicalUrl) postCanonicalUrl.value = post.canonical_url || "";
+        if (postOgImage) postOgImage.value = post.og_image || "";
         if (postExcerpt)
             setMarkdownEditorValue(postExcerpt.id, post.excerpt || "");
         if (postContent) setMarkdownEditorValue(postContent.id, post.content);
         if (postPublished) postPublished.checked = post.published;
-        if (imagePreview && imagePreviewImg && post.image_mime) {
+        if (imagePreview && imagePreviewImg && post.image_url) {
             imagePreview.classList.remove("hidden");
-            imagePreviewImg.src = api.blog.getBlogPostImageUrl(post.id);
+            imagePreviewImg.src = post.image_url;
         }
+        this.showGallery(post.id, post.images);
         if (modal) modal.classList.remove("hidden");
     }
 
+    private showGallery(postId: number, images: BlogPostImage[]): void {
+        this.galleryPostId = postId;
+        this.galleryImages = images;
+        this.elements.galleryPanel?.classList.remove("hidden");
+        this.renderGallery();
+    }
+
+    private hideGallery(): void {
+        this.galleryPostId = null;
+        this.galleryImages = [];
+        this.galleryChanged = false;
+        this.elements.galleryPanel?.classList.add("hidden");
+        if (this.elements.galleryList) this.elements.galleryList.innerHTML = "";
+    }
+
+    private renderGallery(): void {
+        const { galleryList } = this.elements;
+        if (!galleryList) return;
+
+        const last = this.galleryImages.length - 1;
+        galleryList.innerHTML = this.galleryImages.length
+            ? this.galleryImages
+                  .map(
+                      (image, index) => `
+          <div class="rounded-lg border border-gray-200 overflow-hidden">
+            <img src="${escapeHtml(image.url)}" alt="" class="w-full h-24 object-cover">
+            <div class="flex justify-between gap-1 p-1 text-xs">
+              <button type="button" data-gallery-action="left" data-index="${index}" class="px-1 text-gray-500 hover:text-primary disabled:opacity-30" ${index === 0 ? "disabled" : ""}>←</button>
+              <button type="button" data-gallery-action="copy" data-index="${index}" class="text-primary hover:underline">Odkaz</button>
+              <button type="button" data-gallery-action="delete" data-index="${index}" class="text-gray-400 hover:text-red-500">Zmazať</button>
+              <button type="button" data-gallery-action="right" data-index="${index}" class="px-1 text-gray-500 hover:text-primary disabled:opacity-30" ${index === last ? "disabled" : ""}>→</button>
+            </div>
+          </div>`,
+                  )
+                  .join("")
+            : '<p class="col-span-2 text-xs text-gray-400 m-0">Príspevok nemá žiadne obrázky.</p>';
+    }
+
+    private handleGalleryClick(event: Event): void {
+        const button = (event.target as HTMLElement).closest<HTMLElement>(
+            "[data-gallery-action]",
+        );
+        const image = this.galleryImages[Number(button?.dataset.index)];
+        if (!button || !image) return;
+
+        const index = Number(button.dataset.index);
+        switch (button.dataset.galleryAction) {
+            case "left":
+                this.moveGalleryImage(index, index - 1);
+                break;
+            case "right":
+                this.moveGalleryImage(index, index + 1);
+                break;
+            case "copy":
+                navigator.clipboard
+                    .writeText(`![](${image.url})`)
+                    .catch(() => prompt("Odkaz na obrázok:", image.url));
+                break;
+            case "delete":
+                showConfirmDialog("Zmazať obrázok?", () =>
+                    this.deleteGalleryImage(image.id),
+                );
+                break;
+        }
+    }
+
+    private async uploadGalleryImage(): Promise<void> {
+        const { galleryUpload } = this.elements;
+        const file = galleryUpload?.files?.[0];
+        if (!file || this.galleryPostId === null) return;
+
+        try {
+            const image = await api.admin.uploadBlogPostImage(
+                this.galleryPostId,
+                file,
+            );
+            this.galleryImages.push(image);
+            this.galleryChanged = true;
+            this.renderGallery();
+        } catch (error) {
+            console.error("Failed to upload blog post image:", error);
+            alert("Chyba pri nahrávaní obrázka.");
+        } finally {
+            if (galleryUpload) galleryUpload.value = "";
+        }
+    }
+
+    private async importPosts(): Promise<void> {
+        const { importFile } = this.elements;
+        const file = importFile?.files?.[0];
+        if (!file) return;
+
+        try {
+            const imported = await api.admin.importBlogPosts(file);
+            alert(`Importované príspevky: ${imported.length}`);
+            this.currentPage = 1;
+            await this.loadPosts();
+        } catch (error) {
+            console.error("Failed to import blog posts:", error);
+            const message =
+                error instanceof Error && error.message
+                    ? `\n${error.message}`
+                    : "";
+            alert(`Chyba pri importe príspevkov.${message}`);
+        } finally {
+            if (importFile) importFile.value = "";
+        }
+    }
+
+    private async moveGalleryImage(from: number, to: number): Promise<void> {
+        if (this.galleryPostId === null) return;
+        if (to < 0 || to >= this.galleryImages.length) return;
+
+        const ids = this.galleryImages.map((image) => image.id);
+        [ids[from], ids[to]] = [ids[to], ids[from]];
+        try {
+            this.galleryImages = await api.admin.reorderBlogPostImages(
+                this.galleryPostId,
+                ids,
+            );
+            this.galleryChanged = true;
+            this.renderGallery();
+        } catch (error) {
+            console.error("Failed to reorder blog post images:", error);
+            alert("Chyba pri zmene poradia obrázkov.");
+        }
+    }
+
+    private async deleteGalleryImage(imageId: number): Promise<void> {
+        if (this.galleryPostId === null) return;
+
+        try {
+            await api.admin.deleteBlogPostImage(this.galleryPostId, imageId);
+            this.galleryImages = this.galleryImages.filter(
+                (image) => image.id !== imageId,
+            );
+            this.galleryChanged = true;
+            this.renderGallery();
+        } catch (error) {
+            console.error("Failed to delete blog post image:", error);
+            alert("Chyba pri mazaní obrázka.");
+        }
+    }
+
     private handleImageChange(): void {
         const {
             postImage,
@@ -416,6 +900,7 @@ export class BlogPageController {
         if (modal) modal.classList.remove("hidden");
         if (postExcerpt) setMarkdownEditorValue(postExcerpt.id, "");
         if (postContent) setMarkdownEditorValue(postContent.id, "");
+        this.hideGallery();
     }
 
     private closeModal(): void {
@@ -444,6 +929,9 @@ export class BlogPageController {
         if (imagePreview) imagePreview.classList.add("hidden");
         if (blogImageCropContainer)
             blogImageCropContainer.classList.add("hidden");
+        // The cover shown in the list may have changed
+        if (this.galleryChanged) void this.loadPosts();
+        this.hideGallery();
     }
 }
 
@@ -0,0 +1,92 @@
+import { api } from "../api";
+
+/** Template edited on the page; the only one the backend knows so far */
+const CONTACT_AUTO_REPLY = "contact_auto_reply";
+
+export function initEmailTemplatesAdmin(): void {
+    const form = document.getElementById(
+        "email-template-form",
+    ) as HTMLFormElement | null;
+    const active = document.getElementById(
+        "email-template-active",
+    ) as HTMLInputElement | null;
+    const subject = document.getElementById(
+        "email-template-subject",
+    ) as HTMLInputElement | null;
+    const body = document.getElementById(
+        "email-template-body",
+    ) as HTMLTextAreaElement | null;
+    const placeholders = document.getElementById(
+        "email-template-placeholders",
+    ) as HTMLParagraphElement | null;
+    const success = document.getElementById(
+        "email-template-success",
+    ) as HTMLDivElement | null;
+    const error = document.getElementById(
+        "email-template-error",
+    ) as HTMLDivElement | null;
+
+    if (!form || !active || !subject || !body) {
+        return;
+    }
+
+    const activeInput = active;
+    const subjectInput = subject;
+    const bodyInput = body;
+
+    function showError(message: string): void {
+        if (error) {
+            error.textContent = message;
+            error.classList.remove("hidden");
+        }
+    }
+
+    async function loadTemplate(): Promise<void> {
+        try {
+            const templates = await api.admin.getEmailTemplates();
+            const template = templates.find(
+                (candidate) => candidate.name === CONTACT_AUTO_REPLY,
+            );
+            if (!template) return;
+
+            activeInput.checked = template.is_active;
+            subjectInput.value = template.subject;
+            bodyInput.value = template.body;
+            if (placeholders) {
+                placeholders.textContent = `Dostupné premenné: ${template.placeholders
+                    .map((name) => `{${name}}`)
+                    .join(", ")}`;
+            }
+        } catch (err) {
+            console.error("Failed to load email templates:", err);
+            showError("Nepodarilo sa načítať šablónu.");
+        }
+    }
+
+    form.addEventListener("submit", async (event) => {
+        event.preventDefault();
+        success?.classList.add("hidden");
+        error?.classList.add("hidden");
+
+        try {
+            await api.admin.saveEmailTemplate(CONTACT_AUTO_REPLY, {
+                subject: subjectInput.value.trim(),
+                body: bodyInput.value.trim(),
+                is_active: activeInput.checked,
+            });
+            if (success) {
+                success.textContent = "Šablóna bola uložená.";
+                success.classList.remove("hidden");
+            }
+            await loadTemplate();
+        } catch (err) {
+            showError(
+                err instanceof Error
+                    ? err.message
+                    : "Chyba pri ukladaní šablóny.",
+            );
+        }
+    });
+
+    loadTemplate();
+}
@@ -1,4 +1,5 @@
 import { api } from "../api";
+import { ApiError } from "../api-client";
 
 /**
  * Initialize admin login form
@@ -13,12 +14,18 @@ export function initAdminLogin(): void {
     const passwordInput = document.getElementById(
         "password",
     ) as HTMLInputElement | null;
+    const rememberMeInput = document.getElementById(
+        "remember-me",
+    ) as HTMLInputElement | null;
     const loginBtn = document.getElementById(
         "login-btn",
     ) as HTMLButtonElement | null;
     const errorMessage = document.getElementById(
         "error-message",
     ) as HTMLDivElement | null;
+    const oidcLink = document.getElementById(
+        "oidc-login",
+    ) as HTMLAnchorElement | null;
 
     if (
         !form ||
@@ -30,6 +37,22 @@ export function initAdminLogin(): void {
         return;
     }
 
+    if (new URLSearchParams(window.location.search).get("error") === "oidc") {
+        errorMessage.textContent =
+            "Prihlásenie cez SSO zlyhalo alebo tento účet nemá prístup.";
+        errorMessage.classList.remove("hidden");
+    }
+
+    api.admin
+        .getStatus()
+        .then((status) => {
+            if (status.oidc_enabled) {
+                oidcLink?.classList.remove("hidden");
+                oidcLink?.classList.add("block");
+            }
+        })
+        .catch(() => {});
+
     form.addEventListener("submit", async (e: Event) => {
         e.preventDefault();
 
@@ -42,11 +65,17 @@ export function initAdminLogin(): void {
             loginBtn.disabled = true;
             errorMessage.classList.add("hidden");
 
-            await api.admin.login(username, password);
+            await api.admin.login(
+                username,
+                password,
+                rememberMeInput?.checked ?? false,
+            );
             window.location.href = "/admin/messages";
         } catch (err) {
             errorMessage.textContent =
-                "Nesprávne prihlasovacie údaje. Skúste to znova.";
+                err instanceof ApiError && err.status === 429
+                    ? "Príliš veľa neúspešných pokusov. Skúste to neskôr."
+                    : "Nesprávne prihlasovacie údaje. Skúste to znova.";
             errorMessage.classList.remove("hidden");
             usernameInput.disabled = false;
             passwordInput.disabled = false;
@@ -1,4 +1,15 @@
-import { api, type Message, type ArchivedMessage } from "../../lib/api";
+import {
+    api,
+    type Message,
+    type ArchivedMessage,
+    type BulkMessageAction,
+    type Label,
+    type MessageAttachment,
+    type MessageNote,
+    type MessageReply,
+    type MessageThread,
+    type SpamScoreFilter,
+} from "../../lib/api";
 import { format } from "date-fns";
 import { sk } from "date-fns/locale";
 import {
@@ -9,11 +20,153 @@ import {
     updatePaginationControls,
 } from "./utils";
 import type { PaginationState } from "./types";
+import { refreshUnreadBadge } from "../components/admin-navbar";
+
+/** Spam score from which a message is flagged as suspicious */
+const SUSPICIOUS_SPAM_SCORE = 50;
+
+const SPAM_FILTERS: Record<string, SpamScoreFilter> = {
+    all: {},
+    clean: { max: SUSPICIOUS_SPAM_SCORE - 1 },
+    suspicious: { min: SUSPICIOUS_SPAM_SCORE },
+};
+
+function formatFileSize(bytes: number): string {
+    if (bytes < 1024) return `${bytes} B`;
+    if (bytes < 1024 * 1024) return `${Math.round(bytes / 1024)} kB`;
+    return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+function formatDate(value: string): string {
+    return format(new Date(value), "d. MMMM yyyy HH:mm", { locale: sk });
+}
+
+function attachmentLinks(
+    messageId: number,
+    attachments: MessageAttachment[],
+): string {
+    return attachments
+        .map(
+            (attachment) => `
+          <a href="${api.admin.getMessageAttachmentUrl(messageId, attachment.id)}" download class="px-4 py-2 bg-white border border-gray-200 text-gray-700 hover:text-primary hover:border-primary/30 text-xs font-bold rounded-xl transition-colors">
+            Príloha: ${escapeHtml(attachment.filename)} (${formatFileSize(attachment.size_bytes)})
+          </a>`,
+        )
+        .join("");
+}
+
+function replyHistory(replies: MessageReply[]): string {
+    return replies
+        .map(
+            (reply) => `
+          <div class="bg-blue-50/50 p-5 rounded-xl border-l-4 border-blue-300">
+            <div class="flex flex-wrap items-center gap-3 mb-2 text-[10px] font-black uppercase tracking-widest text-gray-400">
+              <span>Odpoveď od ${escapeHtml(reply.admin_username)}</span>
+              <span>${formatDate(reply.sent_at)}</span>
+            </div>
+            <p class="text-sm font-bold text-gray-900 mb-2 break-words">${escapeHtml(reply.subject)}</p>
+            <div class="text-sm text-gray-700 whitespace-pre-wrap break-words">${escapeHtml(reply.body)}</div>
+          </div>`,
+        )
+        .join("");
+}
+
+function threadHistory(currentId: number, thread: MessageThread): string {
+    return thread.messages
+        .map(
+            (entry) => `
+          <div class="p-5 rounded-xl border ${entry.state === "active" && entry.message_id === currentId ? "border-primary/40 bg-primary/5" : "border-gray-100 bg-white"}">
+            <div class="flex flex-wrap items-center gap-3 mb-2 text-[10px] font-black uppercase tracking-widest text-gray-400">
+              <span>${formatDate(entry.created_at)}</span>
+              ${entry.state === "archived" ? "<span>Archivovaná</span>" : ""}
+              ${entry.subject ? `<span class="text-gray-900 normal-case tracking-normal text-sm">${escapeHtml(entry.subject)}</span>` : ""}
+            </div>
+            <div class="text-sm text-gray-700 whitespace-pre-wrap break-words">${escapeHtml(entry.message)}</div>
+            ${entry.replies.length > 0 ? `<div class="mt-3 space-y-3">${replyHistory(entry.replies)}</div>` : ""}
+          </div>`,
+        )
+        .join("");
+}
+
+function noteList(messageId: number, notes: MessageNote[]): string {
+    return notes
+        .map(
+            (note) => `
+          <div class="bg-amber-50/50 p-4 rounded-xl border-l-4 border-amber-300">
+            <div class="flex flex-wrap items-center gap-3 mb-2 text-[10px] font-black uppercase tracking-widest text-gray-400">
+              <span>Poznámka od ${escapeHtml(note.admin_username)}</span>
+              <span>${formatDate(note.created_at)}</span>
+              <button
+                onclick="window.deleteMessageNote && window.deleteMessageNote(${messageId}, ${note.id})"
+                class="ml-auto text-gray-400 hover:text-red-600 transition-colors"
+                title="Zmazať poznámku"
+              >
+                Zmazať
+              </button>
+            </div>
+            <div class="text-sm text-gray-700 whitespace-pre-wrap break-words">${escapeHtml(note.body)}</div>
+          </div>`,
+        )
+        .join("");
+}
+
+function labelChips(
+    messageId: number,
+    assigned: Label[],
+    available: Label[],
+): string {
+    const chips = assigned
+        .map(
+            (label) => `
+          <span class="inline-flex items-center gap-1.5 px-3 py-1 text-[10px] font-black uppercase tracking-widest rounded-full text-white" style="background-color: ${escapeHtml(label.color)}">
+            ${escapeHtml(label.name)}
+            <button
+              onclick="window.removeMessageLabel && window.removeMessageLabel(${messageId}, ${label.id})"
+              class="opacity-70 hover:opacity-100"
+              title="Odobrať štítok"
+            >&times;</button>
+          </span>`,
+        )
+        .join("");
+    const unassigned = available.filter(
+        (label) => !assigned.some((other) => other.id === label.id),
+    );
+    if (unassigned.length === 0) return chips;
+
+    const options = unassigned
+        .map(
+            (label) =>
+                `<option value="${label.id}">${escapeHtml(label.name)}</option>`,
+        )
+        .join("");
+    return `${chips}
+          <select data-label-add="${messageId}" aria-label="Pridať štítok" class="px-3 py-1 bg-white border border-gray-200 text-gray-500 text-[10px] font-black uppercase tracking-widest rounded-full">
+            <option value="">+ Štítok</option>
+            ${options}
+          </select>`;
+}
+
+function spamScoreBadge(score: number): string {
+    if (score <= 0) return "";
+    const suspicious = score >= SUSPICIOUS_SPAM_SCORE;
+    const classes = suspicious
+        ? "bg-red-50 text-red-700 border-red-100"
+        : "bg-gray-50 text-gray-500 border-gray-100";
+    return `
+                <span class="px-3 py-1 ${classes} text-[10px] font-black uppercase tracking-widest rounded-full border" title="Skóre spamu">
+                  ${suspicious ? "Podozrivá" : "Spam skóre"}: ${score}
+                </span>`;
+}
 
 // Extend window with admin actions
 declare global {
     interface Window {
         archiveMessage?: (id: number) => Promise<void>;
+        markMessageRead?: (id: number, read: boolean) => Promise<void>;
+        toggleReplyForm?: (id: number) => void;
+        toggleMessageThread?: (id: number) => Promise<void>;
+        deleteMessageNote?: (id: number, noteId: number) => Promise<void>;
+        removeMessageLabel?: (id: number, labelId: number) => Promise<void>;
         restoreMessage?: (id: number) => Promise<void>;
         permanentlyDeleteArchivedMessage?: (id: number) => Promise<void>;
     }
@@ -32,6 +185,11 @@ export interface MessagesPageElements {
     nextPageBtn: HTMLButtonElement | null;
     currentPageNum: HTMLElement | null;
     refreshBtn: HTMLButtonElement | null;
+    spamFilter: HTMLSelectElement | null;
+    labelFilter: HTMLSelectElement | null;
+    bulkActions: HTMLElement | null;
+    searchForm: HTMLFormElement | null;
+    searchInput: HTMLInputElement | null;
     activeCount: HTMLElement | null;
     archivedCount: HTMLElement | null;
 }
@@ -41,6 +199,7 @@ export class MessagesPageController {
     private currentPage = 1;
     private activeTotal = 0;
     private archivedTotal = 0;
+    private labels: Label[] = [];
     private elements: MessagesPageElements;
 
     constructor(elements: MessagesPageElements) {
@@ -54,8 +213,14 @@ export class MessagesPageController {
     }
 
     private setupEventListeners(): void {
-        const { activeTab, archivedTab, refreshBtn, prevPageBtn, nextPageBtn } =
-            this.elements;
+        const {
+            activeTab,
+            archivedTab,
+            refreshBtn,
+            spamFilter,
+            prevPageBtn,
+            nextPageBtn,
+        } = this.elements;
 
         activeTab?.addEventListener("click", () => {
             if (this.currentView === "archived") {
@@ -74,12 +239,144 @@ export class MessagesPageController {
             this.loadCurrentView();
         });
 
+        this.setupBulkActions();
+
+        this.elements.searchForm?.addEventListener("submit", (event) => {
+            event.preventDefault();
+            this.currentPage = 1;
+            this.loadCurrentView();
+        });
+
+        spamFilter?.addEventListener("change", () => {
+            this.currentPage = 1;
+            if (this.currentView === "active") this.loadActiveMessages();
+        });
+
+        this.elements.labelFilter?.addEventListener("change", () => {
+            this.currentPage = 1;
+            if (this.currentView === "active") this.loadActiveMessages();
+        });
+
+        this.elements.activeContainer?.addEventListener("change", (event) => {
+            const select = event.target as HTMLSelectElement;
+            if (!select.dataset.labelAdd || !select.value) return;
+            void this.addLabel(Number(select.dataset.labelAdd), Number(select.value));
+        });
+
+        this.elements.activeContainer?.addEventListener("submit", (event) => {
+            const form = event.target as HTMLFormElement;
+            if (form.dataset.noteForm) {
+                event.preventDefault();
+                void this.addNote(form);
+                return;
+            }
+            if (!form.dataset.replyForm) return;
+            event.preventDefault();
+            void this.sendReply(form);
+        });
+
         prevPageBtn?.addEventListener("click", () => this.handlePrevPage());
         nextPageBtn?.addEventListener("click", () => this.handleNextPage());
 
         this.setupWindowFunctions();
     }
 
+    private selectedMessageIds(): number[] {
+        const boxes =
+            this.elements.activeContainer?.querySelectorAll<HTMLInputElement>(
+                "[data-select-message]:checked",
+            ) ?? [];
+        return Array.from(boxes).map((box) => Number(box.value));
+    }
+
+    /**
+     * Keep the bulk bar in sync with the checkboxes of the active messages
+     */
+    private updateBulkActions(): void {
+        const { bulkActions, activeContainer } = this.elements;
+        if (!bulkActions || !activeContainer) return;
+
+        const boxes = activeContainer.querySelectorAll<HTMLInputElement>(
+            "[data-select-message]",
+        );
+        const selected = this.selectedMessageIds().length;
+        bulkActions.classList.toggle(
+            "hidden",
+            this.currentView !== "active" || boxes.length === 0,
+        );
+
+        const count = bulkActions.querySelector("#bulk-selected-count");
+        if (count) count.textContent = String(selected);
+        const selectAll =
+            bulkActions.querySelector<HTMLInputElement>("#bulk-select-all");
+        if (selectAll) {
+            selectAll.checked = boxes.length > 0 && selected === boxes.length;
+        }
+        bulkActions
+            .querySelectorAll<HTMLButtonElement>("[data-bulk-action]")
+            .forEach((button) => {
+                button.disabled = selected === 0;
+            });
+    }
+
+    private setupBulkActions(): void {
+        const { bulkActions, activeContainer } = this.elements;
+        if (!bulkActions || !activeContainer) return;
+
+        activeContainer.addEventListener("change", (event) => {
+            const target = event.target as HTMLElement;
+            if (target.matches("[data-select-message]")) {
+                this.updateBulkActions();
+            }
+        });
+
+        bulkActions
+            .querySelector<HTMLInputElement>("#bulk-select-all")
+            ?.addEventListener("change", (event) => {
+                const checked = (event.target as HTMLInputElement).checked;
+                activeContainer
+                    .querySelectorAll<HTMLInputElement>("[data-select-message]")
+                    .forEach((box) => {
+                        box.checked = checked;
+                    });
+                this.updateBulkActions();
+            });
+
+        bulkActions
+            .querySelectorAll<HTMLButtonElement>("[data-bulk-action]")
+            .forEach((button) => {
+                button.addEventListener("click", () => {
+                    const action = button.dataset
+                        .bulkAction as BulkMessageAction;
+                    const ids = this.selectedMessageIds();
+                    if (ids.length === 0) return;
+
+                    const run = async () => {
+                        try {
+                            await api.admin.bulkUpdateMessages(ids, action);
+                            await this.loadAllCounts();
+                            await this.loadCurrentView();
+                            await refreshUnreadBadge();
+                        } catch (error) {
+                            console.error("Bulk action failed:", error);
+                            alert("Hromadnú akciu sa nepodarilo vykonať.");
+                        }
+                    };
+
+                    if (action === "mark_read") {
+                        void run();
+                    } else {
+                        showConfirmDialog(
+                            action === "delete"
+                                ? `Naozaj chcete natrvalo zmazať ${ids.length} správ? Táto akcia je nezvratná!`
+                                : `Naozaj chcete archivovať ${ids.length} správ?`,
+                            run,
+                        );
+                    }
+                });
+            });
+    }
+
     private setupWindowFunctions(): void {
         window.archiveMessage = async (id: number) => {
             showConfirmDialog(
@@ -89,6 +386,7 @@ export class MessagesPageController {
                         await api.admin.archiveMessage(id);
                         await this.loadAllCounts();
                         await this.loadCurrentView();
+                        await refreshUnreadBadge();
                     } catch (error) {
                         console.error("Failed to archive message:", error);
                         alert("Nepodarilo sa archivovať správu.");
@@ -97,14 +395,80 @@ export class MessagesPageController {
             );
         };
 
+        window.markMessageRead = async (id: number, read: boolean) => {
+            try {
+                await api.admin.markMessageRead(id, read);
+                await this.loadCurrentView();
+                await refreshUnreadBadge();
+            } catch (error) {
+                console.error("Failed to update read status:", error);
+                alert("Nepodarilo sa zmeniť stav správy.");
+            }
+        };
+
+        window.toggleReplyForm = (id: number) => {
+            const form = this.elements.activeContainer?.querySelector(
+                `[data-reply-form="${id}"]`,
+            );
+            form?.classList.toggle("hidden");
+        };
+
+        window.toggleMessageThread = async (id: number) => {
+            const slot = this.elements.activeContainer?.querySelector<HTMLElement>(
+                `[data-thread-for="${id}"]`,
+            );
+            if (!slot) return;
+            if (!slot.classList.contains("hidden")) {
+                slot.classList.add("hidden");
+                return;
+            }
+
+            try {
+                const thread = await api.admin.getMessageThread(
+                    slot.dataset.email ?? "",
+                );
+                slot.innerHTML = threadHistory(id, thread);
+                slot.classList.remove("hidden");
+            } catch (error) {
+                console.error("Failed to load message thread:", error);
+                alert("Nepodarilo sa načítať konverzáciu.");
+            }
+        };
+
+        window.removeMessageLabel = async (id: number, labelId: number) => {
+            const detail = await api.admin.getMessage(id);
+            await this.updateMessageLabels(
+                id,
+                detail.labels
+                    .map((label) => label.id)
+                    .filter((other) => other !== labelId),
+            );
+        };
+
+        window.deleteMessageNote = async (id: number, noteId: number) => {
+            showConfirmDialog(
+                "Naozaj chcete zmazať túto poznámku?",
+                async () => {
+                    try {
+                        await api.admin.deleteMessageNote(id, noteId);
+                        await this.loadCurrentView();
+                    } catch (error) {
+                        console.error("Failed to delete note:", error);
+                        alert("Nepodarilo sa zmazať poznámku.");
+                    }
+                },
+            );
+        };
+
         window.restoreMessage = async (id: number) => {
             showConfirmDialog(
                 "Naozaj chcete obnoviť túto správu?",
                 async () => {
                     try {
-                        await api.admin.restoreMessage(id);
+                        await api.admin.restoreArchivedMessage(id);
                         await this.loadAllCounts();
                         await this.loadCurrentView();
+                        await refreshUnreadBadge();
                     } catch (error) {
                         console.error("Failed to restore message:", error);
                         alert("Nepodarilo sa obnoviť správu.");
@@ -136,6 +500,7 @@ export class MessagesPageController {
     private async loadInitialData(): Promise<void> {
         try {
             await this.loadAllCounts();
+            await this.loadLabels();
             await this.loadCurrentView();
         } catch (error) {
             console.error("Initialization error:", error);
@@ -193,10 +558,27 @@ export class MessagesPageController {
         noArchivedMessages?.classList.add("hidden");
         if (activeContainer) activeContainer.innerHTML = "";
         if (archivedContainer) archivedContainer.classList.add("hidden");
+        this.updateBulkActions();
         this.elements.paginationControls?.classList.add("hidden");
 
         try {
-            const response = await api.admin.getMessages(this.currentPage);
+            // A search query takes precedence over the spam filter
+            const searchQuery = this.elements.searchInput?.value.trim() ?? "";
+            const spamFilter =
+                SPAM_FILTERS[this.elements.spamFilter?.value ?? "all"] ?? {};
+            const labelFilter = this.elements.labelFilter?.value;
+            const response = searchQuery
+                ? await api.admin.searchMessages(
+                      searchQuery,
+                      this.currentPage,
+                      10,
+                  )
+                : await api.admin.getMessages(
+                      this.currentPage,
+                      10,
+                      spamFilter,
+                      labelFilter ? Number(labelFilter) : undefined,
+                  );
             const messages = response.data;
 
             if (messages.length === 0) {
@@ -236,8 +618,12 @@ export class MessagesPageController {
         this.elements.paginationControls?.classList.add("hidden");
 
         try {
+            // The search box applies to the archive as well
+            const searchQuery = this.elements.searchInput?.value.trim() ?? "";
             const response = await api.admin.getArchivedMessages(
                 this.currentPage,
+                10,
+                { q: searchQuery || undefined },
             );
             const messages = response.data;
 
@@ -274,6 +660,8 @@ export class MessagesPageController {
 
         // Replace icon placeholders with cloned templates
         this.replaceIconPlaceholders(this.elements.activeContainer);
+        void this.loadMessageDetails(this.elements.activeContainer);
+        this.updateBulkActions();
     }
 
     private renderArchivedMessages(messages: ArchivedMessage[]): void {
@@ -286,6 +674,189 @@ export class MessagesPageController {
 
         // Replace icon placeholders with cloned templates
         this.replaceIconPlaceholders(this.elements.archivedContainer);
+        void this.loadAttachments(this.elements.archivedContainer);
+    }
+
+    /**
+     * Fill in the attachments, reply history and notes of every rendered
+     * active message
+     */
+    private async loadMessageDetails(container: HTMLElement): Promise<void> {
+        const slots = container.querySelectorAll<HTMLElement>(
+            "[data-replies-for]",
+        );
+        await Promise.all(
+            Array.from(slots).map(async (slot) => {
+                const messageId = Number(slot.dataset.repliesFor);
+                try {
+                    const detail = await api.admin.getMessage(messageId);
+                    const attachmentSlot = container.querySelector<HTMLElement>(
+                        `[data-attachments-for="${messageId}"]`,
+                    );
+                    if (attachmentSlot && detail.attachments.length > 0) {
+                        attachmentSlot.innerHTML = attachmentLinks(
+                            messageId,
+                            detail.attachments,
+                        );
+                        attachmentSlot.classList.remove("hidden");
+                    }
+                    if (detail.replies.length > 0) {
+                        slot.innerHTML = replyHistory(detail.replies);
+                        slot.classList.remove("hidden");
+                    }
+                    const noteSlot = container.querySelector<HTMLElement>(
+                        `[data-notes-for="${messageId}"]`,
+                    );
+                    if (noteSlot) {
+                        noteSlot.innerHTML = noteList(messageId, detail.notes);
+                    }
+                    const labelSlot = container.querySelector<HTMLElement>(
+                        `[data-labels-for="${messageId}"]`,
+                    );
+                    if (labelSlot) {
+                        labelSlot.innerHTML = labelChips(
+                            messageId,
+                            detail.labels,
+                            this.labels,
+                        );
+                    }
+                } catch (error) {
+                    console.error("Failed to load message details:", error);
+                }
+            }),
+        );
+    }
+
+    /**
+     * Send the reply typed into a message's reply form
+     */
+    private async sendReply(form: HTMLFormElement): Promise<void> {
+        const messageId = Number(form.dataset.replyForm);
+        const body = (
+            form.elements.namedItem("body") as HTMLTextAreaElement
+        ).value.trim();
+        if (!body) return;
+
+        const submit = form.querySelector<HTMLButtonElement>(
+            "button[type=submit]",
+        );
+        if (submit) submit.disabled = true;
+        try {
+            await api.admin.replyToMessage(messageId, { body });
+            await this.loadCurrentView();
+            await refreshUnreadBadge();
+        } catch (error) {
+            console.error("Failed to send reply:", error);
+            alert(
+                error instanceof Error
+                    ? error.message
+                    : "Nepodarilo sa odoslať odpoveď.",
+            );
+            if (submit) submit.disabled = false;
+        }
+    }
+
+    /**
+     * Load the label list and fill in the label filter
+     */
+    private async loadLabels(): Promise<void> {
+        try {
+            this.labels = await api.admin.getLabels();
+        } catch (error) {
+            console.error("Failed to load labels:", error);
+            return;
+        }
+
+        const filter = this.elements.labelFilter;
+        if (!filter) return;
+        for (const label of this.labels) {
+            const option = document.createElement("option");
+            option.value = String(label.id);
+            option.textContent = label.name;
+            filter.appendChild(option);
+        }
+    }
+
+    private async addLabel(messageId: number, labelId: number): Promise<void> {
+        const detail = await api.admin.getMessage(messageId);
+        await this.updateMessageLabels(messageId, [
+            ...detail.labels.map((label) => label.id),
+            labelId,
+        ]);
+    }
+
+    /**
+     * Replace a message's labels and redraw its label chips
+     */
+    private async updateMessageLabels(
+        messageId: number,
+        labelIds: number[],
+    ): Promise<void> {
+        try {
+            const labels = await api.admin.setMessageLabels(messageId, labelIds);
+            const labelSlot = this.elements.activeContainer?.querySelector(
+                `[data-labels-for="${messageId}"]`,
+            );
+            if (labelSlot) {
+                labelSlot.innerHTML = labelChips(messageId, labels, this.labels);
+            }
+        } catch (error) {
+            console.error("Failed to update labels:", error);
+            alert("Nepodarilo sa zmeniť štítky správy.");
+        }
+    }
+
+    /**
+     * Save the internal note typed into a message's note form
+     */
+    private async addNote(form: HTMLFormElement): Promise<void> {
+        const messageId = Number(form.dataset.noteForm);
+        const input = form.elements.namedItem("body") as HTMLTextAreaElement;
+        const body = input.value.trim();
+        if (!body) return;
+
+        try {
+            await api.admin.addMessageNote(messageId, body);
+            input.value = "";
+            const detail = await api.admin.getMessage(messageId);
+            const noteSlot = this.elements.activeContainer?.querySelector(
+                `[data-notes-for="${messageId}"]`,
+            );
+            if (noteSlot) {
+                noteSlot.innerHTML = noteList(messageId, detail.notes);
+            }
+        } catch (error) {
+            console.error("Failed to add note:", error);
+            alert(
+                error instanceof Error
+                    ? error.message
+                    : "Nepodarilo sa uložiť poznámku.",
+            );
+        }
+    }
+
+    /**
+     * Fill in the download links of every rendered message with attachments
+     */
+    private async loadAttachments(container: HTMLElement): Promise<void> {
+        const slots = container.querySelectorAll<HTMLElement>(
+            "[data-attachments-for]",
+        );
+        await Promise.all(
+            Array.from(slots).map(async (slot) => {
+                const messageId = Number(slot.dataset.attachmentsFor);
+                try {
+                    const attachments =
+                        await api.admin.getMessageAttachments(messageId);
+                    if (attachments.length === 0) return;
+
+                    slot.innerHTML = attachmentLinks(messageId, attachments);
+                    slot.classList.remove("hidden");
+                } catch (error) {
+                    console.error("Failed to load attachments:", error);
+                }
+            }),
+        );
     }
 
     private replaceIconPlaceholders(container: HTMLElement): void {
@@ -322,13 +893,25 @@ export class MessagesPageController {
         const formattedDate = format(date, "d. MMMM yyyy HH:mm", {
             locale: sk,
         });
+        const isRead = msg.read_at !== null;
 
         return `
       <div class="bg-white p-6 md:p-8 rounded-2xl border border-gray-100 shadow-sm transition-all duration-300 hover:shadow-xl hover:border-primary/20">
         <div class="flex flex-col lg:flex-row justify-between lg:items-start gap-6 mb-6">
           <div class="space-y-4 min-w-0 flex-1">
             <div class="flex flex-wrap items-center gap-3">
-              <span class="px-3 py-1 bg-blue-50 text-primary text-[10px] font-black uppercase tracking-widest rounded-full border border-blue-100">Nová Správa</span>
+              <input
+                type="checkbox"
+                data-select-message
+                value="${msg.id}"
+                aria-label="Vybrať správu"
+                class="w-4 h-4 text-primary border-gray-300 rounded focus:ring-primary"
+              />
+              ${
+                  isRead
+                      ? `<span class="px-3 py-1 bg-gray-50 text-gray-500 text-[10px] font-black uppercase tracking-widest rounded-full border border-gray-100">Prečítaná</span>`
+                      : `<span class="px-3 py-1 bg-blue-50 text-primary text-[10px] font-black uppercase tracking-widest rounded-full border border-blue-100">Nová Správa</span>`
+              }
               <span class="flex items-center gap-1.5 text-xs text-gray-400 font-bold uppercase tracking-wider">
                 <span class="icon-calendar"></span>
                 ${formattedDate}
@@ -353,9 +936,47 @@ export class MessagesPageController {
                 </a>`
                       : ""
               }
+              ${
+                  msg.preferred_contact
+                      ? `
+                <span class="px-3 py-1 bg-amber-50 text-amber-700 text-[10px] font-black uppercase tracking-widest rounded-full border border-amber-100">
+                  Preferuje ${msg.preferred_contact === "phone" ? "telefón" : "email"}
+                </span>`
+                      : ""
+              }
+              ${spamScoreBadge(msg.spam_score)}
+              ${
+                  msg.answered_at
+                      ? `
+                <span class="px-3 py-1 bg-green-50 text-green-700 text-[10px] font-black uppercase tracking-widest rounded-full border border-green-100">
+                  Zodpovedaná
+                </span>`
+                      : ""
+              }
             </div>
           </div>
           <div class="flex shrink-0 gap-3">
+            <button
+              onclick="window.toggleMessageThread && window.toggleMessageThread(${msg.id})"
+              class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-blue-50 hover:text-blue-600 hover:border-blue-200 transition-all duration-300 flex items-center justify-center gap-2 text-[10px] font-black uppercase tracking-widest rounded-xl"
+              title="Všetky správy od tohto odosielateľa"
+            >
+              Konverzácia
+            </button>
+            <button
+              onclick="window.toggleReplyForm && window.toggleReplyForm(${msg.id})"
+              class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-blue-50 hover:text-blue-600 hover:border-blue-200 transition-all duration-300 flex items-center justify-center gap-2 text-[10px] font-black uppercase tracking-widest rounded-xl"
+              title="Odpovedať emailom"
+            >
+              Odpovedať
+            </button>
+            <button
+              onclick="window.markMessageRead && window.markMessageRead(${msg.id}, ${!isRead})"
+              class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-blue-50 hover:text-blue-600 hover:border-blue-200 transition-all duration-300 flex items-center justify-center gap-2 text-[10px] font-black uppercase tracking-widest rounded-xl"
+              title="${isRead ? "Označiť ako neprečítanú" : "Označiť ako prečítanú"}"
+            >
+              ${isRead ? "Neprečítaná" : "Prečítaná"}
+            </button>
             <button
               onclick="window.archiveMessage && window.archiveMessage(${msg.id})"
               class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-blue-50 hover:text-blue-600 hover:border-blue-200 transition-all duration-300 flex items-center justify-center gap-2 text-[10px] font-black uppercase tracking-widest rounded-xl group"
@@ -369,6 +990,45 @@ export class MessagesPageController {
         <div class="bg-gray-50 p-6 rounded-xl border-l-4 border-primary text-gray-700 whitespace-pre-wrap break-words leading-relaxed text-sm font-medium italic">
           ${escapeHtml(msg.message)}
         </div>
+        <div data-labels-for="${msg.id}" class="mt-4 flex flex-wrap items-center gap-2 empty:hidden"></div>
+        <div data-attachments-for="${msg.id}" class="hidden mt-4 flex flex-wrap gap-3"></div>
+        <div data-replies-for="${msg.id}" class="hidden mt-6 space-y-3"></div>
+        <div data-thread-for="${msg.id}" data-email="${escapeHtml(msg.email)}" class="hidden mt-6 space-y-3"></div>
+        <div data-notes-for="${msg.id}" class="mt-6 space-y-3 empty:hidden"></div>
+        <form data-note-form="${msg.id}" class="mt-4 flex gap-3">
+          <textarea
+            name="body"
+            rows="1"
+            required
+            maxlength="5000"
+            placeholder="Interná poznámka"
+            class="grow px-4 py-2.5 bg-gray-50 border border-gray-200 rounded-xl focus:border-primary focus:ring-4 focus:ring-primary/10 outline-none text-sm resize-y"
+          ></textarea>
+          <button
+            type="submit"
+            class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-amber-50 hover:text-amber-700 hover:border-amber-200 transition-all duration-300 text-[10px] font-black uppercase tracking-widest rounded-xl"
+          >
+            Pridať poznámku
+          </button>
+        </form>
+        <form data-reply-form="${msg.id}" class="hidden mt-6 space-y-3">
+          <textarea
+            name="body"
+            rows="6"
+            required
+            maxlength="10000"
+            placeholder="Odpoveď pre ${escapeHtml(msg.email)}"
+            class="w-full px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl focus:border-primary focus:ring-4 focus:ring-primary/10 outline-none text-sm resize-y"
+          ></textarea>
+          <div class="flex justify-end">
+            <button
+              type="submit"
+              class="px-6 py-2.5 bg-primary text-white text-xs font-black uppercase tracking-widest rounded-xl hover:bg-[#0353e9] transition-all shadow-lg hover:shadow-xl active:scale-95 disabled:opacity-50"
+            >
+              Odoslať odpoveď
+            </button>
+          </div>
+        </form>
       </div>
     `;
     }
@@ -413,6 +1073,15 @@ export class MessagesPageController {
                 </a>`
                       : ""
               }
+              ${
+                  msg.preferred_contact
+                      ? `
+                <span class="px-3 py-1 bg-amber-50 text-amber-700 text-[10px] font-black uppercase tracking-widest rounded-full border border-amber-100">
+                  Preferuje ${msg.preferred_contact === "phone" ? "telefón" : "email"}
+                </span>`
+                      : ""
+              }
+              ${spamScoreBadge(msg.spam_score)}
             </div>
             <div class="flex items-center gap-4 text-xs text-gray-500">
               <span class="flex items-center gap-1.5">
@@ -423,7 +1092,7 @@ export class MessagesPageController {
           </div>
           <div class="flex shrink-0 gap-3">
             <button
-              onclick="window.restoreMessage && window.restoreMessage(${msg.original_id})"
+              onclick="window.restoreMessage && window.restoreMessage(${msg.id})"
               class="px-5 py-2.5 bg-gray-50 border border-gray-200 text-gray-600 hover:bg-green-50 hover:text-green-600 hover:border-green-200 transition-all duration-300 flex items-center justify-center gap-2 text-[10px] font-black uppercase tracking-widest rounded-xl group"
               title="Obnoviť správu"
             >
@@ -443,6 +1112,7 @@ export class MessagesPageController {
         <div class="bg-gray-50 p-6 rounded-xl border-l-4 border-gray-300 text-gray-700 whitespace-pre-wrap break-words leading-relaxed text-sm font-medium italic">
           ${escapeHtml(msg.message)}
         </div>
+        <div data-attachments-for="${msg.original_id}" class="hidden mt-4 flex flex-wrap gap-3"></div>
       </div>
     `;
     }
@@ -526,9 +1196,10 @@ export class MessagesPageController {
             );
         }
 
-        // Hide active container and its empty state
+        // Hide active container, its empty state and bulk actions
         this.elements.activeContainer?.classList.add("hidden");
         this.elements.noActiveMessages?.classList.add("hidden");
+        this.elements.bulkActions?.classList.add("hidden");
 
         // Show archived container
         this.elements.archivedContainer?.classList.remove("hidden");
@@ -1,5 +1,16 @@
-import { api, type OfferSummary } from "../../lib/api";
-import { escapeHtml, showConfirmDialog } from "./utils";
+import {
+    api,
+    type AdminOfferStatus,
+    type OfferClickStats,
+    type OfferSummary,
+} from "../../lib/api";
+import {
+    escapeHtml,
+    showConfirmDialog,
+    suggestedSlug,
+    updatePaginationControls,
+} from "./utils";
+import type { PaginationState } from "./types";
 import L from "leaflet";
 import Cropper from "cropperjs";
 import { setMarkdownEditorValue } from "./markdown-editor";
@@ -41,6 +52,9 @@ export interface OffersPageElements {
     offerLink: HTMLInputElement | null;
     offerLatitude: HTMLInputElement | null;
     offerLongitude: HTMLInputElement | null;
+    offerActive: HTMLInputElement | null;
+    offerStartsAt: HTMLInputElement | null;
+    offerEndsAt: HTMLInputElement | null;
     offerImage: HTMLInputElement | null;
     imagePreview: HTMLElement | null;
     imagePreviewImg: HTMLImageElement | null;
@@ -49,8 +63,65 @@ export interface OffersPageElements {
     imageCropPreview: HTMLImageElement | null;
     cropApply: HTMLButtonElement | null;
     cropCancel: HTMLButtonElement | null;
+    orderBtn: HTMLElement | null;
+    orderModal: HTMLElement | null;
+    orderModalClose: HTMLElement | null;
+    orderList: HTMLElement | null;
+    importFile: HTMLInputElement | null;
+    clicksBtn: HTMLElement | null;
+    clicksModal: HTMLElement | null;
+    clicksModalClose: HTMLElement | null;
+    clicksRangeForm: HTMLFormElement | null;
+    clicksFrom: HTMLInputElement | null;
+    clicksTo: HTMLInputElement | null;
+    clicksList: HTMLElement | null;
+    statusFilter: HTMLSelectElement | null;
+    searchForm: HTMLFormElement | null;
+    searchInput: HTMLInputElement | null;
+    paginationControls: HTMLElement | null;
+    prevPageBtn: HTMLButtonElement | null;
+    nextPageBtn: HTMLButtonElement | null;
+    currentPageNum: HTMLElement | null;
 }
 
+const OFFERS_PER_PAGE = 12;
+/** Days shown in the click stats when the modal is opened */
+const CLICK_STATS_DAYS = 30;
+
+/** A local date as YYYY-MM-DD, `daysAgo` days before today */
+function dateInputValue(daysAgo = 0): string {
+    const date = new Date();
+    date.setDate(date.getDate() - daysAgo);
+    const offset = date.getTimezoneOffset() * 60000;
+    return new Date(date.getTime() - offset).toISOString().slice(0, 10);
+}
+
+/** UTC time from the API as the value of a datetime-local input */
+function toLocalInputValue(value: string | null): string {
+    if (!value) return "";
+    const date = new Date(`${value}Z`);
+    const offset = date.getTimezoneOffset() * 60000;
+    return new Date(date.getTime() - offset).toISOString().slice(0, 16);
+}
+
+/** Value of a datetime-local input as an RFC 3339 UTC time */
+function toUtcTime(value: string): string {
+    return value ? new Date(value).toISOString() : "";
+}
+
+/** Why an offer is not shown on the site, if it is not */
+function offerStatusLabel(offer: OfferSummary): string | null {
+    const now = Date.now();
+    if (!offer.active) return "Vypnutá";
+    if (offer.ends_at && new Date(`${offer.ends_at}Z`).getTime() <= now)
+        return "Skončená";
+    if (offer.starts_at && new Date(`${offer.starts_at}Z`).getTime() > now)
+        return "Naplánovaná";
+    return null;
+}
+/** Largest page of the admin offers API, used to load all offers */
+const MAX_OFFERS_PER_PAGE = 50;
+
 export interface OfferFormData {
     id?: string;
     title: string;
@@ -60,12 +131,17 @@ export interface OfferFormData {
     link: string;
     latitude?: string;
     longitude?: string;
+    active: boolean;
+    startsAt: string;
+    endsAt: string;
     imageFile?: File;
 }
 
 export class OffersPageController {
     private elements: OffersPageElements;
     private offersData: OfferSummary[] = [];
+    private currentPage = 1;
+    private orderedOffers: OfferSummary[] = [];
     private map: any = null;
     private marker: any = null;
     private cropper: any = null;
@@ -103,6 +179,43 @@ export class OffersPageController {
         offerImage?.addEventListener("change", () => this.handleImageChange());
         cropApply?.addEventListener("click", () => this.applyCrop());
         cropCancel?.addEventListener("click", () => this.cancelCrop());
+        this.elements.orderBtn?.addEventListener("click", () =>
+            this.openOrderModal(),
+        );
+        this.elements.orderModalClose?.addEventListener("click", () =>
+            this.closeOrderModal(),
+        );
+        this.elements.orderList?.addEventListener("click", (event) =>
+            this.handleOrderClick(event),
+        );
+        this.elements.importFile?.addEventListener("change", () =>
+            this.importOffers(),
+        );
+        this.elements.clicksBtn?.addEventListener("click", () =>
+            this.openClicksModal(),
+        );
+        this.elements.clicksModalClose?.addEventListener("click", () =>
+            this.elements.clicksModal?.classList.add("hidden"),
+        );
+        this.elements.clicksRangeForm?.addEventListener("submit", (event) => {
+            event.preventDefault();
+            this.loadClickStats();
+        });
+        this.elements.statusFilter?.addEventListener("change", () => {
+            this.currentPage = 1;
+            this.loadOffers();
+        });
+        this.elements.searchForm?.addEventListener("submit", (event) => {
+            event.preventDefault();
+            this.currentPage = 1;
+            this.loadOffers();
+        });
+        this.elements.prevPageBtn?.addEventListener("click", () =>
+            this.changePage(-1),
+        );
+        this.elements.nextPageBtn?.addEventListener("click", () =>
+            this.changePage(1),
+        );
 
         // Sync input fields with map
         offerLatitude?.addEventListener("input", () =>
@@ -121,7 +234,7 @@ export class OffersPageController {
         };
 
         window.deleteOffer = async (id: number) => {
-            showConfirmDialog("Zmazať?", async () => {
+            showConfirmDialog("Presunúť ponuku do koša?", async () => {
                 try {
                     await api.admin.deleteOffer(id);
                     await this.loadOffers();
@@ -140,7 +253,15 @@ export class OffersPageController {
         if (container) container.innerHTML = "";
 
         try {
-            this.offersData = await api.offers.getOffers();
+            const { data, total, limit } = await api.admin.getAllOffers({
+                page: this.currentPage,
+                limit: OFFERS_PER_PAGE,
+                status: (this.elements.statusFilter?.value ||
+                    "all") as AdminOfferStatus,
+                q: this.elements.searchInput?.value.trim() || undefined,
+            });
+            this.offersData = data;
+            this.updatePagination(total, limit);
 
             if (this.offersData.length === 0) {
                 noOffers?.classList.remove("hidden");
@@ -156,6 +277,191 @@ export class OffersPageController {
         }
     }
 
+    private updatePagination(total: number, limit: number): void {
+        const pagination: PaginationState = {
+            currentPage: this.currentPage,
+            totalPages: Math.ceil(total / limit),
+            totalItems: total,
+            itemsPerPage: limit,
+        };
+
+        updatePaginationControls(
+            pagination,
+            this.elements.currentPageNum,
+            this.elements.prevPageBtn,
+            this.elements.nextPageBtn,
+            this.elements.paginationControls,
+        );
+    }
+
+    private changePage(delta: number): void {
+        if (this.currentPage + delta < 1) return;
+        this.currentPage += delta;
+        this.loadOffers();
+        window.scrollTo({ top: 0, behavior: "smooth" });
+    }
+
+    private async loadAllOffers(): Promise<OfferSummary[]> {
+        const offers: OfferSummary[] = [];
+        for (let page = 1; ; page++) {
+            const { data, total } = await api.admin.getAllOffers({
+                page,
+                limit: MAX_OFFERS_PER_PAGE,
+            });
+            offers.push(...data);
+            if (data.length === 0 || offers.length >= total) return offers;
+        }
+    }
+
+    private async importOffers(): Promise<void> {
+        const { importFile } = this.elements;
+        const file = importFile?.files?.[0];
+        if (!file) return;
+
+        try {
+            const { imported, errors } = await api.admin.importOffers(file);
+            const rejected = errors
+                .map((error) => `Riadok ${error.row}: ${error.message}`)
+                .join("\n");
+            alert(
+                `Importované ponuky: ${imported.length}` +
+                    (errors.length > 0
+                        ? `\nNeimportované riadky: ${errors.length}\n${rejected}`
+                        : ""),
+            );
+            this.currentPage = 1;
+            await this.loadOffers();
+        } catch (error) {
+            console.error("Failed to import offers:", error);
+            const message =
+                error instanceof Error && error.message
+                    ? `\n${error.message}`
+                    : "";
+            alert(`Chyba pri importe ponúk.${message}`);
+        } finally {
+            if (importFile) importFile.value = "";
+        }
+    }
+
+    private openClicksModal(): void {
+        const { clicksFrom, clicksTo, clicksModal } = this.elements;
+        if (clicksFrom) clicksFrom.value = dateInputValue(CLICK_STATS_DAYS - 1);
+        if (clicksTo) clicksTo.value = dateInputValue();
+        clicksModal?.classList.remove("hidden");
+        this.loadClickStats();
+    }
+
+    private async loadClickStats(): Promise<void> {
+        const { clicksFrom, clicksTo, clicksList } = this.elements;
+        if (!clicksList) return;
+
+        try {
+            const stats = await api.admin.getOfferClickStats(
+                clicksFrom?.value || undefined,
+                clicksTo?.value || undefined,
+            );
+            this.renderClickStats(stats);
+        } catch (error) {
+            console.error("Failed to load offer clicks:", error);
+            alert("Chyba pri načítaní.");
+        }
+    }
+
+    private renderClickStats(stats: OfferClickStats[]): void {
+        const { clicksList } = this.elements;
+        if (!clicksList) return;
+
+        if (stats.length === 0) {
+            clicksList.innerHTML = `<li class="text-sm text-gray-500">Žiadne kliknutia v tomto období.</li>`;
+            return;
+        }
+        const busiestDay = Math.max(
+            ...stats.flatMap((offer) => offer.daily.map((day) => day.clicks)),
+        );
+        clicksList.innerHTML = stats
+            .map(
+                (offer) => `
+          <li class="px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl">
+            <div class="flex items-center justify-between gap-3">
+              <span class="text-sm font-bold text-gray-900 break-words">${escapeHtml(offer.title)}</span>
+              <span class="text-sm font-black text-primary">${offer.total}</span>
+            </div>
+            <div class="mt-2 flex items-end gap-px h-8">
+              ${offer.daily
+                  .map(
+                      (day) =>
+                          `<span title="${day.date}: ${day.clicks}" class="flex-1 max-w-3 bg-primary/60 rounded-sm" style="height: ${Math.max(10, Math.round((day.clicks / busiestDay) * 100))}%"></span>`,
+                  )
+                  .join("")}
+            </div>
+          </li>
+        `,
+            )
+            .join("");
+    }
+
+    private async openOrderModal(): Promise<void> {
+        try {
+            this.orderedOffers = await this.loadAllOffers();
+            this.renderOrderList();
+            this.elements.orderModal?.classList.remove("hidden");
+        } catch (error) {
+            console.error("Failed to load offers:", error);
+            alert("Chyba pri načítaní.");
+        }
+    }
+
+    private closeOrderModal(): void {
+        this.elements.orderModal?.classList.add("hidden");
+        this.orderedOffers = [];
+        this.loadOffers();
+    }
+
+    private renderOrderList(): void {
+        const { orderList } = this.elements;
+        if (!orderList) return;
+
+        const last = this.orderedOffers.length - 1;
+        orderList.innerHTML = this.orderedOffers
+            .map(
+                (offer, index) => `
+          <li class="flex items-center gap-3 px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl">
+            <span class="w-6 text-xs font-black text-gray-400">${index + 1}.</span>
+            <span class="grow text-sm font-bold text-gray-900 break-words">${escapeHtml(offer.title)}</span>
+            <button type="button" data-order-action="up" data-index="${index}" aria-label="Posunúť vyššie" class="px-2 text-gray-500 hover:text-primary disabled:opacity-30" ${index === 0 ? "disabled" : ""}>↑</button>
+            <button type="button" data-order-action="down" data-index="${index}" aria-label="Posunúť nižšie" class="px-2 text-gray-500 hover:text-primary disabled:opacity-30" ${index === last ? "disabled" : ""}>↓</button>
+          </li>`,
+            )
+            .join("");
+    }
+
+    private handleOrderClick(event: Event): void {
+        const button = (event.target as HTMLElement).closest<HTMLElement>(
+            "[data-order-action]",
+        );
+        if (!button) return;
+
+        const index = Number(button.dataset.index);
+        const target =
+            button.dataset.orderAction === "up" ? index - 1 : index + 1;
+        this.moveOffer(index, target);
+    }
+
+    private async moveOffer(from: number, to: number): Promise<void> {
+        if (to < 0 || to >= this.orderedOffers.length) return;
+
+        const reordered = [...this.orderedOffers];
+        [reordered[from], reordered[to]] = [reordered[to], reordered[from]];
+        try {
+            await api.admin.reorderOffers(reordered.map((offer) => offer.id));
+            this.orderedOffers = reordered;
+            this.renderOrderList();
+        } catch (error) {
+            console.error("Failed to reorder offers:", error);
+            alert("Chyba pri zmene poradia ponúk.");
+        }
+    }
+
     private renderOffers(): void {
         if (!this.elements.container) return;
 
@@ -199,11 +505,15 @@ export class OffersPageController {
         const title = escapeHtml(offer.title);
         const slug = escapeHtml(offer.slug);
         const excerpt = escapeHtml(offer.excerpt ?? offer.content ?? "");
+        const status = offerStatusLabel(offer);
 
         return `
       <div class="h-full bg-white border border-gray-200 rounded-2xl p-6 shadow-sm transition-all duration-300 hover:shadow-xl hover:border-primary/20 hover:-translate-y-1 flex flex-col">
         ${offer.image_mime ? `<img src="${imageUrl}" alt="${title}" class="w-full h-48 object-cover rounded-lg mb-4">` : ""}
-        <h3 class="m-0 text-lg font-bold text-gray-900 leading-tight mb-3 break-words">${title}</h3>
+        <div class="flex items-start justify-between gap-4 mb-3">
+          <h3 class="m-0 text-lg font-bold text-gray-900 leading-tight break-words">${title}</h3>
+          ${status ? `<span class="px-2 py-1 text-xs font-bold uppercase tracking-wider bg-gray-100 text-gray-500 rounded">${status}</span>` : ""}
+        </div>
         <p class="text-sm text-gray-500 mb-4"><strong>Slug:</strong> <code class="bg-gray-100 px-2 py-1 rounded text-xs break-all">${slug}</code></p>
         ${excerpt ? `<p class="text-sm text-gray-600 mb-4 line-clamp-3 break-words">${excerpt}</p>` : ""}
         <div class="mt-auto pt-4 flex gap-2">
@@ -236,6 +546,9 @@ export class OffersPageController {
             offerLink,
             offerLatitude,
             offerLongitude,
+            offerActive,
+            offerStartsAt,
+            offerEndsAt,
             offerImage,
         } = this.elements;
 
@@ -248,6 +561,9 @@ export class OffersPageController {
             link: offerLink?.value || "",
             latitude: offerLatitude?.value || "",
             longitude: offerLongitude?.value || "",
+            active: offerActive?.checked ?? true,
+            startsAt: offerStartsAt?.value || "",
+            endsAt: offerEndsAt?.value || "",
             imageFile: offerImage?.files?.[0],
         };
     }
@@ -263,6 +579,11 @@ export class OffersPageController {
             if (formData.latitude) data.append("latitude", formData.latitude);
             if (formData.longitude)
                 data.append("longitude", formData.longitude);
+            data.append("active", String(formData.active));
+            if (formData.startsAt)
+                data.append("starts_at", toUtcTime(formData.startsAt));
+            if (formData.endsAt)
+                data.append("ends_at", toUtcTime(formData.endsAt));
 
             if (formData.id) {
                 // If image is provided, update it. Otherwise, don't send the field and backend keeps existing image
@@ -271,20 +592,40 @@ export class OffersPageController {
                 } else if (formData.imageFile) {
                     data.append("image", formData.imageFile);
                 }
-                await api.admin.updateOffer(parseInt(formData.id, 10), data);
-            } else {
-                if (this.croppedImageBlob) {
-                    data.append("image", this.croppedImageBlob, "image.jpg");
-                } else if (formData.imageFile) {
-                    data.append("image", formData.imageFile);
-                }
-                await api.admin.createOffer(data);
+                const updated = await api.admin.updateOffer(
+                    parseInt(formData.id, 10),
+                    data,
+                );
+                // Show the saved offer in place, keeping the page and order
+                this.offersData = this.offersData.map((offer) =>
+                    offer.id === updated.id ? updated : offer,
+                );
+                this.closeModal();
+                this.renderOffers();
+                return;
             }
 
+            if (this.croppedImageBlob) {
+                data.append("image", this.croppedImageBlob, "image.jpg");
+            } else if (formData.imageFile) {
+                data.append("image", formData.imageFile);
+            }
+            await api.admin.createOffer(data);
+
             this.closeModal();
             await this.loadOffers();
         } catch (error) {
             console.error("Failed to save offer:", error);
+            const suggested = suggestedSlug(error);
+            if (suggested !== null) {
+                if (this.elements.offerSlug) {
+                    this.elements.offerSlug.value = suggested;
+                }
+                alert(
+                    `Adresa "${formData.slug}" je už použitá. Navrhujeme "${suggested}", uložte znova.`,
+                );
+                return;
+            }
             alert("Chyba pri ukladaní.");
         }
     }
@@ -302,6 +643,9 @@ export class OffersPageController {
             offerLink,
             offerLatitude,
             offerLongitude,
+            offerActive,
+            offerStartsAt,
+            offerEndsAt,
             imagePreview,
             imagePreviewImg,
             modal,
@@ -321,6 +665,10 @@ export class OffersPageController {
             offerLongitude.value = offer.longitude
                 ? String(offer.longitude)
                 : "";
+        if (offerActive) offerActive.checked = offer.active;
+        if (offerStartsAt)
+            offerStartsAt.value = toLocalInputValue(offer.starts_at);
+        if (offerEndsAt) offerEndsAt.value = toLocalInputValue(offer.ends_at);
         if (imagePreview && imagePreviewImg) {
             imagePreview.classList.remove("hidden");
             imagePreviewImg.src = api.offers.getOfferImageUrl(offer.id);
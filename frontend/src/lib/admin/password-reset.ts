@@ -0,0 +1,134 @@
+import { api } from "../api";
+
+function getResetToken(): string | null {
+    return new URLSearchParams(window.location.search).get("token");
+}
+
+function showError(element: HTMLDivElement, message: string): void {
+    element.textContent = message;
+    element.classList.remove("hidden");
+}
+
+function initRequestForm(
+    subtitle: HTMLParagraphElement,
+    errorMessage: HTMLDivElement,
+    successMessage: HTMLDivElement,
+): void {
+    const form = document.getElementById(
+        "reset-request-form",
+    ) as HTMLFormElement | null;
+    const usernameInput = document.getElementById(
+        "reset-username",
+    ) as HTMLInputElement | null;
+    const submitBtn = document.getElementById(
+        "reset-request-btn",
+    ) as HTMLButtonElement | null;
+
+    if (!form || !usernameInput || !submitBtn) {
+        return;
+    }
+
+    subtitle.textContent = "Pošleme vám odkaz na zmenu hesla";
+    form.classList.remove("hidden");
+
+    form.addEventListener("submit", async (event) => {
+        event.preventDefault();
+
+        try {
+            submitBtn.disabled = true;
+            errorMessage.classList.add("hidden");
+
+            await api.admin.requestPasswordReset(usernameInput.value.trim());
+            form.classList.add("hidden");
+            successMessage.textContent =
+                "Ak účet existuje, odkaz na obnovenie hesla bol odoslaný na jeho e-mail.";
+            successMessage.classList.remove("hidden");
+        } catch (error) {
+            showError(
+                errorMessage,
+                error instanceof Error
+                    ? error.message
+                    : "Nepodarilo sa odoslať žiadosť.",
+            );
+            submitBtn.disabled = false;
+        }
+    });
+}
+
+function initConfirmForm(
+    token: string,
+    subtitle: HTMLParagraphElement,
+    errorMessage: HTMLDivElement,
+): void {
+    const form = document.getElementById(
+        "reset-confirm-form",
+    ) as HTMLFormElement | null;
+    const passwordInput = document.getElementById(
+        "password",
+    ) as HTMLInputElement | null;
+    const confirmInput = document.getElementById(
+        "confirm-password",
+    ) as HTMLInputElement | null;
+    const submitBtn = document.getElementById(
+        "reset-confirm-btn",
+    ) as HTMLButtonElement | null;
+
+    if (!form || !passwordInput || !confirmInput || !submitBtn) {
+        return;
+    }
+
+    subtitle.textContent = "Zvoľte si nové heslo";
+    form.classList.remove("hidden");
+
+    form.addEventListener("submit", async (event) => {
+        event.preventDefault();
+
+        if (passwordInput.value !== confirmInput.value) {
+            showError(errorMessage, "Heslá sa nezhodujú.");
+            return;
+        }
+
+        try {
+            submitBtn.disabled = true;
+            passwordInput.disabled = true;
+            confirmInput.disabled = true;
+            errorMessage.classList.add("hidden");
+
+            await api.admin.confirmPasswordReset(token, passwordInput.value);
+            window.location.href = "/admin/login";
+        } catch (error) {
+            showError(
+                errorMessage,
+                error instanceof Error
+                    ? error.message
+                    : "Odkaz je neplatný alebo expirovaný.",
+            );
+            submitBtn.disabled = false;
+            passwordInput.disabled = false;
+            confirmInput.disabled = false;
+        }
+    });
+}
+
+export function initAdminPasswordReset(): void {
+    const subtitle = document.getElementById(
+        "reset-subtitle",
+    ) as HTMLParagraphElement | null;
+    const errorMessage = document.getElementById(
+        "error-message",
+    ) as HTMLDivElement | null;
+    const successMessage = document.getElementById(
+        "success-message",
+    ) as HTMLDivElement | null;
+
+    if (!subtitle || !errorMessage || !successMessage) {
+        return;
+    }
+
+    const token = getResetToken();
+    if (token) {
+        initConfirmForm(token, subtitle, errorMessage);
+    } else {
+        initRequestForm(subtitle, errorMessage, successMessage);
+    }
+}
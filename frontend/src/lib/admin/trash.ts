@@ -0,0 +1,136 @@
+import { api, type TrashItem, type TrashItemKind } from "../../lib/api";
+import { escapeHtml, showConfirmDialog } from "./utils";
+
+/** Most recently deleted items shown, the server's largest page */
+const TRASH_LIMIT = 100;
+
+export interface TrashModalElements {
+    button: HTMLElement | null;
+    modal: HTMLElement | null;
+    modalClose: HTMLElement | null;
+    list: HTMLElement | null;
+}
+
+/** Trash of one kind of content, opened from that content's admin page */
+export class TrashModalController {
+    private kind: TrashItemKind;
+    private elements: TrashModalElements;
+    private onRestore: () => void;
+
+    constructor(
+        kind: TrashItemKind,
+        elements: TrashModalElements,
+        onRestore: () => void,
+    ) {
+        this.kind = kind;
+        this.elements = elements;
+        this.onRestore = onRestore;
+
+        elements.button?.addEventListener("click", () => this.open());
+        elements.modalClose?.addEventListener("click", () =>
+            elements.modal?.classList.add("hidden"),
+        );
+        elements.list?.addEventListener("click", (event) =>
+            this.handleClick(event),
+        );
+    }
+
+    private async open(): Promise<void> {
+        await this.load();
+        this.elements.modal?.classList.remove("hidden");
+    }
+
+    private async load(): Promise<void> {
+        try {
+            const { data } = await api.admin.getTrash(
+                this.kind,
+                1,
+                TRASH_LIMIT,
+            );
+            this.render(data);
+        } catch (error) {
+            console.error("Failed to load trash:", error);
+            alert("Chyba pri načítaní.");
+        }
+    }
+
+    private render(items: TrashItem[]): void {
+        const { list } = this.elements;
+        if (!list) return;
+
+        if (items.length === 0) {
+            list.innerHTML = `<li class="text-sm text-gray-500">Kôš je prázdny.</li>`;
+            return;
+        }
+        list.innerHTML = items
+            .map(
+                (item) => `
+          <li class="flex items-center justify-between gap-3 px-4 py-3 bg-gray-50 border border-gray-200 rounded-xl">
+            <div class="min-w-0">
+              <div class="text-sm font-bold text-gray-900 break-words">${escapeHtml(item.title)}</div>
+              <div class="text-xs text-gray-500">Zmazané ${new Date(item.deleted_at).toLocaleString("sk-SK")}</div>
+            </div>
+            <div class="flex gap-2 shrink-0">
+              <button type="button" data-trash-action="restore" data-id="${item.id}" class="px-3 py-2 bg-white border border-gray-200 hover:border-primary text-gray-700 font-bold text-xs rounded-lg">Obnoviť</button>
+              <button type="button" data-trash-action="delete" data-id="${item.id}" class="px-3 py-2 bg-white border border-gray-200 hover:border-red-500 text-red-500 font-bold text-xs rounded-lg">Zmazať natrvalo</button>
+            </div>
+          </li>
+        `,
+            )
+            .join("");
+    }
+
+    private handleClick(event: Event): void {
+        const button = (event.target as HTMLElement).closest<HTMLElement>(
+            "[data-trash-action]",
+        );
+        if (!button) return;
+
+        const id = Number(button.dataset.id);
+        if (button.dataset.trashAction === "restore") {
+            this.restore(id);
+        } else {
+            showConfirmDialog(
+                "Zmazať natrvalo? Túto akciu nie je možné vrátiť.",
+                () => this.permanentlyDelete(id),
+            );
+        }
+    }
+
+    private async restore(id: number): Promise<void> {
+        try {
+            if (this.kind === "offer") {
+                await api.admin.restoreTrashedOffer(id);
+            } else {
+                await api.admin.restoreTrashedBlogPost(id);
+            }
+            await this.load();
+            this.onRestore();
+        } catch (error) {
+            console.error("Failed to restore from trash:", error);
+            alert("Chyba pri obnovení.");
+        }
+    }
+
+    private async permanentlyDelete(id: number): Promise<void> {
+        try {
+            if (this.kind === "offer") {
+                await api.admin.permanentlyDeleteOffer(id);
+            } else {
+                await api.admin.permanentlyDeleteBlogPost(id);
+            }
+            await this.load();
+        } catch (error) {
+            console.error("Failed to delete from trash:", error);
+            alert("Chyba pri mazaní.");
+        }
+    }
+}
+
+export function initializeTrashModal(
+    kind: TrashItemKind,
+    elements: TrashModalElements,
+    onRestore: () => void,
+): TrashModalController {
+    return new TrashModalController(kind, elements, onRestore);
+}
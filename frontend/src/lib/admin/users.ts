@@ -30,11 +30,13 @@ export interface UsersPageElements {
     userId: HTMLInputElement | null;
     username: HTMLInputElement | null;
     password: HTMLInputElement | null;
+    email: HTMLInputElement | null;
     inviteModal: HTMLElement | null;
     inviteModalClose: HTMLButtonElement | null;
     inviteModalCancel: HTMLButtonElement | null;
     inviteForm: HTMLFormElement | null;
     inviteUsername: HTMLInputElement | null;
+    inviteEmail: HTMLInputElement | null;
 }
 
 class UsersPageController {
@@ -174,6 +176,8 @@ class UsersPageController {
           ${isCurrentUser ? '<span class="px-2 py-1 text-xs font-bold uppercase tracking-wider bg-blue-100 text-primary rounded">Vy</span>' : ""}
         </div>
         <p class="text-sm text-gray-500 mb-2"><strong>ID:</strong> <code class="bg-gray-100 px-2 py-1 rounded text-xs">${user.id}</code></p>
+        <p class="text-sm text-gray-500 mb-2"><strong>Rola:</strong> ${escapeHtml(user.role)}</p>
+        <p class="text-sm text-gray-500 mb-2 break-all"><strong>E-mail:</strong> ${user.email ? escapeHtml(user.email) : "—"}</p>
         <div class="text-xs text-gray-400 space-y-1 mb-4">
           <div>Vytvorený: ${createdAt}</div>
           <div>Aktualizovaný: ${updatedAt}</div>
@@ -222,6 +226,8 @@ class UsersPageController {
         if (this.elements.username)
             this.elements.username.value = user?.username ?? "";
         if (this.elements.password) this.elements.password.value = "";
+        if (this.elements.email)
+            this.elements.email.value = user?.email ?? "";
         this.elements.modal?.classList.remove("hidden");
     }
 
@@ -299,6 +305,7 @@ class UsersPageController {
         const id = this.elements.userId?.value.trim();
         const username = this.elements.username?.value.trim() || "";
         const password = this.elements.password?.value || "";
+        const email = this.elements.email?.value.trim() || "";
 
         try {
             if (id) {
@@ -306,13 +313,20 @@ class UsersPageController {
                     Number(id),
                     username,
                     password || undefined,
+                    undefined,
+                    email,
                 );
             } else {
                 if (!password) {
                     alert("Pri vytváraní používateľa je heslo povinné.");
                     return;
                 }
-                await api.admin.createUser(username, password);
+                await api.admin.createUser(
+                    username,
+                    password,
+                    undefined,
+                    email,
+                );
             }
 
             this.closeModal();
@@ -331,12 +345,22 @@ class UsersPageController {
         event.preventDefault();
 
         const username = this.elements.inviteUsername?.value.trim() || "";
+        const email = this.elements.inviteEmail?.value.trim() || "";
 
         try {
-            const invite = await api.admin.createInvite(username);
+            const invite = await api.admin.createInvite(
+                username,
+                undefined,
+                email,
+            );
             this.closeInviteModal();
             await this.loadData();
 
+            if (invite.email_sent) {
+                alert("Pozvánka bola odoslaná na e-mail.");
+                return;
+            }
+
             const inviteUrl = new URL(
                 invite.invite_path,
                 window.location.origin,
@@ -1,3 +1,4 @@
+import { ApiError } from "../api-client";
 import type { ConfirmCallback, PaginationState, TabConfig } from "./types";
 
 export function escapeHtml(text?: string | null): string {
@@ -10,6 +11,13 @@ export function escapeHtml(text?: string | null): string {
         .replace(/'/g, "&#039;");
 }
 
+/** Free slug offered by the server when a save hit a duplicate slug. */
+export function suggestedSlug(error: unknown): string | null {
+    if (!(error instanceof ApiError) || error.status !== 409) return null;
+    const data = error.data as { suggested_slug?: string | null } | undefined;
+    return data?.suggested_slug ?? null;
+}
+
 export function showConfirmDialog(
     message: string,
     onConfirm: ConfirmCallback,
@@ -155,6 +155,23 @@ export class ApiClient {
         return this.handleResponse<T>(response);
     }
 
+    async patch<T>(path: string, options: ApiRequestOptions = {}): Promise<T> {
+        const { params, body, ...fetchOptions } = options;
+        const url = this.buildUrl(path, params);
+
+        const response = await fetch(url, {
+            ...fetchOptions,
+            method: "PATCH",
+            headers: {
+                ...this.config.defaultHeaders,
+                ...fetchOptions.headers,
+            },
+            body,
+        });
+
+        return this.handleResponse<T>(response);
+    }
+
     async delete<T>(path: string, options: ApiRequestOptions = {}): Promise<T> {
         const { params, ...fetchOptions } = options;
         const url = this.buildUrl(path, params);
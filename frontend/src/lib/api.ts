@@ -6,6 +6,37 @@ export interface Message {
     subject: string | null;
     message: string;
     created_at: string;
+    preferred_contact: "email" | "phone" | null;
+    spam_score: number;
+    /** `null` until an admin marks the message as read */
+    read_at: string | null;
+    /** When the last reply was sent; `null` until an admin replies */
+    answered_at: string | null;
+    /** The offer the inquiry was sent from, if any */
+    offer_id: number | null;
+}
+
+/** Inclusive spam score bounds for filtering the message list */
+export interface SpamScoreFilter {
+    min?: number;
+    max?: number;
+}
+
+export type ArchiveSort =
+    | "archived_desc"
+    | "archived_asc"
+    | "created_desc"
+    | "created_asc"
+    | "relevance";
+
+/** Search, `YYYY-MM-DD` date ranges and order of the archived message list */
+export interface ArchiveFilter {
+    q?: string;
+    createdFrom?: string;
+    createdTo?: string;
+    archivedFrom?: string;
+    archivedTo?: string;
+    sort?: ArchiveSort;
 }
 
 export interface PaginatedMessages {
@@ -15,6 +46,47 @@ export interface PaginatedMessages {
     limit: number;
 }
 
+export interface AuditLogEntry {
+    id: number;
+    admin_user_id: number | null;
+    admin_username: string;
+    action: string;
+    entity_type: string;
+    entity_id: string;
+    ip_address: string | null;
+    created_at: string;
+}
+
+export interface PaginatedAuditLog {
+    data: AuditLogEntry[];
+    total: number;
+    page: number;
+    limit: number;
+}
+
+export type ApiKeyScope =
+    | "content"
+    | "messages"
+    | "delete_messages"
+    | "users"
+    | "audit";
+
+export interface ApiKey {
+    id: number;
+    user_id: number;
+    name: string;
+    key_prefix: string;
+    scopes: ApiKeyScope[];
+    expires_at: string | null;
+    last_used_at: string | null;
+    created_at: string;
+}
+
+export interface ApiKeyCreated {
+    key: string;
+    api_key: ApiKey;
+}
+
 export interface ArchivedMessage {
     id: number;
     original_id: number;
@@ -25,6 +97,131 @@ export interface ArchivedMessage {
     message: string;
     created_at: string;
     archived_at: string;
+    preferred_contact: "email" | "phone" | null;
+    spam_score: number;
+    /** `null` until an admin marks the message as read */
+    read_at: string | null;
+    /** When the last reply was sent; `null` until an admin replies */
+    answered_at: string | null;
+}
+
+export interface ContactSubmission {
+    name: string;
+    email: string;
+    phone?: string;
+    subject?: string;
+    message: string;
+    preferred_contact?: string;
+    /** Id of the offer the inquiry is about */
+    offer_id?: number;
+    company?: string;
+    captcha_token?: string;
+    /** Unix timestamp in milliseconds of when the form was rendered */
+    form_started_at?: number;
+}
+
+export interface MessageAttachment {
+    id: number;
+    message_id: number;
+    filename: string;
+    content_type: string;
+    size_bytes: number;
+    created_at: string;
+}
+
+export interface MessageReply {
+    id: number;
+    message_id: number;
+    admin_user_id: number | null;
+    admin_username: string;
+    subject: string;
+    body: string;
+    sent_at: string;
+}
+
+/** Internal staff note on a message */
+export interface MessageNote {
+    id: number;
+    message_id: number;
+    admin_user_id: number | null;
+    admin_username: string;
+    body: string;
+    created_at: string;
+}
+
+/** Category admins sort messages into */
+export interface Label {
+    id: number;
+    name: string;
+    /** Badge color as `#rrggbb` */
+    color: string;
+    created_at: string;
+}
+
+export type BulkMessageAction = "archive" | "delete" | "mark_read";
+
+export interface BulkMessagesResponse {
+    action: BulkMessageAction;
+    processed: number;
+    missing: number[];
+}
+
+/** A message with its attachments, reply history, internal notes and labels */
+export interface MessageDetail extends Message {
+    attachments: MessageAttachment[];
+    replies: MessageReply[];
+    notes: MessageNote[];
+    labels: Label[];
+}
+
+/** A message in a sender's thread, active or archived */
+export interface ThreadMessage {
+    state: "active" | "archived";
+    /** Id of the active message, or the original id of an archived one */
+    message_id: number;
+    archive_id: number | null;
+    name: string;
+    email: string;
+    phone: string | null;
+    subject: string | null;
+    message: string;
+    created_at: string;
+    archived_at: string | null;
+    preferred_contact: "email" | "phone" | null;
+    spam_score: number;
+    read_at: string | null;
+    answered_at: string | null;
+    replies: MessageReply[];
+}
+
+/** Every message from one sender email, oldest first */
+export interface MessageThread {
+    email: string;
+    messages: ThreadMessage[];
+}
+
+export type CaptchaProvider = "hcaptcha" | "turnstile" | "recaptcha";
+
+export interface CaptchaSettings {
+    provider: CaptchaProvider | null;
+    site_key: string | null;
+}
+
+/** Optional contact fields the backend requires */
+export interface ContactRequiredFields {
+    phone: boolean;
+    subject: boolean;
+}
+
+export interface ContactFieldError {
+    field: string;
+    message: string;
+}
+
+export interface ContactSubmitResponse {
+    success: boolean;
+    message: string;
+    errors: ContactFieldError[];
 }
 
 export interface PaginatedArchivedMessages {
@@ -34,6 +231,16 @@ export interface PaginatedArchivedMessages {
     limit: number;
 }
 
+/** An image of an offer; `url` stays the same when images are reordered */
+export interface OfferImage {
+    id: number;
+    offer_id: number;
+    image_mime: string;
+    position: number;
+    created_at: string;
+    url: string;
+}
+
 export interface OfferSummary {
     id: number;
     title: string;
@@ -41,22 +248,277 @@ export interface OfferSummary {
     excerpt: string | null;
     content: string | null;
     link: string | null;
+    /** Type of the primary image, the first of `images` */
     image_mime: string | null;
+    /** URL of the primary image */
+    image_url: string | null;
+    images: OfferImage[];
     created_at: string;
+    updated_at: string;
+    short_code: string;
     latitude?: number | null;
     longitude?: number | null;
+    /** Place in the order set by admins, lowest first */
+    sort_order: number;
+    /** Switched off offers are hidden from the site */
+    active: boolean;
+    /** Time window the offer is shown in, as UTC times; null is no limit */
+    starts_at: string | null;
+    ends_at: string | null;
+}
+
+export type AdminOfferStatus =
+    | "all"
+    | "active"
+    | "scheduled"
+    | "expired"
+    | "inactive";
+
+export interface PaginatedOffers {
+    data: OfferSummary[];
+    total: number;
+    page: number;
+    limit: number;
 }
 
+export interface OfferImportReport {
+    /** Created offers with their row in the file, the header being row 1 */
+    imported: { row: number; id: number; title: string; slug: string }[];
+    errors: { row: number; message: string }[];
+}
+
+export interface DailyOfferClicks {
+    /** UTC day as YYYY-MM-DD */
+    date: string;
+    clicks: number;
+}
+
+export interface OfferClickStats {
+    offer_id: number;
+    title: string;
+    slug: string;
+    total: number;
+    /** Only days with clicks, oldest first */
+    daily: DailyOfferClicks[];
+}
+
+export type TrashItemKind = "offer" | "blog_post";
+
+/** A deleted offer or blog post that can still be restored */
+export interface TrashItem {
+    type: TrashItemKind;
+    id: number;
+    title: string;
+    slug: string;
+    deleted_at: string;
+}
+
+export interface PaginatedTrash {
+    data: TrashItem[];
+    total: number;
+    page: number;
+    limit: number;
+}
+
+export interface OfferFilter {
+    page?: number;
+    limit?: number;
+    /** Admin list only */
+    status?: AdminOfferStatus;
+    /** Full-text search on the public list, titles only in the admin list */
+    q?: string;
+}
+
+export interface Tag {
+    id: number;
+    name: string;
+    slug: string;
+    created_at: string;
+}
+
+export interface TagUsage extends Tag {
+    post_count: number;
+}
+
+/** An image of a blog post; `url` stays the same when images are reordered */
+export interface BlogPostImage {
+    id: number;
+    blog_post_id: number;
+    image_mime: string;
+    position: number;
+    created_at: string;
+    url: string;
+}
+
+export interface BlogAuthor {
+    id: number;
+    name: string;
+    /** Gravatar image of the author's email */
+    avatar_url: string | null;
+}
+
+/** An author with the number of their published posts */
+export type BlogAuthorSummary = BlogAuthor & { post_count: number };
+
 export interface BlogPost {
     id: number;
     title: string;
     slug: string;
     excerpt: string | null;
+    /** Markdown source */
     content: string;
+    /** Content rendered and sanitized by the server */
+    content_html: string;
+    /** Words in the text of `content`, counted when the post is saved */
+    word_count: number;
+    /** Estimated minutes to read `content` */
+    reading_time_minutes: number;
+    meta_title: string | null;
+    meta_description: string | null;
+    canonical_url: string | null;
+    og_image: string | null;
+    /** `null` for posts without a recorded or remaining author */
+    author: BlogAuthor | null;
+    tags: Tag[];
+    /** Type of the cover, the first of `images` */
     image_mime: string | null;
+    /** URL of the cover image */
+    image_url: string | null;
+    images: BlogPostImage[];
     published: boolean;
+    /** Highlighted on the homepage, ordered by `featured_order` */
+    featured: boolean;
+    featured_order: number;
+    /** Set while the post is archived and hidden from the public site */
+    archived_at: string | null;
+    /** Reads counted so far; recent views are saved in batches */
+    view_count: number;
     created_at: string;
     updated_at: string;
+    short_code: string;
+}
+
+/** A saved earlier version of a blog post, without its content */
+export interface BlogPostRevisionSummary {
+    id: number;
+    blog_post_id: number;
+    title: string;
+    slug: string;
+    published: boolean;
+    admin_user_id: number | null;
+    admin_username: string;
+    created_at: string;
+}
+
+export interface BlogPostRevisionDiff {
+    from: BlogPostRevisionSummary;
+    /** `null` when compared with the current post */
+    to: BlogPostRevisionSummary | null;
+    changed_fields: string[];
+    content_diff: string;
+}
+
+/** Blog post in the public list, without its content */
+export type BlogPostSummary = Omit<
+    BlogPost,
+    | "content"
+    | "content_html"
+    | "word_count"
+    | "images"
+    | "author"
+    | "published"
+    | "featured"
+    | "featured_order"
+    | "archived_at"
+    | "view_count"
+    | "meta_title"
+    | "meta_description"
+    | "canonical_url"
+    | "og_image"
+>;
+
+/** SEO and OpenGraph metadata of a published post, with fallbacks filled in */
+export interface BlogPostMeta {
+    title: string;
+    description: string | null;
+    canonical_url: string;
+    og_image: string | null;
+    og_type: string;
+    site_name: string;
+    published_time: string;
+    modified_time: string;
+}
+
+/** A post in the admin list, drafts included, without its content */
+export type AdminBlogPostSummary = BlogPostSummary & {
+    published: boolean;
+    featured: boolean;
+    featured_order: number;
+    archived_at: string | null;
+    view_count: number;
+};
+
+export interface PaginatedAdminBlogPosts {
+    data: AdminBlogPostSummary[];
+    total: number;
+    page: number;
+    limit: number;
+}
+
+/** `all` is every post that is not archived */
+/** A post created by a blog import */
+export interface BlogImportedPost {
+    id: number;
+    title: string;
+    slug: string;
+    published: boolean;
+    images: number;
+}
+
+export interface BlogPreviewLink {
+    url: string;
+    token: string;
+    expires_at: string;
+}
+
+export type AdminBlogPostStatus = "all" | "draft" | "published" | "archived";
+
+export interface AdminBlogPostFilter {
+    page?: number;
+    limit?: number;
+    status?: AdminBlogPostStatus;
+    /** Text searched in titles */
+    q?: string;
+}
+
+/** A published post with its read count */
+export type PopularBlogPost = BlogPostSummary & { view_count: number };
+
+export interface PaginatedBlogPosts {
+    data: BlogPostSummary[];
+    total: number;
+    page: number;
+    limit: number;
+}
+
+export interface BlogPostFilter {
+    page?: number;
+    limit?: number;
+    tag?: string;
+    /** Id of the author */
+    author?: number;
+    q?: string;
+}
+
+export interface SearchResult {
+    type: "blog_post" | "offer";
+    id: number;
+    title: string;
+    slug: string;
+    url: string;
+    /** Escaped text around the first match, with matches in `<mark>` */
+    snippet_html: string;
+    score: number;
 }
 
 export interface AdminStatus {
@@ -64,11 +526,30 @@ export interface AdminStatus {
     setup_required: boolean;
     current_user_id: number | null;
     current_username: string | null;
+    current_role: AdminRole | null;
+    oidc_enabled: boolean;
+}
+
+export interface AdminSessionRefresh {
+    expires_in_secs: number;
 }
 
+export interface AdminSession {
+    id: string;
+    created_at: string;
+    expires_at: string;
+    ip_address: string | null;
+    user_agent: string | null;
+    current: boolean;
+}
+
+export type AdminRole = "editor" | "moderator" | "superadmin";
+
 export interface AdminUser {
     id: number;
     username: string;
+    role: AdminRole;
+    email: string | null;
     created_at: string;
     updated_at: string;
 }
@@ -76,8 +557,11 @@ export interface AdminUser {
 export interface AdminUserInvite {
     id: number;
     username: string;
+    role: AdminRole;
     token: string;
     invite_path: string;
+    email: string | null;
+    email_sent: boolean;
     expires_at: string;
     created_at: string;
 }
@@ -94,6 +578,18 @@ export interface Banner {
     updated_at: string;
 }
 
+export interface EmailTemplate {
+    id: number;
+    name: string;
+    subject: string;
+    body: string;
+    is_active: boolean;
+    /** Placeholders such as `name` usable as `{name}` */
+    placeholders: string[];
+    created_at: string;
+    updated_at: string;
+}
+
 import { apiClient } from "./api-client";
 
 class AdminApi {
@@ -101,9 +597,17 @@ class AdminApi {
         return apiClient.get<AdminStatus>("/admin/status");
     }
 
-    async login(username: string, password: string): Promise<void> {
+    async login(
+        username: string,
+        password: string,
+        rememberMe = false,
+    ): Promise<void> {
         await apiClient.post<void>("/admin/login", {
-            body: JSON.stringify({ username, password }),
+            body: JSON.stringify({
+                username,
+                password,
+                remember_me: rememberMe,
+            }),
         });
     }
 
@@ -128,41 +632,237 @@ class AdminApi {
         });
     }
 
+    async requestPasswordReset(username: string): Promise<void> {
+        await apiClient.post<void>("/admin/password-reset/request", {
+            body: JSON.stringify({ username }),
+        });
+    }
+
+    async confirmPasswordReset(token: string, password: string): Promise<void> {
+        await apiClient.post<void>("/admin/password-reset/confirm", {
+            body: JSON.stringify({ token, password }),
+        });
+    }
+
     async logout(): Promise<void> {
         await apiClient.post<void>("/admin/logout");
     }
 
+    async refreshSession(): Promise<AdminSessionRefresh> {
+        return apiClient.post<AdminSessionRefresh>("/admin/refresh");
+    }
+
+    async getSessions(): Promise<AdminSession[]> {
+        return apiClient.get<AdminSession[]>("/admin/api/sessions");
+    }
+
+    async revokeSession(id: string): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/sessions/${id}`);
+    }
+
+    async revokeAllSessions(): Promise<void> {
+        return apiClient.delete<void>("/admin/api/sessions");
+    }
+
+    async getAuditLog(
+        page: number = 1,
+        limit: number = 50,
+    ): Promise<PaginatedAuditLog> {
+        return apiClient.get<PaginatedAuditLog>("/admin/api/audit", {
+            params: { page, limit },
+        });
+    }
+
+    async getApiKeys(): Promise<ApiKey[]> {
+        return apiClient.get<ApiKey[]>("/admin/api/keys");
+    }
+
+    async createApiKey(
+        name: string,
+        scopes: ApiKeyScope[],
+        expiresInDays?: number,
+    ): Promise<ApiKeyCreated> {
+        return apiClient.post<ApiKeyCreated>("/admin/api/keys", {
+            body: JSON.stringify({
+                name,
+                scopes,
+                expires_in_days: expiresInDays ?? null,
+            }),
+        });
+    }
+
+    async updateApiKeyScopes(id: number, scopes: ApiKeyScope[]): Promise<void> {
+        return apiClient.put<void>(`/admin/api/keys/${id}`, {
+            body: JSON.stringify({ scopes }),
+        });
+    }
+
+    async revokeApiKey(id: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/keys/${id}`);
+    }
+
     async getMessages(
         page: number = 1,
         limit: number = 10,
+        spamScore: SpamScoreFilter = {},
+        labelId?: number,
     ): Promise<PaginatedMessages> {
+        const params: Record<string, number> = { page, limit };
+        if (spamScore.min !== undefined) params.min_spam_score = spamScore.min;
+        if (spamScore.max !== undefined) params.max_spam_score = spamScore.max;
+        if (labelId !== undefined) params.label = labelId;
         return apiClient.get<PaginatedMessages>("/admin/api/messages", {
-            params: { page, limit },
+            params,
         });
     }
 
-    async archiveMessage(id: number): Promise<void> {
-        return apiClient.post<void>(`/admin/api/messages/${id}/archive`, {
+    /** Full-text search over active messages, best matches first */
+    async searchMessages(
+        query: string,
+        page: number = 1,
+        limit: number = 10,
+    ): Promise<PaginatedMessages> {
+        return apiClient.get<PaginatedMessages>("/admin/api/messages/search", {
+            params: { q: query, page, limit },
+        });
+    }
+
+    /** Apply one action to several active messages in one transaction */
+    async bulkUpdateMessages(
+        ids: number[],
+        action: BulkMessageAction,
+    ): Promise<BulkMessagesResponse> {
+        return apiClient.post<BulkMessagesResponse>(
+            "/admin/api/messages/bulk",
+            {
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ ids, action }),
+            },
+        );
+    }
+
+    async getMessage(id: number): Promise<MessageDetail> {
+        return apiClient.get<MessageDetail>(`/admin/api/messages/${id}`);
+    }
+
+    async getMessageThread(email: string): Promise<MessageThread> {
+        const params = new URLSearchParams({ email });
+        return apiClient.get<MessageThread>(
+            `/admin/api/messages/threads?${params}`,
+        );
+    }
+
+    async replyToMessage(
+        id: number,
+        reply: { subject?: string; body: string },
+    ): Promise<MessageReply> {
+        return apiClient.post<MessageReply>(
+            `/admin/api/messages/${id}/reply`,
+            {
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify(reply),
+            },
+        );
+    }
+
+    async addMessageNote(id: number, body: string): Promise<MessageNote> {
+        return apiClient.post<MessageNote>(`/admin/api/messages/${id}/notes`, {
             headers: { "Content-Type": "application/json" },
-            body: JSON.stringify({ action: "archive" }),
+            body: JSON.stringify({ body }),
+        });
+    }
+
+    async deleteMessageNote(id: number, noteId: number): Promise<void> {
+        return apiClient.delete<void>(
+            `/admin/api/messages/${id}/notes/${noteId}`,
+        );
+    }
+
+    async setMessageLabels(id: number, labelIds: number[]): Promise<Label[]> {
+        return apiClient.put<Label[]>(`/admin/api/messages/${id}/labels`, {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ label_ids: labelIds }),
+        });
+    }
+
+    async getLabels(): Promise<Label[]> {
+        return apiClient.get<Label[]>("/admin/api/labels");
+    }
+
+    async createLabel(label: { name: string; color?: string }): Promise<Label> {
+        return apiClient.post<Label>("/admin/api/labels", {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(label),
+        });
+    }
+
+    async updateLabel(
+        id: number,
+        label: { name: string; color?: string },
+    ): Promise<Label> {
+        return apiClient.put<Label>(`/admin/api/labels/${id}`, {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(label),
+        });
+    }
+
+    async deleteLabel(id: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/labels/${id}`);
+    }
+
+    async getMessageAttachments(id: number): Promise<MessageAttachment[]> {
+        return apiClient.get<MessageAttachment[]>(
+            `/admin/api/messages/${id}/attachments`,
+        );
+    }
+
+    getMessageAttachmentUrl(messageId: number, attachmentId: number): string {
+        return `/admin/api/messages/${messageId}/attachments/${attachmentId}`;
+    }
+
+    async markMessageRead(id: number, read: boolean): Promise<void> {
+        return apiClient.patch<void>(`/admin/api/messages/${id}/read`, {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ read }),
         });
     }
 
-    async restoreMessage(id: number): Promise<void> {
+    async getUnreadMessageCount(): Promise<number> {
+        const response = await apiClient.get<{ unread: number }>(
+            "/admin/api/messages/unread-count",
+        );
+        return response.unread;
+    }
+
+    async archiveMessage(id: number): Promise<void> {
         return apiClient.post<void>(`/admin/api/messages/${id}/archive`, {
             headers: { "Content-Type": "application/json" },
-            body: JSON.stringify({ action: "restore" }),
+            body: JSON.stringify({ action: "archive" }),
         });
     }
 
+    async restoreArchivedMessage(archiveId: number): Promise<void> {
+        return apiClient.post<void>(
+            `/admin/api/archived/messages/${archiveId}/restore`,
+        );
+    }
+
     async getArchivedMessages(
         page: number = 1,
         limit: number = 10,
+        filter: ArchiveFilter = {},
     ): Promise<PaginatedArchivedMessages> {
+        const params: Record<string, string | number> = { page, limit };
+        if (filter.q) params.q = filter.q;
+        if (filter.createdFrom) params.created_from = filter.createdFrom;
+        if (filter.createdTo) params.created_to = filter.createdTo;
+        if (filter.archivedFrom) params.archived_from = filter.archivedFrom;
+        if (filter.archivedTo) params.archived_to = filter.archivedTo;
+        if (filter.sort) params.sort = filter.sort;
         return apiClient.get<PaginatedArchivedMessages>(
             "/admin/api/archived/messages",
             {
-                params: { page, limit },
+                params,
             },
         );
     }
@@ -186,9 +886,17 @@ class AdminApi {
         return apiClient.get<AdminUserInvite[]>("/admin/api/users/invites");
     }
 
-    async createInvite(username: string): Promise<AdminUserInvite> {
+    async createInvite(
+        username: string,
+        role?: AdminRole,
+        email?: string,
+    ): Promise<AdminUserInvite> {
         return apiClient.post<AdminUserInvite>("/admin/api/users/invites", {
-            body: JSON.stringify({ username }),
+            body: JSON.stringify({
+                username,
+                role: role ?? null,
+                email: email || null,
+            }),
         });
     }
 
@@ -217,9 +925,33 @@ class AdminApi {
         return apiClient.delete<void>("/admin/api/banner");
     }
 
-    async createUser(username: string, password: string): Promise<AdminUser> {
+    async getEmailTemplates(): Promise<EmailTemplate[]> {
+        return apiClient.get<EmailTemplate[]>("/admin/api/email-templates");
+    }
+
+    async saveEmailTemplate(
+        name: string,
+        payload: { subject: string; body: string; is_active: boolean },
+    ): Promise<EmailTemplate> {
+        return apiClient.put<EmailTemplate>(
+            `/admin/api/email-templates/${encodeURIComponent(name)}`,
+            { body: JSON.stringify(payload) },
+        );
+    }
+
+    async createUser(
+        username: string,
+        password: string,
+        role?: AdminRole,
+        email?: string,
+    ): Promise<AdminUser> {
         return apiClient.post<AdminUser>("/admin/api/users", {
-            body: JSON.stringify({ username, password }),
+            body: JSON.stringify({
+                username,
+                password,
+                role: role ?? null,
+                email: email || null,
+            }),
         });
     }
 
@@ -227,9 +959,16 @@ class AdminApi {
         id: number,
         username: string,
         password?: string,
+        role?: AdminRole,
+        email?: string,
     ): Promise<void> {
         return apiClient.put<void>(`/admin/api/users/${id}`, {
-            body: JSON.stringify({ username, password: password || null }),
+            body: JSON.stringify({
+                username,
+                password: password || null,
+                role: role ?? null,
+                email: email ?? null,
+            }),
         });
     }
 
@@ -237,37 +976,313 @@ class AdminApi {
         return apiClient.delete<void>(`/admin/api/users/${id}`);
     }
 
-    async updateOffer(id: number, formData: FormData): Promise<void> {
-        return apiClient.putMultipart<void>(
+    async changePassword(
+        currentPassword: string,
+        newPassword: string,
+    ): Promise<void> {
+        return apiClient.post<void>("/admin/api/password", {
+            body: JSON.stringify({
+                current_password: currentPassword,
+                new_password: newPassword,
+            }),
+        });
+    }
+
+    /** Returns the offer as saved */
+    async updateOffer(id: number, formData: FormData): Promise<OfferSummary> {
+        return apiClient.putMultipart<OfferSummary>(
             `/admin/api/offers/${id}`,
             formData,
         );
     }
 
+    async getAllOffers(filter: OfferFilter = {}): Promise<PaginatedOffers> {
+        const params: Record<string, string | number> = {};
+        if (filter.page !== undefined) params.page = filter.page;
+        if (filter.limit !== undefined) params.limit = filter.limit;
+        if (filter.status && filter.status !== "all")
+            params.status = filter.status;
+        if (filter.q) params.q = filter.q;
+        return apiClient.get<PaginatedOffers>("/admin/api/offers", {
+            params,
+        });
+    }
+
+    async reorderOffers(offerIds: number[]): Promise<void> {
+        return apiClient.put<void>("/admin/api/offers/reorder", {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ offer_ids: offerIds }),
+        });
+    }
+
+    async importOffers(file: File): Promise<OfferImportReport> {
+        const formData = new FormData();
+        formData.append("file", file);
+        return apiClient.postMultipart<OfferImportReport>(
+            "/admin/api/offers/import",
+            formData,
+        );
+    }
+
+    async getOfferClickStats(
+        from?: string,
+        to?: string,
+    ): Promise<OfferClickStats[]> {
+        const params: Record<string, string> = {};
+        if (from) params.from = from;
+        if (to) params.to = to;
+        return apiClient.get<OfferClickStats[]>("/admin/api/offers/clicks", {
+            params,
+        });
+    }
+
+    /** Moves the offer to the trash */
     async deleteOffer(id: number): Promise<void> {
         return apiClient.delete<void>(`/admin/api/offers/${id}`);
     }
 
+    async getOfferImages(id: number): Promise<OfferImage[]> {
+        return apiClient.get<OfferImage[]>(`/admin/api/offers/${id}/images`);
+    }
+
+    async uploadOfferImage(id: number, image: File): Promise<OfferImage> {
+        const formData = new FormData();
+        formData.append("image", image);
+        return apiClient.postMultipart<OfferImage>(
+            `/admin/api/offers/${id}/images`,
+            formData,
+        );
+    }
+
+    async reorderOfferImages(
+        id: number,
+        imageIds: number[],
+    ): Promise<OfferImage[]> {
+        return apiClient.put<OfferImage[]>(
+            `/admin/api/offers/${id}/images/order`,
+            {
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ image_ids: imageIds }),
+            },
+        );
+    }
+
+    /** Moves the image to the front; returns the images in their new order */
+    async setPrimaryOfferImage(
+        id: number,
+        imageId: number,
+    ): Promise<OfferImage[]> {
+        return apiClient.post<OfferImage[]>(
+            `/admin/api/offers/${id}/images/${imageId}/primary`,
+        );
+    }
+
+    async deleteOfferImage(id: number, imageId: number): Promise<void> {
+        return apiClient.delete<void>(
+            `/admin/api/offers/${id}/images/${imageId}`,
+        );
+    }
+
     async createBlogPost(formData: FormData): Promise<BlogPost> {
         return apiClient.postMultipart<BlogPost>("/admin/api/blog", formData);
     }
 
-    async updateBlogPost(id: number, formData: FormData): Promise<void> {
-        return apiClient.putMultipart<void>(`/admin/api/blog/${id}`, formData);
+    /** Returns the post as saved */
+    async updateBlogPost(id: number, formData: FormData): Promise<BlogPost> {
+        return apiClient.putMultipart<BlogPost>(
+            `/admin/api/blog/${id}`,
+            formData,
+        );
     }
 
+    /** Moves the post to the trash */
     async deleteBlogPost(id: number): Promise<void> {
         return apiClient.delete<void>(`/admin/api/blog/${id}`);
     }
 
-    async getAllBlogPosts(): Promise<BlogPost[]> {
-        return apiClient.get<BlogPost[]>("/admin/api/blog");
+    async getTrash(
+        kind?: TrashItemKind,
+        page?: number,
+        limit?: number,
+    ): Promise<PaginatedTrash> {
+        const params: Record<string, string | number> = {};
+        if (kind) params.kind = kind;
+        if (page !== undefined) params.page = page;
+        if (limit !== undefined) params.limit = limit;
+        return apiClient.get<PaginatedTrash>("/admin/api/trash", { params });
+    }
+
+    async restoreTrashedOffer(id: number): Promise<void> {
+        return apiClient.post<void>(`/admin/api/trash/offers/${id}/restore`);
+    }
+
+    async permanentlyDeleteOffer(id: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/trash/offers/${id}`);
+    }
+
+    async restoreTrashedBlogPost(id: number): Promise<void> {
+        return apiClient.post<void>(`/admin/api/trash/blog/${id}/restore`);
+    }
+
+    async permanentlyDeleteBlogPost(id: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/trash/blog/${id}`);
+    }
+
+    async getAllBlogPosts(
+        filter: AdminBlogPostFilter = {},
+    ): Promise<PaginatedAdminBlogPosts> {
+        const params: Record<string, string | number> = {};
+        if (filter.page !== undefined) params.page = filter.page;
+        if (filter.limit !== undefined) params.limit = filter.limit;
+        if (filter.status && filter.status !== "all")
+            params.status = filter.status;
+        if (filter.q) params.q = filter.q;
+        return apiClient.get<PaginatedAdminBlogPosts>("/admin/api/blog", {
+            params,
+        });
+    }
+
+    async importBlogPosts(file: File): Promise<BlogImportedPost[]> {
+        const formData = new FormData();
+        formData.append("file", file);
+        return apiClient.postMultipart<BlogImportedPost[]>(
+            "/admin/api/blog/import",
+            formData,
+        );
+    }
+
+    async createBlogPreviewLink(id: number): Promise<BlogPreviewLink> {
+        return apiClient.post<BlogPreviewLink>(
+            `/admin/api/blog/${id}/preview-link`,
+        );
+    }
+
+    async duplicateBlogPost(id: number): Promise<BlogPost> {
+        return apiClient.post<BlogPost>(`/admin/api/blog/${id}/duplicate`);
+    }
+
+    async featureBlogPost(
+        id: number,
+        featured: boolean,
+        featuredOrder = 0,
+    ): Promise<void> {
+        return apiClient.post<void>(`/admin/api/blog/${id}/feature`, {
+            body: JSON.stringify({ featured, featured_order: featuredOrder }),
+        });
+    }
+
+    async archiveBlogPost(id: number): Promise<void> {
+        return apiClient.post<void>(`/admin/api/blog/${id}/archive`);
+    }
+
+    async restoreBlogPost(id: number): Promise<void> {
+        return apiClient.post<void>(`/admin/api/blog/${id}/restore`);
+    }
+
+    async getBlogPost(id: number): Promise<BlogPost> {
+        return apiClient.get<BlogPost>(`/admin/api/blog/${id}`);
+    }
+
+    async previewBlogPost(content: string): Promise<string> {
+        const preview = await apiClient.post<{ html: string }>(
+            "/admin/api/blog/preview",
+            {
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ content }),
+            },
+        );
+        return preview.html;
+    }
+
+    async getBlogPostImages(id: number): Promise<BlogPostImage[]> {
+        return apiClient.get<BlogPostImage[]>(`/admin/api/blog/${id}/images`);
+    }
+
+    async uploadBlogPostImage(id: number, image: File): Promise<BlogPostImage> {
+        const formData = new FormData();
+        formData.append("image", image);
+        return apiClient.postMultipart<BlogPostImage>(
+            `/admin/api/blog/${id}/images`,
+            formData,
+        );
+    }
+
+    async reorderBlogPostImages(
+        id: number,
+        imageIds: number[],
+    ): Promise<BlogPostImage[]> {
+        return apiClient.put<BlogPostImage[]>(
+            `/admin/api/blog/${id}/images/order`,
+            {
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ image_ids: imageIds }),
+            },
+        );
+    }
+
+    async deleteBlogPostImage(id: number, imageId: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/blog/${id}/images/${imageId}`);
+    }
+
+    async getBlogPostRevisions(id: number): Promise<BlogPostRevisionSummary[]> {
+        return apiClient.get<BlogPostRevisionSummary[]>(
+            `/admin/api/blog/${id}/revisions`,
+        );
+    }
+
+    async diffBlogPostRevisions(
+        id: number,
+        from: number,
+        to?: number,
+    ): Promise<BlogPostRevisionDiff> {
+        return apiClient.get<BlogPostRevisionDiff>(
+            `/admin/api/blog/${id}/revisions/diff`,
+            { params: to === undefined ? { from } : { from, to } },
+        );
+    }
+
+    async restoreBlogPostRevision(
+        id: number,
+        revisionId: number,
+    ): Promise<BlogPost> {
+        return apiClient.post<BlogPost>(
+            `/admin/api/blog/${id}/revisions/${revisionId}/restore`,
+        );
+    }
+
+    async getTags(): Promise<TagUsage[]> {
+        return apiClient.get<TagUsage[]>("/admin/api/tags");
+    }
+
+    async createTag(tag: { name: string; slug?: string }): Promise<Tag> {
+        return apiClient.post<Tag>("/admin/api/tags", {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(tag),
+        });
+    }
+
+    async updateTag(
+        id: number,
+        tag: { name: string; slug?: string },
+    ): Promise<Tag> {
+        return apiClient.put<Tag>(`/admin/api/tags/${id}`, {
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(tag),
+        });
+    }
+
+    async deleteTag(id: number): Promise<void> {
+        return apiClient.delete<void>(`/admin/api/tags/${id}`);
     }
 }
 
 class PublicApi {
-    async getOffers(): Promise<OfferSummary[]> {
-        return apiClient.get<OfferSummary[]>("/api/offers");
+    async getOffers(filter: OfferFilter = {}): Promise<PaginatedOffers> {
+        const params: Record<string, string | number> = {};
+        if (filter.page !== undefined) params.page = filter.page;
+        if (filter.limit !== undefined) params.limit = filter.limit;
+        if (filter.q) params.q = filter.q;
+        return apiClient.get<PaginatedOffers>("/api/offers", { params });
     }
 
     async getOfferBySlug(slug: string): Promise<OfferSummary> {
@@ -280,20 +1295,91 @@ class PublicApi {
         return `/api/offers/${id}/image`;
     }
 
-    async getBlogPosts(): Promise<BlogPost[]> {
-        return apiClient.get<BlogPost[]>("/api/blog");
+    /** Offer link that counts the click before redirecting to it */
+    getOfferLinkUrl(id: number): string {
+        return `/api/offers/${id}/go`;
+    }
+
+    async getBlogPosts(
+        filter: BlogPostFilter = {},
+    ): Promise<PaginatedBlogPosts> {
+        const params: Record<string, string | number> = {};
+        if (filter.page !== undefined) params.page = filter.page;
+        if (filter.limit !== undefined) params.limit = filter.limit;
+        if (filter.tag) params.tag = filter.tag;
+        if (filter.author !== undefined) params.author = filter.author;
+        if (filter.q) params.q = filter.q;
+        return apiClient.get<PaginatedBlogPosts>("/api/blog", { params });
+    }
+
+    async getFeaturedBlogPosts(limit?: number): Promise<BlogPostSummary[]> {
+        const params: Record<string, number> = {};
+        if (limit !== undefined) params.limit = limit;
+        return apiClient.get<BlogPostSummary[]>("/api/blog/featured", {
+            params,
+        });
+    }
+
+    async getBlogAuthors(): Promise<BlogAuthorSummary[]> {
+        return apiClient.get<BlogAuthorSummary[]>("/api/blog/authors");
     }
 
     async getBanner(): Promise<Banner | null> {
         return apiClient.get<Banner | null>("/api/banner");
     }
 
+    async getBlogPostMeta(slug: string): Promise<BlogPostMeta> {
+        return apiClient.get<BlogPostMeta>(
+            `/api/blog/${encodeURIComponent(slug)}/meta`,
+        );
+    }
+
+    async getPopularBlogPosts(limit?: number): Promise<PopularBlogPost[]> {
+        const params: Record<string, number> = {};
+        if (limit !== undefined) params.limit = limit;
+        return apiClient.get<PopularBlogPost[]>("/api/blog/popular", {
+            params,
+        });
+    }
+
     async getBlogPostBySlug(slug: string): Promise<BlogPost> {
         return apiClient.get<BlogPost>(`/api/blog/${encodeURIComponent(slug)}`);
     }
 
-    getBlogPostImageUrl(id: number): string {
-        return `/api/blog/${id}/image`;
+    async getBlogPostPreview(token: string): Promise<BlogPost> {
+        return apiClient.get<BlogPost>(
+            `/api/blog/preview/${encodeURIComponent(token)}`,
+        );
+    }
+
+    async search(q: string, limit?: number): Promise<SearchResult[]> {
+        const params: Record<string, string | number> = { q };
+        if (limit !== undefined) params.limit = limit;
+        return apiClient.get<SearchResult[]>("/api/search", { params });
+    }
+
+    async getCaptchaSettings(): Promise<CaptchaSettings> {
+        return apiClient.get<CaptchaSettings>("/api/contact/captcha");
+    }
+
+    async getRequiredFields(): Promise<ContactRequiredFields> {
+        return apiClient.get<ContactRequiredFields>("/api/contact/fields");
+    }
+
+    async submitContact(
+        submission: ContactSubmission,
+    ): Promise<ContactSubmitResponse> {
+        return apiClient.post<ContactSubmitResponse>("/api/contact", {
+            body: JSON.stringify(submission),
+        });
+    }
+
+    /** Submit the contact form as multipart, e.g. with an `attachment` */
+    async submitContactForm(formData: FormData): Promise<ContactSubmitResponse> {
+        return apiClient.postMultipart<ContactSubmitResponse>(
+            "/api/contact",
+            formData,
+        );
     }
 }
 
@@ -304,4 +1390,6 @@ export const api = {
     banner: publicApi,
     offers: publicApi,
     blog: publicApi,
+    contact: publicApi,
+    search: publicApi,
 };
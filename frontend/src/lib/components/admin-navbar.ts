@@ -4,6 +4,23 @@ import { api } from "../api";
  * Admin Navbar utilities with mobile menu and auth features
  */
 
+/**
+ * Show the number of unread messages next to the messages link. Hidden when
+ * there are none or the admin may not see messages.
+ */
+export async function refreshUnreadBadge(): Promise<void> {
+    const badge = document.getElementById("admin-unread-badge");
+    if (!badge) return;
+
+    try {
+        const unread = await api.admin.getUnreadMessageCount();
+        badge.textContent = String(unread);
+        badge.classList.toggle("hidden", unread === 0);
+    } catch (e) {
+        badge.classList.add("hidden");
+    }
+}
+
 /**
  * Initialize admin navbar with mobile menu and logout functionality
  */
@@ -45,6 +62,8 @@ export function initAdminNavbar(): void {
         link.addEventListener("click", () => setMenu(false));
     });
 
+    void refreshUnreadBadge();
+
     // Admin auth check
     (async function () {
         const logoutBtnMobile = document.getElementById(
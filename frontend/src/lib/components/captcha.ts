@@ -0,0 +1,68 @@
+/**
+ * Optional CAPTCHA widget for the contact form. The provider and site key
+ * come from the backend, so the widget only loads when it is configured.
+ */
+
+import { api, type CaptchaProvider } from "../api";
+
+const WIDGETS: Record<
+    CaptchaProvider,
+    { script: string; className: string; global: string }
+> = {
+    hcaptcha: {
+        script: "https://js.hcaptcha.com/1/api.js",
+        className: "h-captcha",
+        global: "hcaptcha",
+    },
+    turnstile: {
+        script: "https://challenges.cloudflare.com/turnstile/v0/api.js",
+        className: "cf-turnstile",
+        global: "turnstile",
+    },
+    recaptcha: {
+        script: "https://www.google.com/recaptcha/api.js",
+        className: "g-recaptcha",
+        global: "grecaptcha",
+    },
+};
+
+let activeProvider: CaptchaProvider | null = null;
+
+/**
+ * Render the widget into `container` when a CAPTCHA is configured. The
+ * widget adds its response field to the surrounding form by itself.
+ */
+export async function initCaptcha(container: HTMLElement): Promise<void> {
+    try {
+        const settings = await api.contact.getCaptchaSettings();
+        if (!settings.provider || !settings.site_key) return;
+
+        const widget = WIDGETS[settings.provider];
+        const element = document.createElement("div");
+        element.className = widget.className;
+        element.dataset.sitekey = settings.site_key;
+        container.appendChild(element);
+        container.classList.remove("hidden");
+
+        const script = document.createElement("script");
+        script.src = widget.script;
+        script.async = true;
+        script.defer = true;
+        document.head.appendChild(script);
+        activeProvider = settings.provider;
+    } catch (error) {
+        console.error("Failed to load CAPTCHA settings:", error);
+    }
+}
+
+/**
+ * Reset the widget after a submission, since every token is single-use
+ */
+export function resetCaptcha(): void {
+    if (!activeProvider) return;
+
+    const global = (window as unknown as Record<string, unknown>)[
+        WIDGETS[activeProvider].global
+    ] as { reset?: () => void } | undefined;
+    global?.reset?.();
+}
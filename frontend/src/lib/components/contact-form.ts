@@ -2,6 +2,14 @@
  * Contact form handler with validation and submission
  */
 
+import {
+    api,
+    type ContactSubmission,
+    type ContactSubmitResponse,
+} from "../api";
+import { ApiError } from "../api-client";
+import { initCaptcha, resetCaptcha } from "./captcha";
+
 export interface ContactFormElements {
     form: HTMLFormElement;
     feedback: HTMLElement | null;
@@ -11,6 +19,120 @@ export interface ContactFormElements {
     btnText: HTMLElement | null;
 }
 
+const SUCCESS_MESSAGE =
+    "Ďakujeme, Vaša správa bola úspešne odoslaná. Čoskoro Vás budeme kontaktovať.";
+const RATE_LIMITED_MESSAGE =
+    "Odoslali ste príliš veľa správ. Skúste to prosím o niekoľko minút.";
+const FAILURE_MESSAGE =
+    "Nepodarilo sa odoslať správu. Skúste to prosím neskôr alebo nás kontaktujte telefonicky.";
+
+/**
+ * Messages per invalid field, for the JSON endpoint's field errors and the
+ * `error` query parameter set by the form-post redirect
+ */
+const ERROR_MESSAGES: Record<string, string> = {
+    name: "Zadajte meno (najviac 100 znakov, bez špeciálnych znakov).",
+    email: "Zadajte platnú emailovú adresu.",
+    phone: "Zadajte telefónne číslo (najviac 32 znakov, bez špeciálnych znakov).",
+    subject: "Zadajte predmet (najviac 200 znakov, bez špeciálnych znakov).",
+    message:
+        "Správa je prázdna, príliš dlhá (najviac 5000 znakov) alebo obsahuje neplatné znaky.",
+    preferred_contact:
+        "Zadajte telefónne číslo, ak preferujete odpoveď telefonicky.",
+    captcha: "Potvrďte prosím, že nie ste robot.",
+    captcha_token: "Potvrďte prosím, že nie ste robot.",
+    attachment:
+        "Prílohu sa nepodarilo prijať. Povolené sú súbory PDF, PNG, JPG, WebP a TXT do 5 MB.",
+    rate_limited: RATE_LIMITED_MESSAGE,
+    server: FAILURE_MESSAGE,
+};
+
+function showFeedback(
+    feedback: HTMLElement,
+    message: string,
+    success: boolean,
+): void {
+    feedback.textContent = message;
+    feedback.classList.remove("hidden");
+    if (success) {
+        feedback.classList.add(
+            "bg-green-50",
+            "text-green-700",
+            "border-green-200",
+        );
+    } else {
+        feedback.classList.add("bg-red-50", "text-red-700", "border-red-200");
+    }
+}
+
+/**
+ * Record when the form was rendered; the backend treats submissions made
+ * within seconds of it as likely spam
+ */
+function stampFormStart(form: HTMLFormElement): void {
+    const input = form.elements.namedItem(
+        "form_started_at",
+    ) as HTMLInputElement | null;
+    if (input) input.value = String(Date.now());
+}
+
+/**
+ * Mark the optional fields the backend is configured to require, so the
+ * form asks for them before submitting
+ */
+async function applyRequiredFields(form: HTMLFormElement): Promise<void> {
+    try {
+        const required = await api.contact.getRequiredFields();
+        for (const field of ["phone", "subject"] as const) {
+            if (!required[field]) continue;
+            const input = form.elements.namedItem(
+                field,
+            ) as HTMLInputElement | null;
+            if (!input) continue;
+            input.required = true;
+            form.querySelector(
+                `label[for="${field}"] [data-optional-hint]`,
+            )?.remove();
+        }
+    } catch (error) {
+        console.error("Failed to load required contact fields:", error);
+    }
+}
+
+/**
+ * Outcome of a submission from the query string of the contact page the
+ * backend redirected to: `null` on success, otherwise the error message
+ */
+function submissionError(params: URLSearchParams): string | null {
+    const error = params.get("error");
+    if (!error) return null;
+    return ERROR_MESSAGES[error] ?? FAILURE_MESSAGE;
+}
+
+/**
+ * Mark the fields rejected by the backend and return a summary message
+ */
+function showFieldErrors(
+    form: HTMLFormElement,
+    response: ContactSubmitResponse,
+): string {
+    let firstInput: HTMLInputElement | null = null;
+    for (const error of response.errors) {
+        const input = form.elements.namedItem(
+            error.field,
+        ) as HTMLInputElement | null;
+        if (!input) continue;
+        input.setCustomValidity(ERROR_MESSAGES[error.field] ?? error.message);
+        firstInput ??= input;
+    }
+    firstInput?.reportValidity();
+
+    const first = response.errors[0];
+    return first
+        ? (ERROR_MESSAGES[first.field] ?? FAILURE_MESSAGE)
+        : FAILURE_MESSAGE;
+}
+
 /**
  * Initialize contact form with validation and submission handling
  */
@@ -26,6 +148,19 @@ export function initContactForm(): void {
 
     if (!form) return;
 
+    stampFormStart(form);
+    void applyRequiredFields(form);
+
+    const captchaContainer = document.getElementById("captcha-widget");
+    if (captchaContainer) void initCaptcha(captchaContainer);
+
+    // Outcome of a submission made without JavaScript
+    const params = new URLSearchParams(window.location.search);
+    if (feedback && (params.has("sent") || params.has("error"))) {
+        const error = submissionError(params);
+        showFeedback(feedback, error ?? SUCCESS_MESSAGE, error === null);
+    }
+
     // Clear custom validity on input
     form.addEventListener("input", (e) => {
         const target = e.target as HTMLInputElement | HTMLTextAreaElement;
@@ -50,6 +185,23 @@ export function initContactForm(): void {
             return;
         }
 
+        // Phone number is required when phone is the preferred reply channel
+        const preferredInput = form.elements.namedItem(
+            "preferred_contact",
+        ) as HTMLSelectElement | null;
+        const phoneInput = form.elements.namedItem("phone") as HTMLInputElement;
+        if (
+            preferredInput?.value === "phone" &&
+            phoneInput &&
+            !phoneInput.value.trim()
+        ) {
+            phoneInput.setCustomValidity(
+                "Zadajte telefónne číslo, ak preferujete odpoveď telefonicky.",
+            );
+            phoneInput.reportValidity();
+            return;
+        }
+
         // Custom message length check
         const messageInput = form.elements.namedItem(
             "message",
@@ -72,38 +224,36 @@ export function initContactForm(): void {
 
         try {
             const formData = new FormData(form);
-
-            const response = await fetch(form.action, {
-                method: "POST",
-                body: formData,
-            });
-
-            if (response.ok) {
-                // Success
-                feedback.textContent =
-                    "Ďakujeme, Vaša správa bola úspešne odoslaná. Čoskoro Vás budeme kontaktovať.";
-                feedback.classList.remove("hidden");
-                feedback.classList.add(
-                    "bg-green-50",
-                    "text-green-700",
-                    "border-green-200",
-                );
-                form.reset();
+            const attachment = formData.get("attachment");
+            if (attachment instanceof File && attachment.size > 0) {
+                await api.contact.submitContactForm(formData);
             } else {
-                throw new Error("Server returned error");
+                formData.delete("attachment");
+                const { form_started_at, ...fields } =
+                    Object.fromEntries(formData);
+                const submission = {
+                    ...fields,
+                    form_started_at: Number(form_started_at) || undefined,
+                } as unknown as ContactSubmission;
+
+                await api.contact.submitContact(submission);
             }
+            showFeedback(feedback, SUCCESS_MESSAGE, true);
+            form.reset();
+            stampFormStart(form);
         } catch (error) {
-            // Error
-            console.error(error);
-            feedback.textContent =
-                "Nepodarilo sa odoslať správu. Skúste to prosím neskôr alebo nás kontaktujte telefonicky.";
-            feedback.classList.remove("hidden");
-            feedback.classList.add(
-                "bg-red-50",
-                "text-red-700",
-                "border-red-200",
-            );
+            if (error instanceof ApiError && error.status === 422) {
+                const response = error.data as ContactSubmitResponse;
+                showFeedback(feedback, showFieldErrors(form, response), false);
+            } else if (error instanceof ApiError && error.status === 429) {
+                showFeedback(feedback, RATE_LIMITED_MESSAGE, false);
+            } else {
+                console.error(error);
+                showFeedback(feedback, FAILURE_MESSAGE, false);
+            }
         } finally {
+            resetCaptcha();
+
             // Re-enable button
             submitBtn.disabled = false;
             if (btnIcon) btnIcon.classList.remove("hidden");
@@ -30,6 +30,7 @@ export function initNavbar(
         if (pathname.startsWith("/cennik")) return "cennik";
         if (pathname.startsWith("/blog")) return "blog";
         if (pathname.startsWith("/contact")) return "contact";
+        if (pathname.startsWith("/search")) return "search";
         if (pathname.startsWith("/admin")) return "admin";
         return "";
     }
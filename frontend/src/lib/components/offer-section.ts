@@ -13,6 +13,9 @@ L.Icon.Default.mergeOptions({
     shadowUrl: markerShadow,
 });
 
+/** Largest page of the offers API; the map shows every offer on it */
+const OFFERS_LIMIT = 50;
+
 let map: L.Map | null = null;
 let markers: L.Marker[] = [];
 
@@ -83,7 +86,7 @@ function initMap(offers: OfferSummary[]) {
             <h3 style="margin: 0 0 8px 0; font-size: 14px; font-weight: bold;">${offer.title || "Objekt"}</h3>
             <div style="margin: 0; font-size: 12px; color: #666;">${offer.excerpt ? markdownToHtml(offer.excerpt) : offer.content ? markdownToHtml(offer.content) : ""}</div>
             <a href="${detailUrl}" style="display: inline-block; margin-top: 8px; color: #0f62fe; font-size: 12px; font-weight: 600;">Detail ponuky →</a>
-            ${offer.link ? `<a href="${offer.link}" target="_blank" rel="noopener noreferrer" style="display: inline-block; margin-top: 8px; margin-left: 12px; color: #4b5563; font-size: 12px; font-weight: 600;">Externý odkaz</a>` : ""}
+            ${offer.link ? `<a href="${api.offers.getOfferLinkUrl(offer.id)}" target="_blank" rel="noopener" style="display: inline-block; margin-top: 8px; margin-left: 12px; color: #4b5563; font-size: 12px; font-weight: 600;">Externý odkaz</a>` : ""}
           </div>
         `;
 
@@ -123,7 +126,7 @@ export function initOfferSection(): void {
 
         const img = document.createElement("img");
         img.className = "w-full h-48 object-cover";
-        img.src = `/api/offers/${encodeURIComponent(offer.id)}/image`;
+        img.src = `/api/offers/${encodeURIComponent(offer.id)}/image?size=medium`;
         img.alt = offer.title || "Ponuka";
         img.loading = "lazy";
 
@@ -222,9 +225,9 @@ export function initOfferSection(): void {
 
         if (offer.link && offer.link.trim() !== "") {
             const externalLink = document.createElement("a");
-            externalLink.href = offer.link;
+            externalLink.href = api.offers.getOfferLinkUrl(offer.id);
             externalLink.target = "_blank";
-            externalLink.rel = "noopener noreferrer";
+            externalLink.rel = "noopener";
             externalLink.className =
                 "inline-flex items-center gap-2 text-sm font-bold text-gray-600 hover:text-gray-900 transition-colors no-underline";
             externalLink.textContent = "Externý odkaz";
@@ -248,7 +251,9 @@ export function initOfferSection(): void {
 
     async function loadOffers() {
         try {
-            const offers = await api.offers.getOffers();
+            const { data: offers } = await api.offers.getOffers({
+                limit: OFFERS_LIMIT,
+            });
 
             if (container) container.innerHTML = "";
 
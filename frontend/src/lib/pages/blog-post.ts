@@ -1,4 +1,4 @@
-import { api } from "../api";
+import { api, type BlogPostMeta } from "../api";
 import { markdownToHtml } from "../../utils/markdown";
 
 function getBlogSlug(): string | null {
@@ -17,17 +17,72 @@ function getBlogSlug(): string | null {
     return null;
 }
 
+/** Token of a preview link that shows an unpublished post. */
+function getPreviewToken(): string | null {
+    return new URLSearchParams(window.location.search).get("preview");
+}
+
 function escapeHtml(text: string): string {
     const div = document.createElement("div");
     div.textContent = text;
     return div.innerHTML;
 }
 
+/** Create or update a `<meta>` tag identified by `name` or `property`. */
+function setMetaTag(
+    attribute: "name" | "property",
+    key: string,
+    value: string | null,
+): void {
+    let tag = document.head.querySelector<HTMLMetaElement>(
+        `meta[${attribute}="${key}"]`,
+    );
+    if (!value) {
+        tag?.remove();
+        return;
+    }
+    if (!tag) {
+        tag = document.createElement("meta");
+        tag.setAttribute(attribute, key);
+        document.head.appendChild(tag);
+    }
+    tag.content = value;
+}
+
+function applyPostMeta(meta: BlogPostMeta): void {
+    document.title = `${meta.title} | Blog | ${meta.site_name}`;
+    setMetaTag("name", "description", meta.description);
+    setMetaTag("property", "og:title", meta.title);
+    setMetaTag("property", "og:description", meta.description);
+    setMetaTag("property", "og:type", meta.og_type);
+    setMetaTag("property", "og:url", meta.canonical_url);
+    setMetaTag("property", "og:image", meta.og_image);
+    setMetaTag("property", "og:site_name", meta.site_name);
+    setMetaTag("property", "article:published_time", meta.published_time);
+    setMetaTag("property", "article:modified_time", meta.modified_time);
+    setMetaTag(
+        "name",
+        "twitter:card",
+        meta.og_image ? "summary_large_image" : "summary",
+    );
+
+    let canonical = document.head.querySelector<HTMLLinkElement>(
+        'link[rel="canonical"]',
+    );
+    if (!canonical) {
+        canonical = document.createElement("link");
+        canonical.rel = "canonical";
+        document.head.appendChild(canonical);
+    }
+    canonical.href = meta.canonical_url;
+}
+
 export async function initBlogPostPage(): Promise<void> {
     const loading = document.getElementById("loading-post");
     const error = document.getElementById("error-post");
     const content = document.getElementById("post-content");
     const slug = getBlogSlug();
+    const previewToken = getPreviewToken();
 
     if (!slug || !content) {
         error?.classList.remove("hidden");
@@ -36,9 +91,11 @@ export async function initBlogPostPage(): Promise<void> {
     }
 
     try {
-        const [post, allPosts] = await Promise.all([
-            api.blog.getBlogPostBySlug(slug),
-            api.blog.getBlogPosts(),
+        const [post, recentPosts] = await Promise.all([
+            previewToken
+                ? api.blog.getBlogPostPreview(previewToken)
+                : api.blog.getBlogPostBySlug(slug),
+            api.blog.getBlogPosts({ limit: 4 }),
         ]);
 
         const createdDate = new Date(post.created_at);
@@ -55,13 +112,28 @@ export async function initBlogPostPage(): Promise<void> {
         });
 
         document.title = `${post.title} | Blog | MK-SBD`;
+        if (previewToken) {
+            // Previews must not end up in search engines
+            setMetaTag("name", "robots", "noindex, nofollow");
+        } else {
+            // The page works without metadata, so a failure only gets logged
+            api.blog
+                .getBlogPostMeta(slug)
+                .then(applyPostMeta)
+                .catch((e) => console.error("Error loading post metadata:", e));
+        }
 
-        const relatedPosts = allPosts
+        const relatedPosts = recentPosts.data
             .filter((entry) => entry.slug !== post.slug)
             .slice(0, 3);
 
         content.innerHTML = `
       <article class="max-w-4xl mx-auto">
+        ${
+            previewToken
+                ? `<div class="mb-6 p-4 bg-yellow-50 border border-yellow-200 text-yellow-800 rounded-lg text-sm">Náhľad článku${post.published ? "" : ", ktorý ešte nie je zverejnený"}.</div>`
+                : ""
+        }
         <nav class="mb-6 text-sm text-gray-500">
           <a href="/blog" class="hover:text-primary transition-colors no-underline">Blog</a>
           <span class="mx-2">/</span>
@@ -73,16 +145,22 @@ export async function initBlogPostPage(): Promise<void> {
             ${escapeHtml(post.title)}
           </h1>
           <div class="flex flex-wrap items-center gap-4 text-sm text-gray-500">
+            ${
+                post.author
+                    ? `<span class="inline-flex items-center gap-2">${post.author.avatar_url ? `<img src="${escapeHtml(post.author.avatar_url)}" alt="" class="w-8 h-8 rounded-full" loading="lazy">` : ""}${escapeHtml(post.author.name)}</span>`
+                    : ""
+            }
             <time datetime="${post.created_at}">${formattedCreated}</time>
             ${post.updated_at !== post.created_at ? `<span>Aktualizované: ${formattedUpdated}</span>` : ""}
+            ${post.reading_time_minutes > 0 ? `<span>${post.reading_time_minutes} min čítania</span>` : ""}
           </div>
         </header>
 
         ${
-            post.image_mime
+            post.image_url
                 ? `
           <div class="mb-8">
-            <img src="${api.blog.getBlogPostImageUrl(post.id)}" alt="${escapeHtml(post.title)}" class="w-full h-auto rounded-2xl shadow-lg">
+            <img src="${post.image_url}" alt="${escapeHtml(post.title)}" class="w-full h-auto rounded-2xl shadow-lg">
           </div>
         `
                 : ""
@@ -101,7 +179,7 @@ export async function initBlogPostPage(): Promise<void> {
         }
 
         <div class="prose prose-lg max-w-none">
-          ${markdownToHtml(post.content)}
+          ${post.content_html}
         </div>
 
         <div class="mt-12 pt-8 border-t border-gray-200 flex flex-wrap gap-4 items-center justify-between">
@@ -125,7 +203,7 @@ export async function initBlogPostPage(): Promise<void> {
                 .map(
                     (entry) => `
               <article class="bg-white border border-gray-200 rounded-2xl overflow-hidden shadow-sm transition-all duration-300 hover:shadow-xl hover:border-primary/20 hover:-translate-y-1">
-                ${entry.image_mime ? `<img src="${api.blog.getBlogPostImageUrl(entry.id)}" alt="${escapeHtml(entry.title)}" class="w-full h-44 object-cover">` : ""}
+                ${entry.image_url ? `<img src="${entry.image_url}?size=medium" alt="${escapeHtml(entry.title)}" class="w-full h-44 object-cover">` : ""}
                 <div class="p-6">
                   <h3 class="m-0 mb-3 text-lg font-bold text-gray-900 leading-tight">
                     <a href="/blog/${encodeURIComponent(entry.slug)}" class="no-underline text-inherit hover:text-primary transition-colors">${escapeHtml(entry.title)}</a>
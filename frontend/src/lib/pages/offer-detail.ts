@@ -94,7 +94,7 @@ export async function initOfferDetailPage(): Promise<void> {
             <h2 class="m-0 mb-4 text-xl font-bold text-gray-900">Rýchle akcie</h2>
             <div class="flex flex-col gap-3">
               <a href="/contact" class="inline-flex items-center justify-center px-5 py-3 rounded-xl bg-primary text-white no-underline font-bold hover:bg-[#0353e9] transition-colors">Kontaktovať nás</a>
-              ${offer.link ? `<a href="${offer.link}" target="_blank" rel="noopener noreferrer" class="inline-flex items-center justify-center px-5 py-3 rounded-xl border border-primary/20 text-primary no-underline font-bold hover:bg-primary/5 transition-colors">Externý odkaz</a>` : ""}
+              ${offer.link ? `<a href="${api.offers.getOfferLinkUrl(offer.id)}" target="_blank" rel="noopener" class="inline-flex items-center justify-center px-5 py-3 rounded-xl border border-primary/20 text-primary no-underline font-bold hover:bg-primary/5 transition-colors">Externý odkaz</a>` : ""}
               <a href="/offer" class="inline-flex items-center justify-center px-5 py-3 rounded-xl border border-gray-200 text-gray-700 no-underline font-bold hover:border-primary/20 hover:text-primary transition-colors">Späť na ponuku</a>
             </div>
           </section>